@@ -13,7 +13,13 @@ def main():
     
     # Get the content being written/edited
     tool_input = event.get("tool_input", {})
-    content = tool_input.get("newString") or tool_input.get("content") or ""
+    # Accept either spelling of the key
+    content = (
+        tool_input.get("new_string")
+        or tool_input.get("newString")
+        or tool_input.get("content")
+        or ""
+    )
     
     # Check for console.log
     if "console.log" in content:
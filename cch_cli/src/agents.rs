@@ -0,0 +1,313 @@
+//! Hook payloads and responses of coding agents other than Claude Code
+//!
+//! CCH speaks Claude Code's hooks protocol. Cursor's hooks, and OpenCode
+//! through the plugin `cch install --agent opencode` writes, send their own
+//! payloads; [`normalize`] maps them onto Claude Code's event shape so one
+//! policy set covers every agent, and [`cursor_response`] answers Cursor in
+//! its own format. OpenCode's plugin reads Claude Code responses directly.
+
+use serde_json::{Map, Value, json};
+
+use crate::models::Response;
+
+/// The coding agent a hook event came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Agent {
+    Claude,
+    /// Cursor, with the name of its hook event (e.g. `beforeShellExecution`)
+    Cursor(String),
+    /// OpenCode, through the CCH plugin
+    OpenCode,
+}
+
+/// Cursor hook events CCH answers, in the order `cch install` lists them
+pub const CURSOR_EVENTS: [&str; 6] = [
+    "beforeShellExecution",
+    "beforeMCPExecution",
+    "beforeReadFile",
+    "afterFileEdit",
+    "beforeSubmitPrompt",
+    "stop",
+];
+
+/// OpenCode's lowercase tool names and the Claude Code tools they match
+const OPENCODE_TOOLS: [(&str, &str); 11] = [
+    ("bash", "Bash"),
+    ("read", "Read"),
+    ("write", "Write"),
+    ("edit", "Edit"),
+    ("multiedit", "MultiEdit"),
+    ("glob", "Glob"),
+    ("grep", "Grep"),
+    ("list", "LS"),
+    ("webfetch", "WebFetch"),
+    ("task", "Task"),
+    ("todowrite", "TodoWrite"),
+];
+
+impl Agent {
+    /// The agent that sent `raw`: the OpenCode plugin marks its payloads,
+    /// Cursor's event names are its own, anything else is Claude Code's
+    pub fn detect(raw: &Value) -> Self {
+        if raw.get("agent").and_then(Value::as_str) == Some("opencode") {
+            return Agent::OpenCode;
+        }
+        match raw.get("hook_event_name").and_then(Value::as_str) {
+            Some(name) if CURSOR_EVENTS.contains(&name) => Agent::Cursor(name.to_string()),
+            _ => Agent::Claude,
+        }
+    }
+}
+
+/// `raw` from `agent` in Claude Code's event shape
+pub fn normalize(agent: &Agent, raw: Value) -> Value {
+    match agent {
+        Agent::Claude => raw,
+        Agent::Cursor(event) => cursor_event(event, &raw),
+        Agent::OpenCode => opencode_event(&raw),
+    }
+}
+
+/// A Cursor hook payload as a Claude Code event
+///
+/// Shell commands become Bash, file reads Read and edits (after the fact)
+/// PostToolUse Edit events; MCP calls keep their tool name behind `mcp__`.
+/// The conversation is the session and the first workspace root the `cwd`.
+fn cursor_event(event: &str, raw: &Value) -> Value {
+    let field = |name: &str| raw.get(name).cloned().unwrap_or(Value::Null);
+    let (hook_event_name, tool_name, tool_input) = match event {
+        "beforeShellExecution" => (
+            "PreToolUse",
+            json!("Bash"),
+            json!({ "command": field("command") }),
+        ),
+        "beforeMCPExecution" => {
+            let name = raw.get("tool_name").and_then(Value::as_str).unwrap_or("");
+            // Cursor sends MCP arguments as a JSON string
+            let input = match field("tool_input") {
+                Value::String(s) => serde_json::from_str(&s).unwrap_or(Value::String(s)),
+                other => other,
+            };
+            ("PreToolUse", json!(format!("mcp__{}", name)), input)
+        }
+        "beforeReadFile" => (
+            "PreToolUse",
+            json!("Read"),
+            json!({ "file_path": field("file_path") }),
+        ),
+        "afterFileEdit" => (
+            "PostToolUse",
+            json!("Edit"),
+            json!({ "file_path": field("file_path"), "edits": field("edits") }),
+        ),
+        "beforeSubmitPrompt" => ("UserPromptSubmit", Value::Null, Value::Null),
+        _ => ("Stop", Value::Null, Value::Null),
+    };
+    let cwd = raw
+        .get("cwd")
+        .or_else(|| raw.get("workspace_roots").and_then(|roots| roots.get(0)))
+        .cloned()
+        .unwrap_or(Value::Null);
+    object([
+        ("hook_event_name", json!(hook_event_name)),
+        ("session_id", session_id(raw.get("conversation_id"))),
+        ("tool_name", tool_name),
+        ("tool_input", tool_input),
+        ("cwd", cwd),
+        ("prompt", field("prompt")),
+        ("user_id", field("user_email")),
+    ])
+}
+
+/// An OpenCode plugin payload as a Claude Code event
+///
+/// `tool.execute.before`/`after` become PreToolUse/PostToolUse with the
+/// tool renamed to its Claude Code name (OpenCode's camelCase arguments are
+/// normalized like any other), and `session.idle` becomes Stop.
+fn opencode_event(raw: &Value) -> Value {
+    let event = raw.get("event").and_then(Value::as_str).unwrap_or("");
+    let hook_event_name = match event {
+        "tool.execute.before" => "PreToolUse",
+        "tool.execute.after" => "PostToolUse",
+        _ => "Stop",
+    };
+    let tool_name = raw.get("tool").and_then(Value::as_str).map(|tool| {
+        OPENCODE_TOOLS
+            .iter()
+            .find(|(opencode, _)| *opencode == tool)
+            .map_or(tool, |(_, claude)| claude)
+            .to_string()
+    });
+    object([
+        ("hook_event_name", json!(hook_event_name)),
+        ("session_id", session_id(raw.get("session_id"))),
+        ("tool_name", json!(tool_name)),
+        (
+            "tool_input",
+            raw.get("args").cloned().unwrap_or(Value::Null),
+        ),
+        (
+            "tool_response",
+            raw.get("output").cloned().unwrap_or(Value::Null),
+        ),
+        ("cwd", raw.get("cwd").cloned().unwrap_or(Value::Null)),
+    ])
+}
+
+fn session_id(id: Option<&Value>) -> Value {
+    id.filter(|id| id.is_string())
+        .cloned()
+        .unwrap_or_else(|| json!("unknown"))
+}
+
+/// A JSON object of the non-null `fields`
+fn object<const N: usize>(fields: [(&str, Value); N]) -> Value {
+    let map: Map<String, Value> = fields
+        .into_iter()
+        .filter(|(_, value)| !value.is_null())
+        .map(|(key, value)| (key.to_string(), value))
+        .collect();
+    Value::Object(map)
+}
+
+/// `response` in the format Cursor expects for hook `event`
+///
+/// Shell and MCP calls get a `permission` (`allow`, `deny` or `ask`), file
+/// reads only `allow` or `deny`, prompts `continue`. The block reason, or
+/// the injected context of an allowed call, goes to the agent as
+/// `agent_message`; Cursor has no other way to add context. Edits and stops
+/// are only observed.
+pub fn cursor_response(event: &str, response: &Response) -> Value {
+    let reason = response
+        .reason
+        .clone()
+        .or_else(|| {
+            response
+                .hook_specific_output
+                .as_ref()
+                .and_then(|o| o.permission_decision_reason.clone())
+        })
+        .map_or(Value::Null, Value::String);
+    match event {
+        "beforeSubmitPrompt" => object([
+            ("continue", json!(response.continue_)),
+            ("user_message", reason),
+        ]),
+        "afterFileEdit" | "stop" => json!({}),
+        _ => {
+            let permission = match (response.continue_, response.asks()) {
+                (false, _) => "deny",
+                (true, true) if event == "beforeReadFile" => "deny",
+                (true, true) => "ask",
+                (true, false) => "allow",
+            };
+            let agent_message = if permission == "allow" {
+                response.context.clone().map_or(Value::Null, Value::String)
+            } else {
+                reason.clone()
+            };
+            let user_message = if permission == "allow" {
+                Value::Null
+            } else {
+                reason
+            };
+            object([
+                ("permission", json!(permission)),
+                ("user_message", user_message),
+                ("agent_message", agent_message),
+            ])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Event, EventType};
+
+    #[test]
+    fn test_cursor_and_opencode_payloads_become_claude_events() {
+        let cursor = json!({
+            "hook_event_name": "beforeShellExecution",
+            "conversation_id": "c1",
+            "generation_id": "g1",
+            "command": "git push --force",
+            "cwd": "/repo",
+            "workspace_roots": ["/repo"]
+        });
+        let (event, agent) = Event::from_agent_payload(cursor).unwrap();
+        assert_eq!(agent, Agent::Cursor("beforeShellExecution".to_string()));
+        assert_eq!(event.hook_event_name, EventType::PreToolUse);
+        assert_eq!(event.tool_name.as_deref(), Some("Bash"));
+        assert_eq!(event.tool_input.unwrap()["command"], "git push --force");
+        assert_eq!(event.session_id, "c1");
+        assert_eq!(event.cwd.as_deref(), Some("/repo"));
+
+        let (event, _) = Event::from_agent_payload(json!({
+            "hook_event_name": "beforeMCPExecution",
+            "conversation_id": "c1",
+            "tool_name": "create_issue",
+            "tool_input": "{\"title\":\"x\"}",
+            "workspace_roots": ["/repo"]
+        }))
+        .unwrap();
+        assert_eq!(event.tool_name.as_deref(), Some("mcp__create_issue"));
+        assert_eq!(event.tool_input.unwrap()["title"], "x");
+        assert_eq!(event.cwd.as_deref(), Some("/repo"));
+
+        let (event, agent) = Event::from_agent_payload(json!({
+            "agent": "opencode",
+            "event": "tool.execute.before",
+            "tool": "edit",
+            "args": { "filePath": "src/lib.rs", "oldString": "a", "newString": "b" },
+            "session_id": "ses_1",
+            "cwd": "/repo"
+        }))
+        .unwrap();
+        assert_eq!(agent, Agent::OpenCode);
+        assert_eq!(event.tool_name.as_deref(), Some("Edit"));
+        assert_eq!(event.tool_input.unwrap()["file_path"], "src/lib.rs");
+
+        // Claude Code's own events pass through untouched
+        let (event, agent) = Event::from_agent_payload(json!({
+            "hook_event_name": "Stop",
+            "session_id": "s"
+        }))
+        .unwrap();
+        assert_eq!(agent, Agent::Claude);
+        assert_eq!(event.hook_event_name, EventType::Stop);
+    }
+
+    #[test]
+    fn test_cursor_response_per_event() {
+        let blocked = Response::block("No force pushes");
+        assert_eq!(
+            cursor_response("beforeShellExecution", &blocked),
+            json!({
+                "permission": "deny",
+                "user_message": "No force pushes",
+                "agent_message": "No force pushes"
+            })
+        );
+        assert_eq!(
+            cursor_response("beforeSubmitPrompt", &blocked),
+            json!({ "continue": false, "user_message": "No force pushes" })
+        );
+        assert_eq!(cursor_response("afterFileEdit", &blocked), json!({}));
+
+        let ask = Response::ask("Deploys need a second look");
+        assert_eq!(
+            cursor_response("beforeMCPExecution", &ask)["permission"],
+            "ask"
+        );
+        assert_eq!(
+            cursor_response("beforeReadFile", &ask)["permission"],
+            "deny"
+        );
+
+        assert_eq!(
+            cursor_response("beforeShellExecution", &Response::inject("Use pnpm")),
+            json!({ "permission": "allow", "agent_message": "Use pnpm" })
+        );
+    }
+}
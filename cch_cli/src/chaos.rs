@@ -0,0 +1,49 @@
+//! Failure injection for testing fail-open behavior
+//!
+//! In a binary built with `--features chaos`, `CCH_CHAOS` names the failures
+//! to inject (comma-separated), so tests can drive the error paths that
+//! otherwise only run when something breaks:
+//!
+//! - `validator_timeout`: validator scripts hang until their timeout
+//! - `spawn_fail`: validator scripts fail to start
+//! - `config_corrupt`: config files read as malformed YAML
+//!
+//! Without the feature, nothing is ever injected and the checks compile
+//! away.
+
+/// A failure that `CCH_CHAOS` can inject
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    ValidatorTimeout,
+    SpawnFail,
+    ConfigCorrupt,
+}
+
+#[cfg(feature = "chaos")]
+impl Fault {
+    /// The fault's name in `CCH_CHAOS`
+    pub const fn name(self) -> &'static str {
+        match self {
+            Fault::ValidatorTimeout => "validator_timeout",
+            Fault::SpawnFail => "spawn_fail",
+            Fault::ConfigCorrupt => "config_corrupt",
+        }
+    }
+}
+
+/// Whether `CCH_CHAOS` asks for `fault`
+#[cfg(feature = "chaos")]
+pub fn injected(fault: Fault) -> bool {
+    let injected = std::env::var("CCH_CHAOS")
+        .is_ok_and(|faults| faults.split(',').any(|f| f.trim() == fault.name()));
+    if injected {
+        tracing::warn!("CCH_CHAOS: injecting {}", fault.name());
+    }
+    injected
+}
+
+/// Whether `CCH_CHAOS` asks for `fault` (never: built without `chaos`)
+#[cfg(not(feature = "chaos"))]
+pub const fn injected(_fault: Fault) -> bool {
+    false
+}
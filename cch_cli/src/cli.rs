@@ -1,6 +1,18 @@
+pub mod bundle;
+pub mod clean;
+pub mod coverage;
 pub mod debug;
+pub mod doctor;
 pub mod explain;
+pub mod import;
 pub mod init;
 pub mod install;
 pub mod logs;
+pub mod pack;
+pub mod rule;
+pub mod session;
+pub mod snapshot;
+pub mod stats;
+pub mod statusline;
+pub mod test;
 pub mod validate;
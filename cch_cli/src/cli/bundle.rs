@@ -0,0 +1,88 @@
+//! CCH Bundle Command - Move a complete policy to an offline machine
+//!
+//! `cch bundle export` writes the project's configuration and every file it
+//! references into one bundle file; `cch bundle import` verifies the bundle's
+//! hashes and installs it into the current project. No network access is
+//! needed on either side.
+
+use anyhow::{Context, Result};
+use std::path::Path;
+
+use crate::bundle::{Bundle, safe_relative_path};
+
+/// Export the project's policy as a bundle
+pub async fn export(out: String, config: Option<String>) -> Result<()> {
+    let config_path = config.unwrap_or_else(|| ".claude/hooks.yaml".to_string());
+
+    // Referenced files are relative to the project root that holds .claude/
+    let project_root = Path::new(&config_path)
+        .parent()
+        .and_then(Path::parent)
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+
+    let bundle = Bundle::export(Path::new(&config_path), project_root)?;
+    std::fs::write(&out, bundle.to_yaml()?).with_context(|| format!("Failed to write {}", out))?;
+
+    println!("✓ Exported bundle to {}", out);
+    for file in &bundle.files {
+        match file.trust {
+            Some(trust) => println!("    - {} ({})", file.path, trust),
+            None => println!("    - {}", file.path),
+        }
+    }
+    Ok(())
+}
+
+/// Verify a bundle and install it into the current project
+///
+/// Nothing is written unless every hash checks out and, without `force`,
+/// no existing file would be overwritten with different content.
+pub async fn import(file: String, force: bool) -> Result<()> {
+    let bundle = Bundle::from_yaml(
+        &std::fs::read_to_string(&file).with_context(|| format!("Failed to read {}", file))?,
+    )?;
+    bundle
+        .verify()
+        .with_context(|| format!("Refusing to import {}", file))?;
+
+    let project_root = Path::new(".");
+    let mut unchanged = Vec::new();
+    let mut conflicts = Vec::new();
+    for bundled in &bundle.files {
+        let path = project_root.join(safe_relative_path(&bundled.path)?);
+        match std::fs::read_to_string(&path) {
+            Ok(existing) if existing == bundled.content => unchanged.push(bundled.path.as_str()),
+            Ok(_) => conflicts.push(bundled.path.as_str()),
+            Err(_) => {}
+        }
+    }
+    if !conflicts.is_empty() && !force {
+        anyhow::bail!(
+            "Import would overwrite local changes to: {}. Re-run with --force to replace them",
+            conflicts.join(", ")
+        );
+    }
+
+    for bundled in &bundle.files {
+        if unchanged.contains(&bundled.path.as_str()) {
+            continue;
+        }
+        bundled.install(project_root)?;
+        let marker = if conflicts.contains(&bundled.path.as_str()) {
+            "~"
+        } else {
+            "+"
+        };
+        println!("  {} {}", marker, bundled.path);
+    }
+    for path in &unchanged {
+        println!("  = {}", path);
+    }
+    println!(
+        "✓ Imported bundle from cch {} ({} files verified)",
+        bundle.bundle.cch_version,
+        bundle.files.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,202 @@
+//! CCH Clean Command - Remove on-disk caches and state
+//!
+//! Only removes what the selected targets own: the audit log and its
+//! rotated segments, the cache, and session and global state. Other files
+//! in the same directories (the change journal, rule rename mappings, the
+//! protocol record) belong to other features and are kept. Lists what
+//! would be removed, with sizes, and asks before deleting unless `--yes`.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use crate::logging::Logger;
+use crate::state::{cache_dir, state_dir};
+
+/// Category of on-disk artifacts that can be cleaned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// Audit logs and latency histograms (~/.claude/logs)
+    Logs,
+    /// Derived caches (~/.claude/cache/cch)
+    Cache,
+    /// Session and global state (~/.claude/state)
+    State,
+}
+
+impl Target {
+    fn label(self) -> &'static str {
+        match self {
+            Target::Logs => "logs",
+            Target::Cache => "cache",
+            Target::State => "state",
+        }
+    }
+
+    /// Directory holding this target's artifacts
+    pub fn path(self) -> Result<PathBuf> {
+        match self {
+            Target::Logs => Logger::default_log_path()
+                .parent()
+                .map(Path::to_path_buf)
+                .context("Log path has no parent directory"),
+            Target::Cache => cache_dir(),
+            Target::State => state_dir(),
+        }
+    }
+
+    /// The files and directories under `dir` this target owns
+    fn artifacts(self, dir: &Path) -> Vec<PathBuf> {
+        let mut artifacts: Vec<PathBuf> = match self {
+            // The whole cache directory is CCH's own
+            Target::Cache => vec![dir.to_path_buf()],
+            Target::State => vec![dir.join("sessions"), dir.join("global.json")],
+            Target::Logs => {
+                let mut logs: Vec<PathBuf> = fs::read_dir(dir)
+                    .map(|entries| {
+                        entries
+                            .filter_map(|e| e.ok())
+                            .map(|e| e.path())
+                            .filter(|path| {
+                                path.file_name().and_then(|name| name.to_str()).is_some_and(
+                                    |name| name == "cch.log" || name.starts_with("cch.log."),
+                                )
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                logs.push(dir.join("latency"));
+                logs
+            }
+        };
+        artifacts.retain(|path| fs::symlink_metadata(path).is_ok());
+        artifacts.sort();
+        artifacts
+    }
+}
+
+/// How `cch clean` proceeds once it has listed what it would remove
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Confirm {
+    /// Ask on the terminal
+    Ask,
+    /// Remove without asking (`--yes`)
+    Yes,
+    /// Only list (`--dry-run`)
+    DryRun,
+}
+
+/// Run the clean command for the selected targets
+pub async fn run(targets: Vec<Target>, confirm: Confirm) -> Result<()> {
+    if targets.is_empty() {
+        println!("Nothing selected. Current disk usage:");
+        for target in [Target::Logs, Target::Cache, Target::State] {
+            let path = target.path()?;
+            println!(
+                "  {:<6} {:>10}  {}",
+                target.label(),
+                format_size(dir_size(&path)),
+                path.display()
+            );
+        }
+        println!();
+        println!("Use --logs, --cache, --state, or --all to remove them.");
+        return Ok(());
+    }
+
+    let mut artifacts = Vec::new();
+    for target in targets {
+        let path = target.path()?;
+        let owned = target.artifacts(&path);
+        if owned.is_empty() {
+            println!(
+                "○ {}: nothing to remove ({})",
+                target.label(),
+                path.display()
+            );
+            continue;
+        }
+        println!("{}:", target.label());
+        for artifact in owned {
+            let size = dir_size(&artifact);
+            println!("  {:>10}  {}", format_size(size), artifact.display());
+            artifacts.push((artifact, size));
+        }
+    }
+    if artifacts.is_empty() {
+        return Ok(());
+    }
+
+    let total = artifacts.iter().map(|(_, size)| size).sum();
+    println!();
+    match confirm {
+        Confirm::DryRun => {
+            println!("Would free {} (dry run)", format_size(total));
+            return Ok(());
+        }
+        Confirm::Ask if !std::io::stdin().is_terminal() => {
+            anyhow::bail!("Refusing to remove files without confirmation; pass --yes");
+        }
+        Confirm::Ask if !ask(&format!("Remove these ({})?", format_size(total)))? => {
+            println!("Nothing removed");
+            return Ok(());
+        }
+        Confirm::Ask | Confirm::Yes => {}
+    }
+
+    for (artifact, _) in &artifacts {
+        let removed = if fs::symlink_metadata(artifact).is_ok_and(|m| m.is_dir()) {
+            fs::remove_dir_all(artifact)
+        } else {
+            fs::remove_file(artifact)
+        };
+        removed.with_context(|| format!("Failed to remove {}", artifact.display()))?;
+    }
+    println!("✓ Freed {}", format_size(total));
+
+    Ok(())
+}
+
+/// Ask a yes/no question on the terminal, defaulting to no
+fn ask(question: &str) -> Result<bool> {
+    print!("{} [y/N] ", question);
+    std::io::stdout().flush()?;
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    Ok(matches!(input.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Compute the total size in bytes of all files under a path
+fn dir_size(path: &Path) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| dir_size(&e.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// Format a byte count for display
+#[allow(clippy::cast_precision_loss)]
+fn format_size(bytes: u64) -> String {
+    const KB: u64 = 1024;
+    const MB: u64 = KB * 1024;
+
+    if bytes >= MB {
+        format!("{:.1} MB", bytes as f64 / MB as f64)
+    } else if bytes >= KB {
+        format!("{:.1} KB", bytes as f64 / KB as f64)
+    } else {
+        format!("{} B", bytes)
+    }
+}
@@ -0,0 +1,185 @@
+//! CCH Coverage Command - Cross-reference rules against the audit log
+//!
+//! Reports rules that never matched, matchers that never evaluated true,
+//! and event types that no rule ever applied to, to guide config cleanup.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::BTreeMap;
+
+use crate::config::Config;
+use crate::logging::{LogQuery, QueryFilters};
+use crate::models::{LogEntry, MatcherResults, Rule};
+
+/// Run the coverage report
+///
+/// # Arguments
+/// * `since` - Window start: a relative age (`30d`, `12h`, `2w`) or RFC3339
+pub async fn run(since: String) -> Result<()> {
+    let since = parse_since(&since)?;
+    let config = Config::load(None)?;
+    let entries = LogQuery::new().query(QueryFilters {
+        since: Some(since),
+        ..Default::default()
+    })?;
+
+    println!(
+        "Coverage since {} ({} events, {} rules)",
+        since.format("%Y-%m-%d %H:%M"),
+        entries.len(),
+        config.rules.len()
+    );
+    println!();
+
+    if entries.is_empty() {
+        println!("No log entries found in this window.");
+        return Ok(());
+    }
+
+    let unmatched = unmatched_rules(&config.rules, &entries);
+    println!("Rules that never matched ({}):", unmatched.len());
+    for name in &unmatched {
+        println!("  - {}", name);
+    }
+    println!();
+
+    let has_debug_data = entries.iter().any(|e| e.rule_evaluations.is_some());
+    println!("Matchers that never evaluated true:");
+    if has_debug_data {
+        let dead = dead_matchers(&entries);
+        if dead.is_empty() {
+            println!("  (none)");
+        }
+        for (rule, matchers) in dead {
+            println!("  - {}: {}", rule, matchers.join(", "));
+        }
+    } else {
+        println!("  (no matcher data; enable settings.debug_logs to collect it)");
+    }
+    println!();
+
+    let uncovered = uncovered_event_types(&entries);
+    println!("Event types with no matching rules ({}):", uncovered.len());
+    for (event_type, count) in uncovered {
+        println!("  - {}: {} events", event_type, count);
+    }
+
+    Ok(())
+}
+
+/// Parse a `--since` value: `<N>m`, `<N>h`, `<N>d`, `<N>w`, or RFC3339
+fn parse_since(spec: &str) -> Result<DateTime<Utc>> {
+    parse_time("since", spec)
+}
+
+/// Parse the value of time option `--<flag>`: a relative age (`<N>m`,
+/// `<N>h`, `<N>d`, `<N>w`) before now, or an RFC3339 timestamp
+pub(crate) fn parse_time(flag: &str, spec: &str) -> Result<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(spec) {
+        return Ok(time.with_timezone(&Utc));
+    }
+
+    let (amount, unit) = spec.split_at(spec.len().saturating_sub(1));
+    let amount: i64 = amount.parse().with_context(|| {
+        format!(
+            "Invalid --{} '{}': use e.g. 30d, 12h, or RFC3339",
+            flag, spec
+        )
+    })?;
+    let duration = match unit {
+        "m" => Duration::minutes(amount),
+        "h" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        "w" => Duration::weeks(amount),
+        _ => anyhow::bail!("Invalid --{} unit in '{}': use m, h, d, or w", flag, spec),
+    };
+    Ok(Utc::now() - duration)
+}
+
+/// Names of configured rules that appear in no entry's `rules_matched`
+pub(crate) fn unmatched_rules<'a>(rules: &'a [Rule], entries: &[LogEntry]) -> Vec<&'a str> {
+    rules
+        .iter()
+        .filter(|rule| {
+            !entries
+                .iter()
+                .any(|e| e.rules_matched.iter().any(|m| m == &rule.name))
+        })
+        .map(|rule| rule.name.as_str())
+        .collect()
+}
+
+/// Per rule, the matchers that were evaluated but never came out true
+fn dead_matchers(entries: &[LogEntry]) -> BTreeMap<String, Vec<&'static str>> {
+    // rule -> matcher -> ever true
+    let mut seen: BTreeMap<String, BTreeMap<&'static str, bool>> = BTreeMap::new();
+    for evaluation in entries
+        .iter()
+        .flat_map(|e| e.rule_evaluations.iter().flatten())
+    {
+        let Some(ref results) = evaluation.matcher_results else {
+            continue;
+        };
+        let rule = seen.entry(evaluation.rule_name.clone()).or_default();
+        for (matcher, result) in matcher_fields(results) {
+            if let Some(matched) = result {
+                *rule.entry(matcher).or_default() |= matched;
+            }
+        }
+    }
+
+    seen.into_iter()
+        .filter_map(|(rule, matchers)| {
+            let dead: Vec<&str> = matchers
+                .into_iter()
+                .filter(|(_, ever_true)| !ever_true)
+                .map(|(matcher, _)| matcher)
+                .collect();
+            (!dead.is_empty()).then_some((rule, dead))
+        })
+        .collect()
+}
+
+fn matcher_fields(results: &MatcherResults) -> [(&'static str, Option<bool>); 20] {
+    [
+        ("tools", results.tools_matched),
+        ("extensions", results.extensions_matched),
+        ("directories", results.directories_matched),
+        ("command_match", results.command_match_matched),
+        ("operations", results.operations_matched),
+        ("zone", results.zone_matched),
+        ("exclude_tools", results.exclude_tools_matched),
+        ("exclude_extensions", results.exclude_extensions_matched),
+        ("exclude_directories", results.exclude_directories_matched),
+        ("command_not_match", results.command_not_match_matched),
+        ("tool_output_match", results.tool_output_match_matched),
+        ("exit_code", results.exit_code_matched),
+        ("prompt_match", results.prompt_match_matched),
+        ("background", results.background_matched),
+        ("protected_paths", results.protected_paths_matched),
+        ("expr", results.expr_matched),
+        ("any", results.any_matched),
+        ("all", results.all_matched),
+        ("not", results.not_matched),
+        ("policy", results.policy_matched),
+    ]
+}
+
+/// Event types where no logged event matched any rule, with event counts
+fn uncovered_event_types(entries: &[LogEntry]) -> Vec<(String, usize)> {
+    // event type -> (events, events with a match)
+    let mut counts: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    for entry in entries {
+        let (total, matched) = counts.entry(&entry.event_type).or_default();
+        *total += 1;
+        if !entry.rules_matched.is_empty() {
+            *matched += 1;
+        }
+    }
+
+    counts
+        .into_iter()
+        .filter(|(_, (_, matched))| *matched == 0)
+        .map(|(event_type, (total, _))| (event_type.to_string(), total))
+        .collect()
+}
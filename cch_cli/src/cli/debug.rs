@@ -1,6 +1,8 @@
 //! CCH Debug Command - Simulate and debug hook events
 //!
-//! Allows testing rules without invoking Claude Code.
+//! Allows testing rules without invoking Claude Code. Simulated events
+//! aren't written to the audit log or session state, and don't run the
+//! post-processor, unless `--record` is given.
 
 use anyhow::{Context, Result};
 use chrono::Utc;
@@ -8,8 +10,11 @@ use serde_json::json;
 use std::io::Write;
 
 use crate::config::Config;
+use crate::config::cache::ConfigCache;
 use crate::hooks;
-use crate::models::{DebugConfig, Event, EventType as ModelEventType};
+use crate::models::{
+    DebugConfig, Event, EventType as ModelEventType, MatcherResults, Response, RuleMetadata,
+};
 
 /// Event type for simulation (CLI parsing)
 #[derive(Debug, Clone, Copy)]
@@ -30,7 +35,7 @@ impl SimEventType {
         }
     }
 
-    fn from_str(s: &str) -> Option<Self> {
+    pub(crate) fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "pretooluse" | "pre" | "pre-tool-use" => Some(SimEventType::PreToolUse),
             "posttooluse" | "post" | "post-tool-use" => Some(SimEventType::PostToolUse),
@@ -48,6 +53,7 @@ pub async fn run(
     command: Option<String>,
     path: Option<String>,
     verbose: bool,
+    record: bool,
 ) -> Result<()> {
     let event_type = SimEventType::from_str(&event_type).context(format!(
         "Unknown event type: '{}'\nValid types: PreToolUse, PostToolUse, SessionStart, PermissionRequest",
@@ -74,7 +80,13 @@ pub async fn run(
 
     // Process the event with debug enabled
     let debug_config = DebugConfig::new(true, config.settings.debug_logs);
-    let response = hooks::process_event(event, &debug_config).await?;
+    let response = if record {
+        hooks::process_event(event.clone(), &debug_config).await?
+    } else {
+        hooks::simulate_event(event.clone(), &debug_config)
+            .await?
+            .response
+    };
     let response_json = serde_json::to_string_pretty(&response)?;
 
     println!("Response:");
@@ -85,9 +97,16 @@ pub async fn run(
     // Show rule evaluation summary
     if verbose {
         print_rule_summary(&config);
+        print_matcher_evaluation(&event, &config);
     }
 
-    // Explain the outcome
+    print_summary(&response);
+
+    Ok(())
+}
+
+/// Explain the outcome
+fn print_summary(response: &Response) {
     println!("Summary:");
     println!("{}", "-".repeat(40));
     if response.continue_ {
@@ -102,12 +121,10 @@ pub async fn run(
             response.reason.as_deref().unwrap_or("No reason provided")
         );
     }
-
-    Ok(())
 }
 
 /// Build a simulated event
-fn build_event(
+pub(crate) fn build_event(
     event_type: SimEventType,
     tool: Option<String>,
     command: Option<String>,
@@ -156,6 +173,9 @@ fn build_event(
         cwd: None,
         permission_mode: None,
         tool_use_id: None,
+        prompt: None,
+        legacy_input_keys: false,
+        tool_response: None,
     }
 }
 
@@ -170,7 +190,7 @@ fn print_rule_summary(config: &Config) {
         let priority = metadata.map_or(50, |m| m.priority);
         let status = if enabled { "✓" } else { "○" };
 
-        println!("  {} [P{}] {}", status, priority, rule.name,);
+        println!("  {} [P{}] {}", status, priority, rule.name);
         if let Some(desc) = &rule.description {
             println!("      {}", desc);
         }
@@ -178,6 +198,72 @@ fn print_rule_summary(config: &Config) {
     println!();
 }
 
+/// Print how each enabled rule's matchers evaluated against the event
+fn print_matcher_evaluation(event: &Event, config: &Config) {
+    println!("Matcher Evaluation:");
+    println!("{}", "-".repeat(40));
+
+    for rule in config.enabled_rules() {
+        let (matched, results) = hooks::matches_rule_with_debug(event, rule, config);
+        println!("  {} {}", mark(matched), rule.name);
+        if let Some(results) = results {
+            print_matcher_results(&results, 6);
+        }
+    }
+    println!();
+}
+
+/// Print one matcher block's results, descending into any/all/not
+fn print_matcher_results(results: &MatcherResults, indent: usize) {
+    let pad = " ".repeat(indent);
+    let leaves = [
+        ("tools", results.tools_matched),
+        ("extensions", results.extensions_matched),
+        ("directories", results.directories_matched),
+        ("command_match", results.command_match_matched),
+        ("operations", results.operations_matched),
+        ("zone", results.zone_matched),
+        ("exclude_tools", results.exclude_tools_matched),
+        ("exclude_extensions", results.exclude_extensions_matched),
+        ("exclude_directories", results.exclude_directories_matched),
+        ("command_not_match", results.command_not_match_matched),
+        ("tool_output_match", results.tool_output_match_matched),
+        ("exit_code", results.exit_code_matched),
+        ("prompt_match", results.prompt_match_matched),
+        ("background", results.background_matched),
+        ("protected_paths", results.protected_paths_matched),
+        ("expr", results.expr_matched),
+        ("policy", results.policy_matched),
+    ];
+    for (name, matched) in leaves {
+        if let Some(matched) = matched {
+            println!("{}{} {}", pad, mark(matched), name);
+        }
+    }
+
+    let blocks = [
+        ("any", results.any_matched, &results.any),
+        ("all", results.all_matched, &results.all),
+    ];
+    for (name, matched, nested) in blocks {
+        if let (Some(matched), Some(nested)) = (matched, nested) {
+            println!("{}{} {}", pad, mark(matched), name);
+            for (i, results) in nested.iter().enumerate() {
+                println!("{}    block {}", pad, i + 1);
+                print_matcher_results(results, indent + 8);
+            }
+        }
+    }
+    if let (Some(matched), Some(nested)) = (results.not_matched, &results.not) {
+        println!("{}{} not", pad, mark(matched));
+        print_matcher_results(nested, indent + 4);
+    }
+}
+
+fn mark(matched: bool) -> &'static str {
+    if matched { "✓" } else { "✗" }
+}
+
 /// Generate a simple UUID-like string
 fn uuid_simple() -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -188,14 +274,21 @@ fn uuid_simple() -> String {
 }
 
 /// Interactive debug mode
+///
+/// Events are evaluated, without logging, against an in-memory copy of the
+/// configuration: `disable` and `enable` try out rule changes without
+/// touching hooks.yaml, and `rerun` re-evaluates the last event under them.
+/// Edits saved to the configuration files are picked up before the next
+/// event, and a config that fails to load keeps the previous one in use.
 pub async fn interactive() -> Result<()> {
+    let cache = ConfigCache::default();
+    let mut config = Config::clone(&*cache.get(None)?);
+    let mut last_event: Option<Event> = None;
+
     println!("CCH Interactive Debug Mode");
     println!("{}", "=".repeat(60));
     println!("Enter events as JSON or use shortcuts:");
-    println!("  bash <command>    - Simulate Bash tool");
-    println!("  write <path>      - Simulate Write tool");
-    println!("  read <path>       - Simulate Read tool");
-    println!("  quit              - Exit");
+    print_repl_commands();
     println!();
 
     let stdin = std::io::stdin();
@@ -206,7 +299,9 @@ pub async fn interactive() -> Result<()> {
         stdout.flush()?;
 
         let mut input = String::new();
-        stdin.read_line(&mut input)?;
+        if stdin.read_line(&mut input)? == 0 {
+            break;
+        }
         let input = input.trim();
 
         if input.is_empty() {
@@ -220,64 +315,149 @@ pub async fn interactive() -> Result<()> {
 
         // Parse shortcuts
         let parts: Vec<&str> = input.splitn(2, ' ').collect();
-        match parts.first().map(|s| s.to_lowercase()).as_deref() {
-            Some("bash") => {
-                let cmd = (*parts.get(1).unwrap_or(&"echo test")).to_string();
-                run(
-                    "PreToolUse".to_string(),
-                    Some("Bash".to_string()),
-                    Some(cmd),
-                    None,
-                    false,
-                )
-                .await?;
+        let arg = parts.get(1).map(|s| s.trim());
+        let event = match parts.first().map(|s| s.to_lowercase()).as_deref() {
+            Some("bash") => Some(build_event(
+                SimEventType::PreToolUse,
+                Some("Bash".to_string()),
+                Some(arg.unwrap_or("echo test").to_string()),
+                None,
+            )),
+            Some(tool @ ("write" | "read")) => Some(build_event(
+                SimEventType::PreToolUse,
+                Some(if tool == "write" { "Write" } else { "Read" }.to_string()),
+                None,
+                Some(arg.unwrap_or("test.txt").to_string()),
+            )),
+            Some("rerun") => {
+                if last_event.is_none() {
+                    println!("No event to rerun yet.");
+                }
+                last_event.take()
             }
-            Some("write") => {
-                let path = (*parts.get(1).unwrap_or(&"test.txt")).to_string();
-                run(
-                    "PreToolUse".to_string(),
-                    Some("Write".to_string()),
-                    None,
-                    Some(path),
-                    false,
-                )
-                .await?;
+            Some(toggle @ ("disable" | "enable")) => {
+                match arg {
+                    Some(name) => match set_enabled(&mut config, name, toggle == "enable") {
+                        Ok(()) => println!("Rule '{}' {}d (in memory only)", name, toggle),
+                        Err(e) => println!("{}", e),
+                    },
+                    None => println!("Usage: {} <rule>", toggle),
+                }
+                None
             }
-            Some("read") => {
-                let path = (*parts.get(1).unwrap_or(&"test.txt")).to_string();
-                run(
-                    "PreToolUse".to_string(),
-                    Some("Read".to_string()),
-                    None,
-                    Some(path),
-                    false,
-                )
-                .await?;
+            Some("reload") => {
+                match cache.lookup(None) {
+                    Ok(lookup) => {
+                        config = Config::clone(&lookup.config);
+                        match lookup.error {
+                            Some(e) => print_reload_error(&e),
+                            None => {
+                                println!(
+                                    "Reloaded {} rules from configuration",
+                                    config.rules.len()
+                                );
+                            }
+                        }
+                    }
+                    Err(e) => print_reload_error(&e),
+                }
+                None
             }
             Some("help") => {
                 println!("Commands:");
-                println!("  bash <command>  - Test a bash command");
-                println!("  write <path>    - Test writing to a file");
-                println!("  read <path>     - Test reading a file");
-                println!("  quit            - Exit");
+                print_repl_commands();
+                None
             }
             _ => {
                 // Try to parse as JSON
-                match serde_json::from_str::<Event>(input) {
-                    Ok(event) => {
-                        let config = Config::load(None)?;
-                        let debug_config = DebugConfig::new(true, config.settings.debug_logs);
-                        let response = hooks::process_event(event, &debug_config).await?;
-                        println!("{}", serde_json::to_string_pretty(&response)?);
-                    }
-                    Err(_) => {
-                        println!("Unknown command or invalid JSON. Type 'help' for options.");
-                    }
+                if let Ok(event) = serde_json::from_str::<Event>(input) {
+                    Some(event)
+                } else {
+                    println!("Unknown command or invalid JSON. Type 'help' for options.");
+                    None
                 }
             }
+        };
+
+        if let Some(event) = event {
+            refresh(&cache, &mut config);
+            let report = hooks::evaluate_with_config(&event, &config).await?;
+            println!("{}", serde_json::to_string_pretty(&report.response)?);
+            let matched: Vec<&str> = report
+                .matched_rules
+                .iter()
+                .map(|r| r.name.as_str())
+                .collect();
+            println!(
+                "Matched rules: {}",
+                if matched.is_empty() {
+                    "none".to_string()
+                } else {
+                    matched.join(", ")
+                }
+            );
+            print_summary(&report.response);
+            last_event = Some(event);
         }
         println!();
     }
 
     Ok(())
 }
+
+/// Pick up configuration files changed since the last event
+fn refresh(cache: &ConfigCache, config: &mut Config) {
+    match cache.lookup(None) {
+        Ok(lookup) if lookup.reloaded => {
+            *config = Config::clone(&lookup.config);
+            println!(
+                "Configuration changed on disk; reloaded {} rules",
+                config.rules.len()
+            );
+        }
+        Ok(lookup) => {
+            if let Some(e) = lookup.error {
+                print_reload_error(&e);
+            }
+        }
+        Err(e) => print_reload_error(&e),
+    }
+}
+
+fn print_reload_error(e: &anyhow::Error) {
+    println!("Reload failed, keeping the previous configuration: {:#}", e);
+}
+
+fn print_repl_commands() {
+    println!("  bash <command>    - Simulate Bash tool");
+    println!("  write <path>      - Simulate Write tool");
+    println!("  read <path>       - Simulate Read tool");
+    println!("  disable <rule>    - Turn a rule off for this session");
+    println!("  enable <rule>     - Turn a rule back on");
+    println!("  rerun             - Evaluate the last event again");
+    println!("  reload            - Re-read the configuration, undoing enable/disable");
+    println!("  quit              - Exit");
+}
+
+/// Enable or disable a rule in the REPL's copy of the configuration
+fn set_enabled(config: &mut Config, name: &str, enabled: bool) -> Result<()> {
+    let script_timeout = config.settings.script_timeout;
+    let rule = config
+        .rules
+        .iter_mut()
+        .find(|r| r.name == name)
+        .with_context(|| format!("No rule named '{}'", name))?;
+    match rule.metadata {
+        Some(ref mut metadata) => metadata.enabled = enabled,
+        // Rules without metadata are enabled and use the default timeout
+        None if enabled => {}
+        None => {
+            rule.metadata = Some(RuleMetadata {
+                priority: 0,
+                timeout: script_timeout,
+                enabled,
+            });
+        }
+    }
+    Ok(())
+}
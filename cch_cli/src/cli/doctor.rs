@@ -0,0 +1,133 @@
+//! CCH Doctor Command - Diagnose the setup and how hooks talk to CCH
+//!
+//! `--environment` checks everything a working install needs, from the
+//! hook registration in Claude Code settings to the binary on PATH, and
+//! prints a fix for each failure. `--protocol` shows which spellings of
+//! event fields have been received, from the counters every processed event
+//! updates, as evidence of which legacy variants are still in use.
+//! `cch verify-protocol` checks the same setup against the hook protocol of
+//! a given Claude Code version.
+
+mod compatibility;
+mod environment;
+
+use anyhow::Result;
+
+use crate::logging::protocol::{self, ProtocolStats};
+use environment::{Check, Status};
+
+/// Report a doctor command can print
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    /// Setup checks with fixes for failures
+    Environment,
+    /// Field spellings seen in received events
+    Protocol,
+}
+
+/// Run the doctor command for the selected sections (all when none are)
+///
+/// Fails when any environment check does, after printing every section.
+pub async fn run(sections: Vec<Section>) -> Result<()> {
+    let sections = if sections.is_empty() {
+        vec![Section::Environment, Section::Protocol]
+    } else {
+        sections
+    };
+    let mut failures = 0;
+    for (index, section) in sections.into_iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        match section {
+            Section::Environment => {
+                println!("Environment");
+                let checks = environment::checks();
+                failures += checks.iter().filter(|c| c.status == Status::Fail).count();
+                checks.iter().for_each(print_check);
+            }
+            Section::Protocol => {
+                let path = protocol::protocol_path();
+                println!("Protocol variants ({})", path.display());
+                print_protocol(&protocol::load(&path)?);
+            }
+        }
+    }
+    if failures > 0 {
+        anyhow::bail!("{} doctor check(s) failed", failures);
+    }
+    Ok(())
+}
+
+/// Check the hook registration and rules against the protocol of Claude
+/// Code `claude_version` (X.Y or X.Y.Z)
+///
+/// Fails when the registration is malformed; unsupported events and
+/// response fields are warnings.
+pub fn verify_protocol(claude_version: &str) -> Result<()> {
+    let version: compatibility::Version = claude_version.parse()?;
+    println!("Protocol compatibility with Claude Code {}", version);
+    let checks = compatibility::checks(version)?;
+    checks.iter().for_each(print_check);
+    let failures = checks.iter().filter(|c| c.status == Status::Fail).count();
+    if failures > 0 {
+        anyhow::bail!("{} protocol check(s) failed", failures);
+    }
+    Ok(())
+}
+
+fn print_check(check: &Check) {
+    let mark = match check.status {
+        Status::Pass => "✓",
+        Status::Warn => "⚠️ ",
+        Status::Fail => "✗",
+    };
+    println!("  {} {}: {}", mark, check.name, check.detail);
+    if let Some(ref fix) = check.fix {
+        println!("      Fix: {}", fix);
+    }
+}
+
+fn print_protocol(stats: &ProtocolStats) {
+    if stats.events == 0 {
+        println!("  No events counted yet.");
+        return;
+    }
+    println!("  {} events counted", stats.events);
+
+    let mut legacy = Vec::new();
+    for (field, spellings) in &stats.fields {
+        println!();
+        println!("  {}", field);
+        let mut spellings: Vec<_> = spellings.iter().collect();
+        spellings.sort_by_key(|(_, seen)| std::cmp::Reverse(seen.count));
+        for (spelling, seen) in spellings {
+            let is_legacy = !is_canonical(field, spelling);
+            if is_legacy {
+                legacy.push(spelling.as_str());
+            }
+            println!(
+                "    {:<20} {:>8}  last seen {}{}",
+                spelling,
+                seen.count,
+                seen.last_seen.format("%Y-%m-%d"),
+                if is_legacy { "  (legacy)" } else { "" }
+            );
+        }
+    }
+
+    println!();
+    if legacy.is_empty() {
+        println!("  No legacy spellings seen.");
+    } else {
+        legacy.sort_unstable();
+        legacy.dedup();
+        println!("  Legacy spellings in use: {}", legacy.join(", "));
+    }
+}
+
+/// Whether `spelling` is the canonical name of `field` (`tool_input.<key>`
+/// for tool input keys)
+fn is_canonical(field: &str, spelling: &str) -> bool {
+    field.strip_prefix("tool_input.").unwrap_or(field) == spelling
+}
@@ -0,0 +1,426 @@
+//! Protocol compatibility with a given Claude Code release
+//!
+//! `cch verify-protocol --claude-version X.Y` compares the installed hook
+//! registration and the configured rules with compatibility.yaml, a matrix
+//! bundled into the binary of the first Claude Code release supporting each
+//! hook event and response field CCH relies on. A version without a patch
+//! number stands for the newest release of that line.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+
+use super::environment::{self, Check};
+use crate::cli::install::{self, Scope};
+use crate::config::Config;
+use crate::models::{InjectChannel, Matchers};
+
+const MATRIX: &str = include_str!("compatibility.yaml");
+
+#[derive(Deserialize)]
+struct Matrix {
+    events: BTreeMap<String, Version>,
+    response_fields: BTreeMap<String, Version>,
+}
+
+/// A Claude Code version; without `patch` it stands for a release line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Version {
+    major: u32,
+    minor: u32,
+    patch: Option<u32>,
+}
+
+impl Version {
+    /// Whether this release supports a feature introduced in `since`
+    fn includes(self, since: Version) -> bool {
+        (self.major, self.minor, self.patch.unwrap_or(u32::MAX))
+            >= (since.major, since.minor, since.patch.unwrap_or(0))
+    }
+}
+
+impl std::str::FromStr for Version {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parts: Vec<&str> = s.trim().trim_start_matches('v').split('.').collect();
+        let number = |part: &str| {
+            part.parse::<u32>()
+                .with_context(|| format!("Invalid Claude Code version '{}'", s))
+        };
+        match parts.as_slice() {
+            [major, minor] => Ok(Self {
+                major: number(major)?,
+                minor: number(minor)?,
+                patch: None,
+            }),
+            [major, minor, patch] => Ok(Self {
+                major: number(major)?,
+                minor: number(minor)?,
+                patch: Some(number(patch)?),
+            }),
+            _ => anyhow::bail!("Invalid Claude Code version '{}': expected X.Y or X.Y.Z", s),
+        }
+    }
+}
+
+impl TryFrom<String> for Version {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.patch {
+            Some(patch) => write!(f, "{}.{}.{}", self.major, self.minor, patch),
+            None => write!(f, "{}.{}", self.major, self.minor),
+        }
+    }
+}
+
+/// Run every compatibility check for Claude Code `version`
+pub fn checks(version: Version) -> Result<Vec<Check>> {
+    let matrix: Matrix =
+        serde_yaml::from_str(MATRIX).context("Bundled compatibility matrix is invalid")?;
+    let (mut checks, registered) = registration();
+    let config = Config::load(None);
+
+    checks.extend(registered_event_checks(&matrix, version, &registered));
+    match config {
+        Ok(config) => {
+            checks.extend(targeted_event_checks(
+                &matrix,
+                version,
+                &registered,
+                &config,
+            ));
+            checks.extend(response_field_checks(
+                &matrix,
+                version,
+                &registered,
+                &config,
+            ));
+        }
+        Err(e) => checks.push(Check::warn(
+            "hooks.yaml",
+            format!("{:#}", e),
+            "Fix the config so the events and responses its rules use can be checked",
+        )),
+    }
+    Ok(checks)
+}
+
+/// Shape problems in the Claude Code settings files, and the events CCH is
+/// registered for there
+fn registration() -> (Vec<Check>, BTreeSet<String>) {
+    let mut checks = Vec::new();
+    let mut events = BTreeSet::new();
+    for scope in [Scope::Project, Scope::Global] {
+        let Ok(path) = install::get_settings_path(scope) else {
+            continue;
+        };
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let Ok(settings) = serde_json::from_str::<serde_json::Value>(&content) else {
+            checks.push(Check::fail(
+                "Hook shape",
+                format!("{} is not valid JSON", path.display()),
+                format!("Fix the JSON syntax in {}", path.display()),
+            ));
+            continue;
+        };
+        let (problems, _) = environment::inspect_hooks(&settings);
+        if problems.is_empty() {
+            checks.push(Check::pass(
+                "Hook shape",
+                format!("{} ({})", path.display(), install::scope_name(scope)),
+            ));
+        } else {
+            checks.push(Check::fail(
+                "Hook shape",
+                format!("{}: {}", path.display(), problems.join("; ")),
+                "Rewrite the hooks in Claude Code's format: cch uninstall && cch install",
+            ));
+        }
+        events.extend(cch_events(&settings));
+    }
+    (checks, events)
+}
+
+/// Events whose hooks in `settings` run CCH
+fn cch_events(settings: &serde_json::Value) -> Vec<String> {
+    let Some(hooks) = settings.get("hooks").and_then(|h| h.as_object()) else {
+        return Vec::new();
+    };
+    hooks
+        .iter()
+        .filter(|(_, entries)| {
+            entries
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| entry.get("hooks")?.as_array())
+                .flatten()
+                .filter_map(|hook| hook.get("command")?.as_str())
+                .any(|command| command.contains("cch"))
+        })
+        .map(|(event, _)| event.clone())
+        .collect()
+}
+
+/// Registered events this version doesn't emit
+fn registered_event_checks(
+    matrix: &Matrix,
+    version: Version,
+    registered: &BTreeSet<String>,
+) -> Vec<Check> {
+    if registered.is_empty() {
+        return vec![Check::fail(
+            "Registered events",
+            "CCH is not registered as a hook in any Claude Code settings file",
+            "Run `cch install` (or `cch install --global`)",
+        )];
+    }
+    let mut checks = Vec::new();
+    let mut emitted = Vec::new();
+    for event in registered {
+        match matrix.events.get(event) {
+            Some(&since) if version.includes(since) => emitted.push(event.as_str()),
+            Some(since) => checks.push(Check::warn(
+                "Registered events",
+                format!(
+                    "CCH is registered for {}, which Claude Code {} doesn't emit (added in {})",
+                    event, version, since
+                ),
+                format!("Upgrade Claude Code to {} or later", since),
+            )),
+            None => checks.push(Check::warn(
+                "Registered events",
+                format!("{} is not a hook event CCH knows", event),
+                format!("Remove the {} hook from the Claude Code settings", event),
+            )),
+        }
+    }
+    if !emitted.is_empty() {
+        checks.insert(
+            0,
+            Check::pass(
+                "Registered events",
+                format!("Claude Code {} emits {}", version, emitted.join(", ")),
+            ),
+        );
+    }
+    checks
+}
+
+/// Events rules match on (`operations`) that never reach CCH, because this
+/// version doesn't emit them or CCH isn't registered for them
+fn targeted_event_checks(
+    matrix: &Matrix,
+    version: Version,
+    registered: &BTreeSet<String>,
+    config: &Config,
+) -> Vec<Check> {
+    let mut targeted = BTreeSet::new();
+    for rule in config.enabled_rules() {
+        collect_operations(&rule.matchers, &mut targeted);
+    }
+
+    let mut checks = Vec::new();
+    for event in &targeted {
+        match matrix.events.get(event) {
+            Some(&since) if !version.includes(since) => checks.push(Check::warn(
+                "Rule events",
+                format!(
+                    "Rules match {} events, which Claude Code {} doesn't emit (added in {})",
+                    event, version, since
+                ),
+                format!("Upgrade Claude Code to {} or later", since),
+            )),
+            Some(_) if !registered.contains(event) => checks.push(Check::warn(
+                "Rule events",
+                format!(
+                    "Rules match {} events, but CCH isn't registered for them",
+                    event
+                ),
+                format!(
+                    "Add a {} hook running cch to the Claude Code settings",
+                    event
+                ),
+            )),
+            _ => {}
+        }
+    }
+    if checks.is_empty() {
+        checks.push(Check::pass(
+            "Rule events",
+            "Every event rules match on reaches CCH",
+        ));
+    }
+    checks
+}
+
+/// Event types a matcher block requires, outside `not` blocks
+fn collect_operations(matchers: &Matchers, events: &mut BTreeSet<String>) {
+    events.extend(matchers.operations.iter().flatten().cloned());
+    for nested in matchers.any.iter().chain(&matchers.all).flatten() {
+        collect_operations(nested, events);
+    }
+}
+
+/// Response fields CCH sends under this config that this version ignores
+fn response_field_checks(
+    matrix: &Matrix,
+    version: Version,
+    registered: &BTreeSet<String>,
+    config: &Config,
+) -> Vec<Check> {
+    let rules = config.enabled_rules();
+    let system_messages = rules.iter().any(|rule| {
+        rule.actions
+            .inject
+            .as_ref()
+            .is_some_and(|inject| inject.channel() == InjectChannel::SystemMessage)
+    });
+    let any_rule = |action: fn(&crate::models::Actions) -> bool| {
+        rules.iter().any(|rule| action(&rule.actions))
+    };
+    let used = [
+        ("continue", true, "every response"),
+        ("reason", true, "blocks"),
+        (
+            "hookSpecificOutput.permissionDecision",
+            rules.iter().any(|rule| rule.actions.ask == Some(true)),
+            "rules with `ask: true`",
+        ),
+        (
+            "hookSpecificOutput.additionalContext",
+            registered.contains("UserPromptSubmit") || any_rule(|a| a.filter_results.is_some()),
+            "context injected into prompts and filtered search results",
+        ),
+        (
+            "hookSpecificOutput.updatedInput",
+            any_rule(|a| a.rewrite.is_some()),
+            "rules with `rewrite`",
+        ),
+        (
+            "hookSpecificOutput.updatedMCPToolOutput",
+            any_rule(|a| a.truncate_output.is_some()),
+            "rules with `truncate_output`",
+        ),
+        (
+            "systemMessage",
+            system_messages,
+            "injects with `channel: system_message`",
+        ),
+    ];
+
+    let mut checks = Vec::new();
+    let mut supported = Vec::new();
+    for (field, in_use, purpose) in used {
+        let Some(&since) = matrix.response_fields.get(field) else {
+            continue;
+        };
+        if !in_use {
+            continue;
+        }
+        if version.includes(since) {
+            supported.push(field);
+        } else {
+            checks.push(Check::warn(
+                "Response fields",
+                format!(
+                    "Claude Code {} ignores `{}`, used for {} (added in {})",
+                    version, field, purpose, since
+                ),
+                format!("Upgrade Claude Code to {} or later", since),
+            ));
+        }
+    }
+    if !supported.is_empty() {
+        checks.insert(
+            0,
+            Check::pass(
+                "Response fields",
+                format!("Claude Code {} reads {}", version, supported.join(", ")),
+            ),
+        );
+    }
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matrix_covers_every_event_type() {
+        let matrix: Matrix = serde_yaml::from_str(MATRIX).unwrap();
+        for event in matrix.events.keys() {
+            let event_type: crate::models::EventType =
+                serde_json::from_value(serde_json::Value::String(event.clone())).unwrap();
+            assert_eq!(&event_type.to_string(), event);
+        }
+        assert_eq!(matrix.events.len(), 13);
+    }
+
+    #[test]
+    fn test_matrix_covers_every_response_field() {
+        use crate::models::{HookSpecificOutput, PermissionDecision, Response};
+
+        let matrix: Matrix = serde_yaml::from_str(MATRIX).unwrap();
+        let mut response = Response::block("reason");
+        response.system_message = Some("message".to_string());
+        response.hook_specific_output = Some(HookSpecificOutput {
+            hook_event_name: "PreToolUse".to_string(),
+            permission_decision: Some(PermissionDecision::Ask),
+            permission_decision_reason: Some("reason".to_string()),
+            additional_context: Some("context".to_string()),
+            updated_input: Some(serde_json::json!({})),
+            updated_mcp_tool_output: Some(serde_json::json!("output")),
+        });
+        let json = serde_json::to_value(&response).unwrap();
+
+        // `context` and `timing` are CCH's own fields; `hookEventName` and
+        // the decision reason come with the output they belong to
+        let mut fields: Vec<String> = json
+            .as_object()
+            .unwrap()
+            .keys()
+            .filter(|k| !matches!(k.as_str(), "context" | "timing" | "hookSpecificOutput"))
+            .cloned()
+            .collect();
+        fields.extend(
+            json["hookSpecificOutput"]
+                .as_object()
+                .unwrap()
+                .keys()
+                .filter(|k| !matches!(k.as_str(), "hookEventName" | "permissionDecisionReason"))
+                .map(|k| format!("hookSpecificOutput.{}", k)),
+        );
+        for field in fields {
+            assert!(
+                matrix.response_fields.contains_key(&field),
+                "{} missing from compatibility.yaml",
+                field
+            );
+        }
+    }
+
+    #[test]
+    fn test_version_includes() {
+        let v = |s: &str| s.parse::<Version>().unwrap();
+        assert!(v("1.0").includes(v("1.0.62")));
+        assert!(!v("1.0.61").includes(v("1.0.62")));
+        assert!(v("2.0.0").includes(v("1.0.62")));
+        assert!(!v("1.0").includes(v("2.0.43")));
+        assert!("1".parse::<Version>().is_err());
+        assert!("1.x".parse::<Version>().is_err());
+    }
+}
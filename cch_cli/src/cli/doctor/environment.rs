@@ -0,0 +1,455 @@
+//! Environment checks for `cch doctor`
+//!
+//! Each check looks at one piece of the setup hooks depend on: the Claude
+//! Code settings that register CCH, the hooks.yaml it loads, the files rules
+//! point at, the log directory, and the binary on PATH. A failing check
+//! carries the fix to apply.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::cli::install::{self, Scope};
+use crate::config::Config;
+use crate::logging::Logger;
+use crate::models::{EventType, RunAction};
+
+/// Outcome of one check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// One diagnostic result
+#[derive(Debug)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub detail: String,
+    /// What to do about a warning or failure
+    pub fix: Option<String>,
+}
+
+impl Check {
+    pub(super) fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Pass,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    pub(super) fn warn(
+        name: &'static str,
+        detail: impl Into<String>,
+        fix: impl Into<String>,
+    ) -> Self {
+        Self {
+            name,
+            status: Status::Warn,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    pub(super) fn fail(
+        name: &'static str,
+        detail: impl Into<String>,
+        fix: impl Into<String>,
+    ) -> Self {
+        Self {
+            name,
+            status: Status::Fail,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Run every environment check, in the order they are reported
+pub fn checks() -> Vec<Check> {
+    let mut checks = settings_checks();
+    let (check, config) = config_check();
+    checks.push(check);
+    if let Some(config) = config {
+        checks.push(script_check(&config));
+        checks.push(context_file_check(&config));
+    }
+    checks.push(log_dir_check());
+    checks.push(path_check());
+    checks
+}
+
+/// The version this binary reports
+fn own_version() -> &'static str {
+    env!("CARGO_PKG_VERSION")
+}
+
+/// Hook registration in the project and global Claude Code settings
+fn settings_checks() -> Vec<Check> {
+    let mut checks = Vec::new();
+    let mut commands = BTreeSet::new();
+    for scope in [Scope::Project, Scope::Global] {
+        let Ok(path) = install::get_settings_path(scope) else {
+            continue;
+        };
+        if !path.exists() {
+            continue;
+        }
+        let reinstall = match scope {
+            Scope::Project => "cch uninstall && cch install",
+            Scope::Global => "cch uninstall --global && cch install --global",
+        };
+        let settings = fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| Ok(serde_json::from_str::<serde_json::Value>(&content)?));
+        let settings = match settings {
+            Ok(settings) => settings,
+            Err(e) => {
+                checks.push(Check::fail(
+                    "Claude settings",
+                    format!("{} is not valid JSON: {}", path.display(), e),
+                    format!("Fix the JSON syntax in {}", path.display()),
+                ));
+                continue;
+            }
+        };
+
+        let (problems, registered) = inspect_hooks(&settings);
+        if !problems.is_empty() {
+            checks.push(Check::fail(
+                "Claude settings",
+                format!(
+                    "{} ({}): {}",
+                    path.display(),
+                    install::scope_name(scope),
+                    problems.join("; ")
+                ),
+                format!("Rewrite the hooks in Claude Code's format: {}", reinstall),
+            ));
+        } else if !registered.is_empty() {
+            checks.push(Check::pass(
+                "Claude settings",
+                format!(
+                    "CCH registered in {} ({})",
+                    path.display(),
+                    install::scope_name(scope)
+                ),
+            ));
+        }
+        commands.extend(registered);
+    }
+
+    if commands.is_empty() {
+        checks.push(Check::fail(
+            "Claude settings",
+            "CCH is not registered as a hook in any Claude Code settings file",
+            "Run `cch install` (or `cch install --global`)",
+        ));
+    }
+    for command in commands {
+        checks.push(registered_command_check(&command));
+    }
+    checks
+}
+
+/// Schema problems in a settings file's `hooks`, and the CCH commands
+/// registered there
+///
+/// Claude Code expects PascalCase event names, each holding a list of
+/// `{matcher, hooks: [{type: command, command}]}` entries.
+pub(super) fn inspect_hooks(settings: &serde_json::Value) -> (Vec<String>, BTreeSet<String>) {
+    let mut problems = Vec::new();
+    let mut commands = BTreeSet::new();
+    let Some(hooks) = settings.get("hooks") else {
+        return (problems, commands);
+    };
+    let Some(hooks) = hooks.as_object() else {
+        problems.push("`hooks` is not an object".to_string());
+        return (problems, commands);
+    };
+
+    for (event, entries) in hooks {
+        let known = serde_json::from_value::<EventType>(serde_json::Value::String(event.clone()))
+            .is_ok_and(|event_type| event_type.to_string() == *event);
+        if !known {
+            problems.push(format!("unknown hook event `{}`", event));
+        }
+        let Some(entries) = entries.as_array() else {
+            problems.push(format!("`{}` is not a list of matcher entries", event));
+            continue;
+        };
+        for entry in entries {
+            if entry.get("command").is_some() {
+                problems.push(format!(
+                    "`{}` has a bare command instead of a `{{matcher, hooks}}` entry",
+                    event
+                ));
+            }
+            let Some(hook_commands) = entry.get("hooks").and_then(|h| h.as_array()) else {
+                problems.push(format!("`{}` entry has no `hooks` list", event));
+                continue;
+            };
+            for hook in hook_commands {
+                if hook.get("type").and_then(|t| t.as_str()) != Some("command") {
+                    problems.push(format!("`{}` hook is missing `type: command`", event));
+                }
+                if let Some(command) = hook.get("command").and_then(|c| c.as_str()) {
+                    if command.contains("cch") {
+                        commands.insert(command.to_string());
+                    }
+                }
+            }
+        }
+    }
+    problems.dedup();
+    (problems, commands)
+}
+
+/// Whether a registered hook command runs and matches this binary's version
+fn registered_command_check(command: &str) -> Check {
+    match version_of(command) {
+        Some(version) if version == own_version() => {
+            Check::pass("Hook command", format!("{} (version {})", command, version))
+        }
+        Some(version) => Check::fail(
+            "Hook command",
+            format!(
+                "{} is version {}, but this cch is {}",
+                command,
+                version,
+                own_version()
+            ),
+            "Register the current binary: cch uninstall && cch install",
+        ),
+        None => Check::fail(
+            "Hook command",
+            format!("{} does not run", command),
+            "Fix the path: cch uninstall, then cch install --binary /path/to/cch",
+        ),
+    }
+}
+
+/// hooks.yaml layers load and validate; the loaded config when they do
+fn config_check() -> (Check, Option<Config>) {
+    let layers = match Config::layers(None) {
+        Ok(layers) => layers,
+        Err(e) => {
+            return (
+                Check::fail(
+                    "hooks.yaml",
+                    format!("{:#}", e),
+                    "Set HOME so CCH can find ~/.claude",
+                ),
+                None,
+            );
+        }
+    };
+    if layers.is_empty() {
+        return (
+            Check::warn(
+                "hooks.yaml",
+                "No hooks.yaml found; every event is allowed",
+                "Run `cch init` to create .claude/hooks.yaml",
+            ),
+            None,
+        );
+    }
+    let files: Vec<String> = layers.iter().map(|p| p.display().to_string()).collect();
+    match Config::load(None) {
+        Ok(config) => (
+            Check::pass(
+                "hooks.yaml",
+                format!("{} rules from {}", config.rules.len(), files.join(", ")),
+            ),
+            Some(config),
+        ),
+        Err(e) => (
+            Check::fail(
+                "hooks.yaml",
+                format!("{}: {:#}", files.join(", "), e),
+                "Run `cch validate` and fix the reported error",
+            ),
+            None,
+        ),
+    }
+}
+
+/// Validator scripts of enabled rules exist and are executable
+/// (WebAssembly modules need only exist)
+fn script_check(config: &Config) -> Check {
+    let mut problems = Vec::new();
+    let mut fixes = Vec::new();
+    let mut scripts = 0;
+    for rule in config.enabled_rules() {
+        let Some(script) = rule.actions.script_path() else {
+            continue;
+        };
+        scripts += 1;
+        let path = Path::new(script);
+        if !path.is_file() {
+            problems.push(format!("{} (rule '{}') not found", script, rule.name));
+            fixes.push(format!("create {} or correct rule '{}'", script, rule.name));
+        } else if !rule.actions.run.as_ref().is_some_and(RunAction::is_wasm) && !is_executable(path)
+        {
+            problems.push(format!(
+                "{} (rule '{}') is not executable",
+                script, rule.name
+            ));
+            fixes.push(format!("chmod +x {}", script));
+        }
+    }
+    if problems.is_empty() {
+        Check::pass(
+            "Validator scripts",
+            format!("{} script(s) present and executable", scripts),
+        )
+    } else {
+        Check::fail("Validator scripts", problems.join("; "), fixes.join("; "))
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Files named by enabled rules' `inject` and `ensure_header` exist
+fn context_file_check(config: &Config) -> Check {
+    let mut files = Vec::new();
+    for rule in config.enabled_rules() {
+        if let Some(ref inject) = rule.actions.inject {
+            files.extend(inject.sources().iter().map(|s| (s.path(), &rule.name)));
+        }
+        if let Some(ref header) = rule.actions.ensure_header {
+            files.push((header.template.as_str(), &rule.name));
+        }
+    }
+    let missing: Vec<String> = files
+        .iter()
+        .filter(|(path, _)| !Path::new(path).is_file())
+        .map(|(path, rule)| format!("{} (rule '{}')", path, rule))
+        .collect();
+    if missing.is_empty() {
+        Check::pass("Context files", format!("{} file(s) present", files.len()))
+    } else {
+        Check::fail(
+            "Context files",
+            format!("not found: {}", missing.join(", ")),
+            "Create the files or remove them from the rules",
+        )
+    }
+}
+
+/// The log directory exists (or can be created) and takes new files
+fn log_dir_check() -> Check {
+    let Some(dir) = Logger::default_log_path().parent().map(Path::to_path_buf) else {
+        return Check::fail("Log directory", "no log directory", "Set HOME");
+    };
+    let probe = dir.join(format!(".cch-doctor-{}", std::process::id()));
+    let writable = fs::create_dir_all(&dir).and_then(|()| fs::write(&probe, b""));
+    let _ = fs::remove_file(&probe);
+    match writable {
+        Ok(()) => Check::pass("Log directory", format!("{} is writable", dir.display())),
+        Err(e) => Check::fail(
+            "Log directory",
+            format!("{} is not writable: {}", dir.display(), e),
+            format!("Check the ownership and permissions of {}", dir.display()),
+        ),
+    }
+}
+
+/// `cch` is on PATH and is the same version as this binary
+fn path_check() -> Check {
+    let Some(found) = find_on_path() else {
+        return Check::fail(
+            "PATH",
+            "cch is not on PATH",
+            "Run `cargo install --path cch_cli`, or add the directory holding cch to PATH",
+        );
+    };
+    let command = found.display().to_string();
+    match version_of(&command) {
+        Some(version) if version == own_version() => {
+            Check::pass("PATH", format!("{} (version {})", command, version))
+        }
+        Some(version) => Check::fail(
+            "PATH",
+            format!(
+                "{} is version {}, but this cch is {}",
+                command,
+                version,
+                own_version()
+            ),
+            "Reinstall so PATH and the registered hook use the same build",
+        ),
+        None => Check::fail("PATH", format!("{} does not run", command), "Reinstall cch"),
+    }
+}
+
+/// The first `cch` executable in PATH
+fn find_on_path() -> Option<PathBuf> {
+    let name = if cfg!(windows) { "cch.exe" } else { "cch" };
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file() && is_executable(path))
+}
+
+/// The version `command --version` reports, run through the shell the way
+/// Claude Code runs hooks
+fn version_of(command: &str) -> Option<String> {
+    let line = format!("{} --version", command);
+    let output = if cfg!(windows) {
+        Command::new("cmd").args(["/C", &line]).output()
+    } else {
+        Command::new("sh").args(["-c", &line]).output()
+    }
+    .ok()
+    .filter(|output| output.status.success())?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout.split_whitespace().last().map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inspect_hooks() {
+        let settings = serde_json::json!({
+            "hooks": {
+                "PreToolUse": [
+                    { "matcher": "*", "hooks": [{ "type": "command", "command": "/bin/cch" }] }
+                ],
+                "Stop": [{ "matcher": "*", "hooks": [{ "type": "command", "command": "notify" }] }]
+            }
+        });
+        let (problems, commands) = inspect_hooks(&settings);
+        assert!(problems.is_empty());
+        assert_eq!(commands.into_iter().collect::<Vec<_>>(), ["/bin/cch"]);
+
+        let legacy = serde_json::json!({
+            "hooks": { "pre_tool_use": [{ "command": "cch" }] }
+        });
+        let (problems, commands) = inspect_hooks(&legacy);
+        assert_eq!(
+            problems,
+            [
+                "unknown hook event `pre_tool_use`",
+                "`pre_tool_use` has a bare command instead of a `{matcher, hooks}` entry",
+                "`pre_tool_use` entry has no `hooks` list"
+            ]
+        );
+        assert!(commands.is_empty());
+    }
+}
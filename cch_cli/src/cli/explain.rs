@@ -1,9 +1,18 @@
 use anyhow::Result;
 use serde::Serialize;
 
-use crate::config::Config;
+use crate::cli::validate::describe_test;
+use crate::config::{Config, RuleLayer, TieBreak};
+use crate::encryption;
 use crate::logging::{LogQuery, QueryFilters};
-use crate::models::{Decision, Outcome, PolicyMode, Rule};
+use crate::models::{Decision, Matchers, Outcome, PolicyMode, Rule, RunAction};
+
+mod examples;
+
+use examples::Examples;
+
+/// Examples shown per bucket by `--show-matching-examples`
+const EXAMPLE_LIMIT: usize = 5;
 
 /// Explain why rules fired for a given event
 pub async fn run(event_id: String) -> Result<()> {
@@ -39,6 +48,16 @@ pub async fn run(event_id: String) -> Result<()> {
         println!("  Outcome: {:?}", entry.outcome);
         println!("  Processing Time: {}ms", entry.timing.processing_ms);
         println!("  Rules Evaluated: {}", entry.timing.rules_evaluated);
+        let counts = &entry.timing.counts;
+        if counts.rules_matched > 0 {
+            println!(
+                "  Rules Matched: {} ({} blocked, {} warned, {} audited)",
+                counts.rules_matched,
+                counts.rules_blocked,
+                counts.rules_warned,
+                counts.rules_audited
+            );
+        }
 
         // Phase 2.2: Show governance fields
         if let Some(mode) = &entry.mode {
@@ -96,8 +115,36 @@ pub async fn run(event_id: String) -> Result<()> {
 
 /// Explain a specific rule (P2.3-T01 through P2.3-T03)
 ///
-/// Displays mode, priority, metadata, and activity statistics for a rule.
-pub async fn explain_rule(rule_name: String, json_output: bool, no_stats: bool) -> Result<()> {
+/// Displays mode, priority, metadata, and activity statistics for a rule,
+/// and with `show_examples`, synthesized inputs it would and would not match.
+pub async fn explain_rule(
+    rule_name: String,
+    json_output: bool,
+    no_stats: bool,
+    show_examples: bool,
+) -> Result<()> {
+    let (config, rule, examples) = find_rule(&rule_name, show_examples)?;
+    if json_output {
+        let json = rule_json(&rule, &config, no_stats, examples).await?;
+        println!("{}", serde_json::to_string_pretty(&json)?);
+        Ok(())
+    } else {
+        output_rule_text(&rule, &config, no_stats, examples.as_ref()).await
+    }
+}
+
+/// Explain rule `rule_name` as the JSON `cch explain rule --json` prints
+pub(crate) async fn explain_rule_json(
+    rule_name: &str,
+    show_examples: bool,
+) -> Result<serde_json::Value> {
+    let (config, rule, examples) = find_rule(rule_name, show_examples)?;
+    rule_json(&rule, &config, false, examples).await
+}
+
+/// The configuration and rule `rule_name` in it (redacted when encrypted),
+/// with matching examples when asked
+fn find_rule(rule_name: &str, show_examples: bool) -> Result<(Config, Rule, Option<Examples>)> {
     // Load configuration
     let config = Config::load(None)?;
 
@@ -108,15 +155,113 @@ pub async fn explain_rule(rule_name: String, json_output: bool, no_stats: bool)
         .find(|r| r.name == rule_name)
         .ok_or_else(|| anyhow::anyhow!("Rule '{}' not found in configuration", rule_name))?;
 
-    if json_output {
-        output_rule_json(rule, no_stats).await
+    // Never reveal patterns from encrypted sections, not even through examples
+    let sealed = config.is_sealed(&rule.name);
+    let examples = match (show_examples, sealed) {
+        (true, false) => Some(examples::synthesize(rule, &config, EXAMPLE_LIMIT)),
+        (true, true) => {
+            eprintln!("Matching examples are not shown for encrypted rules");
+            None
+        }
+        (false, _) => None,
+    };
+    let rule = if sealed {
+        encryption::redact(rule)
     } else {
-        output_rule_text(rule, no_stats).await
+        rule.clone()
+    };
+    Ok((config, rule, examples))
+}
+
+/// Print each configured matcher of a rule, nesting boolean blocks
+fn print_matchers(matchers: &Matchers, indent: usize) {
+    let pad = " ".repeat(indent);
+    if let Some(ref tools) = matchers.tools {
+        println!("{}tools: {:?}", pad, tools);
+    }
+    if let Some(ref extensions) = matchers.extensions {
+        println!("{}extensions: {:?}", pad, extensions);
+    }
+    if let Some(ref directories) = matchers.directories {
+        println!("{}directories: {:?}", pad, directories);
+    }
+    if let Some(ref operations) = matchers.operations {
+        println!("{}operations: {:?}", pad, operations);
+    }
+    if let Some(ref cmd_match) = matchers.command_match {
+        println!("{}command_match: \"{}\"", pad, cmd_match);
+    }
+    if let Some(zone) = matchers.zone {
+        println!("{}zone: {}", pad, zone);
+    }
+    if let Some(ref tools) = matchers.exclude_tools {
+        println!("{}exclude_tools: {:?}", pad, tools);
+    }
+    if let Some(ref extensions) = matchers.exclude_extensions {
+        println!("{}exclude_extensions: {:?}", pad, extensions);
+    }
+    if let Some(ref directories) = matchers.exclude_directories {
+        println!("{}exclude_directories: {:?}", pad, directories);
+    }
+    if let Some(ref pattern) = matchers.command_not_match {
+        println!("{}command_not_match: \"{}\"", pad, pattern);
+    }
+    if let Some(ref pattern) = matchers.tool_output_match {
+        println!("{}tool_output_match: \"{}\"", pad, pattern);
+    }
+    if let Some(ref exit_code) = matchers.exit_code {
+        println!("{}exit_code: {}", pad, exit_code);
+    }
+    if let Some(ref pattern) = matchers.prompt_match {
+        println!("{}prompt_match: \"{}\"", pad, pattern);
+    }
+    if let Some(background) = matchers.background {
+        println!("{}background: {}", pad, background);
+    }
+    if let Some(ref protected) = matchers.protected_paths {
+        println!("{}protected_paths: deny {:?}", pad, protected.deny);
+        if !protected.allow.is_empty() {
+            println!("{}  allow {:?}", pad, protected.allow);
+        }
+    }
+    if let Some(ref source) = matchers.expr {
+        println!("{}expr: {}", pad, source);
+    }
+    for (name, blocks) in [("any", &matchers.any), ("all", &matchers.all)] {
+        for (i, nested) in blocks.iter().flatten().enumerate() {
+            if i == 0 {
+                println!("{}{}:", pad, name);
+            }
+            println!("{}  - block {}", pad, i + 1);
+            print_matchers(nested, indent + 4);
+        }
+    }
+    if let Some(ref not) = matchers.not {
+        println!("{}not:", pad);
+        print_matchers(not, indent + 2);
+    }
+    if let Some(ref policy) = matchers.policy {
+        println!("{}policy: {}", pad, policy);
+    }
+}
+
+/// Print a rule's `run` action
+fn print_run_action(run: &RunAction) {
+    let kind = if run.is_wasm() { "run (wasm)" } else { "run" };
+    println!("  {}: {}", kind, run.script_path());
+    println!("  trust: {}", run.trust_level());
+    if run.is_cacheable() {
+        println!("  cacheable: true");
     }
 }
 
 /// Output rule details as formatted text
-async fn output_rule_text(rule: &Rule, no_stats: bool) -> Result<()> {
+async fn output_rule_text(
+    rule: &Rule,
+    config: &Config,
+    no_stats: bool,
+    examples: Option<&Examples>,
+) -> Result<()> {
     println!("Rule: {}", rule.name);
     if let Some(ref desc) = rule.description {
         println!("Description: {}", desc);
@@ -151,25 +296,16 @@ async fn output_rule_text(rule: &Rule, no_stats: bool) -> Result<()> {
             ""
         }
     );
+    println!("Layer: {}", config.rule_layer(rule));
+    println!("Equal priorities ordered by: {}", config.tie_break_order());
+    if rule.stop == Some(true) {
+        println!("Stop: lower-priority rules are skipped when this rule matches");
+    }
     println!();
 
     // Matchers
     println!("Matchers:");
-    if let Some(ref tools) = rule.matchers.tools {
-        println!("  tools: {:?}", tools);
-    }
-    if let Some(ref extensions) = rule.matchers.extensions {
-        println!("  extensions: {:?}", extensions);
-    }
-    if let Some(ref directories) = rule.matchers.directories {
-        println!("  directories: {:?}", directories);
-    }
-    if let Some(ref operations) = rule.matchers.operations {
-        println!("  operations: {:?}", operations);
-    }
-    if let Some(ref cmd_match) = rule.matchers.command_match {
-        println!("  command_match: \"{}\"", cmd_match);
-    }
+    print_matchers(&rule.matchers, 2);
     println!();
 
     // Actions
@@ -177,47 +313,104 @@ async fn output_rule_text(rule: &Rule, no_stats: bool) -> Result<()> {
     if let Some(ref inject) = rule.actions.inject {
         println!("  inject: {}", inject);
     }
-    if let Some(script_path) = rule.actions.script_path() {
-        println!("  run: {}", script_path);
-        if let Some(trust) = rule.actions.trust_level() {
-            println!("  trust: {}", trust);
-        }
+    if let Some(ref command) = rule.actions.inject_command {
+        println!("  inject_command: \"{}\"", command);
+    }
+    if let Some(ref run) = rule.actions.run {
+        print_run_action(run);
     }
     if let Some(block) = rule.actions.block {
         println!("  block: {}", block);
     }
+    if let Some(ask) = rule.actions.ask {
+        println!("  ask: {}", ask);
+    }
+    if let Some(allow) = rule.actions.allow {
+        println!("  allow: {}", allow);
+    }
+    if let Some(ref files) = rule.actions.require_files {
+        println!("  require_files: {}", files.join(", "));
+    }
     if let Some(ref block_if) = rule.actions.block_if_match {
         println!("  block_if_match: \"{}\"", block_if);
     }
+    if let Some(ref checks) = rule.actions.structural_checks {
+        let names: Vec<String> = checks.iter().map(ToString::to_string).collect();
+        println!("  structural_checks: [{}]", names.join(", "));
+    }
+    if let Some(ref header) = rule.actions.ensure_header {
+        println!("  ensure_header: {}", header.template);
+    }
+    if let Some(ref filter) = rule.actions.filter_results {
+        println!("  filter_results: exclude [{}]", filter.exclude.join(", "));
+    }
+    if let Some(ref truncate) = rule.actions.truncate_output {
+        println!(
+            "  truncate_output: max_lines={:?} max_bytes={:?} strategy={:?}",
+            truncate.max_lines, truncate.max_bytes, truncate.strategy
+        );
+    }
+    if let Some(ref rewrite) = rule.actions.rewrite {
+        println!(
+            "  rewrite: {} pattern={:?} replace={:?} append={:?}",
+            rewrite.field, rewrite.pattern, rewrite.replace, rewrite.append
+        );
+    }
     println!();
 
-    // Governance metadata
-    if let Some(ref gov) = rule.governance {
-        println!("Governance:");
-        if let Some(ref author) = gov.author {
-            println!("  author: {}", author);
-        }
-        if let Some(ref created_by) = gov.created_by {
-            println!("  created_by: {}", created_by);
-        }
-        if let Some(ref reason) = gov.reason {
-            println!("  reason: {}", reason);
-        }
-        if let Some(ref confidence) = gov.confidence {
-            println!("  confidence: {}", confidence);
+    // Exemptions
+    if let Some(ref exemptions) = rule.exemptions {
+        println!("Exemptions:");
+        for exemption in exemptions {
+            println!("  - {}", exemption.justification);
+            if !exemption.paths.is_empty() {
+                println!("    paths: {:?}", exemption.paths);
+            }
+            if !exemption.commands.is_empty() {
+                println!("    commands: {:?}", exemption.commands);
+            }
+            if !exemption.sources.is_empty() {
+                println!("    sources: {:?}", exemption.sources);
+            }
         }
-        if let Some(ref last_reviewed) = gov.last_reviewed {
-            println!("  last_reviewed: {}", last_reviewed);
+        println!();
+    }
+
+    // Bypassing identities
+    if let Some(ref users) = rule.bypass_users {
+        println!("Bypassed for: {}", users.join(", "));
+        println!();
+    }
+
+    // Escalation
+    if let Some(ref escalation) = rule.escalation {
+        println!("Escalation:");
+        println!("  strategy: {}", escalation.strategy);
+        println!("  after: {}", escalation.after);
+        if let Some(ref advisory) = escalation.advisory {
+            println!("  advisory: {}", advisory);
         }
-        if let Some(ref ticket) = gov.ticket {
-            println!("  ticket: {}", ticket);
+        println!();
+    }
+
+    // Custom wording
+    if let Some(ref messages) = rule.messages {
+        println!("Messages:");
+        if let Some(ref warn) = messages.warn {
+            println!("  warn: {}", warn);
         }
-        if let Some(ref tags) = gov.tags {
-            println!("  tags: {:?}", tags);
+        if let Some(ref block) = messages.block {
+            println!("  block: {}", block);
         }
         println!();
     }
 
+    print_governance(rule);
+
+    if let Some(examples) = examples {
+        print_examples(examples);
+    }
+
     // Activity statistics (P2.3-T02)
     if !no_stats {
         print_activity_stats(&rule.name).await?;
@@ -226,8 +419,70 @@ async fn output_rule_text(rule: &Rule, no_stats: bool) -> Result<()> {
     Ok(())
 }
 
+/// Print a rule's governance metadata, flagging it when stale
+fn print_governance(rule: &Rule) {
+    let Some(ref gov) = rule.governance else {
+        return;
+    };
+    println!("Governance:");
+    if let Some(ref author) = gov.author {
+        println!("  author: {}", author);
+    }
+    if let Some(ref created_by) = gov.created_by {
+        println!("  created_by: {}", created_by);
+    }
+    if let Some(ref reason) = gov.reason {
+        println!("  reason: {}", reason);
+    }
+    if let Some(ref confidence) = gov.confidence {
+        println!("  confidence: {}", confidence);
+    }
+    if let Some(ref last_reviewed) = gov.last_reviewed {
+        println!("  last_reviewed: {}", last_reviewed);
+    }
+    if let Some(ref ticket) = gov.ticket {
+        println!("  ticket: {}", ticket);
+    }
+    if let Some(ref tags) = gov.tags {
+        println!("  tags: {:?}", tags);
+    }
+    if let Some(expires) = gov.expires {
+        println!("  expires: {}", expires);
+    }
+    if let Some(review_by) = gov.review_by {
+        println!("  review_by: {}", review_by);
+    }
+    if let Some(staleness) = rule.staleness(chrono::Local::now().date_naive()) {
+        println!("  ⚠️  stale: {}", staleness);
+    }
+    println!();
+}
+
+/// Print synthesized examples as ready-to-paste inline test descriptions
+fn print_examples(examples: &Examples) {
+    println!("Examples:");
+    for (label, tests) in [
+        ("Would match", &examples.matching),
+        ("Would not match", &examples.not_matching),
+    ] {
+        println!("  {}:", label);
+        if tests.is_empty() {
+            println!("    (none found)");
+        }
+        for test in tests {
+            println!("    - {}", describe_test(test));
+        }
+    }
+    println!();
+}
+
 /// Output rule details as JSON (P2.3-T03)
-async fn output_rule_json(rule: &Rule, no_stats: bool) -> Result<()> {
+async fn rule_json(
+    rule: &Rule,
+    config: &Config,
+    no_stats: bool,
+    examples: Option<Examples>,
+) -> Result<serde_json::Value> {
     #[derive(Serialize)]
     struct RuleOutput<'a> {
         name: &'a str,
@@ -236,19 +491,38 @@ async fn output_rule_json(rule: &Rule, no_stats: bool) -> Result<()> {
         mode_is_default: bool,
         priority: i32,
         priority_is_default: bool,
+        layer: RuleLayer,
+        #[serde(skip_serializing_if = "<[_]>::is_empty")]
+        priority_tie_break: &'a [TieBreak],
         matchers: &'a crate::models::Matchers,
         actions: ActionsOutput<'a>,
         governance: Option<&'a crate::models::GovernanceMetadata>,
         #[serde(skip_serializing_if = "Option::is_none")]
+        bypass_users: Option<&'a [String]>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        messages: Option<&'a crate::models::RuleMessages>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        stop: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
         activity: Option<ActivityStats>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        examples: Option<Examples>,
     }
 
     #[derive(Serialize)]
     struct ActionsOutput<'a> {
-        inject: Option<&'a str>,
+        inject: Option<&'a crate::models::InjectAction>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        inject_command: Option<&'a str>,
         run: Option<&'a str>,
         trust: Option<crate::models::TrustLevel>,
         block: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        ask: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        require_files: Option<&'a [String]>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        allow: Option<bool>,
         block_if_match: Option<&'a str>,
     }
 
@@ -273,10 +547,14 @@ async fn output_rule_json(rule: &Rule, no_stats: bool) -> Result<()> {
             .unwrap_or(true);
 
     let actions = ActionsOutput {
-        inject: rule.actions.inject.as_deref(),
+        inject: rule.actions.inject.as_ref(),
+        inject_command: rule.actions.inject_command.as_deref(),
         run: rule.actions.script_path(),
         trust: rule.actions.trust_level(),
         block: rule.actions.block,
+        ask: rule.actions.ask,
+        require_files: rule.actions.require_files.as_deref(),
+        allow: rule.actions.allow,
         block_if_match: rule.actions.block_if_match.as_deref(),
     };
 
@@ -305,16 +583,19 @@ async fn output_rule_json(rule: &Rule, no_stats: bool) -> Result<()> {
         mode_is_default,
         priority,
         priority_is_default,
+        layer: config.rule_layer(rule),
+        priority_tie_break: &config.settings.priority_tie_break,
         matchers: &rule.matchers,
         actions,
         governance: rule.governance.as_ref(),
+        bypass_users: rule.bypass_users.as_deref(),
+        messages: rule.messages.as_ref(),
+        stop: rule.stop,
         activity,
+        examples,
     };
 
-    let json = serde_json::to_string_pretty(&output)?;
-    println!("{}", json);
-
-    Ok(())
+    Ok(serde_json::to_value(&output)?)
 }
 
 /// Get activity statistics for a rule (P2.3-T02)
@@ -395,6 +676,7 @@ pub async fn list_rules() -> Result<()> {
         return Ok(());
     }
 
+    let today = chrono::Local::now().date_naive();
     println!("Configured rules ({} total):", config.rules.len());
     println!(
         "{:<25} {:<10} {:<8} {:<30}",
@@ -403,7 +685,7 @@ pub async fn list_rules() -> Result<()> {
     println!("{}", "-".repeat(75));
 
     for rule in config.enabled_rules() {
-        let mode = rule.effective_mode();
+        let mode = config.rule_mode(rule, today);
         let priority = rule.effective_priority();
         let desc = rule
             .description
@@ -419,5 +701,28 @@ pub async fn list_rules() -> Result<()> {
         );
     }
 
+    println!();
+    println!("Equal priorities ordered by: {}", config.tie_break_order());
+
+    let stale: Vec<String> = config
+        .rules
+        .iter()
+        .filter_map(|rule| Some(format!("{} ({})", rule.name, rule.staleness(today)?)))
+        .collect();
+    if !stale.is_empty() {
+        println!();
+        println!("Stale rules: {}", stale.join(", "));
+    }
+
+    let from_plugins: Vec<String> = config
+        .rules
+        .iter()
+        .filter_map(|rule| Some(format!("{} ({})", rule.name, rule.plugin()?)))
+        .collect();
+    if !from_plugins.is_empty() {
+        println!();
+        println!("Provided by plugins: {}", from_plugins.join(", "));
+    }
+
     Ok(())
 }
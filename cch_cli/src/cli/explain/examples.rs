@@ -0,0 +1,358 @@
+//! Example inputs for `cch explain rule --show-matching-examples`
+//!
+//! Candidates are synthesized from the rule's matcher structure: strings
+//! sampled from each `command_match` / `command_not_match` regex (one per
+//! alternation branch, plus near misses), and paths built from the
+//! configured directories and extensions, tried against the listed tools
+//! and one tool outside them. Every candidate is then run through the real
+//! matcher, so the match/no-match labels are never guessed.
+
+use regex_syntax::hir::{Class, Hir, HirKind};
+use serde::Serialize;
+
+use crate::cli::debug::{SimEventType, build_event};
+use crate::config::Config;
+use crate::hooks;
+use crate::models::{Matchers, Rule, RuleTest};
+
+/// Most variants kept per regex node, bounding the cartesian products
+const MAX_VARIANTS: usize = 4;
+
+/// Class members preferred when sampling, so `.*` reads as a space
+const PREFERRED_CHARS: &str = " a0-/._";
+
+/// Synthesized examples, split by whether the rule matched them
+///
+/// Each example is a [`RuleTest`], so it can be pasted into the rule's
+/// `tests:` block as is.
+#[derive(Debug, Default, Serialize)]
+pub struct Examples {
+    pub matching: Vec<RuleTest>,
+    pub not_matching: Vec<RuleTest>,
+}
+
+/// Synthesize up to `limit` matching and non-matching examples for a rule
+pub fn synthesize(rule: &Rule, config: &Config, limit: usize) -> Examples {
+    let mut inputs = Inputs::default();
+    inputs.collect(&rule.matchers);
+
+    // Simulate the first event type the rule is restricted to, if any
+    let event = rule
+        .matchers
+        .operations
+        .iter()
+        .flatten()
+        .find(|op| SimEventType::from_str(op).is_some())
+        .cloned();
+    let event_type = event
+        .as_deref()
+        .and_then(SimEventType::from_str)
+        .unwrap_or(SimEventType::PreToolUse);
+
+    let mut examples = Examples::default();
+    for (tool, command, path) in inputs.candidates() {
+        let simulated = build_event(
+            event_type,
+            Some(tool.clone()),
+            command.clone(),
+            path.clone(),
+        );
+        let matched = hooks::matches_rule(&simulated, rule, config);
+        let bucket = if matched {
+            &mut examples.matching
+        } else {
+            &mut examples.not_matching
+        };
+        if bucket.len() < limit {
+            bucket.push(RuleTest {
+                event: event.clone(),
+                tool: Some(tool),
+                command,
+                path,
+                expect_match: matched,
+            });
+        }
+    }
+    examples
+}
+
+/// Raw material for candidates, gathered from every (nested) matcher block
+#[derive(Default)]
+struct Inputs {
+    tools: Vec<String>,
+    commands: Vec<String>,
+    directories: Vec<String>,
+    extensions: Vec<String>,
+}
+
+impl Inputs {
+    fn collect(&mut self, matchers: &Matchers) {
+        for tool in matchers
+            .tools
+            .iter()
+            .chain(&matchers.exclude_tools)
+            .flatten()
+        {
+            push_unique(&mut self.tools, tool.clone());
+        }
+        for pattern in matchers
+            .command_match
+            .iter()
+            .chain(&matchers.command_not_match)
+        {
+            for sample in samples(pattern) {
+                let misses = near_misses(&sample);
+                push_unique(&mut self.commands, sample);
+                for command in misses {
+                    push_unique(&mut self.commands, command);
+                }
+            }
+        }
+        for dir in matchers
+            .directories
+            .iter()
+            .chain(&matchers.exclude_directories)
+            .flatten()
+        {
+            let dir = dir.trim_end_matches("/**").trim_end_matches("/*");
+            push_unique(&mut self.directories, dir.replace('*', "x"));
+        }
+        for ext in matchers
+            .extensions
+            .iter()
+            .chain(&matchers.exclude_extensions)
+            .flatten()
+        {
+            push_unique(&mut self.extensions, ext.clone());
+        }
+
+        for nested in matchers.any.iter().chain(&matchers.all).flatten() {
+            self.collect(nested);
+        }
+        if let Some(ref not) = matchers.not {
+            self.collect(not);
+        }
+    }
+
+    /// `(tool, command, path)` triples, each input also paired with a value
+    /// outside the configured ones
+    fn candidates(mut self) -> Vec<(String, Option<String>, Option<String>)> {
+        if self.tools.is_empty() {
+            self.tools = vec!["Bash".to_string(), "Write".to_string()];
+        } else {
+            add_outsider(&mut self.tools, &["Bash", "Write", "Read"]);
+        }
+        add_outsider(&mut self.commands, &["ls -la"]);
+        add_outsider(&mut self.directories, &["src", "docs"]);
+        add_outsider(&mut self.extensions, &[".rs", ".md"]);
+
+        let mut candidates = Vec::new();
+        for tool in &self.tools {
+            match tool.as_str() {
+                "Bash" | "Glob" | "Grep" => {
+                    for command in &self.commands {
+                        candidates.push((tool.clone(), Some(command.clone()), None));
+                    }
+                }
+                "Write" | "Edit" | "Read" => {
+                    for dir in &self.directories {
+                        for ext in &self.extensions {
+                            let path = format!("{}/example{}", dir, ext);
+                            candidates.push((tool.clone(), None, Some(path)));
+                        }
+                    }
+                }
+                _ => candidates.push((tool.clone(), None, None)),
+            }
+        }
+        candidates
+    }
+}
+
+fn push_unique(values: &mut Vec<String>, value: String) {
+    if !values.contains(&value) {
+        values.push(value);
+    }
+}
+
+/// Add the first of `outsiders` not already present
+fn add_outsider(values: &mut Vec<String>, outsiders: &[&str]) {
+    if let Some(outsider) = outsiders.iter().find(|o| !values.iter().any(|v| v == *o)) {
+        values.push((*outsider).to_string());
+    }
+}
+
+/// Variations of a matching sample that often fall just outside a pattern
+fn near_misses(sample: &str) -> Vec<String> {
+    let mut chars = sample.chars();
+    chars.next_back();
+    let truncated = chars.as_str();
+    let mut misses = Vec::new();
+    if !truncated.trim().is_empty() {
+        misses.push(truncated.to_string());
+    }
+    misses.push(format!("echo {}", sample));
+    misses
+}
+
+/// Strings matching `pattern`, one per alternation branch where possible
+fn samples(pattern: &str) -> Vec<String> {
+    let Ok(hir) = regex_syntax::parse(pattern) else {
+        return Vec::new();
+    };
+    sample(&hir)
+        .into_iter()
+        .filter(|s| !s.trim().is_empty())
+        .collect()
+}
+
+fn sample(hir: &Hir) -> Vec<String> {
+    match hir.kind() {
+        HirKind::Empty | HirKind::Look(_) => vec![String::new()],
+        HirKind::Literal(literal) => vec![String::from_utf8_lossy(&literal.0).into_owned()],
+        HirKind::Class(class) => representative(class)
+            .map(String::from)
+            .into_iter()
+            .collect(),
+        HirKind::Capture(capture) => sample(&capture.sub),
+        HirKind::Repetition(repetition) => {
+            if repetition.max == Some(0) {
+                return vec![String::new()];
+            }
+            let count = repetition.min.clamp(1, 8) as usize;
+            let mut variants: Vec<String> = sample(&repetition.sub)
+                .iter()
+                .map(|s| s.repeat(count))
+                .collect();
+            if repetition.min == 0 {
+                variants.push(String::new());
+            }
+            variants.truncate(MAX_VARIANTS);
+            variants
+        }
+        HirKind::Concat(parts) => parts.iter().fold(vec![String::new()], |prefixes, part| {
+            let suffixes = sample(part);
+            prefixes
+                .iter()
+                .flat_map(|prefix| {
+                    suffixes
+                        .iter()
+                        .map(move |suffix| format!("{prefix}{suffix}"))
+                })
+                .take(MAX_VARIANTS)
+                .collect()
+        }),
+        HirKind::Alternation(branches) => branches
+            .iter()
+            .filter_map(|branch| sample(branch).into_iter().next())
+            .take(MAX_VARIANTS)
+            .collect(),
+    }
+}
+
+/// A readable member of a character class, if it has any
+fn representative(class: &Class) -> Option<char> {
+    match class {
+        Class::Unicode(class) => {
+            let ranges = class.ranges();
+            PREFERRED_CHARS
+                .chars()
+                .find(|&c| ranges.iter().any(|r| r.start() <= c && c <= r.end()))
+                .or_else(|| ranges.first().map(|r| r.start()))
+        }
+        Class::Bytes(class) => {
+            let ranges = class.ranges();
+            PREFERRED_CHARS
+                .bytes()
+                .find(|&b| ranges.iter().any(|r| r.start() <= b && b <= r.end()))
+                .or_else(|| ranges.first().map(|r| r.start()))
+                .map(char::from)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(yaml: &str) -> Rule {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    fn inputs(tests: &[RuleTest]) -> Vec<&str> {
+        tests
+            .iter()
+            .map(|t| t.command.as_deref().or(t.path.as_deref()).unwrap_or(""))
+            .collect()
+    }
+
+    #[test]
+    fn test_samples_cover_alternation_and_repetition() {
+        assert_eq!(
+            samples("git push.*--force"),
+            vec!["git push --force", "git push--force"]
+        );
+        assert_eq!(samples(r"^(rm|del)\s+-rf"), vec!["rm -rf", "del -rf"]);
+        assert_eq!(samples(r"v\d{3}"), vec!["v000"]);
+        assert!(samples("(").is_empty());
+    }
+
+    #[test]
+    fn test_command_examples_are_labelled_by_the_matcher() {
+        let rule = rule(
+            r#"
+name: no-force-push
+matchers:
+  tools: [Bash]
+  command_match: "git push.*--force"
+  command_not_match: "--dry-run"
+actions: { block: true }
+"#,
+        );
+        let examples = synthesize(&rule, &Config::default(), 10);
+
+        let matching = inputs(&examples.matching);
+        assert!(matching.contains(&"git push --force"), "{:?}", matching);
+        assert!(examples.matching.iter().all(|t| t.expect_match));
+
+        let not_matching = inputs(&examples.not_matching);
+        assert!(
+            not_matching.contains(&"git push --forc"),
+            "{:?}",
+            not_matching
+        );
+        assert!(not_matching.contains(&"--dry-run"), "{:?}", not_matching);
+        assert!(
+            not_matching.contains(&"src/example.rs"),
+            "{:?}",
+            not_matching
+        );
+    }
+
+    #[test]
+    fn test_path_examples_use_directories_and_extensions() {
+        let rule = rule(
+            r#"
+name: infra-docs
+matchers:
+  tools: [Write]
+  directories: ["infra/**"]
+  extensions: [".tf"]
+actions: { inject: docs/infra.md }
+"#,
+        );
+        let examples = synthesize(&rule, &Config::default(), 10);
+        assert_eq!(inputs(&examples.matching), vec!["infra/example.tf"]);
+        let not_matching = inputs(&examples.not_matching);
+        assert!(
+            not_matching.contains(&"src/example.tf"),
+            "{:?}",
+            not_matching
+        );
+        assert!(
+            not_matching.contains(&"infra/example.rs"),
+            "{:?}",
+            not_matching
+        );
+    }
+}
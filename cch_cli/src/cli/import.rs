@@ -0,0 +1,481 @@
+//! CCH Import Command - Generate rules from server-side repository settings
+//!
+//! `cch import github --repo-settings` reads the repository's CODEOWNERS
+//! file and its branch protection and writes matching rules into
+//! hooks.yaml, so the agent is held to what GitHub would enforce anyway:
+//!
+//! - paths owned by teams other than `--team` ask before they are edited,
+//!   one `codeowners-<owners>` rule per owner set
+//! - `git push` to a protected branch is blocked (`github-protected-branches`)
+//!
+//! Branch protection comes from a JSON export (`--protection`) or from
+//! `gh api`. Importing again replaces the rules it wrote before.
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::config::edit::ConfigDocument;
+use crate::models::Rule;
+
+/// Where GitHub looks for CODEOWNERS, in its order of precedence
+const CODEOWNERS_PATHS: [&str; 3] = [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// Tools that change files
+const EDIT_TOOLS: [&str; 4] = ["Write", "Edit", "MultiEdit", "NotebookEdit"];
+
+/// Name of the rule blocking pushes to protected branches
+const BRANCHES_RULE: &str = "github-protected-branches";
+
+/// Recorded as the generated rules' `governance.created_by`
+const CREATED_BY: &str = "cch import github";
+
+/// Options for `cch import github`
+pub struct GithubOptions {
+    pub repo_settings: bool,
+    pub codeowners: Option<String>,
+    pub protection: Option<String>,
+    pub repo: Option<String>,
+    pub team: Option<String>,
+    pub dry_run: bool,
+    pub config: Option<String>,
+}
+
+/// One CODEOWNERS line: a path pattern and its owners (none: unowned)
+#[derive(Debug, Clone, PartialEq)]
+struct OwnerEntry {
+    pattern: String,
+    owners: Vec<String>,
+}
+
+/// Import GitHub repository settings as rules
+pub async fn github(options: GithubOptions) -> Result<()> {
+    if !options.repo_settings {
+        anyhow::bail!(
+            "Nothing to import; pass --repo-settings to import CODEOWNERS and branch protection"
+        );
+    }
+    let teams: Vec<String> = options
+        .team
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(owner_handle)
+        .collect();
+
+    let mut rules = Vec::new();
+    match codeowners_path(options.codeowners.as_deref())? {
+        Some(path) => {
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let entries = parse_codeowners(&content);
+            println!(
+                "Read {} CODEOWNERS entries from {}",
+                entries.len(),
+                path.display()
+            );
+            rules.extend(codeowners_rules(&entries, &teams)?);
+        }
+        None => println!("No CODEOWNERS file found; skipping path ownership"),
+    }
+
+    let protection = match &options.protection {
+        Some(path) => {
+            std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))?
+        }
+        None => fetch_protected_branches(options.repo.as_deref())?,
+    };
+    let protection: Value =
+        serde_json::from_str(&protection).context("Failed to parse branch protection JSON")?;
+    let branches = protected_branches(&protection);
+    println!("Found {} protected branches", branches.len());
+    if let Some(rule) = branches_rule(&branches)? {
+        rules.push(rule);
+    }
+
+    if rules.is_empty() {
+        println!("Nothing to import");
+        return Ok(());
+    }
+    if options.dry_run {
+        println!();
+        print!("{}", serde_yaml::to_string(&rules)?);
+        return Ok(());
+    }
+    write_rules(
+        Path::new(options.config.as_deref().unwrap_or(".claude/hooks.yaml")),
+        &rules,
+    )
+}
+
+/// `--codeowners`, else the first CODEOWNERS file GitHub would use
+fn codeowners_path(explicit: Option<&str>) -> Result<Option<PathBuf>> {
+    if let Some(path) = explicit {
+        let path = PathBuf::from(path);
+        if !path.exists() {
+            anyhow::bail!("CODEOWNERS file not found: {}", path.display());
+        }
+        return Ok(Some(path));
+    }
+    Ok(CODEOWNERS_PATHS
+        .iter()
+        .map(PathBuf::from)
+        .find(|path| path.exists()))
+}
+
+/// `name` as a CODEOWNERS owner: teams and users take an `@`, emails don't
+fn owner_handle(name: &str) -> String {
+    if name.contains('@') {
+        name.to_string()
+    } else {
+        format!("@{}", name)
+    }
+}
+
+/// The entries of a CODEOWNERS file, in file order
+fn parse_codeowners(content: &str) -> Vec<OwnerEntry> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.split(" #").next().unwrap_or_default().trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let mut fields = line.split_whitespace();
+            let pattern = fields.next()?.replace("\\#", "#");
+            Some(OwnerEntry {
+                pattern,
+                owners: fields.map(ToString::to_string).collect(),
+            })
+        })
+        .collect()
+}
+
+/// A CODEOWNERS pattern as a `protected_paths` glob
+///
+/// Patterns follow gitignore: a leading or inner `/` anchors the pattern at
+/// the repository root, otherwise it matches at any depth; a name without
+/// wildcards covers everything below it.
+fn codeowners_glob(pattern: &str) -> String {
+    let body = pattern.trim_start_matches('/').trim_end_matches('/');
+    if body == "*" || body.is_empty() {
+        return "**".to_string();
+    }
+    let anchored = pattern.starts_with('/') || body.contains('/');
+    let glob = if anchored {
+        body.to_string()
+    } else {
+        format!("**/{}", body)
+    };
+    let last = body.rsplit('/').next().unwrap_or(body);
+    if pattern.ends_with('/') || !last.contains(['*', '?', '[']) {
+        format!("{}/**", glob)
+    } else {
+        glob
+    }
+}
+
+/// One rule per set of owners outside `teams`, asking before their paths
+/// are edited
+///
+/// The last matching CODEOWNERS line decides a path's owners, so each line
+/// exempts the paths of later lines with other owners.
+fn codeowners_rules(entries: &[OwnerEntry], teams: &[String]) -> Result<Vec<Rule>> {
+    let ours = |entry: &OwnerEntry| {
+        entry
+            .owners
+            .iter()
+            .any(|owner| teams.iter().any(|team| team.eq_ignore_ascii_case(owner)))
+    };
+
+    let mut groups: Vec<(&[String], Vec<Value>)> = Vec::new();
+    for (i, entry) in entries.iter().enumerate() {
+        if entry.owners.is_empty() || ours(entry) {
+            continue;
+        }
+        let allow: Vec<String> = entries[i + 1..]
+            .iter()
+            .filter(|later| later.owners != entry.owners)
+            .map(|later| codeowners_glob(&later.pattern))
+            .collect();
+        let mut paths = json!({ "deny": [codeowners_glob(&entry.pattern)] });
+        if !allow.is_empty() {
+            paths["allow"] = json!(allow);
+        }
+        match groups
+            .iter_mut()
+            .find(|(owners, _)| *owners == entry.owners.as_slice())
+        {
+            Some((_, blocks)) => blocks.push(paths),
+            None => groups.push((&entry.owners, vec![paths])),
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(owners, mut blocks)| {
+            let owners_text = owners.join(" ");
+            let mut matchers = json!({
+                "tools": EDIT_TOOLS,
+                "operations": ["PreToolUse"],
+            });
+            if blocks.len() == 1 {
+                matchers["protected_paths"] = blocks.remove(0);
+            } else {
+                let any: Vec<Value> = blocks
+                    .into_iter()
+                    .map(|paths| json!({ "protected_paths": paths }))
+                    .collect();
+                matchers["any"] = json!(any);
+            }
+            let rule = json!({
+                "name": format!("codeowners-{}", slug(owners)),
+                "description": format!(
+                    "Owned by {} in CODEOWNERS; check with them before changing it",
+                    owners_text
+                ),
+                "matchers": matchers,
+                "actions": { "ask": true },
+                "governance": {
+                    "created_by": CREATED_BY,
+                    "reason": format!("Mirrors CODEOWNERS ({})", owners_text),
+                    "tags": ["github", "codeowners"],
+                },
+            });
+            serde_json::from_value(rule).context("Failed to build CODEOWNERS rule")
+        })
+        .collect()
+}
+
+/// A rule name fragment for `owners`, e.g. `acme-payments` for `@acme/payments`
+fn slug(owners: &[String]) -> String {
+    let slug: String = owners
+        .join("-")
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    slug.split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// The protected branches listed by `gh api` for `repo` (default: the
+/// current repository)
+fn fetch_protected_branches(repo: Option<&str>) -> Result<String> {
+    let endpoint = format!(
+        "repos/{}/branches?protected=true",
+        repo.unwrap_or("{owner}/{repo}")
+    );
+    let output = std::process::Command::new("gh")
+        .args(["api", "--paginate", &endpoint])
+        .output()
+        .context("Failed to run gh; install the GitHub CLI or pass --protection <file>")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "gh api {} failed: {}",
+            endpoint,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Branch names in a branch protection export
+///
+/// Accepts a list of branches (`GET /repos/{repo}/branches?protected=true`),
+/// one branch (`GET .../branches/{branch}`) or one branch's protection
+/// (`GET .../branches/{branch}/protection`, named by its `url`). Branches
+/// listed as unprotected are skipped.
+fn protected_branches(export: &Value) -> Vec<String> {
+    let items = match export {
+        Value::Array(items) => items.iter().collect(),
+        other => vec![other],
+    };
+    let mut branches: Vec<String> = Vec::new();
+    for item in items {
+        if item.get("protected").and_then(Value::as_bool) == Some(false) {
+            continue;
+        }
+        let name = item
+            .get("name")
+            .and_then(Value::as_str)
+            .map(ToString::to_string)
+            .or_else(|| {
+                let url = item.get("url").and_then(Value::as_str)?;
+                let (_, rest) = url.split_once("/branches/")?;
+                Some(rest.trim_end_matches("/protection").to_string())
+            });
+        if let Some(name) = name.filter(|name| !branches.contains(name)) {
+            branches.push(name);
+        }
+    }
+    branches
+}
+
+/// A rule blocking `git push` to `branches`, named on the command line or
+/// checked out when the push names none
+fn branches_rule(branches: &[String]) -> Result<Option<Rule>> {
+    if branches.is_empty() {
+        return Ok(None);
+    }
+    let names = branches
+        .iter()
+        .map(|b| regex::escape(b))
+        .collect::<Vec<_>>()
+        .join("|");
+    let quoted = branches
+        .iter()
+        .map(serde_json::to_string)
+        .collect::<serde_json::Result<Vec<_>>>()?
+        .join(", ");
+    let rule = json!({
+        "name": BRANCHES_RULE,
+        "description": format!(
+            "Protected on GitHub: {}; push to a feature branch and open a pull request",
+            branches.join(", ")
+        ),
+        "matchers": {
+            "tools": ["Bash"],
+            "operations": ["PreToolUse"],
+            "command_match": r"^\s*git\s+push\b",
+            "any": [
+                // The branch as a refspec: `origin main`, `HEAD:main`, `:main`
+                { "command_match": format!(r"[\s:+](refs/heads/)?({})(\s|$)", names) },
+                // No refspec: pushes the checked-out branch
+                {
+                    "command_match": r"^\s*git\s+push(\s+-\S+)*(\s+[^-\s]\S*)?(\s+-\S+)*\s*$",
+                    "expr": format!("branch in [{}]", quoted),
+                },
+            ],
+        },
+        "actions": { "block": true },
+        "governance": {
+            "created_by": CREATED_BY,
+            "reason": "Mirrors GitHub branch protection",
+            "tags": ["github", "branch-protection"],
+        },
+    });
+    serde_json::from_value(rule)
+        .map(Some)
+        .context("Failed to build branch protection rule")
+}
+
+/// Add `rules` to the config at `path`, replacing earlier imports by name
+fn write_rules(path: &Path, rules: &[Rule]) -> Result<()> {
+    let mut document = ConfigDocument::read(path)?;
+    let parsed: Value = serde_yaml::from_str(document.as_str())
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    let existing: Vec<String> = parsed
+        .get("rules")
+        .and_then(Value::as_array)
+        .map(|rules| {
+            rules
+                .iter()
+                .filter_map(|rule| rule.get("name").and_then(Value::as_str))
+                .map(ToString::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut lines = Vec::new();
+    for rule in rules {
+        if existing.contains(&rule.name) {
+            document.replace_rule(&rule.name, rule)?;
+            lines.push(format!("  ~ {} (replaced)", rule.name));
+        } else {
+            document.append_rule(rule)?;
+            lines.push(format!("  + {}", rule.name));
+        }
+    }
+    let merged: Config =
+        serde_yaml::from_str(document.as_str()).context("Imported configuration does not parse")?;
+    merged
+        .validate()
+        .context("Imported configuration is invalid")?;
+    document.save(path)?;
+
+    for line in lines {
+        println!("{}", line);
+    }
+    println!("✓ Updated {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codeowners_globs_follow_gitignore_anchoring() {
+        assert_eq!(codeowners_glob("*"), "**");
+        assert_eq!(codeowners_glob("*.js"), "**/*.js");
+        assert_eq!(codeowners_glob("/docs/"), "docs/**");
+        assert_eq!(codeowners_glob("docs/"), "**/docs/**");
+        assert_eq!(codeowners_glob("apps/github"), "apps/github/**");
+        assert_eq!(codeowners_glob("/build/logs/*"), "build/logs/*");
+    }
+
+    #[test]
+    fn test_codeowners_rules_skip_own_paths_and_later_overrides() {
+        let entries = parse_codeowners(
+            "# Payments\n\
+             /services/payments/ @acme/payments\n\
+             /services/payments/docs/ @acme/web # docs are ours\n\
+             *.sql @acme/dba @alice\n\
+             /web/ @acme/web\n\
+             /vendor/\n",
+        );
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries[2].owners, ["@acme/dba", "@alice"]);
+
+        let rules = codeowners_rules(&entries, &["@acme/web".to_string()]).unwrap();
+        let names: Vec<&str> = rules.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["codeowners-acme-payments", "codeowners-acme-dba-alice"]
+        );
+
+        let payments = rules[0].matchers.protected_paths.as_ref().unwrap();
+        assert_eq!(payments.deny, ["services/payments/**"]);
+        assert_eq!(
+            payments.allow,
+            [
+                "services/payments/docs/**",
+                "**/*.sql",
+                "web/**",
+                "vendor/**"
+            ]
+        );
+        assert_eq!(rules[0].actions.ask, Some(true));
+        assert_eq!(rules[0].matchers.tools.as_ref().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_protected_branches_from_each_export_shape() {
+        let list = json!([
+            { "name": "main", "protected": true },
+            { "name": "release", "protected": true },
+            { "name": "scratch", "protected": false }
+        ]);
+        assert_eq!(protected_branches(&list), ["main", "release"]);
+        let protection = json!({
+            "url": "https://api.github.com/repos/acme/app/branches/develop/protection",
+            "allow_force_pushes": { "enabled": false }
+        });
+        assert_eq!(protected_branches(&protection), ["develop"]);
+
+        let rule = branches_rule(&["main".to_string()]).unwrap().unwrap();
+        let config = Config {
+            rules: vec![rule.clone()],
+            ..serde_yaml::from_str("version: \"1.0\"\nrules: []").unwrap()
+        };
+        config.validate().unwrap();
+        assert_eq!(rule.name, BRANCHES_RULE);
+        assert!(branches_rule(&[]).unwrap().is_none());
+    }
+}
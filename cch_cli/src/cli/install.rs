@@ -1,12 +1,168 @@
 //! CCH Install Command - Register CCH with Claude Code
 //!
-//! Adds CCH hook configuration to Claude Code settings.
+//! Adds CCH hook configuration to Claude Code settings, then sends a
+//! synthetic event through the registered command to check that it works.
+//! Only the chosen events are registered (`--events`, else
+//! `settings.install.events`, else the events the enabled rules can fire
+//! on, see [`rule_footprint`]), so events no rule uses don't cost a hook
+//! round trip. Tool events can likewise be limited to some tools
+//! (`--tools`, else `settings.install.tools`, else the tools the rules
+//! name) through the entry's `matcher`. A `--global` install serves every
+//! project, so without flags it registers every event for every tool.
+//!
+//! `--agent` installs CCH for another coding agent instead: Cursor gets
+//! entries in its `hooks.json`, OpenCode a plugin that pipes tool calls
+//! through CCH (see [`crate::agents`] for how their events are mapped).
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+
+use crate::agents::CURSOR_EVENTS;
+use crate::config::Config;
+use crate::models::{EventType, Matchers, Response};
+
+/// Timeout registered for the hook, also applied to the self-test
+const HOOK_TIMEOUT_SECS: u32 = 5;
+
+/// Session ID of the self-test event, so it can be told apart in the logs
+const SELF_TEST_SESSION: &str = "cch-install-self-test";
+
+/// Events always registered for the session features that run on them
+const SESSION_EVENTS: [EventType; 2] = [EventType::SessionStart, EventType::Stop];
+
+/// Events whose `matcher` is tested against the tool name
+const TOOL_EVENTS: [EventType; 4] = [
+    EventType::PreToolUse,
+    EventType::PostToolUse,
+    EventType::PermissionRequest,
+    EventType::PostToolUseFailure,
+];
+
+/// First line of the OpenCode plugin; uninstall only removes files that
+/// start with it
+const OPENCODE_PLUGIN_MARKER: &str = "// Generated by `cch install --agent opencode`";
+
+/// OpenCode plugin running tool calls and idle sessions through CCH;
+/// `__CCH__` is replaced with the quoted binary path
+const OPENCODE_PLUGIN: &str = r#"// Generated by `cch install --agent opencode`; remove with `cch uninstall --agent opencode`
+const CCH = __CCH__;
+
+export const CchPlugin = async ({ directory }) => {
+  const cch = async (payload) => {
+    const proc = Bun.spawn([CCH], {
+      cwd: directory,
+      stdin: "pipe",
+      stdout: "pipe",
+      stderr: "pipe",
+    });
+    proc.stdin.write(JSON.stringify({ agent: "opencode", cwd: directory, ...payload }));
+    proc.stdin.end();
+    const [stdout, stderr, code] = await Promise.all([
+      new Response(proc.stdout).text(),
+      new Response(proc.stderr).text(),
+      proc.exited,
+    ]);
+    if (code === 2) throw new Error(stderr.trim() || "Blocked by CCH policy");
+    return stdout.trim() ? JSON.parse(stdout) : {};
+  };
+
+  return {
+    "tool.execute.before": async (input, output) => {
+      const response = await cch({
+        event: "tool.execute.before",
+        tool: input.tool,
+        session_id: input.sessionID,
+        args: output.args,
+      });
+      const specific = response.hookSpecificOutput ?? {};
+      if (specific.permissionDecision === "deny" || specific.permissionDecision === "ask") {
+        throw new Error(specific.permissionDecisionReason ?? "Blocked by CCH policy");
+      }
+      if (specific.updatedInput) Object.assign(output.args, specific.updatedInput);
+    },
+    "tool.execute.after": async (input, output) => {
+      await cch({
+        event: "tool.execute.after",
+        tool: input.tool,
+        session_id: input.sessionID,
+        output: output.output,
+      });
+    },
+    event: async ({ event }) => {
+      if (event.type === "session.idle") {
+        await cch({ event: "session.idle", session_id: event.properties?.sessionID });
+      }
+    },
+  };
+};
+"#;
+
+/// Coding agent to install CCH for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Agent {
+    Claude,
+    Cursor,
+    OpenCode,
+}
+
+impl Agent {
+    const ALL: [Agent; 3] = [Agent::Claude, Agent::Cursor, Agent::OpenCode];
+
+    /// The agent called `name` (default: Claude Code)
+    fn parse(name: Option<&str>) -> Result<Self> {
+        let Some(name) = name else {
+            return Ok(Agent::Claude);
+        };
+        Self::ALL
+            .into_iter()
+            .find(|agent| agent.name().eq_ignore_ascii_case(name.trim()))
+            .with_context(|| {
+                let known: Vec<&str> = Self::ALL.iter().map(|a| a.name()).collect();
+                format!("Unknown agent '{}' (agents: {})", name, known.join(", "))
+            })
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Agent::Claude => "claude",
+            Agent::Cursor => "cursor",
+            Agent::OpenCode => "opencode",
+        }
+    }
+
+    /// The file CCH is registered in for `scope`
+    fn config_path(self, scope: Scope) -> Result<PathBuf> {
+        let relative = match self {
+            Agent::Claude => return get_settings_path(scope),
+            Agent::Cursor => ".cursor/hooks.json",
+            Agent::OpenCode => ".opencode/plugin/cch.js",
+        };
+        match scope {
+            Scope::Project => Ok(PathBuf::from(relative)),
+            Scope::Global => {
+                let home = dirs::home_dir().context("Could not determine home directory")?;
+                Ok(match self {
+                    Agent::OpenCode => home.join(".config/opencode/plugin/cch.js"),
+                    _ => home.join(relative),
+                })
+            }
+        }
+    }
+
+    /// Event names `--events` accepts
+    fn events(self) -> Vec<String> {
+        match self {
+            Agent::Claude => EventType::ALL.iter().map(ToString::to_string).collect(),
+            Agent::Cursor => CURSOR_EVENTS.iter().map(ToString::to_string).collect(),
+            Agent::OpenCode => Vec::new(),
+        }
+    }
+}
 
 /// Claude Code settings structure (partial)
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -17,7 +173,7 @@ struct ClaudeSettings {
     other: HashMap<String, serde_json::Value>,
 }
 
-/// Hooks configuration in Claude Code settings.
+/// Hooks configuration in Claude Code settings, by event name.
 ///
 /// Claude Code expects PascalCase event keys with a nested matcher/hooks structure:
 /// ```json
@@ -29,27 +185,24 @@ struct ClaudeSettings {
 ///   }
 /// }
 /// ```
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
-struct HooksConfig {
-    #[serde(rename = "PreToolUse", default, skip_serializing_if = "Vec::is_empty")]
-    pre_tool_use: Vec<MatcherEntry>,
-    #[serde(rename = "PostToolUse", default, skip_serializing_if = "Vec::is_empty")]
-    post_tool_use: Vec<MatcherEntry>,
-    #[serde(rename = "Stop", default, skip_serializing_if = "Vec::is_empty")]
-    stop: Vec<MatcherEntry>,
-    #[serde(
-        rename = "SessionStart",
-        default,
-        skip_serializing_if = "Vec::is_empty"
-    )]
-    session_start: Vec<MatcherEntry>,
-}
+type HooksConfig = BTreeMap<String, Vec<MatcherEntry>>;
 
 /// A matcher entry groups a glob pattern with its hook commands
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct MatcherEntry {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     matcher: String,
+    #[serde(default)]
     hooks: Vec<HookCommand>,
+    #[serde(flatten)]
+    other: HashMap<String, serde_json::Value>,
+}
+
+impl MatcherEntry {
+    /// Whether this entry runs CCH
+    fn runs_cch(&self) -> bool {
+        self.hooks.iter().any(|h| h.command.contains("cch"))
+    }
 }
 
 /// Individual hook command within a matcher entry
@@ -57,9 +210,12 @@ struct MatcherEntry {
 struct HookCommand {
     #[serde(rename = "type")]
     hook_type: String,
+    #[serde(default)]
     command: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     timeout: Option<u32>,
+    #[serde(flatten)]
+    other: HashMap<String, serde_json::Value>,
 }
 
 /// Installation scope
@@ -71,15 +227,34 @@ pub enum Scope {
     Global,
 }
 
-/// Run the install command
-pub async fn run(scope: Scope, binary_path: Option<String>) -> Result<()> {
+/// Run the install command for `agent` (default: Claude Code),
+/// registering `events` for `tools` (each comma-separated) or the
+/// configured ones
+pub async fn run(
+    scope: Scope,
+    binary_path: Option<String>,
+    events: Option<String>,
+    tools: Option<String>,
+    agent: Option<String>,
+) -> Result<()> {
+    let agent = Agent::parse(agent.as_deref())?;
+    check_agent_options(agent, events.as_deref(), tools.as_deref())?;
+    let events = events
+        .map(|events| parse_events(agent, events.split(',')))
+        .transpose()?;
+    let tools = tools
+        .map(|tools| parse_tools(tools.split(',')))
+        .transpose()?;
     let cch_path = resolve_binary_path(binary_path)?;
-    let settings_path = get_settings_path(scope)?;
+    let settings_path = agent.config_path(scope)?;
 
     println!("Installing CCH hook...\n");
     println!("  Binary: {}", cch_path.display());
     println!("  Settings: {}", settings_path.display());
     println!("  Scope: {}", scope_name(scope));
+    if agent != Agent::Claude {
+        println!("  Agent: {}", agent.name());
+    }
     println!();
 
     // Verify hooks.yaml exists for project scope
@@ -93,53 +268,77 @@ pub async fn run(scope: Scope, binary_path: Option<String>) -> Result<()> {
         }
     }
 
+    match agent {
+        Agent::Claude => {}
+        Agent::Cursor => return install_cursor(&settings_path, &cch_path, events).await,
+        Agent::OpenCode => return install_opencode(&settings_path, &cch_path).await,
+    }
+
+    let (events, tools, from_rules) = install_targets(scope, events, tools)?;
+
     // Load or create settings
     let mut settings = load_settings(&settings_path)?;
 
     // Build hook command
     let hook_command = format!("{}", cch_path.display());
 
-    // Create the matcher entry with nested hook command
-    let matcher_entry = MatcherEntry {
-        matcher: "*".to_string(),
-        hooks: vec![HookCommand {
-            hook_type: "command".to_string(),
-            command: hook_command.clone(),
-            timeout: Some(5),
-        }],
-    };
-
     // Get or create hooks config
     let hooks = settings.hooks.get_or_insert_with(HooksConfig::default);
 
-    // Check if already installed (look inside nested hooks[].command)
-    let already_installed = hooks
-        .pre_tool_use
-        .iter()
-        .any(|m| m.hooks.iter().any(|h| h.command.contains("cch")));
+    // Add CCH to each chosen event it isn't registered for yet (look inside
+    // nested hooks[].command), and bring existing entries to the chosen tools
+    let mut added = Vec::new();
+    let mut installed = None;
+    for event in &events {
+        let matcher = event_matcher(event, &tools);
+        let entries = hooks.entry(event.clone()).or_default();
+        if let Some(existing) = entries.iter_mut().find(|m| m.runs_cch()) {
+            if existing.matcher != matcher {
+                existing.matcher.clone_from(&matcher);
+                added.push((event.as_str(), matcher));
+            } else if let Some(hook) = existing.hooks.iter().find(|h| h.command.contains("cch")) {
+                installed.get_or_insert_with(|| hook.command.clone());
+            }
+        } else {
+            entries.push(MatcherEntry {
+                matcher: matcher.clone(),
+                hooks: vec![HookCommand {
+                    hook_type: "command".to_string(),
+                    command: hook_command.clone(),
+                    timeout: Some(HOOK_TIMEOUT_SECS),
+                    other: HashMap::new(),
+                }],
+                other: HashMap::new(),
+            });
+            added.push((event.as_str(), matcher));
+        }
+    }
 
-    if already_installed {
-        println!("✓ CCH is already installed");
+    if added.is_empty() {
+        println!("✓ CCH is already installed for: {}", events.join(", "));
         println!("  To reinstall, first run 'cch uninstall'");
-        return Ok(());
+        println!();
+        return report_self_test(agent, installed.as_deref().unwrap_or(&hook_command)).await;
     }
 
-    // Add CCH to all hook events
-    hooks.pre_tool_use.push(matcher_entry.clone());
-    hooks.post_tool_use.push(matcher_entry.clone());
-    hooks.stop.push(matcher_entry.clone());
-    hooks.session_start.push(matcher_entry);
-
     // Save settings
     save_settings(&settings_path, &settings)?;
+    report_self_test(agent, &hook_command).await?;
 
     println!("✓ CCH installed successfully!\n");
     println!("Hook registered for events:");
-    println!("  • PreToolUse");
-    println!("  • PostToolUse");
-    println!("  • Stop");
-    println!("  • SessionStart");
+    for (event, matcher) in added {
+        match matcher.as_str() {
+            "*" => println!("  • {}", event),
+            tools => println!("  • {} (tools: {})", event, tools),
+        }
+    }
     println!();
+    if from_rules {
+        println!("Registered only for what the enabled rules use; run 'cch install' again");
+        println!("after adding rules for other events or tools.");
+        println!();
+    }
     println!("To verify installation:");
     println!("  cch validate");
     println!();
@@ -149,6 +348,452 @@ pub async fn run(scope: Scope, binary_path: Option<String>) -> Result<()> {
     Ok(())
 }
 
+/// Reject the options `agent` has no use for
+fn check_agent_options(agent: Agent, events: Option<&str>, tools: Option<&str>) -> Result<()> {
+    if agent != Agent::Claude && tools.is_some() {
+        anyhow::bail!("--tools is only supported for Claude Code");
+    }
+    if agent == Agent::OpenCode && events.is_some() {
+        anyhow::bail!("--events is not supported for OpenCode; its plugin handles every event");
+    }
+    Ok(())
+}
+
+/// `agent`'s canonical event names for `names`, rejecting unknown ones
+fn parse_events<'a>(agent: Agent, names: impl IntoIterator<Item = &'a str>) -> Result<Vec<String>> {
+    let known = agent.events();
+    let mut events = Vec::new();
+    for name in names.into_iter().map(str::trim).filter(|n| !n.is_empty()) {
+        let event = known
+            .iter()
+            .find(|event| event.eq_ignore_ascii_case(name))
+            .cloned()
+            .with_context(|| format!("Unknown event '{}' (events: {})", name, known.join(", ")))?;
+        if !events.contains(&event) {
+            events.push(event);
+        }
+    }
+    if events.is_empty() {
+        anyhow::bail!("No events given");
+    }
+    Ok(events)
+}
+
+/// Tool names for `names`, without blanks and repeats
+fn parse_tools<'a>(names: impl IntoIterator<Item = &'a str>) -> Result<Vec<String>> {
+    let mut tools: Vec<String> = Vec::new();
+    for name in names.into_iter().map(str::trim).filter(|n| !n.is_empty()) {
+        if name.contains('|') {
+            anyhow::bail!("Invalid tool '{}': list tools separately", name);
+        }
+        if !tools.iter().any(|tool| tool == name) {
+            tools.push(name.to_string());
+        }
+    }
+    Ok(tools)
+}
+
+/// The `matcher` for CCH's entry under `event`: the tools for tool events,
+/// everything otherwise
+fn event_matcher(event: &str, tools: &[String]) -> String {
+    let tool_event = TOOL_EVENTS.iter().any(|e| e.to_string() == event);
+    if tool_event && !tools.is_empty() {
+        tools.join("|")
+    } else {
+        "*".to_string()
+    }
+}
+
+/// The Claude Code events and tools to register: the given ones, else
+/// `settings.install`'s, else the active rules' footprint (which is
+/// reported by the flag)
+///
+/// A global install is never narrowed by the current project's config:
+/// what isn't given is every event and every tool.
+fn install_targets(
+    scope: Scope,
+    events: Option<Vec<String>>,
+    tools: Option<Vec<String>>,
+) -> Result<(Vec<String>, Vec<String>, bool)> {
+    if matches!(scope, Scope::Global) {
+        return Ok((
+            events.unwrap_or_else(|| Agent::Claude.events()),
+            tools.unwrap_or_default(),
+            false,
+        ));
+    }
+    if let (Some(events), Some(tools)) = (&events, &tools) {
+        return Ok((events.clone(), tools.clone(), false));
+    }
+    let config = Config::load(None).context("Failed to load configuration")?;
+    let configured = config.settings.install.clone().unwrap_or_default();
+    let (rule_events, rule_tools) = rule_footprint(&config);
+    let mut from_rules = false;
+    let events = match events {
+        Some(events) => events,
+        None if configured.events.is_empty() => {
+            from_rules = true;
+            rule_events
+        }
+        None => parse_events(Agent::Claude, configured.events.iter().map(String::as_str))
+            .context("Invalid settings.install.events")?,
+    };
+    let tools = match tools {
+        Some(tools) => tools,
+        None if configured.tools.is_empty() => {
+            from_rules = true;
+            rule_tools
+        }
+        None => parse_tools(configured.tools.iter().map(String::as_str))?,
+    };
+    Ok((events, tools, from_rules))
+}
+
+/// The events and tools the enabled rules and settings of `config` can
+/// fire on
+///
+/// Each rule's matcher tree is walked (see [`matcher_footprint`]); anything
+/// a rule doesn't constrain counts as every event or every tool. Features
+/// that see every event (`event_archive`, `post_processor`) keep everything
+/// registered, `change_journal` needs PreToolUse and PostToolUse for every
+/// tool, and Stop and SessionStart stay registered for the session features
+/// that run on them.
+fn rule_footprint(config: &Config) -> (Vec<String>, Vec<String>) {
+    let settings = &config.settings;
+    let mut footprint = config
+        .enabled_rules()
+        .into_iter()
+        .map(|rule| matcher_footprint(&rule.matchers))
+        .fold(Footprint::NONE, Footprint::or);
+    if settings.event_archive.is_some() || settings.post_processor.is_some() {
+        footprint = Footprint::ANY;
+    }
+    if settings.change_journal {
+        footprint = footprint.or(Footprint {
+            events: Some(event_names(&[
+                EventType::PreToolUse,
+                EventType::PostToolUse,
+            ])),
+            tools: None,
+        });
+    }
+    footprint = footprint.or(Footprint {
+        events: Some(event_names(&SESSION_EVENTS)),
+        tools: Some(BTreeSet::new()),
+    });
+
+    // Known events only, in the order Claude Code documents them
+    let events = EventType::ALL
+        .iter()
+        .map(ToString::to_string)
+        .filter(|event| footprint.events.as_ref().is_none_or(|e| e.contains(event)))
+        .collect();
+    let tools = match footprint.tools {
+        Some(tools) if !tools.iter().any(|tool| tool.contains('|')) => tools.into_iter().collect(),
+        _ => Vec::new(),
+    };
+    (events, tools)
+}
+
+/// Where a rule or matcher block can match; `None` is every event or tool
+#[derive(Debug, Clone, PartialEq)]
+struct Footprint {
+    events: Option<BTreeSet<String>>,
+    tools: Option<BTreeSet<String>>,
+}
+
+impl Footprint {
+    const ANY: Footprint = Footprint {
+        events: None,
+        tools: None,
+    };
+
+    const NONE: Footprint = Footprint {
+        events: Some(BTreeSet::new()),
+        tools: Some(BTreeSet::new()),
+    };
+
+    /// Where both can match
+    fn and(self, other: Footprint) -> Footprint {
+        let both = |a: Option<BTreeSet<String>>, b: Option<BTreeSet<String>>| match (a, b) {
+            (Some(a), Some(b)) => Some(a.intersection(&b).cloned().collect()),
+            (a, b) => a.or(b),
+        };
+        Footprint {
+            events: both(self.events, other.events),
+            tools: both(self.tools, other.tools),
+        }
+    }
+
+    /// Where either can match
+    fn or(self, other: Footprint) -> Footprint {
+        let either = |a: Option<BTreeSet<String>>, b: Option<BTreeSet<String>>| match (a, b) {
+            (Some(mut a), Some(b)) => {
+                a.extend(b);
+                Some(a)
+            }
+            _ => None,
+        };
+        Footprint {
+            events: either(self.events, other.events),
+            tools: either(self.tools, other.tools),
+        }
+    }
+}
+
+/// Where `matchers` can match
+///
+/// `operations` narrows the events, `tools` the tools (and events to tool
+/// events), and `prompt_match` the events to UserPromptSubmit. `any` blocks
+/// can match where one of theirs can and `all` blocks where all of theirs
+/// can; a `not` block can match anywhere its own block doesn't, and every
+/// other matcher may match any event, so neither narrows anything.
+fn matcher_footprint(matchers: &Matchers) -> Footprint {
+    let mut footprint = Footprint::ANY;
+    if let Some(operations) = &matchers.operations {
+        footprint.events = Some(operations.iter().cloned().collect());
+    }
+    if let Some(tools) = &matchers.tools {
+        footprint = footprint.and(Footprint {
+            events: Some(event_names(&TOOL_EVENTS)),
+            tools: Some(tools.iter().cloned().collect()),
+        });
+    }
+    if matchers.prompt_match.is_some() {
+        footprint = footprint.and(Footprint {
+            events: Some(event_names(&[EventType::UserPromptSubmit])),
+            tools: None,
+        });
+    }
+    if let Some(any) = &matchers.any {
+        footprint = footprint.and(
+            any.iter()
+                .map(matcher_footprint)
+                .fold(Footprint::NONE, Footprint::or),
+        );
+    }
+    for block in matchers.all.iter().flatten() {
+        footprint = footprint.and(matcher_footprint(block));
+    }
+    // Tools only matter on tool events
+    let tool_events = event_names(&TOOL_EVENTS);
+    if footprint
+        .events
+        .as_ref()
+        .is_some_and(|events| events.is_disjoint(&tool_events))
+    {
+        footprint.tools = Some(BTreeSet::new());
+    }
+    footprint
+}
+
+fn event_names(events: &[EventType]) -> BTreeSet<String> {
+    events.iter().map(ToString::to_string).collect()
+}
+
+/// Register CCH in Cursor's hooks.json at `path` for `events` (default:
+/// every event CCH answers), keeping the other hooks
+async fn install_cursor(path: &Path, cch_path: &Path, events: Option<Vec<String>>) -> Result<()> {
+    let events = events.unwrap_or_else(|| Agent::Cursor.events());
+    let command = cch_path.display().to_string();
+    let mut config = load_json(path)?;
+    let object = config
+        .as_object_mut()
+        .with_context(|| format!("{} is not a JSON object", path.display()))?;
+    object
+        .entry("version")
+        .or_insert_with(|| serde_json::json!(1));
+    let hooks = object
+        .entry("hooks")
+        .or_insert_with(|| serde_json::json!({}))
+        .as_object_mut()
+        .with_context(|| format!("`hooks` in {} is not an object", path.display()))?;
+
+    let mut added = Vec::new();
+    for event in &events {
+        let entries = hooks
+            .entry(event.clone())
+            .or_insert_with(|| serde_json::json!([]))
+            .as_array_mut()
+            .with_context(|| format!("`hooks.{}` in {} is not a list", event, path.display()))?;
+        if !entries.iter().any(cursor_entry_runs_cch) {
+            entries.push(serde_json::json!({ "command": command }));
+            added.push(event.as_str());
+        }
+    }
+
+    if added.is_empty() {
+        println!("✓ CCH is already installed for: {}", events.join(", "));
+        println!();
+        return report_self_test(Agent::Cursor, &command).await;
+    }
+    save_json(path, &config)?;
+    report_self_test(Agent::Cursor, &command).await?;
+
+    println!("✓ CCH installed successfully!\n");
+    println!("Hook registered for Cursor events:");
+    for event in added {
+        println!("  • {}", event);
+    }
+    println!();
+    println!("To uninstall:");
+    println!("  cch uninstall --agent cursor");
+    Ok(())
+}
+
+/// Whether an entry of Cursor's hooks.json runs CCH
+fn cursor_entry_runs_cch(entry: &serde_json::Value) -> bool {
+    entry
+        .get("command")
+        .and_then(serde_json::Value::as_str)
+        .is_some_and(|command| command.contains("cch"))
+}
+
+/// Write the OpenCode plugin to `path`
+async fn install_opencode(path: &Path, cch_path: &Path) -> Result<()> {
+    let command = cch_path.display().to_string();
+    let plugin = OPENCODE_PLUGIN.replace("__CCH__", &serde_json::to_string(&command)?);
+    if let Ok(existing) = fs::read_to_string(path) {
+        if existing == plugin {
+            println!("✓ CCH is already installed for OpenCode\n");
+            return report_self_test(Agent::OpenCode, &command).await;
+        }
+        if !existing.starts_with(OPENCODE_PLUGIN_MARKER) {
+            anyhow::bail!(
+                "{} exists and was not written by CCH; move it away first",
+                path.display()
+            );
+        }
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create plugin directory")?;
+    }
+    fs::write(path, plugin).context("Failed to write OpenCode plugin")?;
+    report_self_test(Agent::OpenCode, &command).await?;
+
+    println!("✓ CCH installed successfully!\n");
+    println!("OpenCode plugin checks tool calls before and after they run, and idle sessions");
+    println!();
+    println!("To uninstall:");
+    println!("  cch uninstall --agent opencode");
+    Ok(())
+}
+
+/// Run the self-test on `command`, failing the install when it breaks
+async fn report_self_test(agent: Agent, command: &str) -> Result<()> {
+    match self_test(agent, command).await {
+        Ok(outcome) => {
+            println!("✓ Hook self-test passed ({})\n", outcome);
+            Ok(())
+        }
+        Err(e) => {
+            println!("✗ Hook self-test failed: {:#}\n", e);
+            anyhow::bail!(
+                "The registered hook command does not answer hook events: {}\n  \
+                Fix the path (cch uninstall, then cch install --binary /path/to/cch)",
+                command
+            )
+        }
+    }
+}
+
+/// A synthetic read of the project's settings in `agent`'s payload format
+fn self_test_event(agent: Agent, project_dir: &Path) -> serde_json::Value {
+    let file = project_dir.join(".claude/settings.json");
+    match agent {
+        Agent::Claude => serde_json::json!({
+            "hook_event_name": "PreToolUse",
+            "session_id": SELF_TEST_SESSION,
+            "tool_name": "Read",
+            "tool_input": { "file_path": file },
+            "cwd": project_dir,
+        }),
+        Agent::Cursor => serde_json::json!({
+            "hook_event_name": "beforeReadFile",
+            "conversation_id": SELF_TEST_SESSION,
+            "file_path": file,
+            "workspace_roots": [project_dir],
+        }),
+        Agent::OpenCode => serde_json::json!({
+            "agent": "opencode",
+            "event": "tool.execute.before",
+            "session_id": SELF_TEST_SESSION,
+            "tool": "read",
+            "args": { "filePath": file },
+            "cwd": project_dir,
+        }),
+    }
+}
+
+/// Send a synthetic file read from `agent` through `command` the way Claude
+/// Code runs hooks (through the shell, from the project directory with
+/// `CLAUDE_PROJECT_DIR` set) and check that a valid response comes back;
+/// returns what the hook decided
+async fn self_test(agent: Agent, command: &str) -> Result<&'static str> {
+    let project_dir = std::env::current_dir().context("Failed to get current directory")?;
+    let event = self_test_event(agent, &project_dir);
+
+    let mut shell = if cfg!(windows) {
+        let mut shell = tokio::process::Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = tokio::process::Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    let mut child = shell
+        .arg(command)
+        .current_dir(&project_dir)
+        .env("CLAUDE_PROJECT_DIR", &project_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .context("Failed to start a shell for the hook command")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A command that exits without reading stdin is caught by its output
+        let _ = stdin.write_all(event.to_string().as_bytes()).await;
+    }
+
+    let output = tokio::time::timeout(
+        std::time::Duration::from_secs(u64::from(HOOK_TIMEOUT_SECS)),
+        child.wait_with_output(),
+    )
+    .await
+    .map_err(|_| anyhow::anyhow!("no response within {}s", HOOK_TIMEOUT_SECS))??;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    match output.status.code() {
+        // Cursor reads its own response format...
+        Some(0) if agent == Agent::Cursor => {
+            let response: serde_json::Value = serde_json::from_str(stdout.trim())
+                .with_context(|| format!("exit 0 without a hook response: {:?}", stdout.trim()))?;
+            match response
+                .get("permission")
+                .and_then(serde_json::Value::as_str)
+            {
+                Some("allow") => Ok("allowed"),
+                Some(_) => Ok("blocked by policy"),
+                None => anyhow::bail!("response without a permission: {}", response),
+            }
+        }
+        // ...Claude Code a JSON response on stdout...
+        Some(0) => serde_json::from_str::<Response>(stdout.trim())
+            .map(|_| "allowed")
+            .with_context(|| format!("exit 0 without a hook response: {:?}", stdout.trim())),
+        // ...or takes exit code 2 as a block with the reason on stderr
+        Some(2) if !stderr.trim().is_empty() => Ok("blocked by policy"),
+        code => anyhow::bail!(
+            "exited with {}: {}",
+            code.map_or_else(|| "a signal".to_string(), |code| format!("code {}", code)),
+            stderr.trim()
+        ),
+    }
+}
+
 /// Resolve the CCH binary path
 fn resolve_binary_path(explicit_path: Option<String>) -> Result<PathBuf> {
     if let Some(path) = explicit_path {
@@ -190,7 +835,7 @@ fn resolve_binary_path(explicit_path: Option<String>) -> Result<PathBuf> {
 }
 
 /// Get the settings file path based on scope
-fn get_settings_path(scope: Scope) -> Result<PathBuf> {
+pub(crate) fn get_settings_path(scope: Scope) -> Result<PathBuf> {
     match scope {
         Scope::Project => Ok(PathBuf::from(".claude/settings.json")),
         Scope::Global => {
@@ -201,7 +846,7 @@ fn get_settings_path(scope: Scope) -> Result<PathBuf> {
 }
 
 /// Get scope display name
-fn scope_name(scope: Scope) -> &'static str {
+pub(crate) fn scope_name(scope: Scope) -> &'static str {
     match scope {
         Scope::Project => "project",
         Scope::Global => "global",
@@ -235,11 +880,40 @@ fn save_settings(path: &Path, settings: &ClaudeSettings) -> Result<()> {
     Ok(())
 }
 
-/// Uninstall CCH from Claude Code settings
-pub async fn uninstall(scope: Scope) -> Result<()> {
-    let settings_path = get_settings_path(scope)?;
+/// Load a JSON file, or an empty object if it doesn't exist
+fn load_json(path: &Path) -> Result<serde_json::Value> {
+    if !path.exists() {
+        return Ok(serde_json::json!({}));
+    }
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+fn save_json(path: &Path, value: &serde_json::Value) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create settings directory")?;
+    }
+    fs::write(path, serde_json::to_string_pretty(value)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Uninstall CCH from `agent`'s settings (default: Claude Code), from
+/// `events` (comma-separated) only or else from every event
+pub async fn uninstall(scope: Scope, events: Option<String>, agent: Option<String>) -> Result<()> {
+    let agent = Agent::parse(agent.as_deref())?;
+    check_agent_options(agent, events.as_deref(), None)?;
+    let settings_path = agent.config_path(scope)?;
+    let events = events
+        .map(|events| parse_events(agent, events.split(',')))
+        .transpose()?;
 
     println!("Uninstalling CCH...\n");
+    match agent {
+        Agent::Claude => {}
+        Agent::Cursor => return uninstall_cursor(&settings_path, events.as_deref()),
+        Agent::OpenCode => return uninstall_opencode(&settings_path),
+    }
 
     if !settings_path.exists() {
         println!("No settings file found at: {}", settings_path.display());
@@ -248,50 +922,259 @@ pub async fn uninstall(scope: Scope) -> Result<()> {
 
     let mut settings = load_settings(&settings_path)?;
 
-    if let Some(hooks) = &mut settings.hooks {
-        let before = hooks.pre_tool_use.len()
-            + hooks.post_tool_use.len()
-            + hooks.stop.len()
-            + hooks.session_start.len();
-
-        hooks
-            .pre_tool_use
-            .retain(|m| !m.hooks.iter().any(|h| h.command.contains("cch")));
-        hooks
-            .post_tool_use
-            .retain(|m| !m.hooks.iter().any(|h| h.command.contains("cch")));
-        hooks
-            .stop
-            .retain(|m| !m.hooks.iter().any(|h| h.command.contains("cch")));
-        hooks
-            .session_start
-            .retain(|m| !m.hooks.iter().any(|h| h.command.contains("cch")));
-
-        let after = hooks.pre_tool_use.len()
-            + hooks.post_tool_use.len()
-            + hooks.stop.len()
-            + hooks.session_start.len();
-
-        if before == after {
-            println!("CCH was not installed");
-            return Ok(());
-        }
+    let Some(hooks) = &mut settings.hooks else {
+        println!("CCH was not installed");
+        return Ok(());
+    };
 
-        // Clean up empty hooks config
-        if hooks.pre_tool_use.is_empty()
-            && hooks.post_tool_use.is_empty()
-            && hooks.stop.is_empty()
-            && hooks.session_start.is_empty()
+    let mut removed = Vec::new();
+    for (event, entries) in hooks.iter_mut() {
+        if events
+            .as_ref()
+            .is_some_and(|events| !events.contains(event))
         {
-            settings.hooks = None;
+            continue;
         }
-    } else {
+        let before = entries.len();
+        entries.retain(|m| !m.runs_cch());
+        if entries.len() != before {
+            removed.push(event.clone());
+        }
+    }
+
+    if removed.is_empty() {
         println!("CCH was not installed");
         return Ok(());
     }
 
+    // Clean up empty events and hooks config
+    hooks.retain(|_, entries| !entries.is_empty());
+    if hooks.is_empty() {
+        settings.hooks = None;
+    }
+
     save_settings(&settings_path, &settings)?;
     println!("✓ CCH uninstalled successfully");
+    if events.is_some() {
+        println!("  Removed from: {}", removed.join(", "));
+    }
 
     Ok(())
 }
+
+/// Remove CCH's entries from Cursor's hooks.json, for `events` only if given
+fn uninstall_cursor(path: &Path, events: Option<&[String]>) -> Result<()> {
+    let mut config = load_json(path)?;
+    let Some(hooks) = config
+        .get_mut("hooks")
+        .and_then(serde_json::Value::as_object_mut)
+    else {
+        println!("CCH was not installed");
+        return Ok(());
+    };
+
+    let mut removed = Vec::new();
+    for (event, entries) in hooks.iter_mut() {
+        if events.is_some_and(|events| !events.contains(event)) {
+            continue;
+        }
+        let Some(entries) = entries.as_array_mut() else {
+            continue;
+        };
+        let before = entries.len();
+        entries.retain(|entry| !cursor_entry_runs_cch(entry));
+        if entries.len() != before {
+            removed.push(event.clone());
+        }
+    }
+    if removed.is_empty() {
+        println!("CCH was not installed");
+        return Ok(());
+    }
+    hooks.retain(|_, entries| entries.as_array().is_none_or(|e| !e.is_empty()));
+
+    save_json(path, &config)?;
+    println!("✓ CCH uninstalled successfully");
+    if events.is_some() {
+        println!("  Removed from: {}", removed.join(", "));
+    }
+    Ok(())
+}
+
+/// Delete the OpenCode plugin, if CCH wrote it
+fn uninstall_opencode(path: &Path) -> Result<()> {
+    match fs::read_to_string(path) {
+        Ok(content) if content.starts_with(OPENCODE_PLUGIN_MARKER) => {
+            fs::remove_file(path).context("Failed to remove OpenCode plugin")?;
+            println!("✓ CCH uninstalled successfully");
+        }
+        Ok(_) => anyhow::bail!("{} was not written by CCH; leaving it", path.display()),
+        Err(_) => println!("CCH was not installed"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_footprint_narrows_to_rule_events_and_tools() {
+        let config = |rules: &str| -> Config {
+            serde_yaml::from_str(&format!("version: \"1.0\"\nrules:\n{}", rules)).unwrap()
+        };
+
+        let git_only = config(
+            r#"
+  - name: no-force-push
+    matchers: { tools: [Bash], command_match: "git push --force" }
+    actions: { block: true }
+  - name: no-secrets
+    matchers: { tools: [Write, Bash], operations: [PreToolUse] }
+    actions: { block: true }
+  - name: disabled
+    matchers: { operations: [PermissionRequest] }
+    actions: { block: true }
+    metadata: { enabled: false }
+"#,
+        );
+        assert_eq!(
+            rule_footprint(&git_only),
+            (
+                vec![
+                    "PreToolUse".to_string(),
+                    "PostToolUse".to_string(),
+                    "PermissionRequest".to_string(),
+                    "SessionStart".to_string(),
+                    "Stop".to_string(),
+                    "PostToolUseFailure".to_string()
+                ],
+                vec!["Bash".to_string(), "Write".to_string()]
+            )
+        );
+
+        let prompts_and_any_tool = config(
+            r#"
+  - name: prompts
+    matchers: { operations: [UserPromptSubmit], prompt_match: "deploy" }
+    actions: { inject_inline: "Deploys need approval" }
+  - name: audit-failures
+    matchers: { operations: [PostToolUseFailure] }
+    actions: { inject_inline: "Check the failure" }
+  - name: bash
+    matchers: { tools: [Bash] }
+    actions: { block: true }
+"#,
+        );
+        let (events, tools) = rule_footprint(&prompts_and_any_tool);
+        assert_eq!(
+            events,
+            [
+                "PreToolUse",
+                "PostToolUse",
+                "PermissionRequest",
+                "UserPromptSubmit",
+                "SessionStart",
+                "Stop",
+                "PostToolUseFailure"
+            ]
+        );
+        assert!(tools.is_empty(), "a rule for every tool keeps `*`");
+
+        assert_eq!(rule_footprint(&config(" []")).0, ["SessionStart", "Stop"]);
+    }
+
+    #[test]
+    fn test_rule_footprint_walks_matcher_tree() {
+        let footprint = |yaml: &str| {
+            let config: Config =
+                serde_yaml::from_str(&format!("version: \"1.0\"\n{}", yaml)).unwrap();
+            rule_footprint(&config)
+        };
+        let every_event: Vec<String> = EventType::ALL.iter().map(ToString::to_string).collect();
+
+        let (events, tools) = footprint(
+            r#"
+rules:
+  - name: nested
+    matchers:
+      any:
+        - { operations: [PreToolUse], tools: [Bash] }
+        - all: [{ tools: [Write] }, { operations: [PostToolUse] }]
+    actions: { block: true }
+  - name: prompts
+    matchers: { prompt_match: "deploy" }
+    actions: { inject_inline: "Deploys need approval" }
+"#,
+        );
+        assert_eq!(
+            events,
+            [
+                "PreToolUse",
+                "PostToolUse",
+                "UserPromptSubmit",
+                "SessionStart",
+                "Stop"
+            ]
+        );
+        assert_eq!(tools, ["Bash", "Write"]);
+
+        let negated = footprint(
+            r"
+rules:
+  - name: not-reads
+    matchers: { not: { tools: [Read] } }
+    actions: { block: true }
+",
+        );
+        assert_eq!(negated, (every_event.clone(), Vec::new()));
+
+        let unconstrained = footprint(
+            r#"
+rules:
+  - name: commands
+    matchers: { command_match: "rm -rf" }
+    actions: { block: true }
+"#,
+        );
+        assert_eq!(unconstrained, (every_event.clone(), Vec::new()));
+
+        let archived = footprint(
+            r"
+settings: { event_archive: { dir: /tmp/archive } }
+rules:
+  - name: bash
+    matchers: { tools: [Bash], operations: [PreToolUse] }
+    actions: { block: true }
+",
+        );
+        assert_eq!(archived, (every_event, Vec::new()));
+
+        let (events, tools) = footprint(
+            r"
+settings: { change_journal: true }
+rules:
+  - name: bash
+    matchers: { tools: [Bash], operations: [PreToolUse] }
+    actions: { block: true }
+",
+        );
+        assert_eq!(
+            events,
+            ["PreToolUse", "PostToolUse", "SessionStart", "Stop"]
+        );
+        assert!(tools.is_empty(), "the journal needs every tool");
+    }
+
+    #[test]
+    fn test_global_install_is_not_narrowed() {
+        let (events, tools, from_rules) = install_targets(Scope::Global, None, None).unwrap();
+        assert_eq!(events, Agent::Claude.events());
+        assert!(tools.is_empty());
+        assert!(!from_rules);
+
+        let bash = Some(vec!["Bash".to_string()]);
+        let (_, tools, _) = install_targets(Scope::Global, None, bash).unwrap();
+        assert_eq!(tools, ["Bash"]);
+    }
+}
@@ -1,8 +1,11 @@
 use anyhow::Result;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::BTreeMap;
 
-use crate::logging::{LogQuery, QueryFilters};
-use crate::models::{Decision, Outcome, PolicyMode};
+use super::coverage::parse_time;
+use crate::logging::latency::{self, Histogram};
+use crate::logging::{LogQuery, Logger, QueryFilters};
+use crate::models::{Decision, LogEntry, Outcome, PolicyMode};
 
 /// Query and display logs with optional filtering
 ///
@@ -11,29 +14,89 @@ use crate::models::{Decision, Outcome, PolicyMode};
 /// * `since` - Filter entries since this RFC3339 timestamp
 /// * `mode` - Filter by policy mode (enforce, warn, audit)
 /// * `decision` - Filter by decision (allowed, blocked, warned, audited)
+/// * `tool` - Filter by tool name
 pub async fn run(
     limit: usize,
     since: Option<String>,
     mode: Option<String>,
     decision: Option<String>,
+    tool: Option<String>,
 ) -> Result<()> {
     let query = LogQuery::new();
 
-    let mut filters = QueryFilters {
+    let mut filters = parse_filters(since, mode, decision, tool);
+    filters.limit = Some(limit);
+
+    let entries = query.query(filters)?;
+
+    if entries.is_empty() {
+        println!("No log entries found.");
+        return Ok(());
+    }
+
+    println!("Found {} log entries:", entries.len());
+    print_header();
+    for entry in &entries {
+        print_entry(entry);
+    }
+
+    Ok(())
+}
+
+/// How often `--follow` checks the log for new entries
+const FOLLOW_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Stream log entries as they are written, like `tail -f`
+///
+/// Shows the last `limit` matching entries (oldest first), then polls the
+/// log for new ones with the same filters until interrupted.
+pub async fn follow(
+    limit: usize,
+    since: Option<String>,
+    mode: Option<String>,
+    decision: Option<String>,
+    tool: Option<String>,
+) -> Result<()> {
+    let query = LogQuery::new();
+    let filters = parse_filters(since, mode, decision, tool);
+
+    let mut offset = query.end_offset();
+    let recent = query.query(QueryFilters {
         limit: Some(limit),
-        ..Default::default()
-    };
+        ..filters.clone()
+    })?;
 
-    // Parse since timestamp
-    if let Some(since_str) = since {
-        if let Ok(since_time) = DateTime::parse_from_rfc3339(&since_str) {
-            filters.since = Some(since_time.with_timezone(&Utc));
-        } else {
-            println!(
-                "Warning: Invalid since timestamp format. Use RFC3339 format (e.g., 2024-01-01T00:00:00Z)"
-            );
+    println!(
+        "Following {} (Ctrl-C to stop)",
+        Logger::default_log_path().display()
+    );
+    print_header();
+    for entry in recent.iter().rev() {
+        print_entry(entry);
+    }
+
+    loop {
+        let (entries, next) = query.read_new(offset, &filters)?;
+        for entry in &entries {
+            print_entry(entry);
         }
+        offset = next;
+        tokio::time::sleep(FOLLOW_POLL_INTERVAL).await;
     }
+}
+
+/// Build query filters from the command-line values, warning on bad input
+fn parse_filters(
+    since: Option<String>,
+    mode: Option<String>,
+    decision: Option<String>,
+    tool: Option<String>,
+) -> QueryFilters {
+    let mut filters = QueryFilters {
+        since: parse_since(since),
+        tool_name: tool,
+        ..Default::default()
+    };
 
     // Parse mode filter
     if let Some(mode_str) = mode {
@@ -63,46 +126,367 @@ pub async fn run(
         }
     }
 
-    let entries = query.query(filters)?;
+    filters
+}
+
+fn print_header() {
+    println!(
+        "{:<25} {:<15} {:<12} {:<8} {:<8} {:<10} {:>6}",
+        "Timestamp", "Event", "Tool", "Mode", "Decision", "Outcome", "Time"
+    );
+}
+
+fn print_entry(entry: &LogEntry) {
+    let tool = entry.tool_name.as_deref().unwrap_or("-");
+    let mode_str = entry
+        .mode
+        .map(|m| format!("{}", m))
+        .unwrap_or_else(|| "-".to_string());
+    let decision_str = entry
+        .decision
+        .map(|d| format!("{}", d))
+        .unwrap_or_else(|| "-".to_string());
+    let outcome = match entry.outcome {
+        Outcome::Allow => "ALLOW",
+        Outcome::Block => "BLOCK",
+        Outcome::Inject => "INJECT",
+    };
+    let outcome = match entry.repeat_count {
+        Some(count) => format!("{} x{}", outcome, count),
+        None => outcome.to_string(),
+    };
+    // Dry-run decisions were logged but not applied
+    let outcome = if entry.dry_run {
+        format!("{} dry", outcome)
+    } else {
+        outcome
+    };
+
+    println!(
+        "{:<25} {:<15} {:<12} {:<8} {:<8} {:<10} {:>6}ms",
+        entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+        entry.event_type,
+        tool,
+        mode_str,
+        decision_str,
+        outcome,
+        entry.timing.processing_ms
+    );
+}
+
+/// Parse an RFC3339 `--since` value, warning on bad input
+fn parse_since(since: Option<String>) -> Option<DateTime<Utc>> {
+    let since_str = since?;
+    if let Ok(since_time) = DateTime::parse_from_rfc3339(&since_str) {
+        Some(since_time.with_timezone(&Utc))
+    } else {
+        println!(
+            "Warning: Invalid since timestamp format. Use RFC3339 format (e.g., 2024-01-01T00:00:00Z)"
+        );
+        None
+    }
+}
+
+/// Summarize logged activity
+///
+/// # Arguments
+/// * `slow_validators` - Rank validator scripts by p95 latency and timeouts
+/// * `latency` - Show daily percentiles from the latency histograms
+/// * `since` - Only include entries since this RFC3339 timestamp
+pub async fn stats(slow_validators: bool, latency: bool, since: Option<String>) -> Result<()> {
+    let since = parse_since(since);
+    if latency {
+        return print_latency(since.map(|t| t.date_naive()));
+    }
+
+    let filters = QueryFilters {
+        since,
+        ..Default::default()
+    };
+    let entries = LogQuery::new().query(filters)?;
 
     if entries.is_empty() {
         println!("No log entries found.");
         return Ok(());
     }
 
-    println!("Found {} log entries:", entries.len());
+    if slow_validators {
+        print_slow_validators(&entries);
+        return Ok(());
+    }
+
+    let count = |outcome: Outcome| entries.iter().filter(|e| e.outcome == outcome).count();
+    let mut times: Vec<u64> = entries.iter().map(|e| e.timing.processing_ms).collect();
+    times.sort_unstable();
+
+    println!("Events: {}", entries.len());
     println!(
-        "{:<25} {:<15} {:<12} {:<8} {:<8} {:<10} {:>6}",
-        "Timestamp", "Event", "Tool", "Mode", "Decision", "Outcome", "Time"
+        "  allow: {}  inject: {}  block: {}",
+        count(Outcome::Allow),
+        count(Outcome::Inject),
+        count(Outcome::Block)
+    );
+    println!(
+        "Processing time: p50 {}ms  p95 {}ms  max {}ms",
+        percentile(&times, 50),
+        percentile(&times, 95),
+        times.last().copied().unwrap_or(0)
+    );
+
+    Ok(())
+}
+
+/// Activity in one window of the log
+#[derive(Default)]
+struct ActivityStats {
+    events: usize,
+    blocks: usize,
+    warnings: usize,
+
+    /// Events each rule matched
+    rules: BTreeMap<String, usize>,
+}
+
+impl ActivityStats {
+    fn from_entries<'a>(entries: impl IntoIterator<Item = &'a LogEntry>) -> Self {
+        let mut stats = Self::default();
+        for entry in entries {
+            stats.events += 1;
+            if entry.outcome == Outcome::Block {
+                stats.blocks += 1;
+            }
+            if entry.decision == Some(Decision::Warned) {
+                stats.warnings += 1;
+            }
+            for rule in &entry.rules_matched {
+                *stats.rules.entry(rule.clone()).or_default() += 1;
+            }
+        }
+        stats
+    }
+
+    /// `count` as a percentage of the window's events
+    #[allow(clippy::cast_precision_loss)]
+    fn rate(&self, count: usize) -> f64 {
+        if self.events == 0 {
+            0.0
+        } else {
+            100.0 * count as f64 / self.events as f64
+        }
+    }
+}
+
+/// Compare activity in two consecutive windows: from `before` until
+/// `after`, and from `after` until now
+///
+/// Rates rather than counts are compared, so windows of different lengths
+/// still line up; rules are listed by how much their activity changed.
+pub async fn compare(before: &str, after: &str) -> Result<()> {
+    let before_start = parse_time("before", before)?;
+    let after_start = parse_time("after", after)?;
+    if before_start >= after_start {
+        anyhow::bail!(
+            "--before ({}) must start earlier than --after ({})",
+            before,
+            after
+        );
+    }
+
+    let entries = LogQuery::new().query(QueryFilters {
+        since: Some(before_start),
+        ..Default::default()
+    })?;
+    let (earlier, later): (Vec<&LogEntry>, Vec<&LogEntry>) =
+        entries.iter().partition(|e| e.timestamp < after_start);
+    let earlier = ActivityStats::from_entries(earlier);
+    let later = ActivityStats::from_entries(later);
+
+    println!(
+        "Before: {} to {}",
+        before_start.format("%Y-%m-%d %H:%M"),
+        after_start.format("%Y-%m-%d %H:%M")
     );
+    println!("After:  {} to now", after_start.format("%Y-%m-%d %H:%M"));
+    println!();
+    println!("{:<30} {:>9} {:>9} {:>9}", "", "Before", "After", "Delta");
+    println!(
+        "{:<30} {:>9} {:>9} {:>9}",
+        "Events",
+        earlier.events,
+        later.events,
+        count_delta(earlier.events, later.events)
+    );
+    for (label, before_rate, after_rate) in [
+        (
+            "Block rate",
+            earlier.rate(earlier.blocks),
+            later.rate(later.blocks),
+        ),
+        (
+            "Warn rate",
+            earlier.rate(earlier.warnings),
+            later.rate(later.warnings),
+        ),
+    ] {
+        println!(
+            "{:<30} {:>8.1}% {:>8.1}% {:>+7.1}pp",
+            label,
+            before_rate,
+            after_rate,
+            after_rate - before_rate
+        );
+    }
+
+    let mut rules: Vec<(&str, usize, usize)> = earlier
+        .rules
+        .keys()
+        .chain(later.rules.keys())
+        .map(String::as_str)
+        .collect::<std::collections::BTreeSet<_>>()
+        .into_iter()
+        .map(|rule| {
+            let count = |stats: &ActivityStats| stats.rules.get(rule).copied().unwrap_or(0);
+            (rule, count(&earlier), count(&later))
+        })
+        .collect();
+    if rules.is_empty() {
+        return Ok(());
+    }
+    rules.sort_by_key(|&(_, before, after)| std::cmp::Reverse(before.abs_diff(after)));
+
+    println!();
+    println!("Rule matches:");
+    for (rule, before, after) in rules {
+        println!(
+            "  {:<28} {:>9} {:>9} {:>9}",
+            rule,
+            before,
+            after,
+            count_delta(before, after)
+        );
+    }
+    Ok(())
+}
+
+/// Signed change from `before` to `after`, e.g. `+3` or `-12`
+fn count_delta(before: usize, after: usize) -> String {
+    if after >= before {
+        format!("+{}", after - before)
+    } else {
+        format!("-{}", before - after)
+    }
+}
+
+/// Per-script validator latency summary
+struct ValidatorStats {
+    runs: usize,
+    timeouts: usize,
+    p50_ms: u64,
+    p95_ms: u64,
+    max_ms: u64,
+}
+
+/// Aggregate validator runs by script, slowest p95 first
+fn validator_stats(entries: &[LogEntry]) -> Vec<(String, ValidatorStats)> {
+    let mut durations: BTreeMap<&str, (Vec<u64>, usize)> = BTreeMap::new();
+    for run in entries.iter().flat_map(|e| e.validators.iter().flatten()) {
+        let (times, timeouts) = durations.entry(&run.script).or_default();
+        times.push(run.duration_ms);
+        if run.timed_out {
+            *timeouts += 1;
+        }
+    }
+
+    let mut stats: Vec<(String, ValidatorStats)> = durations
+        .into_iter()
+        .map(|(script, (mut times, timeouts))| {
+            times.sort_unstable();
+            let stats = ValidatorStats {
+                runs: times.len(),
+                timeouts,
+                p50_ms: percentile(&times, 50),
+                p95_ms: percentile(&times, 95),
+                max_ms: times.last().copied().unwrap_or(0),
+            };
+            (script.to_string(), stats)
+        })
+        .collect();
+
+    stats.sort_by(|a, b| {
+        b.1.p95_ms
+            .cmp(&a.1.p95_ms)
+            .then(b.1.timeouts.cmp(&a.1.timeouts))
+    });
+    stats
+}
 
-    for entry in entries {
-        let tool = entry.tool_name.as_deref().unwrap_or("-");
-        let mode_str = entry
-            .mode
-            .map(|m| format!("{}", m))
-            .unwrap_or_else(|| "-".to_string());
-        let decision_str = entry
-            .decision
-            .map(|d| format!("{}", d))
-            .unwrap_or_else(|| "-".to_string());
-        let outcome = match entry.outcome {
-            Outcome::Allow => "ALLOW",
-            Outcome::Block => "BLOCK",
-            Outcome::Inject => "INJECT",
-        };
+#[allow(clippy::cast_precision_loss)]
+fn print_slow_validators(entries: &[LogEntry]) {
+    let stats = validator_stats(entries);
+    if stats.is_empty() {
+        println!("No validator executions found in logs.");
+        return;
+    }
 
+    println!("Validators by p95 latency:");
+    println!(
+        "{:<40} {:>6} {:>8} {:>8} {:>8} {:>9}",
+        "Script", "Runs", "p50", "p95", "Max", "Timeouts"
+    );
+    for (script, s) in stats {
         println!(
-            "{:<25} {:<15} {:<12} {:<8} {:<8} {:<10} {:>6}ms",
-            entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
-            entry.event_type,
-            tool,
-            mode_str,
-            decision_str,
-            outcome,
-            entry.timing.processing_ms
+            "{:<40} {:>6} {:>6}ms {:>6}ms {:>6}ms {:>4} ({:.0}%)",
+            script,
+            s.runs,
+            s.p50_ms,
+            s.p95_ms,
+            s.max_ms,
+            s.timeouts,
+            100.0 * s.timeouts as f64 / s.runs as f64
         );
     }
+}
 
+/// Print p50/p95/p99 per day and overall, without reading the log itself
+fn print_latency(since: Option<NaiveDate>) -> Result<()> {
+    let days = latency::load_days(&latency::latency_dir(), since)?;
+    if days.is_empty() {
+        println!("No latency histograms found.");
+        return Ok(());
+    }
+
+    println!(
+        "{:<12} {:>8} {:>8} {:>8} {:>8} {:>8}",
+        "Day", "Events", "p50", "p95", "p99", "Max"
+    );
+    let mut total = Histogram::default();
+    for (day, histogram) in &days {
+        print_latency_row(&day.to_string(), histogram);
+        total.merge(histogram);
+    }
+    if days.len() > 1 {
+        print_latency_row("All", &total);
+    }
     Ok(())
 }
+
+fn print_latency_row(label: &str, histogram: &Histogram) {
+    println!(
+        "{:<12} {:>8} {:>6}ms {:>6}ms {:>6}ms {:>6}ms",
+        label,
+        histogram.count,
+        histogram.percentile(50),
+        histogram.percentile(95),
+        histogram.percentile(99),
+        histogram.max
+    );
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[u64], pct: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (pct * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
@@ -0,0 +1,245 @@
+//! CCH Pack Command - Share rules between projects
+//!
+//! `cch pack create` extracts selected rules and the files they reference
+//! into a single distributable pack file; `cch pack install` merges one
+//! into the current project, resolving conflicts interactively or with
+//! `--strategy`.
+
+use anyhow::{Context, Result};
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+
+use crate::bundle::safe_relative_path;
+use crate::config::Config;
+use crate::config::edit::ConfigDocument;
+use crate::models::Rule;
+use crate::pack::{self, Conflict, Pack, PackFile, PackMetadata, Resolution, Selection};
+
+/// Options for `cch pack create`
+pub struct CreateOptions {
+    pub tags: Vec<String>,
+    pub rules: Vec<String>,
+    pub out: String,
+    pub name: Option<String>,
+    pub version: String,
+    pub description: Option<String>,
+    pub config: Option<String>,
+}
+
+/// Create a pack from the current project's rules
+pub async fn create(options: CreateOptions) -> Result<()> {
+    let config_path = options
+        .config
+        .unwrap_or_else(|| ".claude/hooks.yaml".to_string());
+    let config = Config::from_file(&config_path)?;
+
+    // Referenced files are relative to the project root that holds .claude/
+    let project_root = Path::new(&config_path)
+        .parent()
+        .and_then(Path::parent)
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+
+    let out = Path::new(&options.out);
+    let name = options.name.unwrap_or_else(|| {
+        out.file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("pack")
+            .to_string()
+    });
+
+    let selection = Selection {
+        tags: options.tags,
+        rules: options.rules,
+    };
+    let metadata = PackMetadata {
+        name,
+        version: options.version,
+        description: options.description,
+        created: Some(chrono::Utc::now()),
+    };
+    let pack = Pack::create(&config, &selection, metadata, project_root)?;
+
+    std::fs::write(out, pack.to_yaml()?)
+        .with_context(|| format!("Failed to write {}", out.display()))?;
+
+    println!(
+        "✓ Created pack '{}@{}' at {}",
+        pack.pack.name,
+        pack.pack.version,
+        out.display()
+    );
+    println!("  Rules: {}", pack.rules.len());
+    for rule in &pack.rules {
+        println!("    - {}", rule.name);
+    }
+    if !pack.files.is_empty() {
+        println!("  Bundled files: {}", pack.files.len());
+        for file in &pack.files {
+            println!("    - {}", file.path);
+        }
+    }
+
+    let skipped = config
+        .rules
+        .iter()
+        .filter(|r| selection.includes(r) && config.is_sealed(&r.name))
+        .count();
+    if skipped > 0 {
+        println!("⚠️  Skipped {} encrypted rules", skipped);
+    }
+
+    Ok(())
+}
+
+/// Install a pack into the current project's configuration
+pub async fn install(file: String, strategy: Option<String>, config: Option<String>) -> Result<()> {
+    let strategy = strategy.map(|s| s.parse::<Resolution>()).transpose()?;
+    let pack = Pack::from_yaml(
+        &std::fs::read_to_string(&file).with_context(|| format!("Failed to read {}", file))?,
+    )?;
+
+    let config_path = config.unwrap_or_else(|| ".claude/hooks.yaml".to_string());
+    let project_root = Path::new(&config_path)
+        .parent()
+        .and_then(Path::parent)
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+
+    // Edit the file's text rather than a loaded Config so defaults,
+    // decrypted rules, and compiled policies are not written back, and
+    // comments survive
+    let mut document = ConfigDocument::read(Path::new(&config_path))?;
+    let parsed: serde_yaml::Value = serde_yaml::from_str(document.as_str())
+        .with_context(|| format!("Failed to parse {}", config_path))?;
+    let local: Vec<Rule> = match parsed.get("rules") {
+        Some(rules) if !rules.is_null() => {
+            serde_yaml::from_value(rules.clone()).context("Invalid rules section")?
+        }
+        _ => Vec::new(),
+    };
+    let mut rules = local.clone();
+
+    println!(
+        "Installing pack '{}@{}' ({} rules)",
+        pack.pack.name,
+        pack.pack.version,
+        pack.rules.len()
+    );
+    let interactive = strategy.is_none() && std::io::stdin().is_terminal();
+    let report = pack::install(&mut rules, &pack, |conflict| match strategy {
+        Some(resolution) => Ok(resolution),
+        None if interactive => prompt(conflict),
+        None => Err(anyhow::anyhow!(
+            "Conflict: {}. Re-run interactively or pass --strategy \
+             (keep-local, take-pack, rename, merge)",
+            conflict
+        )),
+    })?;
+
+    // Installing replaces or merges rules in place and appends the rest
+    for (before, after) in local.iter().zip(&rules) {
+        if before != after {
+            document.replace_rule(&before.name, after)?;
+        }
+    }
+    for rule in &rules[local.len()..] {
+        document.append_rule(rule)?;
+    }
+    let merged: Config = serde_yaml::from_str(document.as_str())
+        .context("Installed configuration does not parse")?;
+    merged
+        .validate()
+        .context("Installed configuration is invalid")?;
+
+    for file in &pack.files {
+        write_pack_file(project_root, file, strategy == Some(Resolution::TakePack))?;
+    }
+    document.save(Path::new(&config_path))?;
+
+    for name in &report.added {
+        println!("  + {}", name);
+    }
+    for name in &report.replaced {
+        println!("  ~ {} (replaced by pack)", name);
+    }
+    for (from, to) in &report.renamed {
+        println!("  + {} (renamed from {})", to, from);
+    }
+    for name in &report.merged {
+        println!("  ~ {} (matchers merged)", name);
+    }
+    for name in &report.skipped {
+        println!("  = {} (kept local)", name);
+    }
+    println!("✓ Updated {}", config_path);
+
+    Ok(())
+}
+
+/// Ask the user how to settle one conflict
+fn prompt(conflict: &Conflict) -> Result<Resolution> {
+    println!();
+    println!("⚠️  Conflict: {}", conflict);
+    println!("  local: {}", describe(conflict.local));
+    println!("  pack:  {}", describe(conflict.incoming));
+
+    let stdin = std::io::stdin();
+    loop {
+        print!("  [k]eep local, [t]ake pack, [r]ename, [m]erge matchers? ");
+        std::io::stdout().flush()?;
+
+        let mut input = String::new();
+        if stdin.read_line(&mut input)? == 0 {
+            anyhow::bail!("Installation aborted");
+        }
+        match input.trim().parse() {
+            Ok(resolution) => return Ok(resolution),
+            Err(e) => println!("  {}", e),
+        }
+    }
+}
+
+fn describe(rule: &Rule) -> String {
+    let matchers = &rule.matchers;
+    let mut parts = Vec::new();
+    if let Some(ref tools) = matchers.tools {
+        parts.push(format!("tools={:?}", tools));
+    }
+    if let Some(ref pattern) = matchers.command_match {
+        parts.push(format!("command_match={:?}", pattern));
+    }
+    if let Some(ref extensions) = matchers.extensions {
+        parts.push(format!("extensions={:?}", extensions));
+    }
+    format!("{} {}", rule.name, parts.join(" "))
+}
+
+/// Write a bundled file, keeping differing local copies unless `overwrite`
+fn write_pack_file(project_root: &Path, file: &PackFile, overwrite: bool) -> Result<()> {
+    let path = project_root.join(safe_relative_path(&file.path)?);
+    if let Ok(existing) = std::fs::read_to_string(&path) {
+        if existing == file.content {
+            return Ok(());
+        }
+        if !overwrite {
+            println!(
+                "⚠️  Kept local {} (differs from the pack; use --strategy take-pack to overwrite)",
+                file.path
+            );
+            return Ok(());
+        }
+    }
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, &file.content)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    #[cfg(unix)]
+    if file.executable {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,305 @@
+//! CCH Rule Command - Maintain rules in place
+//!
+//! `cch rule rename <old> <new>` renames a rule in hooks.yaml, editing only
+//! its `name:` line so comments and formatting survive (see
+//! `config::edit`), and in the
+//! `matched` expectations of hooks.tests.yaml beside it. With
+//! `--keep-history`, audit log entries recorded under the old name are
+//! attributed to the new one (see `logging::renames`).
+//!
+//! `cch rule preview <rule-file>` replays logged events against a proposed
+//! rule and reports how many it would have matched and blocked, by session
+//! and by command or path, before the rule is merged.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::cli::coverage::parse_time;
+use crate::cli::test;
+use crate::config::Config;
+use crate::config::edit::ConfigDocument;
+use crate::hooks;
+use crate::logging::{LogQuery, Logger, QueryFilters, renames};
+use crate::models::{Event, EventDetails, EventType, LogEntry, Rule};
+
+/// Rows shown in each preview breakdown
+const PREVIEW_TOP: usize = 10;
+
+/// Rename a rule, keeping its history when asked
+pub async fn rename(
+    old: String,
+    new: String,
+    config: Option<String>,
+    keep_history: bool,
+) -> Result<()> {
+    let config_path = config.unwrap_or_else(|| ".claude/hooks.yaml".to_string());
+    let current = Config::from_file(&config_path)?;
+
+    if !current.rules.iter().any(|r| r.name == old) {
+        anyhow::bail!("Rule '{}' not found in {}", old, config_path);
+    }
+    if current.is_sealed(&old) {
+        anyhow::bail!("Rule '{}' is sealed and can't be renamed", old);
+    }
+    if current.rules.iter().any(|r| r.name == new) {
+        anyhow::bail!("A rule named '{}' already exists", new);
+    }
+    if !Regex::new(r"^[a-zA-Z0-9_-]+$")?.is_match(&new) {
+        anyhow::bail!(
+            "Invalid rule name '{}': must contain only letters, numbers, hyphens, and underscores",
+            new
+        );
+    }
+
+    let mut document = ConfigDocument::read(Path::new(&config_path))?;
+    document.rename_rule(&old, &new).with_context(|| {
+        format!(
+            "Can't rename '{}' automatically in {}; edit its name by hand",
+            old, config_path
+        )
+    })?;
+    document.save(Path::new(&config_path))?;
+    println!("✓ Renamed rule '{}' to '{}' in {}", old, new, config_path);
+
+    let tests_path = Path::new(&config_path).with_file_name(test::TESTS_FILE);
+    let updated_tests = test::rename_expectations(&tests_path, &old, &new)?;
+    if updated_tests > 0 {
+        println!(
+            "✓ Updated {} test expectation(s) in {}",
+            updated_tests,
+            tests_path.display()
+        );
+    }
+
+    if keep_history {
+        renames::record(
+            &renames::renames_path(&Logger::default_log_path()),
+            &old,
+            &new,
+        )?;
+        println!(
+            "✓ Past log entries for '{}' now count toward '{}'",
+            old, new
+        );
+    }
+
+    Ok(())
+}
+
+/// Report what the rules in `rule_file` would have done to the events
+/// logged since `against_logs`
+///
+/// Validator scripts, `inject_command` and escalation are skipped, so a
+/// preview runs nothing and changes no session state; rules that only
+/// block through a validator show matches but no blocks.
+pub async fn preview(rule_file: String, against_logs: String) -> Result<()> {
+    let since = parse_time("against-logs", &against_logs)?;
+    let content = std::fs::read_to_string(&rule_file)
+        .with_context(|| format!("Failed to read {}", rule_file))?;
+    let rules =
+        parse_rules(&content).with_context(|| format!("Invalid rule file {}", rule_file))?;
+
+    // Keep the project's settings and zones, which matchers consult
+    let mut config = Config::load(None)?;
+    let entries = LogQuery::new().query(QueryFilters {
+        since: Some(since),
+        ..Default::default()
+    })?;
+    let events: Vec<Event> = entries.iter().filter_map(event_from_entry).collect();
+    println!(
+        "Previewing against {} logged events since {}",
+        events.len(),
+        since.format("%Y-%m-%d %H:%M")
+    );
+
+    for rule in rules {
+        let name = rule.name.clone();
+        config.rules = vec![previewable(rule)];
+        config
+            .validate()
+            .with_context(|| format!("Rule '{}' is invalid", name))?;
+
+        let mut impact = Impact::default();
+        for event in &events {
+            let report = hooks::evaluate_with_config(event, &config).await?;
+            if !report.matched_rules.is_empty() {
+                impact.record(event, !report.response.continue_);
+            }
+        }
+        impact.print(&name, events.len());
+    }
+    Ok(())
+}
+
+/// The rules of a rule file: one rule, a list of rules, or a config's
+/// `rules:` section
+fn parse_rules(content: &str) -> Result<Vec<Rule>> {
+    let value: serde_yaml::Value = serde_yaml::from_str(content)?;
+    let rules = match value.get("rules") {
+        Some(rules) => serde_yaml::from_value(rules.clone())?,
+        None if value.is_sequence() => serde_yaml::from_value(value)?,
+        None => vec![serde_yaml::from_value(value)?],
+    };
+    if rules.is_empty() {
+        anyhow::bail!("no rules to preview");
+    }
+    Ok(rules)
+}
+
+/// `rule` with the actions a preview must not perform removed, enabled
+fn previewable(mut rule: Rule) -> Rule {
+    rule.actions.run = None;
+    rule.actions.inject_command = None;
+    rule.actions.throttle = None;
+    rule.escalation = None;
+    if let Some(ref mut metadata) = rule.metadata {
+        metadata.enabled = true;
+    }
+    rule
+}
+
+/// The event a log entry recorded: the raw event when debug logging kept
+/// it, else one rebuilt from the entry's event details
+fn event_from_entry(entry: &LogEntry) -> Option<Event> {
+    if let Some(event) = entry
+        .raw_event
+        .clone()
+        .and_then(|raw| serde_json::from_value(raw).ok())
+    {
+        return Some(event);
+    }
+    let hook_event_name: EventType =
+        serde_json::from_value(serde_json::Value::String(entry.event_type.clone())).ok()?;
+    let details = entry.event_details.as_ref();
+    Some(Event {
+        hook_event_name,
+        tool_name: entry.tool_name.clone(),
+        tool_input: details.and_then(EventDetails::to_tool_input),
+        session_id: entry.session_id.clone(),
+        timestamp: entry.timestamp,
+        user_id: None,
+        transcript_path: None,
+        cwd: None,
+        permission_mode: None,
+        tool_use_id: None,
+        prompt: None,
+        legacy_input_keys: false,
+        tool_response: None,
+    })
+}
+
+/// Matches and blocks of one previewed rule
+#[derive(Default)]
+struct Impact {
+    matched: usize,
+    blocked: usize,
+    /// (matched, blocked) per session and per command or path
+    by_session: BTreeMap<String, (usize, usize)>,
+    by_target: BTreeMap<String, (usize, usize)>,
+}
+
+impl Impact {
+    fn record(&mut self, event: &Event, blocked: bool) {
+        self.matched += 1;
+        self.blocked += usize::from(blocked);
+        let target = event
+            .tool_input
+            .as_ref()
+            .and_then(|input| {
+                ["command", "file_path", "path", "pattern"]
+                    .iter()
+                    .find_map(|key| input.get(key).and_then(|v| v.as_str()))
+            })
+            .map(String::from)
+            .or_else(|| event.tool_name.clone())
+            .unwrap_or_else(|| event.hook_event_name.to_string());
+        for (map, key) in [
+            (&mut self.by_session, event.session_id.clone()),
+            (&mut self.by_target, target),
+        ] {
+            let counts = map.entry(key).or_default();
+            counts.0 += 1;
+            counts.1 += usize::from(blocked);
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn print(&self, rule: &str, total: usize) {
+        println!();
+        println!("Rule '{}':", rule);
+        let percent = |n: usize| {
+            if total == 0 {
+                0.0
+            } else {
+                100.0 * n as f64 / total as f64
+            }
+        };
+        println!(
+            "  Matched: {} ({:.1}%)",
+            self.matched,
+            percent(self.matched)
+        );
+        println!(
+            "  Blocked: {} ({:.1}%)",
+            self.blocked,
+            percent(self.blocked)
+        );
+        for (title, map) in [
+            ("session", &self.by_session),
+            ("command or path", &self.by_target),
+        ] {
+            if map.is_empty() {
+                continue;
+            }
+            println!("  By {} ({}):", title, map.len());
+            let mut rows: Vec<_> = map.iter().collect();
+            rows.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+            for (key, (matched, blocked)) in rows.into_iter().take(PREVIEW_TOP) {
+                println!("    {:>5} matched {:>5} blocked  {}", matched, blocked, key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_inputs() {
+        let single = "name: no-curl\nmatchers: { tools: [Bash] }\nactions: { block: true }";
+        assert_eq!(parse_rules(single).unwrap()[0].name, "no-curl");
+        let listed = format!("- {}", single.replace('\n', "\n  "));
+        assert_eq!(parse_rules(&listed).unwrap().len(), 1);
+        assert_eq!(
+            parse_rules(&format!(
+                "version: \"1.0\"\nrules:\n  {}",
+                listed.replace('\n', "\n  ")
+            ))
+            .unwrap()
+            .len(),
+            1
+        );
+        assert!(parse_rules("rules: []").is_err());
+
+        let entry: LogEntry = serde_json::from_value(serde_json::json!({
+            "timestamp": "2026-01-01T00:00:00Z",
+            "event_type": "PreToolUse",
+            "session_id": "s1",
+            "tool_name": "Bash",
+            "rules_matched": [],
+            "outcome": "allow",
+            "timing": { "processing_ms": 1, "rules_evaluated": 0 },
+            "event_details": { "tool_type": "Bash", "command": "curl example.com" }
+        }))
+        .unwrap();
+        let event = event_from_entry(&entry).unwrap();
+        assert_eq!(event.hook_event_name, EventType::PreToolUse);
+        assert_eq!(
+            event.tool_input,
+            Some(serde_json::json!({ "command": "curl example.com" }))
+        );
+    }
+}
@@ -0,0 +1,70 @@
+//! CCH Session Command - Inspect per-session state
+//!
+//! `cch session diff <id>` lists every file a session wrote or edited,
+//! from the change journal recorded when `settings.change_journal` is on.
+
+use anyhow::Result;
+
+use crate::journal::{self, ChangeStatus, FileChange};
+
+/// Show the net file changes made during a session
+pub async fn diff(session_id: String) -> Result<()> {
+    let records = journal::read(&session_id)?;
+    let changes = journal::summarize(&records);
+
+    println!(
+        "Session {}: {} files touched",
+        session_id,
+        changes
+            .iter()
+            .filter(|c| c.status != ChangeStatus::Unchanged)
+            .count()
+    );
+    println!();
+
+    if changes.is_empty() {
+        println!("No completed Write/Edit operations were journaled.");
+        return Ok(());
+    }
+
+    for change in &changes {
+        println!(
+            "  {} {:<50} {} ({} {})",
+            change.status,
+            change.path,
+            describe_delta(change),
+            change.operations,
+            if change.operations == 1 {
+                "edit"
+            } else {
+                "edits"
+            }
+        );
+    }
+
+    println!();
+    println!("Legend: A added, M modified, D deleted, = unchanged");
+
+    Ok(())
+}
+
+/// Line and byte deltas between the first and last snapshot
+fn describe_delta(change: &FileChange) -> String {
+    let lines = |s: &Option<journal::Snapshot>| s.as_ref().map_or(0, |s| s.lines);
+    let bytes = |s: &Option<journal::Snapshot>| s.as_ref().map_or(0, |s| s.bytes);
+
+    if change.status == ChangeStatus::Modified && change.before.is_none() {
+        return format!(
+            "now {} lines, {} bytes",
+            lines(&change.after),
+            bytes(&change.after)
+        );
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    let (line_delta, byte_delta) = (
+        lines(&change.after) as i64 - lines(&change.before) as i64,
+        bytes(&change.after) as i64 - bytes(&change.before) as i64,
+    );
+    format!("{:+} lines, {:+} bytes", line_delta, byte_delta)
+}
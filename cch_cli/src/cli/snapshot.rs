@@ -0,0 +1,204 @@
+//! CCH Snapshot Command - Save and roll back the project's whole CCH setup
+//!
+//! `cch snapshot create <name>` saves `.claude/hooks.yaml`, every context,
+//! validator and template file its rules reference (with the bundle trust
+//! manifest of hashes and validator trust levels, see [`Bundle`]), and the
+//! hook entries installed in `.claude/settings.json` to
+//! `.claude/snapshots/<name>.yaml`. `cch snapshot restore <name>` verifies
+//! the snapshot and puts all of it back, so a policy experiment can be
+//! undone in one command.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::bundle::Bundle;
+
+const SNAPSHOT_DIR: &str = ".claude/snapshots";
+const CONFIG_PATH: &str = ".claude/hooks.yaml";
+const SETTINGS_PATH: &str = ".claude/settings.json";
+
+/// A saved project setup
+#[derive(Debug, Serialize, Deserialize)]
+struct Snapshot {
+    name: String,
+
+    created: DateTime<Utc>,
+
+    /// `hooks` of .claude/settings.json (absent: nothing was installed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    settings_hooks: Option<serde_json::Value>,
+
+    /// The configuration and the files its rules reference
+    policy: Bundle,
+}
+
+/// Save the current project's setup as snapshot `name`
+pub async fn create(name: String, force: bool) -> Result<()> {
+    let path = create_in(Path::new("."), &name, force)?;
+    println!("✓ Saved snapshot '{}' to {}", name, path.display());
+    Ok(())
+}
+
+/// Restore the current project's setup from snapshot `name`
+pub async fn restore(name: String) -> Result<()> {
+    for line in restore_in(Path::new("."), &name)? {
+        println!("{}", line);
+    }
+    println!("✓ Restored snapshot '{}'", name);
+    Ok(())
+}
+
+/// List the current project's snapshots
+pub async fn list() -> Result<()> {
+    let snapshots = list_in(Path::new("."))?;
+    if snapshots.is_empty() {
+        println!("No snapshots. Save one with `cch snapshot create <name>`");
+    }
+    for snapshot in snapshots {
+        println!(
+            "{}  {}  ({} files)",
+            snapshot.name,
+            snapshot.created.format("%Y-%m-%d %H:%M:%S UTC"),
+            snapshot.policy.files.len()
+        );
+    }
+    Ok(())
+}
+
+fn snapshot_path(project_root: &Path, name: &str) -> Result<PathBuf> {
+    if !Regex::new(r"^[a-zA-Z0-9_-]+$")?.is_match(name) {
+        anyhow::bail!(
+            "Invalid snapshot name '{}': must contain only letters, numbers, hyphens, and underscores",
+            name
+        );
+    }
+    Ok(project_root
+        .join(SNAPSHOT_DIR)
+        .join(format!("{}.yaml", name)))
+}
+
+fn create_in(project_root: &Path, name: &str, force: bool) -> Result<PathBuf> {
+    let path = snapshot_path(project_root, name)?;
+    if path.exists() && !force {
+        anyhow::bail!(
+            "Snapshot '{}' already exists. Re-run with --force to replace it",
+            name
+        );
+    }
+
+    let policy = Bundle::export(&project_root.join(CONFIG_PATH), project_root)?;
+    let settings_hooks = read_settings(project_root)?.and_then(|mut settings| {
+        settings
+            .as_object_mut()
+            .and_then(|settings| settings.remove("hooks"))
+    });
+    let snapshot = Snapshot {
+        name: name.to_string(),
+        created: Utc::now(),
+        settings_hooks,
+        policy,
+    };
+
+    std::fs::create_dir_all(project_root.join(SNAPSHOT_DIR))?;
+    let content = serde_yaml::to_string(&snapshot).context("Failed to serialize snapshot")?;
+    std::fs::write(&path, content)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Restore snapshot `name`, returning one line per restored item
+///
+/// Nothing is written unless the snapshot's trust manifest checks out.
+fn restore_in(project_root: &Path, name: &str) -> Result<Vec<String>> {
+    let path = snapshot_path(project_root, name)?;
+    let content = std::fs::read_to_string(&path).with_context(|| {
+        let available: Vec<String> = list_in(project_root)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        match available.as_slice() {
+            [] => format!("Snapshot '{}' not found", name),
+            _ => format!(
+                "Snapshot '{}' not found (available: {})",
+                name,
+                available.join(", ")
+            ),
+        }
+    })?;
+    let snapshot: Snapshot = serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    snapshot
+        .policy
+        .verify()
+        .with_context(|| format!("Refusing to restore snapshot '{}'", name))?;
+
+    let mut restored = Vec::new();
+    for file in &snapshot.policy.files {
+        let current = std::fs::read_to_string(project_root.join(&file.path)).ok();
+        if current.as_deref() == Some(file.content.as_str()) {
+            restored.push(format!("  = {}", file.path));
+        } else {
+            file.install(project_root)?;
+            restored.push(format!("  ~ {}", file.path));
+        }
+    }
+
+    let mut settings = read_settings(project_root)?.unwrap_or_else(|| serde_json::json!({}));
+    let object = settings
+        .as_object_mut()
+        .with_context(|| format!("{} is not a JSON object", SETTINGS_PATH))?;
+    let changed = match snapshot.settings_hooks {
+        Some(hooks) => object.insert("hooks".to_string(), hooks.clone()) != Some(hooks),
+        None => object.remove("hooks").is_some(),
+    };
+    if changed {
+        let path = project_root.join(SETTINGS_PATH);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(&settings)?)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        restored.push(format!("  ~ {} (hooks)", SETTINGS_PATH));
+    } else {
+        restored.push(format!("  = {} (hooks)", SETTINGS_PATH));
+    }
+    Ok(restored)
+}
+
+/// The project's settings.json, if it exists
+fn read_settings(project_root: &Path) -> Result<Option<serde_json::Value>> {
+    let path = project_root.join(SETTINGS_PATH);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Saved snapshots, oldest first
+fn list_in(project_root: &Path) -> Result<Vec<Snapshot>> {
+    let Ok(entries) = std::fs::read_dir(project_root.join(SNAPSHOT_DIR)) else {
+        return Ok(Vec::new());
+    };
+    let mut snapshots = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("yaml") {
+            continue;
+        }
+        let content = std::fs::read_to_string(&path)?;
+        match serde_yaml::from_str::<Snapshot>(&content) {
+            Ok(snapshot) => snapshots.push(snapshot),
+            Err(e) => tracing::warn!("Skipping unreadable snapshot {}: {}", path.display(), e),
+        }
+    }
+    snapshots.sort_by_key(|s| s.created);
+    Ok(snapshots)
+}
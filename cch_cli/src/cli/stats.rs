@@ -0,0 +1,269 @@
+//! CCH Stats Command - Aggregate the audit log
+//!
+//! Summarizes a window of the log: decisions per rule, blocks per day, the
+//! most-blocked commands, processing time, and configured rules that never
+//! fired, as text or JSON.
+
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use super::coverage::{parse_time, unmatched_rules};
+use crate::config::Config;
+use crate::logging::{LogQuery, QueryFilters};
+use crate::models::{Decision, EventDetails, LogEntry, Outcome, Rule};
+
+/// Blocked commands listed
+const TOP_COMMANDS: usize = 10;
+
+/// Decisions recorded for one rule
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct DecisionCounts {
+    pub allowed: usize,
+    pub blocked: usize,
+    pub warned: usize,
+    pub audited: usize,
+    pub asked: usize,
+}
+
+impl DecisionCounts {
+    fn record(&mut self, decision: Decision) {
+        match decision {
+            Decision::Allowed => self.allowed += 1,
+            Decision::Blocked => self.blocked += 1,
+            Decision::Warned => self.warned += 1,
+            Decision::Audited => self.audited += 1,
+            Decision::Asked => self.asked += 1,
+        }
+    }
+}
+
+/// A blocked Bash command and how often it was blocked
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CommandCount {
+    pub command: String,
+    pub count: usize,
+}
+
+/// Aggregated activity in one window of the log
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Stats {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub events: usize,
+    pub avg_processing_ms: f64,
+
+    /// Decisions per matched rule; an event's decision counts for every
+    /// rule it matched
+    pub rules: BTreeMap<String, DecisionCounts>,
+    pub blocks_per_day: BTreeMap<NaiveDate, usize>,
+    pub top_blocked_commands: Vec<CommandCount>,
+
+    /// Configured rules that matched no event in the window
+    pub never_fired: Vec<String>,
+}
+
+impl Stats {
+    #[allow(clippy::cast_precision_loss)]
+    fn aggregate(entries: &[LogEntry], rules: &[Rule]) -> Self {
+        let mut stats = Stats {
+            since: None,
+            until: None,
+            events: entries.len(),
+            avg_processing_ms: 0.0,
+            rules: BTreeMap::new(),
+            blocks_per_day: BTreeMap::new(),
+            top_blocked_commands: Vec::new(),
+            never_fired: unmatched_rules(rules, entries)
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        };
+        let mut commands: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut total_ms = 0;
+
+        for entry in entries {
+            total_ms += entry.timing.processing_ms;
+            let decision = entry.decision.unwrap_or(match entry.outcome {
+                Outcome::Block => Decision::Blocked,
+                Outcome::Allow | Outcome::Inject => Decision::Allowed,
+            });
+            for rule in &entry.rules_matched {
+                stats
+                    .rules
+                    .entry(rule.clone())
+                    .or_default()
+                    .record(decision);
+            }
+            if entry.outcome == Outcome::Block {
+                *stats
+                    .blocks_per_day
+                    .entry(entry.timestamp.date_naive())
+                    .or_default() += 1;
+                if let Some(EventDetails::Bash { command, .. }) = &entry.event_details {
+                    *commands.entry(command.as_str()).or_default() += 1;
+                }
+            }
+        }
+
+        if !entries.is_empty() {
+            stats.avg_processing_ms = total_ms as f64 / entries.len() as f64;
+        }
+        let mut commands: Vec<(&str, usize)> = commands.into_iter().collect();
+        commands.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        stats.top_blocked_commands = commands
+            .into_iter()
+            .take(TOP_COMMANDS)
+            .map(|(command, count)| CommandCount {
+                command: command.to_string(),
+                count,
+            })
+            .collect();
+        stats
+    }
+
+    fn print(&self) {
+        let bound = |time: Option<DateTime<Utc>>, default: &str| {
+            time.map_or_else(
+                || default.to_string(),
+                |time| time.format("%Y-%m-%d %H:%M").to_string(),
+            )
+        };
+        println!(
+            "Audit log: {} events ({} to {})",
+            self.events,
+            bound(self.since, "start"),
+            bound(self.until, "now")
+        );
+        println!("Average processing time: {:.1}ms", self.avg_processing_ms);
+        println!();
+
+        println!("Decisions per rule ({}):", self.rules.len());
+        if !self.rules.is_empty() {
+            println!(
+                "  {:<30} {:>8} {:>8} {:>8} {:>8} {:>8}",
+                "rule", "allowed", "blocked", "warned", "audited", "asked"
+            );
+        }
+        for (rule, counts) in &self.rules {
+            println!(
+                "  {:<30} {:>8} {:>8} {:>8} {:>8} {:>8}",
+                rule, counts.allowed, counts.blocked, counts.warned, counts.audited, counts.asked
+            );
+        }
+        println!();
+
+        println!("Blocks per day:");
+        if self.blocks_per_day.is_empty() {
+            println!("  (none)");
+        }
+        for (day, blocks) in &self.blocks_per_day {
+            println!("  {}  {}", day, blocks);
+        }
+        println!();
+
+        println!("Top blocked commands:");
+        if self.top_blocked_commands.is_empty() {
+            println!("  (none)");
+        }
+        for CommandCount { command, count } in &self.top_blocked_commands {
+            println!("  {:>5}  {}", count, command);
+        }
+        println!();
+
+        println!("Rules that never fired ({}):", self.never_fired.len());
+        for name in &self.never_fired {
+            println!("  - {}", name);
+        }
+    }
+}
+
+/// Run the stats report
+///
+/// # Arguments
+/// * `since` - Window start: a relative age (`30d`, `12h`, `2w`) or RFC3339
+/// * `until` - Window end, in the same forms (default: now)
+/// * `json` - Print the report as JSON
+pub async fn run(since: Option<String>, until: Option<String>, json: bool) -> Result<()> {
+    let since = since.map(|spec| parse_time("since", &spec)).transpose()?;
+    let until = until.map(|spec| parse_time("until", &spec)).transpose()?;
+    let config = Config::load(None)?;
+    let entries = LogQuery::new().query(QueryFilters {
+        since,
+        until,
+        ..Default::default()
+    })?;
+
+    let mut stats = Stats::aggregate(&entries, &config.rules);
+    stats.since = since;
+    stats.until = until;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+    } else {
+        stats.print();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregate() {
+        let entry = |day: u32, command: &str, rules: &[&str], decision: Option<&str>| {
+            serde_json::from_value::<LogEntry>(serde_json::json!({
+                "timestamp": format!("2026-10-{:02}T12:00:00Z", day),
+                "event_type": "PreToolUse",
+                "session_id": "s",
+                "tool_name": "Bash",
+                "rules_matched": rules,
+                "outcome": if decision == Some("blocked") { "block" } else { "allow" },
+                "decision": decision,
+                "timing": { "processing_ms": day, "rules_evaluated": 2 },
+                "event_details": { "tool_type": "Bash", "command": command }
+            }))
+            .unwrap()
+        };
+        let entries = [
+            entry(1, "git push --force", &["no-force-push"], Some("blocked")),
+            entry(
+                1,
+                "git push --force",
+                &["no-force-push", "audit-git"],
+                Some("blocked"),
+            ),
+            entry(2, "rm -rf /", &["no-force-push"], Some("blocked")),
+            entry(3, "git status", &["audit-git"], Some("audited")),
+            entry(3, "ls", &[], None),
+        ];
+        let rules: Vec<Rule> = serde_yaml::from_str(
+            "[{name: no-force-push, matchers: {}, actions: {}}, {name: unused, matchers: {}, actions: {}}]",
+        )
+        .unwrap();
+
+        let stats = Stats::aggregate(&entries, &rules);
+        assert_eq!(stats.events, 5);
+        assert!((stats.avg_processing_ms - 2.0).abs() < f64::EPSILON);
+        assert_eq!(stats.rules["no-force-push"].blocked, 3);
+        assert_eq!(stats.rules["audit-git"].blocked, 1);
+        assert_eq!(stats.rules["audit-git"].audited, 1);
+        assert_eq!(stats.blocks_per_day.values().collect::<Vec<_>>(), [&2, &1]);
+        assert_eq!(
+            stats.top_blocked_commands,
+            [
+                CommandCount {
+                    command: "git push --force".to_string(),
+                    count: 2
+                },
+                CommandCount {
+                    command: "rm -rf /".to_string(),
+                    count: 1
+                },
+            ]
+        );
+        assert_eq!(stats.never_fired, ["unused"]);
+    }
+}
@@ -0,0 +1,102 @@
+//! CCH Statusline Command - Compact status for Claude Code's statusline
+//!
+//! Claude Code runs the configured `statusLine` command with session details
+//! as JSON on stdin and shows the first line it prints. `cch statusline`
+//! answers with the active config, the number of enabled rules, and how many
+//! operations were blocked this session, e.g. `CCH project · 12 rules · 2 blocked`.
+//!
+//! ```json
+//! { "statusLine": { "type": "command", "command": "cch statusline" } }
+//! ```
+
+use anyhow::Result;
+use serde::Deserialize;
+use std::io::{IsTerminal, Read};
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::logging::{LogQuery, QueryFilters};
+use crate::models::Outcome;
+
+/// The parts of Claude Code's statusline input CCH uses
+#[derive(Debug, Default, Deserialize)]
+struct StatusInput {
+    session_id: Option<String>,
+    cwd: Option<String>,
+    workspace: Option<Workspace>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Workspace {
+    project_dir: Option<String>,
+    current_dir: Option<String>,
+}
+
+/// Print the status line
+pub async fn run(session_id: Option<String>) -> Result<()> {
+    let input = read_input();
+    let project_root = input
+        .workspace
+        .and_then(|w| w.project_dir.or(w.current_dir))
+        .or(input.cwd)
+        .map(PathBuf::from);
+    let session_id = session_id.or(input.session_id);
+
+    println!(
+        "{}",
+        status_line(project_root.as_deref(), session_id.as_deref())?
+    );
+    Ok(())
+}
+
+/// Statusline input from stdin; absent or unreadable input is not an error
+fn read_input() -> StatusInput {
+    let stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        return StatusInput::default();
+    }
+    let mut content = String::new();
+    if stdin.lock().read_to_string(&mut content).is_err() {
+        return StatusInput::default();
+    }
+    serde_json::from_str(&content).unwrap_or_default()
+}
+
+fn status_line(project_root: Option<&Path>, session_id: Option<&str>) -> Result<String> {
+    let Some(path) = Config::locate(project_root)? else {
+        return Ok("CCH off (no hooks.yaml)".to_string());
+    };
+    let scope = if path == Config::global_path()? {
+        "global"
+    } else if path == Config::system_path() {
+        "system"
+    } else {
+        "project"
+    };
+
+    let Ok(config) = Config::load(project_root) else {
+        return Ok(format!("CCH {} · config error", scope));
+    };
+    let enabled = config.rules.iter().filter(|r| r.is_enabled()).count();
+    let mut parts = vec![
+        format!("CCH {}", scope),
+        format!(
+            "{} {}",
+            enabled,
+            if enabled == 1 { "rule" } else { "rules" }
+        ),
+    ];
+
+    if let Some(session_id) = session_id {
+        let blocked = LogQuery::new()
+            .query(QueryFilters {
+                session_id: Some(session_id.to_string()),
+                outcome: Some(Outcome::Block),
+                ..Default::default()
+            })?
+            .len();
+        parts.push(format!("{} blocked", blocked));
+    }
+
+    Ok(parts.join(" · "))
+}
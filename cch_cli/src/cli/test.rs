@@ -0,0 +1,256 @@
+//! CCH Test Command - Run policy test cases
+//!
+//! `cch test` runs the cases in `hooks.tests.yaml`, next to the config,
+//! through the rule engine and reports which expectations held, exiting
+//! non-zero if any failed so the suite can gate CI:
+//!
+//! ```yaml
+//! tests:
+//!   - name: force push is blocked
+//!     event:
+//!       hook_event_name: PreToolUse
+//!       tool_name: Bash
+//!       tool_input: { command: "git push --force origin main" }
+//!     expect:
+//!       decision: blocked
+//!       matched: [no-force-push]
+//!   - name: captured edit event is allowed
+//!     fixture: fixtures/edit-readme.json
+//!     expect: { decision: allowed, matched: [] }
+//! ```
+//!
+//! An event is given inline or as a `fixture` file (JSON or YAML, relative
+//! to the tests file). `session_id` and `timestamp` may be omitted; all
+//! cases of one run share a fresh session, so escalation can be tested by
+//! repeating an event. Session and global state live in memory for the
+//! run: ~/.claude/state is neither read nor written, so results never
+//! depend on earlier runs. `matched` lists every rule expected to match, in any
+//! order; either expectation may be left out.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::hooks;
+use crate::models::{Decision, Event};
+use crate::state::MemoryState;
+
+/// File name of the test suite, beside hooks.yaml
+pub const TESTS_FILE: &str = "hooks.tests.yaml";
+
+#[derive(Debug, Deserialize)]
+struct Suite {
+    #[serde(default)]
+    tests: Vec<TestCase>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TestCase {
+    name: String,
+    #[serde(default)]
+    event: Option<serde_yaml::Value>,
+    #[serde(default)]
+    fixture: Option<String>,
+    #[serde(default)]
+    expect: Expectation,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Expectation {
+    #[serde(default)]
+    decision: Option<Decision>,
+    #[serde(default)]
+    matched: Option<Vec<String>>,
+}
+
+/// Run the policy test suite
+pub async fn run(config_path: Option<String>, tests_path: Option<String>) -> Result<()> {
+    let config = match config_path {
+        Some(ref path) => Config::from_file(path)?,
+        None => Config::load(None)?,
+    };
+    let tests_path = tests_path.map(PathBuf::from).unwrap_or_else(|| {
+        Path::new(config_path.as_deref().unwrap_or(".claude/hooks.yaml")).with_file_name(TESTS_FILE)
+    });
+    let content = std::fs::read_to_string(&tests_path)
+        .with_context(|| format!("Failed to read {}", tests_path.display()))?;
+    let suite: Suite = serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", tests_path.display()))?;
+
+    println!(
+        "Running {} policy tests from {}",
+        suite.tests.len(),
+        tests_path.display()
+    );
+    println!();
+
+    let session_id = format!("cch-test-{}", chrono::Utc::now().timestamp_millis());
+    let base_dir = tests_path.parent().unwrap_or(Path::new("."));
+    let mut state = MemoryState::default();
+    let mut failed = 0;
+    for case in &suite.tests {
+        let failures = match load_event(case, base_dir, &session_id) {
+            Ok(event) => check(&case.expect, &event, &config, &mut state).await,
+            Err(e) => Err(e),
+        };
+        match failures {
+            Ok(failures) if failures.is_empty() => println!("  ✓ {}", case.name),
+            Ok(failures) => {
+                failed += 1;
+                println!("  ✗ {}: {}", case.name, failures.join("; "));
+            }
+            Err(e) => {
+                failed += 1;
+                println!("  ✗ {}: {:#}", case.name, e);
+            }
+        }
+    }
+
+    println!();
+    println!(
+        "Policy tests: {} passed, {} failed",
+        suite.tests.len() - failed,
+        failed
+    );
+    if failed > 0 {
+        anyhow::bail!("{} policy test(s) failed", failed);
+    }
+    Ok(())
+}
+
+/// Replace rule `old` with `new` in the `matched` expectations of a test
+/// file, keeping its formatting; returns the number of lists changed
+pub(crate) fn rename_expectations(path: &Path, old: &str, new: &str) -> Result<usize> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+
+    // Flow lists (`matched: [a, b]`) and block lists (`matched:` then `- a`)
+    let lists = Regex::new(
+        r#"matched:[ \t]*(?:\[[^\]]*\]|(?:\n[ \t]*-[ \t]*["']?[a-zA-Z0-9_-]+["']?[ \t]*(?:#.*)?)+)"#,
+    )?;
+    let name = Regex::new(&format!(
+        r"(^|[^a-zA-Z0-9_-]){}($|[^a-zA-Z0-9_-])",
+        regex::escape(old)
+    ))?;
+    let mut changed = 0;
+    let updated = lists.replace_all(&content, |caps: &regex::Captures| {
+        let list = &caps[0];
+        let renamed = name.replace_all(list, |c: &regex::Captures| {
+            format!("{}{}{}", &c[1], new, &c[2])
+        });
+        if renamed != list {
+            changed += 1;
+        }
+        renamed.into_owned()
+    });
+
+    if changed > 0 {
+        std::fs::write(path, updated.as_ref())
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(changed)
+}
+
+/// The case's event, with the run's session and the current time filled in
+fn load_event(case: &TestCase, base_dir: &Path, session_id: &str) -> Result<Event> {
+    let mut value = match (&case.event, &case.fixture) {
+        (Some(event), None) => event.clone(),
+        (None, Some(fixture)) => {
+            let path = base_dir.join(fixture);
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read fixture {}", path.display()))?;
+            serde_yaml::from_str(&content)
+                .with_context(|| format!("Failed to parse fixture {}", path.display()))?
+        }
+        _ => anyhow::bail!("give exactly one of `event` or `fixture`"),
+    };
+    if let serde_yaml::Value::Mapping(ref mut event) = value {
+        let key = serde_yaml::Value::from("session_id");
+        if !event.contains_key(&key) && !event.contains_key("sessionId") {
+            event.insert(key, session_id.into());
+        }
+    }
+    serde_yaml::from_value(value).context("Invalid event")
+}
+
+/// Differences between the expectation and what the engine decided
+async fn check(
+    expect: &Expectation,
+    event: &Event,
+    config: &Config,
+    state: &mut MemoryState,
+) -> Result<Vec<String>> {
+    let report = hooks::evaluate_in_memory(event, config, state).await?;
+    let mut failures = Vec::new();
+
+    let decision = report.decision.unwrap_or(Decision::Allowed);
+    if let Some(expected) = expect.decision.filter(|d| *d != decision) {
+        failures.push(format!("expected {}, got {}", expected, decision));
+    }
+
+    if let Some(ref expected) = expect.matched {
+        let mut expected: Vec<&str> = expected.iter().map(String::as_str).collect();
+        let mut matched: Vec<&str> = report
+            .matched_rules
+            .iter()
+            .map(|r| r.name.as_str())
+            .collect();
+        expected.sort_unstable();
+        matched.sort_unstable();
+        if expected != matched {
+            failures.push(format!(
+                "expected rules [{}] to match, got [{}]",
+                expected.join(", "),
+                matched.join(", ")
+            ));
+        }
+    }
+
+    Ok(failures)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rename_expectations_keeps_formatting() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(TESTS_FILE);
+        let content = r#"tests:
+  # The command mentions no-force but only matched lists change
+  - name: force push
+    event: { hook_event_name: PreToolUse, tool_name: Bash, tool_input: { command: "echo no-force" } }
+    expect: { decision: blocked, matched: [no-force, no-force-2] }
+  - name: both
+    event: { hook_event_name: PreToolUse, tool_name: Bash }
+    expect:
+      matched:
+        - "no-force"
+        - other
+  - name: no-force
+    event: { hook_event_name: PreToolUse, tool_name: Bash }
+"#;
+        std::fs::write(&path, content).unwrap();
+
+        assert_eq!(
+            rename_expectations(&path, "no-force", "no-force-push").unwrap(),
+            2
+        );
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            content
+                .replace("[no-force,", "[no-force-push,")
+                .replace("\"no-force\"", "\"no-force-push\"")
+        );
+        assert_eq!(
+            rename_expectations(&dir.path().join("missing.yaml"), "a", "b").unwrap(),
+            0
+        );
+    }
+}
@@ -1,10 +1,13 @@
 use anyhow::{Context, Result};
 use std::path::Path;
 
+use crate::cli::debug::{SimEventType, build_event};
 use crate::config::Config;
+use crate::hooks;
+use crate::models::{Rule, RuleTest};
 
 /// Validate configuration file
-pub async fn run(config_path: Option<String>) -> Result<()> {
+pub async fn run(config_path: Option<String>, run_rule_tests: bool) -> Result<()> {
     let config_path = config_path.unwrap_or_else(|| ".claude/hooks.yaml".to_string());
 
     println!("Validating configuration file: {}", config_path);
@@ -31,6 +34,87 @@ pub async fn run(config_path: Option<String>) -> Result<()> {
     println!("✓ Configuration syntax is valid");
     println!("✓ Version: {}", config.version);
     println!("✓ Rules loaded: {}", config.rules.len());
+    if config.dry_run() {
+        println!("⚠️  Dry run: decisions are logged but every event is allowed");
+    }
+    if !config.zones.is_empty() {
+        println!("✓ Trust zones: {}", config.zones.len());
+    }
+    if let Some(ref policies) = config.policies {
+        println!(
+            "✓ Policies: compiled into {} rules",
+            policies.compile().len()
+        );
+    }
+    if !config.encrypted_rules.is_empty() {
+        println!(
+            "✓ Encrypted sections: {} ({} rules decrypted)",
+            config.encrypted_rules.len(),
+            config.sealed_rules.len()
+        );
+        if !crate::encryption::is_available() {
+            println!(
+                "⚠️  This build lacks the `encrypted-config` feature; encrypted rules are skipped"
+            );
+        }
+    }
+
+    if config.settings.encrypt_state && !crate::encryption::is_available() {
+        println!(
+            "⚠️  settings.encrypt_state is on, but this build lacks the `encrypted-config` \
+             feature; session state will not be saved"
+        );
+    }
+
+    if !crate::structural::is_available() {
+        for rule in config
+            .rules
+            .iter()
+            .filter(|r| r.actions.structural_checks.is_some())
+        {
+            println!(
+                "⚠️  Rule '{}' uses structural_checks, but this build lacks the \
+                 `structural-checks` feature; the checks will be skipped",
+                rule.name
+            );
+        }
+    }
+
+    if !crate::wasm_validator::is_available() {
+        for rule in config.rules.iter().filter(|r| {
+            r.actions
+                .run
+                .as_ref()
+                .is_some_and(crate::models::RunAction::is_wasm)
+        }) {
+            println!(
+                "⚠️  Rule '{}' runs a WASM validator, but this build lacks the \
+                 `wasm-validators` feature; it will fail and `fail_open` decides the outcome",
+                rule.name
+            );
+        }
+    }
+
+    for rule in &config.rules {
+        if let Some(ref header) = rule.actions.ensure_header {
+            if !Path::new(&header.template).exists() {
+                println!(
+                    "⚠️  Rule '{}' ensure_header template not found: {}",
+                    rule.name, header.template
+                );
+            }
+        }
+    }
+
+    for warning in config.priority_warnings() {
+        println!("⚠️  {}", warning);
+    }
+    for warning in config.pattern_warnings() {
+        println!("⚠️  {}", warning);
+    }
+    for warning in config.staleness_warnings(chrono::Local::now().date_naive()) {
+        println!("⚠️  {}", warning);
+    }
 
     let enabled_rules = config.enabled_rules();
     println!("✓ Enabled rules: {}", enabled_rules.len());
@@ -48,5 +132,85 @@ pub async fn run(config_path: Option<String>) -> Result<()> {
         }
     }
 
+    if run_rule_tests {
+        run_inline_rule_tests(&config)?;
+    }
+
+    Ok(())
+}
+
+/// Execute the inline `tests:` blocks declared on rules
+fn run_inline_rule_tests(config: &Config) -> Result<()> {
+    println!();
+    println!("Running inline rule tests...");
+
+    let mut passed = 0;
+    let mut failed = 0;
+
+    for rule in &config.rules {
+        for test in rule.tests.iter().flatten() {
+            match run_rule_test(config, rule, test) {
+                Ok(true) => {
+                    passed += 1;
+                    println!("  ✓ {}: {}", rule.name, describe_test(test));
+                }
+                Ok(false) => {
+                    failed += 1;
+                    println!(
+                        "  ✗ {}: {} (expected {}, got {})",
+                        rule.name,
+                        describe_test(test),
+                        match_label(test.expect_match),
+                        match_label(!test.expect_match)
+                    );
+                }
+                Err(e) => {
+                    failed += 1;
+                    println!("  ✗ {}: {} ({})", rule.name, describe_test(test), e);
+                }
+            }
+        }
+    }
+
+    if passed + failed == 0 {
+        println!("  No rules declare inline tests");
+        return Ok(());
+    }
+
+    println!("Rule tests: {} passed, {} failed", passed, failed);
+    if failed > 0 {
+        anyhow::bail!("{} rule test(s) failed", failed);
+    }
+
     Ok(())
 }
+
+/// Run a single inline test, returning whether the expectation held
+fn run_rule_test(config: &Config, rule: &Rule, test: &RuleTest) -> Result<bool> {
+    let event_name = test.event.as_deref().unwrap_or("PreToolUse");
+    let event_type = SimEventType::from_str(event_name)
+        .with_context(|| format!("unknown event type '{}'", event_name))?;
+
+    let event = build_event(
+        event_type,
+        Some(test.effective_tool().to_string()),
+        test.command.clone(),
+        test.path.clone(),
+    );
+
+    Ok(hooks::matches_rule(&event, rule, config) == test.expect_match)
+}
+
+/// Short description of a test case for reporting
+pub(crate) fn describe_test(test: &RuleTest) -> String {
+    let input = test
+        .command
+        .as_deref()
+        .or(test.path.as_deref())
+        .unwrap_or("<no input>");
+    format!("{} \"{}\"", test.effective_tool(), input)
+}
+
+fn match_label(matched: bool) -> &'static str {
+    if matched { "match" } else { "no match" }
+}
@@ -2,14 +2,28 @@
 #![allow(clippy::unnecessary_map_or)]
 
 use anyhow::{Context, Result};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use crate::models::Rule;
+use crate::encryption;
+use crate::models::{Matchers, PolicyMode, Rule, Zone};
+use crate::policy::Policies;
+
+pub mod cache;
+pub mod edit;
+mod interpolate;
+mod layers;
+mod patterns;
+mod plugins;
+mod snapshot;
+mod strict;
 
 /// Global CCH settings
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Settings {
     /// Logging verbosity level
     #[serde(default = "default_log_level")]
@@ -19,6 +33,10 @@ pub struct Settings {
     #[serde(default = "default_max_context_size")]
     pub max_context_size: usize,
 
+    /// Maximum size of a hook event read from stdin in bytes
+    #[serde(default = "default_max_input_size")]
+    pub max_input_size: usize,
+
     /// Default script execution timeout in seconds
     #[serde(default = "default_script_timeout")]
     pub script_timeout: u32,
@@ -30,6 +48,327 @@ pub struct Settings {
     /// Enable debug logging with full event and rule details
     #[serde(default = "default_debug_logs")]
     pub debug_logs: bool,
+
+    /// Append a machine-parsable summary of matched rules to the response
+    #[serde(default)]
+    pub explain_in_response: bool,
+
+    /// Collapse multiple warn-mode warnings into one summary block
+    #[serde(default)]
+    pub collapse_warnings: bool,
+
+    /// Record file hashes around Write/Edit operations in a per-session journal
+    #[serde(default)]
+    pub change_journal: bool,
+
+    /// After this many identical blocks in one session, send a short
+    /// "stop retrying" reason instead of the full one (unset disables)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat_block_limit: Option<u32>,
+
+    /// Reject unknown keys in rules (default: on for `version: "2.0"` and
+    /// later, off for older configs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strict_parsing: Option<bool>,
+
+    /// Cache the parsed config and its compiled patterns under
+    /// ~/.claude/cache/cch/snapshots so one-shot invocations skip YAML
+    /// parsing and pattern compilation until the file changes
+    #[serde(default = "default_config_snapshot")]
+    pub config_snapshot: bool,
+
+    /// Named priority bands keyed by governance tag, mapped to each band's
+    /// minimum priority (a band extends up to the next band's minimum)
+    #[serde(default = "default_priority_bands")]
+    pub priority_bands: BTreeMap<String, i32>,
+
+    /// Mode that rules past their `governance.expires` date are demoted to
+    /// (`enforce` keeps enforcing them)
+    #[serde(default = "default_expired_rule_mode")]
+    pub expired_rule_mode: PolicyMode,
+
+    /// Tools whose events only run pure matchers: validator scripts,
+    /// `inject_command`, the post-processor and the git lookup behind
+    /// `bypass_users` are skipped so high-frequency reads never spawn a
+    /// process
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fast_path_tools: Vec<String>,
+
+    /// Summarize the active policy to each new session on SessionStart
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_banner: Option<SessionBanner>,
+
+    /// Where audit log entries are written (the JSON Lines log at
+    /// ~/.claude/logs/cch.log when empty)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub log_sinks: Vec<LogSinkConfig>,
+
+    /// Whether every matching rule runs (`all`) or evaluation stops after
+    /// the first matching rule that isn't in audit mode (`first_match`)
+    #[serde(default)]
+    pub evaluation: EvaluationMode,
+
+    /// Script given the final response and an event summary, which may
+    /// block or warn about an allowed operation but never allow a blocked one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_processor: Option<String>,
+
+    /// Evaluate and log every event but always allow it, to try a policy
+    /// set before enforcing it (`CCH_DRY_RUN` overrides)
+    #[serde(default)]
+    pub dry_run: bool,
+
+    /// Encrypt session state at rest with a machine-local key (needs the
+    /// `encrypted-config` feature)
+    #[serde(default)]
+    pub encrypt_state: bool,
+
+    /// Remove session state untouched for this many days (unset keeps it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_ttl_days: Option<u32>,
+
+    /// How rules of equal priority are ordered, first criterion first;
+    /// file order settles any remaining tie
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub priority_tie_break: Vec<TieBreak>,
+
+    /// Let a `cch:allow <rule> <justification>` comment in written content
+    /// suppress that rule's `block_if_match` hit on the same or next line
+    #[serde(default)]
+    pub inline_suppressions: bool,
+
+    /// What `cch install` registers with Claude Code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub install: Option<InstallSettings>,
+
+    /// Archive every event in full, redacted, to daily JSON Lines files
+    /// kept apart from the audit log (unset disables)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_archive: Option<EventArchive>,
+}
+
+/// `settings.evaluation`: how many matching rules are applied to an event
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EvaluationMode {
+    /// Apply every matching rule and merge their responses
+    #[default]
+    All,
+    /// Apply only the highest-priority matching rule
+    FirstMatch,
+}
+
+/// One criterion of `settings.priority_tie_break`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TieBreak {
+    /// Rule name, in lexicographic order
+    Name,
+    /// Source layer, most specific first: project, user, system, then plugins
+    Layer,
+}
+
+/// The config layer a rule was defined in, lowest precedence first
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleLayer {
+    Plugin,
+    System,
+    User,
+    Project,
+}
+
+impl RuleLayer {
+    /// The layer of config file `path`
+    fn of(path: &Path) -> Self {
+        if path == Config::system_path() {
+            Self::System
+        } else if Config::global_path().is_ok_and(|global| global == path) {
+            Self::User
+        } else {
+            Self::Project
+        }
+    }
+}
+
+impl std::fmt::Display for RuleLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Plugin => "plugin",
+            Self::System => "system",
+            Self::User => "user",
+            Self::Project => "project",
+        })
+    }
+}
+
+/// `settings.session_banner`: the policy summary injected once per session
+///
+/// ```yaml
+/// settings:
+///   session_banner:
+///     profile: platform-strict
+///     note: "Ask in #platform before working around a block."
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SessionBanner {
+    /// Name of the policy profile, shown in the banner
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+
+    /// Extra line appended to the banner
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// `settings.install`: the hook events `cch install` registers when no
+/// `--events` are given, and the tools they're limited to when no
+/// `--tools` are
+///
+/// ```yaml
+/// settings:
+///   install:
+///     events: [PreToolUse, UserPromptSubmit]
+///     tools: [Bash, Write, Edit]
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct InstallSettings {
+    /// Event names, e.g. `PreToolUse` (empty: the default events)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<String>,
+
+    /// Tools that tool events are registered for (empty: every tool)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<String>,
+}
+
+/// `settings.event_archive`: the raw event archive for offline analysis
+///
+/// ```yaml
+/// settings:
+///   event_archive:
+///     dir: /data/cch-archive        # ~/.claude/archive unless set
+///     sample_rate: 0.1              # fraction of sessions archived
+///     redact: ['ghp_[A-Za-z0-9]+']
+///     redact_fields: [password, authorization]
+///     retention_days: 30
+/// ```
+///
+/// Common secret shapes (cloud keys, tokens, private keys) are redacted on
+/// top of `redact` and `redact_fields` unless `default_redactions` is off.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EventArchive {
+    /// Directory of the daily `events-YYYY-MM-DD.jsonl` files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<PathBuf>,
+
+    /// Fraction of sessions whose events are archived, from 0.0 to 1.0
+    #[serde(default = "default_archive_sample_rate")]
+    pub sample_rate: f64,
+
+    /// Regexes whose matches in any string of the event are redacted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redact: Vec<String>,
+
+    /// Keys, at any depth and in any case, whose values are redacted whole
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redact_fields: Vec<String>,
+
+    /// Also redact the built-in secret patterns and field names
+    #[serde(default = "default_redactions")]
+    pub default_redactions: bool,
+
+    /// Remove daily files older than this many days (unset keeps them)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention_days: Option<u32>,
+
+    /// File format of the archive; only JSON Lines is written
+    #[serde(default)]
+    pub format: ArchiveFormat,
+}
+
+/// `settings.event_archive.format`
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveFormat {
+    #[default]
+    Jsonl,
+    /// Accepted by the parser so it can be rejected with an explanation
+    Parquet,
+}
+
+impl EventArchive {
+    fn validate(&self) -> Result<()> {
+        if !(0.0..=1.0).contains(&self.sample_rate) {
+            anyhow::bail!("settings.event_archive.sample_rate must be between 0.0 and 1.0");
+        }
+        if self.retention_days == Some(0) {
+            anyhow::bail!("settings.event_archive.retention_days must be >= 1");
+        }
+        if self.format == ArchiveFormat::Parquet {
+            anyhow::bail!(
+                "settings.event_archive.format: parquet is not supported; the archive is written as JSON Lines (format: jsonl), which tools such as DuckDB can convert"
+            );
+        }
+        self.redact_patterns().map(|_| ())
+    }
+
+    /// The compiled `redact` patterns
+    pub fn redact_patterns(&self) -> Result<Vec<Regex>> {
+        self.redact
+            .iter()
+            .map(|p| {
+                crate::regex_cache::compile(p).with_context(|| {
+                    format!("Invalid settings.event_archive.redact pattern '{}'", p)
+                })
+            })
+            .collect()
+    }
+}
+
+fn default_archive_sample_rate() -> f64 {
+    1.0
+}
+
+fn default_redactions() -> bool {
+    true
+}
+
+/// One entry of `settings.log_sinks`
+///
+/// ```yaml
+/// settings:
+///   log_sinks:
+///     - type: jsonl              # ~/.claude/logs/cch.log unless `path` is set
+///     - type: syslog             # /dev/log unless `address` is set
+///       address: 127.0.0.1:514
+///     - type: webhook
+///       url: http://audit.internal:8080/cch
+///       timeout_ms: 500
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LogSinkConfig {
+    /// JSON Lines file, as read by `cch logs` and `cch explain`
+    Jsonl {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        path: Option<PathBuf>,
+    },
+    /// RFC 5424 syslog over a local socket path or UDP `host:port`
+    Syslog {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        address: Option<String>,
+    },
+    /// HTTP POST of each entry as JSON
+    Webhook {
+        url: String,
+        #[serde(default = "default_webhook_timeout_ms")]
+        timeout_ms: u64,
+    },
+}
+
+fn default_webhook_timeout_ms() -> u64 {
+    1000
 }
 
 fn default_log_level() -> String {
@@ -40,6 +379,10 @@ fn default_max_context_size() -> usize {
     1024 * 1024 // 1MB
 }
 
+fn default_max_input_size() -> usize {
+    4 * 1024 * 1024 // 4MB
+}
+
 fn default_script_timeout() -> u32 {
     5
 }
@@ -48,10 +391,26 @@ fn default_fail_open() -> bool {
     true
 }
 
+fn default_config_snapshot() -> bool {
+    true
+}
+
 fn default_debug_logs() -> bool {
     false
 }
 
+fn default_expired_rule_mode() -> PolicyMode {
+    PolicyMode::Audit
+}
+
+fn default_priority_bands() -> BTreeMap<String, i32> {
+    BTreeMap::from([
+        ("security".to_string(), 1000),
+        ("quality".to_string(), 500),
+        ("info".to_string(), 0),
+    ])
+}
+
 /// Complete CCH configuration
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Config {
@@ -64,6 +423,108 @@ pub struct Config {
     /// Global CCH settings
     #[serde(default)]
     pub settings: Settings,
+
+    /// Directory trust zones used by the `zone` matcher
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub zones: Vec<Zone>,
+
+    /// High-level allow/deny lists compiled into rules at load
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policies: Option<Policies>,
+
+    /// Age-encrypted, ASCII-armored YAML rule lists merged into `rules` at load
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub encrypted_rules: Vec<String>,
+
+    /// Names of rules that were decrypted from `encrypted_rules`
+    #[serde(skip)]
+    pub sealed_rules: Vec<String>,
+
+    /// Layer that defined each rule, by name (unlisted: the project's)
+    #[serde(skip)]
+    pub rule_layers: BTreeMap<String, RuleLayer>,
+
+    /// The `settings` keys the file set explicitly, which are all that a
+    /// layer overrides when layers are merged
+    #[serde(skip)]
+    pub declared_settings: serde_yaml::Mapping,
+}
+
+/// Whether a matcher block, at any depth, has an empty `any` list
+fn has_empty_any(matchers: &Matchers) -> bool {
+    matchers.any.as_ref().is_some_and(Vec::is_empty)
+        || matchers
+            .any
+            .iter()
+            .chain(&matchers.all)
+            .flatten()
+            .any(has_empty_any)
+        || matchers.not.as_deref().is_some_and(has_empty_any)
+}
+
+/// Every `expr` in a matcher block, at any depth
+fn matcher_expressions<'m>(matchers: &'m Matchers, expressions: &mut Vec<&'m str>) {
+    expressions.extend(matchers.expr.as_deref());
+    for nested in matchers.any.iter().chain(&matchers.all).flatten() {
+        matcher_expressions(nested, expressions);
+    }
+    if let Some(ref not) = matchers.not {
+        matcher_expressions(not, expressions);
+    }
+}
+
+/// Every regex in a matcher block, at any depth, with its matcher kind
+/// Every regex in a rule, with the key it came from
+pub(crate) fn rule_patterns(rule: &Rule) -> Vec<(&'static str, &str)> {
+    let mut patterns = Vec::new();
+    matcher_patterns(&rule.matchers, &mut patterns);
+    patterns.extend(
+        rule.actions
+            .block_if_match
+            .as_deref()
+            .map(|p| ("block_if_match", p)),
+    );
+    patterns.extend(
+        rule.actions
+            .rewrite
+            .as_ref()
+            .and_then(|r| r.pattern.as_deref())
+            .map(|p| ("rewrite", p)),
+    );
+    patterns
+}
+
+fn matcher_patterns<'m>(matchers: &'m Matchers, patterns: &mut Vec<(&'static str, &'m str)>) {
+    patterns.extend(
+        matchers
+            .command_match
+            .as_deref()
+            .map(|p| ("command_match", p)),
+    );
+    patterns.extend(
+        matchers
+            .command_not_match
+            .as_deref()
+            .map(|p| ("command_not_match", p)),
+    );
+    patterns.extend(
+        matchers
+            .tool_output_match
+            .as_deref()
+            .map(|p| ("tool_output_match", p)),
+    );
+    patterns.extend(
+        matchers
+            .prompt_match
+            .as_deref()
+            .map(|p| ("prompt_match", p)),
+    );
+    for nested in matchers.any.iter().chain(&matchers.all).flatten() {
+        matcher_patterns(nested, patterns);
+    }
+    if let Some(ref not) = matchers.not {
+        matcher_patterns(not, patterns);
+    }
 }
 
 impl Default for Settings {
@@ -71,9 +532,30 @@ impl Default for Settings {
         Self {
             log_level: default_log_level(),
             max_context_size: default_max_context_size(),
+            max_input_size: default_max_input_size(),
             script_timeout: default_script_timeout(),
             fail_open: default_fail_open(),
             debug_logs: default_debug_logs(),
+            explain_in_response: false,
+            collapse_warnings: false,
+            change_journal: false,
+            repeat_block_limit: None,
+            strict_parsing: None,
+            config_snapshot: default_config_snapshot(),
+            priority_bands: default_priority_bands(),
+            expired_rule_mode: default_expired_rule_mode(),
+            fast_path_tools: Vec::new(),
+            session_banner: None,
+            log_sinks: Vec::new(),
+            evaluation: EvaluationMode::All,
+            post_processor: None,
+            dry_run: false,
+            encrypt_state: false,
+            state_ttl_days: None,
+            priority_tie_break: Vec::new(),
+            inline_suppressions: false,
+            install: None,
+            event_archive: None,
         }
     }
 }
@@ -81,48 +563,229 @@ impl Default for Settings {
 impl Config {
     /// Load configuration from YAML file
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let content = fs::read_to_string(&path)
-            .with_context(|| format!("Failed to read config file: {}", path.as_ref().display()))?;
+        let config = Self::load_file(path.as_ref())?;
+        config.validate()?;
+        Ok(config)
+    }
 
-        let config: Config = serde_yaml::from_str(&content)
-            .with_context(|| format!("Failed to parse config file: {}", path.as_ref().display()))?;
+    /// Load a config file without validating it, for callers that validate
+    /// the result of merging it with other files
+    fn load_file(path: &Path) -> Result<Self> {
+        let content = Self::read(path)?;
 
-        config.validate()?;
+        let mut config = match snapshot::load(path, &content) {
+            Some(config) => config,
+            None => Self::parse(path, &content)?,
+        };
+
+        config.unseal()?;
+        if let Some(ref policies) = config.policies {
+            config.rules.extend(policies.compile());
+        }
         Ok(config)
     }
 
-    /// Load configuration with fallback hierarchy
+    /// Read a config file with environment variables interpolated
+    fn read(path: &Path) -> Result<String> {
+        let mut content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+        if crate::chaos::injected(crate::chaos::Fault::ConfigCorrupt) {
+            content = "version: \"1.0\"\nrules: [\n".to_string();
+        }
+        interpolate::interpolate(&content)
+            .with_context(|| format!("Failed to interpolate config file: {}", path.display()))
+    }
+
+    /// Parse a config document, refreshing its parsed-config snapshot
+    fn parse(path: &Path, content: &str) -> Result<Self> {
+        let failed = || format!("Failed to parse config file: {}", path.display());
+        let document: serde_yaml::Value = serde_yaml::from_str(content).with_context(failed)?;
+        let declared_settings = match document.get("settings") {
+            Some(serde_yaml::Value::Mapping(settings)) => settings.clone(),
+            _ => serde_yaml::Mapping::new(),
+        };
+        let mut config = match Config::deserialize(document) {
+            Ok(config) => config,
+            Err(e) => {
+                // Parse the text again only to report where the error is
+                let located = serde_yaml::from_str::<Config>(content).err().unwrap_or(e);
+                return Err(located).with_context(failed);
+            }
+        };
+        config.declared_settings = declared_settings;
+
+        if config.strict_parsing() {
+            strict::check(content)
+                .with_context(|| format!("Strict parsing failed for {}", path.display()))?;
+        }
+
+        if config.settings.config_snapshot {
+            snapshot::store(path, content, &config);
+        }
+        Ok(config)
+    }
+
+    /// Whether decisions are only logged and every event is allowed
+    /// (`settings.dry_run`, overridden by `CCH_DRY_RUN=1` or `0`)
+    pub fn dry_run(&self) -> bool {
+        match std::env::var("CCH_DRY_RUN").as_deref() {
+            Ok("1" | "true") => true,
+            Ok("0" | "false") => false,
+            _ => self.settings.dry_run,
+        }
+    }
+
+    /// Whether rules are parsed strictly, rejecting unknown keys
+    pub fn strict_parsing(&self) -> bool {
+        self.settings.strict_parsing.unwrap_or_else(|| {
+            self.version
+                .split('.')
+                .next()
+                .and_then(|major| major.parse::<u32>().ok())
+                .is_some_and(|major| major >= 2)
+        })
+    }
+
+    /// Load the merged configuration of every layer that exists (see
+    /// [`Config::layers`]); with no config files, the defaults
     pub fn load(project_root: Option<&Path>) -> Result<Self> {
-        // Try project-specific config first
+        let mut config = match Self::layers(project_root)?.as_slice() {
+            [] => Self::default(),
+            [path] => {
+                let config = layers::load_layer(path)?;
+                config.validate()?;
+                config
+            }
+            paths => layers::load(paths)?,
+        };
+        plugins::merge(
+            &mut config,
+            &plugins::fragments(&Self::claude_dirs(project_root)?),
+        )?;
+        Ok(config)
+    }
+
+    /// Find the most specific hooks.yaml: the project's, else the user's,
+    /// else the system-wide one
+    pub fn locate(project_root: Option<&Path>) -> Result<Option<PathBuf>> {
+        Ok(Self::layers(project_root)?.pop())
+    }
+
+    /// Existing config files in merge order, lowest precedence first: the
+    /// system-wide file, the user's, then the project's
+    pub fn layers(project_root: Option<&Path>) -> Result<Vec<PathBuf>> {
+        let mut layers = vec![Self::system_path()];
+        layers.extend(
+            Self::claude_dirs(project_root)?
+                .into_iter()
+                .map(|dir| dir.join("hooks.yaml")),
+        );
+
+        // The project may be the home directory, making its config the user's
+        layers.retain(|path| path.exists());
+        layers.dedup_by(|a, b| a.canonicalize().ok() == b.canonicalize().ok());
+        Ok(layers)
+    }
+
+    /// The user's and then the project's `.claude` directories
+    fn claude_dirs(project_root: Option<&Path>) -> Result<Vec<PathBuf>> {
+        let mut dirs = vec![
+            dirs::home_dir()
+                .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
+                .join(".claude"),
+        ];
         let effective_root = project_root
             .map(|p| p.to_path_buf())
             .or_else(|| std::env::current_dir().ok());
-
         if let Some(root) = effective_root {
-            let project_config = root.join(".claude").join("hooks.yaml");
-            if project_config.exists() {
-                return Self::from_file(&project_config);
-            }
+            dirs.push(root.join(".claude"));
         }
+        dirs.dedup_by(|a, b| a.canonicalize().ok() == b.canonicalize().ok());
+        Ok(dirs)
+    }
 
-        // Fall back to user-global config
-        let home_config = dirs::home_dir()
+    /// The system-wide config path (`$CCH_SYSTEM_CONFIG`, default
+    /// /etc/cch/hooks.yaml), typically managed by an organization
+    pub fn system_path() -> PathBuf {
+        std::env::var_os("CCH_SYSTEM_CONFIG")
+            .map_or_else(|| PathBuf::from("/etc/cch/hooks.yaml"), PathBuf::from)
+    }
+
+    /// The user-global config path (~/.claude/hooks.yaml)
+    pub fn global_path() -> Result<PathBuf> {
+        Ok(dirs::home_dir()
             .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
             .join(".claude")
-            .join("hooks.yaml");
+            .join("hooks.yaml"))
+    }
 
-        if home_config.exists() {
-            return Self::from_file(&home_config);
+    /// Decrypt `encrypted_rules` sections and merge them into `rules`
+    ///
+    /// Sections that can't be decrypted are skipped with a warning when
+    /// `fail_open` is set, and are an error otherwise.
+    fn unseal(&mut self) -> Result<()> {
+        for (index, section) in self.encrypted_rules.iter().enumerate() {
+            let rules = encryption::decrypt_section(section).and_then(|yaml| {
+                serde_yaml::from_str::<Vec<Rule>>(&yaml)
+                    .context("Decrypted section is not a YAML list of rules")
+            });
+            match rules {
+                Ok(rules) => {
+                    self.sealed_rules
+                        .extend(rules.iter().map(|r| r.name.clone()));
+                    self.rules.extend(rules);
+                }
+                Err(e) if self.settings.fail_open => {
+                    tracing::warn!("Skipping encrypted rule section {}: {:#}", index + 1, e);
+                }
+                Err(e) => {
+                    return Err(e.context(format!("Encrypted rule section {}", index + 1)));
+                }
+            }
         }
+        Ok(())
+    }
+
+    /// The config layer that defined `rule`
+    pub fn rule_layer(&self, rule: &Rule) -> RuleLayer {
+        if rule.plugin().is_some() {
+            return RuleLayer::Plugin;
+        }
+        self.rule_layers
+            .get(&rule.name)
+            .copied()
+            .unwrap_or(RuleLayer::Project)
+    }
+
+    /// How `enabled_rules` orders rules of equal priority, for display
+    pub fn tie_break_order(&self) -> String {
+        let mut criteria: Vec<&str> = self
+            .settings
+            .priority_tie_break
+            .iter()
+            .map(|criterion| match criterion {
+                TieBreak::Name => "name",
+                TieBreak::Layer => "layer",
+            })
+            .collect();
+        criteria.push("file order");
+        criteria.join(", then ")
+    }
 
-        // Return empty config if no files found
-        Ok(Self::default())
+    /// Whether a rule was loaded from an encrypted section
+    pub fn is_sealed(&self, rule_name: &str) -> bool {
+        self.sealed_rules.iter().any(|n| n == rule_name)
     }
 
     /// Validate configuration integrity
     pub fn validate(&self) -> Result<()> {
         // Validate version format
-        if !regex::Regex::new(r"^\d+\.\d+$")?.is_match(&self.version) {
+        let numeric = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+        if !self
+            .version
+            .split_once('.')
+            .is_some_and(|(major, minor)| numeric(major) && numeric(minor))
+        {
             return Err(anyhow::anyhow!("Invalid version format: {}", self.version));
         }
 
@@ -134,15 +797,294 @@ impl Config {
             }
 
             // Validate rule name format
-            if !regex::Regex::new(r"^[a-zA-Z0-9_-]+$")?.is_match(&rule.name) {
+            if rule.name.is_empty()
+                || !rule
+                    .name
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+            {
                 return Err(anyhow::anyhow!("Invalid rule name format: {}", rule.name));
             }
+
+            // Validate exemptions are justified and well-formed
+            for exemption in rule.exemptions.iter().flatten() {
+                if exemption.justification.trim().is_empty() {
+                    return Err(anyhow::anyhow!(
+                        "Exemption in rule '{}' has an empty justification",
+                        rule.name
+                    ));
+                }
+                if exemption.paths.is_empty()
+                    && exemption.commands.is_empty()
+                    && exemption.sources.is_empty()
+                {
+                    return Err(anyhow::anyhow!(
+                        "Exemption in rule '{}' lists no paths, commands, or sources",
+                        rule.name
+                    ));
+                }
+                for pattern in &exemption.commands {
+                    crate::regex_cache::check(pattern).map_err(|e| {
+                        anyhow::anyhow!(
+                            "Invalid exemption command pattern in rule '{}': {}",
+                            rule.name,
+                            e
+                        )
+                    })?;
+                }
+            }
+
+            // Compile every pattern now (unless a snapshot already vouches
+            // for it), so a typo is a config error rather than a rule that
+            // silently never matches
+            for (kind, pattern) in rule_patterns(rule) {
+                if crate::regex_cache::check(pattern).is_err() {
+                    return Err(anyhow::anyhow!(
+                        "Invalid {} pattern in rule '{}': {:?}: {}",
+                        kind,
+                        rule.name,
+                        pattern,
+                        crate::regex_cache::describe_error(pattern)
+                    ));
+                }
+            }
+
+            let mut expressions = Vec::new();
+            matcher_expressions(&rule.matchers, &mut expressions);
+            for source in expressions {
+                crate::expr::compile(source).map_err(|e| {
+                    anyhow::anyhow!("Invalid expr in rule '{}': {:?}: {}", rule.name, source, e)
+                })?;
+            }
+
+            if has_empty_any(&rule.matchers) {
+                return Err(anyhow::anyhow!(
+                    "Matchers in rule '{}' have an empty `any` block, which never matches",
+                    rule.name
+                ));
+            }
+
+            // An exception rule that also blocks contradicts itself
+            if rule.actions.allow == Some(true)
+                && (rule.actions.block == Some(true)
+                    || rule.actions.block_if_match.is_some()
+                    || rule.actions.ask == Some(true))
+            {
+                return Err(anyhow::anyhow!(
+                    "Rule '{}' sets allow and also blocks or asks; use one or the other",
+                    rule.name
+                ));
+            }
+
+            if let Some(ref filter) = rule.actions.filter_results {
+                if filter.exclude.is_empty() {
+                    return Err(anyhow::anyhow!(
+                        "filter_results in rule '{}' lists no exclude patterns",
+                        rule.name
+                    ));
+                }
+            }
+
+            if let Some(ref rewrite) = rule.actions.rewrite {
+                if rewrite.pattern.is_some() != rewrite.replace.is_some() {
+                    return Err(anyhow::anyhow!(
+                        "rewrite in rule '{}' needs both pattern and replace",
+                        rule.name
+                    ));
+                }
+                if rewrite.pattern.is_none() && rewrite.append.is_none() {
+                    return Err(anyhow::anyhow!(
+                        "rewrite in rule '{}' sets neither pattern nor append",
+                        rule.name
+                    ));
+                }
+            }
+
+            if let Some(ref throttle) = rule.actions.throttle {
+                if throttle.max == 0 {
+                    return Err(anyhow::anyhow!(
+                        "Throttle in rule '{}' must have max >= 1",
+                        rule.name
+                    ));
+                }
+                throttle
+                    .window()
+                    .with_context(|| format!("Throttle in rule '{}'", rule.name))?;
+            }
+
+            if let Some(ref escalation) = rule.escalation {
+                if escalation.after == 0 {
+                    return Err(anyhow::anyhow!(
+                        "Escalation in rule '{}' must have after >= 1",
+                        rule.name
+                    ));
+                }
+            }
+        }
+
+        if self.settings.state_ttl_days == Some(0) {
+            return Err(anyhow::anyhow!("settings.state_ttl_days must be >= 1"));
+        }
+
+        if let Some(ref archive) = self.settings.event_archive {
+            archive.validate()?;
+        }
+
+        // Webhook URLs are checked here; sockets are only opened at startup
+        for sink in &self.settings.log_sinks {
+            if let LogSinkConfig::Webhook { url, timeout_ms } = sink {
+                crate::logging::sinks::WebhookSink::new(
+                    url,
+                    std::time::Duration::from_millis(*timeout_ms),
+                )?;
+            }
         }
 
         Ok(())
     }
 
-    /// Get enabled rules sorted by priority (highest first)
+    /// Report rules whose command_match or block_if_match patterns duplicate
+    /// or already cover another rule's
+    ///
+    /// Encrypted rules are left out so their patterns are never printed.
+    pub fn pattern_warnings(&self) -> Vec<String> {
+        let collect = |get: fn(&Rule) -> Option<&String>| {
+            self.rules
+                .iter()
+                .filter(|rule| rule.is_enabled() && !self.is_sealed(&rule.name))
+                .filter_map(|rule| {
+                    get(rule).map(|pattern| patterns::RulePattern {
+                        rule: &rule.name,
+                        pattern,
+                        blocks: rule.actions.block == Some(true)
+                            || rule.actions.block_if_match.is_some(),
+                    })
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut warnings = patterns::overlaps(
+            "command_match",
+            &collect(|r| r.matchers.command_match.as_ref()),
+        );
+        warnings.extend(patterns::overlaps(
+            "block_if_match",
+            &collect(|r| r.actions.block_if_match.as_ref()),
+        ));
+        warnings
+    }
+
+    /// Check explicit rule priorities against the configured priority bands
+    ///
+    /// A rule belongs to the band named by the first of its governance tags
+    /// that is a band name. Also reports rules that share a priority and
+    /// that `settings.priority_tie_break` does not order, since their
+    /// relative order then depends on file order.
+    pub fn priority_warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let tie_break = &self.settings.priority_tie_break;
+        // Rules only tie when every tie-break criterion leaves them equal;
+        // names are unique, so breaking ties by name orders every rule
+        let mut ties: BTreeMap<(i32, Option<RuleLayer>), Vec<&str>> = BTreeMap::new();
+
+        for rule in &self.rules {
+            let Some(priority) = rule
+                .priority
+                .or_else(|| rule.metadata.as_ref().map(|m| m.priority))
+            else {
+                continue;
+            };
+            if !tie_break.contains(&TieBreak::Name) {
+                let layer = tie_break
+                    .contains(&TieBreak::Layer)
+                    .then(|| self.rule_layer(rule));
+                ties.entry((priority, layer)).or_default().push(&rule.name);
+            }
+
+            let band = rule
+                .governance
+                .as_ref()
+                .and_then(|g| g.tags.as_ref())
+                .and_then(|tags| {
+                    tags.iter()
+                        .find_map(|t| self.settings.priority_bands.get_key_value(t))
+                });
+            let Some((band, &min)) = band else {
+                continue;
+            };
+
+            // The band ends where the next higher band starts
+            let max = self
+                .settings
+                .priority_bands
+                .values()
+                .copied()
+                .filter(|&m| m > min)
+                .min();
+            let in_band = priority >= min && max.is_none_or(|max| priority < max);
+            if !in_band {
+                let range = match max {
+                    Some(max) => format!("{}..{}", min, max - 1),
+                    None => format!("{}+", min),
+                };
+                warnings.push(format!(
+                    "Rule '{}' has priority {} outside its '{}' band ({})",
+                    rule.name, priority, band, range
+                ));
+            }
+        }
+
+        for ((priority, _), names) in ties {
+            if names.len() > 1 {
+                warnings.push(format!(
+                    "Rules {} share priority {} and are ordered by {}",
+                    names
+                        .iter()
+                        .map(|n| format!("'{}'", n))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    priority,
+                    self.tie_break_order()
+                ));
+            }
+        }
+
+        warnings
+    }
+
+    /// A rule's mode on `today`: expired rules are demoted to
+    /// `settings.expired_rule_mode`
+    pub fn rule_mode(&self, rule: &Rule, today: chrono::NaiveDate) -> PolicyMode {
+        let mode = rule.effective_mode();
+        if rule.is_expired(today) {
+            mode.least_strict(self.settings.expired_rule_mode)
+        } else {
+            mode
+        }
+    }
+
+    /// Warnings for rules past their `expires` or `review_by` dates
+    pub fn staleness_warnings(&self, today: chrono::NaiveDate) -> Vec<String> {
+        self.rules
+            .iter()
+            .filter_map(|rule| {
+                let staleness = rule.staleness(today)?;
+                Some(if rule.is_expired(today) {
+                    format!(
+                        "Rule '{}' {} and runs in '{}' mode",
+                        rule.name,
+                        staleness,
+                        self.rule_mode(rule, today)
+                    )
+                } else {
+                    format!("Rule '{}' {}", rule.name, staleness)
+                })
+            })
+            .collect()
+    }
+
+    /// Get enabled rules sorted by priority (highest first), with ties
+    /// broken by `settings.priority_tie_break` and then file order
     pub fn enabled_rules(&self) -> Vec<&Rule> {
         let mut rules: Vec<&Rule> = self.rules.iter().filter(|r| r.is_enabled()).collect();
 
@@ -151,11 +1093,27 @@ impl Config {
         rules.sort_by(|a, b| {
             let a_priority = a.effective_priority();
             let b_priority = b.effective_priority();
-            b_priority.cmp(&a_priority) // Higher priority first
+            b_priority // Higher priority first
+                .cmp(&a_priority)
+                .then_with(|| self.tie_break(a, b))
         });
 
         rules
     }
+
+    /// Order of two rules of equal priority under `priority_tie_break`
+    fn tie_break(&self, a: &Rule, b: &Rule) -> std::cmp::Ordering {
+        self.settings.priority_tie_break.iter().fold(
+            std::cmp::Ordering::Equal,
+            |order, criterion| {
+                order.then_with(|| match criterion {
+                    TieBreak::Name => a.name.cmp(&b.name),
+                    // More specific layers first
+                    TieBreak::Layer => self.rule_layer(b).cmp(&self.rule_layer(a)),
+                })
+            },
+        )
+    }
 }
 
 impl Default for Config {
@@ -164,6 +1122,12 @@ impl Default for Config {
             version: "1.0".to_string(),
             rules: Vec::new(),
             settings: Settings::default(),
+            zones: Vec::new(),
+            policies: None,
+            encrypted_rules: Vec::new(),
+            sealed_rules: Vec::new(),
+            rule_layers: BTreeMap::new(),
+            declared_settings: serde_yaml::Mapping::new(),
         }
     }
 }
@@ -190,16 +1154,49 @@ mod tests {
                     directories: None,
                     operations: None,
                     command_match: None,
+                    zone: None,
+                    exclude_tools: None,
+                    exclude_extensions: None,
+                    exclude_directories: None,
+                    command_not_match: None,
+                    tool_output_match: None,
+                    exit_code: None,
+                    prompt_match: None,
+                    background: None,
+                    protected_paths: None,
+                    expr: None,
+                    any: None,
+                    all: None,
+                    not: None,
+                    policy: None,
                 },
                 actions: crate::models::Actions {
                     inject: None,
                     run: None,
                     block: Some(true),
                     block_if_match: None,
+                    truncate_output: None,
+                    filter_results: None,
+                    structural_checks: None,
+                    ensure_header: None,
+                    inject_command: None,
+                    ask: None,
+                    require_files: None,
+                    allow: None,
+                    inject_once_per_session: None,
+                    throttle: None,
+                    enrich_prompt: None,
+                    rewrite: None,
                 },
                 mode: None,
                 priority: None,
                 governance: None,
+                tests: None,
+                exemptions: None,
+                escalation: None,
+                bypass_users: None,
+                messages: None,
+                stop: None,
                 metadata: Some(RuleMetadata {
                     priority: 0,
                     timeout: 5,
@@ -207,6 +1204,12 @@ mod tests {
                 }),
             }],
             settings: Settings::default(),
+            zones: Vec::new(),
+            policies: None,
+            encrypted_rules: Vec::new(),
+            sealed_rules: Vec::new(),
+            rule_layers: BTreeMap::new(),
+            declared_settings: serde_yaml::Mapping::new(),
         };
 
         assert!(config.validate().is_ok());
@@ -226,16 +1229,49 @@ mod tests {
                         directories: None,
                         operations: None,
                         command_match: None,
+                        zone: None,
+                        exclude_tools: None,
+                        exclude_extensions: None,
+                        exclude_directories: None,
+                        command_not_match: None,
+                        tool_output_match: None,
+                        exit_code: None,
+                        prompt_match: None,
+                        background: None,
+                        protected_paths: None,
+                        expr: None,
+                        any: None,
+                        all: None,
+                        not: None,
+                        policy: None,
                     },
                     actions: crate::models::Actions {
                         inject: None,
                         run: None,
                         block: Some(true),
                         block_if_match: None,
+                        truncate_output: None,
+                        filter_results: None,
+                        structural_checks: None,
+                        ensure_header: None,
+                        inject_command: None,
+                        ask: None,
+                        require_files: None,
+                        allow: None,
+                        inject_once_per_session: None,
+                        throttle: None,
+                        enrich_prompt: None,
+                        rewrite: None,
                     },
                     mode: None,
                     priority: None,
                     governance: None,
+                    tests: None,
+                    exemptions: None,
+                    escalation: None,
+                    bypass_users: None,
+                    messages: None,
+                    stop: None,
                     metadata: None,
                 },
                 Rule {
@@ -247,25 +1283,342 @@ mod tests {
                         directories: None,
                         operations: None,
                         command_match: None,
+                        zone: None,
+                        exclude_tools: None,
+                        exclude_extensions: None,
+                        exclude_directories: None,
+                        command_not_match: None,
+                        tool_output_match: None,
+                        exit_code: None,
+                        prompt_match: None,
+                        background: None,
+                        protected_paths: None,
+                        expr: None,
+                        any: None,
+                        all: None,
+                        not: None,
+                        policy: None,
                     },
                     actions: crate::models::Actions {
                         inject: None,
                         run: None,
                         block: Some(false),
                         block_if_match: None,
+                        truncate_output: None,
+                        filter_results: None,
+                        structural_checks: None,
+                        ensure_header: None,
+                        inject_command: None,
+                        ask: None,
+                        require_files: None,
+                        allow: None,
+                        inject_once_per_session: None,
+                        throttle: None,
+                        enrich_prompt: None,
+                        rewrite: None,
                     },
                     mode: None,
                     priority: None,
                     governance: None,
+                    tests: None,
+                    exemptions: None,
+                    escalation: None,
+                    bypass_users: None,
+                    messages: None,
+                    stop: None,
                     metadata: None,
                 },
             ],
             settings: Settings::default(),
+            zones: Vec::new(),
+            policies: None,
+            encrypted_rules: Vec::new(),
+            sealed_rules: Vec::new(),
+            rule_layers: BTreeMap::new(),
+            declared_settings: serde_yaml::Mapping::new(),
         };
 
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_exemption_requires_justification() {
+        let yaml = r#"
+version: "1.0"
+rules:
+  - name: block-force-push
+    matchers:
+      command_match: "git push.*--force"
+    actions:
+      block: true
+    exemptions:
+      - paths: [vendor/**]
+        justification: "  "
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("empty justification"));
+
+        // Omitting the justification entirely fails to parse
+        let missing = yaml.replace("        justification: \"  \"\n", "");
+        assert!(serde_yaml::from_str::<Config>(&missing).is_err());
+    }
+
+    #[test]
+    fn test_empty_any_block_is_rejected() {
+        let yaml = r#"
+version: "1.0"
+rules:
+  - name: never
+    matchers:
+      not:
+        any: []
+    actions:
+      block: true
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("empty `any` block"));
+    }
+
+    #[test]
+    fn test_invalid_patterns_are_rejected() {
+        let yaml = r#"
+version: "1.0"
+rules:
+  - name: nested
+    matchers:
+      any:
+        - command_match: "git push"
+        - not: { command_not_match: "rm -rf (/" }
+    actions:
+      block: true
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(
+            err.contains(
+                "Invalid command_not_match pattern in rule 'nested': \"rm -rf (/\": unclosed group at column 8"
+            ),
+            "{}",
+            err
+        );
+
+        let fixed: Config = serde_yaml::from_str(&yaml.replace("(/", "/")).unwrap();
+        assert!(fixed.validate().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_expressions_are_rejected() {
+        let yaml = r#"
+version: "1.0"
+rules:
+  - name: large-src-writes
+    matchers:
+      all:
+        - expr: 'path.startsWith("src/") && size(content) > 10000 && brnch != "main"'
+    actions:
+      block: true
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(
+            err.contains("Invalid expr in rule 'large-src-writes'")
+                && err.contains("unknown field 'brnch' at column 53"),
+            "{}",
+            err
+        );
+
+        let fixed: Config = serde_yaml::from_str(&yaml.replace("brnch", "branch")).unwrap();
+        assert!(fixed.validate().is_ok());
+    }
+
+    #[test]
+    fn test_event_archive_rejects_parquet() {
+        let yaml = r#"
+version: "1.0"
+settings:
+  event_archive:
+    format: parquet
+rules: []
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("parquet is not supported"), "{}", err);
+
+        let fixed: Config = serde_yaml::from_str(&yaml.replace("parquet", "jsonl")).unwrap();
+        assert!(fixed.validate().is_ok());
+        let archive = fixed.settings.event_archive.unwrap();
+        assert!(archive.default_redactions);
+    }
+
+    #[test]
+    fn test_strict_parsing_follows_version() {
+        let typo = r#"
+version: "2.0"
+rules:
+  - name: block-force-push
+    matchers:
+      commands_match: "git push.*--force"
+    actions:
+      block: true
+"#;
+        let mut file = NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, typo.as_bytes()).unwrap();
+        let err = Config::from_file(file.path()).unwrap_err();
+        assert!(format!("{:#}", err).contains("unknown field `commands_match`"));
+
+        // Opting out, or a 1.x config, keeps the lenient behavior
+        let lenient = [
+            typo.replace("version: \"2.0\"", "version: \"1.0\""),
+            format!("{}settings:\n  strict_parsing: false\n", typo),
+        ];
+        for yaml in lenient {
+            let mut file = NamedTempFile::new().unwrap();
+            std::io::Write::write_all(&mut file, yaml.as_bytes()).unwrap();
+            assert!(Config::from_file(file.path()).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_priority_band_warnings() {
+        let yaml = r#"
+version: "1.0"
+rules:
+  - name: block-secrets
+    priority: 1200
+    governance:
+      tags: [security]
+    matchers: {}
+    actions: { block: true }
+  - name: lint-context
+    priority: 1000
+    governance:
+      tags: [quality]
+    matchers: {}
+    actions: { inject: .claude/context/lint.md }
+  - name: block-force-push
+    priority: 1200
+    matchers: {}
+    actions: { block: true }
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        let warnings = config.priority_warnings();
+
+        assert_eq!(warnings.len(), 2);
+        assert!(
+            warnings[0]
+                .contains("'lint-context' has priority 1000 outside its 'quality' band (500..999)")
+        );
+        assert!(warnings[1].contains(
+            "'block-secrets', 'block-force-push' share priority 1200 and are ordered by file order"
+        ));
+
+        let mut config = config;
+        config.settings.priority_tie_break = vec![TieBreak::Layer];
+        let warnings = config.priority_warnings();
+        assert!(warnings[1].ends_with("are ordered by layer, then file order"));
+
+        config.settings.priority_tie_break = vec![TieBreak::Layer, TieBreak::Name];
+        assert_eq!(config.priority_warnings().len(), 1);
+    }
+
+    #[test]
+    fn test_expired_rules_are_demoted_and_flagged() {
+        let yaml = r#"
+version: "1.0"
+rules:
+  - name: freeze
+    matchers: {}
+    actions: { block: true }
+    governance: { expires: 2026-03-01 }
+  - name: legacy-check
+    mode: warn
+    matchers: {}
+    actions: { block: true }
+    governance: { review_by: 2026-02-01 }
+"#;
+        let mut config: Config = serde_yaml::from_str(yaml).unwrap();
+        let day = |d: &str| d.parse::<chrono::NaiveDate>().unwrap();
+
+        assert_eq!(
+            config.rule_mode(&config.rules[0], day("2026-02-28")),
+            PolicyMode::Enforce
+        );
+        assert_eq!(
+            config.rule_mode(&config.rules[0], day("2026-03-01")),
+            PolicyMode::Audit
+        );
+        assert!(config.staleness_warnings(day("2026-01-15")).is_empty());
+        assert_eq!(
+            config.staleness_warnings(day("2026-03-01")),
+            vec![
+                "Rule 'freeze' expired on 2026-03-01 and runs in 'audit' mode",
+                "Rule 'legacy-check' review was due by 2026-02-01",
+            ]
+        );
+
+        // Demotion never makes a rule stricter
+        config.settings.expired_rule_mode = PolicyMode::Warn;
+        config.rules[1].governance.as_mut().unwrap().expires = Some(day("2026-03-01"));
+        config.rules[1].mode = Some(PolicyMode::Audit);
+        assert_eq!(
+            config.rule_mode(&config.rules[0], day("2026-03-01")),
+            PolicyMode::Warn
+        );
+        assert_eq!(
+            config.rule_mode(&config.rules[1], day("2026-03-01")),
+            PolicyMode::Audit
+        );
+    }
+
+    #[test]
+    fn test_priority_tie_break() {
+        let mut config: Config = serde_yaml::from_str(
+            r#"
+version: "1.0"
+rules:
+  - name: zeta
+    matchers: { tools: [Bash] }
+    actions: { block: true }
+  - name: alpha
+    matchers: { tools: [Bash] }
+    actions: { block: true }
+  - name: org
+    matchers: { tools: [Bash] }
+    actions: { block: true }
+  - name: first
+    priority: 10
+    matchers: { tools: [Bash] }
+    actions: { block: true }
+"#,
+        )
+        .unwrap();
+        config
+            .rule_layers
+            .insert("org".to_string(), RuleLayer::System);
+        let order = |config: &Config| -> Vec<String> {
+            config
+                .enabled_rules()
+                .iter()
+                .map(|r| r.name.clone())
+                .collect()
+        };
+
+        assert_eq!(order(&config), ["first", "zeta", "alpha", "org"]);
+        assert_eq!(config.tie_break_order(), "file order");
+
+        config.settings.priority_tie_break = vec![TieBreak::Name];
+        assert_eq!(order(&config), ["first", "alpha", "org", "zeta"]);
+
+        config.settings.priority_tie_break = vec![TieBreak::Layer, TieBreak::Name];
+        assert_eq!(order(&config), ["first", "alpha", "zeta", "org"]);
+        assert_eq!(
+            config.tie_break_order(),
+            "layer, then name, then file order"
+        );
+    }
+
     #[test]
     fn test_rule_priority_sorting() {
         let config = Config {
@@ -280,16 +1633,49 @@ mod tests {
                         directories: None,
                         operations: None,
                         command_match: None,
+                        zone: None,
+                        exclude_tools: None,
+                        exclude_extensions: None,
+                        exclude_directories: None,
+                        command_not_match: None,
+                        tool_output_match: None,
+                        exit_code: None,
+                        prompt_match: None,
+                        background: None,
+                        protected_paths: None,
+                        expr: None,
+                        any: None,
+                        all: None,
+                        not: None,
+                        policy: None,
                     },
                     actions: crate::models::Actions {
                         inject: None,
                         run: None,
                         block: Some(true),
                         block_if_match: None,
+                        truncate_output: None,
+                        filter_results: None,
+                        structural_checks: None,
+                        ensure_header: None,
+                        inject_command: None,
+                        ask: None,
+                        require_files: None,
+                        allow: None,
+                        inject_once_per_session: None,
+                        throttle: None,
+                        enrich_prompt: None,
+                        rewrite: None,
                     },
                     mode: None,
                     priority: None,
                     governance: None,
+                    tests: None,
+                    exemptions: None,
+                    escalation: None,
+                    bypass_users: None,
+                    messages: None,
+                    stop: None,
                     metadata: Some(RuleMetadata {
                         priority: 0,
                         timeout: 5,
@@ -305,16 +1691,49 @@ mod tests {
                         directories: None,
                         operations: None,
                         command_match: None,
+                        zone: None,
+                        exclude_tools: None,
+                        exclude_extensions: None,
+                        exclude_directories: None,
+                        command_not_match: None,
+                        tool_output_match: None,
+                        exit_code: None,
+                        prompt_match: None,
+                        background: None,
+                        protected_paths: None,
+                        expr: None,
+                        any: None,
+                        all: None,
+                        not: None,
+                        policy: None,
                     },
                     actions: crate::models::Actions {
                         inject: None,
                         run: None,
                         block: Some(false),
                         block_if_match: None,
+                        truncate_output: None,
+                        filter_results: None,
+                        structural_checks: None,
+                        ensure_header: None,
+                        inject_command: None,
+                        ask: None,
+                        require_files: None,
+                        allow: None,
+                        inject_once_per_session: None,
+                        throttle: None,
+                        enrich_prompt: None,
+                        rewrite: None,
                     },
                     mode: None,
                     priority: None,
                     governance: None,
+                    tests: None,
+                    exemptions: None,
+                    escalation: None,
+                    bypass_users: None,
+                    messages: None,
+                    stop: None,
                     metadata: Some(RuleMetadata {
                         priority: 10,
                         timeout: 5,
@@ -323,6 +1742,12 @@ mod tests {
                 },
             ],
             settings: Settings::default(),
+            zones: Vec::new(),
+            policies: None,
+            encrypted_rules: Vec::new(),
+            sealed_rules: Vec::new(),
+            rule_layers: BTreeMap::new(),
+            declared_settings: serde_yaml::Mapping::new(),
         };
 
         let enabled_rules = config.enabled_rules();
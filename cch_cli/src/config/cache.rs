@@ -0,0 +1,240 @@
+//! In-memory config cache for long-running processes
+//!
+//! The daemon and the debug REPL evaluate many events, so the merged config
+//! of each project root is kept in memory and reused until one of its
+//! sources changes. Sources are every hooks.yaml layer and plugin fragment
+//! that could contribute to the config, whether or not it exists yet.
+//!
+//! Sources are polled rather than watched: each lookup compares their
+//! modification times and sizes with those seen at the last load, which
+//! costs a few `stat` calls per event and works on any platform without a
+//! file-watching service. When those match, the files' SHA-256 content
+//! hashes are compared too, so an edit that keeps the size and lands within
+//! the filesystem's mtime granularity is still picked up, at the cost of
+//! reading the (small) config files on each lookup.
+//!
+//! A reload that fails keeps serving the last good config for that root and
+//! reports the error once, so saving a half-edited hooks.yaml doesn't take
+//! the process down. Only a root that has never loaded returns the error.
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+use sha2::{Digest, Sha256};
+
+use super::{Config, plugins};
+
+/// Modification time and size of each source path (`None`: missing)
+type Fingerprint = Vec<(PathBuf, Option<(SystemTime, u64)>)>;
+
+/// SHA-256 of each source's content, in fingerprint order (`None`: missing,
+/// unreadable or a directory)
+type ContentHashes = Vec<Option<[u8; 32]>>;
+
+/// Loaded configs keyed by project root
+#[derive(Default)]
+pub struct ConfigCache {
+    entries: Mutex<HashMap<PathBuf, Entry>>,
+}
+
+struct Entry {
+    config: Arc<Config>,
+    sources: Fingerprint,
+    hashes: ContentHashes,
+}
+
+/// The outcome of a cache lookup
+pub struct Lookup {
+    pub config: Arc<Config>,
+    /// Whether the config was loaded from disk by this lookup
+    pub reloaded: bool,
+    /// Why a reload failed; `config` is then the last good one
+    pub error: Option<anyhow::Error>,
+}
+
+/// The cache shared by everything in this process
+pub fn shared() -> &'static ConfigCache {
+    static CACHE: OnceLock<ConfigCache> = OnceLock::new();
+    CACHE.get_or_init(ConfigCache::default)
+}
+
+impl ConfigCache {
+    /// The config for `project_root`, logging a failed reload
+    pub fn get(&self, project_root: Option<&Path>) -> Result<Arc<Config>> {
+        let lookup = self.lookup(project_root)?;
+        if let Some(e) = lookup.error {
+            tracing::error!("Config reload failed, keeping the previous config: {:#}", e);
+        }
+        Ok(lookup.config)
+    }
+
+    /// The config for `project_root`, reloaded if any source changed
+    pub fn lookup(&self, project_root: Option<&Path>) -> Result<Lookup> {
+        let root = effective_root(project_root);
+        let sources = fingerprint(project_root)?;
+        let mut entries = self
+            .entries
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        let mut hashes = None;
+        if let Some(entry) = entries.get(&root) {
+            if entry.sources == sources {
+                let current = content_hashes(&sources);
+                if entry.hashes == current {
+                    return Ok(Lookup {
+                        config: Arc::clone(&entry.config),
+                        reloaded: false,
+                        error: None,
+                    });
+                }
+                hashes = Some(current);
+            }
+        }
+        let hashes = hashes.unwrap_or_else(|| content_hashes(&sources));
+
+        match (Config::load(project_root), entries.get_mut(&root)) {
+            (Ok(config), _) => {
+                let config = Arc::new(config);
+                entries.insert(
+                    root,
+                    Entry {
+                        config: Arc::clone(&config),
+                        sources,
+                        hashes,
+                    },
+                );
+                Ok(Lookup {
+                    config,
+                    reloaded: true,
+                    error: None,
+                })
+            }
+            // Don't retry (or report) the broken sources until they change
+            (Err(e), Some(entry)) => {
+                entry.sources = sources;
+                entry.hashes = hashes;
+                Ok(Lookup {
+                    config: Arc::clone(&entry.config),
+                    reloaded: false,
+                    error: Some(e),
+                })
+            }
+            (Err(e), None) => Err(e),
+        }
+    }
+}
+
+impl Config {
+    /// Like [`Config::load`], but served from the process-wide cache
+    pub fn load_cached(project_root: Option<&Path>) -> Result<Arc<Config>> {
+        shared().get(project_root)
+    }
+}
+
+fn effective_root(project_root: Option<&Path>) -> PathBuf {
+    project_root
+        .map(Path::to_path_buf)
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_default()
+}
+
+/// Every path whose change can change the loaded config
+fn fingerprint(project_root: Option<&Path>) -> Result<Fingerprint> {
+    let claude_dirs = Config::claude_dirs(project_root)?;
+    let mut paths = vec![Config::system_path()];
+    for dir in &claude_dirs {
+        paths.push(dir.join("hooks.yaml"));
+        // Adding or removing a plugin changes its directory
+        paths.push(dir.join("plugins"));
+    }
+    paths.extend(
+        plugins::fragments(&claude_dirs)
+            .into_iter()
+            .map(|(_, path)| path),
+    );
+
+    Ok(paths
+        .into_iter()
+        .map(|path| {
+            let stamp = std::fs::metadata(&path)
+                .ok()
+                .and_then(|m| Some((m.modified().ok()?, m.len())));
+            (path, stamp)
+        })
+        .collect())
+}
+
+fn content_hashes(sources: &Fingerprint) -> ContentHashes {
+    sources
+        .iter()
+        .map(|(path, _)| {
+            let content = std::fs::read(path).ok()?;
+            Some(Sha256::digest(&content).into())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reloads_on_change_and_keeps_last_good_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let path = root.join(".claude/hooks.yaml");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let rule = |name: &str| {
+            format!(
+                "version: \"1.0\"\nrules:\n  - name: {}\n    matchers: {{ tools: [Bash] }}\n    actions: {{ block: true }}\n",
+                name
+            )
+        };
+        let names = |config: &Config| -> Vec<String> {
+            config.rules.iter().map(|r| r.name.clone()).collect()
+        };
+
+        let cache = ConfigCache::default();
+        std::fs::write(&path, rule("first")).unwrap();
+        let lookup = cache.lookup(Some(root)).unwrap();
+        assert!(lookup.reloaded);
+        assert!(names(&lookup.config).contains(&"first".to_string()));
+
+        let lookup = cache.lookup(Some(root)).unwrap();
+        assert!(!lookup.reloaded);
+
+        std::fs::write(&path, rule("second-rule")).unwrap();
+        let lookup = cache.lookup(Some(root)).unwrap();
+        assert!(lookup.reloaded);
+        assert!(names(&lookup.config).contains(&"second-rule".to_string()));
+
+        // An edit keeping the size and mtime is caught by the content hash
+        let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+        std::fs::write(&path, rule("second-ruli")).unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        let lookup = cache.lookup(Some(root)).unwrap();
+        assert!(lookup.reloaded);
+        assert!(names(&lookup.config).contains(&"second-ruli".to_string()));
+
+        // A broken edit is reported once while the last good config stays
+        std::fs::write(&path, "rules: [\n").unwrap();
+        let lookup = cache.lookup(Some(root)).unwrap();
+        assert!(lookup.error.is_some());
+        assert!(names(&lookup.config).contains(&"second-ruli".to_string()));
+        let lookup = cache.lookup(Some(root)).unwrap();
+        assert!(lookup.error.is_none());
+        assert!(names(&lookup.config).contains(&"second-ruli".to_string()));
+
+        // Without a good config to fall back on, the error is returned
+        assert!(ConfigCache::default().lookup(Some(root)).is_err());
+    }
+}
@@ -0,0 +1,419 @@
+//! Comment-preserving edits to hooks.yaml
+//!
+//! Commands that change a config file edit its text rather than writing
+//! back a re-serialized document, so comments, key order, quoting and
+//! blank lines survive everywhere except the lines an edit replaces. Rules
+//! are addressed by name within the top-level `rules:` sequence:
+//!
+//! - renaming touches only the rule's `name:` line
+//! - replacing a rule rewrites its item but keeps the comments above it
+//! - appended rules follow the indentation of the existing items
+//!
+//! `save` re-parses the edited file and restores the original if the edit
+//! left it unparseable.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::ops::Range;
+use std::path::Path;
+
+use super::Config;
+use crate::models::Rule;
+
+/// A config file's text, edited in place
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigDocument {
+    content: String,
+}
+
+/// One item of the `rules:` sequence
+struct RuleItem {
+    name: Option<String>,
+    /// Bytes from the item's `-` through its last line
+    span: Range<usize>,
+}
+
+/// Where the `rules:` key is and what follows it
+enum RulesSection {
+    /// No top-level `rules:` key
+    Missing,
+    /// `rules:` with no items; new items go at byte `insert_at`
+    Empty { insert_at: usize },
+    /// `rules: []` on the line spanning these bytes
+    EmptyFlow { line: Range<usize> },
+    /// A block sequence of items indented by `indent`
+    Block { indent: usize, items: Vec<RuleItem> },
+}
+
+impl ConfigDocument {
+    /// Wrap config text for editing
+    pub fn new(content: impl Into<String>) -> Self {
+        Self {
+            content: content.into(),
+        }
+    }
+
+    /// Read a config file, or start an empty config if it doesn't exist
+    pub fn read(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(Self::new(content)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Ok(Self::new("version: \"1.0\"\n"))
+            }
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    /// The edited text
+    pub fn as_str(&self) -> &str {
+        &self.content
+    }
+
+    /// Write the document to `path`, restoring the previous file if the
+    /// result doesn't load as a config
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let previous = std::fs::read_to_string(path).ok();
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, &self.content)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        if let Err(e) = Config::from_file(path) {
+            match previous {
+                Some(previous) => std::fs::write(path, previous)
+                    .with_context(|| format!("Failed to restore {}", path.display()))?,
+                None => std::fs::remove_file(path)?,
+            }
+            return Err(e.context(format!("Edit left {} invalid; restored it", path.display())));
+        }
+        Ok(())
+    }
+
+    /// Rename rule `old`, editing only its `name:` line
+    pub fn rename_rule(&mut self, old: &str, new: &str) -> Result<()> {
+        let span = self.rule_span(old)?;
+        let line = Regex::new(&format!(
+            r#"(?m)^(\s*(?:-\s+)?name:\s*)(["']?){}(["']?\s*(?:#.*)?)$"#,
+            regex::escape(old)
+        ))?;
+        let item = &self.content[span.clone()];
+        let renamed = match line.find_iter(item).count() {
+            1 => line
+                .replace(item, |caps: &regex::Captures| {
+                    format!("{}{}{}{}", &caps[1], &caps[2], new, &caps[3])
+                })
+                .into_owned(),
+            0 => anyhow::bail!("no `name: {}` line found", old),
+            n => anyhow::bail!("{} lines declare `name: {}`", n, old),
+        };
+        self.content.replace_range(span, &renamed);
+        Ok(())
+    }
+
+    /// Replace rule `name` with `rule`, keeping the comments above it
+    pub fn replace_rule(&mut self, name: &str, rule: &Rule) -> Result<()> {
+        let span = self.rule_span(name)?;
+        let RulesSection::Block { indent, .. } = self.rules_section()? else {
+            unreachable!("rule_span found an item");
+        };
+        let mut rendered = render_item(rule, indent)?;
+        rendered.pop(); // the item's own line break stays in place
+        self.content.replace_range(span, &rendered);
+        Ok(())
+    }
+
+    /// Add `rule` after the last rule
+    pub fn append_rule(&mut self, rule: &Rule) -> Result<()> {
+        match self.rules_section()? {
+            RulesSection::Missing => {
+                let rendered = format!("rules:\n{}", render_item(rule, 2)?);
+                self.insert_line(self.content.len(), &rendered);
+            }
+            RulesSection::Empty { insert_at } => {
+                let rendered = render_item(rule, 2)?;
+                self.insert_line(insert_at, &rendered);
+            }
+            RulesSection::EmptyFlow { line } => {
+                // Keep any comment after the `[]`
+                let text = &self.content[line.clone()];
+                let after = &text[text.find("[]").map_or(text.len(), |i| i + 2)..];
+                let replacement = format!("rules:{}\n{}", after.trim_end(), render_item(rule, 2)?);
+                let end = (line.end + 1).min(self.content.len());
+                self.content.replace_range(line.start..end, &replacement);
+            }
+            RulesSection::Block { indent, items } => {
+                let end = items.last().map_or(0, |item| item.span.end);
+                let next_line = self.content[end..]
+                    .find('\n')
+                    .map_or(self.content.len(), |i| end + i + 1);
+                let rendered = render_item(rule, indent)?;
+                self.insert_line(next_line, &rendered);
+            }
+        }
+        Ok(())
+    }
+
+    /// Insert whole lines at byte `at`, the start of a line or the end of
+    /// the text
+    fn insert_line(&mut self, at: usize, lines: &str) {
+        if at == self.content.len() && !self.content.is_empty() && !self.content.ends_with('\n') {
+            self.content.push('\n');
+            self.content.push_str(lines);
+        } else {
+            self.content.insert_str(at, lines);
+        }
+    }
+
+    /// Byte span of the item declaring rule `name`
+    fn rule_span(&self, name: &str) -> Result<Range<usize>> {
+        let RulesSection::Block { items, .. } = self.rules_section()? else {
+            anyhow::bail!("no rules section");
+        };
+        let mut found = items
+            .into_iter()
+            .filter(|i| i.name.as_deref() == Some(name));
+        match (found.next(), found.next()) {
+            (Some(item), None) => Ok(item.span),
+            (None, _) => anyhow::bail!("no rule named '{}'", name),
+            (Some(_), Some(_)) => anyhow::bail!("several rules are named '{}'", name),
+        }
+    }
+
+    fn rules_section(&self) -> Result<RulesSection> {
+        let mut lines = Vec::new();
+        let mut offset = 0;
+        for line in self.content.split_inclusive('\n') {
+            lines.push((offset, line.trim_end_matches(['\n', '\r'])));
+            offset += line.len();
+        }
+
+        let Some(key) = lines.iter().position(|(_, line)| {
+            line.strip_prefix("rules:")
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t']))
+        }) else {
+            return Ok(RulesSection::Missing);
+        };
+        let (key_start, key_line) = lines[key];
+        let value = strip_comment(&key_line["rules:".len()..]).trim();
+        if value == "[]" {
+            return Ok(RulesSection::EmptyFlow {
+                line: key_start..key_start + key_line.len(),
+            });
+        }
+        if !value.is_empty() {
+            anyhow::bail!("`rules:` isn't a block sequence; edit it by hand");
+        }
+
+        let after_key = lines.get(key + 1).map_or(self.content.len(), |(o, _)| *o);
+        let mut indent = None;
+        let mut items: Vec<RuleItem> = Vec::new();
+        for &(start, line) in &lines[key + 1..] {
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() {
+                continue;
+            }
+            let depth = line.len() - trimmed.len();
+            let is_dash = trimmed == "-" || trimmed.starts_with("- ");
+            // Lines nested deeper than the dashes belong to the current item
+            if let (Some(indent), Some(item)) = (indent, items.last_mut()) {
+                if depth > indent {
+                    item.span.end = start + line.len();
+                    continue;
+                }
+            }
+            // Comments between items (or the section end) stay where they are
+            if trimmed.starts_with('#') {
+                continue;
+            }
+            if !is_dash || indent.is_some_and(|indent| indent != depth) {
+                break;
+            }
+            indent = Some(depth);
+            items.push(RuleItem {
+                name: None,
+                span: start..start + line.len(),
+            });
+        }
+
+        let Some(indent) = indent else {
+            return Ok(RulesSection::Empty {
+                insert_at: after_key,
+            });
+        };
+        for item in &mut items {
+            item.name = item_name(&self.content[item.span.clone()], indent);
+        }
+        Ok(RulesSection::Block { indent, items })
+    }
+}
+
+/// The `name` of a sequence item's mapping, if it parses
+fn item_name(item: &str, indent: usize) -> Option<String> {
+    let dedented: String = item
+        .split_inclusive('\n')
+        .map(|line| line.get(indent..).unwrap_or(line.trim_start()))
+        .collect();
+    let parsed: Vec<serde_yaml::Value> = serde_yaml::from_str(&dedented).ok()?;
+    parsed.first()?.get("name")?.as_str().map(String::from)
+}
+
+/// `rule` as a sequence item at `indent`, ending in a line break
+fn render_item(rule: &Rule, indent: usize) -> Result<String> {
+    let yaml = serde_yaml::to_string(rule).context("Failed to serialize rule")?;
+    let mut item = String::new();
+    for (i, line) in yaml.lines().enumerate() {
+        item.push_str(&" ".repeat(indent));
+        item.push_str(if i == 0 { "- " } else { "  " });
+        item.push_str(line);
+        item.push('\n');
+    }
+    Ok(item)
+}
+
+/// `value` without a trailing ` # comment`
+fn strip_comment(value: &str) -> &str {
+    value.find(" #").map_or(value, |i| &value[..i])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"version: "1.0"
+
+# Policy owned by platform
+rules:
+  # Keep force pushes off main
+  - name: "no-force"   # owned by platform
+    description: Reported as no-force
+    matchers: { tools: [Bash], command_match: "git push --force" }
+    actions:
+      block: true
+
+  - description: |
+      # Not a comment
+      Multi-line
+    name: docs
+    matchers: { tools: [Write] }
+    actions: { inject: docs.md }
+settings:
+  fail_open: true   # keep working if a validator breaks
+"#;
+
+    fn rule(name: &str) -> Rule {
+        serde_yaml::from_str(&format!(
+            "name: {}\nmatchers: {{ tools: [Read] }}\nactions: {{ block: true }}",
+            name
+        ))
+        .unwrap()
+    }
+
+    fn rules(document: &ConfigDocument) -> Vec<String> {
+        let parsed: serde_yaml::Value = serde_yaml::from_str(document.as_str()).unwrap();
+        let rules: Vec<Rule> = serde_yaml::from_value(parsed["rules"].clone()).unwrap();
+        rules.into_iter().map(|r| r.name).collect()
+    }
+
+    #[test]
+    fn test_rename_rule_keeps_formatting() {
+        let mut document = ConfigDocument::new(CONFIG);
+        document
+            .rename_rule("no-force", "block-force-push")
+            .unwrap();
+        assert_eq!(
+            document.as_str(),
+            CONFIG.replace("\"no-force\"", "\"block-force-push\"")
+        );
+        assert!(document.rename_rule("missing", "x").is_err());
+
+        let mut duplicated =
+            ConfigDocument::new(CONFIG.replace("settings:", "  - name: docs\nsettings:"));
+        assert!(duplicated.rename_rule("docs", "x").is_err());
+    }
+
+    #[test]
+    fn test_replace_rule_keeps_surrounding_comments() {
+        let mut document = ConfigDocument::new(CONFIG);
+        document
+            .replace_rule("no-force", &rule("no-reads"))
+            .unwrap();
+        assert_eq!(rules(&document), ["no-reads", "docs"]);
+        assert!(
+            document
+                .as_str()
+                .contains("  # Keep force pushes off main\n  - name: no-reads\n")
+        );
+        assert!(
+            document
+                .as_str()
+                .contains("block: true\n\n  - description: |")
+        );
+        assert!(
+            document
+                .as_str()
+                .contains("# keep working if a validator breaks")
+        );
+
+        document.replace_rule("docs", &rule("docs")).unwrap();
+        assert!(document.as_str().ends_with(
+            "    block: true\nsettings:\n  fail_open: true   # keep working if a validator breaks\n"
+        ));
+        assert!(document.replace_rule("missing", &rule("x")).is_err());
+    }
+
+    #[test]
+    fn test_append_rule() {
+        let mut document = ConfigDocument::new(CONFIG);
+        document.append_rule(&rule("no-reads")).unwrap();
+        assert_eq!(rules(&document), ["no-force", "docs", "no-reads"]);
+        assert!(document.as_str().contains("# Policy owned by platform"));
+        assert!(
+            document
+                .as_str()
+                .contains("    actions: { inject: docs.md }\n  - name: no-reads\n")
+        );
+
+        for (content, expected) in [
+            (
+                "version: \"1.0\"",
+                "version: \"1.0\"\nrules:\n  - name: a\n",
+            ),
+            ("rules:\nsettings: {}\n", "rules:\n  - name: a\n"),
+            (
+                "rules: []  # none yet\n",
+                "rules:  # none yet\n  - name: a\n",
+            ),
+            (
+                "rules:\n- { name: b, matchers: {}, actions: {} }",
+                "- { name: b, matchers: {}, actions: {} }\n- name: a\n",
+            ),
+        ] {
+            let mut document = ConfigDocument::new(content);
+            document.append_rule(&rule("a")).unwrap();
+            assert!(
+                document.as_str().contains(expected),
+                "{}",
+                document.as_str()
+            );
+            assert!(rules(&document).contains(&"a".to_string()));
+        }
+
+        let mut flow = ConfigDocument::new("rules: [{ name: a }]\n");
+        assert!(flow.append_rule(&rule("b")).is_err());
+    }
+
+    #[test]
+    fn test_save_restores_invalid_edits() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".claude/hooks.yaml");
+
+        let mut document = ConfigDocument::read(&path).unwrap();
+        document.append_rule(&rule("a")).unwrap();
+        document.save(&path).unwrap();
+        assert_eq!(Config::from_file(&path).unwrap().rules.len(), 1);
+
+        let broken = ConfigDocument::new("rules: [\n");
+        assert!(broken.save(&path).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), document.as_str());
+    }
+}
@@ -0,0 +1,125 @@
+//! Environment variable interpolation in hooks.yaml
+//!
+//! Before a config document is parsed, `${NAME}` is replaced with the value
+//! of the environment variable `NAME`, and `${NAME:-default}` falls back to
+//! `default` when `NAME` is unset or empty. A `${NAME}` without a default is
+//! required: loading fails and lists every unresolved one. `$${` produces
+//! a literal `${`, e.g. for shell variables in `inject_command`.
+//!
+//! Interpolation is textual, so it also applies inside comments.
+
+use anyhow::Result;
+
+/// Interpolate `content` from the process environment
+pub fn interpolate(content: &str) -> Result<String> {
+    interpolate_with(content, |name| std::env::var(name).ok())
+}
+
+/// Interpolate `content`, looking variables up with `lookup`
+pub fn interpolate_with(content: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String> {
+    let mut output = String::with_capacity(content.len());
+    let mut unresolved = Vec::new();
+    let mut rest = content;
+
+    while let Some(start) = rest.find("${") {
+        // `$${` escapes a literal `${`
+        if rest[..start].ends_with('$') {
+            output.push_str(&rest[..start - 1]);
+            output.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            output.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+        let expression = &after[..end];
+        let (name, default) = match expression.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (expression, None),
+        };
+
+        if !is_variable_name(name) {
+            // Not ours (e.g. a regex quantifier); keep it verbatim
+            output.push_str("${");
+            rest = after;
+            continue;
+        }
+
+        match (lookup(name).filter(|value| !value.is_empty()), default) {
+            (Some(value), _) => output.push_str(&value),
+            (None, Some(default)) => output.push_str(default),
+            (None, None) => {
+                let line = content[..content.len() - rest.len() + start]
+                    .matches('\n')
+                    .count()
+                    + 1;
+                unresolved.push(format!("{} (line {})", name, line));
+            }
+        }
+        rest = &after[end + 1..];
+    }
+    output.push_str(rest);
+
+    if !unresolved.is_empty() {
+        anyhow::bail!(
+            "Unresolved environment variables: {}. Set them, or give a default with ${{NAME:-default}}",
+            unresolved.join(", ")
+        );
+    }
+    Ok(output)
+}
+
+fn is_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "VALIDATORS" => Some("/opt/validators".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_interpolation_and_defaults() {
+        let content = "run: ${VALIDATORS}/check.sh\nurl: ${HOOK_URL:-http://localhost}\nlevel: ${EMPTY:-info}\n";
+        assert_eq!(
+            interpolate_with(content, lookup).unwrap(),
+            "run: /opt/validators/check.sh\nurl: http://localhost\nlevel: info\n"
+        );
+    }
+
+    #[test]
+    fn test_escapes_and_non_variables_are_kept() {
+        assert_eq!(
+            interpolate_with("cmd: echo $${BRANCH}\nmatch: a${2,3}\nopen: ${", lookup).unwrap(),
+            "cmd: echo ${BRANCH}\nmatch: a${2,3}\nopen: ${"
+        );
+    }
+
+    #[test]
+    fn test_unresolved_variables_are_reported() {
+        let err = interpolate_with("a: ${MISSING}\nb: ${ALSO_MISSING}\n", lookup)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("MISSING (line 1), ALSO_MISSING (line 2)"),
+            "{}",
+            err
+        );
+    }
+}
@@ -0,0 +1,134 @@
+//! Layered configuration
+//!
+//! `Config::load` merges every hooks.yaml that exists instead of using only
+//! the most specific one, so organization-wide rules keep applying after a
+//! project adds its own config. Layers are applied lowest precedence first:
+//!
+//! 1. the system file (`$CCH_SYSTEM_CONFIG`, default `/etc/cch/hooks.yaml`)
+//! 2. the user's `~/.claude/hooks.yaml`
+//! 3. the project's `.claude/hooks.yaml`
+//!
+//! A rule in a later layer replaces an earlier layer's rule of the same
+//! name, keeping its position. Settings are merged key by key, so a project
+//! that only sets `fail_open` still inherits the user's `log_level`; a
+//! nested setting such as `priority_bands` is replaced as a whole. Zones
+//! and encrypted sections accumulate, and `version` and `policies` come
+//! from the most specific layer that declares them.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+use super::{Config, RuleLayer, Settings};
+
+/// Load and merge the config files at `paths`, lowest precedence first
+pub(super) fn load(paths: &[PathBuf]) -> Result<Config> {
+    let mut merged: Option<Config> = None;
+    let mut settings = serde_yaml::Mapping::new();
+
+    // Each file is parsed once, and only the merged config is validated
+    for path in paths {
+        let layer = load_layer(path)?;
+        settings.extend(layer.declared_settings.clone());
+        merged = Some(match merged {
+            Some(lower) => overlay(lower, layer),
+            None => layer,
+        });
+    }
+
+    let mut config = merged.unwrap_or_default();
+    config.settings =
+        serde_yaml::from_value::<Settings>(serde_yaml::Value::Mapping(settings.clone()))
+            .context("Failed to merge settings across config layers")?;
+    config.declared_settings = settings;
+    config.validate()?;
+    Ok(config)
+}
+
+/// Load one config file, unvalidated, recording its layer for each rule
+pub(super) fn load_layer(path: &Path) -> Result<Config> {
+    let mut config = Config::load_file(path)?;
+    let layer = RuleLayer::of(path);
+    config.rule_layers = config
+        .rules
+        .iter()
+        .map(|rule| (rule.name.clone(), layer))
+        .collect();
+    Ok(config)
+}
+
+/// Apply `upper` on top of `lower`
+fn overlay(mut lower: Config, upper: Config) -> Config {
+    for rule in upper.rules {
+        match lower.rules.iter_mut().find(|r| r.name == rule.name) {
+            Some(existing) => *existing = rule,
+            None => lower.rules.push(rule),
+        }
+    }
+    lower.version = upper.version;
+    lower.zones.extend(upper.zones);
+    lower.policies = upper.policies.or(lower.policies);
+    lower.encrypted_rules.extend(upper.encrypted_rules);
+    lower.sealed_rules.extend(upper.sealed_rules);
+    lower.rule_layers.extend(upper.rule_layers);
+    lower
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layers_merge_rules_and_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let global = dir.path().join("global.yaml");
+        let project = dir.path().join("project.yaml");
+        std::fs::write(
+            &global,
+            r#"
+version: "1.0"
+settings:
+  log_level: debug
+  fail_open: false
+rules:
+  - name: org-no-force-push
+    matchers: { tools: [Bash], command_match: "git push --force" }
+    actions: { block: true }
+  - name: shared
+    matchers: { tools: [Write] }
+    actions: { block: true }
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            &project,
+            r#"
+version: "1.0"
+settings:
+  fail_open: true
+rules:
+  - name: shared
+    matchers: { tools: [Edit] }
+    actions: { block: true }
+  - name: project-only
+    matchers: { tools: [Read] }
+    actions: { block: false }
+"#,
+        )
+        .unwrap();
+
+        let config = load(&[global, project]).unwrap();
+        let names: Vec<&str> = config.rules.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["org-no-force-push", "shared", "project-only"]);
+        assert_eq!(
+            config.rules[1].matchers.tools.as_deref(),
+            Some(&["Edit".to_string()][..])
+        );
+
+        assert_eq!(config.settings.log_level, "debug");
+        assert!(config.settings.fail_open);
+        assert_eq!(
+            config.settings.script_timeout,
+            Settings::default().script_timeout
+        );
+    }
+}
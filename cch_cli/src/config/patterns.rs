@@ -0,0 +1,265 @@
+//! Duplicate and overlapping regex detection across rules
+//!
+//! A heuristic, not a regex equivalence checker: patterns are normalized
+//! (redundant `.*` at the edges, `[0-9]` vs `\d`) and compared for equality,
+//! and a pure literal pattern is reported as subsuming any pattern whose
+//! every match must contain that literal. Anything with groups or
+//! alternation is only compared for equality.
+
+/// A rule's pattern, as compared by [`overlaps`]
+pub struct RulePattern<'a> {
+    pub rule: &'a str,
+    pub pattern: &'a str,
+    /// Whether the rule blocks; subsumption only matters between blockers
+    pub blocks: bool,
+}
+
+/// Describe duplicate and subsumed patterns of one matcher kind
+pub fn overlaps(kind: &str, patterns: &[RulePattern]) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for (i, a) in patterns.iter().enumerate() {
+        for b in &patterns[i + 1..] {
+            let (na, nb) = (normalize(a.pattern), normalize(b.pattern));
+            if na == nb {
+                warnings.push(format!(
+                    "Rules '{}' and '{}' have duplicate {} patterns: {:?}",
+                    a.rule, b.rule, kind, a.pattern
+                ));
+            } else if a.blocks && b.blocks {
+                for (wide, narrow) in [(a, b), (b, a)] {
+                    if subsumes(&normalize(wide.pattern), &normalize(narrow.pattern)) {
+                        warnings.push(format!(
+                            "Rule '{}' {} {:?} already covers rule '{}' {:?}",
+                            wide.rule, kind, wide.pattern, narrow.rule, narrow.pattern
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    warnings
+}
+
+/// Rewrite a pattern into a canonical form for comparison
+fn normalize(pattern: &str) -> String {
+    let mut p = pattern.trim().replace("[0-9]", r"\d");
+
+    // Unanchored edges make `.*` (or an anchored `^.*` / `.*$`) redundant
+    for prefix in ["^.*", ".*"] {
+        if let Some(rest) = p.strip_prefix(prefix) {
+            p = rest.to_string();
+            break;
+        }
+    }
+    for suffix in [".*$", ".*"] {
+        if let Some(rest) = p.strip_suffix(suffix) {
+            if !ends_with_escape(rest) {
+                p = rest.to_string();
+                break;
+            }
+        }
+    }
+    p
+}
+
+/// Whether the next character after `s` would be escaped by a backslash
+fn ends_with_escape(s: &str) -> bool {
+    s.chars().rev().take_while(|&c| c == '\\').count() % 2 == 1
+}
+
+/// Whether every string matching `narrow` also matches `wide`
+///
+/// Only detected when `wide` is a pure literal contained in one of the
+/// literals every `narrow` match has to include.
+fn subsumes(wide: &str, narrow: &str) -> bool {
+    let Some((wide_literals, true)) = required_literals(wide) else {
+        return false;
+    };
+    let [literal] = wide_literals.as_slice() else {
+        return false;
+    };
+    required_literals(narrow)
+        .is_some_and(|(literals, _)| literals.iter().any(|l| l.contains(literal.as_str())))
+}
+
+/// Literal runs every match of `pattern` must contain, and whether the
+/// pattern is nothing but a single literal
+///
+/// Returns `None` for patterns with groups or alternation.
+fn required_literals(pattern: &str) -> Option<(Vec<String>, bool)> {
+    let mut literals = Vec::new();
+    let mut current = String::new();
+    let mut pure = true;
+    let mut chars = pattern.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '(' | ')' | '|' => return None,
+            '\\' => match chars.next()? {
+                e if e.is_ascii_alphanumeric() => {
+                    // Classes (\d, \s, ...) and escapes like \n end a run
+                    pure = false;
+                    flush(&mut current, &mut literals);
+                    skip_quantifier(&mut chars);
+                    continue;
+                }
+                e => current.push(e),
+            },
+            '[' => {
+                pure = false;
+                flush(&mut current, &mut literals);
+                let mut escaped = false;
+                for c in chars.by_ref() {
+                    match c {
+                        '\\' if !escaped => escaped = true,
+                        ']' if !escaped => break,
+                        _ => escaped = false,
+                    }
+                }
+                skip_quantifier(&mut chars);
+                continue;
+            }
+            '.' | '^' | '$' => {
+                pure = false;
+                flush(&mut current, &mut literals);
+                skip_quantifier(&mut chars);
+                continue;
+            }
+            c => current.push(c),
+        }
+
+        // A quantifier applies to the literal character just pushed
+        match chars.peek() {
+            Some('+') => {
+                pure = false;
+                skip_quantifier(&mut chars);
+            }
+            Some('*' | '?' | '{') => {
+                pure = false;
+                current.pop();
+                flush(&mut current, &mut literals);
+                skip_quantifier(&mut chars);
+            }
+            _ => {}
+        }
+    }
+    flush(&mut current, &mut literals);
+    Some((literals, pure))
+}
+
+/// End the current literal run
+fn flush(current: &mut String, literals: &mut Vec<String>) {
+    if !current.is_empty() {
+        literals.push(std::mem::take(current));
+    }
+}
+
+/// Consume a quantifier (and a lazy/possessive marker) if one follows
+fn skip_quantifier(chars: &mut std::iter::Peekable<std::str::Chars>) {
+    match chars.peek() {
+        Some('*' | '+' | '?') => {
+            chars.next();
+        }
+        Some('{') => {
+            for c in chars.by_ref() {
+                if c == '}' {
+                    break;
+                }
+            }
+        }
+        _ => return,
+    }
+    if chars.peek() == Some(&'?') {
+        chars.next();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern<'a>(rule: &'a str, pattern: &'a str) -> RulePattern<'a> {
+        RulePattern {
+            rule,
+            pattern,
+            blocks: true,
+        }
+    }
+
+    #[test]
+    fn test_normalized_duplicates() {
+        let warnings = overlaps(
+            "command_match",
+            &[
+                pattern("a", "git push.*--force"),
+                pattern("b", ".*git push.*--force.*"),
+            ],
+        );
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("Rules 'a' and 'b' have duplicate command_match"));
+
+        let warnings = overlaps(
+            "command_match",
+            &[pattern("a", r"v[0-9]+"), pattern("b", r"v\d+")],
+        );
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_literal_subsumes_narrower_pattern() {
+        let warnings = overlaps(
+            "command_match",
+            &[
+                pattern("force", "git push.*--force"),
+                pattern("any-push", "git push"),
+            ],
+        );
+        assert_eq!(
+            warnings,
+            vec![
+                r#"Rule 'any-push' command_match "git push" already covers rule 'force' "git push.*--force""#
+            ]
+        );
+    }
+
+    #[test]
+    fn test_no_false_subsumption() {
+        let cases = [
+            // Optional character: "git pus" need not contain "git push"
+            ("git push", "git push?"),
+            // Alternation is not analyzed
+            ("rm", "(rm|del) -rf"),
+            // Anything with metacharacters is not a literal
+            ("git.push", "git push --force"),
+        ];
+        for (wide, narrow) in cases {
+            assert!(
+                overlaps("command_match", &[pattern("w", wide), pattern("n", narrow)]).is_empty(),
+                "{} vs {}",
+                wide,
+                narrow
+            );
+        }
+
+        // Only blocking rules are compared for subsumption
+        let mut inject = pattern("docs", "git push");
+        inject.blocks = false;
+        assert!(overlaps("command_match", &[inject, pattern("n", "git push -f")]).is_empty());
+    }
+
+    #[test]
+    fn test_required_literals() {
+        assert_eq!(
+            required_literals(r"git\s+push.*\.env"),
+            Some((vec!["git".into(), "push".into(), ".env".into()], false))
+        );
+        assert_eq!(
+            required_literals("npm install"),
+            Some((vec!["npm install".into()], true))
+        );
+        assert_eq!(
+            required_literals("colou?r"),
+            Some((vec!["colo".into(), "r".into()], false))
+        );
+    }
+}
@@ -0,0 +1,176 @@
+//! Rule fragments shipped by Claude Code plugins
+//!
+//! A plugin can bring CCH rules in `.claude/plugins/<name>/cch/rules.yaml`,
+//! under the project or the user's home:
+//!
+//! ```yaml
+//! rules:
+//!   - name: terraform-plan-first
+//!     matchers: { tools: [Bash], command_match: "terraform apply" }
+//!     actions: { block: true }
+//! ```
+//!
+//! Fragment rules are merged after the configured ones and stamped with
+//! `created_by: plugin:<name>`, so `cch explain` shows where they came from.
+//! A configured rule of the same name always wins, which lets a project
+//! override or disable any plugin rule.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use super::Config;
+use crate::models::{GovernanceMetadata, PLUGIN_PROVENANCE_PREFIX, Rule};
+
+#[derive(Deserialize)]
+struct Fragment {
+    #[serde(default)]
+    rules: Vec<Rule>,
+}
+
+/// Every plugin fragment under the `.claude` directories, as (plugin, path),
+/// sorted by plugin name within each directory
+pub(super) fn fragments(claude_dirs: &[PathBuf]) -> Vec<(String, PathBuf)> {
+    let mut fragments = Vec::new();
+    for claude_dir in claude_dirs {
+        let Ok(entries) = std::fs::read_dir(claude_dir.join("plugins")) else {
+            continue;
+        };
+        let mut found: Vec<(String, PathBuf)> = entries
+            .filter_map(|entry| {
+                let plugin_dir = entry.ok()?.path();
+                let name = plugin_dir.file_name()?.to_str()?.to_string();
+                let path = plugin_dir.join("cch").join("rules.yaml");
+                path.is_file().then_some((name, path))
+            })
+            .collect();
+        found.sort();
+        fragments.extend(found);
+    }
+    fragments
+}
+
+/// Merge the rules of each fragment into `config`
+///
+/// A fragment that can't be read or is invalid is skipped with a warning
+/// when `fail_open` is set, and is an error otherwise.
+pub(super) fn merge(config: &mut Config, fragments: &[(String, PathBuf)]) -> Result<()> {
+    for (plugin, path) in fragments {
+        match load_fragment(plugin, path) {
+            Ok(rules) => {
+                for rule in rules {
+                    if config.rules.iter().any(|r| r.name == rule.name) {
+                        tracing::debug!(
+                            "Plugin '{}' rule '{}' is overridden by the configuration",
+                            plugin,
+                            rule.name
+                        );
+                        continue;
+                    }
+                    config.rules.push(rule);
+                }
+            }
+            Err(e) if config.settings.fail_open => {
+                tracing::warn!("Skipping plugin '{}' rules: {:#}", plugin, e);
+            }
+            Err(e) => return Err(e.context(format!("Plugin '{}'", plugin))),
+        }
+    }
+    Ok(())
+}
+
+fn load_fragment(plugin: &str, path: &Path) -> Result<Vec<Rule>> {
+    let content = Config::read(path)?;
+    let fragment: Fragment = serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    let mut rules = fragment.rules;
+    for rule in &mut rules {
+        rule.governance
+            .get_or_insert_with(GovernanceMetadata::default)
+            .created_by = Some(format!("{}{}", PLUGIN_PROVENANCE_PREFIX, plugin));
+    }
+
+    let fragment = Config {
+        rules,
+        ..Config::default()
+    };
+    fragment
+        .validate()
+        .with_context(|| format!("Invalid rules in {}", path.display()))?;
+    Ok(fragment.rules)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_fragment(claude_dir: &Path, plugin: &str, content: &str) {
+        let dir = claude_dir.join("plugins").join(plugin).join("cch");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("rules.yaml"), content).unwrap();
+    }
+
+    #[test]
+    fn test_fragments_merge_with_provenance() {
+        let dir = tempfile::tempdir().unwrap();
+        let claude_dir = dir.path().join(".claude");
+        write_fragment(
+            &claude_dir,
+            "terraform",
+            r#"
+rules:
+  - name: plan-first
+    matchers: { tools: [Bash], command_match: "terraform apply" }
+    actions: { block: true }
+    governance: { created_by: someone-else }
+  - name: shared
+    matchers: { tools: [Bash] }
+    actions: { block: true }
+"#,
+        );
+        std::fs::create_dir_all(claude_dir.join("plugins/no-rules")).unwrap();
+
+        let fragments = fragments(&[claude_dir]);
+        assert_eq!(fragments.len(), 1);
+        assert_eq!(fragments[0].0, "terraform");
+
+        let mut config: Config = serde_yaml::from_str(
+            r#"
+version: "1.0"
+rules:
+  - name: shared
+    matchers: { tools: [Write] }
+    actions: { block: false }
+"#,
+        )
+        .unwrap();
+        merge(&mut config, &fragments).unwrap();
+
+        let names: Vec<&str> = config.rules.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["shared", "plan-first"]);
+        assert_eq!(config.rules[0].plugin(), None);
+        assert_eq!(config.rules[1].plugin(), Some("terraform"));
+    }
+
+    #[test]
+    fn test_invalid_fragment_follows_fail_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let claude_dir = dir.path().join(".claude");
+        write_fragment(&claude_dir, "broken", "rules: [not a rule]\n");
+        let fragments = fragments(&[claude_dir]);
+
+        let mut config = Config::default();
+        config.settings.fail_open = true;
+        merge(&mut config, &fragments).unwrap();
+        assert!(config.rules.is_empty());
+
+        config.settings.fail_open = false;
+        let err = merge(&mut config, &fragments).unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("Plugin 'broken'"),
+            "{:#}",
+            err
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! Snapshots of parsed configuration
+//!
+//! Each successfully loaded hooks.yaml is also cached under
+//! `~/.claude/cache/cch/snapshots/`, so a one-shot `cch` invocation can skip
+//! the YAML parse, the strict-key check and pattern compilation. A snapshot
+//! is keyed by the config path and only used while the file's content hash,
+//! the `cch` version and the CPU architecture match, so editing the config
+//! (or upgrading) transparently refreshes it.
+//!
+//! Besides the config, a snapshot lists every rule pattern that compiled
+//! when it was written, each with its DFA serialized by `regex-automata`
+//! (`rewrite` patterns, which need a `Regex` to replace text, and patterns
+//! whose DFA would be too large have none). Loading a snapshot hands them to
+//! [`crate::regex_cache::restore`], so `Config::validate` doesn't compile
+//! them again and matching searches the DFA.
+//!
+//! Snapshots hold the document as parsed, before encrypted sections are
+//! decrypted or policies compiled, so no plaintext of sealed rules is
+//! written to disk and those steps still run on every load.
+//!
+//! The file is a magic number followed by 8-byte aligned sections, each a
+//! little-endian `u64` length and its bytes: the JSON header, config and
+//! declared settings, the pattern count, then a pattern and its DFA (empty
+//! when it has none) for each pattern.
+
+use regex_automata::dfa::dense;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+use super::Config;
+use crate::regex_cache::{self, Dfa};
+use crate::state::cache_dir;
+
+/// Start of every snapshot file; bump the last byte when the layout changes
+const MAGIC: &[u8; 8] = b"CCHSNAP\x01";
+
+/// Alignment of each section, enough for a DFA's `u32` tables
+const ALIGN: usize = 8;
+
+/// Restored patterns, each with its DFA when the snapshot has one
+type Patterns = Vec<(String, Option<Dfa>)>;
+
+/// Header identifying what a snapshot was built from
+#[derive(serde::Serialize, serde::Deserialize, PartialEq)]
+struct Header {
+    cch_version: String,
+    /// DFAs are stored in native byte order
+    arch: String,
+    source_sha256: String,
+}
+
+impl Header {
+    fn for_content(content: &str) -> Self {
+        Self {
+            cch_version: env!("CARGO_PKG_VERSION").to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            source_sha256: format!("{:x}", Sha256::digest(content.as_bytes())),
+        }
+    }
+}
+
+fn snapshot_path(config_path: &Path) -> Option<PathBuf> {
+    let config_path = config_path.canonicalize().ok()?;
+    let key = Sha256::digest(config_path.to_string_lossy().as_bytes());
+    Some(
+        cache_dir()
+            .ok()?
+            .join("snapshots")
+            .join(format!("{:x}.bin", key)),
+    )
+}
+
+/// Load the parsed config for `content`, if a current snapshot exists, and
+/// restore its patterns into the regex cache
+pub fn load(config_path: &Path, content: &str) -> Option<Config> {
+    let bytes = std::fs::read(snapshot_path(config_path)?).ok()?;
+    let (config, patterns) = decode(&bytes, content)?;
+    regex_cache::restore(patterns);
+    Some(config)
+}
+
+/// Save a snapshot of the parsed config; failures only cost a YAML parse
+/// and pattern compilation on the next load
+pub fn store(config_path: &Path, content: &str, config: &Config) {
+    let result = (|| -> anyhow::Result<()> {
+        let path = snapshot_path(config_path).ok_or_else(|| anyhow::anyhow!("no snapshot path"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("bin.tmp");
+        std::fs::write(&tmp, encode(content, config)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    })();
+    if let Err(e) = result {
+        tracing::debug!("Failed to write config snapshot: {}", e);
+    }
+}
+
+/// Every pattern of the config's rules, policies and exemptions that
+/// compiles, with whether it only needs a yes/no match
+fn patterns(config: &Config) -> Vec<(String, bool)> {
+    let policy_rules = config
+        .policies
+        .as_ref()
+        .map(|policies| policies.compile())
+        .unwrap_or_default();
+    let mut patterns: Vec<(String, bool)> = Vec::new();
+    for rule in config.rules.iter().chain(&policy_rules) {
+        let exemptions = rule.exemptions.iter().flatten();
+        let commands = exemptions.flat_map(|e| e.commands.iter().map(String::as_str));
+        let found = super::rule_patterns(rule)
+            .into_iter()
+            .map(|(kind, pattern)| (pattern, kind != "rewrite"))
+            .chain(commands.map(|pattern| (pattern, true)));
+        for (pattern, match_only) in found {
+            if regex_cache::compile(pattern).is_err() {
+                continue;
+            }
+            match patterns.iter_mut().find(|(p, _)| p == pattern) {
+                Some(entry) => entry.1 |= match_only,
+                None => patterns.push((pattern.to_string(), match_only)),
+            }
+        }
+    }
+    patterns
+}
+
+fn encode(content: &str, config: &Config) -> anyhow::Result<Vec<u8>> {
+    let mut out = MAGIC.to_vec();
+    put(
+        &mut out,
+        &serde_json::to_vec(&Header::for_content(content))?,
+    );
+    put(&mut out, &serde_json::to_vec(config)?);
+    put(&mut out, &serde_json::to_vec(&config.declared_settings)?);
+    let patterns = patterns(config);
+    put(&mut out, &(patterns.len() as u64).to_le_bytes());
+    for (pattern, match_only) in patterns {
+        let dfa = match_only
+            .then(|| regex_cache::build_dfa(&pattern))
+            .flatten()
+            .map(|dfa| dfa.to_bytes_native_endian());
+        put(&mut out, pattern.as_bytes());
+        put(
+            &mut out,
+            dfa.as_ref().map_or(&[][..], |(bytes, pad)| &bytes[*pad..]),
+        );
+    }
+    Ok(out)
+}
+
+/// Append one length-prefixed section, padded to [`ALIGN`]
+fn put(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+    out.extend_from_slice(bytes);
+    out.resize(out.len().next_multiple_of(ALIGN), 0);
+}
+
+/// Take the section at `*pos`, moving past it
+fn take<'b>(bytes: &'b [u8], pos: &mut usize) -> Option<&'b [u8]> {
+    let len = u64::from_le_bytes(bytes.get(*pos..*pos + 8)?.try_into().ok()?);
+    let start = *pos + 8;
+    let end = start.checked_add(usize::try_from(len).ok()?)?;
+    let section = bytes.get(start..end)?;
+    *pos = end.next_multiple_of(ALIGN);
+    Some(section)
+}
+
+fn decode(bytes: &[u8], content: &str) -> Option<(Config, Patterns)> {
+    let mut pos = MAGIC.len();
+    if bytes.get(..pos)? != MAGIC {
+        return None;
+    }
+    let header = take(bytes, &mut pos)?;
+    if serde_json::from_slice::<Header>(header).ok()? != Header::for_content(content) {
+        return None;
+    }
+    let (config, settings) = (take(bytes, &mut pos)?, take(bytes, &mut pos)?);
+    let restored = serde_json::from_slice::<Config>(config).and_then(|mut config| {
+        config.declared_settings = serde_json::from_slice(settings)?;
+        Ok(config)
+    });
+    let config = restored
+        .map_err(|e| tracing::debug!("Ignoring unreadable config snapshot: {}", e))
+        .ok()?;
+
+    let count = u64::from_le_bytes(take(bytes, &mut pos)?.try_into().ok()?);
+    let mut patterns = Vec::new();
+    for _ in 0..count {
+        let pattern = std::str::from_utf8(take(bytes, &mut pos)?).ok()?;
+        let dfa = take(bytes, &mut pos)?;
+        // An unaligned read buffer only costs the DFA; the pattern is still
+        // known to compile
+        let dfa = (!dfa.is_empty())
+            .then(|| dense::DFA::from_bytes(dfa).ok())
+            .flatten()
+            .map(|(dfa, _)| dfa.to_owned());
+        patterns.push((pattern.to_string(), dfa));
+    }
+    Some((config, patterns))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[test]
+    fn test_fixtures_round_trip_through_json() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/hooks");
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let content = std::fs::read_to_string(&path).unwrap();
+            let parsed: Config = serde_yaml::from_str(&content).unwrap();
+            let restored: Config =
+                serde_json::from_str(&serde_json::to_string(&parsed).unwrap()).unwrap();
+            assert_eq!(parsed, restored, "{}", path.display());
+
+            // `Config::parse` deserializes from the parsed document
+            let document: serde_yaml::Value = serde_yaml::from_str(&content).unwrap();
+            let from_document = Config::deserialize(document).unwrap();
+            assert_eq!(parsed, from_document, "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn test_snapshot_round_trips_config_and_patterns() {
+        let content = "version: \"1.0\"";
+        let rules = serde_yaml::from_str(
+            r#"
+- name: no-force-push
+  matchers:
+    command_match: "git push.*--force"
+  actions:
+    block: true
+- name: pin-installs
+  matchers:
+    tools: [Bash]
+  actions:
+    rewrite:
+      pattern: "npm install"
+      replace: "npm ci"
+- name: broken
+  matchers:
+    prompt_match: "(unclosed"
+  actions:
+    block: true
+"#,
+        )
+        .unwrap();
+        let config = Config {
+            rules,
+            ..Config::default()
+        };
+
+        let bytes = encode(content, &config).unwrap();
+        let (restored, patterns) = decode(&bytes, content).unwrap();
+        assert_eq!(restored, config);
+        let summary: Vec<(&str, bool)> = patterns
+            .iter()
+            .map(|(pattern, dfa)| (pattern.as_str(), dfa.is_some()))
+            .collect();
+        assert_eq!(
+            summary,
+            [("git push.*--force", true), ("npm install", false)]
+        );
+
+        assert!(decode(&bytes, "version: \"1.1\"").is_none());
+        assert!(decode(&bytes[..bytes.len() - 1], content).is_none());
+    }
+
+    #[test]
+    fn test_header_tracks_content() {
+        assert!(Header::for_content("a") == Header::for_content("a"));
+        assert!(Header::for_content("a") != Header::for_content("b"));
+    }
+}
@@ -0,0 +1,381 @@
+//! Strict parsing of rule definitions
+//!
+//! serde ignores unknown keys in `Rule`, `Matchers` and `Actions`, so a typo
+//! like `commands_match` silently turns a matcher off. When
+//! `settings.strict_parsing` is on, the raw YAML is walked once more against
+//! the known key sets below; unknown keys fail with serde_yaml's path and
+//! line/column information.
+//!
+//! Keep these lists in sync with the structs in `models.rs`.
+
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use std::fmt;
+
+/// Expected structure of a YAML node
+#[derive(Clone, Copy)]
+enum Shape {
+    /// Anything; not checked further
+    Any,
+    /// A sequence whose items have the given shape
+    Seq(&'static Shape),
+    /// A mapping with exactly these keys (all optional)
+    Fields(&'static [(&'static str, Shape)]),
+    /// A mapping where only the listed keys are checked further
+    Open(&'static [(&'static str, Shape)]),
+    /// A matcher block; matchers nest, so this stands in for `MATCHER_FIELDS`
+    Matchers,
+}
+
+const MATCHER_FIELDS: &[(&str, Shape)] = &[
+    ("tools", Shape::Any),
+    ("extensions", Shape::Any),
+    ("directories", Shape::Any),
+    ("operations", Shape::Any),
+    ("command_match", Shape::Any),
+    ("zone", Shape::Any),
+    ("exclude_tools", Shape::Any),
+    ("exclude_extensions", Shape::Any),
+    ("exclude_directories", Shape::Any),
+    ("command_not_match", Shape::Any),
+    ("tool_output_match", Shape::Any),
+    ("exit_code", Shape::Any),
+    ("prompt_match", Shape::Any),
+    ("background", Shape::Any),
+    (
+        "protected_paths",
+        Shape::Fields(&[("deny", Shape::Any), ("allow", Shape::Any)]),
+    ),
+    ("expr", Shape::Any),
+    ("any", Shape::Seq(&Shape::Matchers)),
+    ("all", Shape::Seq(&Shape::Matchers)),
+    ("not", Shape::Matchers),
+];
+
+const INJECT_SOURCE: Shape = Shape::Fields(&[("file", Shape::Any), ("header", Shape::Any)]);
+
+/// `inject` is a path, a `{file, header}` mapping, a list of either, or a
+/// `{files, position, channel}` mapping
+const INJECT: Shape = Shape::Seq(&Shape::Fields(&[
+    ("file", Shape::Any),
+    ("header", Shape::Any),
+    ("files", Shape::Seq(&INJECT_SOURCE)),
+    ("position", Shape::Any),
+    ("channel", Shape::Any),
+]));
+
+/// `run` is either a script path or a mapping (for a script or a
+/// WebAssembly module)
+const RUN: Shape = Shape::Fields(&[
+    ("script", Shape::Any),
+    ("wasm", Shape::Any),
+    ("trust", Shape::Any),
+    ("env", Shape::Any),
+    ("cacheable", Shape::Any),
+]);
+
+const ACTIONS: Shape = Shape::Fields(&[
+    ("inject", INJECT),
+    ("run", RUN),
+    ("block", Shape::Any),
+    ("block_if_match", Shape::Any),
+    (
+        "truncate_output",
+        Shape::Fields(&[
+            ("max_lines", Shape::Any),
+            ("max_bytes", Shape::Any),
+            ("strategy", Shape::Any),
+        ]),
+    ),
+    ("filter_results", Shape::Fields(&[("exclude", Shape::Any)])),
+    ("structural_checks", Shape::Any),
+    ("ensure_header", Shape::Fields(&[("template", Shape::Any)])),
+    ("inject_command", Shape::Any),
+    ("ask", Shape::Any),
+    ("require_files", Shape::Any),
+    ("allow", Shape::Any),
+    ("inject_once_per_session", Shape::Any),
+    (
+        "throttle",
+        Shape::Fields(&[
+            ("max", Shape::Any),
+            ("per", Shape::Any),
+            ("scope", Shape::Any),
+        ]),
+    ),
+    (
+        "enrich_prompt",
+        Shape::Fields(&[
+            ("glossary", Shape::Any),
+            ("instructions", Shape::Any),
+            ("once_per_session", Shape::Any),
+        ]),
+    ),
+    (
+        "rewrite",
+        Shape::Fields(&[
+            ("field", Shape::Any),
+            ("pattern", Shape::Any),
+            ("replace", Shape::Any),
+            ("append", Shape::Any),
+        ]),
+    ),
+]);
+
+const GOVERNANCE: Shape = Shape::Fields(&[
+    ("author", Shape::Any),
+    ("created_by", Shape::Any),
+    ("reason", Shape::Any),
+    ("confidence", Shape::Any),
+    ("last_reviewed", Shape::Any),
+    ("ticket", Shape::Any),
+    ("tags", Shape::Any),
+    ("expires", Shape::Any),
+    ("review_by", Shape::Any),
+]);
+
+const METADATA: Shape = Shape::Fields(&[
+    ("priority", Shape::Any),
+    ("timeout", Shape::Any),
+    ("enabled", Shape::Any),
+]);
+
+const RULE_TEST: Shape = Shape::Fields(&[
+    ("event", Shape::Any),
+    ("tool", Shape::Any),
+    ("command", Shape::Any),
+    ("path", Shape::Any),
+    ("match", Shape::Any),
+]);
+
+const EXEMPTION: Shape = Shape::Fields(&[
+    ("paths", Shape::Any),
+    ("commands", Shape::Any),
+    ("sources", Shape::Any),
+    ("justification", Shape::Any),
+]);
+
+const ESCALATION: Shape = Shape::Fields(&[
+    ("strategy", Shape::Any),
+    ("after", Shape::Any),
+    ("advisory", Shape::Any),
+]);
+
+const MESSAGES: Shape = Shape::Fields(&[("warn", Shape::Any), ("block", Shape::Any)]);
+
+const RULE: Shape = Shape::Fields(&[
+    ("name", Shape::Any),
+    ("description", Shape::Any),
+    ("matchers", Shape::Matchers),
+    ("actions", ACTIONS),
+    ("mode", Shape::Any),
+    ("priority", Shape::Any),
+    ("governance", GOVERNANCE),
+    ("metadata", METADATA),
+    ("tests", Shape::Seq(&RULE_TEST)),
+    ("exemptions", Shape::Seq(&EXEMPTION)),
+    ("escalation", ESCALATION),
+    ("bypass_users", Shape::Any),
+    ("messages", MESSAGES),
+    ("stop", Shape::Any),
+]);
+
+const CONFIG: Shape = Shape::Open(&[("rules", Shape::Seq(&RULE))]);
+
+/// Check a configuration document for unknown keys in its rules
+pub fn check(content: &str) -> Result<(), serde_yaml::Error> {
+    CONFIG.deserialize(serde_yaml::Deserializer::from_str(content))
+}
+
+impl<'de> DeserializeSeed<'de> for Shape {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        match self {
+            Shape::Any => deserializer.deserialize_ignored_any(IgnoredAny).map(|_| ()),
+            _ => deserializer.deserialize_any(self),
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for Shape {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a rule definition")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let (fields, open) = match self {
+            Shape::Fields(fields) => (fields, false),
+            Shape::Matchers => (MATCHER_FIELDS, false),
+            // A single item where a list of them is expected
+            Shape::Seq(Shape::Fields(fields)) => (*fields, false),
+            Shape::Open(fields) => (fields, true),
+            _ => (&[][..], true),
+        };
+        while let Some(shape) = map.next_key_seed(Key { fields, open })? {
+            map.next_value_seed(shape)?;
+        }
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let item = match self {
+            Shape::Seq(item) => *item,
+            _ => Shape::Any,
+        };
+        while seq.next_element_seed(item)?.is_some() {}
+        Ok(())
+    }
+
+    // Scalars are left for the regular parse to type-check
+    fn visit_bool<E>(self, _: bool) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_unit<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_none<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        self.deserialize(deserializer)
+    }
+}
+
+/// Resolves a mapping key to the shape of its value, failing on unknown
+/// keys so the error carries the key's own location
+struct Key {
+    fields: &'static [(&'static str, Shape)],
+    open: bool,
+}
+
+impl<'de> DeserializeSeed<'de> for Key {
+    type Value = Shape;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Shape, D::Error> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl Visitor<'_> for Key {
+    type Value = Shape;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a field name")
+    }
+
+    fn visit_str<E: de::Error>(self, key: &str) -> Result<Shape, E> {
+        match self.fields.iter().find(|(name, _)| *name == key) {
+            Some((_, shape)) => Ok(*shape),
+            None if self.open => Ok(Shape::Any),
+            None => {
+                let expected: Vec<&str> = self.fields.iter().map(|(name, _)| *name).collect();
+                Err(E::custom(format_args!(
+                    "unknown field `{}`, expected one of: {}",
+                    key,
+                    expected.join(", ")
+                )))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_matcher_key_is_located() {
+        let yaml = r#"version: "2.0"
+rules:
+  - name: block-force-push
+    matchers:
+      tools: [Bash]
+      commands_match: "git push --force"
+    actions:
+      block: true
+"#;
+        let message = check(yaml).unwrap_err().to_string();
+        assert!(message.contains("rules[0].matchers"), "{}", message);
+        assert!(
+            message.contains("unknown field `commands_match`"),
+            "{}",
+            message
+        );
+        assert!(message.contains("line 6"), "{}", message);
+    }
+
+    #[test]
+    fn test_nested_matchers_are_checked() {
+        let yaml = r#"version: "2.0"
+rules:
+  - name: r
+    matchers:
+      any:
+        - command_match: "rm -rf"
+        - not: { comand_match: "git" }
+    actions: { block: true }
+"#;
+        let message = check(yaml).unwrap_err().to_string();
+        assert!(
+            message.contains("rules[0].matchers.any[1].not"),
+            "{}",
+            message
+        );
+        assert!(
+            message.contains("unknown field `comand_match`"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn test_known_keys_pass() {
+        let yaml = r#"version: "2.0"
+custom_top_level: ignored
+rules:
+  - name: r
+    matchers: { tools: [Bash], command_match: "x" }
+    actions:
+      run: { script: check.sh, trust: local }
+      truncate_output: { max_lines: 5 }
+    tests:
+      - { tool: Bash, command: x, match: true }
+    escalation: { strategy: warn_then_block, after: 2 }
+  - name: wasm
+    actions:
+      run: { wasm: check.wasm, cacheable: true }
+"#;
+        check(yaml).unwrap();
+    }
+
+    #[test]
+    fn test_fixtures_pass_strict_check() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/hooks");
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let content = std::fs::read_to_string(&path).unwrap();
+            check(&content).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+        }
+    }
+}
@@ -0,0 +1,406 @@
+//! Long-running CCH daemon
+//!
+//! `cch daemon` keeps the policy engine warm so hook invocations skip most
+//! of the cold-start cost. It listens on a Unix socket
+//! (`~/.claude/run/cch/cch.sock`); `cch` run as a hook forwards each event
+//! there with [`forward`] and falls back to evaluating in-process when no
+//! daemon accepts the connection. The socket protocol is one JSON line each
+//! way: a [`ForwardedEvent`] in, a [`Response`] (or `{"error": ...}`) out.
+//!
+//! Once a daemon has accepted an event it may already have run the event's
+//! actions, so a failure after that point (timeout, dropped connection,
+//! evaluation error) is not retried in-process; the hook applies
+//! `settings.fail_open` instead.
+//!
+//! With `--http`, the daemon also serves a small JSON API on a loopback
+//! address, so local tools (editor plugins, pre-commit wrappers) can ask
+//! for decisions without spawning `cch`:
+//!
+//! - `POST /v1/evaluate` — body is a hook event as any supported agent
+//!   sends it (Claude Code, Cursor or OpenCode); returns an
+//!   [`EvaluationReport`](crate::models::EvaluationReport). The event is
+//!   simulated like `cch debug` does: nothing is logged and no session
+//!   state is saved, so probing the API never changes later decisions
+//! - `GET /v1/health` — liveness check
+//!
+//! Requests must name the listener in their `Host` header (`localhost`,
+//! `127.0.0.1` or `[::1]` with its port), so a web page can't reach the
+//! API by rebinding its own domain to the loopback address.
+//!
+//! The HTTP handling is deliberately minimal (one request per connection,
+//! `Content-Length` bodies only) to avoid pulling in a web framework.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, Take};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::hooks;
+use crate::logging;
+use crate::models::{DebugConfig, Event, Response};
+use crate::state::runtime_dir;
+
+/// Largest request head (request line and headers) accepted
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+
+/// A hook event forwarded to the daemon over its socket
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ForwardedEvent {
+    /// Version of the forwarding `cch`; the daemon refuses other versions
+    /// so an upgraded binary never talks to a stale daemon
+    pub version: String,
+
+    /// Whether the invocation asked for debug logging
+    #[serde(default)]
+    pub debug_logs: bool,
+
+    pub event: Event,
+}
+
+/// Default daemon socket (~/.claude/run/cch/cch.sock)
+pub fn socket_path() -> Result<PathBuf> {
+    Ok(runtime_dir()?.join("cch.sock"))
+}
+
+/// Run the daemon: the hook socket, plus the HTTP API when `http` is set
+pub async fn run(
+    socket: PathBuf,
+    http: Option<SocketAddr>,
+    debug_config: DebugConfig,
+    max_input_size: usize,
+) -> Result<()> {
+    let http = async {
+        match http {
+            Some(addr) => serve_http(addr, debug_config.clone(), max_input_size).await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::try_join!(
+        serve_socket(&socket, debug_config.clone(), max_input_size),
+        http
+    )?;
+    Ok(())
+}
+
+/// Serve forwarded hook events on a Unix socket until the process is stopped
+#[cfg(unix)]
+pub async fn serve_socket(
+    path: &Path,
+    debug_config: DebugConfig,
+    max_input_size: usize,
+) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    use tokio::net::UnixListener;
+
+    if path.exists() {
+        if std::os::unix::net::UnixStream::connect(path).is_ok() {
+            anyhow::bail!("A cch daemon is already listening on {}", path.display());
+        }
+        // Left behind by a daemon that did not shut down cleanly
+        std::fs::remove_file(path)?;
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let listener =
+        UnixListener::bind(path).with_context(|| format!("Failed to bind {}", path.display()))?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    println!("cch daemon listening on {}", path.display());
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let debug_config = debug_config.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_forwarded(stream, &debug_config, max_input_size).await {
+                tracing::warn!("Forwarded event failed: {}", e);
+            }
+        });
+    }
+}
+
+#[cfg(not(unix))]
+pub async fn serve_socket(
+    _path: &Path,
+    _debug_config: DebugConfig,
+    _max_input_size: usize,
+) -> Result<()> {
+    anyhow::bail!("The daemon socket is only supported on Unix platforms")
+}
+
+#[cfg(unix)]
+async fn handle_forwarded(
+    stream: tokio::net::UnixStream,
+    debug_config: &DebugConfig,
+    max_input_size: usize,
+) -> Result<()> {
+    let (read, mut write) = stream.into_split();
+    let mut line = String::new();
+    BufReader::new(read.take(max_input_size as u64 + 1))
+        .read_line(&mut line)
+        .await?;
+
+    let reply = match serde_json::from_str::<ForwardedEvent>(&line) {
+        Ok(forwarded) if forwarded.version != env!("CARGO_PKG_VERSION") => json!({
+            "error": format!("Daemon runs cch {}", env!("CARGO_PKG_VERSION")),
+            "refused": true
+        }),
+        Ok(forwarded) => {
+            let debug_config = DebugConfig {
+                enabled: debug_config.enabled || forwarded.debug_logs,
+            };
+            match hooks::process_event(forwarded.event, &debug_config).await {
+                Ok(response) => serde_json::to_value(response)?,
+                Err(e) => json!({ "error": e.to_string() }),
+            }
+        }
+        Err(e) => json!({ "error": format!("Invalid forwarded event: {}", e) }),
+    };
+
+    let mut reply = serde_json::to_vec(&reply)?;
+    reply.push(b'\n');
+    write.write_all(&reply).await?;
+    Ok(())
+}
+
+/// Outcome of forwarding a hook event to the daemon
+#[derive(Debug)]
+pub enum Forwarded {
+    /// The daemon's response
+    Answered(Box<Response>),
+
+    /// No daemon took the event (not running, stale socket, version
+    /// mismatch), so it is safe to evaluate in-process
+    Unavailable,
+
+    /// The daemon accepted the event but gave no response; it may have
+    /// acted on the event, so it must not be evaluated again
+    Failed(anyhow::Error),
+}
+
+/// Forward a hook event to a running daemon
+#[cfg(unix)]
+pub fn forward(event: &Event, debug_logs: bool, timeout: Duration) -> Forwarded {
+    match socket_path() {
+        Ok(path) => forward_to(&path, event, debug_logs, timeout),
+        Err(_) => Forwarded::Unavailable,
+    }
+}
+
+#[cfg(unix)]
+fn forward_to(path: &Path, event: &Event, debug_logs: bool, timeout: Duration) -> Forwarded {
+    use std::io::{BufRead, Write};
+    use std::os::unix::net::UnixStream;
+
+    let Ok(mut stream) = UnixStream::connect(path) else {
+        return Forwarded::Unavailable;
+    };
+    let result = (|| -> Result<Forwarded> {
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+
+        let mut request = serde_json::to_vec(&ForwardedEvent {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            debug_logs,
+            event: event.clone(),
+        })?;
+        request.push(b'\n');
+        stream.write_all(&request)?;
+
+        let mut line = String::new();
+        std::io::BufReader::new(&stream).read_line(&mut line)?;
+        let reply: serde_json::Value = serde_json::from_str(&line)
+            .with_context(|| format!("Invalid daemon reply: {:?}", line))?;
+        if let Some(error) = reply.get("error").and_then(|e| e.as_str()) {
+            if reply["refused"] == true {
+                tracing::warn!("Daemon refused event, evaluating in-process: {}", error);
+                return Ok(Forwarded::Unavailable);
+            }
+            anyhow::bail!("{}", error);
+        }
+        Ok(Forwarded::Answered(Box::new(serde_json::from_value(
+            reply,
+        )?)))
+    })();
+
+    result.unwrap_or_else(Forwarded::Failed)
+}
+
+#[cfg(not(unix))]
+pub fn forward(_event: &Event, _debug_logs: bool, _timeout: Duration) -> Forwarded {
+    Forwarded::Unavailable
+}
+
+/// Serve the decision API until the process is stopped
+pub async fn serve_http(
+    addr: SocketAddr,
+    debug_config: DebugConfig,
+    max_body_bytes: usize,
+) -> Result<()> {
+    if !addr.ip().is_loopback() {
+        tracing::warn!(
+            "Decision API bound to non-loopback address {}; it has no authentication",
+            addr
+        );
+    }
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind {}", addr))?;
+    let local = listener.local_addr()?;
+    println!("cch daemon listening on http://{}", local);
+
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let debug_config = debug_config.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, local, &debug_config, max_body_bytes).await {
+                tracing::warn!("Request from {} failed: {}", peer, e);
+            }
+        });
+    }
+}
+
+/// A parsed HTTP request
+struct Request {
+    method: String,
+    path: String,
+    host: Option<String>,
+    body: Vec<u8>,
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    local: SocketAddr,
+    debug_config: &DebugConfig,
+    max_body_bytes: usize,
+) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+    let (status, body) = match read_request(&mut reader, max_body_bytes).await {
+        Ok(request) if !host_allowed(request.host.as_deref(), local) => {
+            (403, json!({ "error": "Host not allowed" }))
+        }
+        Ok(request) => route(request, debug_config).await,
+        Err(e) => (400, json!({ "error": e.to_string() })),
+    };
+
+    let body = serde_json::to_vec(&body)?;
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        reason_phrase(status),
+        body.len()
+    );
+    let stream = reader.get_mut();
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(&body).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+async fn read_request(reader: &mut BufReader<TcpStream>, max_body_bytes: usize) -> Result<Request> {
+    // Cap what the head may read, so a line that never ends can't grow
+    // without bound
+    let mut head = (&mut *reader).take(MAX_HEAD_BYTES as u64);
+    let request_line = read_head_line(&mut head).await?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        anyhow::bail!("Malformed request line");
+    };
+
+    let mut content_length = 0;
+    let mut host = None;
+    loop {
+        let line = read_head_line(&mut head).await?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().context("Invalid Content-Length")?;
+            } else if name.trim().eq_ignore_ascii_case("host") {
+                host = Some(value.trim().to_ascii_lowercase());
+            }
+        }
+    }
+
+    if content_length > max_body_bytes {
+        anyhow::bail!("Request body exceeds {} bytes", max_body_bytes);
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).await?;
+
+    Ok(Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        host,
+        body,
+    })
+}
+
+/// Read one line of the request head
+async fn read_head_line(head: &mut Take<&mut BufReader<TcpStream>>) -> Result<String> {
+    let mut line = String::new();
+    head.read_line(&mut line).await?;
+    if !line.ends_with('\n') {
+        if head.limit() == 0 {
+            anyhow::bail!("Request headers too large");
+        }
+        anyhow::bail!("Connection closed before end of headers");
+    }
+    Ok(line)
+}
+
+/// Whether `host` names the listener at `local` by a loopback name or its
+/// own address; any other name may be a DNS-rebound page
+fn host_allowed(host: Option<&str>, local: SocketAddr) -> bool {
+    let Some(host) = host else {
+        return false;
+    };
+    let port = local.port();
+    let names = ["localhost", "127.0.0.1", "[::1]"];
+    names
+        .iter()
+        .any(|name| host == format!("{}:{}", name, port) || (port == 80 && host == *name))
+        || host == local.to_string()
+}
+
+async fn route(request: Request, debug_config: &DebugConfig) -> (u16, serde_json::Value) {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("POST", "/v1/evaluate") => {
+            let event =
+                serde_json::from_slice::<serde_json::Value>(&request.body).and_then(|raw| {
+                    logging::protocol::observe(&raw);
+                    Event::from_agent_payload(raw).map(|(event, _)| event)
+                });
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => return (400, json!({ "error": format!("Invalid event: {}", e) })),
+            };
+            match hooks::simulate_event(event, debug_config).await {
+                Ok(report) => (200, serde_json::to_value(report).unwrap_or_default()),
+                Err(e) => (500, json!({ "error": e.to_string() })),
+            }
+        }
+        ("GET", "/v1/health") => (
+            200,
+            json!({ "status": "ok", "version": env!("CARGO_PKG_VERSION") }),
+        ),
+        (_, "/v1/evaluate" | "/v1/health") => (405, json!({ "error": "Method not allowed" })),
+        _ => (404, json!({ "error": "Not found" })),
+    }
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    }
+}
@@ -0,0 +1,240 @@
+//! Encrypted config sections
+//!
+//! Rules whose patterns would reveal sensitive formats (e.g. secret-scanning
+//! regexes) can be stored as age-encrypted YAML in `hooks.yaml`:
+//!
+//! ```yaml
+//! encrypted_rules:
+//!   - |
+//!     -----BEGIN AGE ENCRYPTED FILE-----
+//!     ...
+//!     -----END AGE ENCRYPTED FILE-----
+//! ```
+//!
+//! Each block is a YAML list of rules encrypted with
+//! `age -a -r <recipient>`. The identity is read from `CCH_AGE_KEY`, the
+//! file named by `CCH_AGE_KEY_FILE`, or `~/.config/cch/age-key.txt`.
+//! Decryption is only compiled in with the `encrypted-config` feature.
+//!
+//! The same feature encrypts session state at rest (`settings.encrypt_state`)
+//! with a machine-local identity, created on first use at
+//! `~/.config/cch/state-key.txt` and readable only by its owner.
+
+use anyhow::Result;
+
+use crate::models::Rule;
+
+/// Placeholder shown instead of encrypted patterns
+pub const PLACEHOLDER: &str = "<encrypted>";
+
+/// Whether this binary was built with age decryption support
+pub const fn is_available() -> bool {
+    cfg!(feature = "encrypted-config")
+}
+
+/// Decrypt an ASCII-armored age block to plaintext YAML
+#[cfg(feature = "encrypted-config")]
+pub fn decrypt_section(armored: &str) -> Result<String> {
+    age_impl::decrypt_section(armored)
+}
+
+/// Decrypt an encrypted section (unavailable: built without `encrypted-config`)
+#[cfg(not(feature = "encrypted-config"))]
+pub fn decrypt_section(_armored: &str) -> Result<String> {
+    Err(anyhow::anyhow!(
+        "encrypted config sections require CCH built with `--features encrypted-config`"
+    ))
+}
+
+/// Whether `bytes` is an age-encrypted file rather than plaintext
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"age-encryption.org/")
+}
+
+/// Encrypt state to the machine-local key, creating the key on first use
+#[cfg(feature = "encrypted-config")]
+pub fn encrypt_state(plaintext: &[u8]) -> Result<Vec<u8>> {
+    let identity = age_impl::state_identity(&state_key_path()?)?;
+    age::encrypt(&identity.to_public(), plaintext)
+        .map_err(|e| anyhow::anyhow!("Failed to encrypt state: {}", e))
+}
+
+/// Decrypt state written by [`encrypt_state`]
+#[cfg(feature = "encrypted-config")]
+pub fn decrypt_state(ciphertext: &[u8]) -> Result<Vec<u8>> {
+    let identity = age_impl::state_identity(&state_key_path()?)?;
+    age::decrypt(&identity, ciphertext)
+        .map_err(|e| anyhow::anyhow!("Failed to decrypt state: {}", e))
+}
+
+/// Encrypt state (unavailable: built without `encrypted-config`)
+#[cfg(not(feature = "encrypted-config"))]
+pub fn encrypt_state(_plaintext: &[u8]) -> Result<Vec<u8>> {
+    Err(anyhow::anyhow!(
+        "encrypted session state requires CCH built with `--features encrypted-config`"
+    ))
+}
+
+/// Decrypt state (unavailable: built without `encrypted-config`)
+#[cfg(not(feature = "encrypted-config"))]
+pub fn decrypt_state(_ciphertext: &[u8]) -> Result<Vec<u8>> {
+    Err(anyhow::anyhow!(
+        "encrypted session state requires CCH built with `--features encrypted-config`"
+    ))
+}
+
+/// The machine-local key session state is encrypted with
+#[cfg(feature = "encrypted-config")]
+fn state_key_path() -> Result<std::path::PathBuf> {
+    Ok(dirs::home_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
+        .join(".config")
+        .join("cch")
+        .join("state-key.txt"))
+}
+
+/// Copy of a rule with its patterns replaced by a placeholder, for display
+pub fn redact(rule: &Rule) -> Rule {
+    let mut redacted = rule.clone();
+    let placeholder = || PLACEHOLDER.to_string();
+    redacted.matchers.command_match = rule.matchers.command_match.as_ref().map(|_| placeholder());
+    redacted.actions.block_if_match = rule.actions.block_if_match.as_ref().map(|_| placeholder());
+    redacted.tests = None;
+    redacted
+}
+
+#[cfg(feature = "encrypted-config")]
+mod age_impl {
+    use anyhow::{Context, Result};
+    use std::str::FromStr;
+
+    use age::x25519::Identity;
+
+    pub(super) fn decrypt_section(armored: &str) -> Result<String> {
+        decrypt_with(armored, &load_identity()?)
+    }
+
+    pub(super) fn decrypt_with(armored: &str, identity: &Identity) -> Result<String> {
+        let plaintext = age::decrypt(identity, armored.trim().as_bytes())
+            .context("Failed to decrypt encrypted config section")?;
+        String::from_utf8(plaintext).context("Decrypted config section is not UTF-8")
+    }
+
+    /// Find the age identity in the environment or the user key file
+    fn load_identity() -> Result<Identity> {
+        if let Ok(key) = std::env::var("CCH_AGE_KEY") {
+            return parse_identity(&key).context("CCH_AGE_KEY is not a valid age identity");
+        }
+
+        let path = match std::env::var("CCH_AGE_KEY_FILE") {
+            Ok(path) => path.into(),
+            Err(_) => dirs::home_dir()
+                .context("Could not determine home directory")?
+                .join(".config")
+                .join("cch")
+                .join("age-key.txt"),
+        };
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("No age key found (tried {})", path.display()))?;
+        parse_identity(&contents)
+            .with_context(|| format!("No valid age identity in {}", path.display()))
+    }
+
+    /// The identity in the key file at `path`, generating it when there is none
+    ///
+    /// The file is created exclusively, so concurrent hooks agree on one key.
+    pub(super) fn state_identity(path: &std::path::Path) -> Result<Identity> {
+        use age::secrecy::ExposeSecret;
+        use std::io::Write;
+
+        if let Ok(contents) = std::fs::read_to_string(path) {
+            return parse_identity(&contents)
+                .with_context(|| format!("No valid age identity in {}", path.display()));
+        }
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let identity = Identity::generate();
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        match options.open(path) {
+            Ok(mut file) => {
+                writeln!(
+                    file,
+                    "# CCH session state key\n# public key: {}\n{}",
+                    identity.to_public(),
+                    identity.to_string().expose_secret()
+                )
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+                Ok(identity)
+            }
+            // Another hook created it first
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                parse_identity(&std::fs::read_to_string(path)?)
+            }
+            Err(e) => Err(e).with_context(|| format!("Failed to create {}", path.display())),
+        }
+    }
+
+    /// Parse the first identity line, skipping `#` comments as age key files do
+    pub(super) fn parse_identity(contents: &str) -> Result<Identity> {
+        contents
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .find_map(|l| Identity::from_str(l).ok())
+            .context("no AGE-SECRET-KEY line")
+    }
+}
+
+#[cfg(all(test, feature = "encrypted-config"))]
+mod tests {
+    use super::*;
+    use age::secrecy::ExposeSecret;
+
+    #[test]
+    fn test_decrypt_section_roundtrip() {
+        let identity = age::x25519::Identity::generate();
+        let armored =
+            age::encrypt_and_armor(&identity.to_public(), b"- name: secret-rule\n").unwrap();
+
+        // Key files carry comment lines before the identity
+        let key_file = format!(
+            "# created: 2026-01-01\n# public key: {}\n{}\n",
+            identity.to_public(),
+            identity.to_string().expose_secret()
+        );
+        let parsed = age_impl::parse_identity(&key_file).unwrap();
+
+        let plaintext = age_impl::decrypt_with(&armored, &parsed).unwrap();
+        assert_eq!(plaintext, "- name: secret-rule\n");
+
+        let other = age::x25519::Identity::generate();
+        assert!(age_impl::decrypt_with(&armored, &other).is_err());
+    }
+
+    #[test]
+    fn test_state_identity_is_created_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cch/state-key.txt");
+        let created = age_impl::state_identity(&path).unwrap();
+        let loaded = age_impl::state_identity(&path).unwrap();
+        assert_eq!(
+            created.to_public().to_string(),
+            loaded.to_public().to_string()
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let ciphertext = age::encrypt(&loaded.to_public(), b"{}").unwrap();
+        assert!(is_encrypted(&ciphertext));
+        assert!(!is_encrypted(b"{}"));
+        assert_eq!(age::decrypt(&created, &ciphertext).unwrap(), b"{}");
+    }
+}
@@ -0,0 +1,138 @@
+//! Embeddable policy evaluation
+//!
+//! `PolicyEngine` evaluates events against a `Config` the caller provides,
+//! for tools that embed CCH (the desktop UI, editor integrations) instead
+//! of running the `cch` binary. Evaluation reads nothing from stdin, writes
+//! nothing to stdout, and doesn't touch the event log or change journal;
+//! the response-shaping settings applied to hook events (post-processor,
+//! dry run, session banner, `explain_in_response`) are left out as well.
+//!
+//! Rules still act as they do for a hook event: validators run, context
+//! files are read, and throttles, escalations and once-per-session injects
+//! keep their counts in the session state.
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::hooks;
+use crate::models::{
+    AppliedExemption, DebugConfig, Decision, Event, MatchedRule, Response, RuleEvaluation,
+};
+
+/// Evaluates events against one configuration
+#[derive(Debug, Clone)]
+pub struct PolicyEngine {
+    config: Config,
+}
+
+/// What the rules decided for an event, and why
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct EvaluationResult {
+    /// Decision of the highest-priority matching rule (`Allowed` when none
+    /// matched)
+    pub decision: Decision,
+
+    /// The response a hook would have returned
+    pub response: Response,
+
+    /// Matching rules, highest priority first
+    pub matched_rules: Vec<MatchedRule>,
+
+    /// Context the rules injected, if any
+    pub injected_context: Option<String>,
+
+    /// Every enabled rule with the result of each of its matchers
+    pub rule_evaluations: Vec<RuleEvaluation>,
+
+    /// Rules skipped through exemptions or `bypass_users`
+    pub exemptions: Vec<AppliedExemption>,
+}
+
+impl PolicyEngine {
+    /// An engine for `config`, as returned by `Config::load` or
+    /// `Config::from_file`
+    pub fn new(config: Config) -> Self {
+        Self { config }
+    }
+
+    /// The configuration events are evaluated against
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Evaluate `event` against the configured rules
+    pub async fn evaluate(&self, event: Event) -> Result<EvaluationResult> {
+        let (report, rule_evaluations) = hooks::evaluate_traced(
+            &event,
+            &self.config,
+            &DebugConfig { enabled: true },
+            hooks::Persistence::ReadWrite,
+        )
+        .await?;
+        Ok(EvaluationResult {
+            decision: report.decision.unwrap_or(Decision::Allowed),
+            injected_context: report.response.context.clone(),
+            response: report.response,
+            matched_rules: report.matched_rules,
+            rule_evaluations,
+            exemptions: report.exemptions,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_evaluate_reports_rules_and_decision() {
+        let dir = tempfile::tempdir().unwrap();
+        let context = dir.path().join("git.md");
+        std::fs::write(&context, "Use feature branches.").unwrap();
+        let config: Config = serde_yaml::from_str(&format!(
+            r#"
+version: "1.0"
+rules:
+  - name: no-force-push
+    priority: 10
+    matchers: {{ tools: [Bash], command_match: "git push --force" }}
+    actions: {{ block: true }}
+  - name: git-guidance
+    matchers: {{ tools: [Bash], command_match: "^git " }}
+    actions: {{ inject: "{}" }}
+"#,
+            context.display()
+        ))
+        .unwrap();
+        let engine = PolicyEngine::new(config);
+        let event = |command: &str| -> Event {
+            serde_json::from_value(serde_json::json!({
+                "hook_event_name": "PreToolUse",
+                "tool_name": "Bash",
+                "tool_input": { "command": command },
+                "session_id": "engine-test",
+            }))
+            .unwrap()
+        };
+
+        let result = engine.evaluate(event("git status")).await.unwrap();
+        assert_eq!(result.decision, Decision::Allowed);
+        assert_eq!(result.matched_rules.len(), 1);
+        assert!(
+            result
+                .injected_context
+                .is_some_and(|c| c.contains("Use feature branches."))
+        );
+        assert_eq!(result.rule_evaluations.len(), 2);
+        assert!(!result.rule_evaluations[0].matched);
+        assert!(result.rule_evaluations[0].matcher_results.is_some());
+
+        let result = engine
+            .evaluate(event("git push --force origin main"))
+            .await
+            .unwrap();
+        assert_eq!(result.decision, Decision::Blocked);
+        assert_eq!(result.matched_rules[0].name, "no-force-push");
+    }
+}
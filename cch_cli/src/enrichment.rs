@@ -0,0 +1,129 @@
+//! Prompt enrichment for UserPromptSubmit events
+//!
+//! `actions.enrich_prompt` adds the project's glossary and standing
+//! instructions to the context of a submitted prompt, which Claude Code
+//! receives as `additionalContext`. Every entry has a key the session
+//! records once it is sent, so a long conversation isn't padded with the
+//! same expansions on every prompt.
+
+use std::collections::BTreeSet;
+
+use crate::models::PromptEnrichment;
+
+/// A glossary expansion or instruction to add to a prompt
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    /// Session state key: `glossary:<term>` or `instruction:<text>`
+    pub key: String,
+    pub text: String,
+    glossary: bool,
+}
+
+/// The entries of `enrichment` that apply to `prompt`
+pub fn entries(enrichment: &PromptEnrichment, prompt: &str) -> Vec<Entry> {
+    let glossary = enrichment
+        .glossary
+        .iter()
+        .filter(|(term, _)| mentions(prompt, term))
+        .map(|(term, expansion)| Entry {
+            key: glossary_key(term),
+            text: format!("{}: {}", term, expansion),
+            glossary: true,
+        });
+    let instructions = enrichment.instructions.iter().map(|instruction| Entry {
+        key: instruction_key(instruction),
+        text: instruction.clone(),
+        glossary: false,
+    });
+    glossary.chain(instructions).collect()
+}
+
+/// `enrichment` without the entries whose keys are in `sent`
+pub fn unsent(enrichment: &PromptEnrichment, sent: &BTreeSet<String>) -> PromptEnrichment {
+    let mut unsent = enrichment.clone();
+    unsent
+        .glossary
+        .retain(|term, _| !sent.contains(&glossary_key(term)));
+    unsent
+        .instructions
+        .retain(|instruction| !sent.contains(&instruction_key(instruction)));
+    unsent
+}
+
+fn glossary_key(term: &str) -> String {
+    format!("glossary:{}", term)
+}
+
+fn instruction_key(instruction: &str) -> String {
+    format!("instruction:{}", instruction)
+}
+
+/// Context text listing `entries`, if there are any
+pub fn render(entries: &[Entry]) -> Option<String> {
+    let mut sections = Vec::new();
+    for (glossary, heading) in [(true, "Glossary:"), (false, "Standing instructions:")] {
+        let lines: Vec<String> = entries
+            .iter()
+            .filter(|entry| entry.glossary == glossary)
+            .map(|entry| format!("- {}", entry.text))
+            .collect();
+        if !lines.is_empty() {
+            sections.push(format!("{}\n{}", heading, lines.join("\n")));
+        }
+    }
+    (!sections.is_empty()).then(|| sections.join("\n\n"))
+}
+
+/// Whether `prompt` contains `term` as a whole word
+fn mentions(prompt: &str, term: &str) -> bool {
+    !term.is_empty()
+        && prompt.match_indices(term).any(|(i, _)| {
+            let before = prompt[..i].chars().next_back();
+            let after = prompt[i + term.len()..].chars().next();
+            !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_match_whole_terms_and_skip_sent() {
+        let enrichment: PromptEnrichment = serde_yaml::from_str(
+            r"
+glossary:
+  CDK: AWS Cloud Development Kit
+  IQ: Installation Qualification
+  C++: the engine's language
+instructions:
+  - Use pnpm.
+",
+        )
+        .unwrap();
+        assert!(enrichment.once_per_session);
+
+        let found = entries(&enrichment, "Deploy the CDK stack, then fix C++ IQs");
+        let keys: Vec<&str> = found.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            ["glossary:C++", "glossary:CDK", "instruction:Use pnpm."]
+        );
+        assert_eq!(
+            render(&found).unwrap(),
+            "Glossary:\n- C++: the engine's language\n- CDK: AWS Cloud Development Kit\n\nStanding instructions:\n- Use pnpm."
+        );
+
+        let sent = BTreeSet::from([
+            "glossary:CDK".to_string(),
+            "instruction:Use pnpm.".to_string(),
+        ]);
+        let unsent = unsent(&enrichment, &sent);
+        let found = entries(&unsent, "CDK and IQ");
+        assert_eq!(
+            render(&found).unwrap(),
+            "Glossary:\n- IQ: Installation Qualification"
+        );
+        assert_eq!(render(&entries(&unsent, "cdk")), None);
+    }
+}
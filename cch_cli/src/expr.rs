@@ -0,0 +1,741 @@
+//! Expression matcher (`matchers.expr`)
+//!
+//! Regex matchers test one field each; `expr` combines fields in a small
+//! CEL-like language:
+//!
+//! ```yaml
+//! matchers:
+//!   tools: [Write]
+//!   expr: 'path.startsWith("src/") && size(content) > 10000 && branch != "main"'
+//! ```
+//!
+//! An expression sees the event as these fields, each `null` when the event
+//! doesn't carry it:
+//!
+//! | Field        | Value                                                  |
+//! |--------------|--------------------------------------------------------|
+//! | `event`      | hook event name, e.g. `"PreToolUse"`                   |
+//! | `tool`       | tool name                                              |
+//! | `command`    | Bash command                                           |
+//! | `path`       | target file, relative to `cwd` when it lies under it   |
+//! | `content`    | written content (`content` or `new_string`)            |
+//! | `prompt`     | submitted prompt                                       |
+//! | `output`     | PostToolUse output                                     |
+//! | `exit_code`  | PostToolUse exit code                                  |
+//! | `cwd`        | the event's working directory                          |
+//! | `session_id` | session id                                             |
+//! | `branch`     | current git branch of `cwd` (`null` when detached)     |
+//! | `input`      | the raw tool input, e.g. `input.replace_all`           |
+//!
+//! Operators are `||`, `&&`, `!`, `==`, `!=`, `<`, `<=`, `>`, `>=` and `in`
+//! (list membership or map key), with member access (`input.file_path`)
+//! and indexing (`input["file_path"]`, `list[0]`). `size(x)` counts the
+//! characters of a string or the items of a list or map; strings have the
+//! methods `startsWith`, `endsWith`, `contains` and `matches` (a regex).
+//! Literals are strings in single or double quotes, integers, `true`,
+//! `false`, `null` and lists (`["main", "release"]`).
+//!
+//! `Config::validate` compiles every expression, so syntax errors, unknown
+//! fields or functions and invalid regex literals are config errors. An
+//! expression that fails while evaluating, e.g. `size(null)` or `"a" < 1`,
+//! doesn't match.
+
+use anyhow::{Result, anyhow, bail};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::hooks::{content_from_input, file_path_from_input};
+use crate::models::Event;
+use crate::regex_cache;
+
+/// Fields of the event object
+pub const FIELDS: &[&str] = &[
+    "event",
+    "tool",
+    "command",
+    "path",
+    "content",
+    "prompt",
+    "output",
+    "exit_code",
+    "cwd",
+    "session_id",
+    "branch",
+    "input",
+];
+
+/// A compiled expression
+#[derive(Debug)]
+pub struct Expression {
+    root: Node,
+}
+
+#[derive(Debug)]
+enum Node {
+    Literal(Value),
+    List(Vec<Node>),
+    Field(String),
+    Member(Box<Node>, String),
+    Index(Box<Node>, Box<Node>),
+    /// Function call; a method's receiver is its first argument
+    Call(Function, Vec<Node>),
+    Not(Box<Node>),
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    Compare(Op, Box<Node>, Box<Node>),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Function {
+    Size,
+    StartsWith,
+    EndsWith,
+    Contains,
+    Matches,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    In,
+}
+
+/// Compiled expressions, or why they don't compile, by source
+type Cache = HashMap<String, Result<Arc<Expression>, String>>;
+
+static CACHE: OnceLock<Mutex<Cache>> = OnceLock::new();
+
+/// The compiled form of `source`, compiling it on first use
+pub fn compile(source: &str) -> Result<Arc<Expression>> {
+    let cache = CACHE.get_or_init(Mutex::default);
+    let mut cache = cache
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    cache
+        .entry(source.to_string())
+        .or_insert_with(|| {
+            Expression::parse(source)
+                .map(Arc::new)
+                .map_err(|e| e.to_string())
+        })
+        .clone()
+        .map_err(|e| anyhow!(e))
+}
+
+/// Whether `event` satisfies the expression `source`
+pub fn matches(event: &Event, source: &str) -> bool {
+    let Ok(expression) = compile(source) else {
+        return false;
+    };
+    match expression.evaluate(&|name| field(event, name)) {
+        Ok(matched) => matched,
+        Err(e) => {
+            tracing::debug!("Expression {:?} doesn't match: {}", source, e);
+            false
+        }
+    }
+}
+
+impl Expression {
+    /// Parse `source`, checking its fields, functions and regex literals
+    pub fn parse(source: &str) -> Result<Self> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            end: source.chars().count() + 1,
+        };
+        let root = parser.or()?;
+        if let Some((token, column)) = parser.tokens.get(parser.pos) {
+            bail!("unexpected {} at column {}", token, column);
+        }
+        Ok(Self { root })
+    }
+
+    /// Evaluate against an event object whose fields `fields` looks up;
+    /// anything but `true` or `false` is an error
+    pub fn evaluate(&self, fields: &dyn Fn(&str) -> Value) -> Result<bool> {
+        match eval(&self.root, fields)? {
+            Value::Bool(b) => Ok(b),
+            other => bail!("expression is {}, not true or false", describe(&other)),
+        }
+    }
+}
+
+// =============================================================================
+// Tokens
+// =============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Int(i64),
+    Punct(&'static str),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Ident(name) => write!(f, "'{}'", name),
+            Token::Str(s) => write!(f, "{:?}", s),
+            Token::Int(n) => write!(f, "{}", n),
+            Token::Punct(p) => write!(f, "'{}'", p),
+        }
+    }
+}
+
+const PUNCTUATION: &[&str] = &[
+    "||", "&&", "==", "!=", "<=", ">=", "<", ">", "!", "(", ")", "[", "]", ",", ".", "-",
+];
+
+/// Tokens of `source`, each with its 1-based column
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '"' || c == '\'' {
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => bail!("unterminated string at column {}", column),
+                    Some(&q) if q == c => break,
+                    Some('\\') => {
+                        text.push(match chars.get(i + 1) {
+                            Some('n') => '\n',
+                            Some('t') => '\t',
+                            Some(&escaped) => escaped,
+                            None => bail!("unterminated string at column {}", column),
+                        });
+                        i += 2;
+                    }
+                    Some(&other) => {
+                        text.push(other);
+                        i += 1;
+                    }
+                }
+            }
+            i += 1;
+            tokens.push((Token::Str(text), column));
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while chars.get(i).is_some_and(char::is_ascii_digit) {
+                i += 1;
+            }
+            let digits: String = chars[start..i].iter().collect();
+            let n = digits
+                .parse()
+                .map_err(|_| anyhow!("integer too large at column {}", column))?;
+            tokens.push((Token::Int(n), column));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while chars
+                .get(i)
+                .is_some_and(|c| c.is_alphanumeric() || *c == '_')
+            {
+                i += 1;
+            }
+            tokens.push((Token::Ident(chars[start..i].iter().collect()), column));
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let Some(punct) = PUNCTUATION.iter().find(|p| rest.starts_with(**p)) else {
+                bail!("unexpected character '{}' at column {}", c, column);
+            };
+            i += punct.len();
+            tokens.push((Token::Punct(punct), column));
+        }
+    }
+    Ok(tokens)
+}
+
+// =============================================================================
+// Parser
+// =============================================================================
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    /// Column reported for a premature end
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn column(&self) -> usize {
+        self.tokens.get(self.pos).map_or(self.end, |(_, c)| *c)
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Punct(p)) if *p == punct) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<()> {
+        if self.eat(punct) {
+            return Ok(());
+        }
+        match self.peek() {
+            Some(token) => bail!(
+                "expected '{}' but found {} at column {}",
+                punct,
+                token,
+                self.column()
+            ),
+            None => bail!("expected '{}' at the end of the expression", punct),
+        }
+    }
+
+    fn or(&mut self) -> Result<Node> {
+        let mut node = self.and()?;
+        while self.eat("||") {
+            node = Node::Or(Box::new(node), Box::new(self.and()?));
+        }
+        Ok(node)
+    }
+
+    fn and(&mut self) -> Result<Node> {
+        let mut node = self.comparison()?;
+        while self.eat("&&") {
+            node = Node::And(Box::new(node), Box::new(self.comparison()?));
+        }
+        Ok(node)
+    }
+
+    fn comparison(&mut self) -> Result<Node> {
+        let left = self.unary()?;
+        let op = match self.peek() {
+            Some(Token::Punct("==")) => Op::Eq,
+            Some(Token::Punct("!=")) => Op::Ne,
+            Some(Token::Punct("<")) => Op::Lt,
+            Some(Token::Punct("<=")) => Op::Le,
+            Some(Token::Punct(">")) => Op::Gt,
+            Some(Token::Punct(">=")) => Op::Ge,
+            Some(Token::Ident(word)) if word == "in" => Op::In,
+            _ => return Ok(left),
+        };
+        self.pos += 1;
+        let right = self.unary()?;
+        Ok(Node::Compare(op, Box::new(left), Box::new(right)))
+    }
+
+    fn unary(&mut self) -> Result<Node> {
+        if self.eat("!") {
+            return Ok(Node::Not(Box::new(self.unary()?)));
+        }
+        if self.eat("-") {
+            return match self.peek() {
+                Some(&Token::Int(n)) => {
+                    self.pos += 1;
+                    Ok(Node::Literal(Value::from(-n)))
+                }
+                _ => bail!("expected a number after '-' at column {}", self.column()),
+            };
+        }
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Result<Node> {
+        let mut node = self.primary()?;
+        loop {
+            if self.eat(".") {
+                let column = self.column();
+                let Some(Token::Ident(name)) = self.peek().cloned() else {
+                    bail!("expected a field or method name at column {}", column);
+                };
+                self.pos += 1;
+                if self.eat("(") {
+                    let function = function(&name, true, column)?;
+                    let mut args = vec![node];
+                    args.extend(self.arguments()?);
+                    node = call(function, args, column)?;
+                } else {
+                    node = Node::Member(Box::new(node), name);
+                }
+            } else if self.eat("[") {
+                let index = self.or()?;
+                self.expect("]")?;
+                node = Node::Index(Box::new(node), Box::new(index));
+            } else {
+                return Ok(node);
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Result<Node> {
+        let column = self.column();
+        let Some(token) = self.peek().cloned() else {
+            bail!("expression ends early");
+        };
+        self.pos += 1;
+        match token {
+            Token::Str(s) => Ok(Node::Literal(Value::String(s))),
+            Token::Int(n) => Ok(Node::Literal(Value::from(n))),
+            Token::Punct("(") => {
+                let node = self.or()?;
+                self.expect(")")?;
+                Ok(node)
+            }
+            Token::Punct("[") => {
+                let items = self.list("]")?;
+                Ok(Node::List(items))
+            }
+            Token::Ident(name) => match name.as_str() {
+                "true" => Ok(Node::Literal(Value::Bool(true))),
+                "false" => Ok(Node::Literal(Value::Bool(false))),
+                "null" => Ok(Node::Literal(Value::Null)),
+                _ if self.eat("(") => {
+                    let function = function(&name, false, column)?;
+                    let args = self.arguments()?;
+                    call(function, args, column)
+                }
+                _ if FIELDS.contains(&name.as_str()) => Ok(Node::Field(name)),
+                _ => bail!(
+                    "unknown field '{}' at column {} (fields: {})",
+                    name,
+                    column,
+                    FIELDS.join(", ")
+                ),
+            },
+            other @ Token::Punct(_) => bail!("unexpected {} at column {}", other, column),
+        }
+    }
+
+    /// Arguments after an opening parenthesis
+    fn arguments(&mut self) -> Result<Vec<Node>> {
+        self.list(")")
+    }
+
+    /// Comma-separated expressions up to `close`
+    fn list(&mut self, close: &str) -> Result<Vec<Node>> {
+        let mut items = Vec::new();
+        if self.eat(close) {
+            return Ok(items);
+        }
+        loop {
+            items.push(self.or()?);
+            if self.eat(close) {
+                return Ok(items);
+            }
+            self.expect(",")?;
+        }
+    }
+}
+
+fn function(name: &str, method: bool, column: usize) -> Result<Function> {
+    Ok(match (name, method) {
+        ("size", _) => Function::Size,
+        ("startsWith", true) => Function::StartsWith,
+        ("endsWith", true) => Function::EndsWith,
+        ("contains", true) => Function::Contains,
+        ("matches", true) => Function::Matches,
+        (_, false) => bail!("unknown function '{}' at column {}", name, column),
+        (_, true) => bail!("unknown method '{}' at column {}", name, column),
+    })
+}
+
+/// A call node, with its argument count and any regex literal checked
+fn call(function: Function, args: Vec<Node>, column: usize) -> Result<Node> {
+    // Every function takes one argument besides a method's receiver
+    let expected = if matches!(function, Function::Size) {
+        1
+    } else {
+        2
+    };
+    if args.len() != expected {
+        bail!("expected one argument at column {}", column);
+    }
+    if let (Function::Matches, Some(Node::Literal(Value::String(pattern)))) =
+        (function, args.get(1))
+    {
+        if regex_cache::compile(pattern).is_err() {
+            bail!(
+                "invalid regex {:?} at column {}: {}",
+                pattern,
+                column,
+                regex_cache::describe_error(pattern)
+            );
+        }
+    }
+    Ok(Node::Call(function, args))
+}
+
+// =============================================================================
+// Evaluation
+// =============================================================================
+
+fn eval(node: &Node, fields: &dyn Fn(&str) -> Value) -> Result<Value> {
+    Ok(match node {
+        Node::Literal(value) => value.clone(),
+        Node::List(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| eval(item, fields))
+                .collect::<Result<_>>()?,
+        ),
+        Node::Field(name) => fields(name),
+        Node::Member(target, name) => match eval(target, fields)? {
+            Value::Object(map) => map.get(name).cloned().unwrap_or(Value::Null),
+            Value::Null => Value::Null,
+            other => bail!("{} has no field '{}'", describe(&other), name),
+        },
+        Node::Index(target, index) => match (eval(target, fields)?, eval(index, fields)?) {
+            (Value::Object(map), Value::String(key)) => {
+                map.get(&key).cloned().unwrap_or(Value::Null)
+            }
+            (Value::Array(items), Value::Number(n)) => n
+                .as_u64()
+                .and_then(|i| items.get(usize::try_from(i).ok()?).cloned())
+                .unwrap_or(Value::Null),
+            (Value::Null, _) => Value::Null,
+            (target, index) => bail!(
+                "can't index {} with {}",
+                describe(&target),
+                describe(&index)
+            ),
+        },
+        Node::Call(function, args) => {
+            let args: Vec<Value> = args
+                .iter()
+                .map(|arg| eval(arg, fields))
+                .collect::<Result<_>>()?;
+            apply(*function, &args)?
+        }
+        Node::Not(inner) => Value::Bool(!truth(&eval(inner, fields)?)?),
+        Node::And(left, right) => {
+            Value::Bool(truth(&eval(left, fields)?)? && truth(&eval(right, fields)?)?)
+        }
+        Node::Or(left, right) => {
+            Value::Bool(truth(&eval(left, fields)?)? || truth(&eval(right, fields)?)?)
+        }
+        Node::Compare(op, left, right) => {
+            Value::Bool(compare(*op, &eval(left, fields)?, &eval(right, fields)?)?)
+        }
+    })
+}
+
+fn truth(value: &Value) -> Result<bool> {
+    match value {
+        Value::Bool(b) => Ok(*b),
+        other => bail!("expected true or false, found {}", describe(other)),
+    }
+}
+
+fn apply(function: Function, args: &[Value]) -> Result<Value> {
+    if let (Function::Size, [value]) = (function, args) {
+        return Ok(Value::from(match value {
+            Value::String(s) => s.chars().count(),
+            Value::Array(items) => items.len(),
+            Value::Object(map) => map.len(),
+            other => bail!("size() of {}", describe(other)),
+        }));
+    }
+    if let (Function::Contains, [Value::Array(items), item]) = (function, args) {
+        return Ok(Value::Bool(items.contains(item)));
+    }
+    let (Some(Value::String(s)), Some(Value::String(arg))) = (args.first(), args.get(1)) else {
+        bail!("{:?} needs a string and a string argument", function);
+    };
+    Ok(Value::Bool(match function {
+        Function::StartsWith => s.starts_with(arg.as_str()),
+        Function::EndsWith => s.ends_with(arg.as_str()),
+        Function::Contains => s.contains(arg.as_str()),
+        Function::Matches => regex_cache::compile(arg)
+            .map_err(|e| anyhow!("invalid regex {:?}: {}", arg, e))?
+            .is_match(s),
+        Function::Size => unreachable!("size() takes one argument"),
+    }))
+}
+
+fn compare(op: Op, left: &Value, right: &Value) -> Result<bool> {
+    let ordering = match (op, left, right) {
+        (Op::Eq, ..) => return Ok(left == right),
+        (Op::Ne, ..) => return Ok(left != right),
+        (Op::In, item, Value::Array(items)) => return Ok(items.contains(item)),
+        (Op::In, Value::String(key), Value::Object(map)) => return Ok(map.contains_key(key)),
+        (Op::In, _, Value::Null) => return Ok(false),
+        (Op::In, ..) => bail!("'in' needs a list or map, found {}", describe(right)),
+        (_, Value::Number(a), Value::Number(b)) => a
+            .as_i64()
+            .zip(b.as_i64())
+            .map(|(a, b)| a.cmp(&b))
+            .ok_or_else(|| anyhow!("numbers out of range"))?,
+        (_, Value::String(a), Value::String(b)) => a.cmp(b),
+        _ => bail!("can't order {} and {}", describe(left), describe(right)),
+    };
+    Ok(match op {
+        Op::Lt => ordering.is_lt(),
+        Op::Le => ordering.is_le(),
+        Op::Gt => ordering.is_gt(),
+        _ => ordering.is_ge(),
+    })
+}
+
+fn describe(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a bool",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "a list",
+        Value::Object(_) => "a map",
+    }
+}
+
+// =============================================================================
+// Event object
+// =============================================================================
+
+/// The value of event object field `name`
+fn field(event: &Event, name: &str) -> Value {
+    let input = event.tool_input.as_ref();
+    let text = |s: Option<&str>| s.map_or(Value::Null, |s| Value::String(s.to_string()));
+    match name {
+        "event" => Value::String(event.hook_event_name.to_string()),
+        "tool" => text(event.tool_name.as_deref()),
+        "command" => text(input.and_then(|i| i.get("command")?.as_str())),
+        "path" => text(
+            input
+                .and_then(file_path_from_input)
+                .map(|path| relative_to_cwd(path, event.cwd.as_deref())),
+        ),
+        "content" => text(input.and_then(content_from_input)),
+        "prompt" => text(event.prompt.as_deref()),
+        "output" => text(event.tool_output()),
+        "exit_code" => event.tool_exit_code().map_or(Value::Null, Value::from),
+        "cwd" => text(event.cwd.as_deref()),
+        "session_id" => Value::String(event.session_id.clone()),
+        "branch" => text(event.cwd.as_deref().and_then(git_branch).as_deref()),
+        "input" => input.cloned().unwrap_or(Value::Null),
+        _ => Value::Null,
+    }
+}
+
+fn relative_to_cwd<'a>(path: &'a str, cwd: Option<&str>) -> &'a str {
+    cwd.and_then(|cwd| Path::new(path).strip_prefix(cwd).ok())
+        .and_then(Path::to_str)
+        .unwrap_or(path)
+}
+
+/// The branch checked out in the repository containing `dir`, read from
+/// `.git/HEAD` rather than by running git
+fn git_branch(dir: &str) -> Option<String> {
+    let git = Path::new(dir)
+        .ancestors()
+        .map(|dir| dir.join(".git"))
+        .find(|git| git.exists())?;
+    // A worktree's .git is a file pointing at its git directory
+    let git_dir = if git.is_file() {
+        let pointer = std::fs::read_to_string(&git).ok()?;
+        let target = pointer.trim().strip_prefix("gitdir:")?.trim();
+        git.parent()?.join(target)
+    } else {
+        git
+    };
+    let head = std::fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    head.trim()
+        .strip_prefix("ref: refs/heads/")
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn eval_with(source: &str, object: &Value) -> Result<bool> {
+        Expression::parse(source)?
+            .evaluate(&|name| object.get(name).cloned().unwrap_or(Value::Null))
+    }
+
+    #[test]
+    fn test_evaluate_combines_fields() {
+        let object = json!({
+            "path": "src/lib.rs",
+            "content": "x".repeat(20),
+            "branch": "feature/expr",
+            "input": { "replace_all": true, "tags": ["a", "b"] },
+        });
+        let expr = r#"path.startsWith("src/") && size(content) > 10 && branch != "main""#;
+        assert!(eval_with(expr, &object).unwrap());
+        assert!(!eval_with(r#"branch in ["main", 'release']"#, &object).unwrap());
+        assert!(eval_with("input.replace_all && !(exit_code == 0)", &object).unwrap());
+        assert!(eval_with(r#""b" in input.tags && input["tags"][0] == "a""#, &object).unwrap());
+        assert!(eval_with(r#"path.matches("\\.rs$") || tool == "Bash""#, &object).unwrap());
+        assert!(eval_with("content.size() >= 20 && exit_code == null", &object).unwrap());
+
+        // Runtime type errors don't match rather than erroring out a hook
+        assert!(eval_with("size(prompt) > 0", &object).is_err());
+        assert!(eval_with("path", &object).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors_name_the_problem() {
+        let error = |source: &str| Expression::parse(source).unwrap_err().to_string();
+        assert_eq!(error("path == \"a\" &&"), "expression ends early");
+        assert!(error("brnch == \"main\"").starts_with("unknown field 'brnch' at column 1"));
+        assert_eq!(
+            error("path.endswith(\".rs\")"),
+            "unknown method 'endswith' at column 6"
+        );
+        assert_eq!(
+            error("(tool == \"Bash\""),
+            "expected ')' at the end of the expression"
+        );
+        assert!(error("command.matches(\"(\")").starts_with("invalid regex \"(\" at column 9"));
+        assert_eq!(
+            error("tool = \"Bash\""),
+            "unexpected character '=' at column 6"
+        );
+    }
+
+    #[test]
+    fn test_matches_binds_event_fields() {
+        let event: Event = serde_json::from_value(json!({
+            "hook_event_name": "PreToolUse",
+            "tool_name": "Write",
+            "tool_input": { "file_path": "/repo/src/main.rs", "content": "fn main() {}" },
+            "session_id": "expr-test",
+            "cwd": "/repo",
+        }))
+        .unwrap();
+        assert!(matches(
+            &event,
+            r#"event == "PreToolUse" && tool == "Write" && path == "src/main.rs""#
+        ));
+        assert!(matches(&event, "size(content) == 12 && command == null"));
+        assert!(!matches(&event, "size(command) > 0"));
+    }
+
+    #[test]
+    fn test_git_branch_reads_head() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".git")).unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join(".git/HEAD"), "ref: refs/heads/main\n").unwrap();
+        let src = dir.path().join("src");
+        assert_eq!(git_branch(src.to_str().unwrap()).as_deref(), Some("main"));
+
+        std::fs::write(dir.path().join(".git/HEAD"), "3f2a9c1\n").unwrap();
+        assert_eq!(git_branch(src.to_str().unwrap()), None);
+    }
+}
@@ -0,0 +1,113 @@
+//! Front-matter conditions on injected context files
+//!
+//! A markdown file named by `inject` may open with a YAML front-matter block
+//! describing when it applies, so the document carries its own constraints
+//! instead of each rule repeating them:
+//!
+//! ```markdown
+//! ---
+//! only_tools: [Edit, Write]   # skip events from other tools
+//! max_injections: 2           # at most twice per session
+//! priority: 10                # injected before lower-priority files
+//! ---
+//! # Infrastructure guidelines
+//! ```
+//!
+//! The block is stripped before injection. Other keys (`title`, ...) are
+//! ignored, and a block that isn't valid YAML is injected as written.
+
+use serde::Deserialize;
+
+/// Conditions read from a context file's front matter
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+pub struct FrontMatter {
+    /// Tools whose events the file is injected for (unset: all)
+    #[serde(default)]
+    pub only_tools: Option<Vec<String>>,
+
+    /// Most times the file is injected in one session (unset: unlimited)
+    #[serde(default)]
+    pub max_injections: Option<u32>,
+
+    /// Files of one `inject` list are injected highest priority first
+    #[serde(default)]
+    pub priority: i32,
+}
+
+impl FrontMatter {
+    /// Whether `only_tools` admits an event from `tool_name`
+    pub fn allows_tool(&self, tool_name: Option<&str>) -> bool {
+        self.only_tools.as_ref().is_none_or(|tools| {
+            tool_name.is_some_and(|tool_name| tools.iter().any(|t| t == tool_name))
+        })
+    }
+}
+
+/// Split `content` into its front matter and the body to inject
+///
+/// Content without a front-matter block is returned whole with default
+/// (unconditional) front matter.
+pub fn parse(content: &str) -> (FrontMatter, &str) {
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return (FrontMatter::default(), content);
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            let yaml = &rest[..offset];
+            let body = &rest[offset + line.len()..];
+            return match serde_yaml::from_str::<Option<FrontMatter>>(yaml) {
+                Ok(front_matter) => (front_matter.unwrap_or_default(), body),
+                Err(e) => {
+                    tracing::warn!("Ignoring malformed front matter: {}", e);
+                    (FrontMatter::default(), content)
+                }
+            };
+        }
+        offset += line.len();
+    }
+    // No closing delimiter: not front matter
+    (FrontMatter::default(), content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_front_matter() {
+        let (front_matter, body) = parse(
+            "---\nonly_tools: [Edit, Write]\nmax_injections: 2\npriority: 5\ntitle: CDK\n---\n# CDK\n",
+        );
+        assert_eq!(
+            front_matter,
+            FrontMatter {
+                only_tools: Some(vec!["Edit".to_string(), "Write".to_string()]),
+                max_injections: Some(2),
+                priority: 5,
+            }
+        );
+        assert_eq!(body, "# CDK\n");
+        assert!(front_matter.allows_tool(Some("Write")));
+        assert!(!front_matter.allows_tool(Some("Bash")));
+        assert!(!front_matter.allows_tool(None));
+
+        let (front_matter, body) = parse("---\n---\nbody");
+        assert_eq!(front_matter, FrontMatter::default());
+        assert_eq!(body, "body");
+        assert!(front_matter.allows_tool(None));
+
+        // No block, an unclosed block, or invalid YAML inject everything
+        for content in [
+            "# Title\n---\n",
+            "---\npriority: 1\n",
+            "---\npriority: [\n---\nx",
+        ] {
+            assert_eq!(parse(content), (FrontMatter::default(), content));
+        }
+    }
+}
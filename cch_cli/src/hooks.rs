@@ -1,45 +1,165 @@
 use anyhow::Result;
-use regex::Regex;
+use serde::{Deserialize, Serialize};
 
+use std::borrow::Cow;
+use std::cell::OnceCell;
 use std::path::Path;
 use tokio::process::Command;
 use tokio::time::{Duration, timeout};
 
-use crate::config::Config;
-use crate::logging::log_entry;
+use crate::chaos::{self, Fault};
+use crate::config::{Config, EvaluationMode, EventArchive, SessionBanner, Settings};
+use crate::enrichment;
+use crate::expr;
+use crate::front_matter::{self, FrontMatter};
+use crate::journal;
+use crate::logging::{MAX_LOGGED_STRING_BYTES, archive, latency, log_entry, truncate_for_log};
 use crate::models::LogMetadata;
 use crate::models::{
-    DebugConfig, Decision, Event, EventDetails, GovernanceMetadata, LogEntry, LogTiming,
-    MatcherResults, Outcome, PolicyMode, Response, ResponseSummary, Rule, RuleEvaluation, Timing,
-    TrustLevel,
+    AllowOverride, AppliedExemption, DebugConfig, Decision, EnsureHeader, Escalation,
+    EscalationStrategy, EvaluationReport, Event, EventDetails, EventType, Exemption, ExitCodeMatch,
+    FilterResults, GovernanceMetadata, InjectAction, InjectChannel, InjectPosition, InjectSource,
+    LOG_SCHEMA_VERSION, LogEntry, LogTiming, MatchedRule, MatcherResults, Matchers, Outcome,
+    PolicyMode, PostProcessorRun, PromptEnrichment, ProtectedPaths, Response, ResponseSummary,
+    RewriteInput, Rule, RuleCounts, RuleEvaluation, RuleMessages, RunAction, Sensitivity,
+    ThrottleScope, Timing, TruncateOutput, TrustLevel, ValidatorRun, path_is_under, zone_level,
 };
+use crate::policy::PolicyMatcher;
+use crate::regex_cache;
+use crate::state::{self, GlobalState, MemoryState, SessionState};
+use crate::structural::{self, StructuralCheck};
+use crate::validator_cache;
+use crate::wasm_validator;
 
 /// Process a hook event and return the appropriate response
 pub async fn process_event(event: Event, debug_config: &DebugConfig) -> Result<Response> {
+    Ok(evaluate_event(event, debug_config).await?.response)
+}
+
+/// Process a hook event and report the response together with the rules
+/// and exemptions that produced it
+pub async fn evaluate_event(event: Event, debug_config: &DebugConfig) -> Result<EvaluationReport> {
+    run_event(event, debug_config, true).await
+}
+
+/// Process a simulated event (`cch debug`, the daemon's decision API)
+/// exactly like a real one, but without writing the audit log entry,
+/// latency sample, change journal or session state, and without running
+/// the post-processor, so simulations leave no trace in `cch logs`,
+/// `cch stats` or later decisions
+pub async fn simulate_event(event: Event, debug_config: &DebugConfig) -> Result<EvaluationReport> {
+    run_event(event, debug_config, false).await
+}
+
+/// Process a hook event, recording it in the audit trail and session state
+/// when `record` is set
+async fn run_event(
+    event: Event,
+    debug_config: &DebugConfig,
+    record: bool,
+) -> Result<EvaluationReport> {
     let start_time = std::time::Instant::now();
 
     // Load configuration using the event's cwd (sent by Claude Code) for project-level config
-    let config = Config::load(event.cwd.as_ref().map(|p| Path::new(p.as_str())))?;
+    let config = Config::load_cached(event.cwd.as_ref().map(|p| Path::new(p.as_str())))?;
+    let debug_config = &DebugConfig {
+        enabled: debug_config.enabled || config.settings.debug_logs,
+    };
+    // A dry run must not leave throttles or escalations behind either
+    let dry_run = config.dry_run();
+    let mut state_store = StateStore::new(Persistence::saving(record && !dry_run));
 
     // Evaluate rules (with optional debug tracking)
-    let (matched_rules, response, rule_evaluations) =
-        evaluate_rules(&event, &config, debug_config).await?;
+    let Evaluation {
+        matched_rules,
+        response,
+        rule_evaluations,
+        exemptions,
+        validator_runs,
+        context_truncated,
+        suppressed_injections,
+        allow_override,
+        counts,
+    } = evaluate_rules(&event, &config, debug_config, &mut state_store).await?;
+
+    // The organization's post-processor gets the final veto
+    let mut response = response;
+    let post_processor = if record {
+        post_process(&event, &matched_rules, &mut response, &config).await?
+    } else {
+        None
+    };
+
+    // Journal file state around writes; a blocked call never writes, so
+    // only allowed operations get a before snapshot
+    if record && config.settings.change_journal && (response.continue_ || dry_run) {
+        if let Err(e) = journal::record(&event) {
+            tracing::warn!("Failed to record change journal: {}", e);
+        }
+    }
 
     let processing_time = start_time.elapsed().as_millis() as u64;
+    let timing = Timing {
+        processing_ms: processing_time,
+        rules_evaluated: rule_evaluations.len(),
+        counts,
+    };
 
     // Build enhanced logging fields
     let event_details = EventDetails::extract(&event);
-    let response_summary = ResponseSummary::from_response(&response);
 
     // Extract governance data from the primary matched rule (first/highest priority)
     let (primary_mode, primary_priority, primary_governance, trust_level) =
-        extract_governance_data(&matched_rules);
+        extract_governance_data(&matched_rules, &config);
 
     // Determine decision based on response and mode
-    let decision = primary_mode.map(|m| determine_decision(&response, m));
+    let decision = primary_mode.map(|m| event_decision(&response, m, &timing.counts));
+
+    // Summarize the governing policy when a session starts
+    let banner = response
+        .continue_
+        .then(|| session_banner(&event, &config, &mut state_store))
+        .flatten();
+    if let Some(banner) = banner {
+        response.context = Some(match response.context.take() {
+            Some(context) => format!("{}\n\n{}", banner, context),
+            None => banner,
+        });
+    }
+
+    // Tell Claude which rules applied so it stops retrying trivial variations
+    if config.settings.explain_in_response && (!matched_rules.is_empty() || !exemptions.is_empty())
+    {
+        append_policy_summary(
+            &mut response,
+            policy_summary(&matched_rules, &exemptions, decision, &config),
+        );
+    }
+
+    // Stop repeating the full reason to an agent that keeps retrying
+    let repeat_count = match (config.settings.repeat_block_limit, response.continue_) {
+        (Some(limit), false) => suppress_repeated_block(
+            &event,
+            &matched_rules,
+            &mut response,
+            limit,
+            &mut state_store,
+        ),
+        _ => None,
+    };
+    state_store.save(&event, &config.settings);
+
+    // Claude Code only adds context to a prompt through hookSpecificOutput
+    if event.hook_event_name == EventType::UserPromptSubmit && response.continue_ {
+        response.add_prompt_context();
+    }
+
+    let mut response_summary = ResponseSummary::from_response(&response);
+    response_summary.context_truncated = context_truncated;
 
     // Log the event with enhanced fields
     let entry = LogEntry {
+        schema_version: LOG_SCHEMA_VERSION,
         timestamp: event.timestamp,
         event_type: format!("{:?}", event.hook_event_name),
         session_id: event.session_id.clone(),
@@ -50,26 +170,27 @@ pub async fn process_event(event: Event, debug_config: &DebugConfig) -> Result<R
             true => Outcome::Allow,
             false => Outcome::Block,
         },
-        timing: LogTiming {
-            processing_ms: processing_time,
-            rules_evaluated: config.enabled_rules().len(),
-        },
+        timing: LogTiming::from(&timing),
         metadata: Some(LogMetadata {
             injected_files: response
                 .context
                 .as_ref()
                 .map(|_| vec!["injected".to_string()]),
             validator_output: None,
+            suppressed_injections: (!suppressed_injections.is_empty())
+                .then_some(suppressed_injections),
         }),
         // Enhanced logging fields (CRD-001)
         event_details: Some(event_details),
         response: Some(response_summary),
-        raw_event: if debug_config.enabled {
-            Some(serde_json::to_value(&event).unwrap_or_default())
+        raw_event: if debug_config.enabled && repeat_count.is_none() {
+            let mut raw = serde_json::to_value(&event).unwrap_or_default();
+            truncate_for_log(&mut raw, MAX_LOGGED_STRING_BYTES);
+            Some(raw)
         } else {
             None
         },
-        rule_evaluations: if debug_config.enabled {
+        rule_evaluations: if debug_config.enabled && repeat_count.is_none() {
             Some(rule_evaluations)
         } else {
             None
@@ -80,25 +201,240 @@ pub async fn process_event(event: Event, debug_config: &DebugConfig) -> Result<R
         decision,
         governance: primary_governance,
         trust_level,
+        exemptions: if exemptions.is_empty() {
+            None
+        } else {
+            Some(exemptions.clone())
+        },
+        validators: if validator_runs.is_empty() {
+            None
+        } else {
+            Some(validator_runs)
+        },
+        repeat_count,
+        allow_override,
+        post_processor,
+        dry_run,
+        invalid_patterns: None,
     };
 
-    // Log asynchronously (don't fail the response if logging fails)
-    let _ = log_entry(entry).await;
+    if record {
+        write_audit(entry, &event, config.settings.event_archive.as_ref()).await;
+    }
+
+    // A dry run logs what the rules decided but lets everything through
+    if dry_run {
+        response = Response::allow();
+    }
 
     // Add timing to response
-    let mut response = response;
-    response.timing = Some(Timing {
-        processing_ms: processing_time,
-        rules_evaluated: config.enabled_rules().len(),
-    });
+    response.timing = Some(timing);
 
-    Ok(response)
+    Ok(EvaluationReport {
+        response,
+        decision,
+        matched_rules: matched_rules
+            .iter()
+            .map(|r| MatchedRule::from(*r))
+            .collect(),
+        exemptions,
+    })
+}
+
+/// Log an evaluated event, with any patterns found invalid since the last
+/// entry, and its latency, and archive it with `settings.event_archive`
+/// (failures don't fail the response)
+async fn write_audit(mut entry: LogEntry, event: &Event, event_archive: Option<&EventArchive>) {
+    let invalid_patterns = regex_cache::take_failures();
+    entry.invalid_patterns = (!invalid_patterns.is_empty()).then_some(invalid_patterns);
+    if let Some(settings) = event_archive {
+        let archived = archive::record(
+            settings,
+            event,
+            entry.decision,
+            &entry.rules_matched,
+            entry.timestamp,
+        );
+        if let Err(e) = archived {
+            tracing::warn!("Failed to archive event: {}", e);
+        }
+    }
+    let processing_ms = entry.timing.processing_ms;
+    let date = entry.timestamp.date_naive();
+    let _ = log_entry(entry).await;
+    if let Err(e) = latency::record(&latency::latency_dir(), date, processing_ms) {
+        tracing::warn!("Failed to record latency: {}", e);
+    }
+}
+
+/// Add the `explain_in_response` summary to the context of an allowed
+/// operation, or to the reason of a blocked one
+fn append_policy_summary(response: &mut Response, summary: String) {
+    if response.continue_ {
+        response.context = Some(match response.context.take() {
+            Some(context) => format!("{}\n\n{}", context, summary),
+            None => summary,
+        });
+    } else {
+        response.reason = Some(match response.reason.take() {
+            Some(reason) => format!("{}\n{}", reason, summary),
+            None => summary,
+        });
+    }
+}
+
+/// Evaluate an event against `config` without logging it or consulting
+/// the response-shaping settings, reporting what the rules alone decided
+///
+/// Each call starts from empty session state and leaves none behind.
+pub async fn evaluate_with_config(event: &Event, config: &Config) -> Result<EvaluationReport> {
+    evaluate_in_memory(event, config, &mut MemoryState::default()).await
+}
+
+/// Like [`evaluate_with_config`], with throttles, escalations and
+/// once-per-session injects counted in `memory` instead of
+/// ~/.claude/state
+///
+/// Used by `cch test`, whose cases share one `memory` per run so
+/// escalation can be tested by repeating an event, while results never
+/// depend on earlier runs or on the user's real sessions.
+pub async fn evaluate_in_memory(
+    event: &Event,
+    config: &Config,
+    memory: &mut MemoryState,
+) -> Result<EvaluationReport> {
+    let mut state_store = StateStore::in_memory(event, memory);
+    let (report, _) =
+        report_evaluation(event, config, &DebugConfig::default(), &mut state_store).await?;
+    state_store.keep(event, memory);
+    Ok(report)
+}
+
+/// Like [`evaluate_with_config`], also returning each enabled rule's
+/// evaluation (with matcher results when `debug_config` is enabled), with
+/// session state handled as `persistence` says
+#[allow(dead_code)] // Used by the embeddable engine, which the binary doesn't include
+pub(crate) async fn evaluate_traced(
+    event: &Event,
+    config: &Config,
+    debug_config: &DebugConfig,
+    persistence: Persistence,
+) -> Result<(EvaluationReport, Vec<RuleEvaluation>)> {
+    let mut state_store = StateStore::new(persistence);
+    let traced = report_evaluation(event, config, debug_config, &mut state_store).await?;
+    state_store.save(event, &config.settings);
+    Ok(traced)
+}
+
+async fn report_evaluation(
+    event: &Event,
+    config: &Config,
+    debug_config: &DebugConfig,
+    state_store: &mut StateStore,
+) -> Result<(EvaluationReport, Vec<RuleEvaluation>)> {
+    let Evaluation {
+        counts,
+        matched_rules,
+        response,
+        rule_evaluations,
+        exemptions,
+        ..
+    } = evaluate_rules(event, config, debug_config, state_store).await?;
+
+    let (primary_mode, ..) = extract_governance_data(&matched_rules, config);
+    let report = EvaluationReport {
+        decision: primary_mode.map(|m| event_decision(&response, m, &counts)),
+        response,
+        matched_rules: matched_rules
+            .iter()
+            .map(|r| MatchedRule::from(*r))
+            .collect(),
+        exemptions,
+    };
+    Ok((report, rule_evaluations))
+}
+
+/// The `settings.session_banner` summary for a SessionStart event, unless
+/// the session was already shown it (it starts again on resume and after
+/// compaction)
+fn session_banner(event: &Event, config: &Config, store: &mut StateStore) -> Option<String> {
+    let banner = config.settings.session_banner.as_ref()?;
+    if event.hook_event_name != EventType::SessionStart {
+        return None;
+    }
+    let state = store.session(event)?;
+    if state.banner_shown {
+        return None;
+    }
+    state.banner_shown = true;
+    Some(render_banner(
+        banner,
+        config,
+        chrono::Local::now().date_naive(),
+    ))
+}
+
+/// Summarize the enabled rules by the mode they run in today
+fn render_banner(banner: &SessionBanner, config: &Config, today: chrono::NaiveDate) -> String {
+    let enabled = config.enabled_rules();
+    let count = |mode: PolicyMode| {
+        enabled
+            .iter()
+            .filter(|rule| config.rule_mode(rule, today) == mode)
+            .count()
+    };
+    let mut text = format!(
+        "[CCH POLICY] {}: {} enforce, {} warn, {} audit rules active. Blocks and warnings in this session come from these rules.",
+        banner.profile.as_ref().map_or_else(
+            || "Active policy".to_string(),
+            |p| format!("Profile '{}'", p)
+        ),
+        count(PolicyMode::Enforce),
+        count(PolicyMode::Warn),
+        count(PolicyMode::Audit)
+    );
+    if let Some(ref note) = banner.note {
+        text.push('\n');
+        text.push_str(note);
+    }
+    text
+}
+
+/// Count an identical block for the session and, past `limit`, replace the
+/// reason with a short advisory. Returns the repeat count once suppressing.
+fn suppress_repeated_block(
+    event: &Event,
+    matched_rules: &[&Rule],
+    response: &mut Response,
+    limit: u32,
+    store: &mut StateStore,
+) -> Option<u32> {
+    let count = store
+        .session(event)?
+        .record_block(response.reason.as_deref().unwrap_or_default());
+    if count <= limit {
+        return None;
+    }
+
+    let rules = matched_rules
+        .iter()
+        .map(|r| format!("'{}'", r.name))
+        .collect::<Vec<_>>()
+        .join(", ");
+    response.reason = Some(format!(
+        "Blocked again by rule {} ({} identical blocks this session). \
+         Stop retrying this operation; it will keep being blocked. \
+         Ask the user how to proceed or take a different approach.",
+        rules, count
+    ));
+    Some(count)
 }
 
 /// Extract governance data from matched rules
 /// Returns (mode, priority, governance, trust_level) from the primary (first) matched rule
 fn extract_governance_data(
     matched_rules: &[&Rule],
+    config: &Config,
 ) -> (
     Option<PolicyMode>,
     Option<i32>,
@@ -106,7 +442,7 @@ fn extract_governance_data(
     Option<TrustLevel>,
 ) {
     if let Some(primary) = matched_rules.first() {
-        let mode = Some(primary.effective_mode());
+        let mode = Some(config.rule_mode(primary, chrono::Local::now().date_naive()));
         let priority = Some(primary.effective_priority());
         let governance = primary.governance.clone();
         let trust_level = primary.actions.trust_level();
@@ -116,25 +452,110 @@ fn extract_governance_data(
     }
 }
 
-/// Evaluate all enabled rules against an event
+/// Build the one-line policy summary for `settings.explain_in_response`
+///
+/// Format: `[cch-policy] {"decision":...,"matched":[...],"exempted":[...]}`
+/// Each rule is listed with the mode it runs in today.
+fn policy_summary(
+    matched_rules: &[&Rule],
+    exemptions: &[AppliedExemption],
+    decision: Option<Decision>,
+    config: &Config,
+) -> String {
+    let today = chrono::Local::now().date_naive();
+    let matched: Vec<serde_json::Value> = matched_rules
+        .iter()
+        .map(|rule| {
+            serde_json::json!({
+                "rule": rule.name,
+                "mode": config.rule_mode(rule, today),
+                "why": rule.description,
+            })
+        })
+        .collect();
+
+    let summary = serde_json::json!({
+        "decision": decision.unwrap_or(Decision::Allowed),
+        "matched": matched,
+        "exempted": exemptions,
+    });
+    format!("[cch-policy] {}", summary)
+}
+
+/// Outcome of evaluating all rules against one event
+struct Evaluation<'a> {
+    matched_rules: Vec<&'a Rule>,
+    response: Response,
+    rule_evaluations: Vec<RuleEvaluation>,
+    exemptions: Vec<AppliedExemption>,
+    validator_runs: Vec<ValidatorRun>,
+    context_truncated: bool,
+    suppressed_injections: Vec<String>,
+    allow_override: Option<AllowOverride>,
+    counts: RuleCounts,
+}
+
+/// Evaluate all enabled rules against an event, keeping throttle,
+/// escalation and injection counts in `state_store` for the caller to save
 /// Rules are sorted by priority (higher first) by config.enabled_rules()
 async fn evaluate_rules<'a>(
     event: &'a Event,
     config: &'a Config,
     debug_config: &DebugConfig,
-) -> Result<(Vec<&'a Rule>, Response, Vec<RuleEvaluation>)> {
+    state_store: &mut StateStore,
+) -> Result<Evaluation<'a>> {
     let mut matched_rules = Vec::new();
     let mut response = Response::allow();
     let mut rule_evaluations = Vec::new();
+    let mut applied_exemptions = Vec::new();
+    let mut validator_runs = Vec::new();
+    let mut warnings: Vec<(String, String)> = Vec::new();
+    let mut suppressed_injections = Vec::new();
+    let mut identities = None;
+    let mut cap = ContextCap::new(config.settings.max_context_size);
+    let mut allowing_rule: Option<&Rule> = None;
+    let mut overridden = Vec::new();
+    let mut counts = RuleCounts::default();
+
+    let today = chrono::Local::now().date_naive();
 
     // Get enabled rules (already sorted by priority in Config::enabled_rules)
     for rule in config.enabled_rules() {
-        let (matched, matcher_results) = if debug_config.enabled {
-            matches_rule_with_debug(event, rule)
+        let (mut matched, matcher_results) = if debug_config.enabled {
+            matches_rule_with_debug(event, rule, config)
         } else {
-            (matches_rule(event, rule), None)
+            (matches_rule(event, rule, config), None)
         };
 
+        // Exempted events are treated as non-matching, but recorded
+        if matched {
+            if let Some(exemption) = find_exemption(event, rule) {
+                tracing::info!("Rule '{}' exempted: {}", rule.name, exemption.justification);
+                applied_exemptions.push(AppliedExemption {
+                    rule: rule.name.clone(),
+                    justification: exemption.justification.clone(),
+                });
+                matched = false;
+            } else if let Some(justification) = inline_suppression(event, rule, config) {
+                tracing::info!("Rule '{}' suppressed inline: {}", rule.name, justification);
+                applied_exemptions.push(AppliedExemption {
+                    rule: rule.name.clone(),
+                    justification,
+                });
+                matched = false;
+            } else if let Some(user) = bypassing_user(event, rule, config, &mut identities) {
+                tracing::info!("Rule '{}' bypassed for {}", rule.name, user);
+                applied_exemptions.push(AppliedExemption {
+                    rule: rule.name.clone(),
+                    justification: format!("bypass_users: {}", user),
+                });
+                matched = false;
+            } else if !throttle_permits(event, rule, state_store) {
+                tracing::info!("Rule '{}' throttled", rule.name);
+                matched = false;
+            }
+        }
+
         let rule_evaluation = RuleEvaluation {
             rule_name: rule.name.clone(),
             matched,
@@ -146,21 +567,601 @@ async fn evaluate_rules<'a>(
             matched_rules.push(rule);
 
             // Execute rule actions based on mode (Phase 2 Governance)
-            let mode = rule.effective_mode();
-            let rule_response = execute_rule_actions_with_mode(event, rule, config, mode).await?;
+            let mut mode = config.rule_mode(rule, today);
+            let escalation = rule
+                .escalation
+                .as_ref()
+                .filter(|_| mode != PolicyMode::Audit);
+            let (actionable, suppressed) = unsent_injections(event, rule, state_store);
+            let actionable = unsent_enrichment(event, actionable, state_store);
+            suppressed_injections.extend(suppressed);
+            let offense_key = escalation_key(event);
+            if let Some(escalated) =
+                escalated_mode(event, rule, escalation, &offense_key, state_store)
+            {
+                mode = escalated;
+            }
+
+            let mut rule_response = execute_rule_actions_with_mode(
+                event,
+                &actionable,
+                config,
+                mode,
+                &mut validator_runs,
+                state_store,
+            )
+            .await?;
+
+            if override_block(allowing_rule, rule, &mut rule_response) {
+                overridden.push(rule.name.clone());
+            }
+            if allowing_rule.is_none()
+                && mode == PolicyMode::Enforce
+                && rule.actions.allow == Some(true)
+            {
+                allowing_rule = Some(rule);
+            }
+
+            if let (Some(escalation), Some(state)) = (
+                escalation,
+                escalation.and_then(|_| state_store.session(event)),
+            ) {
+                record_offense(rule, escalation, state, &offense_key, &mut rule_response);
+            }
+            record_sent_injections(event, &actionable, &rule_response, state_store);
+            record_sent_enrichment(event, &actionable, &rule_response, state_store);
+
+            // Collect warnings (warn-mode rules and validator `warn`
+            // verdicts) separately so duplicates can be dropped
+            let warning = rule_response.context.as_deref().and_then(parse_warning);
+            counts.record(mode, !rule_response.continue_, warning.is_some());
+            if let Some((rule_name, reason)) = warning {
+                let warning = (rule_name.to_string(), reason.to_string());
+                if !warnings.contains(&warning) {
+                    warnings.push(warning);
+                }
+            } else {
+                // Merge responses based on mode (block takes precedence, inject accumulates)
+                response = merge_placed(
+                    response,
+                    rule_response,
+                    actionable.actions.inject.as_ref(),
+                    mode,
+                    &mut cap,
+                );
+            }
+
+            if stops_evaluation(rule, mode, config) {
+                tracing::debug!("Rule '{}' stops evaluation", rule.name);
+                break;
+            }
+        }
+    }
+
+    let response = merge_warnings(
+        response,
+        &warnings,
+        &config.rules,
+        config.settings.collapse_warnings,
+        &mut cap,
+    );
+
+    Ok(Evaluation {
+        counts,
+        matched_rules,
+        response,
+        rule_evaluations,
+        exemptions: applied_exemptions,
+        validator_runs,
+        context_truncated: cap.truncated,
+        suppressed_injections,
+        allow_override: allowing_rule
+            .filter(|_| !overridden.is_empty())
+            .map(|rule| AllowOverride {
+                rule: rule.name.clone(),
+                overridden,
+            }),
+    })
+}
 
-            // Merge responses based on mode (block takes precedence, inject accumulates)
-            response = merge_responses_with_mode(response, rule_response, mode);
+/// Lift `rule`'s block when an `allow` rule of strictly higher priority
+/// already matched; returns whether the block was overridden
+fn override_block(allowing_rule: Option<&Rule>, rule: &Rule, response: &mut Response) -> bool {
+    let allow =
+        allowing_rule.filter(|allow| allow.effective_priority() > rule.effective_priority());
+    let Some(allow) = allow.filter(|_| !response.continue_) else {
+        return false;
+    };
+    tracing::info!(
+        "Rule '{}' block overridden by allow rule '{}'",
+        rule.name,
+        allow.name
+    );
+    response.continue_ = true;
+    response.reason = None;
+    true
+}
+
+/// Whether a matched rule ends evaluation, by its `stop` flag or under
+/// `settings.evaluation: first_match`; audit rules only observe, so they
+/// never do
+fn stops_evaluation(rule: &Rule, mode: PolicyMode, config: &Config) -> bool {
+    mode != PolicyMode::Audit
+        && (rule.stop.unwrap_or(false) || config.settings.evaluation == EvaluationMode::FirstMatch)
+}
+
+/// Identities of whoever triggered the event: its `user_id` and the git
+/// author (name and email) in its working directory, which honors
+/// `GIT_AUTHOR_*` as CI runners commonly set them. Fast-path events don't
+/// run git, so only their `user_id` counts.
+fn user_identities(event: &Event, config: &Config) -> Vec<String> {
+    let mut identities: Vec<String> = event.user_id.iter().cloned().collect();
+    if on_fast_path(event, config) {
+        return identities;
+    }
+
+    let mut git = std::process::Command::new("git");
+    git.args(["var", "GIT_AUTHOR_IDENT"]);
+    if let Some(cwd) = event.cwd.as_deref().filter(|dir| Path::new(dir).is_dir()) {
+        git.current_dir(cwd);
+    }
+    if let Ok(output) = git.output() {
+        let ident = String::from_utf8_lossy(&output.stdout);
+        // "Name <email> timestamp tz"
+        if let Some((name, rest)) = ident.split_once('<') {
+            identities.push(name.trim().to_string());
+            if let Some((email, _)) = rest.split_once('>') {
+                identities.push(email.trim().to_string());
+            }
+        }
+    }
+    identities.retain(|identity| !identity.is_empty());
+    identities
+}
+
+/// The first of the rule's `bypass_users` among the event's identities,
+/// which are looked up once per event and only for rules that need them;
+/// identities compare case-insensitively, as emails do
+fn bypassing_user<'r>(
+    event: &Event,
+    rule: &'r Rule,
+    config: &Config,
+    identities: &mut Option<Vec<String>>,
+) -> Option<&'r str> {
+    let users = rule.bypass_users.as_ref()?;
+    let identities = identities.get_or_insert_with(|| user_identities(event, config));
+    users
+        .iter()
+        .find(|user| identities.iter().any(|id| id.eq_ignore_ascii_case(user)))
+        .map(String::as_str)
+}
+
+/// The mode a `warn_then_block` escalation puts a matched rule in: warn
+/// until the offense reaches `after`, enforce from then on
+fn escalated_mode(
+    event: &Event,
+    rule: &Rule,
+    escalation: Option<&Escalation>,
+    key: &str,
+    store: &mut StateStore,
+) -> Option<PolicyMode> {
+    let escalation = escalation.filter(|e| e.strategy == EscalationStrategy::WarnThenBlock)?;
+    let offense = store.session(event)?.offenses(&rule.name, key) + 1;
+    Some(if offense >= escalation.after {
+        PolicyMode::Enforce
+    } else {
+        PolicyMode::Warn
+    })
+}
+
+/// Remember an offense (a block, or a warning about a would-be block) and
+/// add the `block_then_advise` advisory once blocks repeat
+fn record_offense(
+    rule: &Rule,
+    escalation: &Escalation,
+    state: &mut SessionState,
+    key: &str,
+    response: &mut Response,
+) {
+    let offended = !response.continue_
+        || response
+            .context
+            .as_deref()
+            .is_some_and(|c| parse_warning(c).is_some());
+    if !offended {
+        return;
+    }
+    state.record_offense(&rule.name, key);
+    if escalation.strategy == EscalationStrategy::BlockThenAdvise && !response.continue_ {
+        let blocks = state.rule_offenses(&rule.name);
+        if blocks >= escalation.after {
+            let reason = response.reason.take().unwrap_or_default();
+            response.reason = Some(format!(
+                "{}\n{}",
+                reason,
+                advisory(rule, escalation, blocks)
+            ));
+        }
+    }
+}
+
+/// `rule` without the inject sources this session already received, when
+/// it sets `inject_once_per_session`, and the paths of those sources
+fn unsent_injections<'r>(
+    event: &Event,
+    rule: &'r Rule,
+    store: &mut StateStore,
+) -> (Cow<'r, Rule>, Vec<String>) {
+    if rule.actions.inject_once_per_session != Some(true) {
+        return (Cow::Borrowed(rule), Vec::new());
+    }
+    let (Some(inject), Some(state)) = (rule.actions.inject.as_ref(), store.session(event)) else {
+        return (Cow::Borrowed(rule), Vec::new());
+    };
+    let (sent, unsent): (Vec<&InjectSource>, Vec<&InjectSource>) = inject
+        .sources()
+        .iter()
+        .partition(|source| state.sent_injections.contains(source.path()));
+    if sent.is_empty() {
+        return (Cow::Borrowed(rule), Vec::new());
+    }
+    tracing::debug!(
+        "Rule '{}' skips {} inject source(s) already sent",
+        rule.name,
+        sent.len()
+    );
+    let mut rule = rule.clone();
+    rule.actions.inject =
+        (!unsent.is_empty()).then(|| inject.with_sources(unsent.into_iter().cloned().collect()));
+    let sent = sent
+        .iter()
+        .map(|source| source.path().to_string())
+        .collect();
+    (Cow::Owned(rule), sent)
+}
+
+/// Remember the inject sources of an `inject_once_per_session` rule once
+/// its response carried context (rather than a warning)
+fn record_sent_injections(event: &Event, rule: &Rule, response: &Response, store: &mut StateStore) {
+    let injected = response
+        .context
+        .as_deref()
+        .is_some_and(|c| parse_warning(c).is_none());
+    if rule.actions.inject_once_per_session != Some(true)
+        || rule.actions.inject.is_none()
+        || !injected
+    {
+        return;
+    }
+    if let Some(state) = store.session(event) {
+        state
+            .sent_injections
+            .extend(rule.actions.inject_paths().map(str::to_string));
+    }
+}
+
+/// `rule` without the `enrich_prompt` entries this session already
+/// received, unless it sends them on every prompt
+fn unsent_enrichment<'r>(
+    event: &Event,
+    rule: Cow<'r, Rule>,
+    store: &mut StateStore,
+) -> Cow<'r, Rule> {
+    let Some(enrichment) = rule
+        .actions
+        .enrich_prompt
+        .as_ref()
+        .filter(|enrichment| enrichment.once_per_session)
+    else {
+        return rule;
+    };
+    let Some(sent) = store
+        .session(event)
+        .map(|state| &state.sent_enrichments)
+        .filter(|sent| !sent.is_empty())
+    else {
+        return rule;
+    };
+    let unsent = enrichment::unsent(enrichment, sent);
+    let mut rule = rule.into_owned();
+    rule.actions.enrich_prompt = Some(unsent);
+    Cow::Owned(rule)
+}
+
+/// Remember the `enrich_prompt` entries a response sent as context
+fn record_sent_enrichment(event: &Event, rule: &Rule, response: &Response, store: &mut StateStore) {
+    let prompt = event
+        .prompt
+        .as_deref()
+        .filter(|_| event.hook_event_name == EventType::UserPromptSubmit);
+    let (Some(enrichment), Some(prompt)) = (rule.actions.enrich_prompt.as_ref(), prompt) else {
+        return;
+    };
+    let injected = response
+        .context
+        .as_deref()
+        .is_some_and(|c| parse_warning(c).is_none());
+    if !enrichment.once_per_session || !injected {
+        return;
+    }
+    let entries = enrichment::entries(enrichment, prompt);
+    if let Some(state) = store.session(event) {
+        state
+            .sent_enrichments
+            .extend(entries.into_iter().map(|entry| entry.key));
+    }
+}
+
+/// How an evaluation treats the session and global state in
+/// `~/.claude/state`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Persistence {
+    /// Load the state and save what evaluation changed (hook events)
+    ReadWrite,
+    /// Load the state but discard changes (simulations and dry runs)
+    ReadOnly,
+    /// Start from empty (or in-memory) state and discard changes
+    Ephemeral,
+}
+
+impl Persistence {
+    /// [`Persistence::ReadWrite`] when `save` is set, else
+    /// [`Persistence::ReadOnly`]
+    fn saving(save: bool) -> Self {
+        if save {
+            Self::ReadWrite
+        } else {
+            Self::ReadOnly
+        }
+    }
+}
+
+/// Session and global state, each loaded the first time a rule needs it
+/// and saved once evaluation ends
+struct StateStore {
+    persistence: Persistence,
+    session: OnceCell<Option<SessionState>>,
+    global: OnceCell<Option<GlobalState>>,
+}
+
+impl StateStore {
+    fn new(persistence: Persistence) -> Self {
+        Self {
+            persistence,
+            session: OnceCell::new(),
+            global: OnceCell::new(),
         }
     }
 
-    Ok((matched_rules, response, rule_evaluations))
+    /// A store over the state in `memory`, never reading or writing disk
+    fn in_memory(event: &Event, memory: &MemoryState) -> Self {
+        let session = memory
+            .sessions
+            .get(&event.session_id)
+            .cloned()
+            .unwrap_or_default();
+        Self {
+            persistence: Persistence::Ephemeral,
+            session: OnceCell::from(Some(session)),
+            global: OnceCell::from(Some(memory.global.clone())),
+        }
+    }
+
+    /// Put the state back into `memory` for the next evaluation
+    fn keep(self, event: &Event, memory: &mut MemoryState) {
+        if let Some(Some(state)) = self.session.into_inner() {
+            memory.sessions.insert(event.session_id.clone(), state);
+        }
+        if let Some(Some(state)) = self.global.into_inner() {
+            memory.global = state;
+        }
+    }
+
+    fn session(&mut self, event: &Event) -> Option<&mut SessionState> {
+        let ephemeral = self.persistence == Persistence::Ephemeral;
+        self.session.get_or_init(|| {
+            if ephemeral {
+                Some(SessionState::default())
+            } else {
+                load_session_state(event)
+            }
+        });
+        self.session.get_mut()?.as_mut()
+    }
+
+    fn global(&mut self) -> Option<&mut GlobalState> {
+        let ephemeral = self.persistence == Persistence::Ephemeral;
+        self.global.get_or_init(|| {
+            if ephemeral {
+                return Some(GlobalState::default());
+            }
+            GlobalState::load()
+                .map_err(|e| tracing::warn!("Failed to load global state: {}", e))
+                .ok()
+        });
+        self.global.get_mut()?.as_mut()
+    }
+
+    /// Save what was loaded, then drop sessions past `state_ttl_days`;
+    /// nothing is written unless the store is [`Persistence::ReadWrite`]
+    fn save(self, event: &Event, settings: &Settings) {
+        if self.persistence != Persistence::ReadWrite {
+            return;
+        }
+        if let Some(Some(state)) = self.session.into_inner() {
+            if let Err(e) = state.save(&event.session_id, settings.encrypt_state) {
+                tracing::warn!("Failed to save session state: {}", e);
+            }
+        }
+        if let Some(Some(state)) = self.global.into_inner() {
+            if let Err(e) = state.save(settings.encrypt_state) {
+                tracing::warn!("Failed to save global state: {}", e);
+            }
+        }
+        if let Some(ttl_days) = settings.state_ttl_days {
+            match state::expire_sessions(ttl_days) {
+                Ok(0) => {}
+                Ok(removed) => tracing::debug!("Expired {} session state file(s)", removed),
+                Err(e) => tracing::warn!("Failed to expire session state: {}", e),
+            }
+        }
+    }
+}
+
+/// Whether a matched rule's `throttle` lets it fire, recording the firing
+/// in the session or global state; without readable state the rule fires
+fn throttle_permits(event: &Event, rule: &Rule, store: &mut StateStore) -> bool {
+    let Some(ref throttle) = rule.actions.throttle else {
+        return true;
+    };
+    let firings = match throttle.scope {
+        ThrottleScope::Session => store.session(event).map(|state| &mut state.firings),
+        ThrottleScope::Global => store.global().map(|state| &mut state.firings),
+    };
+    let Some(firings) = firings else {
+        return true;
+    };
+    // `per` was checked when the config loaded
+    let window = throttle.window().ok().flatten();
+    firings.try_fire(&rule.name, throttle.max, window, chrono::Utc::now())
+}
+
+/// Load the session state; escalation, throttles and injection limits are
+/// skipped (fail open) when it can't be read
+fn load_session_state(event: &Event) -> Option<SessionState> {
+    SessionState::load(&event.session_id)
+        .map_err(|e| tracing::warn!("Failed to load session state: {}", e))
+        .ok()
+}
+
+/// Identify the offending operation: the Bash command or target path
+fn escalation_key(event: &Event) -> String {
+    let tool_input = event.tool_input.as_ref();
+    tool_input
+        .and_then(|ti| ti.get("command"))
+        .and_then(|c| c.as_str())
+        .or_else(|| tool_input.and_then(target_path_from_input))
+        .map(|key| key.trim().to_string())
+        .or_else(|| event.tool_name.clone())
+        .unwrap_or_default()
+}
+
+/// Advisory appended to repeated blocks by `block_then_advise`
+fn advisory(rule: &Rule, escalation: &Escalation, blocks: u32) -> String {
+    let advice = escalation
+        .advisory
+        .as_deref()
+        .unwrap_or("Stop retrying variations of this operation and ask the user how to proceed.");
+    format!(
+        "[ADVISORY] Rule '{}' has blocked {} operations this session. {}",
+        rule.name, blocks, advice
+    )
+}
+
+/// Find the first exemption of a rule that applies to the event
+fn find_exemption<'r>(event: &Event, rule: &'r Rule) -> Option<&'r Exemption> {
+    let exemptions = rule.exemptions.as_ref()?;
+    let tool_input = event.tool_input.as_ref();
+
+    let path = tool_input.and_then(target_path_from_input);
+    let command = tool_input
+        .and_then(|ti| ti.get("command"))
+        .and_then(|c| c.as_str());
+    let source = tool_input
+        .filter(|_| event.hook_event_name == EventType::SessionStart)
+        .and_then(|ti| ti.get("source"))
+        .and_then(|s| s.as_str());
+
+    exemptions.iter().find(|exemption| {
+        let path_exempt = path.is_some_and(|p| {
+            exemption
+                .paths
+                .iter()
+                .any(|pattern| path_is_under(pattern, p, event.cwd.as_deref()))
+        });
+        let command_exempt = command.is_some_and(|c| {
+            exemption
+                .commands
+                .iter()
+                .any(|pattern| regex_cache::is_match(pattern, c).unwrap_or(false))
+        });
+        let source_exempt = source.is_some_and(|s| exemption.sources.iter().any(|src| src == s));
+
+        path_exempt || command_exempt || source_exempt
+    })
+}
+
+/// Get the target file path from tool input
+pub(crate) fn file_path_from_input(tool_input: &serde_json::Value) -> Option<&str> {
+    tool_input.get("file_path").and_then(|p| p.as_str())
+}
+
+/// Get the path an event targets: the file for Read/Write/Edit, or the
+/// search root for Glob/Grep
+fn target_path_from_input(tool_input: &serde_json::Value) -> Option<&str> {
+    file_path_from_input(tool_input).or_else(|| tool_input.get("path").and_then(|p| p.as_str()))
+}
+
+/// Check whether the event's target path lies in a zone at least as
+/// sensitive as `required`
+fn matches_zone(event: &Event, required: Sensitivity, config: &Config) -> bool {
+    event
+        .tool_input
+        .as_ref()
+        .and_then(target_path_from_input)
+        .is_some_and(|path| zone_level(&config.zones, path, event.cwd.as_deref()) >= required)
+}
+
+/// Split a multi-edit input (`edits: [...]`) into one Edit-shaped event per
+/// edit, carrying that edit's path and content
+///
+/// Returns `None` for other inputs. An edit without its own `file_path`
+/// targets the operation's file.
+fn edit_events(event: &Event) -> Option<Vec<Event>> {
+    let tool_input = event.tool_input.as_ref()?.as_object()?;
+    let edits = tool_input.get("edits")?.as_array()?;
+    if edits.is_empty() {
+        return None;
+    }
+
+    let mut base = tool_input.clone();
+    base.remove("edits");
+    Some(
+        edits
+            .iter()
+            .filter_map(|edit| edit.as_object())
+            .map(|edit| {
+                let mut input = base.clone();
+                input.extend(edit.clone());
+                Event {
+                    tool_input: Some(serde_json::Value::Object(input)),
+                    ..event.clone()
+                }
+            })
+            .collect(),
+    )
+}
+
+/// The events content checks run against: each edit of a multi-edit, or
+/// the event itself
+fn edit_targets(event: &Event) -> Vec<Event> {
+    edit_events(event).unwrap_or_else(|| vec![event.clone()])
 }
 
 /// Check if a rule matches the given event
-fn matches_rule(event: &Event, rule: &Rule) -> bool {
-    let matchers = &rule.matchers;
+///
+/// A multi-edit matches when any of its edits does.
+pub fn matches_rule(event: &Event, rule: &Rule, config: &Config) -> bool {
+    match edit_events(event) {
+        Some(edits) => edits
+            .iter()
+            .any(|edit| matches_matchers(edit, &rule.matchers, config)),
+        None => matches_matchers(event, &rule.matchers, config),
+    }
+}
 
+/// Check a (possibly nested) matcher block against the event
+fn matches_matchers(event: &Event, matchers: &Matchers, config: &Config) -> bool {
     // Check tool name
     if let Some(ref tools) = matchers.tools {
         if let Some(ref tool_name) = event.tool_name {
@@ -176,10 +1177,8 @@ fn matches_rule(event: &Event, rule: &Rule) -> bool {
     if let Some(ref pattern) = matchers.command_match {
         if let Some(ref tool_input) = event.tool_input {
             if let Some(command) = tool_input.get("command").and_then(|c| c.as_str()) {
-                if let Ok(regex) = Regex::new(pattern) {
-                    if !regex.is_match(command) {
-                        return false;
-                    }
+                if let Ok(false) = regex_cache::is_match(pattern, command) {
+                    return false;
                 }
             }
         }
@@ -188,7 +1187,7 @@ fn matches_rule(event: &Event, rule: &Rule) -> bool {
     // Check file extensions
     if let Some(ref extensions) = matchers.extensions {
         if let Some(ref tool_input) = event.tool_input {
-            if let Some(file_path) = tool_input.get("filePath").and_then(|p| p.as_str()) {
+            if let Some(file_path) = file_path_from_input(tool_input) {
                 let path_ext = Path::new(file_path)
                     .extension()
                     .and_then(|ext| ext.to_str())
@@ -207,7 +1206,7 @@ fn matches_rule(event: &Event, rule: &Rule) -> bool {
     // Check directory patterns
     if let Some(ref directories) = matchers.directories {
         if let Some(ref tool_input) = event.tool_input {
-            if let Some(file_path) = tool_input.get("filePath").and_then(|p| p.as_str()) {
+            if let Some(file_path) = file_path_from_input(tool_input) {
                 let path = Path::new(file_path);
                 let path_str = path.to_string_lossy();
 
@@ -230,12 +1229,216 @@ fn matches_rule(event: &Event, rule: &Rule) -> bool {
         }
     }
 
+    // Check trust zone of the target path
+    if let Some(required) = matchers.zone {
+        if !matches_zone(event, required, config) {
+            return false;
+        }
+    }
+
+    // Check negated matchers
+    if let Some(ref tools) = matchers.exclude_tools {
+        if !passes_exclude_tools(event, tools) {
+            return false;
+        }
+    }
+    if let Some(ref extensions) = matchers.exclude_extensions {
+        if !passes_exclude_extensions(event, extensions) {
+            return false;
+        }
+    }
+    if let Some(ref directories) = matchers.exclude_directories {
+        if !passes_exclude_directories(event, directories) {
+            return false;
+        }
+    }
+    if let Some(ref pattern) = matchers.command_not_match {
+        if !passes_command_not_match(event, pattern) {
+            return false;
+        }
+    }
+
+    // Check what the tool did (PostToolUse results)
+    if let Some(ref pattern) = matchers.tool_output_match {
+        if !matches_tool_output(event, pattern) {
+            return false;
+        }
+    }
+    if let Some(ref exit_code) = matchers.exit_code {
+        if !matches_exit_code(event, exit_code) {
+            return false;
+        }
+    }
+
+    // Check the submitted prompt
+    if let Some(ref pattern) = matchers.prompt_match {
+        if !matches_prompt(event, pattern) {
+            return false;
+        }
+    }
+
+    // Check background shell use
+    if let Some(background) = matchers.background {
+        if event.background_shell() != background {
+            return false;
+        }
+    }
+
+    // Check the canonical target path
+    if let Some(ref protected) = matchers.protected_paths {
+        if !matches_protected_paths(event, protected) {
+            return false;
+        }
+    }
+
+    // Check the expression
+    if let Some(ref source) = matchers.expr {
+        if !expr::matches(event, source) {
+            return false;
+        }
+    }
+
+    // Check nested boolean blocks
+    if let Some(ref any) = matchers.any {
+        if !any.iter().any(|m| matches_matchers(event, m, config)) {
+            return false;
+        }
+    }
+    if let Some(ref all) = matchers.all {
+        if !all.iter().all(|m| matches_matchers(event, m, config)) {
+            return false;
+        }
+    }
+    if let Some(ref not) = matchers.not {
+        if matches_matchers(event, not, config) {
+            return false;
+        }
+    }
+
+    // Check compiled `policies:` condition
+    if let Some(ref policy) = matchers.policy {
+        if !matches_policy(event, policy) {
+            return false;
+        }
+    }
+
     true
 }
 
+/// Negated matchers pass unless the event has the excluded property;
+/// events without a tool, path, or command are never excluded
+fn passes_exclude_tools(event: &Event, tools: &[String]) -> bool {
+    event
+        .tool_name
+        .as_ref()
+        .is_none_or(|tool_name| !tools.contains(tool_name))
+}
+
+fn passes_exclude_extensions(event: &Event, extensions: &[String]) -> bool {
+    let Some(file_path) = event.tool_input.as_ref().and_then(file_path_from_input) else {
+        return true;
+    };
+    let path_ext = Path::new(file_path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("");
+    !extensions
+        .iter()
+        .any(|ext| ext == &format!(".{}", path_ext))
+}
+
+fn passes_exclude_directories(event: &Event, directories: &[String]) -> bool {
+    let Some(file_path) = event.tool_input.as_ref().and_then(file_path_from_input) else {
+        return true;
+    };
+    !directories.iter().any(|dir| {
+        file_path.contains(dir.trim_end_matches("/**"))
+            || file_path.contains(dir.trim_end_matches("/*"))
+    })
+}
+
+fn passes_command_not_match(event: &Event, pattern: &str) -> bool {
+    let command = event
+        .tool_input
+        .as_ref()
+        .and_then(|ti| ti.get("command"))
+        .and_then(|c| c.as_str());
+    command.is_none_or(|command| !regex_cache::is_match(pattern, command).unwrap_or(false))
+}
+
+/// Result matchers only match events carrying a tool result
+fn matches_tool_output(event: &Event, pattern: &str) -> bool {
+    event
+        .tool_output()
+        .into_iter()
+        .chain(event.tool_stderr())
+        .any(|text| regex_cache::is_match(pattern, text).unwrap_or(false))
+}
+
+fn matches_exit_code(event: &Event, expected: &ExitCodeMatch) -> bool {
+    event
+        .tool_exit_code()
+        .is_some_and(|code| expected.matches(code))
+}
+
+fn matches_prompt(event: &Event, pattern: &str) -> bool {
+    event
+        .prompt
+        .as_deref()
+        .is_some_and(|prompt| regex_cache::is_match(pattern, prompt).unwrap_or(false))
+}
+
+/// Check whether the event's target path resolves into a protected path
+///
+/// Relative paths are resolved from the event's `cwd`, else the process's.
+fn matches_protected_paths(event: &Event, protected: &ProtectedPaths) -> bool {
+    let Some(path) = event.tool_input.as_ref().and_then(target_path_from_input) else {
+        return false;
+    };
+    let cwd = event
+        .cwd
+        .as_deref()
+        .map(std::path::PathBuf::from)
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_default();
+    protected.protects(path, &cwd)
+}
+
+/// Check a compiled policy condition against the event's tool input
+fn matches_policy(event: &Event, policy: &PolicyMatcher) -> bool {
+    event
+        .tool_input
+        .as_ref()
+        .is_some_and(|input| policy.matches(input, event.cwd.as_deref()))
+}
+
 /// Check if a rule matches the given event (debug version with matcher results)
-fn matches_rule_with_debug(event: &Event, rule: &Rule) -> (bool, Option<MatcherResults>) {
-    let matchers = &rule.matchers;
+pub fn matches_rule_with_debug(
+    event: &Event,
+    rule: &Rule,
+    config: &Config,
+) -> (bool, Option<MatcherResults>) {
+    let Some(edits) = edit_events(event) else {
+        let (matched, results) = evaluate_matchers(event, &rule.matchers, config);
+        return (matched, Some(results));
+    };
+
+    let (matched, results): (Vec<bool>, Vec<MatcherResults>) = edits
+        .iter()
+        .map(|edit| evaluate_matchers(edit, &rule.matchers, config))
+        .unzip();
+    let shown = matched.iter().position(|m| *m).unwrap_or(0);
+    let mut summary = results[shown].clone();
+    summary.edits = Some(results);
+    (matched.contains(&true), Some(summary))
+}
+
+/// Evaluate a (possibly nested) matcher block, recording each matcher's result
+fn evaluate_matchers(
+    event: &Event,
+    matchers: &Matchers,
+    config: &Config,
+) -> (bool, MatcherResults) {
     let mut matcher_results = MatcherResults::default();
     let mut overall_match = true;
 
@@ -256,11 +1459,7 @@ fn matches_rule_with_debug(event: &Event, rule: &Rule) -> (bool, Option<MatcherR
         matcher_results.command_match_matched =
             Some(if let Some(ref tool_input) = event.tool_input {
                 if let Some(command) = tool_input.get("command").and_then(|c| c.as_str()) {
-                    if let Ok(regex) = Regex::new(pattern) {
-                        regex.is_match(command)
-                    } else {
-                        false
-                    }
+                    regex_cache::is_match(pattern, command).unwrap_or(false)
                 } else {
                     false
                 }
@@ -275,7 +1474,7 @@ fn matches_rule_with_debug(event: &Event, rule: &Rule) -> (bool, Option<MatcherR
     // Check file extensions
     if let Some(ref extensions) = matchers.extensions {
         matcher_results.extensions_matched = Some(if let Some(ref tool_input) = event.tool_input {
-            if let Some(file_path) = tool_input.get("filePath").and_then(|p| p.as_str()) {
+            if let Some(file_path) = file_path_from_input(tool_input) {
                 let path_ext = Path::new(file_path)
                     .extension()
                     .and_then(|ext| ext.to_str())
@@ -299,7 +1498,7 @@ fn matches_rule_with_debug(event: &Event, rule: &Rule) -> (bool, Option<MatcherR
     if let Some(ref directories) = matchers.directories {
         matcher_results.directories_matched =
             Some(if let Some(ref tool_input) = event.tool_input {
-                if let Some(file_path) = tool_input.get("filePath").and_then(|p| p.as_str()) {
+                if let Some(file_path) = file_path_from_input(tool_input) {
                     let path = Path::new(file_path);
                     let path_str = path.to_string_lossy();
 
@@ -330,11 +1529,139 @@ fn matches_rule_with_debug(event: &Event, rule: &Rule) -> (bool, Option<MatcherR
         }
     }
 
-    (overall_match, Some(matcher_results))
+    // Check trust zone of the target path
+    if let Some(required) = matchers.zone {
+        matcher_results.zone_matched = Some(matches_zone(event, required, config));
+        if !matcher_results.zone_matched.unwrap() {
+            overall_match = false;
+        }
+    }
+
+    // Check negated matchers
+    if let Some(ref tools) = matchers.exclude_tools {
+        matcher_results.exclude_tools_matched = Some(passes_exclude_tools(event, tools));
+        if !matcher_results.exclude_tools_matched.unwrap() {
+            overall_match = false;
+        }
+    }
+    if let Some(ref extensions) = matchers.exclude_extensions {
+        matcher_results.exclude_extensions_matched =
+            Some(passes_exclude_extensions(event, extensions));
+        if !matcher_results.exclude_extensions_matched.unwrap() {
+            overall_match = false;
+        }
+    }
+    if let Some(ref directories) = matchers.exclude_directories {
+        matcher_results.exclude_directories_matched =
+            Some(passes_exclude_directories(event, directories));
+        if !matcher_results.exclude_directories_matched.unwrap() {
+            overall_match = false;
+        }
+    }
+    if let Some(ref pattern) = matchers.command_not_match {
+        matcher_results.command_not_match_matched = Some(passes_command_not_match(event, pattern));
+        if !matcher_results.command_not_match_matched.unwrap() {
+            overall_match = false;
+        }
+    }
+
+    // Check what the tool did (PostToolUse results)
+    if let Some(ref pattern) = matchers.tool_output_match {
+        matcher_results.tool_output_match_matched = Some(matches_tool_output(event, pattern));
+        if !matcher_results.tool_output_match_matched.unwrap() {
+            overall_match = false;
+        }
+    }
+    if let Some(ref exit_code) = matchers.exit_code {
+        matcher_results.exit_code_matched = Some(matches_exit_code(event, exit_code));
+        if !matcher_results.exit_code_matched.unwrap() {
+            overall_match = false;
+        }
+    }
+
+    // Check the submitted prompt
+    if let Some(ref pattern) = matchers.prompt_match {
+        matcher_results.prompt_match_matched = Some(matches_prompt(event, pattern));
+        if !matcher_results.prompt_match_matched.unwrap() {
+            overall_match = false;
+        }
+    }
+
+    // Check background shell use
+    if let Some(background) = matchers.background {
+        matcher_results.background_matched = Some(event.background_shell() == background);
+        if !matcher_results.background_matched.unwrap() {
+            overall_match = false;
+        }
+    }
+
+    // Check the canonical target path
+    if let Some(ref protected) = matchers.protected_paths {
+        matcher_results.protected_paths_matched = Some(matches_protected_paths(event, protected));
+        if !matcher_results.protected_paths_matched.unwrap() {
+            overall_match = false;
+        }
+    }
+
+    // Check the expression
+    if let Some(ref source) = matchers.expr {
+        matcher_results.expr_matched = Some(expr::matches(event, source));
+        if !matcher_results.expr_matched.unwrap() {
+            overall_match = false;
+        }
+    }
+
+    // Check nested boolean blocks
+    if let Some(ref any) = matchers.any {
+        let (matched, results): (Vec<bool>, Vec<MatcherResults>) = any
+            .iter()
+            .map(|m| evaluate_matchers(event, m, config))
+            .unzip();
+        matcher_results.any_matched = Some(matched.contains(&true));
+        matcher_results.any = Some(results);
+        if !matcher_results.any_matched.unwrap() {
+            overall_match = false;
+        }
+    }
+    if let Some(ref all) = matchers.all {
+        let (matched, results): (Vec<bool>, Vec<MatcherResults>) = all
+            .iter()
+            .map(|m| evaluate_matchers(event, m, config))
+            .unzip();
+        matcher_results.all_matched = Some(!matched.contains(&false));
+        matcher_results.all = Some(results);
+        if !matcher_results.all_matched.unwrap() {
+            overall_match = false;
+        }
+    }
+    if let Some(ref not) = matchers.not {
+        let (matched, results) = evaluate_matchers(event, not, config);
+        matcher_results.not_matched = Some(!matched);
+        matcher_results.not = Some(Box::new(results));
+        if matched {
+            overall_match = false;
+        }
+    }
+
+    // Check compiled `policies:` condition
+    if let Some(ref policy) = matchers.policy {
+        matcher_results.policy_matched = Some(matches_policy(event, policy));
+        if !matcher_results.policy_matched.unwrap() {
+            overall_match = false;
+        }
+    }
+
+    (overall_match, matcher_results)
 }
 
 /// Execute actions for a matching rule
-async fn execute_rule_actions(event: &Event, rule: &Rule, config: &Config) -> Result<Response> {
+async fn execute_rule_actions(
+    event: &Event,
+    rule: &Rule,
+    config: &Config,
+    validator_runs: &mut Vec<ValidatorRun>,
+    state_store: &mut StateStore,
+) -> Result<Response> {
     let actions = &rule.actions;
 
     // Handle blocking
@@ -348,42 +1675,126 @@ async fn execute_rule_actions(event: &Event, rule: &Rule, config: &Config) -> Re
         }
     }
 
+    // Handle approval prompts
+    if actions.ask == Some(true) {
+        return Ok(ask_response(event, rule));
+    }
+
     // Handle conditional blocking
     if let Some(ref pattern) = actions.block_if_match {
-        if let Some(ref tool_input) = event.tool_input {
-            if let Some(content) = tool_input
-                .get("newString")
-                .or_else(|| tool_input.get("content"))
-                .and_then(|c| c.as_str())
-            {
-                if let Ok(regex) = Regex::new(pattern) {
-                    if regex.is_match(content) {
-                        return Ok(Response::block(format!(
-                            "Content blocked by rule '{}': matches pattern '{}'",
-                            rule.name, pattern
-                        )));
-                    }
+        if content_matches(event, pattern) {
+            return Ok(Response::block(format!(
+                "Content blocked by rule '{}': matches pattern '{}'",
+                rule.name, pattern
+            )));
+        }
+    }
+
+    // Handle mandated project files
+    if let Some(ref files) = actions.require_files {
+        let missing = missing_files(event, files);
+        if !missing.is_empty() {
+            return Ok(missing_files_response(event, rule, &missing));
+        }
+    }
+
+    // Handle built-in structural checks
+    if let Some(ref checks) = actions.structural_checks {
+        match structural_violations(event, checks) {
+            Ok(Some(summary)) => {
+                return Ok(Response::block(format!(
+                    "Content blocked by rule '{}': {}",
+                    rule.name, summary
+                )));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tracing::warn!("Structural checks failed for rule '{}': {}", rule.name, e);
+                if !config.settings.fail_open {
+                    return Err(e);
                 }
             }
         }
     }
 
-    // Handle context injection
-    if let Some(ref inject_path) = actions.inject {
-        match read_context_file(inject_path).await {
-            Ok(context) => {
-                return Ok(Response::inject(context));
+    // Handle required file headers
+    if let Some(ref header) = actions.ensure_header {
+        match missing_header(event, header).await {
+            Ok(Some(path)) => {
+                return Ok(Response::block(format!(
+                    "Blocked by rule '{}': {} is missing the required header from {}",
+                    rule.name, path, header.template
+                )));
             }
+            Ok(None) => {}
             Err(e) => {
-                tracing::warn!("Failed to read context file '{}': {}", inject_path, e);
-                // Continue without injection rather than failing
+                tracing::warn!("Header check failed for rule '{}': {}", rule.name, e);
+                if !config.settings.fail_open {
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    // Handle PostToolUse Glob/Grep result filtering (then truncation).
+    // Claude Code can't replace a built-in tool's output, so the filtered
+    // results are added as context telling the model to use them instead
+    if let Some(ref filter) = actions.filter_results {
+        if let Some(output) = filtered_results(event, filter) {
+            let output = actions
+                .truncate_output
+                .as_ref()
+                .and_then(|truncate| truncate.apply(&output))
+                .unwrap_or(output);
+            return Ok(Response::tool_result_context(format!(
+                "Rule '{}' excludes results under {} from this {} search; disregard them and use only these results:\n{}",
+                rule.name,
+                filter.exclude.join(", "),
+                event.tool_name.as_deref().unwrap_or("tool"),
+                output
+            )));
+        }
+    }
+
+    // Handle PostToolUse output truncation. MCP results are replaced;
+    // Claude Code keeps a built-in tool's output, so the shortened output
+    // is added as context telling the model to work from it
+    if let Some(ref truncate) = actions.truncate_output {
+        if let Some(output) = truncated_output(event, truncate) {
+            let tool = event.tool_name.as_deref().unwrap_or("tool");
+            if tool.starts_with("mcp__") {
+                return Ok(Response::update_mcp_output(output));
             }
+            return Ok(Response::tool_result_context(format!(
+                "Rule '{}' shortens {} output; work from this version rather than the full output:\n{}",
+                rule.name, tool, output
+            )));
+        }
+    }
+
+    // Handle PreToolUse input rewriting
+    if let Some(ref rewrite) = actions.rewrite {
+        if let Some(rewritten) = rewritten_input(event, rewrite) {
+            let mut response = Response::update_input(rewritten.input, event);
+            response.system_message = Some(format!(
+                "Rule '{}' rewrote {}: {} -> {}",
+                rule.name, rewrite.field, rewritten.before, rewritten.after
+            ));
+            return Ok(response);
         }
     }
 
+    // Handle context injection; unreadable sources are skipped rather than failing
+    if let Some(context) = injected_context(event, rule, config, state_store).await {
+        return Ok(Response::inject(context));
+    }
+
     // Handle script execution
-    if let Some(script_path) = actions.script_path() {
-        match execute_validator_script(event, script_path, rule, config).await {
+    if let Some(script_path) = actions
+        .script_path()
+        .filter(|_| !on_fast_path(event, config))
+    {
+        match execute_validator_script(event, script_path, rule, config, validator_runs).await {
             Ok(script_response) => {
                 return Ok(script_response);
             }
@@ -400,63 +1811,583 @@ async fn execute_rule_actions(event: &Event, rule: &Rule, config: &Config) -> Re
     Ok(Response::allow())
 }
 
-/// Read context file for injection
-async fn read_context_file(path: &str) -> Result<String> {
-    let content = tokio::fs::read_to_string(path).await?;
-    Ok(content)
+/// Get written content from tool input (`content` for Write, `new_string` for Edit)
+pub(crate) fn content_from_input(tool_input: &serde_json::Value) -> Option<&str> {
+    tool_input
+        .get("new_string")
+        .or_else(|| tool_input.get("content"))
+        .and_then(|c| c.as_str())
 }
 
-/// Execute a validator script
-async fn execute_validator_script(
-    event: &Event,
-    script_path: &str,
-    rule: &Rule,
-    config: &Config,
-) -> Result<Response> {
-    let timeout_duration = rule
-        .metadata
-        .as_ref()
-        .map(|m| m.timeout)
-        .unwrap_or(config.settings.script_timeout);
-
-    let mut command = Command::new(script_path);
-    command.stdin(std::process::Stdio::piped());
-    command.stdout(std::process::Stdio::piped());
-    command.stderr(std::process::Stdio::piped());
+/// Check whether any written content matches `pattern`; every edit of a
+/// multi-edit is checked
+fn content_matches(event: &Event, pattern: &str) -> bool {
+    edit_targets(event)
+        .iter()
+        .filter_map(|edit| edit.tool_input.as_ref().and_then(content_from_input))
+        .any(|content| regex_cache::is_match(pattern, content).unwrap_or(false))
+}
 
-    let child_result = command.spawn();
+/// Marker of an inline suppression comment
+const SUPPRESSION_MARKER: &str = "cch:allow";
 
-    let mut child = match child_result {
-        Ok(c) => c,
-        Err(e) => {
-            tracing::warn!("Failed to spawn validator script '{}': {}", script_path, e);
-            if config.settings.fail_open {
-                return Ok(Response::allow());
+/// With `settings.inline_suppressions`, the justifications of the
+/// `cch:allow <rule>` comments covering every `block_if_match` hit in the
+/// written content; `None` unless there are hits and all are covered
+fn inline_suppression(event: &Event, rule: &Rule, config: &Config) -> Option<String> {
+    if !config.settings.inline_suppressions {
+        return None;
+    }
+    let regex = regex_cache::compile(rule.actions.block_if_match.as_deref()?).ok()?;
+    let mut justifications: Vec<String> = Vec::new();
+    for edit in edit_targets(event) {
+        let Some(content) = edit.tool_input.as_ref().and_then(content_from_input) else {
+            continue;
+        };
+        for hit in regex.find_iter(content) {
+            let justification = suppression_near(content, hit.start(), &rule.name)?;
+            if !justifications.contains(&justification) {
+                justifications.push(justification);
             }
-            return Err(e.into());
         }
+    }
+    (!justifications.is_empty())
+        .then(|| format!("{}: {}", SUPPRESSION_MARKER, justifications.join("; ")))
+}
+
+/// Justification of a `cch:allow <rule>` comment on the line holding byte
+/// `offset` of `content` or on the line above it
+fn suppression_near(content: &str, offset: usize, rule_name: &str) -> Option<String> {
+    let line = content[..offset].matches('\n').count();
+    let first = line.saturating_sub(1);
+    content
+        .lines()
+        .skip(first)
+        .take(line - first + 1)
+        .find_map(|text| suppression_comment(text, rule_name))
+}
+
+/// The justification of a `cch:allow <rule> <justification>` comment for
+/// `rule_name` in `line`; comments without a justification don't count
+fn suppression_comment(line: &str, rule_name: &str) -> Option<String> {
+    let (_, rest) = line.split_once(SUPPRESSION_MARKER)?;
+    let rest = rest.trim_start();
+    let justification = rest.strip_prefix(rule_name)?;
+    if !justification.is_empty() && !justification.starts_with([' ', '\t', ':']) {
+        return None; // a longer rule name
+    }
+    let justification = justification
+        .trim()
+        .trim_end_matches("*/")
+        .trim_end_matches("-->")
+        .trim()
+        .trim_start_matches([':', '-'])
+        .trim();
+    (!justification.is_empty()).then(|| justification.to_string())
+}
+
+/// Run structural checks on the event's written content, edit by edit
+///
+/// Returns a summary of violations, or `None` when the content passes.
+fn structural_violations(event: &Event, checks: &[StructuralCheck]) -> Result<Option<String>> {
+    let mut summaries = Vec::new();
+    for edit in edit_targets(event) {
+        let Some(tool_input) = edit.tool_input.as_ref() else {
+            continue;
+        };
+        let (Some(path), Some(content)) = (
+            file_path_from_input(tool_input),
+            content_from_input(tool_input),
+        ) else {
+            continue;
+        };
+
+        let violations = structural::run_checks(checks, path, content)?;
+        if !violations.is_empty() {
+            let details: Vec<String> = violations.iter().map(ToString::to_string).collect();
+            summaries.push(format!("{} in {}", details.join("; "), path));
+        }
+    }
+
+    if summaries.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(summaries.join("; ")))
+    }
+}
+
+/// Check a Write event's content for the required header
+///
+/// Returns the file path when the header is missing. Only Write creates
+/// whole files, so other tools are never checked.
+async fn missing_header(event: &Event, header: &EnsureHeader) -> Result<Option<String>> {
+    if event.tool_name.as_deref() != Some("Write") {
+        return Ok(None);
+    }
+    let Some(tool_input) = event.tool_input.as_ref() else {
+        return Ok(None);
+    };
+    let (Some(path), Some(content)) = (
+        file_path_from_input(tool_input),
+        content_from_input(tool_input),
+    ) else {
+        return Ok(None);
     };
 
-    // Send event as JSON to script stdin
-    if let Some(stdin) = child.stdin.as_mut() {
-        let event_json = serde_json::to_string(event)?;
-        tokio::io::AsyncWriteExt::write_all(stdin, event_json.as_bytes()).await?;
+    let template = tokio::fs::read_to_string(&header.template)
+        .await
+        .map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to read header template '{}': {}",
+                header.template,
+                e
+            )
+        })?;
+    if EnsureHeader::is_satisfied_by(&template, content) {
+        return Ok(None);
     }
+    Ok(Some(path.to_string()))
+}
 
-    // Close stdin to signal end of input
-    drop(child.stdin.take());
+/// Truncate a PostToolUse event's output, if it exceeds the configured limits
+fn truncated_output(event: &Event, truncate: &TruncateOutput) -> Option<String> {
+    if event.hook_event_name != EventType::PostToolUse {
+        return None;
+    }
+    event
+        .tool_output()
+        .and_then(|output| truncate.apply(output))
+}
 
-    // Wait for script completion with timeout
-    let output_result = timeout(
-        Duration::from_secs(timeout_duration as u64),
-        child.wait_with_output(),
-    )
-    .await;
+/// A PreToolUse tool input changed by `actions.rewrite`
+struct Rewritten {
+    before: String,
+    after: String,
+    /// The whole new tool input
+    input: serde_json::Value,
+}
 
-    let output = match output_result {
-        Ok(Ok(o)) => o,
-        Ok(Err(e)) => {
-            tracing::warn!("Validator script '{}' failed: {}", script_path, e);
+/// Rewrite a PreToolUse event's tool input, if the rewrite changes it
+fn rewritten_input(event: &Event, rewrite: &RewriteInput) -> Option<Rewritten> {
+    if event.hook_event_name != EventType::PreToolUse {
+        return None;
+    }
+    let mut input = event.tool_input.clone()?;
+    let field = input.get_mut(&rewrite.field)?;
+    let before = field.as_str()?.to_string();
+    let after = rewrite.apply(&before)?;
+    *field = serde_json::Value::String(after.clone());
+    Some(Rewritten {
+        before,
+        after,
+        input,
+    })
+}
+
+/// Filter a PostToolUse Glob/Grep event's results, if any are excluded
+fn filtered_results(event: &Event, filter: &FilterResults) -> Option<String> {
+    if event.hook_event_name != EventType::PostToolUse
+        || !matches!(event.tool_name.as_deref(), Some("Glob" | "Grep"))
+    {
+        return None;
+    }
+    filter.apply(event.search_results()?, event.cwd.as_deref())
+}
+
+/// Read context file for injection
+async fn read_context_file(path: &str) -> Result<String> {
+    let content = tokio::fs::read_to_string(path).await?;
+    Ok(content)
+}
+
+/// Ask the user to approve the operation
+///
+/// Only PreToolUse can return a permission decision. A PermissionRequest
+/// already shows the prompt, so it is left alone; any other event can't be
+/// put to the user and is blocked instead.
+fn ask_response(event: &Event, rule: &Rule) -> Response {
+    let reason = format!(
+        "Rule '{}' requires approval: {}",
+        rule.name,
+        rule.description.as_deref().unwrap_or("No description")
+    );
+    match event.hook_event_name {
+        EventType::PreToolUse => Response::ask(reason),
+        EventType::PermissionRequest => Response::allow(),
+        _ => Response::block(reason),
+    }
+}
+
+/// Files of a `require_files` list missing from the event's project
+fn missing_files<'f>(event: &Event, files: &'f [String]) -> Vec<&'f str> {
+    let root = event
+        .cwd
+        .as_deref()
+        .map(Path::new)
+        .filter(|cwd| cwd.is_dir())
+        .unwrap_or(Path::new("."));
+    files
+        .iter()
+        .filter(|file| !root.join(file).exists())
+        .map(String::as_str)
+        .collect()
+}
+
+/// Respond to missing mandated files: tool calls are blocked, and every
+/// other event (e.g. SessionStart) gets a reminder
+fn missing_files_response(event: &Event, rule: &Rule, missing: &[&str]) -> Response {
+    let missing = missing.join(", ");
+    match event.hook_event_name {
+        EventType::PreToolUse | EventType::PermissionRequest => Response::block(format!(
+            "Blocked by rule '{}': required project files are missing: {}",
+            rule.name, missing
+        )),
+        _ => Response::inject(format!(
+            "[REMINDER] Rule '{}': required project files are missing: {}. {}",
+            rule.name,
+            missing,
+            rule.description
+                .as_deref()
+                .unwrap_or("Add them before continuing.")
+        )),
+    }
+}
+
+/// Whether the event's tool is in `settings.fast_path_tools`, whose events
+/// skip everything that spawns a process: validator scripts,
+/// `inject_command`, the git lookup behind `bypass_users` and the
+/// post-processor
+fn on_fast_path(event: &Event, config: &Config) -> bool {
+    event
+        .tool_name
+        .as_ref()
+        .is_some_and(|tool_name| config.settings.fast_path_tools.contains(tool_name))
+}
+
+/// Context from a rule's `inject` files followed by its `inject_command` output
+async fn injected_context(
+    event: &Event,
+    rule: &Rule,
+    config: &Config,
+    state_store: &mut StateStore,
+) -> Option<String> {
+    let files = match rule.actions.inject {
+        Some(ref inject) => read_injected_context(inject, event, state_store).await,
+        None => None,
+    };
+    let command = match rule.actions.inject_command {
+        Some(ref command) if !on_fast_path(event, config) => {
+            run_inject_command(event, command, rule, config).await
+        }
+        _ => None,
+    };
+    let enrichment = rule
+        .actions
+        .enrich_prompt
+        .as_ref()
+        .and_then(|enrichment| prompt_enrichment(event, enrichment));
+    [files, command, enrichment]
+        .into_iter()
+        .flatten()
+        .reduce(|context, part| format!("{}\n\n{}", context.trim_end(), part))
+}
+
+/// The `enrich_prompt` context for a UserPromptSubmit prompt
+fn prompt_enrichment(event: &Event, enrichment: &PromptEnrichment) -> Option<String> {
+    let prompt = event
+        .prompt
+        .as_deref()
+        .filter(|_| event.hook_event_name == EventType::UserPromptSubmit)?;
+    enrichment::render(&enrichment::entries(enrichment, prompt))
+}
+
+/// Run an `inject_command` and return its stdout, capped at
+/// `settings.max_context_size`
+///
+/// Failures, non-zero exits and timeouts are logged and inject nothing.
+async fn run_inject_command(
+    event: &Event,
+    command_line: &str,
+    rule: &Rule,
+    config: &Config,
+) -> Option<String> {
+    let timeout_duration = rule
+        .metadata
+        .as_ref()
+        .map(|m| m.timeout)
+        .unwrap_or(config.settings.script_timeout);
+
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(command_line);
+    command.stdin(std::process::Stdio::null());
+    command.stdout(std::process::Stdio::piped());
+    command.stderr(std::process::Stdio::null());
+    command.kill_on_drop(true);
+    if let Some(cwd) = event.cwd.as_deref().filter(|cwd| Path::new(cwd).is_dir()) {
+        command.current_dir(cwd);
+    }
+
+    let output = match timeout(
+        Duration::from_secs(timeout_duration as u64),
+        command.output(),
+    )
+    .await
+    {
+        Ok(Ok(output)) if output.status.success() => output,
+        Ok(Ok(output)) => {
+            tracing::warn!(
+                "inject_command '{}' exited with {:?}",
+                command_line,
+                output.status.code()
+            );
+            return None;
+        }
+        Ok(Err(e)) => {
+            tracing::warn!("Failed to run inject_command '{}': {}", command_line, e);
+            return None;
+        }
+        Err(_) => {
+            tracing::warn!(
+                "inject_command '{}' timed out after {}s",
+                command_line,
+                timeout_duration
+            );
+            return None;
+        }
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stdout = stdout.trim_end();
+    if stdout.is_empty() {
+        return None;
+    }
+    Some(cap_context(stdout, config.settings.max_context_size))
+}
+
+/// Truncate context to `max_bytes` on a character boundary, noting the cut
+fn cap_context(context: &str, max_bytes: usize) -> String {
+    if context.len() <= max_bytes {
+        return context.to_string();
+    }
+    let mut end = max_bytes;
+    while !context.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!(
+        "{}\n[output truncated by CCH at {} bytes]",
+        &context[..end],
+        max_bytes
+    )
+}
+
+/// Read an inject action's files and concatenate them in order
+///
+/// A single file is injected verbatim, minus its front matter. Files whose
+/// front matter rules them out for this event are left out, and the rest
+/// are ordered by front-matter `priority` (see [`front_matter`]).
+/// Unreadable files are skipped with a warning; returns `None` when nothing
+/// could be read.
+async fn read_injected_context(
+    inject: &InjectAction,
+    event: &Event,
+    state_store: &mut StateStore,
+) -> Option<String> {
+    let sources = inject.sources();
+    let mut documents = Vec::new();
+    for source in sources {
+        match read_context_file(source.path()).await {
+            Ok(content) => {
+                let (front_matter, body) = front_matter::parse(&content);
+                if front_matter.allows_tool(event.tool_name.as_deref()) {
+                    documents.push((source, front_matter, body.to_string()));
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Failed to read context file '{}': {}", source.path(), e);
+            }
+        }
+    }
+    documents.sort_by_key(|(_, front_matter, _)| std::cmp::Reverse(front_matter.priority));
+    limit_injections(event, &mut documents, state_store);
+
+    let sections: Vec<String> = documents
+        .into_iter()
+        .map(|(source, _, content)| match source.header() {
+            Some(header) => format!("## {}\n\n{}", header, content.trim_end()),
+            None if sources.len() == 1 => content,
+            None => content.trim_end().to_string(),
+        })
+        .collect();
+    (!sections.is_empty()).then(|| sections.join("\n\n"))
+}
+
+/// Drop files already injected `max_injections` times this session and
+/// count the rest; without readable session state nothing is dropped
+fn limit_injections(
+    event: &Event,
+    documents: &mut Vec<(&InjectSource, FrontMatter, String)>,
+    state_store: &mut StateStore,
+) {
+    if documents
+        .iter()
+        .all(|(_, fm, _)| fm.max_injections.is_none())
+    {
+        return;
+    }
+    let Some(state) = state_store.session(event) else {
+        return;
+    };
+    documents.retain(
+        |(source, front_matter, _)| match front_matter.max_injections {
+            Some(max) if state.injections.get(source.path()).copied().unwrap_or(0) >= max => false,
+            Some(_) => {
+                state.record_injection(source.path());
+                true
+            }
+            None => true,
+        },
+    );
+}
+
+/// Environment for a validator: CCH's variables describing the rule and
+/// event, then the rule's own `run.env` (which may override them)
+fn validator_env(event: &Event, rule: &Rule) -> Vec<(String, String)> {
+    let project_root = std::env::var("CLAUDE_PROJECT_DIR")
+        .ok()
+        .or_else(|| event.cwd.clone())
+        .or_else(|| {
+            std::env::current_dir()
+                .ok()
+                .map(|dir| dir.to_string_lossy().into_owned())
+        })
+        .unwrap_or_default();
+    let mut env = vec![
+        ("CCH_RULE_NAME".to_string(), rule.name.clone()),
+        (
+            "CCH_EVENT_TYPE".to_string(),
+            event.hook_event_name.to_string(),
+        ),
+        ("CCH_SESSION_ID".to_string(), event.session_id.clone()),
+        ("CCH_PROJECT_ROOT".to_string(), project_root),
+    ];
+    if let Some(ref run) = rule.actions.run {
+        env.extend(run.env().map(|(k, v)| (k.clone(), v.clone())));
+    }
+    env
+}
+
+/// Execute a validator script, or run a WebAssembly validator in-process
+///
+/// Every run that gets as far as spawning the script (or starting the
+/// module) is recorded in `validator_runs` for the audit log.
+async fn execute_validator_script(
+    event: &Event,
+    script_path: &str,
+    rule: &Rule,
+    config: &Config,
+    validator_runs: &mut Vec<ValidatorRun>,
+) -> Result<Response> {
+    let timeout_duration = rule
+        .metadata
+        .as_ref()
+        .map(|m| m.timeout)
+        .unwrap_or(config.settings.script_timeout);
+
+    let cache_key = rule
+        .actions
+        .run
+        .as_ref()
+        .filter(|run| run.is_cacheable())
+        .zip(event.tool_input.as_ref().and_then(content_from_input))
+        .and_then(|(run, content)| validator_cache::key(script_path, run.env(), content));
+    let cache_dir = validator_cache::validator_cache_dir().ok();
+    if let (Some(key), Some(dir)) = (&cache_key, &cache_dir) {
+        if let Some(response) = validator_cache::load(dir, key) {
+            tracing::debug!("Using cached result of validator '{}'", script_path);
+            return Ok(response);
+        }
+    }
+
+    if rule.actions.run.as_ref().is_some_and(RunAction::is_wasm) {
+        let verdict =
+            match run_wasm_validator(event, script_path, rule, timeout_duration, validator_runs)
+                .await
+            {
+                Ok(verdict) => verdict,
+                Err(e) => {
+                    tracing::warn!("{:#}", e);
+                    if config.settings.fail_open {
+                        return Ok(Response::allow());
+                    }
+                    return Err(e);
+                }
+            };
+        let response = verdict_response(verdict, rule, script_path);
+        cache_validator_response(
+            cache_key.as_ref(),
+            cache_dir.as_deref(),
+            script_path,
+            &response,
+        );
+        return Ok(response);
+    }
+
+    let mut command = Command::new(script_path);
+    command.envs(validator_env(event, rule));
+    command.stdin(std::process::Stdio::piped());
+    command.stdout(std::process::Stdio::piped());
+    command.stderr(std::process::Stdio::piped());
+
+    let child_result = if chaos::injected(Fault::SpawnFail) {
+        Err(std::io::Error::other("spawn failure injected by CCH_CHAOS"))
+    } else {
+        command.spawn()
+    };
+
+    let mut child = match child_result {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::warn!("Failed to spawn validator script '{}': {}", script_path, e);
+            if config.settings.fail_open {
+                return Ok(Response::allow());
+            }
+            return Err(e.into());
+        }
+    };
+
+    // Send event as JSON to script stdin
+    if let Some(stdin) = child.stdin.as_mut() {
+        let event_json = serde_json::to_string(&event.script_payload())?;
+        tokio::io::AsyncWriteExt::write_all(stdin, event_json.as_bytes()).await?;
+    }
+
+    // Close stdin to signal end of input
+    drop(child.stdin.take());
+
+    // Wait for script completion with timeout
+    let started = std::time::Instant::now();
+    let output_result = timeout(Duration::from_secs(timeout_duration as u64), async {
+        if chaos::injected(Fault::ValidatorTimeout) {
+            std::future::pending::<()>().await;
+        }
+        child.wait_with_output().await
+    })
+    .await;
+    validator_runs.push(ValidatorRun {
+        rule: rule.name.clone(),
+        script: script_path.to_string(),
+        duration_ms: started.elapsed().as_millis() as u64,
+        timed_out: output_result.is_err(),
+        exit_code: match output_result {
+            Ok(Ok(ref o)) => o.status.code(),
+            _ => None,
+        },
+    });
+
+    let output = match output_result {
+        Ok(Ok(o)) => o,
+        Ok(Err(e)) => {
+            tracing::warn!("Validator script '{}' failed: {}", script_path, e);
             if config.settings.fail_open {
                 return Ok(Response::allow());
             }
@@ -475,15 +2406,229 @@ async fn execute_validator_script(
         }
     };
 
+    let response = script_response(&output, rule, script_path);
+    cache_validator_response(
+        cache_key.as_ref(),
+        cache_dir.as_deref(),
+        script_path,
+        &response,
+    );
+    Ok(response)
+}
+
+fn cache_validator_response(
+    key: Option<&String>,
+    dir: Option<&Path>,
+    script_path: &str,
+    response: &Response,
+) {
+    if let (Some(key), Some(dir)) = (key, dir) {
+        if let Err(e) = validator_cache::store(dir, key, response) {
+            tracing::warn!(
+                "Failed to cache result of validator '{}': {}",
+                script_path,
+                e
+            );
+        }
+    }
+}
+
+/// Run the WebAssembly validator at `module_path` on a blocking thread,
+/// bounded by the script timeout on top of the module's own fuel budget
+async fn run_wasm_validator(
+    event: &Event,
+    module_path: &str,
+    rule: &Rule,
+    timeout_secs: u32,
+    validator_runs: &mut Vec<ValidatorRun>,
+) -> Result<ValidatorVerdict> {
+    let input = serde_json::to_string(&event.script_payload())?;
+    let path = module_path.to_string();
+    let started = std::time::Instant::now();
+    let result = timeout(
+        Duration::from_secs(u64::from(timeout_secs)),
+        tokio::task::spawn_blocking(move || wasm_validator::run(&path, &input)),
+    )
+    .await;
+    validator_runs.push(ValidatorRun {
+        rule: rule.name.clone(),
+        script: module_path.to_string(),
+        duration_ms: started.elapsed().as_millis() as u64,
+        timed_out: result.is_err(),
+        exit_code: None,
+    });
+
+    let output = match result {
+        Ok(joined) => joined??,
+        Err(_) => anyhow::bail!(
+            "WASM validator '{}' timed out after {}s",
+            module_path,
+            timeout_secs
+        ),
+    };
+    parse_verdict(&output).ok_or_else(|| {
+        anyhow::anyhow!(
+            "WASM validator '{}' did not return a verdict: {}",
+            module_path,
+            output.trim()
+        )
+    })
+}
+
+/// What `settings.post_processor` reads on stdin
+#[derive(Serialize)]
+struct PostProcessorInput<'a> {
+    event: EventSummary<'a>,
+    matched_rules: Vec<&'a str>,
+    response: &'a Response,
+}
+
+#[derive(Serialize)]
+struct EventSummary<'a> {
+    hook_event_name: &'a EventType,
+    session_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_name: Option<&'a str>,
+    details: EventDetails,
+}
+
+/// Run the configured post-processor, unless the event is on the fast
+/// path, which spawns no processes
+async fn post_process(
+    event: &Event,
+    matched_rules: &[&Rule],
+    response: &mut Response,
+    config: &Config,
+) -> Result<Option<PostProcessorRun>> {
+    match config.settings.post_processor.as_deref() {
+        Some(script) if !on_fast_path(event, config) => Ok(Some(
+            run_post_processor(script, event, matched_rules, response, config).await?,
+        )),
+        _ => Ok(None),
+    }
+}
+
+/// Run `settings.post_processor` over the final response
+///
+/// The script answers like a validator (a JSON verdict, else its exit
+/// code) but can only tighten the decision: a block or warning applies to
+/// an operation the rules allowed, and nothing it says allows an operation
+/// they blocked.
+async fn run_post_processor(
+    script_path: &str,
+    event: &Event,
+    matched_rules: &[&Rule],
+    response: &mut Response,
+    config: &Config,
+) -> Result<PostProcessorRun> {
+    let input = serde_json::to_string(&PostProcessorInput {
+        event: EventSummary {
+            hook_event_name: &event.hook_event_name,
+            session_id: &event.session_id,
+            tool_name: event.tool_name.as_deref(),
+            details: EventDetails::extract(event),
+        },
+        matched_rules: matched_rules.iter().map(|r| r.name.as_str()).collect(),
+        response,
+    })?;
+    let mut run = PostProcessorRun {
+        script: script_path.to_string(),
+        duration_ms: 0,
+        timed_out: false,
+        exit_code: None,
+        vetoed: false,
+        reason: None,
+    };
+
+    // `None` when the script runs out of time
+    let timeout_secs = u64::from(config.settings.script_timeout);
+    let started = std::time::Instant::now();
+    let output = async {
+        let mut child = Command::new(script_path)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            tokio::io::AsyncWriteExt::write_all(&mut stdin, input.as_bytes()).await?;
+        }
+        timeout(Duration::from_secs(timeout_secs), child.wait_with_output())
+            .await
+            .ok()
+            .transpose()
+    }
+    .await;
+    run.duration_ms = started.elapsed().as_millis() as u64;
+
+    let output = match output {
+        Ok(Some(output)) => output,
+        failed => {
+            run.timed_out = matches!(failed, Ok(None));
+            let error = match failed {
+                Err(e) => anyhow::Error::from(e),
+                _ => anyhow::anyhow!("Post-processor timed out after {}s", timeout_secs),
+            };
+            tracing::warn!("Post-processor '{}' failed: {}", script_path, error);
+            if config.settings.fail_open {
+                return Ok(run);
+            }
+            return Err(error);
+        }
+    };
+    run.exit_code = output.status.code();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (decision, reason) = match parse_verdict(&stdout) {
+        Some(verdict) => (verdict.decision, verdict.reason),
+        None if output.status.success() => (VerdictDecision::Allow, None),
+        None => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let reason = Some(stderr.trim().to_string()).filter(|r| !r.is_empty());
+            (VerdictDecision::Block, reason)
+        }
+    };
+    run.reason.clone_from(&reason);
+    if !response.continue_ {
+        return Ok(run);
+    }
+    match decision {
+        VerdictDecision::Block => {
+            let reason =
+                reason.unwrap_or_else(|| format!("Blocked by post-processor '{}'", script_path));
+            tracing::info!("Post-processor '{}' vetoed: {}", script_path, reason);
+            *response = Response::block(reason);
+            run.vetoed = true;
+        }
+        VerdictDecision::Warn => {
+            let warning = format!(
+                "[WARNING] Post-processor: {}",
+                reason.as_deref().unwrap_or("flagged this operation")
+            );
+            response.context = Some(match response.context.take() {
+                Some(context) => format!("{}\n\n{}", warning, context),
+                None => warning,
+            });
+        }
+        VerdictDecision::Allow => {}
+    }
+    Ok(run)
+}
+
+/// Response for a finished validator: its JSON verdict, else its exit code
+fn script_response(output: &std::process::Output, rule: &Rule, script_path: &str) -> Response {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if let Some(verdict) = parse_verdict(&stdout) {
+        return verdict_response(verdict, rule, script_path);
+    }
+
     let exit_code = output.status.code().unwrap_or(-1);
 
     if exit_code == 0 {
         // Script allowed the operation - check if stdout has context to inject
-        let stdout = String::from_utf8_lossy(&output.stdout);
         if stdout.trim().is_empty() {
-            Ok(Response::allow())
+            Response::allow()
         } else {
-            Ok(Response::inject(stdout.trim().to_string()))
+            Response::inject(stdout.trim().to_string())
         }
     } else {
         // Script blocked the operation
@@ -493,19 +2638,132 @@ async fn execute_validator_script(
         } else {
             format!("Blocked by validator script: {}", stderr.trim())
         };
-        Ok(Response::block(reason))
+        Response::block(reason)
+    }
+}
+
+/// Structured verdict a validator may print as its whole stdout
+///
+/// ```json
+/// { "decision": "warn", "reason": "File exceeds 400 lines", "context": "..." }
+/// ```
+///
+/// A verdict takes precedence over the script's exit code; any other
+/// output falls back to the exit-code protocol.
+#[derive(Debug, Deserialize)]
+struct ValidatorVerdict {
+    decision: VerdictDecision,
+
+    #[serde(default)]
+    reason: Option<String>,
+
+    #[serde(default)]
+    context: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum VerdictDecision {
+    Allow,
+    Block,
+    Warn,
+}
+
+fn parse_verdict(stdout: &str) -> Option<ValidatorVerdict> {
+    let stdout = stdout.trim();
+    if !stdout.starts_with('{') {
+        return None;
+    }
+    serde_json::from_str(stdout).ok()
+}
+
+/// Response for a validator's verdict; a warning proceeds with the
+/// reason injected ahead of any context
+fn verdict_response(verdict: ValidatorVerdict, rule: &Rule, script_path: &str) -> Response {
+    let context = verdict.context.filter(|c| !c.trim().is_empty());
+    match verdict.decision {
+        VerdictDecision::Block => Response::block(
+            verdict
+                .reason
+                .unwrap_or_else(|| format!("Blocked by validator script '{}'", script_path)),
+        ),
+        VerdictDecision::Warn => {
+            let reason = verdict
+                .reason
+                .unwrap_or_else(|| "flagged by its validator script".to_string());
+            let reason = match context {
+                Some(context) => format!("{}\n\n{}", reason, context.trim()),
+                None => reason,
+            };
+            Response::inject(render_warning(&rule.name, &reason))
+        }
+        VerdictDecision::Allow => match context {
+            Some(context) => Response::inject(context.trim().to_string()),
+            None => Response::allow(),
+        },
+    }
+}
+
+/// Limit on the total context injected for one event
+///
+/// Shared by every merge of an event's responses; records whether any
+/// context had to be cut.
+struct ContextCap {
+    max_bytes: usize,
+    truncated: bool,
+}
+
+impl ContextCap {
+    fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            truncated: false,
+        }
+    }
+
+    /// Fit `context` into what is left after `used` bytes and a separator
+    ///
+    /// Rules merge in priority order, so the lower-priority context is the
+    /// one cut short, or dropped when there is no room for any of it.
+    fn fit(&mut self, used: usize, context: String) -> Option<String> {
+        let separator = if used == 0 { 0 } else { 2 };
+        let remaining = self.max_bytes.saturating_sub(used + separator);
+        if context.len() <= remaining {
+            return Some(context);
+        }
+
+        self.truncated = true;
+        let note = format!("\n[context truncated by CCH at {} bytes]", self.max_bytes);
+        let mut end = remaining.checked_sub(note.len()).filter(|&end| end > 0)?;
+        while !context.is_char_boundary(end) {
+            end -= 1;
+        }
+        Some(format!("{}{}", &context[..end], note))
     }
 }
 
-/// Merge two responses (block takes precedence, inject accumulates)
-fn merge_responses(mut existing: Response, new: Response) -> Response {
+/// Merge two responses (block takes precedence, inject accumulates up to
+/// the context cap)
+fn merge_responses(mut existing: Response, new: Response, cap: &mut ContextCap) -> Response {
     // Block takes precedence
     if !new.continue_ {
         return new;
     }
 
-    // Accumulate context
-    if let Some(new_context) = new.context {
+    // The first permission prompt and input rewrite stand, the latest
+    // output replacement wins
+    existing.hook_specific_output = match (existing.hook_specific_output, new.hook_specific_output)
+    {
+        (Some(kept), Some(new)) => Some(kept.merge(new)),
+        (kept, new) => kept.or(new),
+    };
+
+    // System messages are shown to the user, so they aren't capped
+    existing.system_message = prepend(existing.system_message, new.system_message);
+
+    // Accumulate context within the cap
+    let used = existing.context.as_ref().map_or(0, String::len);
+    if let Some(new_context) = new.context.and_then(|c| cap.fit(used, c)) {
         if let Some(existing_context) = existing.context.as_mut() {
             existing_context.push_str("\n\n");
             existing_context.push_str(&new_context);
@@ -517,6 +2775,45 @@ fn merge_responses(mut existing: Response, new: Response) -> Response {
     existing
 }
 
+/// Merge a rule's response the way its inject action asks: `channel:
+/// system_message` moves the rule's context into the system message, and
+/// `position: prepend` puts it ahead of context already merged
+fn merge_placed(
+    existing: Response,
+    mut new: Response,
+    inject: Option<&InjectAction>,
+    mode: PolicyMode,
+    cap: &mut ContextCap,
+) -> Response {
+    let Some(inject) = inject else {
+        return merge_responses_with_mode(existing, new, mode, cap);
+    };
+    if inject.channel() == InjectChannel::SystemMessage {
+        new.system_message = new.context.take();
+    }
+    if inject.position() == InjectPosition::Append || !new.continue_ {
+        return merge_responses_with_mode(existing, new, mode, cap);
+    }
+
+    let context = new.context.take();
+    let message = new.system_message.take();
+    let mut merged = merge_responses_with_mode(existing, new, mode, cap);
+    if let Some(context) = context {
+        let used = merged.context.as_ref().map_or(0, String::len);
+        merged.context = prepend(cap.fit(used, context), merged.context);
+    }
+    merged.system_message = prepend(message, merged.system_message);
+    merged
+}
+
+/// `first` and `rest` joined as paragraphs, either of which may be missing
+fn prepend(first: Option<String>, rest: Option<String>) -> Option<String> {
+    match (first, rest) {
+        (Some(first), Some(rest)) => Some(format!("{}\n\n{}", first, rest)),
+        (first, rest) => first.or(rest),
+    }
+}
+
 // =============================================================================
 // Phase 2 Governance: Mode-Based Action Execution
 // =============================================================================
@@ -532,15 +2829,24 @@ async fn execute_rule_actions_with_mode(
     rule: &Rule,
     config: &Config,
     mode: PolicyMode,
+    validator_runs: &mut Vec<ValidatorRun>,
+    state_store: &mut StateStore,
 ) -> Result<Response> {
     match mode {
         PolicyMode::Enforce => {
             // Normal execution - delegate to existing function
-            execute_rule_actions(event, rule, config).await
+            let mut response =
+                execute_rule_actions(event, rule, config, validator_runs, state_store).await?;
+            let template = rule.messages.as_ref().and_then(|m| m.block.as_deref());
+            if let (false, Some(template)) = (response.continue_, template) {
+                let reason = response.reason.take().unwrap_or_default();
+                response.reason = Some(RuleMessages::render(template, rule, &reason));
+            }
+            Ok(response)
         }
         PolicyMode::Warn => {
             // Never block, inject warning instead
-            execute_rule_actions_warn_mode(event, rule, config).await
+            execute_rule_actions_warn_mode(event, rule, config, validator_runs, state_store).await
         }
         PolicyMode::Audit => {
             // Log only, no blocking or injection
@@ -549,74 +2855,193 @@ async fn execute_rule_actions_with_mode(
     }
 }
 
+const WARNING_PREFIX: &str = "[WARNING] Rule '";
+const WARNING_FOOTER: &str = "This rule is in 'warn' mode - operation will proceed.";
+
+/// Format the warning a warn-mode rule injects instead of blocking
+fn render_warning(rule_name: &str, reason: &str) -> String {
+    format!(
+        "{}{}' {}\n{}",
+        WARNING_PREFIX, rule_name, reason, WARNING_FOOTER
+    )
+}
+
+/// Split a warning produced by `render_warning` back into (rule, reason)
+fn parse_warning(text: &str) -> Option<(&str, &str)> {
+    let body = text
+        .strip_prefix(WARNING_PREFIX)?
+        .strip_suffix(WARNING_FOOTER)?
+        .trim_end();
+    body.split_once("' ")
+}
+
+/// Merge warn-mode and validator warnings into the response
+///
+/// Identical rule+reason pairs were already dropped by the caller. Each
+/// warning uses its rule's `messages.warn` template when it has one. With
+/// `settings.collapse_warnings`, several warnings instead become one
+/// summary block that groups rules by reason.
+fn merge_warnings(
+    response: Response,
+    warnings: &[(String, String)],
+    rules: &[Rule],
+    collapse: bool,
+    cap: &mut ContextCap,
+) -> Response {
+    if warnings.is_empty() {
+        return response;
+    }
+    if !collapse || warnings.len() == 1 {
+        return warnings.iter().fold(response, |acc, (rule_name, reason)| {
+            let custom = rules
+                .iter()
+                .find(|r| &r.name == rule_name)
+                .and_then(|rule| {
+                    let template = rule.messages.as_ref()?.warn.as_deref()?;
+                    Some(RuleMessages::render(template, rule, reason))
+                });
+            let text = custom.unwrap_or_else(|| render_warning(rule_name, reason));
+            merge_responses(acc, Response::inject(text), cap)
+        });
+    }
+
+    // Group rules by reason, keeping first-seen order
+    let mut by_reason: Vec<(&str, Vec<&str>)> = Vec::new();
+    for (rule, reason) in warnings {
+        match by_reason.iter_mut().find(|(r, _)| r == reason) {
+            Some((_, rules)) => rules.push(rule),
+            None => by_reason.push((reason, vec![rule])),
+        }
+    }
+
+    let mut lines = vec![format!(
+        "[WARNING] {} warn-mode rules flagged this operation:",
+        warnings.len()
+    )];
+    for (reason, rules) in by_reason {
+        let names: Vec<String> = rules.iter().map(|r| format!("'{}'", r)).collect();
+        lines.push(format!(
+            "  - {} ({}x: {})",
+            reason,
+            rules.len(),
+            names.join(", ")
+        ));
+    }
+    lines.push("These rules are in 'warn' mode - operation will proceed.".to_string());
+    merge_responses(response, Response::inject(lines.join("\n")), cap)
+}
+
 /// Execute rule actions in warn mode (never blocks, injects warnings)
 async fn execute_rule_actions_warn_mode(
     event: &Event,
     rule: &Rule,
     config: &Config,
+    validator_runs: &mut Vec<ValidatorRun>,
+    state_store: &mut StateStore,
 ) -> Result<Response> {
     let actions = &rule.actions;
 
     // Convert blocks to warnings
     if let Some(block) = actions.block {
         if block {
-            let warning = format!(
-                "[WARNING] Rule '{}' would block this operation: {}\n\
-                 This rule is in 'warn' mode - operation will proceed.",
-                rule.name,
+            let reason = format!(
+                "would block this operation: {}",
                 rule.description.as_deref().unwrap_or("No description")
             );
-            return Ok(Response::inject(warning));
+            return Ok(Response::inject(render_warning(&rule.name, &reason)));
         }
     }
 
+    // Convert approval prompts to warnings
+    if actions.ask == Some(true) {
+        let reason = format!(
+            "would ask for approval of this operation: {}",
+            rule.description.as_deref().unwrap_or("No description")
+        );
+        return Ok(Response::inject(render_warning(&rule.name, &reason)));
+    }
+
     // Convert conditional blocks to warnings
     if let Some(ref pattern) = actions.block_if_match {
-        if let Some(ref tool_input) = event.tool_input {
-            if let Some(content) = tool_input
-                .get("newString")
-                .or_else(|| tool_input.get("content"))
-                .and_then(|c| c.as_str())
-            {
-                if let Ok(regex) = Regex::new(pattern) {
-                    if regex.is_match(content) {
-                        let warning = format!(
-                            "[WARNING] Rule '{}' would block this content (matches pattern '{}').\n\
-                             This rule is in 'warn' mode - operation will proceed.",
-                            rule.name, pattern
-                        );
-                        return Ok(Response::inject(warning));
-                    }
-                }
+        if content_matches(event, pattern) {
+            let reason = format!("would block this content (matches pattern '{}')", pattern);
+            return Ok(Response::inject(render_warning(&rule.name, &reason)));
+        }
+    }
+
+    // Convert structural check failures to warnings
+    if let Some(ref checks) = actions.structural_checks {
+        match structural_violations(event, checks) {
+            Ok(Some(summary)) => {
+                let reason = format!("would block this content: {}", summary);
+                return Ok(Response::inject(render_warning(&rule.name, &reason)));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tracing::warn!("Structural checks failed for rule '{}': {}", rule.name, e);
             }
         }
     }
 
-    // Context injection still works in warn mode
-    if let Some(ref inject_path) = actions.inject {
-        match read_context_file(inject_path).await {
-            Ok(context) => {
-                return Ok(Response::inject(context));
+    // Convert missing headers to warnings
+    if let Some(ref files) = actions.require_files {
+        let missing = missing_files(event, files);
+        if !missing.is_empty() {
+            let reason = format!(
+                "would block: required project files are missing: {}",
+                missing.join(", ")
+            );
+            return Ok(Response::inject(render_warning(&rule.name, &reason)));
+        }
+    }
+
+    if let Some(ref header) = actions.ensure_header {
+        match missing_header(event, header).await {
+            Ok(Some(path)) => {
+                let reason = format!(
+                    "would block this file: {} is missing the required header from {}",
+                    path, header.template
+                );
+                return Ok(Response::inject(render_warning(&rule.name, &reason)));
             }
+            Ok(None) => {}
             Err(e) => {
-                tracing::warn!("Failed to read context file '{}': {}", inject_path, e);
+                tracing::warn!("Header check failed for rule '{}': {}", rule.name, e);
             }
         }
     }
 
+    // Report rewrites instead of making them
+    if let Some(ref rewrite) = actions.rewrite {
+        if let Some(rewritten) = rewritten_input(event, rewrite) {
+            let reason = format!(
+                "would rewrite {}: {} -> {}",
+                rewrite.field, rewritten.before, rewritten.after
+            );
+            return Ok(Response::inject(render_warning(&rule.name, &reason)));
+        }
+    }
+
+    // Context injection still works in warn mode
+    if let Some(context) = injected_context(event, rule, config, state_store).await {
+        return Ok(Response::inject(context));
+    }
+
     // Script execution - convert blocks to warnings
-    if let Some(script_path) = actions.script_path() {
-        match execute_validator_script(event, script_path, rule, config).await {
+    if let Some(script_path) = actions
+        .script_path()
+        .filter(|_| !on_fast_path(event, config))
+    {
+        match execute_validator_script(event, script_path, rule, config, validator_runs).await {
             Ok(script_response) => {
                 if !script_response.continue_ {
                     // Convert block to warning
-                    let warning = format!(
-                        "[WARNING] Validator script '{}' would block this operation: {}\n\
-                         This rule is in 'warn' mode - operation will proceed.",
+                    let reason = format!(
+                        "would block this operation (validator script '{}'): {}",
                         script_path,
                         script_response.reason.as_deref().unwrap_or("No reason")
                     );
-                    return Ok(Response::inject(warning));
+                    return Ok(Response::inject(render_warning(&rule.name, &reason)));
                 }
                 return Ok(script_response);
             }
@@ -639,20 +3064,34 @@ async fn execute_rule_actions_warn_mode(
 /// - Enforce: Normal merge (blocks take precedence)
 /// - Warn: Blocks become warnings (never blocks)
 /// - Audit: No merging (allow always)
-fn merge_responses_with_mode(existing: Response, new: Response, mode: PolicyMode) -> Response {
+fn merge_responses_with_mode(
+    existing: Response,
+    new: Response,
+    mode: PolicyMode,
+    cap: &mut ContextCap,
+) -> Response {
     match mode {
         PolicyMode::Enforce => {
             // Normal merge behavior
-            merge_responses(existing, new)
+            merge_responses(existing, new, cap)
         }
         PolicyMode::Warn | PolicyMode::Audit => {
             // In warn/audit mode, new response should never block
             // (execute_rule_actions_with_mode ensures this)
-            merge_responses(existing, new)
+            merge_responses(existing, new, cap)
         }
     }
 }
 
+/// The decision logged for an event: an allowed event that raised a
+/// warning (a validator `warn` verdict under an enforce rule) was warned
+fn event_decision(response: &Response, mode: PolicyMode, counts: &RuleCounts) -> Decision {
+    match determine_decision(response, mode) {
+        Decision::Allowed if counts.rules_warned > 0 => Decision::Warned,
+        decision => decision,
+    }
+}
+
 /// Determine the decision outcome based on response and mode
 #[allow(dead_code)] // Used in Phase 2.2 (enhanced logging)
 pub fn determine_decision(response: &Response, mode: PolicyMode) -> Decision {
@@ -668,6 +3107,8 @@ pub fn determine_decision(response: &Response, mode: PolicyMode) -> Decision {
         PolicyMode::Enforce => {
             if !response.continue_ {
                 Decision::Blocked
+            } else if response.asks() {
+                Decision::Asked
             } else {
                 // Both injection and no-injection count as allowed
                 Decision::Allowed
@@ -789,17 +3230,21 @@ pub fn rule_takes_precedence(rule_a: &Rule, rule_b: &Rule) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{Actions, EventType, Matchers};
+    use crate::models::Actions;
     use chrono::Utc;
 
-    #[tokio::test]
-    async fn test_rule_matching() {
-        let event = Event {
+    /// State that starts empty and is never written to ~/.claude/state
+    fn store() -> StateStore {
+        StateStore::new(Persistence::Ephemeral)
+    }
+
+    /// A PreToolUse event for `tool`; tests override other fields with
+    /// `Event { .., ..event(tool, input) }`
+    fn event(tool: &str, input: serde_json::Value) -> Event {
+        Event {
             hook_event_name: EventType::PreToolUse,
-            tool_name: Some("Bash".to_string()),
-            tool_input: Some(serde_json::json!({
-                "command": "git push --force"
-            })),
+            tool_name: Some(tool.to_string()),
+            tool_input: Some(input),
             session_id: "test-session".to_string(),
             timestamp: Utc::now(),
             user_id: None,
@@ -807,14 +3252,109 @@ mod tests {
             cwd: None,
             permission_mode: None,
             tool_use_id: None,
+            prompt: None,
+            legacy_input_keys: false,
+            tool_response: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rule_matching() {
+        let event = event(
+            "Bash",
+            serde_json::json!({
+                "command": "git push --force"
+            }),
+        );
+
+        let rule = Rule {
+            name: "block-force-push".to_string(),
+            description: Some("Block force push".to_string()),
+            matchers: Matchers {
+                tools: Some(vec!["Bash".to_string()]),
+                command_match: Some(r"git push.*--force".to_string()),
+                zone: None,
+                exclude_tools: None,
+                exclude_extensions: None,
+                exclude_directories: None,
+                command_not_match: None,
+                tool_output_match: None,
+                exit_code: None,
+                prompt_match: None,
+                background: None,
+                protected_paths: None,
+                expr: None,
+                any: None,
+                all: None,
+                not: None,
+                policy: None,
+                extensions: None,
+                directories: None,
+                operations: None,
+            },
+            actions: Actions {
+                block: Some(true),
+                inject: None,
+                run: None,
+                block_if_match: None,
+                truncate_output: None,
+                filter_results: None,
+                structural_checks: None,
+                ensure_header: None,
+                inject_command: None,
+                ask: None,
+                require_files: None,
+                allow: None,
+                inject_once_per_session: None,
+                throttle: None,
+                enrich_prompt: None,
+                rewrite: None,
+            },
+            mode: None,
+            priority: None,
+            governance: None,
+            tests: None,
+            exemptions: None,
+            escalation: None,
+            bypass_users: None,
+            messages: None,
+            stop: None,
+            metadata: None,
         };
 
+        assert!(matches_rule(&event, &rule, &Config::default()));
+    }
+
+    #[tokio::test]
+    async fn test_rule_non_matching() {
+        let event = event(
+            "Bash",
+            serde_json::json!({
+                "command": "git status"
+            }),
+        );
+
         let rule = Rule {
             name: "block-force-push".to_string(),
             description: Some("Block force push".to_string()),
             matchers: Matchers {
                 tools: Some(vec!["Bash".to_string()]),
                 command_match: Some(r"git push.*--force".to_string()),
+                zone: None,
+                exclude_tools: None,
+                exclude_extensions: None,
+                exclude_directories: None,
+                command_not_match: None,
+                tool_output_match: None,
+                exit_code: None,
+                prompt_match: None,
+                background: None,
+                protected_paths: None,
+                expr: None,
+                any: None,
+                all: None,
+                not: None,
+                policy: None,
                 extensions: None,
                 directories: None,
                 operations: None,
@@ -824,56 +3364,1336 @@ mod tests {
                 inject: None,
                 run: None,
                 block_if_match: None,
+                truncate_output: None,
+                filter_results: None,
+                structural_checks: None,
+                ensure_header: None,
+                inject_command: None,
+                ask: None,
+                require_files: None,
+                allow: None,
+                inject_once_per_session: None,
+                throttle: None,
+                enrich_prompt: None,
+                rewrite: None,
             },
             mode: None,
             priority: None,
             governance: None,
+            tests: None,
+            exemptions: None,
+            escalation: None,
+            bypass_users: None,
+            messages: None,
+            stop: None,
             metadata: None,
         };
 
-        assert!(matches_rule(&event, &rule));
+        assert!(!matches_rule(&event, &rule, &Config::default()));
     }
 
-    #[tokio::test]
-    async fn test_rule_non_matching() {
-        let event = Event {
-            hook_event_name: EventType::PreToolUse,
-            tool_name: Some("Bash".to_string()),
-            tool_input: Some(serde_json::json!({
-                "command": "git status"
-            })),
-            session_id: "test-session".to_string(),
-            timestamp: Utc::now(),
-            user_id: None,
-            transcript_path: None,
-            cwd: None,
-            permission_mode: None,
-            tool_use_id: None,
+    #[test]
+    fn test_zone_matcher_on_read() {
+        let read_event = |path: &str| Event {
+            cwd: Some("/work/project".to_string()),
+            ..event("Read", serde_json::json!({ "file_path": path }))
         };
 
         let rule = Rule {
-            name: "block-force-push".to_string(),
-            description: Some("Block force push".to_string()),
+            name: "guard-secrets".to_string(),
+            description: None,
             matchers: Matchers {
-                tools: Some(vec!["Bash".to_string()]),
-                command_match: Some(r"git push.*--force".to_string()),
+                tools: Some(vec!["Read".to_string(), "Grep".to_string()]),
                 extensions: None,
                 directories: None,
                 operations: None,
+                command_match: None,
+                zone: Some(Sensitivity::Secret),
+                exclude_tools: None,
+                exclude_extensions: None,
+                exclude_directories: None,
+                command_not_match: None,
+                tool_output_match: None,
+                exit_code: None,
+                prompt_match: None,
+                background: None,
+                protected_paths: None,
+                expr: None,
+                any: None,
+                all: None,
+                not: None,
+                policy: None,
             },
             actions: Actions {
-                block: Some(true),
                 inject: None,
                 run: None,
+                block: Some(true),
                 block_if_match: None,
+                truncate_output: None,
+                filter_results: None,
+                structural_checks: None,
+                ensure_header: None,
+                inject_command: None,
+                ask: None,
+                require_files: None,
+                allow: None,
+                inject_once_per_session: None,
+                throttle: None,
+                enrich_prompt: None,
+                rewrite: None,
             },
             mode: None,
             priority: None,
             governance: None,
+            tests: None,
+            exemptions: None,
+            escalation: None,
+            bypass_users: None,
+            messages: None,
+            stop: None,
             metadata: None,
         };
 
-        assert!(!matches_rule(&event, &rule));
+        let config = Config {
+            zones: vec![crate::models::Zone {
+                path: "secrets/".to_string(),
+                level: Sensitivity::Secret,
+            }],
+            ..Config::default()
+        };
+
+        assert!(matches_rule(
+            &read_event("/work/project/secrets/prod.env"),
+            &rule,
+            &config
+        ));
+        assert!(!matches_rule(
+            &read_event("/work/project/src/main.rs"),
+            &rule,
+            &config
+        ));
+    }
+
+    #[test]
+    fn test_negated_matchers() {
+        let config = Config::default();
+
+        // Block all Bash except read-only git commands
+        let mut rule = create_rule_with_mode("bash-allowlist", PolicyMode::Enforce, 0);
+        rule.matchers.tools = Some(vec!["Bash".to_string()]);
+        rule.matchers.command_not_match = Some(r"^git (status|log|diff)\b".to_string());
+        let bash = |command: &str| event("Bash", serde_json::json!({ "command": command }));
+        assert!(matches_rule(&bash("rm -rf build"), &rule, &config));
+        assert!(!matches_rule(&bash("git status --short"), &rule, &config));
+
+        let (matched, results) = matches_rule_with_debug(&bash("git diff"), &rule, &config);
+        assert!(!matched);
+        let results = results.unwrap();
+        assert_eq!(results.tools_matched, Some(true));
+        assert_eq!(results.command_not_match_matched, Some(false));
+
+        // Writes anywhere except docs, and never to markdown files
+        let mut rule = create_rule_with_mode("no-writes", PolicyMode::Enforce, 0);
+        rule.matchers.exclude_tools = Some(vec!["Read".to_string()]);
+        rule.matchers.exclude_directories = Some(vec!["docs/**".to_string()]);
+        rule.matchers.exclude_extensions = Some(vec![".md".to_string()]);
+        let write = |path: &str| event("Write", serde_json::json!({ "file_path": path }));
+        assert!(matches_rule(&write("src/main.rs"), &rule, &config));
+        assert!(!matches_rule(&write("docs/guide.rs"), &rule, &config));
+        assert!(!matches_rule(&write("src/README.md"), &rule, &config));
+        assert!(!matches_rule(
+            &event("Read", serde_json::json!({ "file_path": "src/main.rs" })),
+            &rule,
+            &config
+        ));
+    }
+
+    #[test]
+    fn test_tool_result_matchers() {
+        let result = |response: Option<serde_json::Value>| Event {
+            hook_event_name: EventType::PostToolUse,
+            tool_response: response,
+            ..event("Bash", serde_json::json!({ "command": "cargo test" }))
+        };
+        let config = Config::default();
+
+        let mut rule = create_rule_with_mode("test-failures", PolicyMode::Enforce, 0);
+        rule.matchers.tools = Some(vec!["Bash".to_string()]);
+        rule.matchers.tool_output_match = Some(r"test result: FAILED|panicked at".to_string());
+        let failed = result(Some(serde_json::json!({
+            "stdout": "test result: FAILED. 3 passed; 1 failed",
+            "stderr": "",
+            "exit_code": 101
+        })));
+        let passed = result(Some(serde_json::json!({
+            "stdout": "test result: ok. 4 passed",
+            "stderr": "",
+            "exit_code": 0
+        })));
+        let panicked = result(Some(serde_json::json!({
+            "stdout": "",
+            "stderr": "thread 'main' panicked at src/main.rs:3:5"
+        })));
+        assert!(matches_rule(&failed, &rule, &config));
+        assert!(!matches_rule(&passed, &rule, &config));
+        assert!(matches_rule(&panicked, &rule, &config));
+        assert!(!matches_rule(&result(None), &rule, &config));
+
+        let mut rule = create_rule_with_mode("bash-failures", PolicyMode::Enforce, 0);
+        rule.matchers.exit_code = Some(serde_yaml::from_str("nonzero").unwrap());
+        assert!(matches_rule(&failed, &rule, &config));
+        assert!(!matches_rule(&passed, &rule, &config));
+        // No exit code reported
+        assert!(!matches_rule(&panicked, &rule, &config));
+
+        rule.matchers.exit_code = Some(serde_yaml::from_str("[1, 2]").unwrap());
+        let (matched, results) = matches_rule_with_debug(&failed, &rule, &config);
+        assert!(!matched);
+        assert_eq!(results.unwrap().exit_code_matched, Some(false));
+        rule.matchers.exit_code = Some(serde_yaml::from_str("101").unwrap());
+        assert!(matches_rule(&failed, &rule, &config));
+    }
+
+    #[tokio::test]
+    async fn test_stop_short_circuits_lower_priority_rules() {
+        let event = event("Bash", serde_json::json!({ "command": "git status" }));
+        let mut allow = create_rule_with_mode("allow-git-status", PolicyMode::Enforce, 100);
+        allow.matchers.command_match = Some("^git status".to_string());
+        allow.actions.block = None;
+        let block = create_rule_with_mode("block-bash", PolicyMode::Enforce, 0);
+        let mut config = Config {
+            rules: vec![block, allow],
+            ..Config::default()
+        };
+        let evaluate = |config: &Config| {
+            let config = config.clone();
+            let event = event.clone();
+            async move {
+                let report = evaluate_with_config(&event, &config).await.unwrap();
+                let matched: Vec<String> =
+                    report.matched_rules.into_iter().map(|r| r.name).collect();
+                (report.response.continue_, matched)
+            }
+        };
+
+        assert_eq!(
+            evaluate(&config).await,
+            (
+                false,
+                vec!["allow-git-status".to_string(), "block-bash".to_string()]
+            )
+        );
+
+        config.rules[1].stop = Some(true);
+        assert_eq!(
+            evaluate(&config).await,
+            (true, vec!["allow-git-status".to_string()])
+        );
+
+        // Audit rules never stop evaluation
+        config.rules[1].mode = Some(PolicyMode::Audit);
+        assert!(!evaluate(&config).await.0);
+
+        config.rules[1].stop = None;
+        config.rules[1].mode = Some(PolicyMode::Enforce);
+        config.settings.evaluation = EvaluationMode::FirstMatch;
+        assert_eq!(
+            evaluate(&config).await,
+            (true, vec!["allow-git-status".to_string()])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_evaluation_counts_checked_rules_by_outcome() {
+        let event = event("Bash", serde_json::json!({ "command": "rm -rf build" }));
+        let mut unrelated = create_rule_with_mode("no-git", PolicyMode::Enforce, 50);
+        unrelated.matchers.command_match = Some("^git ".to_string());
+        let mut config = Config {
+            rules: vec![
+                create_rule_with_mode("warn-rm", PolicyMode::Warn, 40),
+                create_rule_with_mode("audit-rm", PolicyMode::Audit, 30),
+                create_rule_with_mode("block-rm", PolicyMode::Enforce, 20),
+                create_rule_with_mode("block-bash", PolicyMode::Enforce, 10),
+                unrelated,
+            ],
+            ..Config::default()
+        };
+
+        let evaluation = evaluate_rules(&event, &config, &DebugConfig::default(), &mut store())
+            .await
+            .unwrap();
+        assert_eq!(evaluation.rule_evaluations.len(), 5);
+        assert_eq!(
+            evaluation.counts,
+            RuleCounts {
+                rules_matched: 4,
+                rules_blocked: 2,
+                rules_warned: 1,
+                rules_audited: 1,
+            }
+        );
+
+        // Rules after a stopping rule are never checked
+        config.rules[2].stop = Some(true);
+        let evaluation = evaluate_rules(&event, &config, &DebugConfig::default(), &mut store())
+            .await
+            .unwrap();
+        assert_eq!(evaluation.rule_evaluations.len(), 4);
+        assert_eq!(evaluation.counts.rules_matched, 3);
+        assert_eq!(evaluation.counts.rules_blocked, 1);
+    }
+
+    #[test]
+    fn test_background_matcher() {
+        let config = Config::default();
+
+        let mut rule = create_rule_with_mode("no-background-jobs", PolicyMode::Warn, 0);
+        rule.matchers.background = Some(true);
+        let background = event(
+            "Bash",
+            serde_json::json!({ "command": "npm run dev", "run_in_background": true }),
+        );
+        let kill = event("KillShell", serde_json::json!({ "shell_id": "bash_1" }));
+        let foreground = event("Bash", serde_json::json!({ "command": "npm test" }));
+        assert!(matches_rule(&background, &rule, &config));
+        assert!(matches_rule(&kill, &rule, &config));
+        assert!(!matches_rule(&foreground, &rule, &config));
+
+        rule.matchers.background = Some(false);
+        assert!(matches_rule(&foreground, &rule, &config));
+        let (matched, results) = matches_rule_with_debug(&background, &rule, &config);
+        assert!(!matched);
+        assert_eq!(results.unwrap().background_matched, Some(false));
+    }
+
+    #[test]
+    fn test_protected_paths_resolve_relative_paths_and_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = std::fs::canonicalize(dir.path()).unwrap().join("app");
+        std::fs::create_dir_all(project.join("src")).unwrap();
+        std::fs::create_dir_all(project.join("secrets")).unwrap();
+        let event = |tool_name: &str, file_path: &str| Event {
+            cwd: Some(project.to_string_lossy().into_owned()),
+            ..event(tool_name, serde_json::json!({ "file_path": file_path }))
+        };
+        let config = Config::default();
+
+        let mut rule = create_rule_with_mode("protect-secrets", PolicyMode::Enforce, 0);
+        rule.matchers.protected_paths = Some(ProtectedPaths {
+            deny: vec!["/etc/**".to_string(), "secrets/**".to_string()],
+            allow: vec!["secrets/README.md".to_string()],
+        });
+        let depth = project.components().count();
+        let passwd = format!("{}etc/passwd", "../".repeat(depth));
+        assert!(matches_rule(&event("Read", &passwd), &rule, &config));
+        assert!(matches_rule(
+            &event("Write", "src/../secrets/key.pem"),
+            &rule,
+            &config
+        ));
+        assert!(!matches_rule(
+            &event("Edit", "secrets/README.md"),
+            &rule,
+            &config
+        ));
+        assert!(!matches_rule(
+            &event("Write", "src/main.rs"),
+            &rule,
+            &config
+        ));
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(project.join("secrets"), project.join("src/keys")).unwrap();
+            assert!(matches_rule(
+                &event("Write", "src/keys/new.pem"),
+                &rule,
+                &config
+            ));
+        }
+
+        let (_, results) = matches_rule_with_debug(&event("Write", "src/lib.rs"), &rule, &config);
+        assert_eq!(results.unwrap().protected_paths_matched, Some(false));
+    }
+
+    #[tokio::test]
+    async fn test_allow_overrides_lower_priority_blocks() {
+        let event = event(
+            "Bash",
+            serde_json::json!({ "command": "git push origin feature" }),
+        );
+        let mut allow = create_rule_with_mode("allow-feature-push", PolicyMode::Enforce, 100);
+        allow.matchers.command_match = Some("^git push origin feature".to_string());
+        allow.actions.block = None;
+        allow.actions.allow = Some(true);
+        let mut block = create_rule_with_mode("no-push", PolicyMode::Enforce, 0);
+        block.matchers.command_match = Some("^git push".to_string());
+        let mut config = Config {
+            rules: vec![block, allow],
+            ..Config::default()
+        };
+
+        let evaluation = evaluate_rules(&event, &config, &DebugConfig::default(), &mut store())
+            .await
+            .unwrap();
+        assert!(evaluation.response.continue_);
+        assert!(evaluation.response.reason.is_none());
+        assert_eq!(
+            evaluation.allow_override,
+            Some(AllowOverride {
+                rule: "allow-feature-push".to_string(),
+                overridden: vec!["no-push".to_string()],
+            })
+        );
+
+        // Blocks of equal priority still win
+        config.rules[0].priority = Some(100);
+        let evaluation = evaluate_rules(&event, &config, &DebugConfig::default(), &mut store())
+            .await
+            .unwrap();
+        assert!(!evaluation.response.continue_);
+        assert!(evaluation.allow_override.is_none());
+
+        // Audit-mode allow rules don't override
+        config.rules[0].priority = Some(0);
+        config.rules[1].mode = Some(PolicyMode::Audit);
+        let evaluation = evaluate_rules(&event, &config, &DebugConfig::default(), &mut store())
+            .await
+            .unwrap();
+        assert!(!evaluation.response.continue_);
+    }
+
+    #[test]
+    fn test_prompt_match() {
+        let prompt = |hook_event_name, prompt: Option<&str>| Event {
+            hook_event_name,
+            tool_name: None,
+            tool_input: None,
+            prompt: prompt.map(str::to_string),
+            ..event("", serde_json::Value::Null)
+        };
+        let config = Config::default();
+
+        let mut rule = create_rule_with_mode("deploy-runbook", PolicyMode::Enforce, 0);
+        rule.matchers.prompt_match = Some(r"(?i)\bdeploy".to_string());
+        let submit = |text| prompt(EventType::UserPromptSubmit, Some(text));
+        assert!(matches_rule(&submit("Deploy to staging"), &rule, &config));
+        assert!(matches_rule(
+            &submit("how do we redeploy? deploying now"),
+            &rule,
+            &config
+        ));
+        assert!(!matches_rule(&submit("fix the login test"), &rule, &config));
+        assert!(!matches_rule(
+            &prompt(EventType::SessionStart, None),
+            &rule,
+            &config
+        ));
+
+        let (_, results) = matches_rule_with_debug(&submit("deploy"), &rule, &config);
+        assert_eq!(results.unwrap().prompt_match_matched, Some(true));
+    }
+
+    #[test]
+    fn test_boolean_matcher_composition() {
+        let bash = |command: &str| event("Bash", serde_json::json!({ "command": command }));
+        let config = Config::default();
+
+        let mut rule = create_rule_with_mode("destructive", PolicyMode::Enforce, 0);
+        rule.matchers = serde_yaml::from_str(
+            r#"
+tools: [Bash]
+any:
+  - command_match: "rm -rf"
+  - all:
+      - command_match: "git clean"
+      - command_match: "-[a-z]*x"
+not:
+  command_match: "/tmp/"
+"#,
+        )
+        .unwrap();
+
+        assert!(matches_rule(&bash("rm -rf build"), &rule, &config));
+        assert!(matches_rule(&bash("git clean -fdx"), &rule, &config));
+        assert!(!matches_rule(&bash("git clean -fd"), &rule, &config));
+        assert!(!matches_rule(&bash("rm -rf /tmp/cache"), &rule, &config));
+
+        let (matched, results) = matches_rule_with_debug(&bash("git clean -fd"), &rule, &config);
+        assert!(!matched);
+        let results = results.unwrap();
+        assert_eq!(results.any_matched, Some(false));
+        let any = results.any.unwrap();
+        assert_eq!(any[0].command_match_matched, Some(false));
+        assert_eq!(any[1].all_matched, Some(false));
+        assert_eq!(results.not_matched, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_multi_edit_matches_any_edit() {
+        let event = event(
+            "MultiEdit",
+            serde_json::json!({
+                "file_path": "docs/guide.md",
+                "edits": [
+                    { "old_string": "a", "new_string": "intro" },
+                    { "file_path": "src/config.rs", "old_string": "b", "new_string": "API_KEY=abc" }
+                ]
+            }),
+        );
+        let config = Config::default();
+
+        let mut rule = create_rule_with_mode("rust-secrets", PolicyMode::Enforce, 0);
+        rule.matchers = serde_yaml::from_str("extensions: [\".rs\"]").unwrap();
+        rule.actions.block = None;
+        rule.actions.block_if_match = Some("API_KEY".to_string());
+
+        assert!(matches_rule(&event, &rule, &config));
+        let (matched, results) = matches_rule_with_debug(&event, &rule, &config);
+        assert!(matched);
+        let results = results.unwrap();
+        assert_eq!(results.extensions_matched, Some(true));
+        let edits = results.edits.unwrap();
+        assert_eq!(edits[0].extensions_matched, Some(false));
+        assert_eq!(edits[1].extensions_matched, Some(true));
+
+        // Content checks see the second edit's replacement text
+        let response = execute_rule_actions(&event, &rule, &config, &mut Vec::new(), &mut store())
+            .await
+            .unwrap();
+        assert!(!response.continue_);
+    }
+
+    #[tokio::test]
+    async fn test_inline_suppression() {
+        let write = |content: &str| {
+            event(
+                "Write",
+                serde_json::json!({
+                    "file_path": "tests/fixtures.py",
+                    "content": content,
+                }),
+            )
+        };
+        let mut rule = create_rule_with_mode("no-keys", PolicyMode::Enforce, 0);
+        rule.matchers = serde_yaml::from_str("tools: [Write]").unwrap();
+        rule.actions.block = None;
+        rule.actions.block_if_match = Some("AKIA[0-9A-Z]{4}".to_string());
+        let mut config = Config {
+            rules: vec![rule.clone()],
+            ..Config::default()
+        };
+
+        let suppressed = "# cch:allow no-keys -- documented fake key\nKEY = 'AKIAFAKE'\n";
+        assert_eq!(inline_suppression(&write(suppressed), &rule, &config), None);
+
+        config.settings.inline_suppressions = true;
+        assert_eq!(
+            inline_suppression(&write(suppressed), &rule, &config).as_deref(),
+            Some("cch:allow: documented fake key")
+        );
+        let cases = [
+            // Same line, with a comment closer
+            (
+                "KEY = 'AKIAFAKE' /* cch:allow no-keys: test vector */",
+                true,
+            ),
+            // A second hit without its own comment is still blocked
+            (
+                "# cch:allow no-keys fake\nA = 'AKIAFAKE'\n\nB = 'AKIAREAL'",
+                false,
+            ),
+            // Other rules and bare markers don't count
+            ("# cch:allow no-keys-v2 fake\nKEY = 'AKIAFAKE'", false),
+            ("# cch:allow no-keys\nKEY = 'AKIAFAKE'", false),
+            // Too far from the hit
+            ("# cch:allow no-keys fake\n\nKEY = 'AKIAFAKE'", false),
+        ];
+        for (content, expected) in cases {
+            assert_eq!(
+                inline_suppression(&write(content), &rule, &config).is_some(),
+                expected,
+                "{:?}",
+                content
+            );
+        }
+
+        let (report, _) = evaluate_traced(
+            &write(suppressed),
+            &config,
+            &DebugConfig::default(),
+            Persistence::Ephemeral,
+        )
+        .await
+        .unwrap();
+        assert!(report.response.continue_);
+        assert_eq!(report.exemptions[0].rule, "no-keys");
+        assert_eq!(
+            report.exemptions[0].justification,
+            "cch:allow: documented fake key"
+        );
+    }
+
+    #[test]
+    fn test_find_exemption() {
+        let bash_event = |command: &str| event("Bash", serde_json::json!({ "command": command }));
+
+        let mut rule = create_rule_with_mode("block-force-push", PolicyMode::Enforce, 0);
+        rule.exemptions = Some(vec![Exemption {
+            paths: Vec::new(),
+            commands: vec![r"--force-with-lease origin feature/".to_string()],
+            sources: Vec::new(),
+            justification: "Feature branches may be rebased".to_string(),
+        }]);
+
+        let exemption = find_exemption(
+            &bash_event("git push --force-with-lease origin feature/x"),
+            &rule,
+        );
+        assert_eq!(
+            exemption.map(|e| e.justification.as_str()),
+            Some("Feature branches may be rebased")
+        );
+        assert!(find_exemption(&bash_event("git push --force origin main"), &rule).is_none());
+    }
+
+    #[test]
+    fn test_bypassing_user_matches_user_id() {
+        let event = |user_id: &str| Event {
+            user_id: Some(user_id.to_string()),
+            ..event("Bash", serde_json::json!({ "command": "git push" }))
+        };
+
+        let mut rule = create_rule_with_mode("review-checklist", PolicyMode::Enforce, 0);
+        let config = Config::default();
+        let mut identities = None;
+        assert!(
+            bypassing_user(
+                &event("ci-bot@example.com"),
+                &rule,
+                &config,
+                &mut identities
+            )
+            .is_none()
+        );
+        assert!(
+            identities.is_none(),
+            "identities are only looked up when needed"
+        );
+
+        rule.bypass_users = Some(vec!["ci-bot@example.com".to_string()]);
+        assert_eq!(
+            bypassing_user(&event("CI-Bot@Example.com"), &rule, &config, &mut None),
+            Some("ci-bot@example.com")
+        );
+        assert!(bypassing_user(&event("dev@example.com"), &rule, &config, &mut None).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_inject_list_concatenates_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name).to_string_lossy().into_owned();
+        std::fs::write(path("a.md"), "Alpha\n").unwrap();
+        std::fs::write(path("b.md"), "Beta\n").unwrap();
+        let event = Event {
+            tool_input: None,
+            ..event("Edit", serde_json::Value::Null)
+        };
+
+        let inject = InjectAction::Multiple(vec![
+            InjectSource::Path(path("a.md")),
+            InjectSource::Path(path("missing.md")),
+            InjectSource::Section {
+                file: path("b.md"),
+                header: Some("Second".to_string()),
+            },
+        ]);
+        assert_eq!(
+            read_injected_context(&inject, &event, &mut store())
+                .await
+                .as_deref(),
+            Some("Alpha\n\n## Second\n\nBeta")
+        );
+
+        // A single file is injected verbatim
+        let single = InjectAction::Single(InjectSource::Path(path("a.md")));
+        assert_eq!(
+            read_injected_context(&single, &event, &mut store())
+                .await
+                .as_deref(),
+            Some("Alpha\n")
+        );
+
+        let missing = InjectAction::Single(InjectSource::Path(path("missing.md")));
+        assert!(
+            read_injected_context(&missing, &event, &mut store())
+                .await
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_inject_front_matter_filters_and_orders() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name).to_string_lossy().into_owned();
+        std::fs::write(path("general.md"), "General\n").unwrap();
+        std::fs::write(
+            path("bash.md"),
+            "---\nonly_tools: [Bash]\n---\nShell rules\n",
+        )
+        .unwrap();
+        std::fs::write(path("urgent.md"), "---\npriority: 10\n---\nUrgent\n").unwrap();
+        let event = |tool: &str| Event {
+            tool_input: None,
+            ..event(tool, serde_json::Value::Null)
+        };
+
+        let inject = InjectAction::Multiple(
+            ["general.md", "bash.md", "urgent.md"]
+                .map(|name| InjectSource::Path(path(name)))
+                .to_vec(),
+        );
+        assert_eq!(
+            read_injected_context(&inject, &event("Bash"), &mut store())
+                .await
+                .as_deref(),
+            Some("Urgent\n\nGeneral\n\nShell rules")
+        );
+        assert_eq!(
+            read_injected_context(&inject, &event("Edit"), &mut store())
+                .await
+                .as_deref(),
+            Some("Urgent\n\nGeneral")
+        );
+
+        // A single file loses its front matter; ruled out, nothing is injected
+        let single = InjectAction::Single(InjectSource::Path(path("bash.md")));
+        assert_eq!(
+            read_injected_context(&single, &event("Bash"), &mut store())
+                .await
+                .as_deref(),
+            Some("Shell rules\n")
+        );
+        assert!(
+            read_injected_context(&single, &event("Edit"), &mut store())
+                .await
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_inject_command_output_is_capped() {
+        let mut rule = create_rule_with_mode("branch-context", PolicyMode::Enforce, 0);
+        rule.actions.block = None;
+        rule.actions.inject_command = Some("printf 'on branch main'; echo ' and more'".to_string());
+        let event = event("Bash", serde_json::json!({ "command": "git commit" }));
+
+        let response = execute_rule_actions(
+            &event,
+            &rule,
+            &Config::default(),
+            &mut Vec::new(),
+            &mut store(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.context.as_deref(), Some("on branch main and more"));
+
+        let mut config = Config::default();
+        config.settings.max_context_size = 14;
+        let response = execute_rule_actions(&event, &rule, &config, &mut Vec::new(), &mut store())
+            .await
+            .unwrap();
+        assert_eq!(
+            response.context.as_deref(),
+            Some("on branch main\n[output truncated by CCH at 14 bytes]")
+        );
+
+        // A failing command injects nothing
+        rule.actions.inject_command = Some("echo partial; exit 3".to_string());
+        let response = execute_rule_actions(
+            &event,
+            &rule,
+            &Config::default(),
+            &mut Vec::new(),
+            &mut store(),
+        )
+        .await
+        .unwrap();
+        assert!(response.context.is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_validator_receives_rule_environment() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("env.sh");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\ncat > /dev/null\necho \"$CCH_RULE_NAME $CCH_EVENT_TYPE $CCH_SESSION_ID $CCH_PROJECT_ROOT $MAX_LINES\"\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut rule = create_rule_with_mode("file-length", PolicyMode::Enforce, 0);
+        rule.actions.block = None;
+        rule.actions.run = Some(
+            serde_yaml::from_str(&format!(
+                "{{ script: {}, env: {{ MAX_LINES: \"400\" }} }}",
+                script.display()
+            ))
+            .unwrap(),
+        );
+        let event = Event {
+            session_id: "env-session".to_string(),
+            cwd: Some("/tmp/project".to_string()),
+            ..event("Write", serde_json::json!({ "file_path": "src/lib.rs" }))
+        };
+
+        let mut config = Config::default();
+        config.settings.fail_open = false;
+        let response = execute_rule_actions(&event, &rule, &config, &mut Vec::new(), &mut store())
+            .await
+            .unwrap();
+        let context = response.context.unwrap();
+        assert!(
+            context.starts_with("file-length PreToolUse env-session /"),
+            "{}",
+            context
+        );
+        assert!(context.ends_with(" 400"), "{}", context);
+    }
+
+    #[tokio::test]
+    async fn test_validator_json_verdicts() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("verdict.sh");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\ncat > /dev/null\nprintf '%s' \"$VERDICT\"\nexit 1\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let event = Event {
+            session_id: "verdict-session".to_string(),
+            ..event("Write", serde_json::json!({ "file_path": "src/lib.rs" }))
+        };
+        let mut config = Config::default();
+        config.settings.fail_open = false;
+        let run = |verdict: &str| {
+            let mut rule = create_rule_with_mode("file-length", PolicyMode::Enforce, 0);
+            rule.actions.block = None;
+            rule.actions.run = Some(
+                serde_yaml::from_str(&format!(
+                    "{{ script: {}, env: {{ VERDICT: {} }} }}",
+                    script.display(),
+                    serde_json::to_string(verdict).unwrap()
+                ))
+                .unwrap(),
+            );
+            let config = config.clone();
+            let event = event.clone();
+            async move {
+                execute_rule_actions(&event, &rule, &config, &mut Vec::new(), &mut store())
+                    .await
+                    .unwrap()
+            }
+        };
+
+        // A verdict overrides the non-zero exit code
+        let response =
+            run(r#"{"decision": "warn", "reason": "412 lines", "context": "Split it up"}"#).await;
+        assert!(response.continue_);
+        assert_eq!(
+            response.context.as_deref(),
+            Some(render_warning("file-length", "412 lines\n\nSplit it up").as_str())
+        );
+
+        let response = run(r#"{"decision": "allow", "context": "Looks fine"}"#).await;
+        assert!(response.continue_);
+        assert_eq!(response.context.as_deref(), Some("Looks fine"));
+
+        let response = run(r#"{"decision": "block", "reason": "Too long"}"#).await;
+        assert!(!response.continue_);
+        assert_eq!(response.reason.as_deref(), Some("Too long"));
+
+        // Anything else keeps the exit-code protocol
+        let response = run("not json").await;
+        assert!(!response.continue_);
+        let response = run(r#"{"decision": "maybe"}"#).await;
+        assert!(!response.continue_);
+    }
+
+    #[tokio::test]
+    async fn test_validator_warn_verdict_under_enforce_is_warned() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("warn.sh");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\ncat > /dev/null\necho '{\"decision\": \"warn\", \"reason\": \"412 lines\"}'\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut rule = create_rule_with_mode("file-length", PolicyMode::Enforce, 0);
+        rule.actions.block = None;
+        rule.actions.run = Some(RunAction::Simple(script.display().to_string()));
+        let config = Config {
+            rules: vec![rule],
+            ..Config::default()
+        };
+        let event = Event {
+            session_id: "warn-verdict-session".to_string(),
+            ..event("Write", serde_json::json!({ "file_path": "src/lib.rs" }))
+        };
+
+        let report = evaluate_with_config(&event, &config).await.unwrap();
+        assert_eq!(report.decision, Some(Decision::Warned));
+        assert!(report.response.continue_);
+        assert_eq!(
+            report.response.context.as_deref(),
+            Some(render_warning("file-length", "412 lines").as_str())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fast_path_tools_skip_validators_and_commands() {
+        let mut rule = create_rule_with_mode("read-policy", PolicyMode::Enforce, 0);
+        rule.actions.block = None;
+        rule.actions.run = Some(RunAction::Simple("/nonexistent/validator.sh".to_string()));
+        let event = Event {
+            session_id: "fast-session".to_string(),
+            ..event("Read", serde_json::json!({ "file_path": "src/lib.rs" }))
+        };
+
+        // Without the fast path the unrunnable validator fails closed
+        let mut config = Config::default();
+        config.settings.fail_open = false;
+        assert!(
+            execute_rule_actions(&event, &rule, &config, &mut Vec::new(), &mut store())
+                .await
+                .is_err()
+        );
+
+        config.settings.fast_path_tools = vec!["Read".to_string(), "Glob".to_string()];
+        let mut runs = Vec::new();
+        let response = execute_rule_actions(&event, &rule, &config, &mut runs, &mut store())
+            .await
+            .unwrap();
+        assert!(response.continue_);
+        assert!(runs.is_empty());
+
+        rule.actions.run = None;
+        rule.actions.inject_command = Some("echo slow".to_string());
+        let response = execute_rule_actions(&event, &rule, &config, &mut runs, &mut store())
+            .await
+            .unwrap();
+        assert!(response.context.is_none());
+
+        // Pure matchers still apply on the fast path
+        rule.actions.inject_command = None;
+        rule.actions.block = Some(true);
+        let response = execute_rule_actions(&event, &rule, &config, &mut runs, &mut store())
+            .await
+            .unwrap();
+        assert!(!response.continue_);
+    }
+
+    #[tokio::test]
+    async fn test_require_files_reminds_or_blocks() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("CODEOWNERS"), "* @org/team").unwrap();
+        let mut rule = create_rule_with_mode("org-scaffolding", PolicyMode::Enforce, 0);
+        rule.actions.block = None;
+        rule.actions.require_files =
+            Some(vec!["CODEOWNERS".to_string(), "SECURITY.md".to_string()]);
+        let event = |hook_event_name| Event {
+            hook_event_name,
+            tool_name: None,
+            tool_input: None,
+            cwd: Some(dir.path().to_string_lossy().into_owned()),
+            ..event("", serde_json::Value::Null)
+        };
+        let config = Config::default();
+
+        let response = execute_rule_actions(
+            &event(EventType::SessionStart),
+            &rule,
+            &config,
+            &mut Vec::new(),
+            &mut store(),
+        )
+        .await
+        .unwrap();
+        assert!(response.continue_);
+        let context = response.context.unwrap();
+        assert!(context.contains("missing: SECURITY.md."), "{}", context);
+
+        let response = execute_rule_actions(
+            &event(EventType::PreToolUse),
+            &rule,
+            &config,
+            &mut Vec::new(),
+            &mut store(),
+        )
+        .await
+        .unwrap();
+        assert!(!response.continue_);
+
+        std::fs::write(dir.path().join("SECURITY.md"), "Report issues privately").unwrap();
+        let response = execute_rule_actions(
+            &event(EventType::PreToolUse),
+            &rule,
+            &config,
+            &mut Vec::new(),
+            &mut store(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response, Response::allow());
+    }
+
+    #[tokio::test]
+    async fn test_ask_depends_on_event_type() {
+        let mut rule = create_rule_with_mode("approve", PolicyMode::Enforce, 0);
+        rule.actions.block = None;
+        rule.actions.ask = Some(true);
+        let event = |hook_event_name| Event {
+            hook_event_name,
+            ..event("Bash", serde_json::json!({ "command": "terraform apply" }))
+        };
+        let config = Config::default();
+
+        let response = execute_rule_actions(
+            &event(EventType::PreToolUse),
+            &rule,
+            &config,
+            &mut Vec::new(),
+            &mut store(),
+        )
+        .await
+        .unwrap();
+        assert!(response.continue_ && response.asks());
+        assert_eq!(
+            determine_decision(&response, PolicyMode::Enforce),
+            Decision::Asked
+        );
+
+        // The prompt is already showing for permission requests
+        let response = execute_rule_actions(
+            &event(EventType::PermissionRequest),
+            &rule,
+            &config,
+            &mut Vec::new(),
+            &mut store(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response, Response::allow());
+
+        // Events without a permission prompt fail closed
+        let response = execute_rule_actions(
+            &event(EventType::PostToolUse),
+            &rule,
+            &config,
+            &mut Vec::new(),
+            &mut store(),
+        )
+        .await
+        .unwrap();
+        assert!(!response.continue_);
+
+        // An ask survives merging with plain context, and a block still wins
+        let merged = merge_responses(
+            Response::inject("context"),
+            Response::ask("Rule 'approve' requires approval"),
+            &mut ContextCap::new(usize::MAX),
+        );
+        assert!(merged.asks() && merged.context.is_some());
+        assert!(
+            !merge_responses(
+                merged,
+                Response::block("no"),
+                &mut ContextCap::new(usize::MAX)
+            )
+            .continue_
+        );
+    }
+
+    #[test]
+    fn test_policy_summary_reports_expired_rules_demoted() {
+        let current = create_rule_with_mode("current", PolicyMode::Enforce, 0);
+        let mut expired = create_rule_with_mode("expired", PolicyMode::Enforce, 0);
+        expired.governance = Some(GovernanceMetadata {
+            expires: chrono::NaiveDate::from_ymd_opt(2020, 1, 1),
+            ..GovernanceMetadata::default()
+        });
+        let mut config = Config::default();
+        config.settings.expired_rule_mode = PolicyMode::Warn;
+
+        let summary = policy_summary(&[&current, &expired], &[], Some(Decision::Blocked), &config);
+        let json: serde_json::Value =
+            serde_json::from_str(summary.strip_prefix("[cch-policy] ").unwrap()).unwrap();
+        assert_eq!(json["matched"][0]["mode"], "enforce");
+        assert_eq!(json["matched"][1]["rule"], "expired");
+        assert_eq!(json["matched"][1]["mode"], "warn");
+    }
+
+    #[test]
+    fn test_path_matchers_read_file_path_in_either_spelling() {
+        let mut rule = create_rule_with_mode("rust-src", PolicyMode::Enforce, 0);
+        rule.matchers.extensions = Some(vec![".rs".to_string()]);
+        rule.matchers.directories = Some(vec!["src/**".to_string()]);
+        let config = Config::default();
+
+        // Claude Code sends `file_path`
+        let write = event("Write", serde_json::json!({ "file_path": "src/lib.rs" }));
+        assert!(matches_rule(&write, &rule, &config));
+        let other = event("Write", serde_json::json!({ "file_path": "docs/a.md" }));
+        assert!(!matches_rule(&other, &rule, &config));
+
+        // Legacy `filePath` payloads are normalized before matching
+        let (legacy, _) = Event::from_agent_payload(serde_json::json!({
+            "hook_event_name": "PreToolUse",
+            "session_id": "s",
+            "tool_name": "Write",
+            "tool_input": { "filePath": "src/lib.rs" }
+        }))
+        .unwrap();
+        assert!(matches_rule(&legacy, &rule, &config));
+    }
+
+    #[tokio::test]
+    async fn test_truncate_output_on_post_tool_use() {
+        let output = (1..=100)
+            .map(|i| format!("line {}", i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut event = Event {
+            hook_event_name: EventType::PostToolUse,
+            tool_response: Some(serde_json::json!({ "stdout": output, "stderr": "" })),
+            ..event("mcp__build__run", serde_json::json!({ "target": "all" }))
+        };
+
+        let mut rule = create_rule_with_mode("truncate", PolicyMode::Enforce, 0);
+        rule.actions.block = None;
+        rule.actions.truncate_output = Some(TruncateOutput {
+            max_lines: Some(10),
+            max_bytes: None,
+            strategy: crate::models::TruncateStrategy::HeadTail,
+        });
+
+        let response = execute_rule_actions(
+            &event,
+            &rule,
+            &Config::default(),
+            &mut Vec::new(),
+            &mut store(),
+        )
+        .await
+        .unwrap();
+        let updated = response.updated_mcp_output().unwrap().as_str().unwrap();
+        assert!(updated.contains("90 lines truncated"));
+        assert!(updated.ends_with("line 100"));
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["hookSpecificOutput"]["hookEventName"], "PostToolUse");
+        assert_eq!(json["hookSpecificOutput"]["updatedMCPToolOutput"], updated);
+        assert!(json.get("updated_output").is_none());
+        assert!(ResponseSummary::from_response(&response).output_replaced);
+
+        // Built-in tool output can't be replaced, so it is sent as context
+        event.tool_name = Some("Bash".to_string());
+        let response = execute_rule_actions(
+            &event,
+            &rule,
+            &Config::default(),
+            &mut Vec::new(),
+            &mut store(),
+        )
+        .await
+        .unwrap();
+        assert!(response.updated_mcp_output().is_none());
+        let json = serde_json::to_value(&response).unwrap();
+        let context = json["hookSpecificOutput"]["additionalContext"]
+            .as_str()
+            .unwrap();
+        assert!(context.starts_with("Rule 'truncate' shortens Bash output"));
+        assert!(context.contains("90 lines truncated"));
+        assert!(context.ends_with("line 100"));
+
+        // PreToolUse events are never truncated
+        event.tool_name = Some("mcp__build__run".to_string());
+        event.hook_event_name = EventType::PreToolUse;
+        let response = execute_rule_actions(
+            &event,
+            &rule,
+            &Config::default(),
+            &mut Vec::new(),
+            &mut store(),
+        )
+        .await
+        .unwrap();
+        assert!(response.updated_mcp_output().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rewrite_updates_pre_tool_use_input_by_mode() {
+        let mut event = event(
+            "Bash",
+            serde_json::json!({ "command": "terraform apply", "timeout": 60 }),
+        );
+
+        let mut rule = create_rule_with_mode("dry-run", PolicyMode::Enforce, 0);
+        rule.actions.block = None;
+        rule.actions.rewrite = Some(RewriteInput {
+            field: "command".to_string(),
+            pattern: None,
+            replace: None,
+            append: Some(" --dry-run".to_string()),
+        });
+        let config = Config::default();
+
+        let response = execute_rule_actions_with_mode(
+            &event,
+            &rule,
+            &config,
+            PolicyMode::Enforce,
+            &mut Vec::new(),
+            &mut store(),
+        )
+        .await
+        .unwrap();
+        assert!(response.continue_);
+        assert_eq!(
+            response.updated_input(),
+            Some(&serde_json::json!({ "command": "terraform apply --dry-run", "timeout": 60 }))
+        );
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(
+            json["hookSpecificOutput"]["updatedInput"]["command"],
+            "terraform apply --dry-run"
+        );
+        assert!(ResponseSummary::from_response(&response).input_rewritten);
+
+        // A later rule's permission prompt survives the merge
+        let mut cap = ContextCap::new(usize::MAX);
+        let merged = merge_responses(response, Response::ask("Review"), &mut cap);
+        assert!(merged.asks());
+        assert!(merged.updated_input().is_some());
+
+        let warned = execute_rule_actions_with_mode(
+            &event,
+            &rule,
+            &config,
+            PolicyMode::Warn,
+            &mut Vec::new(),
+            &mut store(),
+        )
+        .await
+        .unwrap();
+        assert!(warned.updated_input().is_none());
+        assert!(
+            warned
+                .context
+                .unwrap()
+                .contains("would rewrite command: terraform apply -> terraform apply --dry-run")
+        );
+
+        let audited = execute_rule_actions_with_mode(
+            &event,
+            &rule,
+            &config,
+            PolicyMode::Audit,
+            &mut Vec::new(),
+            &mut store(),
+        )
+        .await
+        .unwrap();
+        assert!(audited.updated_input().is_none());
+
+        // Only PreToolUse input can be rewritten
+        event.hook_event_name = EventType::PostToolUse;
+        let response = execute_rule_actions(&event, &rule, &config, &mut Vec::new(), &mut store())
+            .await
+            .unwrap();
+        assert!(response.updated_input().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_filter_results_on_glob_and_grep() {
+        let mut event = Event {
+            hook_event_name: EventType::PostToolUse,
+            cwd: Some("/repo".to_string()),
+            tool_response: Some(serde_json::json!({
+                "filenames": ["/repo/config/app.yaml", "/repo/secrets/prod.yaml"],
+                "numFiles": 2
+            })),
+            ..event("Glob", serde_json::json!({ "pattern": "**/*.yaml" }))
+        };
+
+        let mut rule = create_rule_with_mode("hide-secrets", PolicyMode::Enforce, 0);
+        rule.actions.block = None;
+        rule.actions.filter_results = Some(FilterResults {
+            exclude: vec!["secrets/**".to_string()],
+        });
+
+        let response = execute_rule_actions(
+            &event,
+            &rule,
+            &Config::default(),
+            &mut Vec::new(),
+            &mut store(),
+        )
+        .await
+        .unwrap();
+        assert!(response.continue_);
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["hookSpecificOutput"]["hookEventName"], "PostToolUse");
+        let context = json["hookSpecificOutput"]["additionalContext"]
+            .as_str()
+            .unwrap();
+        assert!(context.starts_with("Rule 'hide-secrets' excludes results under secrets/**"));
+        assert!(context.ends_with("\n/repo/config/app.yaml\n[1 results hidden by CCH]"));
+        assert!(json.get("updated_output").is_none());
+
+        // Grep content mode filters by the path prefix of each line
+        event.tool_name = Some("Grep".to_string());
+        event.tool_response = Some(serde_json::json!({
+            "content": "src/main.rs:3:password\nsecrets/key.txt:1:password"
+        }));
+        let response = execute_rule_actions(
+            &event,
+            &rule,
+            &Config::default(),
+            &mut Vec::new(),
+            &mut store(),
+        )
+        .await
+        .unwrap();
+        let context = response
+            .hook_specific_output
+            .and_then(|o| o.additional_context)
+            .unwrap();
+        assert!(context.ends_with("\nsrc/main.rs:3:password\n[1 results hidden by CCH]"));
+
+        // Other tools are left alone
+        event.tool_name = Some("Bash".to_string());
+        let response = execute_rule_actions(
+            &event,
+            &rule,
+            &Config::default(),
+            &mut Vec::new(),
+            &mut store(),
+        )
+        .await
+        .unwrap();
+        assert!(response.hook_specific_output.is_none());
     }
 
     #[tokio::test]
@@ -883,15 +4703,45 @@ mod tests {
         let inject = Response::inject("context");
 
         // Block takes precedence
-        let merged = merge_responses(allow.clone(), block.clone());
+        let merged = merge_responses(
+            allow.clone(),
+            block.clone(),
+            &mut ContextCap::new(usize::MAX),
+        );
         assert!(!merged.continue_);
 
         // Inject accumulates
-        let merged = merge_responses(inject.clone(), inject.clone());
+        let merged = merge_responses(
+            inject.clone(),
+            inject.clone(),
+            &mut ContextCap::new(usize::MAX),
+        );
         assert!(merged.continue_);
         assert!(merged.context.as_ref().unwrap().contains("context"));
     }
 
+    #[test]
+    fn test_merged_context_respects_max_context_size() {
+        let mut cap = ContextCap::new(200);
+        let merged = merge_responses(
+            Response::allow(),
+            Response::inject("a".repeat(30)),
+            &mut cap,
+        );
+        let merged = merge_responses(merged, Response::inject("b".repeat(30)), &mut cap);
+        assert!(!cap.truncated);
+        assert_eq!(merged.context.as_ref().unwrap().len(), 62);
+
+        // The lower-priority context is cut to fit, then dropped
+        let merged = merge_responses(merged, Response::inject("c".repeat(300)), &mut cap);
+        assert!(cap.truncated);
+        let context = merged.context.as_ref().unwrap();
+        assert_eq!(context.len(), 200);
+        assert!(context.ends_with("[context truncated by CCH at 200 bytes]"));
+        let merged = merge_responses(merged, Response::inject("d"), &mut cap);
+        assert_eq!(merged.context.unwrap().len(), 200);
+    }
+
     // =========================================================================
     // Phase 2 Governance: Mode-Based Execution Tests
     // =========================================================================
@@ -931,7 +4781,12 @@ mod tests {
         let block = Response::block("blocked");
 
         // In enforce mode, block takes precedence
-        let merged = merge_responses_with_mode(allow, block, PolicyMode::Enforce);
+        let merged = merge_responses_with_mode(
+            allow,
+            block,
+            PolicyMode::Enforce,
+            &mut ContextCap::new(usize::MAX),
+        );
         assert!(!merged.continue_);
     }
 
@@ -941,11 +4796,122 @@ mod tests {
         let warning = Response::inject("warning");
 
         // In warn mode, warnings accumulate but never block
-        let merged = merge_responses_with_mode(allow, warning, PolicyMode::Warn);
+        let merged = merge_responses_with_mode(
+            allow,
+            warning,
+            PolicyMode::Warn,
+            &mut ContextCap::new(usize::MAX),
+        );
         assert!(merged.continue_);
         assert!(merged.context.is_some());
     }
 
+    #[test]
+    fn test_warning_roundtrip() {
+        let text = render_warning("no-console", "would block this content: it's noisy");
+        assert_eq!(
+            parse_warning(&text),
+            Some(("no-console", "would block this content: it's noisy"))
+        );
+        assert!(parse_warning("plain injected context").is_none());
+    }
+
+    #[test]
+    fn test_merge_warnings_collapse() {
+        let warnings = vec![
+            (
+                "a".to_string(),
+                "would block this operation: risky".to_string(),
+            ),
+            (
+                "b".to_string(),
+                "would block this operation: risky".to_string(),
+            ),
+            (
+                "c".to_string(),
+                "would block this content: other".to_string(),
+            ),
+        ];
+
+        let separate = merge_warnings(
+            Response::allow(),
+            &warnings,
+            &[],
+            false,
+            &mut ContextCap::new(usize::MAX),
+        );
+        assert_eq!(separate.context.unwrap().matches("[WARNING]").count(), 3);
+
+        let collapsed = merge_warnings(
+            Response::allow(),
+            &warnings,
+            &[],
+            true,
+            &mut ContextCap::new(usize::MAX),
+        );
+        let context = collapsed.context.unwrap();
+        assert_eq!(context.matches("[WARNING]").count(), 1);
+        assert!(context.contains("3 warn-mode rules"));
+        assert!(context.contains("risky (2x: 'a', 'b')"));
+    }
+
+    #[tokio::test]
+    async fn test_rule_messages_override_block_and_warn_text() {
+        let mut rule = create_rule_with_mode("no-force-push", PolicyMode::Enforce, 0);
+        rule.messages = Some(RuleMessages {
+            warn: Some("Heads up from {rule}: {reason}. See docs/git.md".to_string()),
+            block: Some("{description}. See docs/git.md ({reason})".to_string()),
+        });
+        let event = Event {
+            session_id: "messages-session".to_string(),
+            ..event("Bash", serde_json::json!({ "command": "git push --force" }))
+        };
+        let config = Config::default();
+
+        let blocked = execute_rule_actions_with_mode(
+            &event,
+            &rule,
+            &config,
+            PolicyMode::Enforce,
+            &mut Vec::new(),
+            &mut store(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            blocked.reason.as_deref(),
+            Some(
+                "no-force-push rule. See docs/git.md (Blocked by rule 'no-force-push': no-force-push rule)"
+            )
+        );
+
+        let warned = execute_rule_actions_with_mode(
+            &event,
+            &rule,
+            &config,
+            PolicyMode::Warn,
+            &mut Vec::new(),
+            &mut store(),
+        )
+        .await
+        .unwrap();
+        let warning = parse_warning(warned.context.as_deref().unwrap()).unwrap();
+        let warnings = vec![(warning.0.to_string(), warning.1.to_string())];
+        let merged = merge_warnings(
+            Response::allow(),
+            &warnings,
+            std::slice::from_ref(&rule),
+            false,
+            &mut ContextCap::new(usize::MAX),
+        );
+        assert_eq!(
+            merged.context.as_deref(),
+            Some(
+                "Heads up from no-force-push: would block this operation: no-force-push rule. See docs/git.md"
+            )
+        );
+    }
+
     #[test]
     fn test_rule_effective_mode_defaults_to_enforce() {
         let rule = Rule {
@@ -957,16 +4923,49 @@ mod tests {
                 directories: None,
                 operations: None,
                 command_match: None,
+                zone: None,
+                exclude_tools: None,
+                exclude_extensions: None,
+                exclude_directories: None,
+                command_not_match: None,
+                tool_output_match: None,
+                exit_code: None,
+                prompt_match: None,
+                background: None,
+                protected_paths: None,
+                expr: None,
+                any: None,
+                all: None,
+                not: None,
+                policy: None,
             },
             actions: Actions {
                 inject: None,
                 run: None,
                 block: None,
                 block_if_match: None,
+                truncate_output: None,
+                filter_results: None,
+                structural_checks: None,
+                ensure_header: None,
+                inject_command: None,
+                ask: None,
+                require_files: None,
+                allow: None,
+                inject_once_per_session: None,
+                throttle: None,
+                enrich_prompt: None,
+                rewrite: None,
             },
             mode: None, // No mode specified
             priority: None,
             governance: None,
+            tests: None,
+            exemptions: None,
+            escalation: None,
+            bypass_users: None,
+            messages: None,
+            stop: None,
             metadata: None,
         };
         assert_eq!(rule.effective_mode(), PolicyMode::Enforce);
@@ -983,16 +4982,49 @@ mod tests {
                 directories: None,
                 operations: None,
                 command_match: None,
+                zone: None,
+                exclude_tools: None,
+                exclude_extensions: None,
+                exclude_directories: None,
+                command_not_match: None,
+                tool_output_match: None,
+                exit_code: None,
+                prompt_match: None,
+                background: None,
+                protected_paths: None,
+                expr: None,
+                any: None,
+                all: None,
+                not: None,
+                policy: None,
             },
             actions: Actions {
                 inject: None,
                 run: None,
                 block: None,
                 block_if_match: None,
+                truncate_output: None,
+                filter_results: None,
+                structural_checks: None,
+                ensure_header: None,
+                inject_command: None,
+                ask: None,
+                require_files: None,
+                allow: None,
+                inject_once_per_session: None,
+                throttle: None,
+                enrich_prompt: None,
+                rewrite: None,
             },
             mode: Some(PolicyMode::Audit),
             priority: None,
             governance: None,
+            tests: None,
+            exemptions: None,
+            escalation: None,
+            bypass_users: None,
+            messages: None,
+            stop: None,
             metadata: None,
         };
         assert_eq!(rule.effective_mode(), PolicyMode::Audit);
@@ -1012,16 +5044,49 @@ mod tests {
                 directories: None,
                 operations: None,
                 command_match: None,
+                zone: None,
+                exclude_tools: None,
+                exclude_extensions: None,
+                exclude_directories: None,
+                command_not_match: None,
+                tool_output_match: None,
+                exit_code: None,
+                prompt_match: None,
+                background: None,
+                protected_paths: None,
+                expr: None,
+                any: None,
+                all: None,
+                not: None,
+                policy: None,
             },
             actions: Actions {
                 inject: None,
                 run: None,
                 block: Some(true),
                 block_if_match: None,
+                truncate_output: None,
+                filter_results: None,
+                structural_checks: None,
+                ensure_header: None,
+                inject_command: None,
+                ask: None,
+                require_files: None,
+                allow: None,
+                inject_once_per_session: None,
+                throttle: None,
+                enrich_prompt: None,
+                rewrite: None,
             },
             mode: Some(mode),
             priority: Some(priority),
             governance: None,
+            tests: None,
+            exemptions: None,
+            escalation: None,
+            bypass_users: None,
+            messages: None,
+            stop: None,
             metadata: None,
         }
     }
@@ -0,0 +1,275 @@
+//! Filesystem change journal
+//!
+//! With `settings.change_journal` enabled, CCH hashes the target file of
+//! every Write/Edit/MultiEdit on PreToolUse (before) and PostToolUse (after)
+//! and appends the snapshots to `~/.claude/state/journal/<session>.jsonl`.
+//! `cch session diff <id>` summarizes the journal per file.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::models::{Event, EventType};
+use crate::state;
+
+/// Tools whose file changes are journaled
+const JOURNALED_TOOLS: &[&str] = &["Write", "Edit", "MultiEdit"];
+
+/// When a snapshot was taken relative to the tool call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Before,
+    After,
+}
+
+/// File state at one point in time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Hex-encoded SHA-256 of the file content
+    pub sha256: String,
+    pub bytes: u64,
+    pub lines: usize,
+}
+
+/// One journal line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalRecord {
+    pub timestamp: DateTime<Utc>,
+    pub phase: Phase,
+    pub tool: String,
+    pub path: String,
+    /// File state, or `None` when the file did not exist
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<Snapshot>,
+}
+
+/// How a file changed over a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeStatus {
+    Added,
+    Modified,
+    Deleted,
+    Unchanged,
+}
+
+impl std::fmt::Display for ChangeStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChangeStatus::Added => write!(f, "A"),
+            ChangeStatus::Modified => write!(f, "M"),
+            ChangeStatus::Deleted => write!(f, "D"),
+            ChangeStatus::Unchanged => write!(f, "="),
+        }
+    }
+}
+
+/// Net change to one file across a session
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileChange {
+    pub path: String,
+    pub status: ChangeStatus,
+    /// Number of completed tool calls that touched the file
+    pub operations: usize,
+    /// State before the first operation (`None` if absent or not journaled)
+    pub before: Option<Snapshot>,
+    /// State after the last operation (`None` if absent)
+    pub after: Option<Snapshot>,
+}
+
+/// Get the journal directory (~/.claude/state/journal)
+pub fn journal_dir() -> Result<PathBuf> {
+    Ok(state::state_dir()?.join("journal"))
+}
+
+/// Journal file for a session
+pub fn journal_path(session_id: &str) -> Result<PathBuf> {
+    Ok(journal_dir()?.join(format!("{}.jsonl", state::session_file_stem(session_id))))
+}
+
+/// Append a snapshot for a Write/Edit event; other events are ignored
+pub fn record(event: &Event) -> Result<()> {
+    let phase = match event.hook_event_name {
+        EventType::PreToolUse => Phase::Before,
+        EventType::PostToolUse => Phase::After,
+        _ => return Ok(()),
+    };
+    let Some(tool) = event
+        .tool_name
+        .as_deref()
+        .filter(|t| JOURNALED_TOOLS.contains(t))
+    else {
+        return Ok(());
+    };
+    let Some(path) = event
+        .tool_input
+        .as_ref()
+        .and_then(|input| input.get("file_path"))
+        .and_then(|p| p.as_str())
+    else {
+        return Ok(());
+    };
+
+    let resolved = match event.cwd.as_deref() {
+        Some(cwd) => Path::new(cwd).join(path),
+        None => PathBuf::from(path),
+    };
+    let record = JournalRecord {
+        timestamp: Utc::now(),
+        phase,
+        tool: tool.to_string(),
+        path: path.to_string(),
+        snapshot: snapshot(&resolved),
+    };
+
+    let journal = journal_path(&event.session_id)?;
+    if let Some(dir) = journal.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&journal)
+        .with_context(|| format!("Failed to open journal {}", journal.display()))?;
+    writeln!(file, "{}", serde_json::to_string(&record)?)?;
+    Ok(())
+}
+
+/// Hash a file's current content, or `None` if it can't be read
+fn snapshot(path: &Path) -> Option<Snapshot> {
+    let content = std::fs::read(path).ok()?;
+    Some(Snapshot {
+        sha256: format!("{:x}", Sha256::digest(&content)),
+        bytes: content.len() as u64,
+        lines: String::from_utf8_lossy(&content).lines().count(),
+    })
+}
+
+/// Read all records for a session
+pub fn read(session_id: &str) -> Result<Vec<JournalRecord>> {
+    let path = journal_path(session_id)?;
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("No change journal for session '{}'", session_id))?;
+    content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| serde_json::from_str(l).context("Malformed journal record"))
+        .collect()
+}
+
+/// Collapse a session's records into one net change per file, in the order
+/// files were first touched
+///
+/// Operations that never completed (a before with no after, e.g. because
+/// the tool call was blocked) are not counted.
+pub fn summarize(records: &[JournalRecord]) -> Vec<FileChange> {
+    let mut changes: Vec<(FileChange, bool)> = Vec::new();
+
+    for record in records {
+        let index = if let Some(index) = changes.iter().position(|(c, _)| c.path == record.path) {
+            index
+        } else {
+            changes.push((
+                FileChange {
+                    path: record.path.clone(),
+                    status: ChangeStatus::Unchanged,
+                    operations: 0,
+                    before: None,
+                    after: None,
+                },
+                false,
+            ));
+            changes.len() - 1
+        };
+        let (change, before_known) = &mut changes[index];
+
+        match record.phase {
+            Phase::Before if change.operations == 0 => {
+                change.before.clone_from(&record.snapshot);
+                *before_known = true;
+            }
+            Phase::Before => {}
+            Phase::After => {
+                change.operations += 1;
+                change.after.clone_from(&record.snapshot);
+            }
+        }
+    }
+
+    changes
+        .into_iter()
+        .filter(|(c, _)| c.operations > 0)
+        .map(|(mut change, before_known)| {
+            change.status = match (&change.before, &change.after) {
+                (None, Some(_)) if before_known => ChangeStatus::Added,
+                (_, None) => ChangeStatus::Deleted,
+                (Some(before), Some(after)) if before.sha256 == after.sha256 => {
+                    ChangeStatus::Unchanged
+                }
+                _ => ChangeStatus::Modified,
+            };
+            change
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rec(phase: Phase, path: &str, content: Option<&str>) -> JournalRecord {
+        JournalRecord {
+            timestamp: Utc::now(),
+            phase,
+            tool: "Write".to_string(),
+            path: path.to_string(),
+            snapshot: content.map(|c| Snapshot {
+                sha256: format!("{:x}", Sha256::digest(c.as_bytes())),
+                bytes: c.len() as u64,
+                lines: c.lines().count(),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_summarize_statuses() {
+        let records = vec![
+            rec(Phase::Before, "new.rs", None),
+            rec(Phase::After, "new.rs", Some("fn a() {}\n")),
+            rec(Phase::Before, "lib.rs", Some("a\n")),
+            rec(Phase::After, "lib.rs", Some("a\nb\n")),
+            rec(Phase::Before, "lib.rs", Some("a\nb\n")),
+            rec(Phase::After, "lib.rs", Some("a\nb\nc\n")),
+            rec(Phase::Before, "same.rs", Some("x\n")),
+            rec(Phase::After, "same.rs", Some("x\n")),
+            // Blocked call: no after record
+            rec(Phase::Before, "blocked.rs", Some("y\n")),
+        ];
+
+        let changes = summarize(&records);
+        let statuses: Vec<(&str, ChangeStatus, usize)> = changes
+            .iter()
+            .map(|c| (c.path.as_str(), c.status, c.operations))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("new.rs", ChangeStatus::Added, 1),
+                ("lib.rs", ChangeStatus::Modified, 2),
+                ("same.rs", ChangeStatus::Unchanged, 1),
+            ]
+        );
+        assert_eq!(changes[1].before.as_ref().unwrap().lines, 1);
+        assert_eq!(changes[1].after.as_ref().unwrap().lines, 3);
+    }
+
+    #[test]
+    fn test_summarize_without_before_is_modified() {
+        let changes = summarize(&[rec(Phase::After, "a.rs", Some("a\n"))]);
+        assert_eq!(changes[0].status, ChangeStatus::Modified);
+    }
+}
@@ -26,8 +26,27 @@
 #![allow(clippy::if_not_else)]
 #![allow(clippy::redundant_closure_for_method_calls)]
 
+pub mod agents;
+pub mod bundle;
+pub mod chaos;
 pub mod cli;
 pub mod config;
+pub mod daemon;
+pub mod encryption;
+pub mod engine;
+pub mod enrichment;
+pub mod expr;
+pub mod front_matter;
 pub mod hooks;
+pub mod journal;
 pub mod logging;
+pub mod mcp;
 pub mod models;
+pub mod pack;
+pub mod paths;
+pub mod policy;
+pub mod regex_cache;
+pub mod state;
+pub mod structural;
+pub mod validator_cache;
+pub mod wasm_validator;
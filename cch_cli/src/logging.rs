@@ -1,19 +1,27 @@
-use anyhow::Result;
+pub mod archive;
+pub mod latency;
+pub mod protocol;
+pub mod renames;
+pub mod sinks;
+
+use crate::config::LogSinkConfig;
+use crate::models::{LOG_SCHEMA_VERSION, LogEntry};
+use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
-use std::fs::{File, OpenOptions};
-use std::io::{BufWriter, Write};
+use serde_json::Value;
+use sinks::{JsonlSink, LogSink, MultiSink};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
 use std::path::PathBuf;
-use std::sync::Mutex;
 
-use crate::models::LogEntry;
-
-/// JSON Lines logger for audit trails
+/// Audit trail logger, writing each entry through a [`LogSink`]
 pub struct Logger {
-    writer: Mutex<BufWriter<File>>,
+    sink: Box<dyn LogSink>,
 }
 
 impl Logger {
     /// Create a new logger with the default log file path
+    #[allow(dead_code)]
     pub fn new() -> Result<Self> {
         let log_path = Self::default_log_path();
         Self::with_path(log_path)
@@ -22,20 +30,17 @@ impl Logger {
     /// Create a new logger with a custom log file path
     #[allow(dead_code)]
     pub fn with_path<P: Into<PathBuf>>(path: P) -> Result<Self> {
-        let path = path.into();
-
-        // Ensure parent directory exists
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-
-        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self::with_sink(Box::new(JsonlSink::open(path)?)))
+    }
 
-        let writer = BufWriter::new(file);
+    /// Create a new logger writing to `sink`
+    pub fn with_sink(sink: Box<dyn LogSink>) -> Self {
+        Self { sink }
+    }
 
-        Ok(Self {
-            writer: Mutex::new(writer),
-        })
+    /// Create a new logger for the sinks in `settings.log_sinks`
+    pub fn from_config(sinks: &[LogSinkConfig]) -> Self {
+        Self::with_sink(Box::new(MultiSink::from_config(sinks)))
     }
 
     /// Get the default log file path (~/.claude/logs/cch.log)
@@ -47,13 +52,9 @@ impl Logger {
         path
     }
 
-    /// Log an entry to the JSON Lines file
+    /// Log an entry to the logger's sinks
     pub fn log(&self, entry: LogEntry) -> Result<()> {
-        let json = serde_json::to_string(&entry)?;
-        let mut writer = self.writer.lock().unwrap();
-        writeln!(writer, "{}", json)?;
-        writer.flush()?;
-        Ok(())
+        self.sink.write(&entry)
     }
 
     /// Log an entry asynchronously
@@ -65,48 +66,60 @@ impl Logger {
 }
 
 /// Query logs with filtering and pagination
+///
+/// Entries are reported under the current names of renamed rules (see
+/// [`renames`]). When a query reaches back past the oldest entry of the
+/// active file, rotated segments beside it (`cch.log.1`, `cch.log.2.gz`,
+/// `cch.log.3.zst`, ...) are read too, newest first, decompressing gzip
+/// and zstd segments in-process.
 pub struct LogQuery {
     log_path: PathBuf,
+    renames: renames::Renames,
 }
 
 impl LogQuery {
     /// Create a new log query for the default log file
     pub fn new() -> Self {
-        Self {
-            log_path: Logger::default_log_path(),
-        }
+        Self::with_path(Logger::default_log_path())
     }
 
     /// Create a new log query for a custom log file
     #[allow(dead_code)]
     pub fn with_path<P: Into<PathBuf>>(path: P) -> Self {
-        Self {
-            log_path: path.into(),
-        }
+        let log_path = path.into();
+        let renames = renames::load(&renames::renames_path(&log_path)).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring rule renames: {:#}", e);
+            renames::Renames::new()
+        });
+        Self { log_path, renames }
     }
 
     /// Query logs with optional filters
     pub fn query(&self, filters: QueryFilters) -> Result<Vec<LogEntry>> {
-        if !self.log_path.exists() {
-            return Ok(Vec::new());
-        }
-
-        let content = std::fs::read_to_string(&self.log_path)?;
         let mut entries = Vec::new();
+        let mut oldest = match std::fs::read_to_string(&self.log_path) {
+            Ok(content) => self.collect(&content, &filters, &mut entries),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
 
-        for line in content.lines() {
-            if line.trim().is_empty() {
-                continue;
+        for segment in self.segments() {
+            // Segments only hold entries older than what was already read
+            if let (Some(since), Some(oldest)) = (filters.since, oldest) {
+                if oldest <= since {
+                    break;
+                }
             }
-
-            let entry: LogEntry = serde_json::from_str(line)?;
-            if self.matches_filters(&entry, &filters) {
-                entries.push(entry);
+            match read_segment(&segment) {
+                Ok(content) => {
+                    oldest = self.collect(&content, &filters, &mut entries).or(oldest);
+                }
+                Err(e) => tracing::warn!("Skipping log segment {}: {:#}", segment.display(), e),
             }
         }
 
         // Sort by timestamp (newest first)
-        entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        entries.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
 
         // Apply limit
         if let Some(limit) = filters.limit {
@@ -116,6 +129,108 @@ impl LogQuery {
         Ok(entries)
     }
 
+    /// Parse `content` into `entries`, keeping those matching `filters`;
+    /// returns the oldest timestamp among all of its entries
+    fn collect(
+        &self,
+        content: &str,
+        filters: &QueryFilters,
+        entries: &mut Vec<LogEntry>,
+    ) -> Option<DateTime<Utc>> {
+        let mut oldest: Option<DateTime<Utc>> = None;
+        for line in content.lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let mut entry = match parse_log_line(line) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    tracing::warn!("Skipping unreadable log entry: {}", e);
+                    continue;
+                }
+            };
+            oldest = Some(oldest.map_or(entry.timestamp, |t| t.min(entry.timestamp)));
+            renames::apply(&self.renames, &mut entry);
+            if self.matches_filters(&entry, filters) {
+                entries.push(entry);
+            }
+        }
+        oldest
+    }
+
+    /// Rotated segments of the log, newest (lowest number) first
+    fn segments(&self) -> Vec<PathBuf> {
+        let (Some(dir), Some(name)) = (
+            self.log_path.parent(),
+            self.log_path.file_name().and_then(|n| n.to_str()),
+        ) else {
+            return Vec::new();
+        };
+        let Ok(read_dir) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut segments: Vec<(u32, PathBuf)> = read_dir
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let file_name = path.file_name()?.to_str()?;
+                let suffix = file_name.strip_prefix(name)?.strip_prefix('.')?;
+                let number = suffix.split('.').next()?.parse().ok()?;
+                let extension = &suffix[suffix.find('.').unwrap_or(suffix.len())..];
+                matches!(extension, "" | ".gz" | ".zst").then_some((number, path))
+            })
+            .collect();
+        segments.sort();
+        segments.into_iter().map(|(_, path)| path).collect()
+    }
+
+    /// Size of the log file, where following starts
+    pub fn end_offset(&self) -> u64 {
+        std::fs::metadata(&self.log_path).map_or(0, |m| m.len())
+    }
+
+    /// Read entries appended since `offset`, oldest first, returning them
+    /// with the offset to continue from
+    ///
+    /// A partially written last line is left for the next read, and a log
+    /// that shrank (was rotated or cleaned) is read again from the start.
+    pub fn read_new(&self, offset: u64, filters: &QueryFilters) -> Result<(Vec<LogEntry>, u64)> {
+        let mut file = match File::open(&self.log_path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((Vec::new(), 0)),
+            Err(e) => return Err(e.into()),
+        };
+        let offset = if file.metadata()?.len() < offset {
+            0
+        } else {
+            offset
+        };
+        file.seek(SeekFrom::Start(offset))?;
+        let mut appended = Vec::new();
+        file.read_to_end(&mut appended)?;
+
+        let complete = appended
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |i| i + 1);
+        let mut entries = Vec::new();
+        for line in String::from_utf8_lossy(&appended[..complete]).lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match parse_log_line(line) {
+                Ok(mut entry) => {
+                    renames::apply(&self.renames, &mut entry);
+                    if self.matches_filters(&entry, filters) {
+                        entries.push(entry);
+                    }
+                }
+                Err(e) => tracing::warn!("Skipping unreadable log entry: {}", e),
+            }
+        }
+        Ok((entries, offset + complete as u64))
+    }
+
     /// Check if a log entry matches the given filters
     fn matches_filters(&self, entry: &LogEntry, filters: &QueryFilters) -> bool {
         // Filter by session ID
@@ -177,6 +292,140 @@ impl LogQuery {
     }
 }
 
+/// Longest string kept verbatim in logged event payloads
+pub const MAX_LOGGED_STRING_BYTES: usize = 4096;
+
+/// Shorten long strings anywhere in a JSON value for logging
+///
+/// Keeps megabyte-sized Write content out of `raw_event` while leaving the
+/// shape of the event intact.
+pub fn truncate_for_log(value: &mut Value, limit: usize) {
+    match value {
+        Value::String(s) if s.len() > limit => {
+            let cut = (0..=limit)
+                .rev()
+                .find(|&i| s.is_char_boundary(i))
+                .unwrap_or(0);
+            *s = format!("{}...[truncated {} bytes]", &s[..cut], s.len() - cut);
+        }
+        Value::Array(items) => {
+            for item in items {
+                truncate_for_log(item, limit);
+            }
+        }
+        Value::Object(map) => {
+            for item in map.values_mut() {
+                truncate_for_log(item, limit);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Optional enrichment fields that can be dropped from an entry whose
+/// contents no longer deserialize (e.g. a since-renamed enum variant)
+const OPTIONAL_LOG_FIELDS: &[&str] = &[
+    "metadata",
+    "event_details",
+    "response",
+    "raw_event",
+    "rule_evaluations",
+    "mode",
+    "priority",
+    "decision",
+    "governance",
+    "trust_level",
+    "exemptions",
+    "validators",
+];
+
+/// Parse one JSON Lines log record, upgrading older schema versions
+///
+/// Entries that still fail after upgrading have their optional enrichment
+/// fields stripped one by one, so the core record stays queryable.
+pub fn parse_log_line(line: &str) -> Result<LogEntry> {
+    let mut value: Value = serde_json::from_str(line).context("Log line is not valid JSON")?;
+
+    let version = value
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .unwrap_or(1);
+    if version < 2 {
+        upgrade_v1(&mut value);
+    }
+
+    let mut result = serde_json::from_value::<LogEntry>(value.clone());
+    if result.is_err() {
+        if let Some(object) = value.as_object_mut() {
+            for field in OPTIONAL_LOG_FIELDS {
+                if object.remove(*field).is_some() {
+                    result = serde_json::from_value(Value::Object(object.clone()));
+                    if result.is_ok() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut entry = result.context("Log entry does not match any known schema")?;
+    entry.schema_version = LOG_SCHEMA_VERSION;
+    Ok(entry)
+}
+
+/// Upgrade a version 1 (unversioned) entry in place
+///
+/// Early builds wrote capitalized or `continue` outcomes, `rules_matched`
+/// as objects, and `timing.total_ms` instead of `timing.processing_ms`.
+fn upgrade_v1(value: &mut Value) {
+    let Some(object) = value.as_object_mut() else {
+        return;
+    };
+
+    let context_injected = object
+        .get("context_injected")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    if let Some(outcome) = object.get("outcome").and_then(Value::as_str) {
+        let outcome = match outcome.to_lowercase().as_str() {
+            "continue" if context_injected => "inject".to_string(),
+            "continue" => "allow".to_string(),
+            other => other.to_string(),
+        };
+        object.insert("outcome".to_string(), Value::String(outcome));
+    }
+
+    let rules_matched: Vec<Value> = match object.get("rules_matched") {
+        Some(Value::Array(rules)) => rules
+            .iter()
+            .filter_map(|r| match r {
+                Value::String(_) => Some(r.clone()),
+                Value::Object(o) => o.get("name").cloned(),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    object.insert("rules_matched".to_string(), Value::Array(rules_matched));
+
+    let rules_evaluated = object
+        .get("rules_evaluated")
+        .and_then(Value::as_u64)
+        .unwrap_or(0);
+    let timing = object
+        .entry("timing")
+        .or_insert_with(|| Value::Object(serde_json::Map::new()));
+    if let Some(timing) = timing.as_object_mut() {
+        if !timing.contains_key("processing_ms") {
+            let total = timing.get("total_ms").cloned().unwrap_or(Value::from(0));
+            timing.insert("processing_ms".to_string(), total);
+        }
+        timing
+            .entry("rules_evaluated")
+            .or_insert(Value::from(rules_evaluated));
+    }
+}
+
 /// Filters for log queries
 #[derive(Debug, Clone, Default)]
 pub struct QueryFilters {
@@ -213,9 +462,9 @@ use std::sync::OnceLock;
 /// Global logger instance using OnceLock for safe initialization
 static GLOBAL_LOGGER: OnceLock<Logger> = OnceLock::new();
 
-/// Initialize the global logger
-pub fn init_global_logger() -> Result<()> {
-    let logger = Logger::new()?;
+/// Initialize the global logger with the sinks in `settings.log_sinks`
+pub fn init_global_logger(sinks: &[LogSinkConfig]) -> Result<()> {
+    let logger = Logger::from_config(sinks);
     GLOBAL_LOGGER
         .set(logger)
         .map_err(|_| anyhow::anyhow!("Logger already initialized"))?;
@@ -235,6 +484,21 @@ pub async fn log_entry(entry: LogEntry) -> Result<()> {
     Ok(())
 }
 
+/// Contents of a rotated log segment, decompressed if needed
+fn read_segment(path: &std::path::Path) -> Result<String> {
+    let file = File::open(path)?;
+    let mut reader: Box<dyn Read> = match path.extension().and_then(|e| e.to_str()) {
+        Some("gz") => Box::new(flate2::read::MultiGzDecoder::new(file)),
+        Some("zst") => Box::new(zstd::stream::read::Decoder::new(file)?),
+        _ => Box::new(file),
+    };
+    let mut bytes = Vec::new();
+    reader
+        .read_to_end(&mut bytes)
+        .context("Failed to decompress segment")?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
 /// Rotate log files when they exceed a certain size
 #[allow(dead_code)]
 pub struct LogRotator {
@@ -293,7 +557,8 @@ impl Default for LogRotator {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{LogMetadata, LogTiming, Outcome};
+    use crate::models::{LOG_SCHEMA_VERSION, LogMetadata, LogTiming, Outcome, RuleCounts};
+    use std::io::Write;
     use tempfile::NamedTempFile;
 
     #[tokio::test]
@@ -302,6 +567,7 @@ mod tests {
         let logger = Logger::with_path(temp_file.path()).unwrap();
 
         let entry = LogEntry {
+            schema_version: LOG_SCHEMA_VERSION,
             timestamp: Utc::now(),
             event_type: "PreToolUse".to_string(),
             session_id: "test-session".to_string(),
@@ -311,10 +577,12 @@ mod tests {
             timing: LogTiming {
                 processing_ms: 5,
                 rules_evaluated: 3,
+                counts: RuleCounts::default(),
             },
             metadata: Some(LogMetadata {
                 injected_files: None,
                 validator_output: Some("blocked by policy".to_string()),
+                suppressed_injections: None,
             }),
             // Enhanced logging fields (CRD-001)
             event_details: None,
@@ -327,6 +595,13 @@ mod tests {
             decision: None,
             governance: None,
             trust_level: None,
+            exemptions: None,
+            validators: None,
+            repeat_count: None,
+            allow_override: None,
+            post_processor: None,
+            dry_run: false,
+            invalid_patterns: None,
         };
 
         logger.log_async(entry.clone()).await.unwrap();
@@ -343,6 +618,129 @@ mod tests {
         assert_eq!(entries[0].session_id, "test-session");
     }
 
+    const HISTORICAL_LOG: &str = include_str!("../tests/fixtures/logs/historical.jsonl");
+
+    #[test]
+    fn test_truncate_for_log() {
+        let mut value = serde_json::json!({
+            "tool_input": {"content": "é".repeat(10), "file_path": "a.rs"},
+            "edits": [{"new_string": "abcdefgh"}]
+        });
+        truncate_for_log(&mut value, 5);
+        assert_eq!(value["tool_input"]["content"], "éé...[truncated 16 bytes]");
+        assert_eq!(value["tool_input"]["file_path"], "a.rs");
+        assert_eq!(
+            value["edits"][0]["new_string"],
+            "abcde...[truncated 3 bytes]"
+        );
+    }
+
+    #[test]
+    fn test_parse_historical_log_lines() {
+        let lines: Vec<&str> = HISTORICAL_LOG.lines().collect();
+
+        let basic = parse_log_line(lines[0]).unwrap();
+        assert_eq!(basic.outcome, Outcome::Allow);
+        assert_eq!(basic.schema_version, LOG_SCHEMA_VERSION);
+
+        let prd = parse_log_line(lines[1]).unwrap();
+        assert_eq!(prd.outcome, Outcome::Inject);
+        assert_eq!(
+            prd.rules_matched,
+            vec!["git-commit-guidelines", "block-wip"]
+        );
+        assert_eq!(prd.timing.processing_ms, 3);
+        assert_eq!(prd.timing.rules_evaluated, 12);
+
+        let enhanced = parse_log_line(lines[2]).unwrap();
+        assert_eq!(enhanced.decision, Some(crate::models::Decision::Blocked));
+
+        // Unknown enum values in optional fields are dropped, not fatal
+        let unknown = parse_log_line(lines[3]).unwrap();
+        assert_eq!(unknown.session_id, "v1-unknown-decision");
+        assert!(unknown.decision.is_none());
+
+        let current = parse_log_line(lines[4]).unwrap();
+        assert_eq!(current.exemptions.unwrap()[0].rule, "guard-secrets");
+
+        assert!(parse_log_line(lines[5]).is_err());
+    }
+
+    #[test]
+    fn test_query_skips_unreadable_lines() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(HISTORICAL_LOG.as_bytes()).unwrap();
+
+        let query = LogQuery::with_path(temp_file.path());
+        let entries = query.query(QueryFilters::default()).unwrap();
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries[0].session_id, "v2-current");
+    }
+
+    #[test]
+    fn test_query_reads_rotated_and_compressed_segments() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("cch.log");
+        let line = |session: &str, days_ago: i64| {
+            format!(
+                r#"{{"timestamp":"{}","event_type":"PreToolUse","session_id":"{}","rules_matched":[],"outcome":"allow","timing":{{"processing_ms":1,"rules_evaluated":0}}}}"#,
+                (Utc::now() - chrono::Duration::days(days_ago)).to_rfc3339(),
+                session
+            )
+        };
+        std::fs::write(&log_path, line("active", 0)).unwrap();
+        std::fs::write(dir.path().join("cch.log.1"), line("plain", 10)).unwrap();
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(line("gzip", 40).as_bytes()).unwrap();
+        std::fs::write(dir.path().join("cch.log.2.gz"), gzip.finish().unwrap()).unwrap();
+        let zstd = zstd::encode_all(line("zstd", 90).as_bytes(), 0).unwrap();
+        std::fs::write(dir.path().join("cch.log.3.zst"), zstd).unwrap();
+        std::fs::write(dir.path().join("cch.log.old"), line("ignored", 1)).unwrap();
+
+        let query = LogQuery::with_path(&log_path);
+        let sessions = |since_days: Option<i64>| -> Vec<String> {
+            let filters = QueryFilters {
+                since: since_days.map(|d| Utc::now() - chrono::Duration::days(d)),
+                ..Default::default()
+            };
+            let entries = query.query(filters).unwrap();
+            entries.into_iter().map(|e| e.session_id).collect()
+        };
+        assert_eq!(sessions(Some(5)), ["active"]);
+        assert_eq!(sessions(Some(60)), ["active", "plain", "gzip"]);
+        assert_eq!(sessions(None), ["active", "plain", "gzip", "zstd"]);
+    }
+
+    #[test]
+    fn test_read_new_follows_appended_lines() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        let lines: Vec<&str> = HISTORICAL_LOG.lines().collect();
+        let query = LogQuery::with_path(temp_file.path());
+        let filters = QueryFilters::default();
+
+        writeln!(temp_file, "{}", lines[0]).unwrap();
+        let (entries, offset) = query.read_new(0, &filters).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(offset, query.end_offset());
+
+        // A line still being written waits for its newline
+        write!(temp_file, "{}", lines[4]).unwrap();
+        let (entries, next) = query.read_new(offset, &filters).unwrap();
+        assert!(entries.is_empty());
+        assert_eq!(next, offset);
+        writeln!(temp_file).unwrap();
+        let (entries, _) = query.read_new(next, &filters).unwrap();
+        assert_eq!(entries[0].session_id, "v2-current");
+
+        // Filters apply, and a shrunken log is reread from the start
+        let filters = QueryFilters {
+            session_id: Some("v2-current".to_string()),
+            ..Default::default()
+        };
+        let (entries, _) = query.read_new(u64::MAX, &filters).unwrap();
+        assert_eq!(entries.len(), 1);
+    }
+
     #[test]
     fn test_log_filtering() {
         let temp_file = NamedTempFile::new().unwrap();
@@ -0,0 +1,189 @@
+//! Daily latency histograms
+//!
+//! Every processed event adds its `processing_ms` to a small histogram for
+//! the day under `~/.claude/logs/latency/YYYY-MM-DD.json`, so
+//! `cch logs stats --latency` can report percentiles over weeks of activity
+//! without parsing the JSON Lines log.
+//!
+//! Buckets are log-linear, as in HDR histograms: values below 32 ms are
+//! exact, and every power of two above is split into 16 buckets, so a
+//! reported percentile is at most ~6% above the true value.
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::Logger;
+
+/// Buckets per power of two
+const SUB_BUCKETS: u64 = 16;
+
+/// Latency counts for one day
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Histogram {
+    /// Number of recorded values
+    pub count: u64,
+
+    /// Largest recorded value, in milliseconds
+    pub max: u64,
+
+    /// Counts keyed by bucket index
+    pub buckets: BTreeMap<u64, u64>,
+}
+
+/// Bucket holding `value`
+fn bucket_index(value: u64) -> u64 {
+    let shift = u64::from(value.checked_ilog2().unwrap_or(0)).saturating_sub(4);
+    SUB_BUCKETS * shift + (value >> shift)
+}
+
+/// Largest value that falls into bucket `index`
+fn bucket_high(index: u64) -> u64 {
+    let shift = (index / SUB_BUCKETS).saturating_sub(1);
+    let mantissa = index - SUB_BUCKETS * shift;
+    ((mantissa + 1) << shift) - 1
+}
+
+impl Histogram {
+    /// Add one value
+    pub fn record(&mut self, value_ms: u64) {
+        self.count += 1;
+        self.max = self.max.max(value_ms);
+        *self.buckets.entry(bucket_index(value_ms)).or_default() += 1;
+    }
+
+    /// Add all values of another histogram
+    pub fn merge(&mut self, other: &Histogram) {
+        self.count += other.count;
+        self.max = self.max.max(other.max);
+        for (&index, &count) in &other.buckets {
+            *self.buckets.entry(index).or_default() += count;
+        }
+    }
+
+    /// Nearest-rank percentile, reported as the top of its bucket
+    pub fn percentile(&self, pct: u64) -> u64 {
+        let rank = (pct * self.count).div_ceil(100).max(1);
+        let mut seen = 0;
+        for (&index, &count) in &self.buckets {
+            seen += count;
+            if seen >= rank {
+                return bucket_high(index).min(self.max);
+            }
+        }
+        self.max
+    }
+}
+
+/// Get the histogram directory (~/.claude/logs/latency)
+pub fn latency_dir() -> PathBuf {
+    Logger::default_log_path().with_file_name("latency")
+}
+
+/// Record one event's processing time in the day's histogram
+///
+/// Concurrent hook processes can race on the read-modify-write and lose
+/// an increment; the file itself is always replaced atomically.
+pub fn record(dir: &Path, day: NaiveDate, processing_ms: u64) -> Result<()> {
+    let path = dir.join(format!("{}.json", day));
+    let mut histogram = load(&path)?;
+    histogram.record(processing_ms);
+
+    std::fs::create_dir_all(dir)?;
+    let tmp = path.with_extension(format!("json.{}.tmp", std::process::id()));
+    std::fs::write(&tmp, serde_json::to_string(&histogram)?)?;
+    std::fs::rename(&tmp, &path).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn load(path: &Path) -> Result<Histogram> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content)
+            .with_context(|| format!("Malformed latency histogram {}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Histogram::default()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+/// Load every day's histogram from `since` on, oldest first
+pub fn load_days(dir: &Path, since: Option<NaiveDate>) -> Result<Vec<(NaiveDate, Histogram)>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", dir.display())),
+    };
+
+    let mut days = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let day = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(".json"))
+            .and_then(|stem| stem.parse::<NaiveDate>().ok());
+        if let Some(day) = day.filter(|day| since.is_none_or(|since| *day >= since)) {
+            days.push((day, load(&path)?));
+        }
+    }
+    days.sort_by_key(|(day, _)| *day);
+    Ok(days)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buckets_are_exact_below_32ms_and_bounded_above() {
+        for value in 0..32 {
+            assert_eq!(bucket_high(bucket_index(value)), value);
+        }
+        for value in [32, 33, 100, 1_000, 65_535, 1_000_000] {
+            let high = bucket_high(bucket_index(value));
+            assert!(
+                high >= value && high - value <= value / 16,
+                "{value} -> {high}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let mut histogram = Histogram::default();
+        for value in 1..=100 {
+            histogram.record(value);
+        }
+        assert_eq!(histogram.percentile(50), 51);
+        assert_eq!(histogram.percentile(99), 99);
+        assert_eq!(histogram.percentile(100), 100);
+
+        let mut merged = Histogram::default();
+        merged.merge(&histogram);
+        merged.record(5_000);
+        assert_eq!(merged.count, 101);
+        assert_eq!(merged.percentile(100), 5_000);
+    }
+
+    #[test]
+    fn test_record_and_load_days() {
+        let dir = tempfile::tempdir().unwrap();
+        let day = |d: &str| d.parse::<NaiveDate>().unwrap();
+        record(dir.path(), day("2026-01-01"), 3).unwrap();
+        record(dir.path(), day("2026-01-02"), 7).unwrap();
+        record(dir.path(), day("2026-01-02"), 9).unwrap();
+
+        let days = load_days(dir.path(), None).unwrap();
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[1].1.count, 2);
+        assert_eq!(days[1].1.max, 9);
+
+        let recent = load_days(dir.path(), Some(day("2026-01-02"))).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert!(
+            load_days(&dir.path().join("missing"), None)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
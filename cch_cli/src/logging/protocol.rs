@@ -0,0 +1,127 @@
+//! Protocol variant counters
+//!
+//! Events are accepted in several historical spellings (`event_type` for
+//! `hook_event_name`, `filePath` for `file_path`, ...), all normalized when
+//! the event is parsed. Every event also counts the spellings it used in
+//! `~/.claude/logs/protocol.json`, so `cch doctor --protocol` can show which
+//! variants are still sent before their handling is removed.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::Logger;
+use crate::models::field_spellings;
+
+/// How often one spelling of a field was seen
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Spelling {
+    pub count: u64,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+/// Spelling counts keyed by canonical field, then by spelling
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProtocolStats {
+    /// Events counted
+    pub events: u64,
+
+    #[serde(default)]
+    pub fields: BTreeMap<String, BTreeMap<String, Spelling>>,
+}
+
+impl ProtocolStats {
+    /// Count one event that used `spellings`
+    pub fn record(&mut self, spellings: &[(String, String)], now: DateTime<Utc>) {
+        self.events += 1;
+        for (field, spelling) in spellings {
+            self.fields
+                .entry(field.clone())
+                .or_default()
+                .entry(spelling.clone())
+                .and_modify(|seen| {
+                    seen.count += 1;
+                    seen.last_seen = now;
+                })
+                .or_insert(Spelling {
+                    count: 1,
+                    first_seen: now,
+                    last_seen: now,
+                });
+        }
+    }
+}
+
+/// Get the counters file (~/.claude/logs/protocol.json)
+pub fn protocol_path() -> PathBuf {
+    Logger::default_log_path().with_file_name("protocol.json")
+}
+
+/// Count one event's spellings in the counters file
+///
+/// Like the latency histograms, concurrent hook processes can race on the
+/// read-modify-write and lose an increment.
+pub fn record(path: &Path, spellings: &[(String, String)]) -> Result<()> {
+    let mut stats = load(path)?;
+    stats.record(spellings, Utc::now());
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension(format!("json.{}.tmp", std::process::id()));
+    std::fs::write(&tmp, serde_json::to_string(&stats)?)?;
+    std::fs::rename(&tmp, path).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Count the spellings a raw event used, warning when the counters can't
+/// be written
+pub fn observe(raw: &serde_json::Value) {
+    if let Err(e) = record(&protocol_path(), &field_spellings(raw)) {
+        tracing::warn!("Failed to record protocol counters: {}", e);
+    }
+}
+
+/// Load the counters, empty when none were recorded yet
+pub fn load(path: &Path) -> Result<ProtocolStats> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content)
+            .with_context(|| format!("Malformed protocol counters {}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(ProtocolStats::default()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_counts_spellings() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("protocol.json");
+        let spellings = |spellings: &[(&str, &str)]| -> Vec<(String, String)> {
+            spellings
+                .iter()
+                .map(|(field, spelling)| (field.to_string(), spelling.to_string()))
+                .collect()
+        };
+
+        record(&path, &spellings(&[("hook_event_name", "hook_event_name")])).unwrap();
+        record(&path, &spellings(&[("hook_event_name", "event_type")])).unwrap();
+        record(&path, &spellings(&[("hook_event_name", "hook_event_name")])).unwrap();
+
+        let stats = load(&path).unwrap();
+        assert_eq!(stats.events, 3);
+        let field = &stats.fields["hook_event_name"];
+        assert_eq!(field["hook_event_name"].count, 2);
+        assert_eq!(field["event_type"].count, 1);
+        assert!(field["hook_event_name"].last_seen >= field["event_type"].last_seen);
+        assert_eq!(
+            load(&dir.path().join("missing.json")).unwrap(),
+            ProtocolStats::default()
+        );
+    }
+}
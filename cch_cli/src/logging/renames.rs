@@ -0,0 +1,93 @@
+//! Rule renames applied to the audit log
+//!
+//! `cch rule rename --keep-history` records `old -> new` in `renames.json`
+//! next to the log. Log queries attribute entries recorded under an old
+//! name to the rule's current name, so activity statistics, coverage, and
+//! comparisons survive the rename without rewriting the log.
+
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::models::LogEntry;
+
+/// Current rule name keyed by former name
+pub type Renames = BTreeMap<String, String>;
+
+/// Get the rename mapping path for a log file (renames.json beside it)
+pub fn renames_path(log_path: &Path) -> PathBuf {
+    log_path.with_file_name("renames.json")
+}
+
+/// Load the rename mapping; a missing file means no renames
+pub fn load(path: &Path) -> Result<Renames> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content)
+            .with_context(|| format!("Malformed rule renames {}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Renames::new()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+/// Record that rule `old` is now called `new`
+///
+/// Earlier renames to `old` are pointed at `new` as well, so a chain of
+/// renames resolves in one lookup.
+pub fn record(path: &Path, old: &str, new: &str) -> Result<()> {
+    let mut renames = load(path)?;
+    for current in renames.values_mut() {
+        if current == old {
+            *current = new.to_string();
+        }
+    }
+    renames.insert(old.to_string(), new.to_string());
+    renames.retain(|former, current| former != current);
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(&renames)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Attribute an entry's rules to their current names
+pub fn apply(renames: &Renames, entry: &mut LogEntry) {
+    if renames.is_empty() {
+        return;
+    }
+    for name in &mut entry.rules_matched {
+        if let Some(current) = renames.get(name) {
+            name.clone_from(current);
+        }
+    }
+    for evaluation in entry.rule_evaluations.iter_mut().flatten() {
+        if let Some(current) = renames.get(&evaluation.rule_name) {
+            evaluation.rule_name.clone_from(current);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_resolves_chains() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = renames_path(&dir.path().join("cch.log"));
+        assert!(load(&path).unwrap().is_empty());
+
+        record(&path, "no-force", "no-force-push").unwrap();
+        record(&path, "no-force-push", "block-force-push").unwrap();
+        record(&path, "block-force-push", "no-force").unwrap();
+
+        let renames = load(&path).unwrap();
+        assert_eq!(
+            renames,
+            Renames::from([
+                ("block-force-push".to_string(), "no-force".to_string()),
+                ("no-force-push".to_string(), "no-force".to_string()),
+            ])
+        );
+    }
+}
@@ -0,0 +1,413 @@
+//! Destinations for audit log entries
+//!
+//! The logger writes every entry through one [`LogSink`]. [`MultiSink`]
+//! fans entries out to each sink listed in `settings.log_sinks`; a sink that
+//! fails (full disk, unreachable collector) is reported and skipped without
+//! keeping the entry from the others. With no sinks configured, entries go
+//! to the JSON Lines file that `cch logs` and `cch explain` read.
+
+use anyhow::{Context, Result};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::net::{TcpStream, ToSocketAddrs, UdpSocket};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::Logger;
+use crate::config::LogSinkConfig;
+use crate::models::{LogEntry, Outcome};
+
+/// Syslog socket used when a syslog sink gives no address
+pub const DEFAULT_SYSLOG_ADDRESS: &str = "/dev/log";
+
+/// A destination for audit log entries
+pub trait LogSink: Send + Sync {
+    /// Short description of the destination for error messages
+    fn name(&self) -> String;
+
+    /// Write one entry
+    fn write(&self, entry: &LogEntry) -> Result<()>;
+}
+
+/// Open the sink `config` describes
+pub fn open(config: &LogSinkConfig) -> Result<Box<dyn LogSink>> {
+    Ok(match config {
+        LogSinkConfig::Jsonl { path } => Box::new(JsonlSink::open(
+            path.clone().unwrap_or_else(Logger::default_log_path),
+        )?),
+        LogSinkConfig::Syslog { address } => Box::new(SyslogSink::connect(
+            address.as_deref().unwrap_or(DEFAULT_SYSLOG_ADDRESS),
+        )?),
+        LogSinkConfig::Webhook { url, timeout_ms } => {
+            Box::new(WebhookSink::new(url, Duration::from_millis(*timeout_ms))?)
+        }
+    })
+}
+
+/// Appends entries to a JSON Lines file
+pub struct JsonlSink {
+    path: PathBuf,
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl JsonlSink {
+    /// Open `path` for appending, creating it and its directory if needed
+    pub fn open<P: Into<PathBuf>>(path: P) -> Result<Self> {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self {
+            path,
+            writer: Mutex::new(BufWriter::new(file)),
+        })
+    }
+}
+
+impl LogSink for JsonlSink {
+    fn name(&self) -> String {
+        format!("jsonl {}", self.path.display())
+    }
+
+    fn write(&self, entry: &LogEntry) -> Result<()> {
+        let json = serde_json::to_string(entry)?;
+        let mut writer = self
+            .writer
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        writeln!(writer, "{}", json)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Sends entries to syslog as RFC 5424 messages whose text is the entry's
+/// JSON
+///
+/// An address starting with `/` is a local datagram socket; anything else
+/// is a `host:port` reached over UDP.
+pub struct SyslogSink {
+    address: String,
+    socket: SyslogSocket,
+}
+
+enum SyslogSocket {
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixDatagram),
+    Udp(UdpSocket),
+}
+
+impl SyslogSink {
+    /// Connect to the syslog socket or server at `address`
+    pub fn connect(address: &str) -> Result<Self> {
+        let socket = if address.starts_with('/') {
+            #[cfg(unix)]
+            {
+                let socket = std::os::unix::net::UnixDatagram::unbound()?;
+                socket.connect(address)?;
+                SyslogSocket::Unix(socket)
+            }
+            #[cfg(not(unix))]
+            anyhow::bail!("syslog sockets are only supported on Unix; give a host:port");
+        } else {
+            let server = address
+                .to_socket_addrs()?
+                .next()
+                .with_context(|| format!("{} resolves to no address", address))?;
+            let socket = UdpSocket::bind(if server.is_ipv4() {
+                "0.0.0.0:0"
+            } else {
+                "[::]:0"
+            })?;
+            socket.connect(server)?;
+            SyslogSocket::Udp(socket)
+        };
+        Ok(Self {
+            address: address.to_string(),
+            socket,
+        })
+    }
+
+    /// The syslog message for `entry`: facility user, severity warning for
+    /// blocks and info otherwise
+    fn format(entry: &LogEntry) -> Result<String> {
+        let severity = match entry.outcome {
+            Outcome::Block => 4,
+            Outcome::Allow | Outcome::Inject => 6,
+        };
+        Ok(format!(
+            "<{}>1 {} - cch {} {} - {}",
+            8 + severity,
+            entry
+                .timestamp
+                .to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            std::process::id(),
+            entry.event_type,
+            serde_json::to_string(entry)?
+        ))
+    }
+}
+
+impl LogSink for SyslogSink {
+    fn name(&self) -> String {
+        format!("syslog {}", self.address)
+    }
+
+    fn write(&self, entry: &LogEntry) -> Result<()> {
+        let message = Self::format(entry)?;
+        match &self.socket {
+            #[cfg(unix)]
+            SyslogSocket::Unix(socket) => socket.send(message.as_bytes())?,
+            SyslogSocket::Udp(socket) => socket.send(message.as_bytes())?,
+        };
+        Ok(())
+    }
+}
+
+/// POSTs each entry as JSON to an `http://` endpoint, expecting a 2xx reply
+///
+/// Each entry is sent on its own connection, bounded by the timeout, so a
+/// slow collector delays a hook by at most that long.
+pub struct WebhookSink {
+    url: String,
+    authority: String,
+    host: String,
+    port: u16,
+    path: String,
+    timeout: Duration,
+}
+
+impl WebhookSink {
+    /// Parse `url`, which must be `http://host[:port][/path]`
+    pub fn new(url: &str, timeout: Duration) -> Result<Self> {
+        let rest = url
+            .strip_prefix("http://")
+            .with_context(|| format!("Webhook URL must start with http://: {}", url))?;
+        let (authority, path) = match rest.find('/') {
+            Some(i) => rest.split_at(i),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') => (
+                host,
+                port.parse()
+                    .with_context(|| format!("Invalid port in webhook URL: {}", url))?,
+            ),
+            _ => (authority, 80),
+        };
+        if host.is_empty() {
+            anyhow::bail!("Webhook URL has no host: {}", url);
+        }
+        Ok(Self {
+            url: url.to_string(),
+            authority: authority.to_string(),
+            host: host.trim_matches(['[', ']']).to_string(),
+            port,
+            path: path.to_string(),
+            timeout,
+        })
+    }
+}
+
+impl LogSink for WebhookSink {
+    fn name(&self) -> String {
+        format!("webhook {}", self.url)
+    }
+
+    fn write(&self, entry: &LogEntry) -> Result<()> {
+        let body = serde_json::to_vec(entry)?;
+        let server = (self.host.as_str(), self.port)
+            .to_socket_addrs()?
+            .next()
+            .with_context(|| format!("{} resolves to no address", self.host))?;
+        let mut stream = TcpStream::connect_timeout(&server, self.timeout)?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+
+        write!(
+            stream,
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            self.path,
+            self.authority,
+            body.len()
+        )?;
+        stream.write_all(&body)?;
+
+        let mut status_line = String::new();
+        BufReader::new(&stream).read_line(&mut status_line)?;
+        let status: u16 = status_line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|code| code.parse().ok())
+            .with_context(|| format!("Malformed HTTP response: {:?}", status_line.trim()))?;
+        if !(200..300).contains(&status) {
+            anyhow::bail!("Webhook answered HTTP {}", status);
+        }
+        Ok(())
+    }
+}
+
+/// Writes each entry to every sink it holds
+///
+/// A failing sink is logged with `tracing::warn` and doesn't stop the rest;
+/// `write` only fails when no sink accepted the entry.
+pub struct MultiSink {
+    sinks: Vec<Box<dyn LogSink>>,
+}
+
+impl MultiSink {
+    /// Fan out to `sinks`
+    pub fn new(sinks: Vec<Box<dyn LogSink>>) -> Self {
+        Self { sinks }
+    }
+
+    /// Open the sinks in `settings.log_sinks` (the default JSON Lines file
+    /// when empty), skipping any that can't be opened
+    pub fn from_config(configs: &[LogSinkConfig]) -> Self {
+        let default = [LogSinkConfig::Jsonl { path: None }];
+        let configs = if configs.is_empty() {
+            &default[..]
+        } else {
+            configs
+        };
+        let sinks = configs
+            .iter()
+            .filter_map(|config| {
+                open(config)
+                    .map_err(|e| tracing::warn!("Skipping log sink {:?}: {:#}", config, e))
+                    .ok()
+            })
+            .collect();
+        Self::new(sinks)
+    }
+}
+
+impl LogSink for MultiSink {
+    fn name(&self) -> String {
+        let names: Vec<String> = self.sinks.iter().map(|sink| sink.name()).collect();
+        format!("[{}]", names.join(", "))
+    }
+
+    fn write(&self, entry: &LogEntry) -> Result<()> {
+        let mut failed = 0;
+        for sink in &self.sinks {
+            if let Err(e) = sink.write(entry) {
+                failed += 1;
+                tracing::warn!("Log sink {} failed: {:#}", sink.name(), e);
+            }
+        }
+        if failed > 0 && failed == self.sinks.len() {
+            anyhow::bail!("No log sink accepted the entry ({} failed)", failed);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    fn entry(outcome: Outcome) -> LogEntry {
+        serde_json::from_value(serde_json::json!({
+            "timestamp": "2026-01-05T10:00:00Z",
+            "event_type": "PreToolUse",
+            "session_id": "sink-session",
+            "tool_name": "Bash",
+            "rules_matched": ["no-force-push"],
+            "outcome": outcome,
+            "timing": { "processing_ms": 1, "rules_evaluated": 2 }
+        }))
+        .unwrap()
+    }
+
+    struct FailingSink;
+
+    impl LogSink for FailingSink {
+        fn name(&self) -> String {
+            "failing".to_string()
+        }
+
+        fn write(&self, _entry: &LogEntry) -> Result<()> {
+            anyhow::bail!("disk full")
+        }
+    }
+
+    #[test]
+    fn test_multi_sink_isolates_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cch.log");
+        let multi = MultiSink::new(vec![
+            Box::new(FailingSink),
+            Box::new(JsonlSink::open(&path).unwrap()),
+        ]);
+
+        multi.write(&entry(Outcome::Block)).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content.lines().count(), 1);
+        assert!(content.contains("no-force-push"));
+
+        let all_failing = MultiSink::new(vec![Box::new(FailingSink)]);
+        assert!(all_failing.write(&entry(Outcome::Block)).is_err());
+    }
+
+    #[test]
+    fn test_syslog_sink_sends_rfc5424_message() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let sink = SyslogSink::connect(&server.local_addr().unwrap().to_string()).unwrap();
+        sink.write(&entry(Outcome::Block)).unwrap();
+
+        let mut buf = [0u8; 4096];
+        let len = server.recv(&mut buf).unwrap();
+        let message = std::str::from_utf8(&buf[..len]).unwrap();
+        assert!(
+            message.starts_with("<12>1 2026-01-05T10:00:00.000Z - cch "),
+            "{}",
+            message
+        );
+        assert!(message.contains(" PreToolUse - {"), "{}", message);
+        assert!(message.ends_with('}'), "{}", message);
+    }
+
+    #[test]
+    fn test_webhook_sink_posts_json() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/audit", server.local_addr().unwrap());
+        let respond = |status: &'static str| {
+            let (mut stream, _) = server.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            while !String::from_utf8_lossy(&request).contains("\"timing\"") {
+                let len = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..len]);
+            }
+            stream.write_all(status.as_bytes()).unwrap();
+            String::from_utf8(request).unwrap()
+        };
+
+        let sink = WebhookSink::new(&url, Duration::from_secs(5)).unwrap();
+        std::thread::scope(|s| {
+            let request = s.spawn(|| respond("HTTP/1.1 204 No Content\r\n\r\n"));
+            sink.write(&entry(Outcome::Allow)).unwrap();
+            let request = request.join().unwrap();
+            assert!(
+                request.starts_with("POST /audit HTTP/1.1\r\n"),
+                "{}",
+                request
+            );
+            assert!(request.contains("Content-Type: application/json"));
+            assert!(request.contains("\"session_id\":\"sink-session\""));
+
+            let request = s.spawn(|| respond("HTTP/1.1 503 Service Unavailable\r\n\r\n"));
+            let error = sink.write(&entry(Outcome::Allow)).unwrap_err();
+            request.join().unwrap();
+            assert_eq!(error.to_string(), "Webhook answered HTTP 503");
+        });
+
+        assert!(WebhookSink::new("https://example.com/", Duration::from_secs(1)).is_err());
+        assert!(WebhookSink::new("http://:80/", Duration::from_secs(1)).is_err());
+    }
+}
@@ -1,13 +1,33 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
-use std::io::{self, Read};
+use serde::Deserialize;
+use std::io::{self, BufRead, Read};
+use std::time::Duration;
 use tracing::{error, info};
 
+mod agents;
+mod bundle;
+mod chaos;
 mod cli;
 mod config;
+mod daemon;
+mod encryption;
+mod enrichment;
+mod expr;
+mod front_matter;
 mod hooks;
+mod journal;
 mod logging;
+mod mcp;
 mod models;
+mod pack;
+mod paths;
+mod policy;
+mod regex_cache;
+mod state;
+mod structural;
+mod validator_cache;
+mod wasm_validator;
 
 #[derive(Parser)]
 #[command(name = "cch")]
@@ -41,12 +61,29 @@ enum Commands {
         /// Path to CCH binary (auto-detected if not specified)
         #[arg(short, long)]
         binary: Option<String>,
+        /// Events to register, comma-separated (e.g. PreToolUse,PostToolUse;
+        /// defaults to settings.install.events)
+        #[arg(long)]
+        events: Option<String>,
+        /// Tools to register tool events for, comma-separated (e.g.
+        /// Bash,Write,Edit; defaults to settings.install.tools, else all)
+        #[arg(long)]
+        tools: Option<String>,
+        /// Agent to install for: claude (default), cursor or opencode
+        #[arg(long)]
+        agent: Option<String>,
     },
     /// Uninstall CCH hook from Claude Code settings
     Uninstall {
         /// Uninstall from global settings instead of project-local
         #[arg(short, long)]
         global: bool,
+        /// Events to unregister, comma-separated (default: all)
+        #[arg(long)]
+        events: Option<String>,
+        /// Agent to uninstall from: claude (default), cursor or opencode
+        #[arg(long)]
+        agent: Option<String>,
     },
     /// Simulate an event to test rules
     Debug {
@@ -64,6 +101,9 @@ enum Commands {
         /// Show verbose rule evaluation
         #[arg(short, long)]
         verbose: bool,
+        /// Write the simulated event to the audit log like a real one
+        #[arg(long)]
+        record: bool,
     },
     /// Start interactive debug mode
     Repl,
@@ -72,9 +112,23 @@ enum Commands {
         /// Path to configuration file
         #[arg(short, long)]
         config: Option<String>,
+        /// Execute inline `tests:` declared on rules
+        #[arg(long)]
+        run_rule_tests: bool,
+    },
+    /// Run the policy test cases in hooks.tests.yaml
+    Test {
+        /// Path to configuration file (defaults to the merged configuration)
+        #[arg(short, long)]
+        config: Option<String>,
+        /// Path to the test file (defaults to hooks.tests.yaml beside the config)
+        #[arg(short, long)]
+        tests: Option<String>,
     },
     /// Query and display logs
     Logs {
+        #[command(subcommand)]
+        subcommand: Option<LogsSubcommand>,
         /// Number of recent log entries to show
         #[arg(short, long, default_value = "10")]
         limit: usize,
@@ -87,6 +141,12 @@ enum Commands {
         /// Filter by decision (allowed, blocked, warned, audited)
         #[arg(long)]
         decision: Option<String>,
+        /// Filter by tool name
+        #[arg(long)]
+        tool: Option<String>,
+        /// Keep streaming new entries as they are logged
+        #[arg(short, long)]
+        follow: bool,
     },
     /// Explain rules or events (use 'cch explain --help' for subcommands)
     Explain {
@@ -95,6 +155,298 @@ enum Commands {
         /// Event/session ID to explain (legacy usage)
         event_id: Option<String>,
     },
+    /// Report rules, matchers, and event types with no activity in the logs
+    Coverage {
+        /// Window to analyze: relative age (30d, 12h, 2w) or RFC3339 timestamp
+        #[arg(long, default_value = "30d")]
+        since: String,
+    },
+    /// Aggregate the audit log: decisions per rule, blocks per day, top
+    /// blocked commands, processing time, and rules that never fired
+    Stats {
+        /// Window start: relative age (30d, 12h, 2w) or RFC3339 timestamp
+        /// (default: the whole log)
+        #[arg(long)]
+        since: Option<String>,
+        /// Window end, in the same forms (default: now)
+        #[arg(long)]
+        until: Option<String>,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Run a long-lived daemon that hook invocations forward events to
+    Daemon {
+        /// Unix socket to listen on (default: ~/.claude/run/cch/cch.sock)
+        #[arg(long)]
+        socket: Option<std::path::PathBuf>,
+
+        /// Also serve the HTTP decision API on this loopback address
+        #[arg(long)]
+        http: Option<std::net::SocketAddr>,
+    },
+    /// Generate rules from settings kept elsewhere
+    Import {
+        #[command(subcommand)]
+        subcommand: ImportSubcommand,
+    },
+    /// Serve policy evaluation, rule explanations and logs as MCP tools
+    /// over stdio
+    Mcp,
+    /// Create and manage shareable rule packs
+    Pack {
+        #[command(subcommand)]
+        subcommand: PackSubcommand,
+    },
+    /// Export or import a self-contained policy bundle (for offline machines)
+    Bundle {
+        #[command(subcommand)]
+        subcommand: BundleSubcommand,
+    },
+    /// Save or restore the project's whole CCH setup
+    Snapshot {
+        #[command(subcommand)]
+        subcommand: SnapshotSubcommand,
+    },
+    /// Maintain rules in hooks.yaml
+    Rule {
+        #[command(subcommand)]
+        subcommand: RuleSubcommand,
+    },
+    /// Inspect session activity
+    Session {
+        #[command(subcommand)]
+        subcommand: SessionSubcommand,
+    },
+    /// Print a compact status line for Claude Code's statusLine setting
+    Statusline {
+        /// Session to count blocks for (defaults to the session_id on stdin)
+        #[arg(long)]
+        session: Option<String>,
+    },
+    /// Diagnose the setup and how hooks talk to CCH
+    Doctor {
+        /// Check the setup: hook registration, config, referenced files,
+        /// log directory, and the binary on PATH
+        #[arg(long)]
+        environment: bool,
+        /// Show which spellings of event fields hooks have sent
+        #[arg(long)]
+        protocol: bool,
+    },
+    /// Check the hook setup against a Claude Code version's hook protocol
+    VerifyProtocol {
+        /// Claude Code version to check against (X.Y or X.Y.Z)
+        #[arg(long)]
+        claude_version: String,
+    },
+    /// Remove CCH logs, caches, and session state
+    Clean {
+        /// Remove audit logs
+        #[arg(long)]
+        logs: bool,
+        /// Remove cached data
+        #[arg(long)]
+        cache: bool,
+        /// Remove session state and retry queues
+        #[arg(long)]
+        state: bool,
+        /// Remove everything
+        #[arg(long)]
+        all: bool,
+        /// Remove without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+        /// List what would be removed without removing it
+        #[arg(long, conflicts_with = "yes")]
+        dry_run: bool,
+    },
+}
+
+/// Subcommands for the logs command
+#[derive(Subcommand)]
+enum LogsSubcommand {
+    /// Summarize logged activity
+    Stats {
+        #[command(subcommand)]
+        subcommand: Option<StatsSubcommand>,
+        /// Rank validator scripts by p95 latency and timeout frequency
+        #[arg(long)]
+        slow_validators: bool,
+        /// Show daily processing-time percentiles from the latency histograms
+        #[arg(long, conflicts_with = "slow_validators")]
+        latency: bool,
+        /// Only include logs since timestamp (RFC3339 format)
+        #[arg(long)]
+        since: Option<String>,
+    },
+}
+
+/// Subcommands for the logs stats command
+#[derive(Subcommand)]
+enum StatsSubcommand {
+    /// Compare block rate, warn rate, and per-rule activity across two windows
+    Compare {
+        /// Start of the earlier window, which ends where the later one starts:
+        /// relative age (2w, 14d) or RFC3339 timestamp
+        #[arg(long, default_value = "2w")]
+        before: String,
+        /// Start of the later window, which runs until now
+        #[arg(long, default_value = "1w")]
+        after: String,
+    },
+}
+
+/// Subcommands for the pack command
+#[derive(Subcommand)]
+enum PackSubcommand {
+    /// Bundle selected rules and their referenced files into a pack
+    Create {
+        /// Include rules with this governance tag (repeatable)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Include the rule with this name (repeatable)
+        #[arg(long = "rule")]
+        rules: Vec<String>,
+        /// Output pack file
+        #[arg(short, long)]
+        out: String,
+        /// Pack name (defaults to the output file name)
+        #[arg(long)]
+        name: Option<String>,
+        /// Pack version
+        #[arg(long, default_value = "0.1.0")]
+        version: String,
+        /// Pack description
+        #[arg(long)]
+        description: Option<String>,
+        /// Path to configuration file
+        #[arg(short, long)]
+        config: Option<String>,
+    },
+    /// Install a pack into the project, resolving conflicts with existing rules
+    Install {
+        /// Pack file to install
+        file: String,
+        /// Resolve every conflict without prompting
+        /// (keep-local, take-pack, rename, merge)
+        #[arg(long)]
+        strategy: Option<String>,
+        /// Path to configuration file
+        #[arg(short, long)]
+        config: Option<String>,
+    },
+}
+
+/// Subcommands for the import command
+#[derive(Subcommand)]
+enum ImportSubcommand {
+    /// Generate rules from a GitHub repository's settings
+    Github {
+        /// Import CODEOWNERS and branch protection
+        #[arg(long)]
+        repo_settings: bool,
+        /// CODEOWNERS file (default: .github/CODEOWNERS, CODEOWNERS or
+        /// docs/CODEOWNERS)
+        #[arg(long)]
+        codeowners: Option<String>,
+        /// Branch protection JSON exported from the GitHub API (default:
+        /// fetched with gh)
+        #[arg(long)]
+        protection: Option<String>,
+        /// Repository to fetch branch protection for, as OWNER/NAME
+        /// (default: the current one)
+        #[arg(long)]
+        repo: Option<String>,
+        /// Your teams or users, comma-separated (e.g. @acme/web); paths
+        /// they own stay editable
+        #[arg(long)]
+        team: Option<String>,
+        /// Print the generated rules instead of adding them
+        #[arg(long)]
+        dry_run: bool,
+        /// Path to configuration file
+        #[arg(short, long)]
+        config: Option<String>,
+    },
+}
+
+/// Subcommands for the bundle command
+#[derive(Subcommand)]
+enum BundleSubcommand {
+    /// Write the configuration and every file it references into one bundle
+    Export {
+        /// Output bundle file
+        #[arg(short, long)]
+        out: String,
+        /// Path to configuration file
+        #[arg(short, long)]
+        config: Option<String>,
+    },
+    /// Verify a bundle's hashes and install it into the current project
+    Import {
+        /// Bundle file to import
+        file: String,
+        /// Overwrite local files that differ from the bundle
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+/// Subcommands for the snapshot command
+#[derive(Subcommand)]
+enum SnapshotSubcommand {
+    /// Save hooks.yaml, the files its rules reference and the installed hooks
+    Create {
+        /// Snapshot name
+        name: String,
+        /// Replace an existing snapshot of the same name
+        #[arg(long)]
+        force: bool,
+    },
+    /// Put a saved setup back in place
+    Restore {
+        /// Snapshot name
+        name: String,
+    },
+    /// List saved snapshots
+    List,
+}
+
+/// Subcommands for the rule command
+#[derive(Subcommand)]
+enum RuleSubcommand {
+    /// Rename a rule, optionally keeping its log history
+    Rename {
+        /// Current rule name
+        old: String,
+        /// New rule name
+        new: String,
+        /// Path to configuration file
+        #[arg(short, long)]
+        config: Option<String>,
+        /// Attribute past log entries for the old name to the new one
+        #[arg(long)]
+        keep_history: bool,
+    },
+    /// Estimate a proposed rule's impact against logged events
+    Preview {
+        /// YAML file with the rule (or a list or `rules:` section of them)
+        rule_file: String,
+        /// Window of logs to replay: a relative age (30d, 12h) or RFC3339
+        #[arg(long, default_value = "30d")]
+        against_logs: String,
+    },
+}
+
+/// Subcommands for the session command
+#[derive(Subcommand)]
+enum SessionSubcommand {
+    /// List files the session wrote or edited (requires settings.change_journal)
+    Diff {
+        /// Session ID
+        session_id: String,
+    },
 }
 
 /// Subcommands for the explain command
@@ -110,6 +462,9 @@ enum ExplainSubcommand {
         /// Skip activity statistics (faster)
         #[arg(long)]
         no_stats: bool,
+        /// Show synthesized inputs the rule would and would not match
+        #[arg(long)]
+        show_matching_examples: bool,
     },
     /// List all configured rules
     Rules,
@@ -127,16 +482,16 @@ async fn main() -> Result<()> {
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
         .init();
 
-    // Initialize the global logger for audit trails
-    if let Err(e) = logging::init_global_logger() {
-        tracing::warn!("Failed to initialize logger: {}", e);
-    }
-
     let cli = Cli::parse();
 
     // Load config to get settings for DebugConfig
     let config = config::Config::load(None)?;
 
+    // Initialize the global logger for audit trails
+    if let Err(e) = logging::init_global_logger(&config.settings.log_sinks) {
+        tracing::warn!("Failed to initialize logger: {}", e);
+    }
+
     match cli.command {
         Some(Commands::Init {
             force,
@@ -144,21 +499,31 @@ async fn main() -> Result<()> {
         }) => {
             cli::init::run(force, with_examples).await?;
         }
-        Some(Commands::Install { global, binary }) => {
+        Some(Commands::Install {
+            global,
+            binary,
+            events,
+            tools,
+            agent,
+        }) => {
             let scope = if global {
                 cli::install::Scope::Global
             } else {
                 cli::install::Scope::Project
             };
-            cli::install::run(scope, binary).await?;
+            cli::install::run(scope, binary, events, tools, agent).await?;
         }
-        Some(Commands::Uninstall { global }) => {
+        Some(Commands::Uninstall {
+            global,
+            events,
+            agent,
+        }) => {
             let scope = if global {
                 cli::install::Scope::Global
             } else {
                 cli::install::Scope::Project
             };
-            cli::install::uninstall(scope).await?;
+            cli::install::uninstall(scope, events, agent).await?;
         }
         Some(Commands::Debug {
             event_type,
@@ -166,23 +531,53 @@ async fn main() -> Result<()> {
             command,
             path,
             verbose,
+            record,
         }) => {
-            cli::debug::run(event_type, tool, command, path, verbose).await?;
+            cli::debug::run(event_type, tool, command, path, verbose, record).await?;
         }
         Some(Commands::Repl) => {
             cli::debug::interactive().await?;
         }
-        Some(Commands::Validate { config }) => {
-            cli::validate::run(config).await?;
+        Some(Commands::Validate {
+            config,
+            run_rule_tests,
+        }) => {
+            cli::validate::run(config, run_rule_tests).await?;
+        }
+        Some(Commands::Test { config, tests }) => {
+            cli::test::run(config, tests).await?;
         }
         Some(Commands::Logs {
+            subcommand,
             limit,
             since,
             mode,
             decision,
-        }) => {
-            cli::logs::run(limit, since, mode, decision).await?;
-        }
+            tool,
+            follow,
+        }) => match subcommand {
+            Some(LogsSubcommand::Stats {
+                subcommand: Some(StatsSubcommand::Compare { before, after }),
+                ..
+            }) => {
+                cli::logs::compare(&before, &after).await?;
+            }
+            Some(LogsSubcommand::Stats {
+                subcommand: None,
+                slow_validators,
+                latency,
+                since,
+            }) => {
+                cli::logs::stats(slow_validators, latency, since).await?;
+            }
+            None => {
+                if follow {
+                    cli::logs::follow(limit, since, mode, decision, tool).await?;
+                } else {
+                    cli::logs::run(limit, since, mode, decision, tool).await?;
+                }
+            }
+        },
         Some(Commands::Explain {
             subcommand,
             event_id,
@@ -192,8 +587,10 @@ async fn main() -> Result<()> {
                     name,
                     json,
                     no_stats,
+                    show_matching_examples,
                 }) => {
-                    cli::explain::explain_rule(name, json, no_stats).await?;
+                    cli::explain::explain_rule(name, json, no_stats, show_matching_examples)
+                        .await?;
                 }
                 Some(ExplainSubcommand::Rules) => {
                     cli::explain::list_rules().await?;
@@ -216,6 +613,161 @@ async fn main() -> Result<()> {
                 }
             }
         }
+        Some(Commands::Coverage { since }) => {
+            cli::coverage::run(since).await?;
+        }
+        Some(Commands::Stats { since, until, json }) => {
+            cli::stats::run(since, until, json).await?;
+        }
+        Some(Commands::Daemon { socket, http }) => {
+            let socket = match socket {
+                Some(socket) => socket,
+                None => daemon::socket_path()?,
+            };
+            // Project debug_logs settings are applied per event
+            let debug_config = models::DebugConfig::new(cli.debug_logs, false);
+            daemon::run(socket, http, debug_config, config.settings.max_input_size).await?;
+        }
+        Some(Commands::Import { subcommand }) => match subcommand {
+            ImportSubcommand::Github {
+                repo_settings,
+                codeowners,
+                protection,
+                repo,
+                team,
+                dry_run,
+                config,
+            } => {
+                cli::import::github(cli::import::GithubOptions {
+                    repo_settings,
+                    codeowners,
+                    protection,
+                    repo,
+                    team,
+                    dry_run,
+                    config,
+                })
+                .await?;
+            }
+        },
+        Some(Commands::Mcp) => {
+            mcp::run().await?;
+        }
+        Some(Commands::Pack { subcommand }) => match subcommand {
+            PackSubcommand::Create {
+                tags,
+                rules,
+                out,
+                name,
+                version,
+                description,
+                config,
+            } => {
+                cli::pack::create(cli::pack::CreateOptions {
+                    tags,
+                    rules,
+                    out,
+                    name,
+                    version,
+                    description,
+                    config,
+                })
+                .await?;
+            }
+            PackSubcommand::Install {
+                file,
+                strategy,
+                config,
+            } => {
+                cli::pack::install(file, strategy, config).await?;
+            }
+        },
+        Some(Commands::Bundle { subcommand }) => match subcommand {
+            BundleSubcommand::Export { out, config } => {
+                cli::bundle::export(out, config).await?;
+            }
+            BundleSubcommand::Import { file, force } => {
+                cli::bundle::import(file, force).await?;
+            }
+        },
+        Some(Commands::Snapshot { subcommand }) => match subcommand {
+            SnapshotSubcommand::Create { name, force } => {
+                cli::snapshot::create(name, force).await?;
+            }
+            SnapshotSubcommand::Restore { name } => {
+                cli::snapshot::restore(name).await?;
+            }
+            SnapshotSubcommand::List => {
+                cli::snapshot::list().await?;
+            }
+        },
+        Some(Commands::Rule { subcommand }) => match subcommand {
+            RuleSubcommand::Rename {
+                old,
+                new,
+                config,
+                keep_history,
+            } => {
+                cli::rule::rename(old, new, config, keep_history).await?;
+            }
+            RuleSubcommand::Preview {
+                rule_file,
+                against_logs,
+            } => {
+                cli::rule::preview(rule_file, against_logs).await?;
+            }
+        },
+        Some(Commands::Session { subcommand }) => match subcommand {
+            SessionSubcommand::Diff { session_id } => {
+                cli::session::diff(session_id).await?;
+            }
+        },
+        Some(Commands::Statusline { session }) => {
+            cli::statusline::run(session).await?;
+        }
+        Some(Commands::Doctor {
+            environment,
+            protocol,
+        }) => {
+            let mut sections = Vec::new();
+            if environment {
+                sections.push(cli::doctor::Section::Environment);
+            }
+            if protocol {
+                sections.push(cli::doctor::Section::Protocol);
+            }
+            cli::doctor::run(sections).await?;
+        }
+        Some(Commands::VerifyProtocol { claude_version }) => {
+            cli::doctor::verify_protocol(&claude_version)?;
+        }
+        Some(Commands::Clean {
+            logs,
+            cache,
+            state,
+            all,
+            yes,
+            dry_run,
+        }) => {
+            let mut targets = Vec::new();
+            if logs || all {
+                targets.push(cli::clean::Target::Logs);
+            }
+            if cache || all {
+                targets.push(cli::clean::Target::Cache);
+            }
+            if state || all {
+                targets.push(cli::clean::Target::State);
+            }
+            let confirm = if dry_run {
+                cli::clean::Confirm::DryRun
+            } else if yes {
+                cli::clean::Confirm::Yes
+            } else {
+                cli::clean::Confirm::Ask
+            };
+            cli::clean::run(targets, confirm).await?;
+        }
         None => {
             // No subcommand provided, read from stdin for hook processing
             process_hook_event(&cli, &config).await?;
@@ -225,47 +777,199 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-async fn process_hook_event(cli: &Cli, _config: &config::Config) -> Result<()> {
-    let mut buffer = String::new();
-    io::stdin().read_to_string(&mut buffer)?;
-
-    if buffer.trim().is_empty() {
+/// Read hook events from stdin and respond
+///
+/// Framing: stdin normally holds one JSON event, answered per the hooks
+/// protocol (JSON on stdout, or exit code 2 with the reason on stderr when
+/// blocked). When a wrapper sends several events as JSON Lines (or simply
+/// concatenated objects), each gets one JSON response line on stdout, in
+/// order; if any was blocked, their reasons go to stderr and the exit code
+/// is 2. `settings.max_input_size` applies to each event.
+async fn process_hook_event(cli: &Cli, config: &config::Config) -> Result<()> {
+    // Parse straight from stdin so large payloads are never buffered twice
+    let max_input_size = config.settings.max_input_size;
+    let mut reader = io::BufReader::new(io::stdin().lock().take(0));
+    if !skip_leading_whitespace_capped(&mut reader, max_input_size)? {
         error!("No input received on stdin");
         std::process::exit(1);
     }
+    let (first, agent) = read_event(&mut reader, config)?;
 
-    let event: models::Event = serde_json::from_str(&buffer).map_err(|e| {
-        error!("Failed to parse hook event: {}", e);
-        e
-    })?;
+    // The rest of an oversized event can't be resynchronized, so it ends the input
+    if first.is_err() || !skip_leading_whitespace_capped(&mut reader, max_input_size)? {
+        let response = respond(cli, config, first).await?;
+        // Cursor reads its decision from stdout only
+        if let agents::Agent::Cursor(ref event) = agent {
+            println!("{}", agents::cursor_response(event, &response));
+            return Ok(());
+        }
+        if !response.continue_ {
+            // Claude Code hooks protocol: exit code 2 BLOCKS the tool call.
+            // Only stderr is used as the error message and fed back to Claude.
+            // Exit code 0 with "continue":false only stops the conversation,
+            // it does NOT prevent the tool from executing.
+            let reason = response
+                .reason
+                .as_deref()
+                .unwrap_or("Blocked by CCH policy");
+            eprintln!("{}", reason);
+            std::process::exit(2);
+        }
+
+        // For allowed responses (with or without context injection), output JSON to stdout
+        let json = serde_json::to_string(&response)?;
+        println!("{}", json);
+        return Ok(());
+    }
+
+    // Several events: one response line per event
+    let mut blocked = Vec::new();
+    let mut next = Some((first, agent));
+    while let Some((event, agent)) = next {
+        let oversized = event.is_err();
+        let response = respond(cli, config, event).await?;
+        if let agents::Agent::Cursor(ref event) = agent {
+            println!("{}", agents::cursor_response(event, &response));
+        } else if !response.continue_ {
+            blocked.push(
+                response
+                    .reason
+                    .clone()
+                    .unwrap_or_else(|| "Blocked by CCH policy".to_string()),
+            );
+            println!("{}", serde_json::to_string(&response)?);
+        } else {
+            println!("{}", serde_json::to_string(&response)?);
+        }
+
+        next = if !oversized && skip_leading_whitespace_capped(&mut reader, max_input_size)? {
+            Some(read_event(&mut reader, config)?)
+        } else {
+            None
+        };
+    }
+    if !blocked.is_empty() {
+        eprintln!("{}", blocked.join("\n"));
+        std::process::exit(2);
+    }
+
+    Ok(())
+}
+
+/// An event read from stdin (or the reason it was rejected) and the agent
+/// that sent it
+type ReadEvent = (std::result::Result<models::Event, String>, agents::Agent);
+
+/// Parse one event from the reader; an event over `max_input_size` is
+/// returned as the reason it was rejected
+fn read_event(
+    reader: &mut io::BufReader<io::Take<io::StdinLock<'static>>>,
+    config: &config::Config,
+) -> Result<ReadEvent> {
+    // The raw value is moved into the event, so nothing is held twice
+    let parsed =
+        serde_json::Value::deserialize(&mut serde_json::Deserializer::from_reader(&mut *reader));
+    match parsed {
+        Ok(raw) => {
+            if agents::Agent::detect(&raw) == agents::Agent::Claude {
+                logging::protocol::observe(&raw);
+            }
+            match models::Event::from_agent_payload(raw) {
+                Ok((event, agent)) => Ok((Ok(event), agent)),
+                Err(e) => {
+                    error!("Failed to parse hook event: {}", e);
+                    Err(e.into())
+                }
+            }
+        }
+        // Reading one byte past the cap tells an oversized event from a short one
+        Err(_) if reader.get_ref().limit() == 0 => {
+            let reason = format!(
+                "Hook event exceeds max_input_size ({} bytes)",
+                config.settings.max_input_size
+            );
+            error!("{}", reason);
+            Ok((Err(reason), agents::Agent::Claude))
+        }
+        Err(e) => {
+            error!("Failed to parse hook event: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Evaluate one event, through a running daemon if there is one
+async fn respond(
+    cli: &Cli,
+    config: &config::Config,
+    event: std::result::Result<models::Event, String>,
+) -> Result<models::Response> {
+    let event = match event {
+        Ok(event) => event,
+        Err(_) if config.settings.fail_open => return Ok(models::Response::allow()),
+        Err(reason) => return Ok(models::Response::block(reason)),
+    };
 
     info!(
         "Processing event: {} ({})",
         event.hook_event_name, event.session_id
     );
 
+    // Hand the event to a running daemon, if there is one
+    let debug_logs = cli.debug_logs || std::env::var("CCH_DEBUG_LOGS").is_ok();
+    let forward_timeout = Duration::from_secs(u64::from(config.settings.script_timeout) + 5);
+    let daemon_error = match daemon::forward(&event, debug_logs, forward_timeout) {
+        daemon::Forwarded::Answered(response) => return Ok(*response),
+        daemon::Forwarded::Failed(e) => Some(e),
+        daemon::Forwarded::Unavailable => None,
+    };
+
     // Reload config using the event's cwd so we read the correct project's hooks.yaml
     let project_config =
-        config::Config::load(event.cwd.as_ref().map(|p| std::path::Path::new(p.as_str())))?;
-    let debug_config = models::DebugConfig::new(cli.debug_logs, project_config.settings.debug_logs);
-    let response = hooks::process_event(event, &debug_config).await?;
-
-    if !response.continue_ {
-        // Claude Code hooks protocol: exit code 2 BLOCKS the tool call.
-        // Only stderr is used as the error message and fed back to Claude.
-        // Exit code 0 with "continue":false only stops the conversation,
-        // it does NOT prevent the tool from executing.
-        let reason = response
-            .reason
-            .as_deref()
-            .unwrap_or("Blocked by CCH policy");
-        eprintln!("{}", reason);
-        std::process::exit(2);
+        config::Config::load_cached(event.cwd.as_ref().map(|p| std::path::Path::new(p.as_str())))?;
+    let result = match daemon_error {
+        // The daemon may have run the event's actions already, so
+        // evaluating again could run them twice
+        Some(e) => Err(e.context("cch daemon accepted the event but did not respond")),
+        None => {
+            let debug_config =
+                models::DebugConfig::new(debug_logs, project_config.settings.debug_logs);
+            hooks::process_event(event, &debug_config).await
+        }
+    };
+    match result {
+        // An error exit lets the tool call proceed, so failing
+        // closed has to block explicitly
+        Err(e) if !project_config.settings.fail_open => Ok(models::Response::block(format!(
+            "CCH failed closed: {:#}",
+            e
+        ))),
+        result => result,
     }
+}
 
-    // For allowed responses (with or without context injection), output JSON to stdout
-    let json = serde_json::to_string(&response)?;
-    println!("{}", json);
+/// Skip whitespace before the next event, then give that event a fresh
+/// `max_input_size` allowance (plus one byte to detect oversized events)
+fn skip_leading_whitespace_capped(
+    reader: &mut io::BufReader<io::Take<io::StdinLock<'static>>>,
+    max_input_size: usize,
+) -> io::Result<bool> {
+    reader.get_mut().set_limit(max_input_size as u64 + 1);
+    skip_leading_whitespace(reader)
+}
 
-    Ok(())
+/// Consume leading whitespace, returning whether any input remains
+fn skip_leading_whitespace(reader: &mut impl BufRead) -> io::Result<bool> {
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(false);
+        }
+        let whitespace = buf.iter().take_while(|b| b.is_ascii_whitespace()).count();
+        let has_content = whitespace < buf.len();
+        reader.consume(whitespace);
+        if has_content {
+            return Ok(true);
+        }
+    }
 }
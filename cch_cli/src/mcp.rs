@@ -0,0 +1,372 @@
+//! MCP server mode
+//!
+//! `cch mcp` serves the policy engine over the Model Context Protocol
+//! (JSON-RPC 2.0, one message per line on stdin/stdout), so an agent or an
+//! IDE can ask whether a call would be blocked before making it. It offers
+//! three tools:
+//!
+//! - `evaluate_event` — evaluates a hook event (or just a Bash command or
+//!   file path) against the project's rules, as `cch test` does: nothing
+//!   is logged, and every call starts from empty session state
+//! - `explain_rule` — what `cch explain rule --json` prints
+//! - `query_logs` — recent audit log entries, filtered like `cch logs`
+//!
+//! Tool failures come back as tool results with `isError`, so the caller's
+//! model sees them; malformed requests get JSON-RPC errors.
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use crate::cli::coverage::parse_time;
+use crate::cli::explain;
+use crate::config::Config;
+use crate::hooks;
+use crate::logging::{LogQuery, QueryFilters};
+use crate::models::{Decision, EvaluationReport, Event};
+
+/// Protocol revision answered when the client doesn't name one
+const PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// Session ID of evaluated events that don't carry one
+const MCP_SESSION: &str = "cch-mcp";
+
+/// Log entries returned by `query_logs` unless it asks for a `limit`
+const DEFAULT_LOG_LIMIT: usize = 20;
+
+/// Serve MCP requests from stdin until it closes
+pub async fn run() -> Result<()> {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => handle(message).await,
+            Err(e) => Some(error(&Value::Null, -32700, &format!("Parse error: {}", e))),
+        };
+        if let Some(reply) = reply {
+            stdout.write_all(format!("{}\n", reply).as_bytes()).await?;
+            stdout.flush().await?;
+        }
+    }
+    Ok(())
+}
+
+/// The reply to one JSON-RPC message; notifications get none
+pub async fn handle(message: Value) -> Option<Value> {
+    let id = message.get("id").cloned()?;
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+    let result = match message.get("method").and_then(Value::as_str) {
+        Some("initialize") => json!({
+            "protocolVersion": params
+                .get("protocolVersion")
+                .and_then(Value::as_str)
+                .unwrap_or(PROTOCOL_VERSION),
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "cch", "version": env!("CARGO_PKG_VERSION") },
+        }),
+        Some("ping") => json!({}),
+        Some("tools/list") => json!({ "tools": tools() }),
+        Some("tools/call") => {
+            let name = params.get("name").and_then(Value::as_str).unwrap_or("");
+            let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+            let outcome = match name {
+                "evaluate_event" => evaluate_event(&arguments).await,
+                "explain_rule" => explain_rule(&arguments).await,
+                "query_logs" => query_logs(&arguments),
+                _ => return Some(error(&id, -32602, &format!("Unknown tool '{}'", name))),
+            };
+            tool_result(outcome)
+        }
+        Some(method) => {
+            return Some(error(&id, -32601, &format!("Method not found: {}", method)));
+        }
+        None => return Some(error(&id, -32600, "Invalid request: no method")),
+    };
+    Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+}
+
+fn error(id: &Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+/// A `tools/call` result: a summary line and the structured data, or the
+/// error as text
+fn tool_result(outcome: Result<(String, Value)>) -> Value {
+    match outcome {
+        Ok((summary, data)) => json!({
+            "content": [
+                { "type": "text", "text": summary },
+                { "type": "text", "text": serde_json::to_string_pretty(&data).unwrap_or_default() },
+            ],
+            "structuredContent": data,
+            "isError": false,
+        }),
+        Err(e) => json!({
+            "content": [{ "type": "text", "text": format!("{:#}", e) }],
+            "isError": true,
+        }),
+    }
+}
+
+/// The tools and their input schemas
+fn tools() -> Value {
+    json!([
+        {
+            "name": "evaluate_event",
+            "description": "Check what CCH's rules would do with a tool call before making it: \
+                whether it would be blocked, need approval, or get context injected. \
+                Give a Bash `command`, a `file_path` with its `tool_name`, or a full hook `event`.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "command": { "type": "string", "description": "Bash command to check" },
+                    "tool_name": { "type": "string", "description": "Tool name (default: Bash for a command, Write for a file_path)" },
+                    "file_path": { "type": "string", "description": "File the tool would touch" },
+                    "tool_input": { "type": "object", "description": "Full tool input (instead of command or file_path)" },
+                    "hook_event_name": { "type": "string", "description": "Hook event (default: PreToolUse)" },
+                    "cwd": { "type": "string", "description": "Project directory (default: the server's)" },
+                    "event": { "type": "object", "description": "A complete hook event payload" },
+                },
+            },
+        },
+        {
+            "name": "explain_rule",
+            "description": "Explain a CCH rule: its matchers, actions, mode, priority and recent activity.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string", "description": "Rule name" },
+                    "examples": { "type": "boolean", "description": "Include synthesized inputs the rule would and would not match" },
+                },
+                "required": ["name"],
+            },
+        },
+        {
+            "name": "query_logs",
+            "description": "Recent CCH audit log entries, newest first.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "since": { "type": "string", "description": "Relative age (30m, 12h, 7d) or RFC3339 time" },
+                    "rule": { "type": "string", "description": "Only entries where this rule matched" },
+                    "tool": { "type": "string", "description": "Only entries for this tool" },
+                    "session": { "type": "string", "description": "Only entries of this session" },
+                    "decision": { "type": "string", "description": "allowed, blocked, warned, audited or asked" },
+                    "limit": { "type": "integer", "description": "Maximum entries (default 20)" },
+                },
+            },
+        },
+    ])
+}
+
+/// The string argument `name`, if given
+fn string_arg<'a>(arguments: &'a Value, name: &str) -> Option<&'a str> {
+    arguments.get(name).and_then(Value::as_str)
+}
+
+/// The hook event `evaluate_event`'s arguments describe
+fn event_from_arguments(arguments: &Value) -> Result<Event> {
+    let mut raw = match arguments.get("event") {
+        Some(event) => event.clone(),
+        None => shorthand_event(arguments)?,
+    };
+    if let Some(object) = raw.as_object_mut() {
+        object
+            .entry("session_id")
+            .or_insert_with(|| json!(MCP_SESSION));
+        if let Some(cwd) = string_arg(arguments, "cwd") {
+            object.insert("cwd".to_string(), json!(cwd));
+        } else if !object.contains_key("cwd") {
+            let cwd = std::env::current_dir().context("Failed to get current directory")?;
+            object.insert("cwd".to_string(), json!(cwd));
+        }
+    }
+    let (event, _) = Event::from_agent_payload(raw).context("Invalid event")?;
+    Ok(event)
+}
+
+/// A hook payload built from a `command`, `file_path` or `tool_input`
+fn shorthand_event(arguments: &Value) -> Result<Value> {
+    let tool_input = match (
+        arguments.get("tool_input"),
+        string_arg(arguments, "command"),
+        string_arg(arguments, "file_path"),
+    ) {
+        (Some(input), _, _) => input.clone(),
+        (None, Some(command), _) => json!({ "command": command }),
+        (None, None, Some(path)) => json!({ "file_path": path }),
+        (None, None, None) => {
+            anyhow::bail!("Give a command, a file_path, a tool_input or an event")
+        }
+    };
+    let tool_name =
+        string_arg(arguments, "tool_name").unwrap_or(if tool_input.get("command").is_some() {
+            "Bash"
+        } else {
+            "Write"
+        });
+    Ok(json!({
+        "hook_event_name": string_arg(arguments, "hook_event_name").unwrap_or("PreToolUse"),
+        "tool_name": tool_name,
+        "tool_input": tool_input,
+    }))
+}
+
+async fn evaluate_event(arguments: &Value) -> Result<(String, Value)> {
+    let event = event_from_arguments(arguments)?;
+    let config = Config::load(event.cwd.as_deref().map(std::path::Path::new))?;
+    let report = hooks::evaluate_with_config(&event, &config).await?;
+    Ok((verdict(&report), serde_json::to_value(&report)?))
+}
+
+/// One line saying what the rules decided
+fn verdict(report: &EvaluationReport) -> String {
+    let rules: Vec<&str> = report
+        .matched_rules
+        .iter()
+        .map(|r| r.name.as_str())
+        .collect();
+    let reason = report.response.reason.as_deref().or_else(|| {
+        report
+            .response
+            .hook_specific_output
+            .as_ref()
+            .and_then(|o| o.permission_decision_reason.as_deref())
+    });
+    let verdict = match (report.response.continue_, report.response.asks()) {
+        (false, _) => format!(
+            "Would be blocked: {}",
+            reason.unwrap_or("blocked by policy")
+        ),
+        (true, true) => format!(
+            "Would need approval: {}",
+            reason.unwrap_or("approval required")
+        ),
+        (true, false) if report.decision == Some(Decision::Warned) => {
+            "Would be allowed with a warning".to_string()
+        }
+        (true, false) => "Would be allowed".to_string(),
+    };
+    if rules.is_empty() {
+        format!("{} (no rule matched)", verdict)
+    } else {
+        format!("{} (matched: {})", verdict, rules.join(", "))
+    }
+}
+
+async fn explain_rule(arguments: &Value) -> Result<(String, Value)> {
+    let name = string_arg(arguments, "name").context("Missing rule name")?;
+    let examples = arguments
+        .get("examples")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let rule = explain::explain_rule_json(name, examples).await?;
+    let summary = match rule.get("description").and_then(Value::as_str) {
+        Some(description) => format!("Rule '{}': {}", name, description),
+        None => format!("Rule '{}'", name),
+    };
+    Ok((summary, rule))
+}
+
+fn query_logs(arguments: &Value) -> Result<(String, Value)> {
+    let decision = string_arg(arguments, "decision")
+        .map(|d| {
+            d.parse::<Decision>()
+                .map_err(|_| anyhow::anyhow!("Invalid decision '{}'", d))
+        })
+        .transpose()?;
+    let limit = arguments
+        .get("limit")
+        .and_then(Value::as_u64)
+        .map_or(DEFAULT_LOG_LIMIT, |l| {
+            usize::try_from(l).unwrap_or(usize::MAX)
+        });
+    let filters = QueryFilters {
+        limit: Some(limit),
+        since: string_arg(arguments, "since")
+            .map(|since| parse_time("since", since))
+            .transpose()?,
+        rule_name: string_arg(arguments, "rule").map(ToString::to_string),
+        tool_name: string_arg(arguments, "tool").map(ToString::to_string),
+        session_id: string_arg(arguments, "session").map(ToString::to_string),
+        decision,
+        ..Default::default()
+    };
+    let entries = LogQuery::new().query(filters)?;
+    Ok((
+        format!("{} log entries", entries.len()),
+        json!({ "entries": entries }),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_handle_lists_tools_and_rejects_unknown_methods() {
+        let reply = handle(json!({
+            "jsonrpc": "2.0", "id": 1, "method": "initialize",
+            "params": { "protocolVersion": "2025-03-26" }
+        }))
+        .await
+        .unwrap();
+        assert_eq!(reply["result"]["protocolVersion"], "2025-03-26");
+        assert_eq!(reply["result"]["serverInfo"]["name"], "cch");
+
+        assert!(
+            handle(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))
+                .await
+                .is_none()
+        );
+
+        let reply = handle(json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }))
+            .await
+            .unwrap();
+        let names: Vec<&str> = reply["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["evaluate_event", "explain_rule", "query_logs"]);
+
+        let reply = handle(json!({ "jsonrpc": "2.0", "id": 3, "method": "resources/list" }))
+            .await
+            .unwrap();
+        assert_eq!(reply["error"]["code"], -32601);
+
+        let reply = handle(json!({
+            "jsonrpc": "2.0", "id": 4, "method": "tools/call",
+            "params": { "name": "evaluate_event", "arguments": {} }
+        }))
+        .await
+        .unwrap();
+        assert_eq!(reply["result"]["isError"], true);
+    }
+
+    #[test]
+    fn test_event_from_shorthand_arguments() {
+        let event = event_from_arguments(&json!({ "command": "git push" })).unwrap();
+        assert_eq!(event.tool_name.as_deref(), Some("Bash"));
+        assert_eq!(event.session_id, MCP_SESSION);
+        assert!(event.cwd.is_some());
+
+        let event = event_from_arguments(&json!({
+            "file_path": "src/main.rs",
+            "tool_name": "Edit",
+            "cwd": "/repo"
+        }))
+        .unwrap();
+        assert_eq!(event.tool_name.as_deref(), Some("Edit"));
+        assert_eq!(event.tool_input.unwrap()["file_path"], "src/main.rs");
+        assert_eq!(event.cwd.as_deref(), Some("/repo"));
+    }
+}
@@ -1,5 +1,12 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::agents::Agent;
+use crate::paths;
+use crate::policy::PolicyMatcher;
+use crate::structural::StructuralCheck;
 
 // =============================================================================
 // Phase 2 Governance Types
@@ -33,6 +40,23 @@ impl std::fmt::Display for PolicyMode {
     }
 }
 
+impl PolicyMode {
+    /// The less strict of two modes (audit < warn < enforce)
+    #[must_use]
+    pub fn least_strict(self, other: PolicyMode) -> PolicyMode {
+        let strictness = |mode| match mode {
+            PolicyMode::Enforce => 2,
+            PolicyMode::Warn => 1,
+            PolicyMode::Audit => 0,
+        };
+        if strictness(other) < strictness(self) {
+            other
+        } else {
+            self
+        }
+    }
+}
+
 /// Confidence level for rule metadata
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -64,6 +88,8 @@ pub enum Decision {
     Warned,
     /// Rule matched but only logged (audit mode)
     Audited,
+    /// The user was asked to approve the operation
+    Asked,
 }
 
 impl std::fmt::Display for Decision {
@@ -73,6 +99,7 @@ impl std::fmt::Display for Decision {
             Decision::Blocked => write!(f, "blocked"),
             Decision::Warned => write!(f, "warned"),
             Decision::Audited => write!(f, "audited"),
+            Decision::Asked => write!(f, "asked"),
         }
     }
 }
@@ -86,6 +113,7 @@ impl std::str::FromStr for Decision {
             "blocked" => Ok(Decision::Blocked),
             "warned" => Ok(Decision::Warned),
             "audited" => Ok(Decision::Audited),
+            "asked" => Ok(Decision::Asked),
             _ => Err(format!("Invalid decision: {}", s)),
         }
     }
@@ -128,7 +156,8 @@ impl std::fmt::Display for TrustLevel {
 
 /// Extended run action configuration supporting trust levels
 ///
-/// Supports two YAML formats for backward compatibility:
+/// Supports two YAML formats for backward compatibility, plus validators
+/// compiled to WebAssembly (see [`crate::wasm_validator`]):
 /// ```yaml
 /// # Simple format (existing)
 /// actions:
@@ -139,6 +168,14 @@ impl std::fmt::Display for TrustLevel {
 ///   run:
 ///     script: .claude/validators/check.py
 ///     trust: local
+///     env:
+///       MAX_LINES: "400"
+///     cacheable: true
+///
+/// # WebAssembly module (requires the `wasm-validators` feature)
+/// actions:
+///   run:
+///     wasm: .claude/validators/check.wasm
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(untagged)]
@@ -152,25 +189,71 @@ pub enum RunAction {
         /// Trust level for the script
         #[serde(skip_serializing_if = "Option::is_none")]
         trust: Option<TrustLevel>,
+        /// Extra environment variables for the script
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        env: BTreeMap<String, String>,
+        /// Whether the script's verdict depends only on the written
+        /// content, so results can be cached by content hash
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        cacheable: bool,
+    },
+    /// WebAssembly module run in-process
+    Wasm {
+        /// Path to the compiled module
+        wasm: String,
+        /// Whether the module's verdict depends only on the written
+        /// content, so results can be cached by content hash
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        cacheable: bool,
     },
 }
 
 impl RunAction {
-    /// Get the script path regardless of format
+    /// Get the script (or WebAssembly module) path regardless of format
     pub fn script_path(&self) -> &str {
         match self {
             RunAction::Simple(path) => path,
             RunAction::Extended { script, .. } => script,
+            RunAction::Wasm { wasm, .. } => wasm,
         }
     }
 
     /// Get the trust level (defaults to Local if not specified)
     pub fn trust_level(&self) -> TrustLevel {
         match self {
-            RunAction::Simple(_) => TrustLevel::Local,
+            RunAction::Simple(_) | RunAction::Wasm { .. } => TrustLevel::Local,
             RunAction::Extended { trust, .. } => trust.unwrap_or(TrustLevel::Local),
         }
     }
+
+    /// Whether the script's results may be cached
+    pub fn is_cacheable(&self) -> bool {
+        matches!(
+            self,
+            RunAction::Extended {
+                cacheable: true,
+                ..
+            } | RunAction::Wasm {
+                cacheable: true,
+                ..
+            }
+        )
+    }
+
+    /// Whether this runs a WebAssembly module rather than a script
+    pub fn is_wasm(&self) -> bool {
+        matches!(self, RunAction::Wasm { .. })
+    }
+
+    /// Get the rule's environment variables for the script
+    pub fn env(&self) -> impl Iterator<Item = (&String, &String)> {
+        match self {
+            RunAction::Simple(_) | RunAction::Wasm { .. } => None,
+            RunAction::Extended { env, .. } => Some(env),
+        }
+        .into_iter()
+        .flatten()
+    }
 }
 
 /// Governance metadata for rules - provenance and documentation
@@ -203,6 +286,105 @@ pub struct GovernanceMetadata {
     /// Tags for categorization
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tags: Option<Vec<String>>,
+
+    /// Date from which the rule is stale and demoted to
+    /// `settings.expired_rule_mode`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires: Option<NaiveDate>,
+
+    /// Date by which the rule should be reviewed again
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub review_by: Option<NaiveDate>,
+}
+
+// =============================================================================
+// Directory Trust Zones
+// =============================================================================
+
+/// Sensitivity level of a directory tree
+///
+/// Levels are ordered: `Public < Internal < Secret`. Paths outside any
+/// configured zone are treated as `Public`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sensitivity {
+    /// Freely readable content
+    #[default]
+    Public,
+    /// Internal-only content
+    Internal,
+    /// Sensitive content (credentials, customer data)
+    Secret,
+}
+
+impl std::fmt::Display for Sensitivity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Sensitivity::Public => write!(f, "public"),
+            Sensitivity::Internal => write!(f, "internal"),
+            Sensitivity::Secret => write!(f, "secret"),
+        }
+    }
+}
+
+/// Maps a directory tree to a sensitivity level
+///
+/// ```yaml
+/// zones:
+///   - path: secrets/
+///     level: secret
+///   - path: docs/internal/**
+///     level: internal
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Zone {
+    /// Directory tree, relative to the project root (or absolute)
+    pub path: String,
+
+    /// Sensitivity level of everything under `path`
+    pub level: Sensitivity,
+}
+
+impl Zone {
+    /// Check whether a path falls inside this zone
+    ///
+    /// Absolute paths are made relative to `project_root` before comparing.
+    pub fn contains(&self, path: &str, project_root: Option<&str>) -> bool {
+        path_is_under(&self.path, path, project_root)
+    }
+}
+
+/// Check whether `path` lies under the directory tree `pattern`
+///
+/// Trailing `/**` and `/*` on the pattern are ignored. Absolute paths are
+/// made relative to `project_root` before comparing.
+pub fn path_is_under(pattern: &str, path: &str, project_root: Option<&str>) -> bool {
+    let tree = Path::new(
+        pattern
+            .trim_end_matches("/**")
+            .trim_end_matches("/*")
+            .trim_end_matches('/'),
+    );
+    let path = Path::new(path);
+    let relative = project_root
+        .and_then(|root| path.strip_prefix(root).ok())
+        .unwrap_or(path);
+    let relative = relative.strip_prefix("./").unwrap_or(relative);
+
+    relative.starts_with(tree) || path.starts_with(tree)
+}
+
+/// Resolve the sensitivity of a path against the configured zones
+///
+/// The most sensitive matching zone wins, so nested zones can only raise
+/// the level of a subtree.
+pub fn zone_level(zones: &[Zone], path: &str, project_root: Option<&str>) -> Sensitivity {
+    zones
+        .iter()
+        .filter(|z| z.contains(path, project_root))
+        .map(|z| z.level)
+        .max()
+        .unwrap_or_default()
 }
 
 // =============================================================================
@@ -243,10 +425,279 @@ pub struct Rule {
     /// Legacy metadata field (for backward compatibility)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<RuleMetadata>,
+
+    /// Inline test cases run by `cch validate --run-rule-tests`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tests: Option<Vec<RuleTest>>,
+
+    /// Justified exclusions from this rule
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exemptions: Option<Vec<Exemption>>,
+
+    /// Change behavior when the rule keeps firing within a session
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub escalation: Option<Escalation>,
+
+    /// Identities the rule never applies to, such as CI bots; matched
+    /// against the event's `user_id` and the git author name and email
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bypass_users: Option<Vec<String>>,
+
+    /// Custom wording for the rule's warnings and blocks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub messages: Option<RuleMessages>,
+
+    /// Stop evaluating lower-priority rules once this rule matches
+    /// (audit-mode matches never stop evaluation)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<bool>,
 }
 
-/// Conditions that trigger a rule
+/// Per-rule response templates, replacing CCH's built-in wording
+///
+/// Templates may use `{rule}`, `{description}`, and `{reason}`, the detail
+/// CCH would otherwise have reported (e.g. "would block this operation:
+/// ..." for a warning). Other braces are kept as written.
+///
+/// ```yaml
+/// messages:
+///   warn: "Heads up: {description}. See https://wiki.example.com/git"
+///   block: "{description} ({rule}). See https://wiki.example.com/git"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RuleMessages {
+    /// Replaces the whole warning injected when the rule is in warn mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warn: Option<String>,
+
+    /// Replaces the reason given when the rule blocks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block: Option<String>,
+}
+
+impl RuleMessages {
+    /// Fill a template's placeholders for `rule`
+    pub fn render(template: &str, rule: &Rule, reason: &str) -> String {
+        template
+            .replace("{rule}", &rule.name)
+            .replace(
+                "{description}",
+                rule.description.as_deref().unwrap_or("No description"),
+            )
+            .replace("{reason}", reason)
+    }
+}
+
+/// How a rule escalates on repeat offenses within a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EscalationStrategy {
+    /// Warn about a pattern at first, block it once it repeats
+    WarnThenBlock,
+    /// Block as usual, and add a session advisory once blocks repeat
+    BlockThenAdvise,
+}
+
+impl std::fmt::Display for EscalationStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EscalationStrategy::WarnThenBlock => write!(f, "warn_then_block"),
+            EscalationStrategy::BlockThenAdvise => write!(f, "block_then_advise"),
+        }
+    }
+}
+
+/// Escalation policy backed by the session state store
+///
+/// `warn_then_block` counts offenses per pattern (the Bash command or
+/// target path), so only a repeat of the same operation is blocked.
+/// `block_then_advise` counts every block of the rule, so retrying
+/// variations still triggers the advisory.
+///
+/// ```yaml
+/// escalation:
+///   strategy: block_then_advise
+///   after: 3
+///   advisory: "Stop retrying; ask the user how to proceed."
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Escalation {
+    pub strategy: EscalationStrategy,
+
+    /// Offense number at which the escalated behavior starts
+    #[serde(default = "default_escalation_after")]
+    pub after: u32,
+
+    /// Advisory text for `block_then_advise`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub advisory: Option<String>,
+}
+
+fn default_escalation_after() -> u32 {
+    2
+}
+
+/// `actions.enrich_prompt`: context Claude Code adds to a submitted prompt
+/// (as UserPromptSubmit `additionalContext`)
+///
+/// Glossary entries are added when the prompt mentions the term as a whole
+/// word (case-sensitive); instructions are added to every prompt. Each is
+/// sent once per session unless `once_per_session` is off.
+///
+/// ```yaml
+/// actions:
+///   enrich_prompt:
+///     glossary:
+///       CDK: AWS Cloud Development Kit, our infrastructure-as-code tool
+///       IQ: Installation Qualification test suite
+///     instructions:
+///       - Use pnpm, never npm or yarn.
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PromptEnrichment {
+    /// Expansions of terms, acronyms and project jargon
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub glossary: BTreeMap<String, String>,
+
+    /// Instructions that apply to every prompt
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub instructions: Vec<String>,
+
+    /// Send each glossary entry and instruction once per session
+    #[serde(default = "default_once_per_session")]
+    pub once_per_session: bool,
+}
+
+fn default_once_per_session() -> bool {
+    true
+}
+
+/// `actions.rewrite`: a change to a PreToolUse call's input, returned to
+/// Claude Code as `updatedInput` so the tool runs with the new input
+///
+/// `pattern` is a regex over the string `field` of the tool input (the
+/// Bash `command` by default) whose matches become `replace`, where `$1`
+/// expands a capture group. `append` is added to the end of the field
+/// unless the field already contains it. Rules in warn mode report the
+/// rewrite instead of making it, and audit mode leaves the input alone.
+///
+/// ```yaml
+/// actions:
+///   rewrite:
+///     append: " --dry-run"
+/// ```
+///
+/// ```yaml
+/// actions:
+///   rewrite:
+///     pattern: '^rm -rf (.+)$'
+///     replace: 'mkdir -p .trash && mv $1 .trash/'
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RewriteInput {
+    /// Tool input field to rewrite
+    #[serde(default = "default_rewrite_field")]
+    pub field: String,
+
+    /// Regex whose matches are replaced
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+
+    /// Replacement for `pattern` matches
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replace: Option<String>,
+
+    /// Text added to the end of the field
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub append: Option<String>,
+}
+
+fn default_rewrite_field() -> String {
+    "command".to_string()
+}
+
+impl RewriteInput {
+    /// The rewritten `value`, or `None` when the rewrite changes nothing
+    pub fn apply(&self, value: &str) -> Option<String> {
+        let mut result = value.to_string();
+        if let (Some(pattern), Some(replace)) = (&self.pattern, &self.replace) {
+            let regex = crate::regex_cache::compile(pattern).ok()?;
+            result = regex.replace_all(&result, replace.as_str()).into_owned();
+        }
+        if let Some(ref append) = self.append {
+            if !result.contains(append.trim()) {
+                result.push_str(append);
+            }
+        }
+        (result != value).then_some(result)
+    }
+}
+
+/// An auditable exclusion from a rule
+///
+/// The rule does not apply when the event matches any listed path, command
+/// pattern, or session source. Every exemption must state why it exists.
+///
+/// ```yaml
+/// exemptions:
+///   - paths: [vendor/**]
+///     justification: Third-party code is reviewed upstream
+///   - commands: ["^git push --force-with-lease origin feature/"]
+///     justification: Rebasing personal feature branches is allowed
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Exemption {
+    /// Directory trees excluded from the rule (e.g. `vendor/**`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+
+    /// Regex patterns for excluded Bash commands
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commands: Vec<String>,
+
+    /// Session sources excluded on SessionStart (e.g. `resume`, `compact`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<String>,
+
+    /// Why the exclusion is acceptable (required)
+    pub justification: String,
+}
+
+/// Record of an `allow` rule overriding lower-priority blocks
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AllowOverride {
+    /// Rule whose `allow` action won
+    pub rule: String,
+
+    /// Rules whose blocks it overrode
+    pub overridden: Vec<String>,
+}
+
+/// Record of an exemption that suppressed a matching rule
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AppliedExemption {
+    /// Rule that was exempted
+    pub rule: String,
+
+    /// Justification from the exemption
+    pub justification: String,
+}
+
+/// Conditions that trigger a rule
+///
+/// All configured conditions must hold. `any`, `all` and `not` nest further
+/// matcher blocks to express OR and negation:
+///
+/// ```yaml
+/// matchers:
+///   tools: [Bash]
+///   any:
+///     - command_match: "rm -rf"
+///     - command_match: "git clean -fdx"
+///   not:
+///     directories: ["tmp/**"]
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct Matchers {
     /// Tool names to match (e.g., ["Bash", "Edit"])
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -267,14 +718,128 @@ pub struct Matchers {
     /// Regex pattern for command matching
     #[serde(skip_serializing_if = "Option::is_none")]
     pub command_match: Option<String>,
+
+    /// Minimum sensitivity of the target path's trust zone (e.g. `secret`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zone: Option<Sensitivity>,
+
+    /// Tool names that never match, even when listed in `tools`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude_tools: Option<Vec<String>>,
+
+    /// File extensions that never match (e.g., [".md"])
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude_extensions: Option<Vec<String>>,
+
+    /// Directory patterns that never match (e.g., ["docs/**"])
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude_directories: Option<Vec<String>>,
+
+    /// Regex pattern for commands that never match
+    /// (e.g., `^git (status|log|diff)\b`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command_not_match: Option<String>,
+
+    /// Regex matched against a PostToolUse result's output (and stderr for
+    /// Bash); events without a result never match
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_output_match: Option<String>,
+
+    /// Exit code a PostToolUse result must report: a code, a list of codes,
+    /// `zero` or `nonzero`; results without an exit code never match
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<ExitCodeMatch>,
+
+    /// Regex matched against a UserPromptSubmit prompt; other events never
+    /// match
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_match: Option<String>,
+
+    /// `true` matches Bash calls with `run_in_background` and the
+    /// BashOutput/KillShell tools that manage background shells; `false`
+    /// matches every other event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<bool>,
+
+    /// Globs the canonical target path of a file event must match (`deny`)
+    /// and must not match (`allow`); events without a path never match
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protected_paths: Option<ProtectedPaths>,
+
+    /// Expression over the event's fields, e.g.
+    /// `path.startsWith("src/") && branch != "main"` (see [`crate::expr`])
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expr: Option<String>,
+
+    /// Matches when at least one nested block matches (none listed: never)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub any: Option<Vec<Matchers>>,
+
+    /// Matches when every nested block matches
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub all: Option<Vec<Matchers>>,
+
+    /// Matches when the nested block does not
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not: Option<Box<Matchers>>,
+
+    /// Compiled `policies:` condition (set only on generated rules)
+    #[serde(skip)]
+    pub policy: Option<PolicyMatcher>,
+}
+
+/// `exit_code` matcher value
+///
+/// ```yaml
+/// exit_code: 101          # exactly this code
+/// exit_code: [1, 101]     # any of these
+/// exit_code: nonzero      # any failure
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum ExitCodeMatch {
+    Code(i64),
+    Codes(Vec<i64>),
+    Class(ExitCodeClass),
+}
+
+/// Exit code classes accepted by the `exit_code` matcher
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitCodeClass {
+    Zero,
+    Nonzero,
+}
+
+impl ExitCodeMatch {
+    /// Whether `code` satisfies the matcher
+    pub fn matches(&self, code: i64) -> bool {
+        match self {
+            ExitCodeMatch::Code(expected) => code == *expected,
+            ExitCodeMatch::Codes(expected) => expected.contains(&code),
+            ExitCodeMatch::Class(ExitCodeClass::Zero) => code == 0,
+            ExitCodeMatch::Class(ExitCodeClass::Nonzero) => code != 0,
+        }
+    }
+}
+
+impl std::fmt::Display for ExitCodeMatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExitCodeMatch::Code(code) => write!(f, "{}", code),
+            ExitCodeMatch::Codes(codes) => write!(f, "{:?}", codes),
+            ExitCodeMatch::Class(ExitCodeClass::Zero) => write!(f, "zero"),
+            ExitCodeMatch::Class(ExitCodeClass::Nonzero) => write!(f, "nonzero"),
+        }
+    }
 }
 
 /// Actions to take when rule matches
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Actions {
-    /// Path to context file to inject
+    /// Context file(s) to inject (supports a path or an ordered list)
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub inject: Option<String>,
+    pub inject: Option<InjectAction>,
 
     /// Validator script to execute (supports string or object format)
     ///
@@ -298,6 +863,62 @@ pub struct Actions {
     /// Regex pattern for conditional blocking
     #[serde(skip_serializing_if = "Option::is_none")]
     pub block_if_match: Option<String>,
+
+    /// Truncate large tool output on PostToolUse before it reaches the model
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub truncate_output: Option<TruncateOutput>,
+
+    /// Drop matching paths from Glob/Grep results on PostToolUse
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter_results: Option<FilterResults>,
+
+    /// Built-in tree-sitter checks run against written content
+    /// (requires the `structural-checks` feature)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub structural_checks: Option<Vec<StructuralCheck>>,
+
+    /// Require new files to start with a license/copyright header
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ensure_header: Option<EnsureHeader>,
+
+    /// Shell command whose stdout is injected as context, e.g.
+    /// `git branch --show-current && git status --short`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inject_command: Option<String>,
+
+    /// Ask the user to approve the operation instead of blocking it
+    /// (PreToolUse permission prompt)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ask: Option<bool>,
+
+    /// Project files that must exist, relative to the project root; when
+    /// any is missing, session events get a reminder and tool calls are
+    /// blocked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub require_files: Option<Vec<String>>,
+
+    /// Allow the operation even when lower-priority rules would block it;
+    /// only enforce-mode rules override
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow: Option<bool>,
+
+    /// Send each `inject` source at most once per session; repeats are
+    /// dropped and recorded in the log entry's metadata
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inject_once_per_session: Option<bool>,
+
+    /// Fire at most `max` times per window, per session or globally
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub throttle: Option<Throttle>,
+
+    /// Glossary expansions and standing instructions added to
+    /// UserPromptSubmit prompts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enrich_prompt: Option<PromptEnrichment>,
+
+    /// Rewrite the tool input of a PreToolUse call
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rewrite: Option<RewriteInput>,
 }
 
 impl Actions {
@@ -310,6 +931,439 @@ impl Actions {
     pub fn trust_level(&self) -> Option<TrustLevel> {
         self.run.as_ref().map(|r| r.trust_level())
     }
+
+    /// Paths of all files injected by this rule, in order
+    pub fn inject_paths(&self) -> impl Iterator<Item = &str> {
+        self.inject
+            .iter()
+            .flat_map(|inject| inject.sources())
+            .map(InjectSource::path)
+    }
+}
+
+/// Limit on how often a rule fires, backed by the state store
+///
+/// Once the rule has fired `max` times within the last `per` (in the
+/// event's session, or across sessions for `scope: global`), further
+/// matches are skipped as though the rule hadn't matched. Without `per`
+/// the limit spans the whole session, or all time when global.
+///
+/// ```yaml
+/// actions:
+///   inject: .claude/context/conventions.md
+///   throttle: { max: 1 }                            # once per session
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Throttle {
+    /// Times the rule may fire within the window
+    pub max: u32,
+
+    /// Window length: `<N>s`, `<N>m`, `<N>h`, `<N>d`, or `<N>w`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub per: Option<String>,
+
+    /// Whether firings are counted per session or across sessions
+    #[serde(default)]
+    pub scope: ThrottleScope,
+}
+
+impl Throttle {
+    /// The window `per` describes; `None` when unbounded
+    pub fn window(&self) -> anyhow::Result<Option<chrono::Duration>> {
+        let Some(ref per) = self.per else {
+            return Ok(None);
+        };
+        let (amount, unit) = per.split_at(per.len().saturating_sub(1));
+        let amount: i64 = amount
+            .parse()
+            .ok()
+            .filter(|amount| *amount > 0)
+            .ok_or_else(|| anyhow::anyhow!("Invalid throttle window '{}': use e.g. 10m", per))?;
+        Ok(Some(match unit {
+            "s" => chrono::Duration::seconds(amount),
+            "m" => chrono::Duration::minutes(amount),
+            "h" => chrono::Duration::hours(amount),
+            "d" => chrono::Duration::days(amount),
+            "w" => chrono::Duration::weeks(amount),
+            _ => anyhow::bail!(
+                "Invalid throttle window unit in '{}': use s, m, h, d, or w",
+                per
+            ),
+        }))
+    }
+}
+
+/// What a throttle counts firings across
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThrottleScope {
+    /// Each session has its own allowance
+    #[default]
+    Session,
+    /// All sessions share one allowance
+    Global,
+}
+
+/// Context injection action
+///
+/// Either a single file or a list of files concatenated in order, each
+/// optionally introduced by a section header. The mapping form also says
+/// where the content goes:
+/// ```yaml
+/// # Single file (existing)
+/// inject: .claude/context/security.md
+///
+/// # Ordered list
+/// inject:
+///   - .claude/context/security.md
+///   - file: docs/api-guidelines.md
+///     header: API Guidelines
+///
+/// # Placed ahead of other rules' context, as a system message
+/// inject:
+///   files: [.claude/context/prod-freeze.md]
+///   position: prepend
+///   channel: system_message
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum InjectAction {
+    Single(InjectSource),
+    Multiple(Vec<InjectSource>),
+    Placed {
+        files: Vec<InjectSource>,
+        #[serde(default)]
+        position: InjectPosition,
+        #[serde(default)]
+        channel: InjectChannel,
+    },
+}
+
+impl InjectAction {
+    /// The files to inject, in order
+    pub fn sources(&self) -> &[InjectSource] {
+        match self {
+            InjectAction::Single(source) => std::slice::from_ref(source),
+            InjectAction::Multiple(sources) | InjectAction::Placed { files: sources, .. } => {
+                sources
+            }
+        }
+    }
+
+    /// Where the content goes relative to other rules' context
+    pub fn position(&self) -> InjectPosition {
+        match self {
+            InjectAction::Placed { position, .. } => *position,
+            _ => InjectPosition::default(),
+        }
+    }
+
+    /// Which part of the hook output carries the content
+    pub fn channel(&self) -> InjectChannel {
+        match self {
+            InjectAction::Placed { channel, .. } => *channel,
+            _ => InjectChannel::default(),
+        }
+    }
+
+    /// This action limited to `sources`, keeping its placement
+    #[must_use]
+    pub fn with_sources(&self, sources: Vec<InjectSource>) -> Self {
+        InjectAction::Placed {
+            files: sources,
+            position: self.position(),
+            channel: self.channel(),
+        }
+    }
+}
+
+/// Where injected content goes relative to other rules' context
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InjectPosition {
+    /// After context from higher-priority rules
+    #[default]
+    Append,
+    /// Ahead of all other context
+    Prepend,
+}
+
+/// Which part of the hook output carries injected content
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InjectChannel {
+    /// Additional context for the model
+    #[default]
+    Context,
+    /// `systemMessage`, shown to the user
+    SystemMessage,
+}
+
+impl std::fmt::Display for InjectAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let paths: Vec<&str> = self.sources().iter().map(InjectSource::path).collect();
+        write!(f, "{}", paths.join(", "))
+    }
+}
+
+/// One file of an inject action
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum InjectSource {
+    /// Simple string format: just the file path
+    Path(String),
+    /// File with a section header rendered above its content
+    Section {
+        file: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        header: Option<String>,
+    },
+}
+
+impl InjectSource {
+    pub fn path(&self) -> &str {
+        match self {
+            InjectSource::Path(path) => path,
+            InjectSource::Section { file, .. } => file,
+        }
+    }
+
+    pub fn header(&self) -> Option<&str> {
+        match self {
+            InjectSource::Path(_) => None,
+            InjectSource::Section { header, .. } => header.as_deref(),
+        }
+    }
+}
+
+/// Result filtering for Glob/Grep on PostToolUse
+///
+/// Claude Code can't replace a built-in tool's output, so the filtered
+/// results go back as `additionalContext` telling the model to disregard
+/// the excluded ones.
+///
+/// ```yaml
+/// actions:
+///   filter_results:
+///     exclude: ["secrets/**", ".env"]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FilterResults {
+    /// Directory patterns or paths whose results are hidden
+    pub exclude: Vec<String>,
+}
+
+impl FilterResults {
+    /// Whether a result path falls under one of the excluded patterns
+    pub fn excludes(&self, path: &str, project_root: Option<&str>) -> bool {
+        self.exclude
+            .iter()
+            .any(|pattern| path_is_under(pattern, path, project_root))
+    }
+
+    /// Filter result lines, each a path optionally followed by
+    /// `:line:text` (Grep content mode)
+    ///
+    /// Returns `None` when nothing was excluded.
+    pub fn apply<'a>(
+        &self,
+        lines: impl IntoIterator<Item = &'a str>,
+        project_root: Option<&str>,
+    ) -> Option<String> {
+        let (kept, hidden): (Vec<&str>, Vec<&str>) = lines.into_iter().partition(|line| {
+            let path = line.split(':').next().unwrap_or(line);
+            !self.excludes(path, project_root)
+        });
+        if hidden.is_empty() {
+            return None;
+        }
+        let marker = format!("[{} results hidden by CCH]", hidden.len());
+        Some(
+            kept.into_iter()
+                .chain([marker.as_str()])
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+    }
+}
+
+/// Which part of an oversized output to keep
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TruncateStrategy {
+    /// Keep the beginning of the output
+    Head,
+    /// Keep the end of the output
+    Tail,
+    /// Keep the beginning and the end, dropping the middle
+    #[default]
+    HeadTail,
+}
+
+/// Output truncation settings for PostToolUse events
+///
+/// MCP tool results are replaced (as `updatedMCPToolOutput`). Claude Code
+/// offers no way to replace the output of its built-in tools, so for those
+/// the shortened output is added as `additionalContext` instead.
+///
+/// ```yaml
+/// actions:
+///   truncate_output:
+///     max_lines: 200
+///     max_bytes: 20000
+///     strategy: head_tail
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TruncateOutput {
+    /// Maximum number of lines to keep
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_lines: Option<usize>,
+
+    /// Maximum number of bytes to keep
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_bytes: Option<usize>,
+
+    /// Which part of the output to keep
+    #[serde(default)]
+    pub strategy: TruncateStrategy,
+}
+
+impl TruncateOutput {
+    /// Truncate `text` to the configured limits
+    ///
+    /// Returns `None` when the text is already within limits.
+    pub fn apply(&self, text: &str) -> Option<String> {
+        let mut result = text.to_string();
+        let mut truncated = false;
+
+        if let Some(max_lines) = self.max_lines {
+            let lines: Vec<&str> = result.lines().collect();
+            if lines.len() > max_lines {
+                let dropped = lines.len() - max_lines;
+                let marker = format!("... [{} lines truncated by CCH] ...", dropped);
+                result = match self.strategy {
+                    TruncateStrategy::Head => [&lines[..max_lines], &[marker.as_str()]].concat(),
+                    TruncateStrategy::Tail => [&[marker.as_str()], &lines[dropped..]].concat(),
+                    TruncateStrategy::HeadTail => {
+                        let head = max_lines.div_ceil(2);
+                        let tail = max_lines - head;
+                        [
+                            &lines[..head],
+                            &[marker.as_str()],
+                            &lines[lines.len() - tail..],
+                        ]
+                        .concat()
+                    }
+                }
+                .join("\n");
+                truncated = true;
+            }
+        }
+
+        if let Some(max_bytes) = self.max_bytes {
+            if result.len() > max_bytes {
+                let dropped = result.len() - max_bytes;
+                let marker = format!("\n... [{} bytes truncated by CCH] ...\n", dropped);
+                result = match self.strategy {
+                    TruncateStrategy::Head => {
+                        format!(
+                            "{}{}",
+                            &result[..floor_char_boundary(&result, max_bytes)],
+                            marker
+                        )
+                    }
+                    TruncateStrategy::Tail => {
+                        let start = ceil_char_boundary(&result, dropped);
+                        format!("{}{}", marker, &result[start..])
+                    }
+                    TruncateStrategy::HeadTail => {
+                        let head_end = floor_char_boundary(&result, max_bytes.div_ceil(2));
+                        let tail_start = ceil_char_boundary(&result, result.len() - max_bytes / 2);
+                        format!("{}{}{}", &result[..head_end], marker, &result[tail_start..])
+                    }
+                };
+                truncated = true;
+            }
+        }
+
+        truncated.then_some(result)
+    }
+}
+
+/// Required file header for newly written files
+///
+/// ```yaml
+/// actions:
+///   ensure_header:
+///     template: .claude/header.txt
+/// ```
+///
+/// `{year}` in the template matches any four-digit year. A leading shebang
+/// line in the written file is skipped before comparing.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EnsureHeader {
+    /// Path to the header template
+    pub template: String,
+}
+
+impl EnsureHeader {
+    /// Check whether `content` starts with the header in `template`
+    ///
+    /// Lines are compared with trailing whitespace ignored.
+    pub fn is_satisfied_by(template: &str, content: &str) -> bool {
+        let mut lines = content.lines().peekable();
+        if lines.peek().is_some_and(|l| l.starts_with("#!")) {
+            lines.next();
+        }
+
+        let expected = template.trim_end_matches(['\n', '\r']).lines();
+        for expected_line in expected {
+            let Some(line) = lines.next() else {
+                return false;
+            };
+            if !header_line_matches(expected_line.trim_end(), line.trim_end()) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Compare one header line, treating `{year}` as any four-digit year
+fn header_line_matches(expected: &str, actual: &str) -> bool {
+    let mut parts = expected.split("{year}");
+    let Some(first) = parts.next() else {
+        return actual.is_empty();
+    };
+    let Some(mut rest) = actual.strip_prefix(first) else {
+        return false;
+    };
+    for part in parts {
+        let year_len = rest.chars().take_while(char::is_ascii_digit).count();
+        if year_len != 4 {
+            return false;
+        }
+        match rest[year_len..].strip_prefix(part) {
+            Some(remaining) => rest = remaining,
+            None => return false,
+        }
+    }
+    rest.is_empty()
+}
+
+/// Largest char boundary at or below `index`
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    (0..=index.min(s.len()))
+        .rev()
+        .find(|&i| s.is_char_boundary(i))
+        .unwrap_or(0)
+}
+
+/// Smallest char boundary at or above `index`
+fn ceil_char_boundary(s: &str, index: usize) -> usize {
+    (index.min(s.len())..=s.len())
+        .find(|&i| s.is_char_boundary(i))
+        .unwrap_or(s.len())
 }
 
 /// Additional rule metadata
@@ -328,6 +1382,50 @@ pub struct RuleMetadata {
     pub enabled: bool,
 }
 
+/// Inline expectation for a rule, colocated with the rule in hooks.yaml
+///
+/// ```yaml
+/// tests:
+///   - command: "git push --force origin main"
+///     match: true
+///   - tool: Write
+///     path: src/main.rs
+///     match: false
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RuleTest {
+    /// Event type to simulate (defaults to PreToolUse)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event: Option<String>,
+
+    /// Tool name (defaults to Bash for commands, Write for paths)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+
+    /// Command to test (for Bash/Glob/Grep)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+
+    /// File path to test (for Write/Edit/Read)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+
+    /// Whether the rule is expected to match
+    #[serde(rename = "match")]
+    pub expect_match: bool,
+}
+
+impl RuleTest {
+    /// Tool to simulate, inferred from the provided inputs when not explicit
+    pub fn effective_tool(&self) -> &str {
+        match (&self.tool, &self.command, &self.path) {
+            (Some(tool), _, _) => tool,
+            (None, None, Some(_)) => "Write",
+            _ => "Bash",
+        }
+    }
+}
+
 #[cfg(test)]
 mod governance_tests {
     use super::*;
@@ -519,6 +1617,19 @@ script: .claude/validators/check.py
         assert_eq!(action.trust_level(), TrustLevel::Local); // Default
     }
 
+    #[test]
+    fn test_run_action_wasm() {
+        let yaml = r"
+wasm: .claude/validators/check.wasm
+cacheable: true
+";
+        let action: RunAction = serde_yaml::from_str(yaml).unwrap();
+        assert!(action.is_wasm());
+        assert!(action.is_cacheable());
+        assert_eq!(action.script_path(), ".claude/validators/check.wasm");
+        assert_eq!(action.env().count(), 0);
+    }
+
     #[test]
     fn test_actions_with_run_simple() {
         let yaml = r"
@@ -529,6 +1640,27 @@ run: .claude/validators/test.sh
         assert_eq!(actions.trust_level(), Some(TrustLevel::Local));
     }
 
+    #[test]
+    fn test_actions_with_inject_list() {
+        let yaml = r"
+inject:
+  - .claude/context/security.md
+  - file: docs/api.md
+    header: API Guidelines
+";
+        let actions: Actions = serde_yaml::from_str(yaml).unwrap();
+        let inject = actions.inject.as_ref().unwrap();
+        assert_eq!(inject.sources().len(), 2);
+        assert_eq!(inject.sources()[1].header(), Some("API Guidelines"));
+        assert_eq!(
+            actions.inject_paths().collect::<Vec<_>>(),
+            vec![".claude/context/security.md", "docs/api.md"]
+        );
+
+        let single: Actions = serde_yaml::from_str("inject: .claude/context.md").unwrap();
+        assert_eq!(single.inject.unwrap().to_string(), ".claude/context.md");
+    }
+
     #[test]
     fn test_actions_with_run_extended() {
         let yaml = r"
@@ -542,13 +1674,120 @@ run:
     }
 
     #[test]
-    fn test_actions_without_run() {
-        let yaml = r"
-block: true
-";
-        let actions: Actions = serde_yaml::from_str(yaml).unwrap();
-        assert_eq!(actions.script_path(), None);
-        assert_eq!(actions.trust_level(), None);
+    fn test_actions_without_run() {
+        let yaml = r"
+block: true
+";
+        let actions: Actions = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(actions.script_path(), None);
+        assert_eq!(actions.trust_level(), None);
+    }
+
+    // =========================================================================
+    // TruncateOutput Tests
+    // =========================================================================
+
+    fn numbered_lines(n: usize) -> String {
+        (1..=n)
+            .map(|i| format!("line {}", i))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_ensure_header_matches_year_placeholder() {
+        let template = "// Copyright {year} Acme Corp\n// SPDX-License-Identifier: MIT\n";
+        assert!(EnsureHeader::is_satisfied_by(
+            template,
+            "// Copyright 2026 Acme Corp\n// SPDX-License-Identifier: MIT\n\nfn main() {}\n"
+        ));
+        assert!(EnsureHeader::is_satisfied_by(
+            "# Copyright {year} Acme Corp\n",
+            "#!/usr/bin/env python3\n# Copyright 2025 Acme Corp\n"
+        ));
+        assert!(!EnsureHeader::is_satisfied_by(
+            template,
+            "// Copyright 26 Acme Corp\n// SPDX-License-Identifier: MIT\n"
+        ));
+        assert!(!EnsureHeader::is_satisfied_by(template, "fn main() {}\n"));
+        assert!(!EnsureHeader::is_satisfied_by(template, ""));
+    }
+
+    #[test]
+    fn test_truncate_output_within_limits() {
+        let truncate = TruncateOutput {
+            max_lines: Some(10),
+            max_bytes: Some(1000),
+            strategy: TruncateStrategy::HeadTail,
+        };
+        assert_eq!(truncate.apply(&numbered_lines(5)), None);
+    }
+
+    #[test]
+    fn test_truncate_output_head_tail_lines() {
+        let truncate = TruncateOutput {
+            max_lines: Some(4),
+            max_bytes: None,
+            strategy: TruncateStrategy::HeadTail,
+        };
+        let result = truncate.apply(&numbered_lines(10)).unwrap();
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines[0], "line 1");
+        assert_eq!(lines[1], "line 2");
+        assert_eq!(lines[2], "... [6 lines truncated by CCH] ...");
+        assert_eq!(lines[3], "line 9");
+        assert_eq!(lines[4], "line 10");
+    }
+
+    #[test]
+    fn test_truncate_output_tail_lines() {
+        let truncate = TruncateOutput {
+            max_lines: Some(2),
+            max_bytes: None,
+            strategy: TruncateStrategy::Tail,
+        };
+        let result = truncate.apply(&numbered_lines(5)).unwrap();
+        assert_eq!(result, "... [3 lines truncated by CCH] ...\nline 4\nline 5");
+    }
+
+    #[test]
+    fn test_truncate_output_head_bytes_respects_char_boundary() {
+        let truncate = TruncateOutput {
+            max_lines: None,
+            max_bytes: Some(3),
+            strategy: TruncateStrategy::Head,
+        };
+        let result = truncate.apply("héllo").unwrap();
+        assert!(result.starts_with("hé"));
+        assert!(result.contains("bytes truncated by CCH"));
+    }
+
+    #[test]
+    fn test_truncate_output_yaml_default_strategy() {
+        let truncate: TruncateOutput = serde_yaml::from_str("max_lines: 50").unwrap();
+        assert_eq!(truncate.max_lines, Some(50));
+        assert_eq!(truncate.strategy, TruncateStrategy::HeadTail);
+    }
+
+    #[test]
+    fn test_rewrite_input_replaces_and_appends_once() {
+        let append: RewriteInput = serde_yaml::from_str(r#"append: " --dry-run""#).unwrap();
+        assert_eq!(append.field, "command");
+        assert_eq!(
+            append.apply("terraform apply").as_deref(),
+            Some("terraform apply --dry-run")
+        );
+        assert_eq!(append.apply("terraform apply --dry-run"), None);
+
+        let trash: RewriteInput = serde_yaml::from_str(
+            "pattern: '^rm -rf (.+)$'\nreplace: 'mkdir -p .trash && mv $1 .trash/'",
+        )
+        .unwrap();
+        assert_eq!(
+            trash.apply("rm -rf build").as_deref(),
+            Some("mkdir -p .trash && mv build .trash/")
+        );
+        assert_eq!(trash.apply("ls"), None);
     }
 
     // =========================================================================
@@ -614,6 +1853,54 @@ reason: Code quality
         assert!(meta.confidence.is_none());
     }
 
+    // =========================================================================
+    // Zone Tests
+    // =========================================================================
+
+    fn zone(path: &str, level: Sensitivity) -> Zone {
+        Zone {
+            path: path.to_string(),
+            level,
+        }
+    }
+
+    #[test]
+    fn test_sensitivity_ordering() {
+        assert!(Sensitivity::Secret > Sensitivity::Internal);
+        assert!(Sensitivity::Internal > Sensitivity::Public);
+        assert_eq!(Sensitivity::default(), Sensitivity::Public);
+    }
+
+    #[test]
+    fn test_zone_contains_relative_and_absolute() {
+        let z = zone("secrets/**", Sensitivity::Secret);
+        assert!(z.contains("secrets/prod.env", None));
+        assert!(z.contains("./secrets/prod.env", None));
+        assert!(z.contains(
+            "/home/me/project/secrets/prod.env",
+            Some("/home/me/project")
+        ));
+        assert!(!z.contains("src/secrets.rs", None));
+        assert!(!z.contains("/home/me/project/src/main.rs", Some("/home/me/project")));
+    }
+
+    #[test]
+    fn test_zone_level_most_sensitive_wins() {
+        let zones = vec![
+            zone("docs", Sensitivity::Internal),
+            zone("docs/keys", Sensitivity::Secret),
+        ];
+        assert_eq!(
+            zone_level(&zones, "docs/guide.md", None),
+            Sensitivity::Internal
+        );
+        assert_eq!(
+            zone_level(&zones, "docs/keys/id_rsa", None),
+            Sensitivity::Secret
+        );
+        assert_eq!(zone_level(&zones, "src/lib.rs", None), Sensitivity::Public);
+    }
+
     // =========================================================================
     // Rule Governance Field Tests
     // =========================================================================
@@ -629,16 +1916,49 @@ reason: Code quality
                 directories: None,
                 operations: None,
                 command_match: None,
+                zone: None,
+                exclude_tools: None,
+                exclude_extensions: None,
+                exclude_directories: None,
+                command_not_match: None,
+                tool_output_match: None,
+                exit_code: None,
+                prompt_match: None,
+                background: None,
+                protected_paths: None,
+                expr: None,
+                any: None,
+                all: None,
+                not: None,
+                policy: None,
             },
             actions: Actions {
                 inject: None,
                 run: None,
                 block: None,
                 block_if_match: None,
+                truncate_output: None,
+                filter_results: None,
+                structural_checks: None,
+                ensure_header: None,
+                inject_command: None,
+                ask: None,
+                require_files: None,
+                allow: None,
+                inject_once_per_session: None,
+                throttle: None,
+                enrich_prompt: None,
+                rewrite: None,
             },
             mode: None,
             priority: None,
             governance: None,
+            tests: None,
+            exemptions: None,
+            escalation: None,
+            bypass_users: None,
+            messages: None,
+            stop: None,
             metadata: None,
         };
         assert_eq!(rule.effective_mode(), PolicyMode::Enforce);
@@ -655,16 +1975,49 @@ reason: Code quality
                 directories: None,
                 operations: None,
                 command_match: None,
+                zone: None,
+                exclude_tools: None,
+                exclude_extensions: None,
+                exclude_directories: None,
+                command_not_match: None,
+                tool_output_match: None,
+                exit_code: None,
+                prompt_match: None,
+                background: None,
+                protected_paths: None,
+                expr: None,
+                any: None,
+                all: None,
+                not: None,
+                policy: None,
             },
             actions: Actions {
                 inject: None,
                 run: None,
                 block: None,
                 block_if_match: None,
+                truncate_output: None,
+                filter_results: None,
+                structural_checks: None,
+                ensure_header: None,
+                inject_command: None,
+                ask: None,
+                require_files: None,
+                allow: None,
+                inject_once_per_session: None,
+                throttle: None,
+                enrich_prompt: None,
+                rewrite: None,
             },
             mode: Some(PolicyMode::Audit),
             priority: None,
             governance: None,
+            tests: None,
+            exemptions: None,
+            escalation: None,
+            bypass_users: None,
+            messages: None,
+            stop: None,
             metadata: None,
         };
         assert_eq!(rule.effective_mode(), PolicyMode::Audit);
@@ -681,16 +2034,49 @@ reason: Code quality
                 directories: None,
                 operations: None,
                 command_match: None,
+                zone: None,
+                exclude_tools: None,
+                exclude_extensions: None,
+                exclude_directories: None,
+                command_not_match: None,
+                tool_output_match: None,
+                exit_code: None,
+                prompt_match: None,
+                background: None,
+                protected_paths: None,
+                expr: None,
+                any: None,
+                all: None,
+                not: None,
+                policy: None,
             },
             actions: Actions {
                 inject: None,
                 run: None,
                 block: None,
                 block_if_match: None,
+                truncate_output: None,
+                filter_results: None,
+                structural_checks: None,
+                ensure_header: None,
+                inject_command: None,
+                ask: None,
+                require_files: None,
+                allow: None,
+                inject_once_per_session: None,
+                throttle: None,
+                enrich_prompt: None,
+                rewrite: None,
             },
             mode: None,
             priority: None,
             governance: None,
+            tests: None,
+            exemptions: None,
+            escalation: None,
+            bypass_users: None,
+            messages: None,
+            stop: None,
             metadata: None,
         };
         assert_eq!(rule.effective_priority(), 0);
@@ -707,16 +2093,49 @@ reason: Code quality
                 directories: None,
                 operations: None,
                 command_match: None,
+                zone: None,
+                exclude_tools: None,
+                exclude_extensions: None,
+                exclude_directories: None,
+                command_not_match: None,
+                tool_output_match: None,
+                exit_code: None,
+                prompt_match: None,
+                background: None,
+                protected_paths: None,
+                expr: None,
+                any: None,
+                all: None,
+                not: None,
+                policy: None,
             },
             actions: Actions {
                 inject: None,
                 run: None,
                 block: None,
                 block_if_match: None,
+                truncate_output: None,
+                filter_results: None,
+                structural_checks: None,
+                ensure_header: None,
+                inject_command: None,
+                ask: None,
+                require_files: None,
+                allow: None,
+                inject_once_per_session: None,
+                throttle: None,
+                enrich_prompt: None,
+                rewrite: None,
             },
             mode: None,
             priority: Some(100),
             governance: None,
+            tests: None,
+            exemptions: None,
+            escalation: None,
+            bypass_users: None,
+            messages: None,
+            stop: None,
             metadata: None,
         };
         assert_eq!(rule.effective_priority(), 100);
@@ -733,16 +2152,49 @@ reason: Code quality
                 directories: None,
                 operations: None,
                 command_match: None,
+                zone: None,
+                exclude_tools: None,
+                exclude_extensions: None,
+                exclude_directories: None,
+                command_not_match: None,
+                tool_output_match: None,
+                exit_code: None,
+                prompt_match: None,
+                background: None,
+                protected_paths: None,
+                expr: None,
+                any: None,
+                all: None,
+                not: None,
+                policy: None,
             },
             actions: Actions {
                 inject: None,
                 run: None,
                 block: None,
                 block_if_match: None,
+                truncate_output: None,
+                filter_results: None,
+                structural_checks: None,
+                ensure_header: None,
+                inject_command: None,
+                ask: None,
+                require_files: None,
+                allow: None,
+                inject_once_per_session: None,
+                throttle: None,
+                enrich_prompt: None,
+                rewrite: None,
             },
             mode: None,
             priority: None,
             governance: None,
+            tests: None,
+            exemptions: None,
+            escalation: None,
+            bypass_users: None,
+            messages: None,
+            stop: None,
             metadata: Some(RuleMetadata {
                 priority: 50,
                 timeout: 5,
@@ -763,16 +2215,49 @@ reason: Code quality
                 directories: None,
                 operations: None,
                 command_match: None,
+                zone: None,
+                exclude_tools: None,
+                exclude_extensions: None,
+                exclude_directories: None,
+                command_not_match: None,
+                tool_output_match: None,
+                exit_code: None,
+                prompt_match: None,
+                background: None,
+                protected_paths: None,
+                expr: None,
+                any: None,
+                all: None,
+                not: None,
+                policy: None,
             },
             actions: Actions {
                 inject: None,
                 run: None,
                 block: None,
                 block_if_match: None,
+                truncate_output: None,
+                filter_results: None,
+                structural_checks: None,
+                ensure_header: None,
+                inject_command: None,
+                ask: None,
+                require_files: None,
+                allow: None,
+                inject_once_per_session: None,
+                throttle: None,
+                enrich_prompt: None,
+                rewrite: None,
             },
             mode: None,
             priority: Some(100), // New field takes precedence
             governance: None,
+            tests: None,
+            exemptions: None,
+            escalation: None,
+            bypass_users: None,
+            messages: None,
+            stop: None,
             metadata: Some(RuleMetadata {
                 priority: 50, // Legacy field
                 timeout: 5,
@@ -847,16 +2332,49 @@ reason: Code quality
                 directories: None,
                 operations: None,
                 command_match: None,
+                zone: None,
+                exclude_tools: None,
+                exclude_extensions: None,
+                exclude_directories: None,
+                command_not_match: None,
+                tool_output_match: None,
+                exit_code: None,
+                prompt_match: None,
+                background: None,
+                protected_paths: None,
+                expr: None,
+                any: None,
+                all: None,
+                not: None,
+                policy: None,
             },
             actions: Actions {
                 inject: None,
                 run: None,
                 block: None,
                 block_if_match: None,
+                truncate_output: None,
+                filter_results: None,
+                structural_checks: None,
+                ensure_header: None,
+                inject_command: None,
+                ask: None,
+                require_files: None,
+                allow: None,
+                inject_once_per_session: None,
+                throttle: None,
+                enrich_prompt: None,
+                rewrite: None,
             },
             mode: None,
             priority: Some(priority),
             governance: None,
+            tests: None,
+            exemptions: None,
+            escalation: None,
+            bypass_users: None,
+            messages: None,
+            stop: None,
             metadata: None,
         }
     }
@@ -900,6 +2418,31 @@ governance:
         );
     }
 
+    #[test]
+    fn test_rule_with_inline_tests_yaml() {
+        let yaml = r#"
+name: block-force-push
+matchers:
+  tools: [Bash]
+  command_match: "git push.*--force"
+actions:
+  block: true
+tests:
+  - command: "git push --force origin main"
+    match: true
+  - path: src/main.rs
+    match: false
+"#;
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        let tests = rule.tests.unwrap();
+
+        assert_eq!(tests.len(), 2);
+        assert!(tests[0].expect_match);
+        assert_eq!(tests[0].effective_tool(), "Bash");
+        assert!(!tests[1].expect_match);
+        assert_eq!(tests[1].effective_tool(), "Write");
+    }
+
     #[test]
     fn test_rule_backward_compatible_yaml() {
         // This is an existing v1.0 config format - must still work
@@ -943,10 +2486,188 @@ mod event_details_tests {
             cwd: None,
             permission_mode: None,
             tool_use_id: None,
+            prompt: None,
+            legacy_input_keys: false,
+            tool_response: None,
         };
 
         let details = EventDetails::extract(&event);
-        assert!(matches!(details, EventDetails::Bash { command } if command == "git push --force"));
+        assert!(
+            matches!(details, EventDetails::Bash { command, run_in_background: false } if command == "git push --force")
+        );
+    }
+
+    #[test]
+    fn test_event_accepts_every_historical_spelling() {
+        let canonical: Event = serde_json::from_value(serde_json::json!({
+            "hook_event_name": "PreToolUse",
+            "tool_name": "MultiEdit",
+            "tool_input": {
+                "file_path": "src/lib.rs",
+                "edits": [{"old_string": "a", "new_string": "b", "replace_all": true}]
+            },
+            "session_id": "s",
+            "user_id": "u",
+            "transcript_path": "/t",
+            "permission_mode": "default",
+            "tool_use_id": "t1",
+            "tool_response": "ok"
+        }))
+        .unwrap();
+
+        for legacy in [
+            serde_json::json!({
+                "event_type": "PreToolUse",
+                "tool_name": "MultiEdit",
+                "tool_input": {
+                    "filePath": "src/lib.rs",
+                    "edits": [{"oldString": "a", "newString": "b", "replaceAll": true}]
+                },
+                "session_id": "s",
+                "user_id": "u",
+                "transcript_path": "/t",
+                "permission_mode": "default",
+                "tool_use_id": "t1",
+                "tool_response": "ok"
+            }),
+            serde_json::json!({
+                "hookEventName": "PreToolUse",
+                "toolName": "MultiEdit",
+                "toolInput": {
+                    "filePath": "src/lib.rs",
+                    "edits": [{"oldString": "a", "new_string": "b", "replaceAll": true}]
+                },
+                "sessionId": "s",
+                "userId": "u",
+                "transcriptPath": "/t",
+                "permissionMode": "default",
+                "toolUseId": "t1",
+                "toolResponse": "ok"
+            }),
+        ] {
+            let mut event: Event = serde_json::from_value(legacy).unwrap();
+            event.timestamp = canonical.timestamp;
+            assert_eq!(event, canonical);
+        }
+    }
+
+    #[test]
+    fn test_field_spellings() {
+        let raw = serde_json::json!({
+            "eventType": "PreToolUse",
+            "tool_name": "MultiEdit",
+            "toolInput": {
+                "filePath": "src/lib.rs",
+                "edits": [{"oldString": "a", "new_string": "b"}]
+            },
+            "session_id": "s"
+        });
+        let pairs = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .map(|(field, spelling)| (field.to_string(), spelling.to_string()))
+                .collect()
+        };
+        assert_eq!(
+            field_spellings(&raw),
+            pairs(&[
+                ("hook_event_name", "eventType"),
+                ("tool_name", "tool_name"),
+                ("tool_input", "toolInput"),
+                ("session_id", "session_id"),
+                ("tool_input.new_string", "new_string"),
+                ("tool_input.old_string", "oldString"),
+                ("tool_input.file_path", "filePath"),
+            ])
+        );
+
+        // Every listed alias is one the event accepts
+        for (canonical, aliases) in EVENT_FIELD_ALIASES {
+            for alias in *aliases {
+                let mut raw = serde_json::json!({
+                    "hook_event_name": "Stop",
+                    "session_id": "s",
+                    "tool_response": "ok"
+                });
+                let value = raw[*canonical].take();
+                let value = if value.is_null() {
+                    serde_json::json!("x")
+                } else {
+                    value
+                };
+                raw.as_object_mut().unwrap().remove(*canonical);
+                raw[*alias] = value;
+                let event: Event = serde_json::from_value(raw).unwrap();
+                let event = serde_json::to_value(event).unwrap();
+                assert!(!event[*canonical].is_null(), "{} -> {}", alias, canonical);
+            }
+        }
+    }
+
+    #[test]
+    fn test_normalize_prefers_canonical_key() {
+        let input = normalize_tool_input(serde_json::json!({
+            "filePath": "legacy.rs",
+            "file_path": "canonical.rs",
+            "notebookPath": "a.ipynb"
+        }));
+        assert_eq!(
+            input,
+            serde_json::json!({"file_path": "canonical.rs", "notebook_path": "a.ipynb"})
+        );
+    }
+
+    #[test]
+    fn test_script_payload_carries_both_spellings() {
+        let (event, _) = Event::from_agent_payload(serde_json::json!({
+            "hook_event_name": "PreToolUse",
+            "session_id": "s",
+            "tool_name": "MultiEdit",
+            "tool_input": {
+                "file_path": "a.rs",
+                "edits": [{"old_string": "a", "newString": "b"}]
+            }
+        }))
+        .unwrap();
+        let payload = event.script_payload();
+        assert_eq!(payload["tool_input"]["file_path"], "a.rs");
+        assert_eq!(payload["tool_input"]["filePath"], "a.rs");
+        let edit = &payload["tool_input"]["edits"][0];
+        assert_eq!(edit["new_string"], "b");
+        assert_eq!(edit["newString"], "b");
+        assert_eq!(edit["oldString"], "a");
+    }
+
+    #[test]
+    fn test_update_input_keeps_client_spelling() {
+        let event = |tool_input: serde_json::Value| {
+            Event::from_agent_payload(serde_json::json!({
+                "hook_event_name": "PreToolUse",
+                "session_id": "s",
+                "tool_name": "Write",
+                "tool_input": tool_input
+            }))
+            .unwrap()
+            .0
+        };
+        let rewritten = serde_json::json!({"file_path": "b.rs", "content": "x"});
+        let updated = |event: &Event| {
+            Response::update_input(rewritten.clone(), event)
+                .updated_input()
+                .cloned()
+                .unwrap()
+        };
+
+        let canonical = event(serde_json::json!({"file_path": "a.rs", "content": "x"}));
+        assert!(!canonical.legacy_input_keys);
+        assert_eq!(updated(&canonical), rewritten);
+
+        let legacy = event(serde_json::json!({"filePath": "a.rs", "content": "x"}));
+        assert!(legacy.legacy_input_keys);
+        assert_eq!(
+            updated(&legacy),
+            serde_json::json!({"filePath": "b.rs", "content": "x"})
+        );
     }
 
     #[test]
@@ -954,9 +2675,9 @@ mod event_details_tests {
         let event = Event {
             hook_event_name: EventType::PreToolUse,
             tool_name: Some("Write".to_string()),
-            tool_input: Some(serde_json::json!({
+            tool_input: Some(normalize_tool_input(serde_json::json!({
                 "filePath": "/path/to/file.rs"
-            })),
+            }))),
             session_id: "test-session".to_string(),
             timestamp: Utc::now(),
             user_id: None,
@@ -964,6 +2685,9 @@ mod event_details_tests {
             cwd: None,
             permission_mode: None,
             tool_use_id: None,
+            prompt: None,
+            legacy_input_keys: false,
+            tool_response: None,
         };
 
         let details = EventDetails::extract(&event);
@@ -987,6 +2711,9 @@ mod event_details_tests {
             cwd: None,
             permission_mode: None,
             tool_use_id: None,
+            prompt: None,
+            legacy_input_keys: false,
+            tool_response: None,
         };
 
         let details = EventDetails::extract(&event);
@@ -1000,9 +2727,9 @@ mod event_details_tests {
         let event = Event {
             hook_event_name: EventType::PreToolUse,
             tool_name: Some("Edit".to_string()),
-            tool_input: Some(serde_json::json!({
+            tool_input: Some(normalize_tool_input(serde_json::json!({
                 "filePath": "/path/to/file.rs"
-            })),
+            }))),
             session_id: "test-session".to_string(),
             timestamp: Utc::now(),
             user_id: None,
@@ -1010,6 +2737,9 @@ mod event_details_tests {
             cwd: None,
             permission_mode: None,
             tool_use_id: None,
+            prompt: None,
+            legacy_input_keys: false,
+            tool_response: None,
         };
 
         let details = EventDetails::extract(&event);
@@ -1018,14 +2748,57 @@ mod event_details_tests {
         );
     }
 
+    #[test]
+    fn test_extract_multi_edit_event() {
+        let event = Event {
+            hook_event_name: EventType::PreToolUse,
+            tool_name: Some("MultiEdit".to_string()),
+            tool_input: Some(serde_json::json!({
+                "file_path": "src/lib.rs",
+                "edits": [
+                    {"old_string": "a", "new_string": "bcd"},
+                    {"file_path": "src/main.rs", "old_string": "x", "new_string": ""}
+                ]
+            })),
+            session_id: "test-session".to_string(),
+            timestamp: Utc::now(),
+            user_id: None,
+            transcript_path: None,
+            cwd: None,
+            permission_mode: None,
+            tool_use_id: None,
+            prompt: None,
+            legacy_input_keys: false,
+            tool_response: None,
+        };
+
+        let details = EventDetails::extract(&event);
+        assert_eq!(
+            details,
+            EventDetails::MultiEdit {
+                file_path: "src/lib.rs".to_string(),
+                edits: vec![
+                    EditDetails {
+                        file_path: "src/lib.rs".to_string(),
+                        content_length: 3,
+                    },
+                    EditDetails {
+                        file_path: "src/main.rs".to_string(),
+                        content_length: 0,
+                    },
+                ],
+            }
+        );
+    }
+
     #[test]
     fn test_extract_read_event() {
         let event = Event {
             hook_event_name: EventType::PreToolUse,
             tool_name: Some("Read".to_string()),
-            tool_input: Some(serde_json::json!({
+            tool_input: Some(normalize_tool_input(serde_json::json!({
                 "filePath": "/path/to/file.rs"
-            })),
+            }))),
             session_id: "test-session".to_string(),
             timestamp: Utc::now(),
             user_id: None,
@@ -1033,6 +2806,9 @@ mod event_details_tests {
             cwd: None,
             permission_mode: None,
             tool_use_id: None,
+            prompt: None,
+            legacy_input_keys: false,
+            tool_response: None,
         };
 
         let details = EventDetails::extract(&event);
@@ -1057,6 +2833,9 @@ mod event_details_tests {
             cwd: None,
             permission_mode: None,
             tool_use_id: None,
+            prompt: None,
+            legacy_input_keys: false,
+            tool_response: None,
         };
 
         let details = EventDetails::extract(&event);
@@ -1080,6 +2859,9 @@ mod event_details_tests {
             cwd: None,
             permission_mode: None,
             tool_use_id: None,
+            prompt: None,
+            legacy_input_keys: false,
+            tool_response: None,
         };
 
         let details = EventDetails::extract(&event);
@@ -1105,6 +2887,9 @@ mod event_details_tests {
             cwd: None,
             permission_mode: None,
             tool_use_id: None,
+            prompt: None,
+            legacy_input_keys: false,
+            tool_response: None,
         };
 
         let details = EventDetails::extract(&event);
@@ -1130,6 +2915,9 @@ mod event_details_tests {
             cwd: None,
             permission_mode: None,
             tool_use_id: None,
+            prompt: None,
+            legacy_input_keys: false,
+            tool_response: None,
         };
 
         let details = EventDetails::extract(&event);
@@ -1137,6 +2925,50 @@ mod event_details_tests {
             if tool_name == Some("FutureTool".to_string())));
     }
 
+    #[test]
+    fn test_extract_background_shell_tools() {
+        let event = |tool_name: &str, tool_input| Event {
+            hook_event_name: EventType::PreToolUse,
+            tool_name: Some(tool_name.to_string()),
+            tool_input: Some(tool_input),
+            session_id: "test-session".to_string(),
+            timestamp: Utc::now(),
+            user_id: None,
+            transcript_path: None,
+            cwd: None,
+            permission_mode: None,
+            tool_use_id: None,
+            prompt: None,
+            legacy_input_keys: false,
+            tool_response: None,
+        };
+
+        let bash = event(
+            "Bash",
+            serde_json::json!({ "command": "npm run dev", "run_in_background": true }),
+        );
+        assert!(bash.background_shell());
+        assert!(matches!(EventDetails::extract(&bash),
+            EventDetails::Bash { command, run_in_background: true } if command == "npm run dev"));
+
+        let output = event(
+            "BashOutput",
+            serde_json::json!({ "bash_id": "bash_1", "filter": "ERROR" }),
+        );
+        assert!(output.background_shell());
+        assert!(matches!(EventDetails::extract(&output),
+            EventDetails::BashOutput { shell_id, filter }
+            if shell_id.as_deref() == Some("bash_1") && filter.as_deref() == Some("ERROR")));
+
+        let kill = event("KillShell", serde_json::json!({ "shell_id": "bash_1" }));
+        assert!(kill.background_shell());
+        assert!(matches!(EventDetails::extract(&kill),
+            EventDetails::KillShell { shell_id } if shell_id.as_deref() == Some("bash_1")));
+
+        let foreground = event("Bash", serde_json::json!({ "command": "ls" }));
+        assert!(!foreground.background_shell());
+    }
+
     #[test]
     fn test_response_summary_from_response() {
         let response = Response {
@@ -1144,6 +2976,8 @@ mod event_details_tests {
             context: Some("injected context".to_string()),
             reason: Some("for testing".to_string()),
             timing: None,
+            hook_specific_output: None,
+            system_message: None,
         };
 
         let summary = ResponseSummary::from_response(&response);
@@ -1176,26 +3010,204 @@ fn default_enabled() -> bool {
     true
 }
 
+/// Legacy camelCase tool input keys and their canonical snake_case spelling
+const TOOL_INPUT_ALIASES: &[(&str, &str)] = &[
+    ("filePath", "file_path"),
+    ("oldString", "old_string"),
+    ("newString", "new_string"),
+    ("replaceAll", "replace_all"),
+    ("notebookPath", "notebook_path"),
+    ("newSource", "new_source"),
+    ("cellId", "cell_id"),
+];
+
+/// Legacy spellings of event fields, as accepted by the serde aliases on
+/// [`Event`]
+pub const EVENT_FIELD_ALIASES: &[(&str, &[&str])] = &[
+    (
+        "hook_event_name",
+        &["event_type", "hookEventName", "eventType"],
+    ),
+    ("tool_name", &["toolName"]),
+    ("tool_input", &["toolInput"]),
+    ("session_id", &["sessionId"]),
+    ("user_id", &["userId"]),
+    ("transcript_path", &["transcriptPath"]),
+    ("permission_mode", &["permissionMode"]),
+    ("tool_use_id", &["toolUseId"]),
+    ("tool_response", &["toolResponse"]),
+];
+
+/// Spellings of aliased fields used by a raw event, as
+/// `(canonical field, spelling)` pairs
+///
+/// Tool input keys are reported as `tool_input.<key>`, at any depth.
+pub fn field_spellings(raw: &serde_json::Value) -> Vec<(String, String)> {
+    let mut spellings = Vec::new();
+    let Some(map) = raw.as_object() else {
+        return spellings;
+    };
+    for (canonical, aliases) in EVENT_FIELD_ALIASES {
+        for spelling in std::iter::once(canonical).chain(aliases.iter()) {
+            if map.contains_key(*spelling) {
+                spellings.push(((*canonical).to_string(), (*spelling).to_string()));
+            }
+        }
+    }
+    if let Some(tool_input) = map.get("tool_input").or_else(|| map.get("toolInput")) {
+        tool_input_spellings(tool_input, &mut spellings);
+    }
+    spellings
+}
+
+fn tool_input_spellings(value: &serde_json::Value, spellings: &mut Vec<(String, String)>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                let canonical = TOOL_INPUT_ALIASES
+                    .iter()
+                    .find(|(alias, canonical)| key == alias || key == canonical)
+                    .map(|(_, canonical)| *canonical);
+                if let Some(canonical) = canonical {
+                    spellings.push((format!("tool_input.{}", canonical), key.clone()));
+                }
+                tool_input_spellings(value, spellings);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                tool_input_spellings(item, spellings);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Rewrite legacy tool input keys to their canonical spelling
+///
+/// Applies recursively so nested inputs like `MultiEdit` `edits` are covered.
+/// When both spellings are present the canonical key wins.
+pub fn normalize_tool_input(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut normalized = serde_json::Map::with_capacity(map.len());
+            for (key, value) in map {
+                let canonical = TOOL_INPUT_ALIASES
+                    .iter()
+                    .find(|(alias, _)| *alias == key)
+                    .map(|(_, canonical)| (*canonical).to_string());
+                let value = normalize_tool_input(value);
+                match canonical {
+                    Some(canonical) => {
+                        normalized.entry(canonical).or_insert(value);
+                    }
+                    None => {
+                        normalized.insert(key, value);
+                    }
+                }
+            }
+            serde_json::Value::Object(normalized)
+        }
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.into_iter().map(normalize_tool_input).collect())
+        }
+        other => other,
+    }
+}
+
+/// Whether a raw tool input uses any legacy camelCase key, at any depth
+fn has_legacy_tool_input_keys(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Object(map) => map.iter().any(|(key, value)| {
+            TOOL_INPUT_ALIASES.iter().any(|(alias, _)| alias == key)
+                || has_legacy_tool_input_keys(value)
+        }),
+        serde_json::Value::Array(items) => items.iter().any(has_legacy_tool_input_keys),
+        _ => false,
+    }
+}
+
+/// Copy each canonical tool input key to its legacy camelCase alias, at any
+/// depth, keeping the canonical key too
+pub fn with_legacy_tool_input_keys(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut aliased = serde_json::Map::with_capacity(map.len());
+            for (key, value) in map {
+                let value = with_legacy_tool_input_keys(value);
+                if let Some((alias, _)) = TOOL_INPUT_ALIASES
+                    .iter()
+                    .find(|(_, canonical)| *canonical == key)
+                {
+                    aliased.insert((*alias).to_string(), value.clone());
+                }
+                aliased.insert(key, value);
+            }
+            serde_json::Value::Object(aliased)
+        }
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.into_iter().map(with_legacy_tool_input_keys).collect())
+        }
+        other => other,
+    }
+}
+
+/// Rename canonical tool input keys back to their legacy camelCase spelling
+fn legacy_tool_input(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => map
+            .into_iter()
+            .map(|(key, value)| {
+                let key = TOOL_INPUT_ALIASES
+                    .iter()
+                    .find(|(_, canonical)| *canonical == key)
+                    .map_or(key, |(alias, _)| (*alias).to_string());
+                (key, legacy_tool_input(value))
+            })
+            .collect(),
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.into_iter().map(legacy_tool_input).collect())
+        }
+        other => other,
+    }
+}
+
+fn deserialize_tool_input<'de, D>(deserializer: D) -> Result<Option<serde_json::Value>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(value.map(normalize_tool_input))
+}
+
 /// Claude Code hook event data structure
 ///
-/// Claude Code sends events with `hook_event_name` as the field name.
-/// The `alias = "event_type"` preserves backward compatibility with
-/// debug commands and tests that use the old field name.
+/// Claude Code sends snake_case fields with `hook_event_name` as the event
+/// type. Older payloads, debug commands, and fixtures use `event_type` or
+/// camelCase spellings; serde aliases and [`normalize_tool_input`] map every
+/// historical spelling to this one canonical shape at deserialization, so
+/// matchers and extractors only ever see snake_case keys.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Event {
     /// Hook event type (Claude Code sends as `hook_event_name`)
-    #[serde(alias = "event_type")]
+    #[serde(alias = "event_type", alias = "hookEventName", alias = "eventType")]
     pub hook_event_name: EventType,
 
     /// Name of the tool being used
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "toolName", skip_serializing_if = "Option::is_none")]
     pub tool_name: Option<String>,
 
-    /// Tool parameters and arguments
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Tool parameters and arguments, with keys normalized to snake_case
+    #[serde(
+        alias = "toolInput",
+        default,
+        deserialize_with = "deserialize_tool_input",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub tool_input: Option<serde_json::Value>,
 
     /// Unique session identifier
+    #[serde(alias = "sessionId")]
     pub session_id: String,
 
     /// ISO 8601 timestamp (Claude Code may not send this, so default to now)
@@ -1203,11 +3215,11 @@ pub struct Event {
     pub timestamp: DateTime<Utc>,
 
     /// User identifier if available
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "userId", skip_serializing_if = "Option::is_none")]
     pub user_id: Option<String>,
 
     /// Path to session transcript (sent by Claude Code)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "transcriptPath", skip_serializing_if = "Option::is_none")]
     pub transcript_path: Option<String>,
 
     /// Current working directory (sent by Claude Code)
@@ -1215,12 +3227,25 @@ pub struct Event {
     pub cwd: Option<String>,
 
     /// Permission mode (sent by Claude Code)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "permissionMode", skip_serializing_if = "Option::is_none")]
     pub permission_mode: Option<String>,
 
     /// Tool use ID (sent by Claude Code)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "toolUseId", skip_serializing_if = "Option::is_none")]
     pub tool_use_id: Option<String>,
+
+    /// Tool result (sent by Claude Code on PostToolUse)
+    #[serde(alias = "toolResponse", skip_serializing_if = "Option::is_none")]
+    pub tool_response: Option<serde_json::Value>,
+
+    /// Text the user submitted (sent by Claude Code on UserPromptSubmit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+
+    /// Whether `tool_input` arrived with legacy camelCase keys, so rewritten
+    /// input is sent back in the spelling the client used
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub legacy_input_keys: bool,
 }
 
 /// Supported hook event types
@@ -1244,47 +3269,176 @@ pub enum EventType {
     Setup,
 }
 
-impl std::fmt::Display for EventType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            EventType::PreToolUse => write!(f, "PreToolUse"),
-            EventType::PostToolUse => write!(f, "PostToolUse"),
-            EventType::PermissionRequest => write!(f, "PermissionRequest"),
-            EventType::UserPromptSubmit => write!(f, "UserPromptSubmit"),
-            EventType::SessionStart => write!(f, "SessionStart"),
-            EventType::SessionEnd => write!(f, "SessionEnd"),
-            EventType::PreCompact => write!(f, "PreCompact"),
-            EventType::Stop => write!(f, "Stop"),
-            EventType::PostToolUseFailure => write!(f, "PostToolUseFailure"),
-            EventType::SubagentStart => write!(f, "SubagentStart"),
-            EventType::SubagentStop => write!(f, "SubagentStop"),
-            EventType::Notification => write!(f, "Notification"),
-            EventType::Setup => write!(f, "Setup"),
+impl EventType {
+    /// Every event type, in declaration order
+    pub const ALL: [EventType; 13] = [
+        EventType::PreToolUse,
+        EventType::PostToolUse,
+        EventType::PermissionRequest,
+        EventType::UserPromptSubmit,
+        EventType::SessionStart,
+        EventType::SessionEnd,
+        EventType::PreCompact,
+        EventType::Stop,
+        EventType::PostToolUseFailure,
+        EventType::SubagentStart,
+        EventType::SubagentStop,
+        EventType::Notification,
+        EventType::Setup,
+    ];
+}
+
+impl std::fmt::Display for EventType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EventType::PreToolUse => write!(f, "PreToolUse"),
+            EventType::PostToolUse => write!(f, "PostToolUse"),
+            EventType::PermissionRequest => write!(f, "PermissionRequest"),
+            EventType::UserPromptSubmit => write!(f, "UserPromptSubmit"),
+            EventType::SessionStart => write!(f, "SessionStart"),
+            EventType::SessionEnd => write!(f, "SessionEnd"),
+            EventType::PreCompact => write!(f, "PreCompact"),
+            EventType::Stop => write!(f, "Stop"),
+            EventType::PostToolUseFailure => write!(f, "PostToolUseFailure"),
+            EventType::SubagentStart => write!(f, "SubagentStart"),
+            EventType::SubagentStop => write!(f, "SubagentStop"),
+            EventType::Notification => write!(f, "Notification"),
+            EventType::Setup => write!(f, "Setup"),
+        }
+    }
+}
+
+/// Binary output structure for hook responses
+///
+/// Sent to Claude Code via stdout. The `continue` field controls whether
+/// the operation proceeds or is blocked.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Response {
+    /// Whether the operation should proceed
+    #[serde(rename = "continue")]
+    pub continue_: bool,
+
+    /// Additional context to inject
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+
+    /// Explanation for blocking or context injection
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+
+    /// Performance metrics
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timing: Option<Timing>,
+
+    /// Event-specific output understood by Claude Code, such as a
+    /// PreToolUse permission decision
+    #[serde(rename = "hookSpecificOutput", skip_serializing_if = "Option::is_none")]
+    pub hook_specific_output: Option<HookSpecificOutput>,
+
+    /// Message shown to the user rather than added to the model's context
+    #[serde(rename = "systemMessage", skip_serializing_if = "Option::is_none")]
+    pub system_message: Option<String>,
+}
+
+/// Claude Code's `hookSpecificOutput`: a PreToolUse permission decision or
+/// rewritten tool input, a replacement MCP tool result, or context added to
+/// a prompt or tool result
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HookSpecificOutput {
+    pub hook_event_name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permission_decision: Option<PermissionDecision>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permission_decision_reason: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub additional_context: Option<String>,
+
+    /// Replacement for the whole PreToolUse `tool_input`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_input: Option<serde_json::Value>,
+
+    /// Replacement for a PostToolUse MCP tool's result; Claude Code has no
+    /// way to replace the output of its built-in tools
+    #[serde(
+        rename = "updatedMCPToolOutput",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_mcp_tool_output: Option<serde_json::Value>,
+}
+
+impl HookSpecificOutput {
+    /// Combine with a lower-priority rule's output: the first permission
+    /// decision and the first input rewrite stand, the latest output
+    /// replacement wins
+    #[must_use]
+    pub fn merge(mut self, other: Self) -> Self {
+        if other.updated_mcp_tool_output.is_some() {
+            self.updated_mcp_tool_output = other.updated_mcp_tool_output;
+        }
+        if self.permission_decision.is_none() {
+            self.permission_decision = other.permission_decision;
+            self.permission_decision_reason = other.permission_decision_reason;
+        }
+        if self.updated_input.is_none() {
+            self.updated_input = other.updated_input;
+        }
+        if self.additional_context.is_none() {
+            self.additional_context = other.additional_context;
+        }
+        self
+    }
+}
+
+/// Permission decision returned to Claude Code from a PreToolUse hook
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PermissionDecision {
+    Allow,
+    Deny,
+    Ask,
+}
+
+/// A rule that matched an evaluated event, as reported by the decision API
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MatchedRule {
+    pub name: String,
+
+    pub mode: PolicyMode,
+
+    pub priority: i32,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl From<&Rule> for MatchedRule {
+    fn from(rule: &Rule) -> Self {
+        Self {
+            name: rule.name.clone(),
+            mode: rule.effective_mode(),
+            priority: rule.effective_priority(),
+            description: rule.description.clone(),
         }
     }
 }
 
-/// Binary output structure for hook responses
-///
-/// Sent to Claude Code via stdout. The `continue` field controls whether
-/// the operation proceeds or is blocked.
+/// Full result of evaluating an event: the hook response plus the rules
+/// and exemptions behind it
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub struct Response {
-    /// Whether the operation should proceed
-    #[serde(rename = "continue")]
-    pub continue_: bool,
+pub struct EvaluationReport {
+    pub response: Response,
 
-    /// Additional context to inject
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub context: Option<String>,
+    pub decision: Option<Decision>,
 
-    /// Explanation for blocking or context injection
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub reason: Option<String>,
+    pub matched_rules: Vec<MatchedRule>,
 
-    /// Performance metrics
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub timing: Option<Timing>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exemptions: Vec<AppliedExemption>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -1292,13 +3446,61 @@ pub struct Timing {
     /// Total processing time in milliseconds
     pub processing_ms: u64,
 
-    /// Number of rules checked
+    /// Number of rules checked before evaluation finished (a rule that
+    /// stops evaluation leaves the rest unchecked)
     pub rules_evaluated: usize,
+
+    /// How the checked rules turned out
+    #[serde(flatten)]
+    pub counts: RuleCounts,
+}
+
+/// Outcomes of the rules checked for one event
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct RuleCounts {
+    /// Rules that matched and weren't exempted, suppressed or throttled
+    pub rules_matched: usize,
+
+    /// Matched rules whose response blocked the operation
+    pub rules_blocked: usize,
+
+    /// Matched rules that raised a warning (warn-mode rules, and validator
+    /// `warn` verdicts)
+    pub rules_warned: usize,
+
+    /// Matched audit-mode rules, which only log
+    pub rules_audited: usize,
+}
+
+impl RuleCounts {
+    /// Count a matched rule by what its response did in `mode`
+    pub fn record(&mut self, mode: PolicyMode, blocked: bool, warned: bool) {
+        self.rules_matched += 1;
+        match mode {
+            PolicyMode::Enforce if blocked => self.rules_blocked += 1,
+            PolicyMode::Audit => self.rules_audited += 1,
+            _ if warned => self.rules_warned += 1,
+            _ => {}
+        }
+    }
+}
+
+/// Current `LogEntry` schema version, bumped whenever the on-disk shape
+/// changes in a way older readers can't parse
+pub const LOG_SCHEMA_VERSION: u32 = 2;
+
+fn legacy_schema_version() -> u32 {
+    1
 }
 
 /// Structured audit log record
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct LogEntry {
+    /// Schema version of this record (entries without one are version 1)
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
+
     /// ISO 8601 timestamp with microsecond precision
     pub timestamp: DateTime<Utc>,
 
@@ -1362,6 +3564,93 @@ pub struct LogEntry {
     /// Trust level of validator script (if run action was executed)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub trust_level: Option<TrustLevel>,
+
+    /// Rules that matched but were suppressed by an exemption
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exemptions: Option<Vec<AppliedExemption>>,
+
+    /// Validator scripts executed while processing the event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validators: Option<Vec<ValidatorRun>>,
+
+    /// How many times this session has received the same block reason;
+    /// set only once repeats are being suppressed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeat_count: Option<u32>,
+
+    /// The `allow` rule that overrode lower-priority blocks, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_override: Option<AllowOverride>,
+
+    /// The post-processor's run and whether it vetoed the decision
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_processor: Option<PostProcessorRun>,
+
+    /// Whether the decision was only logged (`settings.dry_run`) and the
+    /// event allowed
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dry_run: bool,
+
+    /// Patterns that failed to compile since the previous entry; they never
+    /// match, so the rules using them are silently ineffective
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invalid_patterns: Option<Vec<InvalidPattern>>,
+}
+
+/// A rule pattern that doesn't compile
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InvalidPattern {
+    pub pattern: String,
+
+    /// Why it doesn't compile
+    pub error: String,
+}
+
+/// Timing of a single validator script execution
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ValidatorRun {
+    /// Rule that ran the script
+    pub rule: String,
+
+    /// Script path as configured
+    pub script: String,
+
+    /// Wall-clock time until exit or timeout
+    pub duration_ms: u64,
+
+    /// Whether the script hit its timeout
+    #[serde(default)]
+    pub timed_out: bool,
+
+    /// Exit code (absent on timeout or signal)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+}
+
+/// One run of `settings.post_processor`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PostProcessorRun {
+    /// Script path as configured
+    pub script: String,
+
+    /// Wall-clock time until exit or timeout
+    pub duration_ms: u64,
+
+    /// Whether the script hit its timeout
+    #[serde(default)]
+    pub timed_out: bool,
+
+    /// Exit code (absent on timeout, signal or spawn failure)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+
+    /// Whether the script blocked an operation the rules allowed
+    #[serde(default)]
+    pub vetoed: bool,
+
+    /// The script's reason for blocking or warning
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
 }
 
 /// Result of rule evaluation
@@ -1381,6 +3670,21 @@ pub struct LogTiming {
 
     /// Rules checked
     pub rules_evaluated: usize,
+
+    /// How the checked rules turned out (zero in entries written before
+    /// the counts were logged)
+    #[serde(flatten)]
+    pub counts: RuleCounts,
+}
+
+impl From<&Timing> for LogTiming {
+    fn from(timing: &Timing) -> Self {
+        Self {
+            processing_ms: timing.processing_ms,
+            rules_evaluated: timing.rules_evaluated,
+            counts: timing.counts,
+        }
+    }
 }
 
 /// Additional log context
@@ -1393,6 +3697,10 @@ pub struct LogMetadata {
     /// Script execution results
     #[serde(skip_serializing_if = "Option::is_none")]
     pub validator_output: Option<String>,
+
+    /// Inject sources left out because this session already received them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suppressed_injections: Option<Vec<String>>,
 }
 
 // =============================================================================
@@ -1404,11 +3712,32 @@ pub struct LogMetadata {
 #[serde(tag = "tool_type")]
 pub enum EventDetails {
     /// Bash command execution
-    Bash { command: String },
+    Bash {
+        command: String,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        run_in_background: bool,
+    },
+    /// Output read from a background shell
+    BashOutput {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        shell_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        filter: Option<String>,
+    },
+    /// Background shell terminated
+    KillShell {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        shell_id: Option<String>,
+    },
     /// File write operation
     Write { file_path: String },
     /// File edit operation
     Edit { file_path: String },
+    /// Several edits in one call, each with its own target
+    MultiEdit {
+        file_path: String,
+        edits: Vec<EditDetails>,
+    },
     /// File read operation
     Read { file_path: String },
     /// Glob pattern search
@@ -1449,8 +3778,51 @@ pub enum EventDetails {
     },
 }
 
+/// `protected_paths`: path globs a rule guards, with exceptions
+///
+/// ```yaml
+/// protected_paths:
+///   deny: ["/etc/**", "~/.ssh/**", "**/.env"]
+///   allow: ["**/.env.example"]
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ProtectedPaths {
+    /// Paths the rule applies to
+    #[serde(default)]
+    pub deny: Vec<String>,
+
+    /// Paths within `deny` that are left alone
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+}
+
+impl ProtectedPaths {
+    /// Whether `path` (as the tool reported it) resolves into a denied
+    /// path that no allow glob exempts
+    pub fn protects(&self, path: &str, cwd: &Path) -> bool {
+        let path = paths::canonicalize(path, cwd);
+        let matches = |globs: &[String]| {
+            globs
+                .iter()
+                .any(|glob| paths::glob_matches(glob, &path, cwd))
+        };
+        matches(&self.deny) && !matches(&self.allow)
+    }
+}
+
+/// One edit of a multi-edit operation
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EditDetails {
+    /// File the edit targets (the operation's file unless the edit names its own)
+    pub file_path: String,
+
+    /// Length of the replacement text
+    pub content_length: usize,
+}
+
 /// Summary of response sent to Claude
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct ResponseSummary {
     /// Whether the operation should continue
     #[serde(rename = "continue")]
@@ -1463,6 +3835,19 @@ pub struct ResponseSummary {
     /// Length of injected context (if any)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub context_length: Option<usize>,
+
+    /// Whether context was cut to fit `settings.max_context_size`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub context_truncated: bool,
+
+    /// Whether the response rewrote the tool input (`actions.rewrite`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub input_rewritten: bool,
+
+    /// Whether the response replaced an MCP tool's result
+    /// (`actions.truncate_output`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub output_replaced: bool,
 }
 
 /// Per-rule evaluation details (debug mode only)
@@ -1501,6 +3886,78 @@ pub struct MatcherResults {
     /// Whether operations matcher matched
     #[serde(skip_serializing_if = "Option::is_none")]
     pub operations_matched: Option<bool>,
+
+    /// Whether zone matcher matched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zone_matched: Option<bool>,
+
+    /// Whether exclude_tools matched (the tool is not excluded)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude_tools_matched: Option<bool>,
+
+    /// Whether exclude_extensions matched (the extension is not excluded)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude_extensions_matched: Option<bool>,
+
+    /// Whether exclude_directories matched (the path is not excluded)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude_directories_matched: Option<bool>,
+
+    /// Whether command_not_match matched (the command is not excluded)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command_not_match_matched: Option<bool>,
+
+    /// Whether tool_output_match matched the tool's output
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_output_match_matched: Option<bool>,
+
+    /// Whether the result's exit code satisfied exit_code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code_matched: Option<bool>,
+
+    /// Whether prompt_match matched the submitted prompt
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_match_matched: Option<bool>,
+
+    /// Whether the event's background-shell use satisfied background
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background_matched: Option<bool>,
+
+    /// Whether the canonical target path fell under protected_paths
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protected_paths_matched: Option<bool>,
+
+    /// Whether expr evaluated to true
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expr_matched: Option<bool>,
+
+    /// Whether the `any` block matched, with each nested block's results
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub any_matched: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub any: Option<Vec<MatcherResults>>,
+
+    /// Whether the `all` block matched, with each nested block's results
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub all_matched: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub all: Option<Vec<MatcherResults>>,
+
+    /// Whether the `not` block matched (its nested block did not)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not_matched: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not: Option<Box<MatcherResults>>,
+
+    /// Whether the compiled policy condition matched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy_matched: Option<bool>,
+
+    /// Results for each edit of a multi-edit operation, in order; the rule
+    /// matches when any edit does, and the fields above are the first
+    /// matching edit's (or the first edit's when none match)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edits: Option<Vec<MatcherResults>>,
 }
 
 /// Debug mode configuration
@@ -1518,6 +3975,101 @@ impl DebugConfig {
     }
 }
 
+impl Event {
+    /// Deserialize a hook payload from any supported agent, mapping Cursor
+    /// and OpenCode payloads onto Claude Code's fields first (see
+    /// [`crate::agents`])
+    pub fn from_agent_payload(raw: serde_json::Value) -> serde_json::Result<(Self, Agent)> {
+        let agent = Agent::detect(&raw);
+        let legacy_input_keys = agent == Agent::Claude
+            && raw
+                .get("tool_input")
+                .or_else(|| raw.get("toolInput"))
+                .is_some_and(has_legacy_tool_input_keys);
+        let mut event = Self::deserialize(crate::agents::normalize(&agent, raw))?;
+        event.legacy_input_keys = legacy_input_keys;
+        Ok((event, agent))
+    }
+
+    /// This event as validator scripts read it on stdin
+    ///
+    /// Tool input keys appear in both their canonical snake_case and legacy
+    /// camelCase spellings, so validators written against either keep matching.
+    pub fn script_payload(&self) -> serde_json::Value {
+        let mut payload = serde_json::to_value(self).unwrap_or_default();
+        if let Some(input) = payload.get_mut("tool_input") {
+            *input = with_legacy_tool_input_keys(input.take());
+        }
+        payload
+    }
+
+    /// Get the textual tool output from a PostToolUse event
+    ///
+    /// Bash results carry `stdout`; other tools may send a plain string.
+    pub fn tool_output(&self) -> Option<&str> {
+        let response = self.tool_response.as_ref()?;
+        response
+            .as_str()
+            .or_else(|| response.get("stdout").and_then(|s| s.as_str()))
+            .or_else(|| response.get("output").and_then(|s| s.as_str()))
+    }
+
+    /// Get the stderr of a PostToolUse Bash event
+    pub fn tool_stderr(&self) -> Option<&str> {
+        self.tool_response.as_ref()?.get("stderr")?.as_str()
+    }
+
+    /// Get the exit code a PostToolUse result reports, if any
+    pub fn tool_exit_code(&self) -> Option<i64> {
+        let response = self.tool_response.as_ref()?;
+        response
+            .get("exit_code")
+            .or_else(|| response.get("exitCode"))
+            .and_then(|code| code.as_i64())
+    }
+
+    /// Whether the event runs or manages a background shell: Bash with
+    /// `run_in_background`, BashOutput or KillShell
+    pub fn background_shell(&self) -> bool {
+        match self.tool_name.as_deref() {
+            Some("Bash") => self
+                .tool_input
+                .as_ref()
+                .and_then(|ti| ti.get("run_in_background"))
+                .and_then(|b| b.as_bool())
+                .unwrap_or(false),
+            Some("BashOutput" | "KillShell") => true,
+            _ => false,
+        }
+    }
+
+    /// Get the background shell a BashOutput/KillShell event targets
+    ///
+    /// BashOutput names it `bash_id`; KillShell uses `shell_id`.
+    pub fn shell_id(&self) -> Option<&str> {
+        let tool_input = self.tool_input.as_ref()?;
+        tool_input
+            .get("shell_id")
+            .or_else(|| tool_input.get("bash_id"))
+            .and_then(|id| id.as_str())
+    }
+
+    /// Get the result lines of a PostToolUse Glob/Grep event
+    ///
+    /// File lists arrive as `filenames`; Grep content mode sends `content`.
+    pub fn search_results(&self) -> Option<Vec<&str>> {
+        let response = self.tool_response.as_ref()?;
+        if let Some(filenames) = response.get("filenames").and_then(|f| f.as_array()) {
+            return Some(filenames.iter().filter_map(|f| f.as_str()).collect());
+        }
+        response
+            .get("content")
+            .and_then(|c| c.as_str())
+            .or_else(|| self.tool_output())
+            .map(|text| text.lines().collect())
+    }
+}
+
 impl EventDetails {
     /// Extract typed details from an Event
     pub fn extract(event: &Event) -> Self {
@@ -1531,11 +4083,27 @@ impl EventDetails {
                     .and_then(|c| c.as_str())
                     .unwrap_or("")
                     .to_string();
-                EventDetails::Bash { command }
+                EventDetails::Bash {
+                    command,
+                    run_in_background: event.background_shell(),
+                }
+            }
+            Some("BashOutput") => {
+                let filter = tool_input
+                    .and_then(|ti| ti.get("filter"))
+                    .and_then(|f| f.as_str())
+                    .map(String::from);
+                EventDetails::BashOutput {
+                    shell_id: event.shell_id().map(String::from),
+                    filter,
+                }
             }
+            Some("KillShell") => EventDetails::KillShell {
+                shell_id: event.shell_id().map(String::from),
+            },
             Some("Write") => {
                 let file_path = tool_input
-                    .and_then(|ti| ti.get("file_path").or_else(|| ti.get("filePath")))
+                    .and_then(|ti| ti.get("file_path"))
                     .and_then(|p| p.as_str())
                     .unwrap_or("")
                     .to_string();
@@ -1543,15 +4111,43 @@ impl EventDetails {
             }
             Some("Edit") => {
                 let file_path = tool_input
-                    .and_then(|ti| ti.get("file_path").or_else(|| ti.get("filePath")))
+                    .and_then(|ti| ti.get("file_path"))
                     .and_then(|p| p.as_str())
                     .unwrap_or("")
                     .to_string();
                 EventDetails::Edit { file_path }
             }
+            Some("MultiEdit") => {
+                let file_path = tool_input
+                    .and_then(|ti| ti.get("file_path"))
+                    .and_then(|p| p.as_str())
+                    .unwrap_or("");
+                let edits = tool_input
+                    .and_then(|ti| ti.get("edits"))
+                    .and_then(|e| e.as_array())
+                    .into_iter()
+                    .flatten()
+                    .map(|edit| EditDetails {
+                        file_path: edit
+                            .get("file_path")
+                            .and_then(|p| p.as_str())
+                            .unwrap_or(file_path)
+                            .to_string(),
+                        content_length: edit
+                            .get("new_string")
+                            .or_else(|| edit.get("content"))
+                            .and_then(|c| c.as_str())
+                            .map_or(0, str::len),
+                    })
+                    .collect();
+                EventDetails::MultiEdit {
+                    file_path: file_path.to_string(),
+                    edits,
+                }
+            }
             Some("Read") => {
                 let file_path = tool_input
-                    .and_then(|ti| ti.get("file_path").or_else(|| ti.get("filePath")))
+                    .and_then(|ti| ti.get("file_path"))
                     .and_then(|p| p.as_str())
                     .unwrap_or("")
                     .to_string();
@@ -1613,6 +4209,62 @@ impl EventDetails {
             },
         }
     }
+
+    /// Rebuild the tool input these details were extracted from, as far as
+    /// they record it (file contents, for one, are not logged)
+    pub fn to_tool_input(&self) -> Option<serde_json::Value> {
+        let mut input = serde_json::Map::new();
+        let mut set = |key: &str, value: Option<&String>| {
+            if let Some(value) = value {
+                input.insert(key.to_string(), value.clone().into());
+            }
+        };
+        match self {
+            EventDetails::Bash {
+                command,
+                run_in_background,
+            } => {
+                set("command", Some(command));
+                if *run_in_background {
+                    input.insert("run_in_background".to_string(), true.into());
+                }
+            }
+            EventDetails::BashOutput { shell_id, filter } => {
+                set("bash_id", shell_id.as_ref());
+                set("filter", filter.as_ref());
+            }
+            EventDetails::KillShell { shell_id } => set("shell_id", shell_id.as_ref()),
+            EventDetails::Write { file_path }
+            | EventDetails::Edit { file_path }
+            | EventDetails::Read { file_path } => set("file_path", Some(file_path)),
+            EventDetails::MultiEdit { file_path, edits } => {
+                set("file_path", Some(file_path));
+                let edits = edits
+                    .iter()
+                    .map(|edit| serde_json::json!({ "file_path": edit.file_path }))
+                    .collect();
+                input.insert("edits".to_string(), serde_json::Value::Array(edits));
+            }
+            EventDetails::Glob { pattern, path } | EventDetails::Grep { pattern, path } => {
+                set("pattern", pattern.as_ref());
+                set("path", path.as_ref());
+            }
+            EventDetails::Session {
+                source,
+                reason,
+                transcript_path,
+                cwd,
+            } => {
+                set("source", source.as_ref());
+                set("reason", reason.as_ref());
+                set("transcript_path", transcript_path.as_ref());
+                set("cwd", cwd.as_ref());
+            }
+            EventDetails::Permission { tool_details, .. } => return tool_details.to_tool_input(),
+            EventDetails::Unknown { .. } => return None,
+        }
+        Some(serde_json::Value::Object(input))
+    }
 }
 
 impl ResponseSummary {
@@ -1622,6 +4274,9 @@ impl ResponseSummary {
             continue_: response.continue_,
             reason: response.reason.clone(),
             context_length: response.context.as_ref().map(|c| c.len()),
+            context_truncated: false,
+            input_rewritten: response.updated_input().is_some(),
+            output_replaced: response.updated_mcp_output().is_some(),
         }
     }
 }
@@ -1640,6 +4295,9 @@ impl Default for RuleMetadata {
 // Rule Helper Methods (Phase 2 Governance)
 // =============================================================================
 
+/// `created_by` prefix of rules merged from Claude Code plugin fragments
+pub const PLUGIN_PROVENANCE_PREFIX: &str = "plugin:";
+
 impl Rule {
     /// Get the effective policy mode (defaults to Enforce)
     #[allow(dead_code)] // Used in Phase 2.1-T05 (mode-based action execution)
@@ -1662,6 +4320,34 @@ impl Rule {
     pub fn is_enabled(&self) -> bool {
         self.metadata.as_ref().map(|m| m.enabled).unwrap_or(true)
     }
+    /// Whether the rule's `governance.expires` date has been reached
+    pub fn is_expired(&self, today: NaiveDate) -> bool {
+        self.governance
+            .as_ref()
+            .and_then(|g| g.expires)
+            .is_some_and(|expires| expires <= today)
+    }
+
+    /// Why the rule is stale: expired, or past its `review_by` date
+    pub fn staleness(&self, today: NaiveDate) -> Option<String> {
+        let governance = self.governance.as_ref()?;
+        match (governance.expires, governance.review_by) {
+            (Some(expires), _) if expires <= today => Some(format!("expired on {}", expires)),
+            (_, Some(review_by)) if review_by < today => {
+                Some(format!("review was due by {}", review_by))
+            }
+            _ => None,
+        }
+    }
+
+    /// Name of the Claude Code plugin that provided the rule, if any
+    pub fn plugin(&self) -> Option<&str> {
+        self.governance
+            .as_ref()?
+            .created_by
+            .as_deref()?
+            .strip_prefix(PLUGIN_PROVENANCE_PREFIX)
+    }
 }
 
 /// Sort rules by priority in descending order (higher numbers first)
@@ -1683,6 +4369,8 @@ impl Response {
             context: None,
             reason: None,
             timing: None,
+            hook_specific_output: None,
+            system_message: None,
         }
     }
 
@@ -1693,6 +4381,8 @@ impl Response {
             context: None,
             reason: Some(reason.into()),
             timing: None,
+            hook_specific_output: None,
+            system_message: None,
         }
     }
 
@@ -1703,6 +4393,130 @@ impl Response {
             context: Some(context.into()),
             reason: None,
             timing: None,
+            hook_specific_output: None,
+            system_message: None,
+        }
+    }
+
+    /// Create a new response replacing an MCP tool's result (PostToolUse)
+    pub fn update_mcp_output(output: impl Into<String>) -> Self {
+        Self {
+            continue_: true,
+            context: None,
+            reason: None,
+            timing: None,
+            hook_specific_output: Some(HookSpecificOutput {
+                hook_event_name: EventType::PostToolUse.to_string(),
+                permission_decision: None,
+                permission_decision_reason: None,
+                additional_context: None,
+                updated_input: None,
+                updated_mcp_tool_output: Some(serde_json::Value::String(output.into())),
+            }),
+            system_message: None,
+        }
+    }
+
+    /// Create a new response adding `context` to a tool's result
+    /// (PostToolUse), for built-in tools whose output can't be replaced
+    pub fn tool_result_context(context: impl Into<String>) -> Self {
+        Self {
+            continue_: true,
+            context: None,
+            reason: None,
+            timing: None,
+            hook_specific_output: Some(HookSpecificOutput {
+                hook_event_name: EventType::PostToolUse.to_string(),
+                permission_decision: None,
+                permission_decision_reason: None,
+                additional_context: Some(context.into()),
+                updated_input: None,
+                updated_mcp_tool_output: None,
+            }),
+            system_message: None,
+        }
+    }
+
+    /// Create a new response asking the user to approve a PreToolUse call
+    pub fn ask(reason: impl Into<String>) -> Self {
+        Self {
+            continue_: true,
+            context: None,
+            reason: None,
+            timing: None,
+            hook_specific_output: Some(HookSpecificOutput {
+                hook_event_name: EventType::PreToolUse.to_string(),
+                permission_decision: Some(PermissionDecision::Ask),
+                permission_decision_reason: Some(reason.into()),
+                additional_context: None,
+                updated_input: None,
+                updated_mcp_tool_output: None,
+            }),
+            system_message: None,
+        }
+    }
+
+    /// Create a new response running `event`'s PreToolUse call with `input`
+    /// instead of its original tool input
+    ///
+    /// Keys are converted back to camelCase when the event's tool input
+    /// arrived that way.
+    pub fn update_input(input: serde_json::Value, event: &Event) -> Self {
+        let input = if event.legacy_input_keys {
+            legacy_tool_input(input)
+        } else {
+            input
+        };
+        Self {
+            continue_: true,
+            context: None,
+            reason: None,
+            timing: None,
+            hook_specific_output: Some(HookSpecificOutput {
+                hook_event_name: EventType::PreToolUse.to_string(),
+                permission_decision: None,
+                permission_decision_reason: None,
+                additional_context: None,
+                updated_input: Some(input),
+                updated_mcp_tool_output: None,
+            }),
+            system_message: None,
+        }
+    }
+
+    /// The rewritten tool input, if this response carries one
+    pub fn updated_input(&self) -> Option<&serde_json::Value> {
+        self.hook_specific_output
+            .as_ref()
+            .and_then(|o| o.updated_input.as_ref())
+    }
+
+    /// The replacement MCP tool result, if this response carries one
+    pub fn updated_mcp_output(&self) -> Option<&serde_json::Value> {
+        self.hook_specific_output
+            .as_ref()
+            .and_then(|o| o.updated_mcp_tool_output.as_ref())
+    }
+
+    /// Whether this response asks the user for permission
+    pub fn asks(&self) -> bool {
+        self.hook_specific_output
+            .as_ref()
+            .is_some_and(|o| o.permission_decision == Some(PermissionDecision::Ask))
+    }
+
+    /// Hand the injected context to Claude Code as UserPromptSubmit
+    /// `additionalContext`, which it adds to the prompt
+    pub fn add_prompt_context(&mut self) {
+        if let Some(ref context) = self.context {
+            self.hook_specific_output = Some(HookSpecificOutput {
+                hook_event_name: EventType::UserPromptSubmit.to_string(),
+                permission_decision: None,
+                permission_decision_reason: None,
+                additional_context: Some(context.clone()),
+                updated_input: None,
+                updated_mcp_tool_output: None,
+            });
         }
     }
 }
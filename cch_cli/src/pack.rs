@@ -0,0 +1,589 @@
+//! Rule packs
+//!
+//! A pack is a single YAML document that carries a set of rules together
+//! with the context, validator, and template files they reference, so the
+//! rules can be shared and installed in another project:
+//!
+//! ```yaml
+//! pack:
+//!   name: security
+//!   version: "1.0.0"
+//! rules:
+//!   - name: block-force-push
+//!     ...
+//! files:
+//!   - path: .claude/validators/check-secrets.sh
+//!     executable: true
+//!     content: |
+//!       #!/bin/bash
+//!       ...
+//! ```
+//!
+//! `cch pack install` merges a pack into a project. Pack rules whose name
+//! or command pattern collides with an existing rule are resolved one at a
+//! time: keep the local rule, take the pack rule, install the pack rule
+//! under a new name, or merge the two rules so the local rule's actions
+//! fire on whatever either rule matched.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::bundle::safe_relative_path;
+use crate::config::Config;
+use crate::models::{GovernanceMetadata, Matchers, Rule};
+
+/// A distributable set of rules and the files they reference
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Pack {
+    pub pack: PackMetadata,
+
+    pub rules: Vec<Rule>,
+
+    /// Context, validator, and template files bundled inline
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<PackFile>,
+}
+
+/// Pack identity and provenance
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PackMetadata {
+    pub name: String,
+
+    pub version: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// When the pack was created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<DateTime<Utc>>,
+}
+
+/// A file referenced by a pack rule, stored at its project-relative path
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PackFile {
+    pub path: String,
+
+    /// Whether the file should be installed executable (validator scripts)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub executable: bool,
+
+    pub content: String,
+}
+
+/// Which rules to put in a pack
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    /// Include rules carrying any of these governance tags
+    pub tags: Vec<String>,
+    /// Include rules with these names
+    pub rules: Vec<String>,
+}
+
+impl Selection {
+    /// Whether a rule is selected; an empty selection selects every rule
+    pub fn includes(&self, rule: &Rule) -> bool {
+        if self.tags.is_empty() && self.rules.is_empty() {
+            return true;
+        }
+        let tagged = rule
+            .governance
+            .as_ref()
+            .and_then(|g| g.tags.as_ref())
+            .is_some_and(|tags| tags.iter().any(|t| self.tags.contains(t)));
+        tagged || self.rules.contains(&rule.name)
+    }
+}
+
+impl Pack {
+    /// Build a pack from the selected rules of a config
+    ///
+    /// Rules decrypted from `encrypted_rules` and rules compiled from
+    /// `policies:` are never exported. Referenced files are read relative
+    /// to `project_root`. Rules without a `created_by` are stamped with the
+    /// pack's `name@version` for provenance.
+    pub fn create(
+        config: &Config,
+        selection: &Selection,
+        metadata: PackMetadata,
+        project_root: &Path,
+    ) -> Result<Self> {
+        let provenance = format!("{}@{}", metadata.name, metadata.version);
+        let mut rules = Vec::new();
+        let mut files: Vec<PackFile> = Vec::new();
+
+        for rule in &config.rules {
+            if !selection.includes(rule)
+                || config.is_sealed(&rule.name)
+                || rule.matchers.policy.is_some()
+            {
+                continue;
+            }
+
+            for path in referenced_files(rule) {
+                if files.iter().any(|f| f.path == path) {
+                    continue;
+                }
+                files.push(read_pack_file(project_root, path).with_context(|| {
+                    format!("Rule '{}' references an unreadable file", rule.name)
+                })?);
+            }
+
+            let mut rule = rule.clone();
+            let governance = rule
+                .governance
+                .get_or_insert_with(GovernanceMetadata::default);
+            if governance.created_by.is_none() {
+                governance.created_by = Some(provenance.clone());
+            }
+            rules.push(rule);
+        }
+
+        if rules.is_empty() {
+            anyhow::bail!("No rules match the selection");
+        }
+
+        Ok(Self {
+            pack: metadata,
+            rules,
+            files,
+        })
+    }
+
+    /// Serialize the pack as YAML
+    pub fn to_yaml(&self) -> Result<String> {
+        serde_yaml::to_string(self).context("Failed to serialize pack")
+    }
+
+    /// Parse a pack from YAML, rejecting it if any bundled file would be
+    /// written outside the project
+    pub fn from_yaml(content: &str) -> Result<Self> {
+        let pack: Self = serde_yaml::from_str(content).context("Failed to parse pack")?;
+        for file in &pack.files {
+            safe_relative_path(&file.path)?;
+        }
+        Ok(pack)
+    }
+}
+
+/// Why a pack rule collides with an existing rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictKind {
+    /// Same rule name
+    Name,
+    /// Different names, but the same matchers (list order aside)
+    Pattern,
+}
+
+/// A pack rule that collides with an existing rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict<'a> {
+    pub kind: ConflictKind,
+    pub local: &'a Rule,
+    pub incoming: &'a Rule,
+}
+
+impl std::fmt::Display for Conflict<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            ConflictKind::Name => write!(f, "rule '{}' already exists", self.incoming.name),
+            ConflictKind::Pattern => write!(
+                f,
+                "pack rule '{}' overlaps local rule '{}'",
+                self.incoming.name, self.local.name
+            ),
+        }
+    }
+}
+
+/// How to settle a conflict
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// Keep the local rule and skip the pack rule
+    KeepLocal,
+    /// Replace the local rule with the pack rule
+    TakePack,
+    /// Install the pack rule under a new, unique name
+    Rename,
+    /// Widen the local rule's matchers with the pack rule's
+    Merge,
+}
+
+impl std::str::FromStr for Resolution {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().replace('_', "-").as_str() {
+            "keep-local" | "keep" | "k" => Ok(Resolution::KeepLocal),
+            "take-pack" | "take" | "t" => Ok(Resolution::TakePack),
+            "rename" | "r" => Ok(Resolution::Rename),
+            "merge" | "m" => Ok(Resolution::Merge),
+            _ => Err(anyhow::anyhow!(
+                "Unknown strategy '{}': use keep-local, take-pack, rename, or merge",
+                s
+            )),
+        }
+    }
+}
+
+/// What installing a pack did to the rule list
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InstallReport {
+    pub added: Vec<String>,
+    pub replaced: Vec<String>,
+    /// (pack name, installed name)
+    pub renamed: Vec<(String, String)>,
+    pub merged: Vec<String>,
+    pub skipped: Vec<String>,
+}
+
+/// Find the existing rule a pack rule collides with, if any
+pub fn find_conflict<'a>(local: &'a [Rule], incoming: &'a Rule) -> Option<Conflict<'a>> {
+    if let Some(rule) = local.iter().find(|r| r.name == incoming.name) {
+        return Some(Conflict {
+            kind: ConflictKind::Name,
+            local: rule,
+            incoming,
+        });
+    }
+    local
+        .iter()
+        .find(|r| patterns_overlap(&r.matchers, &incoming.matchers))
+        .map(|rule| Conflict {
+            kind: ConflictKind::Pattern,
+            local: rule,
+            incoming,
+        })
+}
+
+fn patterns_overlap(a: &Matchers, b: &Matchers) -> bool {
+    normalized(a) == normalized(b)
+}
+
+/// `matchers` with every list sorted, so equal sets compare equal
+fn normalized(matchers: &Matchers) -> Matchers {
+    let mut matchers = matchers.clone();
+    for list in [
+        &mut matchers.tools,
+        &mut matchers.extensions,
+        &mut matchers.directories,
+        &mut matchers.operations,
+        &mut matchers.exclude_tools,
+        &mut matchers.exclude_extensions,
+        &mut matchers.exclude_directories,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        list.sort();
+        list.dedup();
+    }
+    matchers
+}
+
+/// Merge pack rules into `rules`, asking `resolve` how to settle each conflict
+pub fn install(
+    rules: &mut Vec<Rule>,
+    pack: &Pack,
+    mut resolve: impl FnMut(&Conflict) -> Result<Resolution>,
+) -> Result<InstallReport> {
+    let mut report = InstallReport::default();
+
+    for incoming in &pack.rules {
+        let Some(conflict) = find_conflict(rules, incoming) else {
+            rules.push(incoming.clone());
+            report.added.push(incoming.name.clone());
+            continue;
+        };
+        let resolution = resolve(&conflict)?;
+        let local_name = conflict.local.name.clone();
+        let index = rules
+            .iter()
+            .position(|r| r.name == local_name)
+            .expect("conflicting rule is in the list");
+
+        match resolution {
+            Resolution::KeepLocal => report.skipped.push(incoming.name.clone()),
+            Resolution::TakePack => {
+                rules[index] = incoming.clone();
+                report.replaced.push(local_name);
+            }
+            Resolution::Rename => {
+                let mut renamed = incoming.clone();
+                renamed.name = unique_name(rules, &incoming.name, &pack.pack.name);
+                report
+                    .renamed
+                    .push((incoming.name.clone(), renamed.name.clone()));
+                rules.push(renamed);
+            }
+            Resolution::Merge => {
+                merge_matchers(&mut rules[index].matchers, &incoming.matchers);
+                report.merged.push(local_name);
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// `<name>-<pack>`, with a numeric suffix if that is taken too
+fn unique_name(rules: &[Rule], name: &str, pack_name: &str) -> String {
+    let suffix: String = pack_name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    let base = format!("{}-{}", name, suffix);
+    let taken = |candidate: &str| rules.iter().any(|r| r.name == candidate);
+    if !taken(&base) {
+        return base;
+    }
+    // A list of n rules can't occupy all of n + 1 candidate names
+    (2..=rules.len() + 2)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|candidate| !taken(candidate))
+        .unwrap_or(base)
+}
+
+/// Widen `local` so it matches exactly what either rule matched
+///
+/// Matcher fields are ANDed, so unioning them one by one would match
+/// events neither rule did; the two blocks become the arms of an `any`.
+pub fn merge_matchers(local: &mut Matchers, incoming: &Matchers) {
+    if patterns_overlap(local, incoming) {
+        return;
+    }
+    let arms = vec![std::mem::take(local), incoming.clone()];
+    local.any = Some(arms);
+}
+
+/// Project files a rule's actions point at
+pub fn referenced_files(rule: &Rule) -> Vec<&str> {
+    let actions = &rule.actions;
+    actions
+        .inject_paths()
+        .chain(actions.script_path())
+        .chain(actions.ensure_header.as_ref().map(|h| h.template.as_str()))
+        .collect()
+}
+
+fn read_pack_file(project_root: &Path, path: &str) -> Result<PackFile> {
+    let full_path = project_root.join(path);
+    let content = std::fs::read_to_string(&full_path)
+        .with_context(|| format!("Failed to read {}", full_path.display()))?;
+    Ok(PackFile {
+        path: path.to_string(),
+        executable: is_executable(&full_path),
+        content,
+    })
+}
+
+#[cfg(unix)]
+pub(crate) fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn is_executable(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == "sh" || ext == "py")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Config {
+        serde_yaml::from_str(
+            r#"
+version: "1.0"
+rules:
+  - name: inject-security-docs
+    matchers: { tools: [Write] }
+    actions: { inject: .claude/context/security.md }
+    governance: { tags: [security] }
+  - name: style
+    matchers: { tools: [Write] }
+    actions: { inject: .claude/context/style.md }
+    governance: { tags: [style], created_by: style-skill@1.0 }
+"#,
+        )
+        .unwrap()
+    }
+
+    fn metadata() -> PackMetadata {
+        PackMetadata {
+            name: "sec".to_string(),
+            version: "1.0.0".to_string(),
+            description: None,
+            created: None,
+        }
+    }
+
+    #[test]
+    fn test_create_bundles_selected_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".claude/context")).unwrap();
+        std::fs::write(dir.path().join(".claude/context/security.md"), "Be careful").unwrap();
+
+        let selection = Selection {
+            tags: vec!["security".to_string()],
+            rules: Vec::new(),
+        };
+        let pack = Pack::create(&config(), &selection, metadata(), dir.path()).unwrap();
+
+        assert_eq!(pack.rules.len(), 1);
+        assert_eq!(
+            pack.rules[0]
+                .governance
+                .as_ref()
+                .unwrap()
+                .created_by
+                .as_deref(),
+            Some("sec@1.0.0")
+        );
+        assert_eq!(pack.files.len(), 1);
+        assert_eq!(pack.files[0].content, "Be careful");
+
+        let roundtrip = Pack::from_yaml(&pack.to_yaml().unwrap()).unwrap();
+        assert_eq!(roundtrip, pack);
+    }
+
+    #[test]
+    fn test_create_requires_referenced_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let selection = Selection {
+            tags: Vec::new(),
+            rules: vec!["style".to_string()],
+        };
+        let err = Pack::create(&config(), &selection, metadata(), dir.path()).unwrap_err();
+        assert!(err.to_string().contains("'style'"));
+    }
+
+    fn pack_with(rules: Vec<Rule>) -> Pack {
+        Pack {
+            pack: metadata(),
+            rules,
+            files: Vec::new(),
+        }
+    }
+
+    fn rule(name: &str, command: &str) -> Rule {
+        serde_yaml::from_str(&format!(
+            "name: {name}\nmatchers: {{ tools: [Bash], command_match: \"{command}\" }}\nactions: {{ block: true }}"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_install_resolutions() {
+        let pack = pack_with(vec![
+            rule("no-force", "git push -f"),
+            rule("other-name", "rm -rf"),
+            rule("fresh", "curl"),
+        ]);
+
+        for (resolution, expected_names) in [
+            (Resolution::KeepLocal, vec!["no-force", "no-rm", "fresh"]),
+            (
+                Resolution::TakePack,
+                vec!["no-force", "other-name", "fresh"],
+            ),
+            (
+                Resolution::Rename,
+                vec![
+                    "no-force",
+                    "no-rm",
+                    "no-force-sec",
+                    "other-name-sec",
+                    "fresh",
+                ],
+            ),
+            (Resolution::Merge, vec!["no-force", "no-rm", "fresh"]),
+        ] {
+            let mut rules = vec![
+                rule("no-force", "git push --force"),
+                rule("no-rm", "rm -rf"),
+            ];
+            let mut kinds = Vec::new();
+            install(&mut rules, &pack, |conflict| {
+                kinds.push(conflict.kind);
+                Ok(resolution)
+            })
+            .unwrap();
+
+            let names: Vec<&str> = rules.iter().map(|r| r.name.as_str()).collect();
+            assert_eq!(names, expected_names, "{:?}", resolution);
+            assert_eq!(kinds, vec![ConflictKind::Name, ConflictKind::Pattern]);
+            if resolution == Resolution::Merge {
+                let any = rules[0].matchers.any.as_ref().unwrap();
+                assert_eq!(any[0].command_match.as_deref(), Some("git push --force"));
+                assert_eq!(any[1].command_match.as_deref(), Some("git push -f"));
+                assert_eq!(rules[1], rule("no-rm", "rm -rf"));
+            }
+        }
+    }
+
+    #[test]
+    fn test_merge_keeps_each_rule_intact() {
+        let mut local: Matchers =
+            serde_yaml::from_str("{ tools: [Bash], command_match: rm }").unwrap();
+        let incoming: Matchers =
+            serde_yaml::from_str("{ tools: [Write], extensions: [.env] }").unwrap();
+        merge_matchers(&mut local, &incoming);
+
+        assert_eq!(local.tools, None);
+        assert_eq!(local.command_match, None);
+        let any = local.any.unwrap();
+        assert_eq!(any[0].command_match.as_deref(), Some("rm"));
+        assert_eq!(any[1], incoming);
+    }
+
+    #[test]
+    fn test_overlap_compares_every_matcher() {
+        let matchers = |yaml: &str| serde_yaml::from_str::<Matchers>(yaml).unwrap();
+        let a = matchers("{ tools: [Bash, Write], command_match: rm }");
+        assert!(patterns_overlap(
+            &a,
+            &matchers("{ tools: [Write, Bash], command_match: rm }")
+        ));
+        assert!(!patterns_overlap(
+            &a,
+            &matchers("{ tools: [Bash, Write], command_match: rm, zone: secret }")
+        ));
+        assert!(!patterns_overlap(
+            &a,
+            &matchers("{ tools: [Bash], command_match: rm }")
+        ));
+    }
+
+    #[test]
+    fn test_from_yaml_rejects_paths_outside_project() {
+        for path in ["../outside.sh", "/home/u/.bashrc", ".claude/../../x"] {
+            let mut pack = pack_with(vec![rule("r", "rm")]);
+            pack.files.push(PackFile {
+                path: path.to_string(),
+                executable: true,
+                content: "echo".to_string(),
+            });
+            let err = Pack::from_yaml(&pack.to_yaml().unwrap()).unwrap_err();
+            assert!(err.to_string().contains("outside the project"), "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_resolution_from_str() {
+        assert_eq!(
+            "take-pack".parse::<Resolution>().unwrap(),
+            Resolution::TakePack
+        );
+        assert_eq!(
+            "keep_local".parse::<Resolution>().unwrap(),
+            Resolution::KeepLocal
+        );
+        assert_eq!("m".parse::<Resolution>().unwrap(), Resolution::Merge);
+        assert!("overwrite".parse::<Resolution>().is_err());
+    }
+}
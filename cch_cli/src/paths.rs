@@ -0,0 +1,178 @@
+//! Canonical paths and globs for the `protected_paths` matcher
+//!
+//! Substring checks on the path a tool reports are easy to sidestep with
+//! `src/../../etc/passwd` or a symlink into `~/.ssh`, so protected paths
+//! are compared canonically: relative paths are joined to the event's
+//! working directory, and the part of the path that exists is resolved by
+//! the filesystem (following symlinks and `..`). The rest, such as a file
+//! about to be written, is normalized lexically.
+
+use std::path::{Component, Path, PathBuf};
+
+use crate::regex_cache;
+
+/// The canonical form of `path`, with relative paths taken from `cwd`
+pub fn canonicalize(path: &str, cwd: &Path) -> PathBuf {
+    let path = expand_home(path);
+    let path = if path.is_absolute() {
+        path
+    } else {
+        cwd.join(path)
+    };
+
+    // Find the longest prefix that exists; the filesystem resolves it
+    let mut missing = Vec::new();
+    let mut existing = path.as_path();
+    let mut resolved = loop {
+        if let Ok(resolved) = std::fs::canonicalize(existing) {
+            break resolved;
+        }
+        match (existing.parent(), existing.components().next_back()) {
+            (Some(parent), Some(last)) => {
+                missing.push(last);
+                existing = parent;
+            }
+            _ => break PathBuf::from("/"),
+        }
+    };
+    for component in missing.into_iter().rev() {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::Normal(name) => resolved.push(name),
+            _ => {}
+        }
+    }
+    resolved
+}
+
+/// Whether the canonical `path` matches glob `pattern`
+///
+/// `**` spans directories while `*` and `?` stay within one component,
+/// and `dir/**` also matches `dir` itself. Relative patterns are anchored
+/// at `root`, and `~/` at the home directory.
+pub fn glob_matches(pattern: &str, path: &Path, root: &Path) -> bool {
+    let pattern = anchor(pattern, root);
+    regex_cache::compile(&glob_regex(&pattern)).is_ok_and(|re| re.is_match(&path.to_string_lossy()))
+}
+
+/// `pattern` as an absolute glob whose literal leading directories are
+/// canonical, so a pattern under a symlinked root still matches
+fn anchor(pattern: &str, root: &Path) -> String {
+    let is_glob = |part: &str| part.contains(['*', '?', '[']);
+    let parts: Vec<&str> = pattern.split('/').collect();
+    let literal = parts.iter().take_while(|part| !is_glob(part)).count();
+    let prefix = match parts[..literal].join("/") {
+        prefix if prefix.is_empty() && pattern.starts_with('/') => PathBuf::from("/"),
+        prefix if prefix.is_empty() => root.to_path_buf(),
+        prefix => canonicalize(&prefix, root),
+    };
+    let prefix = prefix.to_string_lossy();
+    match &parts[literal..] {
+        [] => prefix.into_owned(),
+        rest => format!("{}/{}", prefix.trim_end_matches('/'), rest.join("/")),
+    }
+}
+
+/// Translate a glob into an anchored regex
+fn glob_regex(glob: &str) -> String {
+    let (glob, subtree) = match glob.strip_suffix("/**") {
+        Some(dir) => (dir, true),
+        None => (glob, false),
+    };
+    let mut regex = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.next_if_eq(&'/').is_some() {
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                regex.push('[');
+                if chars.next_if_eq(&'!').is_some() {
+                    regex.push('^');
+                }
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                    if c == '\\' || c == '[' {
+                        regex.push('\\');
+                    }
+                    regex.push(c);
+                }
+                regex.push(']');
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    if subtree {
+        regex.push_str("(?:/.*)?");
+    }
+    regex.push('$');
+    regex
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonicalize_resolves_dots_and_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        std::fs::create_dir_all(root.join("project/src")).unwrap();
+        std::fs::create_dir_all(root.join("secrets")).unwrap();
+        let project = root.join("project");
+
+        assert_eq!(
+            canonicalize("src/../../secrets/key.pem", &project),
+            root.join("secrets/key.pem")
+        );
+        assert_eq!(
+            canonicalize("new/dir/../file.rs", &project),
+            project.join("new/file.rs")
+        );
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(root.join("secrets"), project.join("link")).unwrap();
+            assert_eq!(
+                canonicalize("link/key.pem", &project),
+                root.join("secrets/key.pem")
+            );
+        }
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        let root = Path::new("/work/app");
+        let matches = |pattern, path| glob_matches(pattern, Path::new(path), root);
+
+        assert!(matches("/etc/**", "/etc"));
+        assert!(matches("/etc/**", "/etc/ssh/sshd_config"));
+        assert!(!matches("/etc/**", "/etcetera/file"));
+        assert!(matches("**/.env", "/work/app/.env"));
+        assert!(matches("**/.env", "/work/app/services/api/.env"));
+        assert!(!matches("**/.env", "/work/app/.env.example"));
+        assert!(matches("config/*.yaml", "/work/app/config/prod.yaml"));
+        assert!(!matches("config/*.yaml", "/work/app/config/env/prod.yaml"));
+        assert!(matches("keys/id_?sa", "/work/app/keys/id_rsa"));
+        assert!(matches("keys/[!.]*", "/work/app/keys/prod.pem"));
+        assert!(!matches("keys/[!.]*", "/work/app/keys/.gitkeep"));
+    }
+}
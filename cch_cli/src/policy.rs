@@ -0,0 +1,340 @@
+//! High-level allow/deny policies
+//!
+//! The `policies:` section declares executable, network host, and filesystem
+//! allow/deny lists in one place:
+//!
+//! ```yaml
+//! policies:
+//!   executables:
+//!     deny: [curl, wget]
+//!   hosts:
+//!     allow: [docs.rs, github.com]
+//!   paths:
+//!     deny: [secrets/, /etc]
+//! ```
+//!
+//! At load time each list is compiled into an ordinary blocking rule named
+//! `policy-<kind>-<allow|deny>`, so logging, `explain`, and modes all work
+//! unchanged.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::models::{Actions, Matchers, Rule, path_is_under};
+
+/// Tools whose input names a filesystem path
+const FILE_TOOLS: &[&str] = &["Read", "Write", "Edit", "MultiEdit", "Glob", "Grep"];
+
+/// Declarative allow/deny lists
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Policies {
+    /// Executables invoked by Bash commands (matched by basename)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub executables: Option<AccessList>,
+
+    /// Network hosts fetched by WebFetch (subdomains included)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hosts: Option<AccessList>,
+
+    /// Filesystem trees touched by file tools
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paths: Option<AccessList>,
+}
+
+/// Allow and deny entries for one kind of resource
+///
+/// A non-empty `allow` list blocks everything not on it; `deny` entries are
+/// always blocked.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct AccessList {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+}
+
+/// Compiled policy condition attached to a generated rule's matchers
+#[derive(Debug, Clone, PartialEq)]
+pub enum PolicyMatcher {
+    /// Some executable in the command is listed
+    ExecutableIn(Vec<String>),
+    /// Some executable in the command is not listed
+    ExecutableNotIn(Vec<String>),
+    /// The fetched host is listed
+    HostIn(Vec<String>),
+    /// The fetched host is not listed
+    HostNotIn(Vec<String>),
+    /// The target path lies under a listed tree
+    PathUnder(Vec<String>),
+    /// The target path lies outside every listed tree
+    PathNotUnder(Vec<String>),
+}
+
+impl std::fmt::Display for PolicyMatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolicyMatcher::ExecutableIn(l) => write!(f, "executable in {:?}", l),
+            PolicyMatcher::ExecutableNotIn(l) => write!(f, "executable not in {:?}", l),
+            PolicyMatcher::HostIn(l) => write!(f, "host in {:?}", l),
+            PolicyMatcher::HostNotIn(l) => write!(f, "host not in {:?}", l),
+            PolicyMatcher::PathUnder(l) => write!(f, "path under {:?}", l),
+            PolicyMatcher::PathNotUnder(l) => write!(f, "path outside {:?}", l),
+        }
+    }
+}
+
+impl PolicyMatcher {
+    /// Evaluate the condition against a tool input
+    ///
+    /// Inputs that don't carry the relevant field never match.
+    pub fn matches(&self, tool_input: &serde_json::Value, project_root: Option<&str>) -> bool {
+        let command = || tool_input.get("command").and_then(|c| c.as_str());
+        let host = || {
+            tool_input
+                .get("url")
+                .and_then(|u| u.as_str())
+                .and_then(url_host)
+        };
+        let path = || {
+            tool_input
+                .get("file_path")
+                .or_else(|| tool_input.get("path"))
+                .and_then(|p| p.as_str())
+        };
+
+        match self {
+            PolicyMatcher::ExecutableIn(list) => command()
+                .is_some_and(|c| executables(c).iter().any(|e| list.iter().any(|l| l == e))),
+            PolicyMatcher::ExecutableNotIn(list) => command()
+                .is_some_and(|c| executables(c).iter().any(|e| !list.iter().any(|l| l == e))),
+            PolicyMatcher::HostIn(list) => host().is_some_and(|h| host_listed(&h, list)),
+            PolicyMatcher::HostNotIn(list) => host().is_some_and(|h| !host_listed(&h, list)),
+            PolicyMatcher::PathUnder(list) => {
+                path().is_some_and(|p| list.iter().any(|tree| path_is_under(tree, p, project_root)))
+            }
+            PolicyMatcher::PathNotUnder(list) => path()
+                .is_some_and(|p| !list.iter().any(|tree| path_is_under(tree, p, project_root))),
+        }
+    }
+}
+
+impl Policies {
+    /// Compile the declared lists into blocking rules
+    pub fn compile(&self) -> Vec<Rule> {
+        let mut rules = Vec::new();
+
+        if let Some(ref list) = self.executables {
+            push_rules(
+                &mut rules,
+                "executables",
+                list,
+                &["Bash"],
+                PolicyMatcher::ExecutableNotIn,
+                PolicyMatcher::ExecutableIn,
+            );
+        }
+        if let Some(ref list) = self.hosts {
+            push_rules(
+                &mut rules,
+                "hosts",
+                list,
+                &["WebFetch"],
+                PolicyMatcher::HostNotIn,
+                PolicyMatcher::HostIn,
+            );
+        }
+        if let Some(ref list) = self.paths {
+            push_rules(
+                &mut rules,
+                "paths",
+                list,
+                FILE_TOOLS,
+                PolicyMatcher::PathNotUnder,
+                PolicyMatcher::PathUnder,
+            );
+        }
+
+        rules
+    }
+}
+
+fn push_rules(
+    rules: &mut Vec<Rule>,
+    kind: &str,
+    list: &AccessList,
+    tools: &[&str],
+    not_allowed: fn(Vec<String>) -> PolicyMatcher,
+    denied: fn(Vec<String>) -> PolicyMatcher,
+) {
+    if !list.allow.is_empty() {
+        rules.push(policy_rule(
+            format!("policy-{}-allow", kind),
+            format!("Only allow-listed {} are permitted", kind),
+            tools,
+            not_allowed(list.allow.clone()),
+        ));
+    }
+    if !list.deny.is_empty() {
+        rules.push(policy_rule(
+            format!("policy-{}-deny", kind),
+            format!("Deny-listed {} are blocked", kind),
+            tools,
+            denied(list.deny.clone()),
+        ));
+    }
+}
+
+fn policy_rule(name: String, description: String, tools: &[&str], matcher: PolicyMatcher) -> Rule {
+    Rule {
+        name,
+        description: Some(description),
+        matchers: Matchers {
+            tools: Some(tools.iter().map(ToString::to_string).collect()),
+            extensions: None,
+            directories: None,
+            operations: None,
+            command_match: None,
+            zone: None,
+            exclude_tools: None,
+            exclude_extensions: None,
+            exclude_directories: None,
+            command_not_match: None,
+            tool_output_match: None,
+            exit_code: None,
+            prompt_match: None,
+            background: None,
+            protected_paths: None,
+            expr: None,
+            any: None,
+            all: None,
+            not: None,
+            policy: Some(matcher),
+        },
+        actions: Actions {
+            inject: None,
+            run: None,
+            block: Some(true),
+            block_if_match: None,
+            truncate_output: None,
+            filter_results: None,
+            structural_checks: None,
+            ensure_header: None,
+            inject_command: None,
+            ask: None,
+            require_files: None,
+            allow: None,
+            inject_once_per_session: None,
+            throttle: None,
+            enrich_prompt: None,
+            rewrite: None,
+        },
+        mode: None,
+        priority: None,
+        governance: None,
+        tests: None,
+        exemptions: None,
+        escalation: None,
+        bypass_users: None,
+        messages: None,
+        stop: None,
+        metadata: None,
+    }
+}
+
+/// Basenames of the executables a shell command invokes
+///
+/// Splits on `;`, `&&`, `||`, and `|`, skipping leading `VAR=value`
+/// assignments in each segment. Redirections like `2>&1` are not separators.
+fn executables(command: &str) -> Vec<String> {
+    command
+        .replace(">&", ">")
+        .replace("&>", ">")
+        .split(['\n', ';', '|', '&'])
+        .filter_map(|segment| {
+            segment
+                .split_whitespace()
+                .find(|token| !token.contains('=') || token.starts_with(['/', '.']))
+                .map(|token| {
+                    Path::new(token)
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or(token)
+                        .to_string()
+                })
+        })
+        .collect()
+}
+
+/// Extract the lowercase host from a URL without pulling in a URL parser
+fn url_host(url: &str) -> Option<String> {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit_once('@').map_or(authority, |(_, h)| h);
+    let host = host.split(':').next()?;
+    (!host.is_empty()).then(|| host.to_lowercase())
+}
+
+/// Whether a host or one of its parent domains is listed
+fn host_listed(host: &str, list: &[String]) -> bool {
+    list.iter().any(|entry| {
+        let entry = entry.to_lowercase();
+        host == entry || host.ends_with(&format!(".{}", entry))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_executables_split_on_separators() {
+        assert_eq!(
+            executables("FOO=1 /usr/bin/curl x 2>&1 | jq . && wget y; ls"),
+            vec!["curl", "jq", "wget", "ls"]
+        );
+    }
+
+    #[test]
+    fn test_url_host() {
+        assert_eq!(
+            url_host("https://user@Docs.rs:443/a?b").as_deref(),
+            Some("docs.rs")
+        );
+        assert_eq!(url_host("github.com/x").as_deref(), Some("github.com"));
+    }
+
+    #[test]
+    fn test_compiled_matchers() {
+        let policies: Policies = serde_yaml::from_str(
+            r"
+executables:
+  deny: [curl]
+hosts:
+  allow: [github.com]
+paths:
+  deny: [secrets/]
+",
+        )
+        .unwrap();
+        let rules = policies.compile();
+        let names: Vec<&str> = rules.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "policy-executables-deny",
+                "policy-hosts-allow",
+                "policy-paths-deny"
+            ]
+        );
+
+        let matcher = |i: usize| rules[i].matchers.policy.as_ref().unwrap();
+        assert!(matcher(0).matches(&json!({"command": "ls && curl evil.sh"}), None));
+        assert!(!matcher(0).matches(&json!({"command": "git status"}), None));
+        assert!(matcher(1).matches(&json!({"url": "https://pastebin.com/x"}), None));
+        assert!(!matcher(1).matches(&json!({"url": "https://api.github.com/x"}), None));
+        assert!(matcher(2).matches(&json!({"file_path": "/p/secrets/key"}), Some("/p")));
+        assert!(!matcher(2).matches(&json!({"file_path": "/p/src/main.rs"}), Some("/p")));
+    }
+}
@@ -0,0 +1,262 @@
+//! Compiled regex cache
+//!
+//! Rule patterns are compiled once per process and shared by every later
+//! evaluation, so a hook call doesn't recompile each rule's `command_match`
+//! and the daemon compiles a pattern only the first time it sees it.
+//! `Config::validate` compiles every rule pattern up front, which both warms
+//! the cache and reports invalid patterns as configuration errors.
+//!
+//! The cache holds at most [`MAX_PATTERNS`] patterns and starts over when it
+//! fills up, so a long-running daemon fed ever-new patterns (for instance by
+//! `expr` `matches()` on event fields) can't grow without bound.
+//!
+//! A one-shot `cch` invocation would still compile every pattern each time,
+//! so config snapshots also carry the patterns that compiled when the
+//! snapshot was written, with a serialized DFA for each one that only needs
+//! a yes/no answer (see [`build_dfa`]). Once [`restore`]d, [`check`] accepts
+//! those patterns without compiling them and [`is_match`] searches their
+//! DFA, leaving a `Regex` to be compiled only for patterns an event reaches
+//! that have no DFA.
+
+use regex::Regex;
+use regex_automata::Input;
+use regex_automata::dfa::{Automaton, dense};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::models::InvalidPattern;
+
+/// Most patterns kept compiled at once
+const MAX_PATTERNS: usize = 1024;
+
+/// Most invalid patterns waiting for an audit log entry
+const MAX_PENDING_FAILURES: usize = 64;
+
+/// Largest DFA built for a snapshot; patterns that determinize into more
+/// (large Unicode classes, long bounded repeats) keep using a `Regex`
+const MAX_DFA_BYTES: usize = 1 << 20;
+
+/// A DFA restored from a config snapshot
+pub type Dfa = dense::DFA<Vec<u32>>;
+
+static CACHE: OnceLock<Mutex<Cache>> = OnceLock::new();
+
+#[derive(Default)]
+struct Cache {
+    patterns: HashMap<String, Result<Regex, regex::Error>>,
+    /// Patterns restored from a config snapshot, known to compile, with
+    /// their DFA when the snapshot has one
+    restored: HashMap<String, Option<Arc<Dfa>>>,
+    /// Invalid patterns not yet reported in the audit log
+    failures: Vec<InvalidPattern>,
+}
+
+impl Cache {
+    fn compile(&mut self, pattern: &str) -> Result<Regex, regex::Error> {
+        if let Some(compiled) = self.patterns.get(pattern) {
+            return compiled.clone();
+        }
+        if self.patterns.len() >= MAX_PATTERNS {
+            self.patterns.clear();
+        }
+        let compiled = Regex::new(pattern);
+        if compiled.is_err() {
+            // Not just validation: every config source (layers, plugin
+            // fragments, configs restored from a snapshot without
+            // revalidation) and `expr` `matches()` arguments computed from
+            // event fields compile through here
+            let error = describe_error(pattern);
+            tracing::warn!(
+                "Pattern {:?} does not compile and never matches: {}",
+                pattern,
+                error
+            );
+            if self.failures.len() < MAX_PENDING_FAILURES {
+                self.failures.push(InvalidPattern {
+                    pattern: pattern.to_string(),
+                    error,
+                });
+            }
+        }
+        self.patterns.insert(pattern.to_string(), compiled.clone());
+        compiled
+    }
+
+    fn restore(&mut self, patterns: Vec<(String, Option<Dfa>)>) {
+        if self.restored.len() + patterns.len() > MAX_PATTERNS {
+            self.restored.clear();
+        }
+        self.restored.extend(
+            patterns
+                .into_iter()
+                .map(|(pattern, dfa)| (pattern, dfa.map(Arc::new))),
+        );
+    }
+}
+
+fn cache() -> std::sync::MutexGuard<'static, Cache> {
+    CACHE
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// The compiled form of `pattern`, compiling it on first use
+///
+/// `Regex` clones share the compiled program, so this is cheap. A pattern
+/// that doesn't compile never matches; it is reported by the next audit
+/// log entry (see [`take_failures`]).
+pub fn compile(pattern: &str) -> Result<Regex, regex::Error> {
+    cache().compile(pattern)
+}
+
+/// Check that `pattern` compiles, without compiling a pattern restored
+/// from a config snapshot
+pub fn check(pattern: &str) -> Result<(), regex::Error> {
+    let mut cache = cache();
+    if cache.restored.contains_key(pattern) {
+        return Ok(());
+    }
+    cache.compile(pattern).map(drop)
+}
+
+/// Whether `pattern` matches anywhere in `haystack`
+///
+/// Searches the pattern's restored DFA when it has one, falling back to the
+/// compiled `Regex` when it doesn't or when the DFA gives up (a Unicode
+/// word boundary next to non-ASCII text).
+pub fn is_match(pattern: &str, haystack: &str) -> Result<bool, regex::Error> {
+    let dfa = cache().restored.get(pattern).cloned().flatten();
+    if let Some(dfa) = dfa {
+        if let Ok(found) = dfa.try_search_fwd(&Input::new(haystack).earliest(true)) {
+            return Ok(found.is_some());
+        }
+    }
+    Ok(compile(pattern)?.is_match(haystack))
+}
+
+/// Register patterns from a config snapshot, each known to compile and
+/// with its DFA when the snapshot has one
+pub fn restore(patterns: Vec<(String, Option<Dfa>)>) {
+    cache().restore(patterns);
+}
+
+/// A DFA matching like `pattern`'s `Regex`, for a config snapshot; `None`
+/// when it would be larger than [`MAX_DFA_BYTES`]
+pub fn build_dfa(pattern: &str) -> Option<Dfa> {
+    let config = dense::Config::new()
+        .unicode_word_boundary(true)
+        .determinize_size_limit(Some(MAX_DFA_BYTES))
+        .dfa_size_limit(Some(MAX_DFA_BYTES));
+    dense::Builder::new().configure(config).build(pattern).ok()
+}
+
+/// Invalid patterns met since the last call, for the audit log
+pub fn take_failures() -> Vec<InvalidPattern> {
+    std::mem::take(&mut cache().failures)
+}
+
+/// One-line description of why `pattern` fails to compile, with the
+/// 1-based column of the problem when the parser reports one
+pub fn describe_error(pattern: &str) -> String {
+    let Err(error) = regex_syntax::Parser::new().parse(pattern) else {
+        // Valid syntax; the compiled program must be over the size limit
+        return match Regex::new(pattern) {
+            Err(regex::Error::CompiledTooBig(limit)) => {
+                format!("compiled pattern exceeds the {} byte size limit", limit)
+            }
+            Err(e) => e.to_string(),
+            Ok(_) => "pattern is valid".to_string(),
+        };
+    };
+    let (kind, span) = match &error {
+        regex_syntax::Error::Parse(e) => (e.kind().to_string(), *e.span()),
+        regex_syntax::Error::Translate(e) => (e.kind().to_string(), *e.span()),
+        _ => return error.to_string(),
+    };
+    format!("{} at column {}", kind, span.start.column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compile_caches_patterns_and_errors() {
+        let first = compile(r"git push.*--force").unwrap();
+        let second = compile(r"git push.*--force").unwrap();
+        assert_eq!(first.as_str(), second.as_str());
+        assert!(second.is_match("git push origin --force"));
+
+        assert!(compile("(unclosed").is_err());
+        assert!(compile("(unclosed").is_err());
+    }
+
+    #[test]
+    fn test_cache_reports_failures_once_and_stays_bounded() {
+        let mut cache = Cache::default();
+        assert!(cache.compile("(unclosed").is_err());
+        assert!(cache.compile("(unclosed").is_err());
+        assert_eq!(
+            std::mem::take(&mut cache.failures),
+            [InvalidPattern {
+                pattern: "(unclosed".to_string(),
+                error: "unclosed group at column 1".to_string(),
+            }]
+        );
+
+        for i in 0..=MAX_PATTERNS {
+            cache.compile(&format!("pattern-{}", i)).unwrap();
+        }
+        assert!(cache.patterns.len() <= MAX_PATTERNS);
+        assert!(cache.compile("pattern-0").unwrap().is_match("pattern-0"));
+        assert!(cache.failures.is_empty());
+    }
+
+    #[test]
+    fn test_restored_dfa_matches_like_regex() {
+        let patterns = [r"git push.*--force", r"(?i)\bDROP\s+TABLE\b", r"^rm -rf /$"];
+        let haystacks = [
+            "git push origin main --force",
+            "git push origin main",
+            "psql -c 'drop table users'",
+            "psql -c 'drop tables'",
+            "é drop table é",
+            "rm -rf /",
+            "",
+        ];
+        let restored = patterns
+            .iter()
+            .map(|p| {
+                let (bytes, pad) = build_dfa(p).unwrap().to_bytes_native_endian();
+                let dfa = dense::DFA::from_bytes(&bytes[pad..]).unwrap().0.to_owned();
+                (p.to_string(), Some(dfa))
+            })
+            .collect();
+        restore(restored);
+
+        for pattern in patterns {
+            assert!(check(pattern).is_ok());
+            let regex = Regex::new(pattern).unwrap();
+            for haystack in haystacks {
+                assert_eq!(
+                    is_match(pattern, haystack).unwrap(),
+                    regex.is_match(haystack),
+                    "{:?} on {:?}",
+                    pattern,
+                    haystack
+                );
+            }
+        }
+        assert!(is_match("(unclosed", "anything").is_err());
+    }
+
+    #[test]
+    fn test_describe_error_reports_column() {
+        assert_eq!(describe_error("git (push"), "unclosed group at column 5");
+        assert_eq!(
+            describe_error(r"rm -rf \q"),
+            "unrecognized escape sequence at column 8"
+        );
+    }
+}
@@ -0,0 +1,347 @@
+//! Per-session state store
+//!
+//! Small JSON documents under `~/.claude/state/sessions/`, one per session,
+//! that let rules remember what happened earlier in the same session (e.g.
+//! how often a rule has already blocked), and `global.json` beside them for
+//! what is remembered across sessions. `cch clean --state` removes them.
+//!
+//! Session state holds fragments of commands, so with
+//! `settings.encrypt_state` the files are age-encrypted to a machine-local
+//! key (see [`crate::encryption`]); either form is read back. With
+//! `settings.state_ttl_days`, session files left untouched that long are
+//! removed.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::encryption;
+
+/// Get the state directory (~/.claude/state)
+pub fn state_dir() -> Result<PathBuf> {
+    let home = dirs::home_dir().context("Could not determine home directory")?;
+    Ok(home.join(".claude").join("state"))
+}
+
+/// Get the cache directory (~/.claude/cache/cch)
+pub fn cache_dir() -> Result<PathBuf> {
+    let home = dirs::home_dir().context("Could not determine home directory")?;
+    Ok(home.join(".claude").join("cache").join("cch"))
+}
+
+/// Get the runtime directory (~/.claude/run/cch)
+///
+/// Holds live endpoints such as the daemon socket, apart from the state
+/// and cache directories that `cch clean` removes.
+pub fn runtime_dir() -> Result<PathBuf> {
+    let home = dirs::home_dir().context("Could not determine home directory")?;
+    Ok(home.join(".claude").join("run").join("cch"))
+}
+
+/// Session ID made safe for use as a file name
+pub fn session_file_stem(session_id: &str) -> String {
+    session_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// State remembered across events of one session
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionState {
+    /// Offense counts per rule, keyed by the offending pattern
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub offenses: BTreeMap<String, BTreeMap<String, u32>>,
+
+    /// Identical block counts, keyed by a digest of the block reason
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub blocks: BTreeMap<String, u32>,
+
+    /// Whether the session was already shown `settings.session_banner`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub banner_shown: bool,
+
+    /// Times each context file with `max_injections` front matter was
+    /// injected, keyed by its path
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub injections: BTreeMap<String, u32>,
+
+    /// Inject sources sent by rules with `inject_once_per_session`
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub sent_injections: BTreeSet<String>,
+
+    /// Glossary terms (`glossary:<term>`) and instructions
+    /// (`instruction:<text>`) sent by `enrich_prompt` rules
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub sent_enrichments: BTreeSet<String>,
+
+    /// Firings of rules throttled per session
+    #[serde(default, skip_serializing_if = "Firings::is_empty")]
+    pub firings: Firings,
+}
+
+impl SessionState {
+    fn path(session_id: &str) -> Result<PathBuf> {
+        Ok(state_dir()?
+            .join("sessions")
+            .join(format!("{}.json", session_file_stem(session_id))))
+    }
+
+    /// Load a session's state, starting empty if none was saved yet
+    pub fn load(session_id: &str) -> Result<Self> {
+        load_json(&Self::path(session_id)?)
+    }
+
+    /// Persist the state, replacing the previous file atomically
+    pub fn save(&self, session_id: &str, encrypt: bool) -> Result<()> {
+        save_json(&Self::path(session_id)?, self, encrypt)
+    }
+
+    /// Offenses recorded so far for a rule and pattern
+    pub fn offenses(&self, rule: &str, key: &str) -> u32 {
+        self.offenses
+            .get(rule)
+            .and_then(|keys| keys.get(key))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Offenses recorded so far for a rule across all patterns
+    pub fn rule_offenses(&self, rule: &str) -> u32 {
+        self.offenses
+            .get(rule)
+            .map_or(0, |keys| keys.values().sum())
+    }
+
+    /// Record one offense and return the new count for the pattern
+    pub fn record_offense(&mut self, rule: &str, key: &str) -> u32 {
+        let count = self
+            .offenses
+            .entry(rule.to_string())
+            .or_default()
+            .entry(key.to_string())
+            .or_default();
+        *count += 1;
+        *count
+    }
+
+    /// Record one injection of a context file and return the new count
+    pub fn record_injection(&mut self, path: &str) -> u32 {
+        let count = self.injections.entry(path.to_string()).or_default();
+        *count += 1;
+        *count
+    }
+
+    /// Record a block with this reason and return how often it was sent
+    pub fn record_block(&mut self, reason: &str) -> u32 {
+        let key = format!("{:x}", Sha256::digest(reason.as_bytes()));
+        let count = self.blocks.entry(key).or_default();
+        *count += 1;
+        *count
+    }
+}
+
+/// State remembered across sessions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GlobalState {
+    /// Firings of rules throttled globally
+    #[serde(default, skip_serializing_if = "Firings::is_empty")]
+    pub firings: Firings,
+}
+
+impl GlobalState {
+    fn path() -> Result<PathBuf> {
+        Ok(state_dir()?.join("global.json"))
+    }
+
+    /// Load the global state, starting empty if none was saved yet
+    pub fn load() -> Result<Self> {
+        load_json(&Self::path()?)
+    }
+
+    /// Persist the state, replacing the previous file atomically
+    pub fn save(&self, encrypt: bool) -> Result<()> {
+        save_json(&Self::path()?, self, encrypt)
+    }
+}
+
+/// Session and global state held in memory instead of ~/.claude/state, for
+/// evaluations that must neither depend on nor change the real state
+#[derive(Debug, Clone, Default)]
+pub struct MemoryState {
+    /// Session state keyed by session ID
+    pub sessions: BTreeMap<String, SessionState>,
+
+    pub global: GlobalState,
+}
+
+/// When throttled rules fired, keyed by rule name
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Firings(BTreeMap<String, Vec<DateTime<Utc>>>);
+
+impl Firings {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Record that `rule` fires at `now`, unless it already fired `max`
+    /// times within `window` before it (ever, without a window); returns
+    /// whether it fires. Firings older than the window are forgotten.
+    pub fn try_fire(
+        &mut self,
+        rule: &str,
+        max: u32,
+        window: Option<Duration>,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let times = self.0.entry(rule.to_string()).or_default();
+        if let Some(window) = window {
+            times.retain(|time| *time > now - window);
+        }
+        let fires = times.len() < max as usize;
+        if fires {
+            times.push(now);
+        }
+        fires
+    }
+}
+
+/// Remove session state not written for `ttl_days`, checking at most once
+/// a day; returns how many sessions were removed
+pub fn expire_sessions(ttl_days: u32) -> Result<usize> {
+    let dir = state_dir()?.join("sessions");
+    let marker = dir.join(".expired");
+    let day = std::time::Duration::from_secs(24 * 60 * 60);
+    let checked_recently = std::fs::metadata(&marker)
+        .and_then(|m| m.modified())
+        .is_ok_and(|checked| checked.elapsed().is_ok_and(|age| age < day));
+    if checked_recently || !dir.exists() {
+        return Ok(0);
+    }
+    let cutoff = SystemTime::now() - day * ttl_days;
+    let removed = remove_older_than(&dir, cutoff)?;
+    std::fs::write(&marker, b"")?;
+    Ok(removed)
+}
+
+/// Remove the `.json` files in `dir` last modified before `cutoff`
+fn remove_older_than(dir: &Path, cutoff: SystemTime) -> Result<usize> {
+    let mut removed = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let stale = path.extension().is_some_and(|ext| ext == "json")
+            && std::fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .is_ok_and(|modified| modified < cutoff);
+        if stale && std::fs::remove_file(&path).is_ok() {
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+fn load_json<T: DeserializeOwned + Default>(path: &Path) -> Result<T> {
+    let content = match std::fs::read(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(T::default()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    let content = if encryption::is_encrypted(&content) {
+        encryption::decrypt_state(&content)
+            .with_context(|| format!("Failed to decrypt {}", path.display()))?
+    } else {
+        content
+    };
+    serde_json::from_slice(&content)
+        .with_context(|| format!("Malformed state file {}", path.display()))
+}
+
+fn save_json<T: Serialize>(path: &Path, value: &T, encrypt: bool) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut content = serde_json::to_vec(value)?;
+    if encrypt {
+        content = encryption::encrypt_state(&content)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, content)?;
+    std::fs::rename(&tmp, path).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offense_counts() {
+        let mut state = SessionState::default();
+        assert_eq!(state.record_offense("r", "git push -f"), 1);
+        assert_eq!(state.record_offense("r", "git push -f"), 2);
+        assert_eq!(state.record_offense("r", "git push --force"), 1);
+        assert_eq!(state.offenses("r", "git push -f"), 2);
+        assert_eq!(state.rule_offenses("r"), 3);
+        assert_eq!(state.rule_offenses("other"), 0);
+    }
+
+    #[test]
+    fn test_block_counts_by_reason() {
+        let mut state = SessionState::default();
+        assert_eq!(state.record_block("Blocked by rule 'a'"), 1);
+        assert_eq!(state.record_block("Blocked by rule 'a'"), 2);
+        assert_eq!(state.record_block("Blocked by rule 'b'"), 1);
+    }
+
+    #[test]
+    fn test_firings_respect_max_and_window() {
+        let mut firings = Firings::default();
+        let start = Utc::now();
+        let window = Some(Duration::minutes(10));
+        let at = |minutes| start + Duration::minutes(minutes);
+
+        assert!(firings.try_fire("r", 2, window, at(0)));
+        assert!(firings.try_fire("r", 2, window, at(1)));
+        assert!(!firings.try_fire("r", 2, window, at(5)));
+        assert!(firings.try_fire("other", 2, window, at(5)));
+        // The first firing has left the window
+        assert!(firings.try_fire("r", 2, window, at(10)));
+        assert!(!firings.try_fire("r", 2, window, at(10)));
+
+        assert!(firings.try_fire("once", 1, None, at(0)));
+        assert!(!firings.try_fire("once", 1, None, at(60 * 24 * 365)));
+    }
+
+    #[test]
+    fn test_remove_older_than() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        let hour = std::time::Duration::from_secs(60 * 60);
+        for (name, age) in [("old.json", 48), ("new.json", 1), ("old.txt", 48)] {
+            let file = std::fs::File::create(dir.path().join(name)).unwrap();
+            file.set_modified(now - hour * age).unwrap();
+        }
+
+        assert_eq!(remove_older_than(dir.path(), now - hour * 24).unwrap(), 1);
+        assert!(!dir.path().join("old.json").exists());
+        assert!(dir.path().join("new.json").exists());
+        assert!(dir.path().join("old.txt").exists());
+    }
+
+    #[test]
+    fn test_session_file_stem() {
+        assert_eq!(session_file_stem("abc-123_x"), "abc-123_x");
+        assert_eq!(session_file_stem("../etc/passwd"), "___etc_passwd");
+    }
+}
@@ -0,0 +1,265 @@
+//! Language-aware structural content checks
+//!
+//! Built-in validators backed by tree-sitter grammars, for checks that
+//! regexes can't express reliably (e.g. "every new function has a doc
+//! comment"). The parsers are only compiled in with the
+//! `structural-checks` cargo feature to keep the default binary small.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// A built-in structural check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StructuralCheck {
+    /// Every function must carry a doc comment (Rust `///`, Python docstring)
+    RequireFnDocs,
+    /// Every TODO/FIXME comment must reference a ticket (e.g. `PROJ-123`, `#42`)
+    TodoRequiresTicket,
+}
+
+impl std::fmt::Display for StructuralCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StructuralCheck::RequireFnDocs => write!(f, "require_fn_docs"),
+            StructuralCheck::TodoRequiresTicket => write!(f, "todo_requires_ticket"),
+        }
+    }
+}
+
+/// A single structural check failure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// Check that failed
+    pub check: StructuralCheck,
+    /// 1-based line number within the checked content
+    pub line: usize,
+    /// Human-readable description
+    pub message: String,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {} ({})", self.line, self.message, self.check)
+    }
+}
+
+/// Whether this binary was built with tree-sitter support
+pub const fn is_available() -> bool {
+    cfg!(feature = "structural-checks")
+}
+
+/// Run structural checks against `content`, choosing the grammar from the
+/// extension of `path`
+///
+/// Files in languages without a bundled grammar produce no violations.
+#[cfg(feature = "structural-checks")]
+pub fn run_checks(checks: &[StructuralCheck], path: &str, content: &str) -> Result<Vec<Violation>> {
+    grammar::run_checks(checks, path, content)
+}
+
+/// Run structural checks (unavailable: built without `structural-checks`)
+#[cfg(not(feature = "structural-checks"))]
+pub fn run_checks(
+    _checks: &[StructuralCheck],
+    _path: &str,
+    _content: &str,
+) -> Result<Vec<Violation>> {
+    Err(anyhow::anyhow!(
+        "structural checks require CCH built with `--features structural-checks`"
+    ))
+}
+
+#[cfg(feature = "structural-checks")]
+mod grammar {
+    use anyhow::{Context, Result};
+    use regex::Regex;
+    use std::path::Path;
+    use std::sync::OnceLock;
+    use tree_sitter::{Language, Node, Parser};
+
+    use super::{StructuralCheck, Violation};
+
+    #[derive(Clone, Copy)]
+    enum Lang {
+        Rust,
+        Python,
+    }
+
+    impl Lang {
+        fn from_path(path: &str) -> Option<Self> {
+            match Path::new(path).extension().and_then(|e| e.to_str()) {
+                Some("rs") => Some(Lang::Rust),
+                Some("py") => Some(Lang::Python),
+                _ => None,
+            }
+        }
+
+        fn language(self) -> Language {
+            match self {
+                Lang::Rust => tree_sitter_rust::LANGUAGE.into(),
+                Lang::Python => tree_sitter_python::LANGUAGE.into(),
+            }
+        }
+    }
+
+    fn ticket_regex() -> &'static Regex {
+        static TICKET: OnceLock<Regex> = OnceLock::new();
+        TICKET.get_or_init(|| Regex::new(r"[A-Z][A-Z0-9]+-\d+|#\d+").expect("valid regex"))
+    }
+
+    pub(super) fn run_checks(
+        checks: &[StructuralCheck],
+        path: &str,
+        content: &str,
+    ) -> Result<Vec<Violation>> {
+        let Some(lang) = Lang::from_path(path) else {
+            return Ok(Vec::new());
+        };
+
+        let mut parser = Parser::new();
+        parser
+            .set_language(&lang.language())
+            .context("Failed to load tree-sitter grammar")?;
+        let tree = parser
+            .parse(content, None)
+            .context("tree-sitter failed to parse content")?;
+
+        let source = content.as_bytes();
+        let mut violations = Vec::new();
+        visit(tree.root_node(), &mut |node| {
+            for check in checks {
+                if let Some(message) = check_node(*check, lang, node, source) {
+                    violations.push(Violation {
+                        check: *check,
+                        line: node.start_position().row + 1,
+                        message,
+                    });
+                }
+            }
+        });
+
+        Ok(violations)
+    }
+
+    fn visit<'t>(node: Node<'t>, f: &mut impl FnMut(Node<'t>)) {
+        f(node);
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            visit(child, f);
+        }
+    }
+
+    fn check_node(check: StructuralCheck, lang: Lang, node: Node, source: &[u8]) -> Option<String> {
+        match check {
+            StructuralCheck::RequireFnDocs => check_fn_docs(lang, node, source),
+            StructuralCheck::TodoRequiresTicket => check_todo_ticket(node, source),
+        }
+    }
+
+    fn check_fn_docs(lang: Lang, node: Node, source: &[u8]) -> Option<String> {
+        let documented = match (lang, node.kind()) {
+            (Lang::Rust, "function_item" | "function_signature_item") => has_rust_doc(node, source),
+            (Lang::Python, "function_definition") => has_python_docstring(node),
+            _ => return None,
+        };
+        if documented {
+            return None;
+        }
+
+        let name = node
+            .child_by_field_name("name")
+            .and_then(|n| n.utf8_text(source).ok())
+            .unwrap_or("<anonymous>");
+        Some(format!("function '{}' has no doc comment", name))
+    }
+
+    /// Rust doc comments are `///` or `/** */` siblings preceding the item,
+    /// possibly separated by attributes
+    fn has_rust_doc(node: Node, source: &[u8]) -> bool {
+        let mut prev = node.prev_sibling();
+        while let Some(sibling) = prev {
+            match sibling.kind() {
+                "attribute_item" => prev = sibling.prev_sibling(),
+                "line_comment" | "block_comment" => {
+                    let text = sibling.utf8_text(source).unwrap_or("");
+                    return text.starts_with("///") || text.starts_with("/**");
+                }
+                _ => return false,
+            }
+        }
+        false
+    }
+
+    /// Python docstrings are a string expression as the first body statement
+    fn has_python_docstring(node: Node) -> bool {
+        node.child_by_field_name("body")
+            .and_then(|body| body.named_child(0))
+            .filter(|stmt| stmt.kind() == "expression_statement")
+            .and_then(|stmt| stmt.named_child(0))
+            .is_some_and(|expr| expr.kind() == "string")
+    }
+
+    fn check_todo_ticket(node: Node, source: &[u8]) -> Option<String> {
+        if !matches!(node.kind(), "line_comment" | "block_comment" | "comment") {
+            return None;
+        }
+        let text = node.utf8_text(source).unwrap_or("");
+        let has_marker = text.contains("TODO") || text.contains("FIXME");
+        if has_marker && !ticket_regex().is_match(text) {
+            return Some("TODO/FIXME comment without a ticket reference".to_string());
+        }
+        None
+    }
+}
+
+#[cfg(all(test, feature = "structural-checks"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rust_fn_without_doc() {
+        let content = "/// Documented\nfn a() {}\n\n#[inline]\nfn b() {}\n";
+        let violations =
+            run_checks(&[StructuralCheck::RequireFnDocs], "src/lib.rs", content).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].line, 5);
+        assert!(violations[0].message.contains("'b'"));
+    }
+
+    #[test]
+    fn test_rust_doc_before_attribute() {
+        let content = "/// Documented\n#[inline]\nfn a() {}\n";
+        let violations =
+            run_checks(&[StructuralCheck::RequireFnDocs], "src/lib.rs", content).unwrap();
+        assert!(violations.is_empty());
+    }
+
+    #[test]
+    fn test_python_docstring() {
+        let content = "def a():\n    \"\"\"Doc.\"\"\"\n    pass\n\ndef b():\n    pass\n";
+        let violations = run_checks(&[StructuralCheck::RequireFnDocs], "app.py", content).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].message.contains("'b'"));
+    }
+
+    #[test]
+    fn test_todo_requires_ticket_ignores_strings() {
+        let content = "// TODO: fix this\n// TODO(PROJ-12): tracked\nlet s = \"TODO\";\n";
+        let violations = run_checks(
+            &[StructuralCheck::TodoRequiresTicket],
+            "src/lib.rs",
+            content,
+        )
+        .unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].line, 1);
+    }
+
+    #[test]
+    fn test_unknown_language_is_skipped() {
+        let violations =
+            run_checks(&[StructuralCheck::RequireFnDocs], "notes.md", "fn a() {}").unwrap();
+        assert!(violations.is_empty());
+    }
+}
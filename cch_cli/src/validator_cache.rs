@@ -0,0 +1,93 @@
+//! Cached validator results
+//!
+//! A validator declared `cacheable: true` promises that its verdict depends
+//! only on the content being written. Its responses are cached under
+//! `~/.claude/cache/cch/validators/`, keyed by a hash of the script, the
+//! rule's `run.env`, and the content, so re-validating unchanged content
+//! across repeated edits skips the subprocess entirely. Editing the script
+//! changes its hash and so retires its old entries; `cch clean --cache`
+//! removes them.
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+use crate::models::Response;
+use crate::state::cache_dir;
+
+/// Get the validator cache directory (~/.claude/cache/cch/validators)
+pub fn validator_cache_dir() -> Result<PathBuf> {
+    Ok(cache_dir()?.join("validators"))
+}
+
+/// Cache key for running `script_path` with `env` on `content`
+///
+/// Returns `None` when the script can't be read, in which case the
+/// validator simply runs uncached.
+pub fn key<'a>(
+    script_path: &str,
+    env: impl Iterator<Item = (&'a String, &'a String)>,
+    content: &str,
+) -> Option<String> {
+    let script = std::fs::read(script_path).ok()?;
+    let mut hasher = Sha256::new();
+    hasher.update(Sha256::digest(&script));
+    for (name, value) in env {
+        hasher.update(format!("{}={}\0", name, value));
+    }
+    hasher.update(Sha256::digest(content.as_bytes()));
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+/// A cached response, if there is a readable one
+pub fn load(dir: &Path, key: &str) -> Option<Response> {
+    let content = std::fs::read_to_string(dir.join(format!("{}.json", key))).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Cache a validator's response
+pub fn store(dir: &Path, key: &str, response: &Response) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}.json", key));
+    let tmp = path.with_extension(format!("json.{}.tmp", std::process::id()));
+    std::fs::write(&tmp, serde_json::to_string(response)?)?;
+    std::fs::rename(&tmp, &path).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_key_tracks_script_env_and_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("check.sh");
+        std::fs::write(&script, "exit 0").unwrap();
+        let script = script.to_str().unwrap();
+        let env = BTreeMap::from([("MAX_LINES".to_string(), "400".to_string())]);
+
+        let base = key(script, env.iter(), "fn main() {}").unwrap();
+        assert_eq!(key(script, env.iter(), "fn main() {}").unwrap(), base);
+        assert_ne!(key(script, env.iter(), "fn main() { }").unwrap(), base);
+        assert_ne!(
+            key(script, std::iter::empty(), "fn main() {}").unwrap(),
+            base
+        );
+
+        std::fs::write(script, "exit 1").unwrap();
+        assert_ne!(key(script, env.iter(), "fn main() {}").unwrap(), base);
+        assert!(key("/nonexistent/check.sh", env.iter(), "").is_none());
+    }
+
+    #[test]
+    fn test_store_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = dir.path().join("validators");
+        assert!(load(&cache, "abc").is_none());
+
+        let response = Response::block("Too long".to_string());
+        store(&cache, "abc", &response).unwrap();
+        assert_eq!(load(&cache, "abc"), Some(response));
+    }
+}
@@ -0,0 +1,175 @@
+//! Validators compiled to WebAssembly
+//!
+//! `run: { wasm: <path> }` runs a validator module inside CCH with the wasmi
+//! interpreter instead of spawning a process, so it starts without an
+//! interpreter and runs unchanged on every platform. Modules may not import
+//! anything (no filesystem, network, clock or environment), and each call
+//! runs under a fuel budget and a memory cap, so a module can neither reach
+//! outside its sandbox nor spin or allocate without bound. The interpreter
+//! is only compiled in with the `wasm-validators` cargo feature.
+//!
+//! A module exports `memory`, `alloc(len: i32) -> i32` and
+//! `validate(ptr: i32, len: i32) -> i64`. CCH writes the event JSON (as a
+//! script validator reads it on stdin) at the address `alloc` returns and
+//! calls `validate`, which returns where its verdict is, packed as
+//! `ptr << 32 | len`. The verdict is the JSON a script validator may print:
+//! `{"decision": "allow" | "block" | "warn", "reason": ..., "context": ...}`.
+
+use anyhow::Result;
+
+/// Whether this binary can run WebAssembly validators
+pub const fn is_available() -> bool {
+    cfg!(feature = "wasm-validators")
+}
+
+/// Run the module at `path` over `input` (the event JSON), returning the
+/// verdict it produced
+#[cfg(feature = "wasm-validators")]
+pub fn run(path: &str, input: &str) -> Result<String> {
+    use anyhow::Context;
+
+    let wasm =
+        std::fs::read(path).with_context(|| format!("Failed to read WASM validator '{}'", path))?;
+    runtime::run(&wasm, input).with_context(|| format!("WASM validator '{}' failed", path))
+}
+
+/// Run a WebAssembly validator (unavailable: built without
+/// `wasm-validators`)
+#[cfg(not(feature = "wasm-validators"))]
+pub fn run(_path: &str, _input: &str) -> Result<String> {
+    Err(anyhow::anyhow!(
+        "WASM validators require CCH built with `--features wasm-validators`"
+    ))
+}
+
+#[cfg(feature = "wasm-validators")]
+mod runtime {
+    use anyhow::{Context, Result};
+    use wasmi::{Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+    /// Fuel a module may burn per call, roughly one unit per instruction
+    const FUEL: u64 = 100_000_000;
+
+    /// Most linear memory a module may have, in bytes
+    const MAX_MEMORY_BYTES: usize = 64 << 20;
+
+    pub(super) fn run(wasm: &[u8], input: &str) -> Result<String> {
+        let mut config = wasmi::Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, wasm).context("Invalid WebAssembly module")?;
+        if let Some(import) = module.imports().next() {
+            anyhow::bail!(
+                "module imports '{}::{}'; validators may not import anything",
+                import.module(),
+                import.name()
+            );
+        }
+
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(MAX_MEMORY_BYTES)
+            .trap_on_grow_failure(true)
+            .build();
+        let mut store = Store::new(&engine, limits);
+        store.limiter(|limits: &mut StoreLimits| limits);
+        store
+            .set_fuel(FUEL)
+            .map_err(|e| anyhow::anyhow!("Failed to set fuel: {}", e))?;
+        let instance = Linker::<StoreLimits>::new(&engine)
+            .instantiate(&mut store, &module)?
+            .start(&mut store)?;
+
+        let memory = instance
+            .get_memory(&store, "memory")
+            .context("module does not export `memory`")?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&store, "alloc")
+            .context("module does not export `alloc(len: i32) -> i32`")?;
+        let validate = instance
+            .get_typed_func::<(i32, i32), i64>(&store, "validate")
+            .context("module does not export `validate(ptr: i32, len: i32) -> i64`")?;
+
+        let len = i32::try_from(input.len()).context("event too large for a WASM validator")?;
+        let ptr = alloc.call(&mut store, len)?;
+        memory
+            .write(&mut store, address(ptr)?, input.as_bytes())
+            .map_err(|e| anyhow::anyhow!("`alloc` returned an address outside memory: {}", e))?;
+        let packed = validate.call(&mut store, (ptr, len))?;
+
+        #[allow(clippy::cast_sign_loss)]
+        let packed = packed as u64;
+        let mut verdict = vec![0; usize::try_from(packed & 0xffff_ffff)?];
+        memory
+            .read(&store, usize::try_from(packed >> 32)?, &mut verdict)
+            .map_err(|e| anyhow::anyhow!("`validate` returned a verdict outside memory: {}", e))?;
+        String::from_utf8(verdict).context("verdict is not UTF-8")
+    }
+
+    fn address(ptr: i32) -> Result<usize> {
+        usize::try_from(ptr).context("`alloc` returned a negative address")
+    }
+}
+
+#[cfg(all(test, feature = "wasm-validators"))]
+mod tests {
+    use super::runtime;
+
+    /// A module answering `verdict`, or echoing its input when `None`;
+    /// `body` runs first inside `validate`
+    fn module(verdict: Option<&str>, body: &str) -> Vec<u8> {
+        let result = match verdict {
+            // The verdict is at address 0
+            Some(verdict) => format!("(i64.const {})", verdict.len()),
+            None => "(i64.or (i64.shl (i64.extend_i32_u (local.get 0)) (i64.const 32)) \
+                     (i64.extend_i32_u (local.get 1)))"
+                .to_string(),
+        };
+        wat::parse_str(format!(
+            r#"(module
+                (memory (export "memory") 1)
+                (data (i32.const 0) "{}")
+                (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+                (func (export "validate") (param i32 i32) (result i64) {} {}))"#,
+            verdict.unwrap_or("").replace('"', "\\\""),
+            body,
+            result
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_returns_verdict() {
+        let verdict = r#"{"decision":"block","reason":"no"}"#;
+        let wasm = module(Some(verdict), "");
+        assert_eq!(runtime::run(&wasm, "{}").unwrap(), verdict);
+    }
+
+    #[test]
+    fn test_module_reads_event() {
+        let event = r#"{"tool_name":"Write"}"#;
+        assert_eq!(runtime::run(&module(None, ""), event).unwrap(), event);
+    }
+
+    #[test]
+    fn test_runaway_module_runs_out_of_fuel() {
+        let wasm = module(Some("{}"), "(loop $spin (br $spin))");
+        let error = format!("{:#}", runtime::run(&wasm, "{}").unwrap_err());
+        assert!(error.contains("fuel"), "{}", error);
+    }
+
+    #[test]
+    fn test_memory_is_capped() {
+        let wasm = module(Some("{}"), "(drop (memory.grow (i32.const 2048)))");
+        assert!(runtime::run(&wasm, "{}").is_err());
+    }
+
+    #[test]
+    fn test_module_with_imports_is_rejected() {
+        let wasm = wat::parse_str(
+            r#"(module (import "env" "clock" (func)) (memory (export "memory") 1))"#,
+        )
+        .unwrap();
+        let error = runtime::run(&wasm, "{}").unwrap_err().to_string();
+        assert!(error.contains("env::clock"), "{}", error);
+    }
+}
@@ -169,6 +169,102 @@ fn test_debug_detects_blocked_command() {
         .stdout(predicate::str::contains("block-force-push"));
 }
 
+#[test]
+fn test_debug_writes_audit_log_only_with_record() {
+    let temp_dir = TempDir::new().unwrap();
+    let home = TempDir::new().unwrap();
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["init"])
+        .assert()
+        .success();
+    let log = home.path().join(".claude/logs/cch.log");
+    let debug = |extra: &[&str]| {
+        cch_cmd()
+            .current_dir(temp_dir.path())
+            .env("HOME", home.path())
+            .args([
+                "debug",
+                "PreToolUse",
+                "--command",
+                "git push --force origin main",
+            ])
+            .args(extra)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Blocked"));
+    };
+
+    debug(&[]);
+    assert_eq!(
+        fs::read_to_string(&log).unwrap_or_default(),
+        "",
+        "simulations stay out of the audit log"
+    );
+
+    debug(&["--record"]);
+    let content = fs::read_to_string(&log).unwrap();
+    assert_eq!(content.lines().count(), 1);
+    assert!(content.contains("block-force-push"));
+}
+
+#[test]
+fn test_debug_leaves_session_state_untouched() {
+    let temp_dir = TempDir::new().unwrap();
+    let home = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join(".claude")).unwrap();
+    fs::write(
+        temp_dir.path().join(".claude/hooks.yaml"),
+        r#"
+version: "1.0"
+settings:
+  repeat_block_limit: 1
+rules:
+  - name: block-force-push-once
+    matchers:
+      tools: [Bash]
+      command_match: "git push --force"
+    actions:
+      block: true
+      throttle: { max: 1, scope: global }
+"#,
+    )
+    .unwrap();
+    let state_files = || -> Vec<(String, Vec<u8>)> {
+        let Ok(entries) = fs::read_dir(home.path().join(".claude/state")) else {
+            return Vec::new();
+        };
+        let mut files: Vec<_> = entries
+            .map(|e| e.unwrap().path())
+            .filter(|p| p.is_file())
+            .map(|p| (p.display().to_string(), fs::read(&p).unwrap()))
+            .collect();
+        files.sort();
+        files
+    };
+    let debug = |extra: &[&str]| {
+        cch_cmd()
+            .current_dir(temp_dir.path())
+            .env("HOME", home.path())
+            .args(["debug", "PreToolUse", "--command", "git push --force"])
+            .args(extra)
+            .assert()
+            .success()
+    };
+
+    // A simulation never consumes the throttle, so both block
+    debug(&[]).stdout(predicate::str::contains("Blocked"));
+    let after_first = state_files();
+    debug(&[]).stdout(predicate::str::contains("Blocked"));
+    assert_eq!(state_files(), after_first);
+    assert!(after_first.is_empty(), "{:?}", after_first);
+
+    // Recorded events do
+    debug(&["--record"]).stdout(predicate::str::contains("Blocked"));
+    assert!(!state_files().is_empty());
+    debug(&["--record"]).stdout(predicate::str::contains("Blocked").not());
+}
+
 #[test]
 fn test_debug_verbose_shows_rules() {
     let temp_dir = TempDir::new().unwrap();
@@ -290,6 +386,55 @@ fn test_install_creates_settings_json() {
     );
 }
 
+#[test]
+fn test_install_self_tests_registered_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let home = TempDir::new().unwrap();
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["init"])
+        .assert()
+        .success();
+
+    let binary = assert_cmd::cargo::cargo_bin("cch");
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .env("HOME", home.path())
+        .args(["install", "--binary", binary.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "✓ Hook self-test passed (allowed)",
+        ));
+
+    // An existing binary that doesn't speak the hook protocol is caught
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let project = TempDir::new().unwrap();
+        fs::create_dir_all(project.path().join(".claude")).unwrap();
+        fs::copy(
+            temp_dir.path().join(".claude/hooks.yaml"),
+            project.path().join(".claude/hooks.yaml"),
+        )
+        .unwrap();
+        let fake = project.path().join("fake-cch");
+        fs::write(&fake, "#!/bin/sh\necho not json\n").unwrap();
+        fs::set_permissions(&fake, fs::Permissions::from_mode(0o755)).unwrap();
+
+        cch_cmd()
+            .current_dir(project.path())
+            .env("HOME", home.path())
+            .args(["install", "--binary", fake.to_str().unwrap()])
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains(
+                "✗ Hook self-test failed: exit 0 without a hook response: \"not json\"",
+            ))
+            .stderr(predicate::str::contains("does not answer hook events"));
+    }
+}
+
 #[test]
 fn test_uninstall_removes_hooks() {
     let temp_dir = TempDir::new().unwrap();
@@ -327,6 +472,378 @@ fn test_uninstall_removes_hooks() {
     );
 }
 
+#[test]
+fn test_install_registers_only_selected_events() {
+    let temp_dir = TempDir::new().unwrap();
+    let home = TempDir::new().unwrap();
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["init"])
+        .assert()
+        .success();
+    let binary = assert_cmd::cargo::cargo_bin("cch");
+    let settings_path = temp_dir.path().join(".claude/settings.json");
+    let events = || -> Vec<String> {
+        let settings: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&settings_path).unwrap()).unwrap();
+        settings["hooks"]
+            .as_object()
+            .map(|hooks| hooks.keys().cloned().collect())
+            .unwrap_or_default()
+    };
+
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .env("HOME", home.path())
+        .args(["install", "--binary", binary.to_str().unwrap()])
+        .args(["--events", "PreToolUse,userpromptsubmit"])
+        .assert()
+        .success();
+    assert_eq!(events(), ["PreToolUse", "UserPromptSubmit"]);
+
+    // Later installs add only the missing events
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .env("HOME", home.path())
+        .args(["install", "--binary", binary.to_str().unwrap()])
+        .args(["--events", "PreToolUse,Stop"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("  • Stop"))
+        .stdout(predicate::str::contains("  • PreToolUse").not());
+    assert_eq!(events(), ["PreToolUse", "Stop", "UserPromptSubmit"]);
+
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .env("HOME", home.path())
+        .args(["uninstall", "--events", "Stop,UserPromptSubmit"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Removed from: Stop, UserPromptSubmit",
+        ));
+    assert_eq!(events(), ["PreToolUse"]);
+
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .env("HOME", home.path())
+        .args(["install", "--events", "PreToolUse,Bogus"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown event 'Bogus'"));
+
+    // settings.install.events picks the events when --events is absent
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .env("HOME", home.path())
+        .args(["uninstall"])
+        .assert()
+        .success();
+    let config = temp_dir.path().join(".claude/hooks.yaml");
+    let yaml = fs::read_to_string(&config).unwrap();
+    fs::write(
+        &config,
+        yaml.replacen(
+            "settings:\n",
+            "settings:\n  install:\n    events: [PostToolUse]\n",
+            1,
+        ),
+    )
+    .unwrap();
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .env("HOME", home.path())
+        .args(["install", "--binary", binary.to_str().unwrap()])
+        .assert()
+        .success();
+    assert_eq!(events(), ["PostToolUse"]);
+}
+
+#[test]
+fn test_install_merges_nested_schema_with_tool_matchers() {
+    let temp_dir = TempDir::new().unwrap();
+    let home = TempDir::new().unwrap();
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["init"])
+        .assert()
+        .success();
+    let settings_path = temp_dir.path().join(".claude/settings.json");
+    fs::write(
+        &settings_path,
+        r#"{
+  "model": "opus",
+  "hooks": {
+    "PreToolUse": [
+      { "matcher": "Bash", "hooks": [{ "type": "command", "command": "./lint.sh", "timeout": 30 }] }
+    ],
+    "Notification": [
+      { "hooks": [{ "type": "command", "command": "notify-send done", "async": true }] }
+    ]
+  }
+}"#,
+    )
+    .unwrap();
+    let binary = assert_cmd::cargo::cargo_bin("cch");
+    let install = |tools: &str| {
+        cch_cmd()
+            .current_dir(temp_dir.path())
+            .env("HOME", home.path())
+            .args(["install", "--binary", binary.to_str().unwrap()])
+            .args(["--events", "PreToolUse,Stop", "--tools", tools])
+            .assert()
+            .success()
+    };
+    let settings = || -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(&settings_path).unwrap()).unwrap()
+    };
+
+    install("Bash,Write,Edit").stdout(predicate::str::contains(
+        "  • PreToolUse (tools: Bash|Write|Edit)",
+    ));
+    let hooks = &settings()["hooks"];
+    assert_eq!(hooks["PreToolUse"][0]["hooks"][0]["command"], "./lint.sh");
+    assert_eq!(hooks["PreToolUse"][1]["matcher"], "Bash|Write|Edit");
+    assert_eq!(hooks["PreToolUse"][1]["hooks"][0]["type"], "command");
+    assert_eq!(hooks["Stop"][0]["matcher"], "*");
+    assert_eq!(hooks["Notification"][0]["hooks"][0]["async"], true);
+    assert_eq!(settings()["model"], "opus");
+
+    // Reinstalling with other tools updates CCH's matcher in place
+    install("Bash").stdout(predicate::str::contains("  • PreToolUse (tools: Bash)"));
+    let hooks = &settings()["hooks"];
+    assert_eq!(hooks["PreToolUse"].as_array().unwrap().len(), 2);
+    assert_eq!(hooks["PreToolUse"][1]["matcher"], "Bash");
+
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .env("HOME", home.path())
+        .args(["uninstall"])
+        .assert()
+        .success();
+    let hooks = &settings()["hooks"];
+    assert_eq!(hooks["PreToolUse"].as_array().unwrap().len(), 1);
+    assert!(hooks.get("Stop").is_none());
+    assert!(hooks.get("Notification").is_some());
+}
+
+#[test]
+fn test_mcp_evaluates_commands_as_tools() {
+    let temp_dir = TempDir::new().unwrap();
+    let home = TempDir::new().unwrap();
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["init"])
+        .assert()
+        .success();
+    let requests = [
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-06-18"}}"#,
+        r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"evaluate_event","arguments":{"command":"git push --force origin main"}}}"#,
+        r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"evaluate_event","arguments":{"command":"git status"}}}"#,
+        r#"{"jsonrpc":"2.0","id":4,"method":"tools/call","params":{"name":"explain_rule","arguments":{"name":"block-force-push"}}}"#,
+    ];
+    let output = cch_cmd()
+        .current_dir(temp_dir.path())
+        .env("HOME", home.path())
+        .arg("mcp")
+        .write_stdin(requests.join("\n"))
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let replies: Vec<serde_json::Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    assert_eq!(replies.len(), 4, "the notification gets no reply");
+    assert_eq!(
+        replies[0]["result"]["capabilities"]["tools"],
+        serde_json::json!({})
+    );
+    assert_eq!(
+        replies[1]["result"]["structuredContent"]["decision"],
+        "blocked"
+    );
+    assert!(
+        replies[1]["result"]["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .starts_with("Would be blocked")
+    );
+    assert_eq!(
+        replies[2]["result"]["content"][0]["text"],
+        "Would be allowed (no rule matched)"
+    );
+    assert_eq!(
+        replies[3]["result"]["structuredContent"]["name"],
+        "block-force-push"
+    );
+    assert_eq!(
+        fs::read_to_string(home.path().join(".claude/logs/cch.log")).unwrap_or_default(),
+        "",
+        "evaluations stay out of the audit log"
+    );
+}
+
+#[test]
+fn test_import_github_repo_settings() {
+    let temp_dir = TempDir::new().unwrap();
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["init"])
+        .assert()
+        .success();
+    fs::create_dir_all(temp_dir.path().join(".github")).unwrap();
+    fs::write(
+        temp_dir.path().join(".github/CODEOWNERS"),
+        "/services/payments/ @acme/payments\n/web/ @acme/web\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("protection.json"),
+        r#"[{"name": "main", "protected": true}, {"name": "wip", "protected": false}]"#,
+    )
+    .unwrap();
+    let import = || {
+        cch_cmd()
+            .current_dir(temp_dir.path())
+            .args(["import", "github", "--repo-settings"])
+            .args(["--protection", "protection.json", "--team", "acme/web"])
+            .assert()
+            .success()
+    };
+
+    import()
+        .stdout(predicate::str::contains("  + codeowners-acme-payments"))
+        .stdout(predicate::str::contains("  + github-protected-branches"))
+        .stdout(predicate::str::contains("codeowners-acme-web").not());
+    let config = fs::read_to_string(temp_dir.path().join(".claude/hooks.yaml")).unwrap();
+    assert!(
+        config.contains("block-force-push"),
+        "existing rules are kept"
+    );
+    assert!(config.contains("services/payments/**"));
+
+    // Importing again replaces the generated rules instead of duplicating them
+    import().stdout(predicate::str::contains(
+        "  ~ github-protected-branches (replaced)",
+    ));
+    let config = fs::read_to_string(temp_dir.path().join(".claude/hooks.yaml")).unwrap();
+    assert_eq!(config.matches("name: github-protected-branches").count(), 1);
+
+    let push = |command: &str| {
+        cch_cmd()
+            .current_dir(temp_dir.path())
+            .args(["debug", "PreToolUse", "--command", command])
+            .assert()
+            .success()
+    };
+    push("git push origin main").stdout(predicate::str::contains("Blocked"));
+    push("git push origin HEAD:main").stdout(predicate::str::contains("Blocked"));
+    push("git push origin feature/main-menu").stdout(predicate::str::contains("Blocked").not());
+
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["import", "github"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("pass --repo-settings"));
+}
+
+#[test]
+fn test_install_for_cursor_and_opencode() {
+    let temp_dir = TempDir::new().unwrap();
+    let home = TempDir::new().unwrap();
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["init"])
+        .assert()
+        .success();
+    let hooks_path = temp_dir.path().join(".cursor/hooks.json");
+    fs::create_dir_all(hooks_path.parent().unwrap()).unwrap();
+    fs::write(
+        &hooks_path,
+        r#"{"version":1,"hooks":{"afterFileEdit":[{"command":"./format.sh"}]}}"#,
+    )
+    .unwrap();
+    let binary = assert_cmd::cargo::cargo_bin("cch");
+    let run = |args: &[&str]| {
+        cch_cmd()
+            .current_dir(temp_dir.path())
+            .env("HOME", home.path())
+            .args(args)
+            .assert()
+    };
+
+    run(&[
+        "install",
+        "--agent",
+        "cursor",
+        "--binary",
+        binary.to_str().unwrap(),
+    ])
+    .success()
+    .stdout(predicate::str::contains("Hook self-test passed"))
+    .stdout(predicate::str::contains("  • beforeShellExecution"));
+    let hooks: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&hooks_path).unwrap()).unwrap();
+    assert_eq!(hooks["version"], 1);
+    assert_eq!(hooks["hooks"]["afterFileEdit"][0]["command"], "./format.sh");
+    assert_eq!(
+        hooks["hooks"]["beforeShellExecution"][0]["command"],
+        binary.to_str().unwrap()
+    );
+
+    // Cursor gets its own response format from the hook
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .env("HOME", home.path())
+        .write_stdin(
+            r#"{"hook_event_name":"beforeShellExecution","conversation_id":"c1","command":"git push --force origin main","workspace_roots":[]}"#,
+        )
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""permission":"deny""#));
+
+    run(&["install", "--agent", "cursor", "--tools", "Bash"])
+        .failure()
+        .stderr(predicate::str::contains(
+            "--tools is only supported for Claude Code",
+        ));
+    run(&["install", "--agent", "gemini"])
+        .failure()
+        .stderr(predicate::str::contains("Unknown agent 'gemini'"));
+
+    run(&["uninstall", "--agent", "cursor"]).success();
+    let hooks: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&hooks_path).unwrap()).unwrap();
+    assert_eq!(
+        hooks["hooks"],
+        serde_json::json!({ "afterFileEdit": [{ "command": "./format.sh" }] })
+    );
+
+    let plugin = temp_dir.path().join(".opencode/plugin/cch.js");
+    run(&[
+        "install",
+        "--agent",
+        "opencode",
+        "--binary",
+        binary.to_str().unwrap(),
+    ])
+    .success()
+    .stdout(predicate::str::contains("Hook self-test passed"));
+    let content = fs::read_to_string(&plugin).unwrap();
+    assert!(content.contains(&serde_json::to_string(binary.to_str().unwrap()).unwrap()));
+    assert!(content.contains("\"tool.execute.before\""));
+
+    run(&["uninstall", "--agent", "opencode"]).success();
+    assert!(!plugin.exists());
+}
+
 // =============================================================================
 // REPL Command Test
 // =============================================================================
@@ -339,3 +856,2214 @@ fn test_repl_help() {
         .success()
         .stdout(predicate::str::contains("interactive debug mode"));
 }
+
+#[test]
+fn test_repl_toggles_rules_and_reruns() {
+    let home = TempDir::new().unwrap();
+    let claude = home.path().join(".claude");
+    fs::create_dir_all(&claude).unwrap();
+    let config = r#"
+version: "1.0"
+rules:
+  - name: no-force-push
+    matchers: { tools: [Bash], command_match: "git push --force" }
+    actions: { block: true }
+"#;
+    fs::write(claude.join("hooks.yaml"), config).unwrap();
+
+    let output = cch_cmd()
+        .current_dir(home.path())
+        .env("HOME", home.path())
+        .arg("repl")
+        .write_stdin("bash git push --force\ndisable no-force-push\nrerun\nenable no-force-push\nrerun\ndisable missing\nquit\n")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let outcomes: Vec<&str> = stdout
+        .lines()
+        .filter(|line| line.starts_with("✓ Allowed") || line.starts_with("✗ Blocked"))
+        .collect();
+    assert_eq!(outcomes.len(), 3, "{}", stdout);
+    assert!(outcomes[0].starts_with("✗ Blocked"));
+    assert!(outcomes[1].starts_with("✓ Allowed"));
+    assert!(outcomes[2].starts_with("✗ Blocked"));
+    assert!(stdout.contains("Rule 'no-force-push' disabled (in memory only)"));
+    assert!(stdout.contains("No rule named 'missing'"));
+
+    // hooks.yaml is untouched
+    assert_eq!(
+        fs::read_to_string(claude.join("hooks.yaml")).unwrap(),
+        config
+    );
+}
+
+// =============================================================================
+// Validate Inline Rule Tests
+// =============================================================================
+
+const INLINE_TESTS_CONFIG: &str = r#"
+version: "1.0"
+rules:
+  - name: block-force-push
+    matchers:
+      tools: [Bash]
+      command_match: "git push.*--force"
+    actions:
+      block: true
+    tests:
+      - command: "git push --force origin main"
+        match: true
+      - command: "git push origin main"
+        match: false
+  - name: rust-context
+    matchers:
+      tools: [Write]
+      extensions: [".rs"]
+    actions:
+      inject: .claude/context/rust.md
+    tests:
+      - path: src/main.rs
+        match: true
+      - path: src/app.py
+        match: false
+"#;
+
+#[test]
+fn test_validate_run_rule_tests_pass() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join(".claude")).unwrap();
+    fs::write(
+        temp_dir.path().join(".claude/hooks.yaml"),
+        INLINE_TESTS_CONFIG,
+    )
+    .unwrap();
+
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["validate", "--run-rule-tests"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Rule tests: 4 passed, 0 failed"));
+}
+
+#[test]
+fn test_validate_run_rule_tests_reports_failure() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join(".claude")).unwrap();
+    let config = INLINE_TESTS_CONFIG.replace(
+        "command: \"git push origin main\"\n        match: false",
+        "command: \"git push origin main\"\n        match: true",
+    );
+    fs::write(temp_dir.path().join(".claude/hooks.yaml"), config).unwrap();
+
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["validate", "--run-rule-tests"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("expected match, got no match"))
+        .stderr(predicate::str::contains("1 rule test(s) failed"));
+}
+
+#[test]
+fn test_validate_interpolates_environment_variables() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join(".claude/context")).unwrap();
+    fs::write(temp_dir.path().join(".claude/context/rust.md"), "Rust").unwrap();
+    fs::write(
+        temp_dir.path().join(".claude/hooks.yaml"),
+        r#"
+version: "1.0"
+rules:
+  - name: rust-context
+    matchers:
+      tools: [Write]
+    actions:
+      inject: ${CONTEXT_DIR:-.claude/context}/rust.md
+  - name: team-validator
+    matchers:
+      tools: [Bash]
+    actions:
+      run: ${TEAM_VALIDATORS}/check.sh
+"#,
+    )
+    .unwrap();
+
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .env("HOME", temp_dir.path())
+        .env_remove("TEAM_VALIDATORS")
+        .arg("validate")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Unresolved environment variables: TEAM_VALIDATORS (line 13)",
+        ));
+}
+
+// =============================================================================
+// Logs Stats Tests
+// =============================================================================
+
+#[test]
+fn test_logs_stats_slow_validators() {
+    let home = TempDir::new().unwrap();
+    let log_dir = home.path().join(".claude/logs");
+    fs::create_dir_all(&log_dir).unwrap();
+
+    let entry = |duration_ms: u64, timed_out: bool, script: &str| {
+        format!(
+            r#"{{"schema_version":2,"timestamp":"2026-01-01T00:00:00Z","event_type":"PreToolUse","session_id":"s","rules_matched":["r"],"outcome":"allow","timing":{{"processing_ms":{duration_ms},"rules_evaluated":1}},"validators":[{{"rule":"r","script":"{script}","duration_ms":{duration_ms},"timed_out":{timed_out}}}]}}"#
+        )
+    };
+    let mut lines = Vec::new();
+    for ms in [10, 12, 15] {
+        lines.push(entry(ms, false, ".claude/validators/fast.sh"));
+    }
+    lines.push(entry(40, false, ".claude/validators/slow.sh"));
+    lines.push(entry(5000, true, ".claude/validators/slow.sh"));
+    fs::write(log_dir.join("cch.log"), lines.join("\n")).unwrap();
+
+    let output = cch_cmd()
+        .env("HOME", home.path())
+        .args(["logs", "stats", "--slow-validators"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Validators by p95 latency"))
+        .get_output()
+        .stdout
+        .clone();
+
+    let stdout = String::from_utf8(output).unwrap();
+    let slow = stdout.find("slow.sh").unwrap();
+    let fast = stdout.find("fast.sh").unwrap();
+    assert!(slow < fast, "slowest validator should be listed first");
+    assert!(stdout.contains("5000ms"));
+    assert!(stdout.contains("1 (50%)"));
+}
+
+#[test]
+fn test_logs_stats_latency_reads_histograms() {
+    let home = TempDir::new().unwrap();
+    let project = TempDir::new().unwrap();
+    fs::create_dir_all(project.path().join(".claude")).unwrap();
+    fs::write(
+        project.path().join(".claude/hooks.yaml"),
+        "version: \"1.0\"\nrules: []\n",
+    )
+    .unwrap();
+
+    for day in ["2026-01-01", "2026-01-02", "2026-01-02"] {
+        let event = serde_json::json!({
+            "hook_event_name": "PreToolUse",
+            "tool_name": "Bash",
+            "tool_input": { "command": "ls" },
+            "session_id": "latency-session",
+            "timestamp": format!("{day}T12:00:00Z")
+        });
+        cch_cmd()
+            .current_dir(project.path())
+            .env("HOME", home.path())
+            .write_stdin(event.to_string())
+            .assert()
+            .success();
+    }
+
+    // The histograms are used, not the log
+    fs::remove_file(home.path().join(".claude/logs/cch.log")).unwrap();
+    cch_cmd()
+        .env("HOME", home.path())
+        .args([
+            "logs",
+            "stats",
+            "--latency",
+            "--since",
+            "2026-01-02T00:00:00Z",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("p99"))
+        .stdout(predicate::str::contains("2026-01-02"))
+        .stdout(predicate::str::contains("2026-01-01").not());
+}
+
+#[test]
+fn test_logs_stats_compare_reports_deltas() {
+    let home = TempDir::new().unwrap();
+    fs::create_dir_all(home.path().join(".claude/logs")).unwrap();
+
+    let entry = |day: &str, matched: &str, outcome: &str, decision: &str| {
+        format!(
+            r#"{{"schema_version":2,"timestamp":"2026-01-{day}T12:00:00Z","event_type":"PreToolUse","session_id":"s","rules_matched":[{matched}],"outcome":"{outcome}","timing":{{"processing_ms":1,"rules_evaluated":1}},"decision":"{decision}"}}"#
+        )
+    };
+    let lines = [
+        entry("01", r#""no-force-push""#, "block", "blocked"),
+        entry("02", r#""no-force-push""#, "block", "blocked"),
+        entry("03", "", "allow", "allowed"),
+        entry("04", "", "allow", "allowed"),
+        entry("10", r#""no-force-push""#, "inject", "warned"),
+        entry("11", "", "allow", "allowed"),
+    ];
+    fs::write(home.path().join(".claude/logs/cch.log"), lines.join("\n")).unwrap();
+
+    cch_cmd()
+        .env("HOME", home.path())
+        .args([
+            "logs",
+            "stats",
+            "compare",
+            "--before",
+            "2025-12-31T00:00:00Z",
+            "--after",
+            "2026-01-05T00:00:00Z",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"Events\s+4\s+2\s+-2").unwrap())
+        .stdout(predicate::str::is_match(r"Block rate\s+50\.0%\s+0\.0%\s+-50\.0pp").unwrap())
+        .stdout(predicate::str::is_match(r"Warn rate\s+0\.0%\s+50\.0%\s+\+50\.0pp").unwrap())
+        .stdout(predicate::str::is_match(r"no-force-push\s+2\s+1\s+-1").unwrap());
+
+    cch_cmd()
+        .env("HOME", home.path())
+        .args([
+            "logs", "stats", "compare", "--before", "1w", "--after", "2w",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("must start earlier"));
+}
+
+// =============================================================================
+// Policy Test Harness Tests
+// =============================================================================
+
+#[test]
+fn test_policy_tests_report_pass_and_fail() {
+    let home = TempDir::new().unwrap();
+    let claude = home.path().join(".claude");
+    fs::create_dir_all(claude.join("fixtures")).unwrap();
+    fs::write(
+        claude.join("hooks.yaml"),
+        r#"
+version: "1.0"
+rules:
+  - name: no-force-push
+    matchers: { tools: [Bash], command_match: "git push --force" }
+    actions: { block: true }
+  - name: console-warning
+    mode: warn
+    matchers: { tools: [Write], extensions: [.js] }
+    actions: { block: true }
+"#,
+    )
+    .unwrap();
+    fs::write(
+        claude.join("fixtures/write-js.json"),
+        r#"{"hook_event_name":"PreToolUse","tool_name":"Write","tool_input":{"file_path":"app.js","content":"x"}}"#,
+    )
+    .unwrap();
+    let suite = r#"
+tests:
+  - name: force push is blocked
+    event:
+      hook_event_name: PreToolUse
+      tool_name: Bash
+      tool_input: { command: "git push --force origin main" }
+    expect: { decision: blocked, matched: [no-force-push] }
+  - name: js writes warn
+    fixture: fixtures/write-js.json
+    expect: { decision: warned, matched: [console-warning] }
+  - name: status is allowed
+    event: { hook_event_name: PreToolUse, tool_name: Bash, tool_input: { command: "git status" } }
+    expect: { decision: allowed, matched: [] }
+"#;
+    fs::write(claude.join("hooks.tests.yaml"), suite).unwrap();
+
+    cch_cmd()
+        .current_dir(home.path())
+        .env("HOME", home.path())
+        .arg("test")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("✓ js writes warn"))
+        .stdout(predicate::str::contains("Policy tests: 3 passed, 0 failed"));
+
+    fs::write(
+        claude.join("hooks.tests.yaml"),
+        suite.replace("decision: allowed", "decision: blocked"),
+    )
+    .unwrap();
+    cch_cmd()
+        .current_dir(home.path())
+        .env("HOME", home.path())
+        .arg("test")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "✗ status is allowed: expected blocked, got allowed",
+        ))
+        .stdout(predicate::str::contains("Policy tests: 2 passed, 1 failed"));
+}
+
+#[test]
+fn test_policy_tests_keep_state_in_memory() {
+    let home = TempDir::new().unwrap();
+    let claude = home.path().join(".claude");
+    fs::create_dir_all(&claude).unwrap();
+    fs::write(
+        claude.join("hooks.yaml"),
+        r#"
+version: "1.0"
+rules:
+  - name: no-force-push-once
+    matchers: { tools: [Bash], command_match: "git push --force" }
+    actions: { block: true, throttle: { max: 1, scope: global } }
+"#,
+    )
+    .unwrap();
+    fs::write(
+        claude.join("hooks.tests.yaml"),
+        r#"
+tests:
+  - name: first push is blocked
+    event: { hook_event_name: PreToolUse, tool_name: Bash, tool_input: { command: "git push --force" } }
+    expect: { decision: blocked }
+  - name: throttled within the run
+    event: { hook_event_name: PreToolUse, tool_name: Bash, tool_input: { command: "git push --force" } }
+    expect: { decision: allowed, matched: [] }
+"#,
+    )
+    .unwrap();
+
+    // Every run starts over, and none leaves state behind
+    for _ in 0..2 {
+        cch_cmd()
+            .current_dir(home.path())
+            .env("HOME", home.path())
+            .arg("test")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Policy tests: 2 passed, 0 failed"));
+    }
+    assert!(!claude.join("state/global.json").exists());
+}
+
+// =============================================================================
+// Rule Rename Tests
+// =============================================================================
+
+#[test]
+fn test_rule_rename_updates_config_and_keeps_history() {
+    let home = TempDir::new().unwrap();
+    fs::create_dir_all(home.path().join(".claude/logs")).unwrap();
+    fs::write(
+        home.path().join(".claude/hooks.yaml"),
+        r#"
+version: "1.0"
+rules:
+  # Owned by the platform team
+  - name: no-force   # legacy name
+    matchers: { tools: [Bash], command_match: "git push --force" }
+    actions: { block: true }
+  - name: other
+    matchers: { tools: [Write] }
+    actions: { block: false }
+"#,
+    )
+    .unwrap();
+    fs::write(
+        home.path().join(".claude/logs/cch.log"),
+        r#"{"schema_version":2,"timestamp":"2026-01-01T12:00:00Z","event_type":"PreToolUse","session_id":"s","rules_matched":["no-force"],"outcome":"block","timing":{"processing_ms":1,"rules_evaluated":1},"decision":"blocked"}"#,
+    )
+    .unwrap();
+
+    cch_cmd()
+        .current_dir(home.path())
+        .env("HOME", home.path())
+        .args(["rule", "rename", "no-force", "other"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already exists"));
+
+    cch_cmd()
+        .current_dir(home.path())
+        .env("HOME", home.path())
+        .args([
+            "rule",
+            "rename",
+            "no-force",
+            "no-force-push",
+            "--keep-history",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Renamed rule 'no-force' to 'no-force-push'",
+        ));
+
+    let config = fs::read_to_string(home.path().join(".claude/hooks.yaml")).unwrap();
+    assert!(config.contains("  - name: no-force-push   # legacy name\n"));
+    assert!(config.contains("# Owned by the platform team"));
+
+    cch_cmd()
+        .current_dir(home.path())
+        .env("HOME", home.path())
+        .args(["explain", "rule", "no-force-push"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Triggered: 1 times"))
+        .stdout(predicate::str::contains("Blocked: 1 times"));
+}
+
+// =============================================================================
+// Encrypted Config Tests
+// =============================================================================
+
+#[test]
+fn test_validate_reports_undecryptable_sections() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join(".claude")).unwrap();
+    fs::write(
+        temp_dir.path().join(".claude/hooks.yaml"),
+        r#"
+version: "1.0"
+rules: []
+encrypted_rules:
+  - |
+    -----BEGIN AGE ENCRYPTED FILE-----
+    YWdlLWVuY3J5cHRpb24ub3JnL3YxCg==
+    -----END AGE ENCRYPTED FILE-----
+settings:
+  fail_open: true
+"#,
+    )
+    .unwrap();
+
+    // Without a usable key the section is skipped, not fatal (fail_open)
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .env("HOME", temp_dir.path())
+        .env_remove("CCH_AGE_KEY")
+        .env_remove("CCH_AGE_KEY_FILE")
+        .args(["validate"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Encrypted sections: 1 (0 rules decrypted)",
+        ));
+}
+
+// =============================================================================
+// Coverage Tests
+// =============================================================================
+
+#[test]
+fn test_coverage_reports_unused_rules_and_event_types() {
+    let home = TempDir::new().unwrap();
+    let project = TempDir::new().unwrap();
+    fs::create_dir_all(home.path().join(".claude/logs")).unwrap();
+    fs::create_dir_all(project.path().join(".claude")).unwrap();
+    fs::write(
+        project.path().join(".claude/hooks.yaml"),
+        r#"
+version: "1.0"
+rules:
+  - name: block-force-push
+    matchers:
+      tools: [Bash]
+      command_match: "git push --force"
+    actions:
+      block: true
+  - name: never-fires
+    matchers:
+      tools: [Write]
+      extensions: [".cobol"]
+    actions:
+      inject: ".claude/cobol.md"
+"#,
+    )
+    .unwrap();
+
+    let entry = |event_type: &str, matched: &str, evaluations: &str| {
+        format!(
+            r#"{{"schema_version":2,"timestamp":"2026-01-01T00:00:00Z","event_type":"{event_type}","session_id":"s","rules_matched":[{matched}],"outcome":"allow","timing":{{"processing_ms":1,"rules_evaluated":2}},"rule_evaluations":[{evaluations}]}}"#
+        )
+    };
+    let evaluation = |rule: &str, tools: bool, other: &str| {
+        format!(
+            r#"{{"rule_name":"{rule}","matched":false,"matcher_results":{{"tools_matched":{tools},{other}}}}}"#
+        )
+    };
+    let lines = [
+        entry(
+            "PreToolUse",
+            r#""block-force-push""#,
+            &evaluation("block-force-push", true, r#""command_match_matched":true"#),
+        ),
+        entry(
+            "PreToolUse",
+            "",
+            &evaluation("never-fires", true, r#""extensions_matched":false"#),
+        ),
+        entry("SessionStart", "", ""),
+    ];
+    fs::write(home.path().join(".claude/logs/cch.log"), lines.join("\n")).unwrap();
+
+    let output = cch_cmd()
+        .current_dir(project.path())
+        .env("HOME", home.path())
+        .args(["coverage", "--since", "2025-12-01T00:00:00Z"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Rules that never matched (1)"))
+        .stdout(predicate::str::contains("- never-fires"))
+        .stdout(predicate::str::contains("- never-fires: extensions"))
+        .stdout(predicate::str::contains("- SessionStart: 1 events"))
+        .get_output()
+        .stdout
+        .clone();
+
+    let stdout = String::from_utf8(output).unwrap();
+    assert!(!stdout.contains("- block-force-push"));
+    assert!(!stdout.contains("- PreToolUse"));
+}
+
+#[test]
+fn test_rule_preview_replays_logged_events() {
+    let home = TempDir::new().unwrap();
+    let project = TempDir::new().unwrap();
+    fs::create_dir_all(home.path().join(".claude/logs")).unwrap();
+
+    let entry = |session: &str, command: &str| {
+        serde_json::json!({
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "event_type": "PreToolUse",
+            "session_id": session,
+            "tool_name": "Bash",
+            "rules_matched": [],
+            "outcome": "allow",
+            "timing": { "processing_ms": 1, "rules_evaluated": 0 },
+            "event_details": { "tool_type": "Bash", "command": command }
+        })
+        .to_string()
+    };
+    let lines = [
+        entry("s1", "curl https://example.com"),
+        entry("s1", "curl https://example.com"),
+        entry("s2", "curl -O https://example.com/x.tgz"),
+        entry("s2", "cargo build"),
+    ];
+    fs::write(home.path().join(".claude/logs/cch.log"), lines.join("\n")).unwrap();
+    fs::write(
+        project.path().join("no-curl.yaml"),
+        r#"
+name: no-curl
+matchers:
+  tools: [Bash]
+  command_match: "^curl "
+actions:
+  block: true
+"#,
+    )
+    .unwrap();
+
+    cch_cmd()
+        .current_dir(project.path())
+        .env("HOME", home.path())
+        .args(["rule", "preview", "no-curl.yaml", "--against-logs", "30d"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("against 4 logged events"))
+        .stdout(predicate::str::contains("Matched: 3 (75.0%)"))
+        .stdout(predicate::str::contains("Blocked: 3 (75.0%)"))
+        .stdout(predicate::str::contains("By session (2):"))
+        .stdout(predicate::str::contains(
+            "2 matched     2 blocked  curl https://example.com",
+        ));
+
+    cch_cmd()
+        .current_dir(project.path())
+        .env("HOME", home.path())
+        .args(["rule", "preview", "missing.yaml"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Failed to read missing.yaml"));
+}
+
+#[test]
+fn test_coverage_rejects_bad_since() {
+    let home = TempDir::new().unwrap();
+    cch_cmd()
+        .env("HOME", home.path())
+        .args(["coverage", "--since", "thirty days"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid --since"));
+}
+
+// =============================================================================
+// Session Diff Tests
+// =============================================================================
+
+#[test]
+fn test_session_diff_lists_journaled_changes() {
+    let home = TempDir::new().unwrap();
+    let project = TempDir::new().unwrap();
+    fs::create_dir_all(project.path().join(".claude")).unwrap();
+    fs::write(
+        project.path().join(".claude/hooks.yaml"),
+        "version: \"1.0\"\nrules: []\nsettings:\n  change_journal: true\n",
+    )
+    .unwrap();
+    fs::write(project.path().join("existing.rs"), "fn a() {}\n").unwrap();
+
+    let hook = |event: &str, path: &str| {
+        let input = serde_json::json!({
+            "hook_event_name": event,
+            "tool_name": "Write",
+            "tool_input": { "file_path": path, "content": "" },
+            "session_id": "journal-session",
+            "cwd": project.path().to_str().unwrap()
+        });
+        cch_cmd()
+            .current_dir(project.path())
+            .env("HOME", home.path())
+            .write_stdin(input.to_string())
+            .assert()
+            .success();
+    };
+
+    hook("PreToolUse", "new.rs");
+    fs::write(project.path().join("new.rs"), "fn b() {}\nfn c() {}\n").unwrap();
+    hook("PostToolUse", "new.rs");
+
+    hook("PreToolUse", "existing.rs");
+    fs::write(project.path().join("existing.rs"), "fn a() { todo!() }\n").unwrap();
+    hook("PostToolUse", "existing.rs");
+
+    cch_cmd()
+        .env("HOME", home.path())
+        .args(["session", "diff", "journal-session"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2 files touched"))
+        .stdout(predicate::str::contains("A new.rs"))
+        .stdout(predicate::str::contains("+2 lines"))
+        .stdout(predicate::str::contains("M existing.rs"));
+}
+
+#[test]
+fn test_session_diff_unknown_session() {
+    let home = TempDir::new().unwrap();
+    cch_cmd()
+        .env("HOME", home.path())
+        .args(["session", "diff", "nope"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No change journal"));
+}
+
+// =============================================================================
+// Statusline Command Tests
+// =============================================================================
+
+#[test]
+fn test_statusline_reports_rules_and_session_blocks() {
+    let home = TempDir::new().unwrap();
+    let project = TempDir::new().unwrap();
+    fs::create_dir_all(project.path().join(".claude")).unwrap();
+    fs::write(
+        project.path().join(".claude/hooks.yaml"),
+        r#"
+version: "1.0"
+rules:
+  - name: block-force-push
+    matchers: { tools: [Bash], command_match: "git push --force" }
+    actions: { block: true }
+  - name: docs
+    matchers: { tools: [Write] }
+    actions: { inject: docs.md }
+  - name: disabled
+    matchers: { tools: [Read] }
+    actions: { block: true }
+    metadata: { enabled: false }
+"#,
+    )
+    .unwrap();
+
+    let event = serde_json::json!({
+        "hook_event_name": "PreToolUse",
+        "tool_name": "Bash",
+        "tool_input": { "command": "git push --force" },
+        "session_id": "status-session"
+    });
+    cch_cmd()
+        .current_dir(project.path())
+        .env("HOME", home.path())
+        .write_stdin(event.to_string())
+        .assert()
+        .code(2);
+
+    let status = serde_json::json!({
+        "session_id": "status-session",
+        "workspace": { "project_dir": project.path().to_str().unwrap() }
+    });
+    cch_cmd()
+        .env("HOME", home.path())
+        .arg("statusline")
+        .write_stdin(status.to_string())
+        .assert()
+        .success()
+        .stdout("CCH project · 2 rules · 1 blocked\n");
+
+    // Without a config anywhere, policy is reported as off
+    cch_cmd()
+        .current_dir(home.path())
+        .env("HOME", home.path())
+        .arg("statusline")
+        .write_stdin("{}")
+        .assert()
+        .success()
+        .stdout("CCH off (no hooks.yaml)\n");
+}
+
+// =============================================================================
+// Pack Command Tests
+// =============================================================================
+
+#[test]
+fn test_pack_create_bundles_tagged_rules() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join(".claude/validators")).unwrap();
+    fs::write(
+        temp_dir.path().join(".claude/hooks.yaml"),
+        r#"
+version: "1.0"
+rules:
+  - name: check-secrets
+    matchers:
+      tools: [Write]
+    actions:
+      run: .claude/validators/check-secrets.sh
+    governance:
+      author: security-team
+      tags: [security]
+  - name: style-docs
+    matchers:
+      tools: [Write]
+    actions:
+      inject: .claude/context/style.md
+    governance:
+      tags: [style]
+"#,
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join(".claude/validators/check-secrets.sh"),
+        "#!/bin/bash\nexit 0\n",
+    )
+    .unwrap();
+
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .args([
+            "pack",
+            "create",
+            "--tag",
+            "security",
+            "--out",
+            "security.yaml",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Created pack 'security@0.1.0'"))
+        .stdout(predicate::str::contains("check-secrets"))
+        .stdout(predicate::str::contains("Bundled files: 1"));
+
+    let pack = fs::read_to_string(temp_dir.path().join("security.yaml")).unwrap();
+    assert!(pack.contains("name: check-secrets"));
+    assert!(!pack.contains("style-docs"));
+    assert!(pack.contains("author: security-team"));
+    assert!(pack.contains("created_by: security@0.1.0"));
+    assert!(pack.contains("path: .claude/validators/check-secrets.sh"));
+    assert!(pack.contains("exit 0"));
+}
+
+#[test]
+fn test_pack_install_resolves_conflicts_with_strategy() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join(".claude")).unwrap();
+    fs::write(
+        temp_dir.path().join(".claude/hooks.yaml"),
+        r#"
+version: "1.0"
+rules:
+  # Reviewed by platform
+  - name: block-force-push
+    matchers:
+      tools: [Bash]
+      command_match: "git push --force"   # not -f
+    actions:
+      block: true
+"#,
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("pack.yaml"),
+        r#"
+pack:
+  name: team
+  version: "1.0.0"
+rules:
+  - name: block-force-push
+    matchers:
+      tools: [Bash]
+      command_match: "git push -f"
+    actions:
+      block: true
+  - name: team-docs
+    matchers:
+      tools: [Write]
+    actions:
+      inject: .claude/context/team.md
+files:
+  - path: .claude/context/team.md
+    content: "Team conventions\n"
+"#,
+    )
+    .unwrap();
+
+    // Without a TTY or --strategy, conflicts are reported instead of guessed
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["pack", "install", "pack.yaml"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "rule 'block-force-push' already exists",
+        ))
+        .stderr(predicate::str::contains("--strategy"));
+
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["pack", "install", "pack.yaml", "--strategy", "rename"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "block-force-push-team (renamed from block-force-push)",
+        ))
+        .stdout(predicate::str::contains("+ team-docs"));
+
+    let config = fs::read_to_string(temp_dir.path().join(".claude/hooks.yaml")).unwrap();
+    assert!(config.contains("  # Reviewed by platform\n  - name: block-force-push\n"));
+    assert!(config.contains("\"git push --force\"   # not -f"));
+    assert!(config.contains("name: block-force-push-team"));
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join(".claude/context/team.md")).unwrap(),
+        "Team conventions\n"
+    );
+
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .args(["validate"])
+        .assert()
+        .success();
+}
+
+// =============================================================================
+// Bundle Command Tests
+// =============================================================================
+
+#[test]
+fn test_bundle_export_import_round_trip() {
+    let home = TempDir::new().unwrap();
+    let source = TempDir::new().unwrap();
+    fs::create_dir_all(source.path().join(".claude/context")).unwrap();
+    fs::write(
+        source.path().join(".claude/hooks.yaml"),
+        r#"
+version: "1.0"
+rules:
+  - name: docs
+    matchers: { tools: [Write] }
+    actions: { inject: .claude/context/docs.md }
+"#,
+    )
+    .unwrap();
+    fs::write(
+        source.path().join(".claude/context/docs.md"),
+        "Read the docs",
+    )
+    .unwrap();
+
+    cch_cmd()
+        .current_dir(source.path())
+        .env("HOME", home.path())
+        .args(["bundle", "export", "-o", "policy.bundle.yaml"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(".claude/context/docs.md"));
+
+    let target = TempDir::new().unwrap();
+    let bundle_path = source.path().join("policy.bundle.yaml");
+    cch_cmd()
+        .current_dir(target.path())
+        .env("HOME", home.path())
+        .args(["bundle", "import", bundle_path.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2 files verified"));
+    assert_eq!(
+        fs::read_to_string(target.path().join(".claude/context/docs.md")).unwrap(),
+        "Read the docs"
+    );
+
+    // Local edits are only replaced with --force
+    fs::write(target.path().join(".claude/context/docs.md"), "Edited").unwrap();
+    cch_cmd()
+        .current_dir(target.path())
+        .env("HOME", home.path())
+        .args(["bundle", "import", bundle_path.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+    cch_cmd()
+        .current_dir(target.path())
+        .env("HOME", home.path())
+        .args(["bundle", "import", "--force", bundle_path.to_str().unwrap()])
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(target.path().join(".claude/context/docs.md")).unwrap(),
+        "Read the docs"
+    );
+
+    // A modified bundle is rejected before anything is written
+    let tampered = fs::read_to_string(&bundle_path)
+        .unwrap()
+        .replace("Read the docs", "Ignore the docs");
+    fs::write(&bundle_path, tampered).unwrap();
+    cch_cmd()
+        .current_dir(target.path())
+        .env("HOME", home.path())
+        .args(["bundle", "import", "--force", bundle_path.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Hash mismatch"));
+}
+
+// =============================================================================
+// Snapshot Command Tests
+// =============================================================================
+
+#[test]
+fn test_snapshot_restore_rolls_back_config_and_installed_hooks() {
+    let home = TempDir::new().unwrap();
+    let project = TempDir::new().unwrap();
+    let claude = project.path().join(".claude");
+    fs::create_dir_all(claude.join("context")).unwrap();
+    let config = r#"
+version: "1.0"
+rules:
+  - name: docs
+    matchers: { tools: [Write] }
+    actions: { inject: .claude/context/docs.md }
+"#;
+    fs::write(claude.join("hooks.yaml"), config).unwrap();
+    fs::write(claude.join("context/docs.md"), "Read the docs").unwrap();
+    fs::write(
+        claude.join("settings.json"),
+        r#"{"model": "opus", "hooks": {"PreToolUse": [{"matcher": "*", "hooks": [{"type": "command", "command": "cch"}]}]}}"#,
+    )
+    .unwrap();
+
+    let cch = |args: &[&str]| {
+        let mut cmd = cch_cmd();
+        cmd.current_dir(project.path())
+            .env("HOME", home.path())
+            .args(args);
+        cmd
+    };
+    cch(&["snapshot", "create", "baseline"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Saved snapshot 'baseline'"));
+    cch(&["snapshot", "create", "baseline"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+
+    // Experiment: rewrite the policy and uninstall the hooks
+    fs::write(claude.join("hooks.yaml"), "version: \"1.0\"\nrules: []\n").unwrap();
+    fs::write(claude.join("context/docs.md"), "Edited").unwrap();
+    fs::write(claude.join("settings.json"), r#"{"model": "opus"}"#).unwrap();
+
+    cch(&["snapshot", "restore", "baseline"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("~ .claude/hooks.yaml"))
+        .stdout(predicate::str::contains("~ .claude/settings.json (hooks)"));
+    assert_eq!(
+        fs::read_to_string(claude.join("hooks.yaml")).unwrap(),
+        config
+    );
+    assert_eq!(
+        fs::read_to_string(claude.join("context/docs.md")).unwrap(),
+        "Read the docs"
+    );
+    let settings: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(claude.join("settings.json")).unwrap()).unwrap();
+    assert_eq!(settings["model"], "opus");
+    assert_eq!(
+        settings["hooks"]["PreToolUse"][0]["hooks"][0]["command"],
+        "cch"
+    );
+
+    cch(&["snapshot", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("baseline"));
+    cch(&["snapshot", "restore", "missing"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("available: baseline"));
+}
+
+// =============================================================================
+// Daemon Tests
+// =============================================================================
+
+/// Stops the spawned daemon even when an assertion fails
+struct DaemonGuard(std::process::Child);
+
+impl Drop for DaemonGuard {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn http_request(addr: &str, request: &str) -> String {
+    use std::io::{Read, Write};
+
+    let mut last_error = None;
+    for _ in 0..100 {
+        match std::net::TcpStream::connect(addr) {
+            Ok(mut stream) => {
+                stream.write_all(request.as_bytes()).unwrap();
+                let mut response = String::new();
+                stream.read_to_string(&mut response).unwrap();
+                return response;
+            }
+            Err(e) => {
+                last_error = Some(e);
+                std::thread::sleep(std::time::Duration::from_millis(50));
+            }
+        }
+    }
+    panic!("daemon did not accept connections: {:?}", last_error);
+}
+
+#[test]
+fn test_daemon_evaluate_returns_matched_rules() {
+    let temp_dir = TempDir::new().unwrap();
+    let claude_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(&claude_dir).unwrap();
+    fs::write(
+        claude_dir.join("hooks.yaml"),
+        r#"version: "1.0"
+rules:
+  - name: block-force-push
+    description: No force pushes
+    matchers:
+      tools: [Bash]
+      command_match: "git push.*--force"
+    actions:
+      block: true
+"#,
+    )
+    .unwrap();
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let addr = format!("127.0.0.1:{}", port);
+    let _daemon = DaemonGuard(
+        std::process::Command::new(assert_cmd::cargo::cargo_bin("cch"))
+            .current_dir(temp_dir.path())
+            .env("HOME", temp_dir.path())
+            .args(["daemon", "--http", &addr])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .unwrap(),
+    );
+
+    let event = serde_json::json!({
+        "hook_event_name": "PreToolUse",
+        "tool_name": "Bash",
+        "tool_input": { "command": "git push origin main --force" },
+        "session_id": "daemon-test",
+        "timestamp": "2025-01-22T12:00:00Z",
+        "cwd": temp_dir.path().to_string_lossy(),
+    })
+    .to_string();
+    let evaluate = |host: &str| {
+        http_request(
+            &addr,
+            &format!(
+                "POST /v1/evaluate HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                host,
+                event.len(),
+                event
+            ),
+        )
+    };
+    let response = evaluate(&format!("localhost:{}", port));
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    let body: serde_json::Value =
+        serde_json::from_str(response.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+    assert_eq!(body["response"]["continue"], false);
+    assert_eq!(body["matched_rules"][0]["name"], "block-force-push");
+    assert_eq!(
+        fs::read_to_string(claude_dir.join("logs/cch.log")).unwrap_or_default(),
+        "",
+        "API evaluations are not audited"
+    );
+
+    // A DNS-rebound page names its own host
+    let response = evaluate(&format!("attacker.example:{}", port));
+    assert!(response.starts_with("HTTP/1.1 403"), "{}", response);
+
+    let response = http_request(
+        &addr,
+        &format!(
+            "POST /v1/evaluate HTTP/1.1\r\nHost: {}\r\nContent-Length: 8\r\n\r\nnot json",
+            addr
+        ),
+    );
+    assert!(response.starts_with("HTTP/1.1 400"), "{}", response);
+
+    let response = http_request(
+        &addr,
+        &format!("GET /v1/health HTTP/1.1\r\nHost: {}\r\n\r\n", addr),
+    );
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    assert!(response.contains("\"status\":\"ok\""));
+
+    // Cursor payloads are mapped as on the hook path
+    let cursor = serde_json::json!({
+        "hook_event_name": "beforeShellExecution",
+        "conversation_id": "daemon-cursor",
+        "generation_id": "g1",
+        "command": "git push origin main --force",
+        "workspace_roots": [temp_dir.path().to_string_lossy()],
+    })
+    .to_string();
+    let response = http_request(
+        &addr,
+        &format!(
+            "POST /v1/evaluate HTTP/1.1\r\nHost: {}\r\nContent-Length: {}\r\n\r\n{}",
+            addr,
+            cursor.len(),
+            cursor
+        ),
+    );
+    let body: serde_json::Value =
+        serde_json::from_str(response.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+    assert_eq!(body["matched_rules"][0]["name"], "block-force-push");
+
+    // A header line that never ends is cut off at the head limit
+    let mut unterminated = format!("GET /v1/health HTTP/1.1\r\nHost: {}\r\nX-Pad: ", addr);
+    unterminated.extend(std::iter::repeat_n('a', 16 * 1024 - unterminated.len()));
+    let response = http_request(&addr, &unterminated);
+    assert!(response.starts_with("HTTP/1.1 400"), "{}", response);
+    assert!(response.contains("too large"), "{}", response);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_daemon_socket_forwarding_with_fallback() {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    let temp_dir = TempDir::new().unwrap();
+    let claude_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(&claude_dir).unwrap();
+    fs::write(
+        claude_dir.join("hooks.yaml"),
+        r#"version: "1.0"
+rules:
+  - name: block-force-push
+    matchers:
+      tools: [Bash]
+      command_match: "git push.*--force"
+    actions:
+      block: true
+"#,
+    )
+    .unwrap();
+
+    let event = serde_json::json!({
+        "hook_event_name": "PreToolUse",
+        "tool_name": "Bash",
+        "tool_input": { "command": "git push --force" },
+        "session_id": "daemon-socket-test",
+        "cwd": temp_dir.path().to_string_lossy(),
+    });
+    let run_hook = || {
+        cch_cmd()
+            .current_dir(temp_dir.path())
+            .env("HOME", temp_dir.path())
+            .write_stdin(event.to_string())
+            .assert()
+            .code(2)
+            .stderr(predicate::str::contains("block-force-push"));
+    };
+
+    let socket = claude_dir.join("run/cch/cch.sock");
+    let mut daemon = DaemonGuard(
+        std::process::Command::new(assert_cmd::cargo::cargo_bin("cch"))
+            .current_dir(temp_dir.path())
+            .env("HOME", temp_dir.path())
+            .arg("daemon")
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .unwrap(),
+    );
+
+    let mut stream = None;
+    for _ in 0..100 {
+        if let Ok(s) = UnixStream::connect(&socket) {
+            stream = Some(s);
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    let mut stream = stream.expect("daemon socket should accept connections");
+    let request = serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "event": event,
+    });
+    writeln!(stream, "{}", request).unwrap();
+    let mut reply = String::new();
+    BufReader::new(&stream).read_line(&mut reply).unwrap();
+    let reply: serde_json::Value = serde_json::from_str(&reply).unwrap();
+    assert_eq!(reply["continue"], false);
+
+    // Hook invocations forward to the daemon
+    run_hook();
+
+    // Cleaning state leaves the live socket alone
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .env("HOME", temp_dir.path())
+        .args(["clean", "--state", "--yes"])
+        .assert()
+        .success();
+    assert!(socket.exists());
+
+    // A dead daemon leaves a stale socket; hooks fall back to in-process
+    daemon.0.kill().unwrap();
+    daemon.0.wait().unwrap();
+    assert!(socket.exists());
+    run_hook();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_daemon_failure_after_connect_applies_fail_open() {
+    use std::io::{BufRead, BufReader};
+    use std::os::unix::net::UnixListener;
+
+    let temp_dir = TempDir::new().unwrap();
+    let claude_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(claude_dir.join("run/cch")).unwrap();
+    let write_config = |fail_open: bool| {
+        fs::write(
+            claude_dir.join("hooks.yaml"),
+            format!(
+                r#"version: "1.0"
+settings:
+  fail_open: {}
+rules:
+  - name: note-ls
+    matchers:
+      tools: [Bash]
+      command_match: "^ls"
+    actions:
+      inject_inline: "listing"
+"#,
+                fail_open
+            ),
+        )
+        .unwrap();
+    };
+
+    // A daemon that takes each event and hangs up without answering
+    let listener = UnixListener::bind(claude_dir.join("run/cch/cch.sock")).unwrap();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut line = String::new();
+            let _ = BufReader::new(stream.unwrap()).read_line(&mut line);
+        }
+    });
+
+    let run = || {
+        cch_cmd()
+            .current_dir(temp_dir.path())
+            .env("HOME", temp_dir.path())
+            .write_stdin(
+                serde_json::json!({
+                    "hook_event_name": "PreToolUse",
+                    "tool_name": "Bash",
+                    "tool_input": { "command": "ls" },
+                    "session_id": "daemon-failure-test",
+                    "cwd": temp_dir.path().to_string_lossy(),
+                })
+                .to_string(),
+            )
+            .assert()
+    };
+
+    // The event is not re-evaluated in-process, so the rule never injects
+    write_config(false);
+    run()
+        .code(2)
+        .stderr(predicate::str::contains("CCH failed closed"))
+        .stdout(predicate::str::contains("listing").not());
+
+    write_config(true);
+    run()
+        .failure()
+        .stdout(predicate::str::contains("listing").not());
+}
+
+// =============================================================================
+// Config Snapshot Tests
+// =============================================================================
+
+#[test]
+fn test_config_snapshot_refreshes_on_change() {
+    let temp_dir = TempDir::new().unwrap();
+    let claude_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(&claude_dir).unwrap();
+    let write_config = |pattern: &str| {
+        fs::write(
+            claude_dir.join("hooks.yaml"),
+            format!(
+                r#"version: "1.0"
+rules:
+  - name: guard
+    matchers:
+      tools: [Bash]
+      command_match: "{}"
+    actions:
+      block: true
+"#,
+                pattern
+            ),
+        )
+        .unwrap();
+    };
+    let run = |command: &str| {
+        cch_cmd()
+            .current_dir(temp_dir.path())
+            .env("HOME", temp_dir.path())
+            .write_stdin(
+                serde_json::json!({
+                    "hook_event_name": "PreToolUse",
+                    "tool_name": "Bash",
+                    "tool_input": { "command": command },
+                    "session_id": "snapshot-test"
+                })
+                .to_string(),
+            )
+            .assert()
+    };
+
+    write_config("rm -rf");
+    run("rm -rf build").code(2);
+    let snapshots = claude_dir.join("cache").join("cch").join("snapshots");
+    assert_eq!(fs::read_dir(&snapshots).unwrap().count(), 1);
+
+    // Served from the snapshot, matching with its stored DFA
+    run("rm -rf build").code(2);
+
+    // An edited config is re-parsed, not served stale
+    write_config("git push");
+    run("rm -rf build").success();
+    run("git push origin").code(2);
+    assert_eq!(fs::read_dir(&snapshots).unwrap().count(), 1);
+}
+
+// =============================================================================
+// Layered Config Tests
+// =============================================================================
+
+#[test]
+fn test_global_and_system_rules_apply_alongside_project_rules() {
+    let home = TempDir::new().unwrap();
+    let project = TempDir::new().unwrap();
+    let system = home.path().join("system-hooks.yaml");
+    fs::create_dir_all(home.path().join(".claude")).unwrap();
+    fs::create_dir_all(project.path().join(".claude")).unwrap();
+
+    let block_rule = |name: &str, pattern: &str| {
+        format!(
+            "  - name: {name}\n    matchers:\n      tools: [Bash]\n      command_match: \"{pattern}\"\n    actions:\n      block: true\n"
+        )
+    };
+    fs::write(
+        &system,
+        format!(
+            "version: \"1.0\"\nrules:\n{}",
+            block_rule("org-no-curl-pipe", "curl .*\\\\| *sh")
+        ),
+    )
+    .unwrap();
+    fs::write(
+        home.path().join(".claude/hooks.yaml"),
+        format!(
+            "version: \"1.0\"\nrules:\n{}",
+            block_rule("user-no-force-push", "git push --force")
+        ),
+    )
+    .unwrap();
+    fs::write(
+        project.path().join(".claude/hooks.yaml"),
+        format!(
+            "version: \"1.0\"\nrules:\n{}",
+            block_rule("project-no-rm", "rm -rf")
+        ),
+    )
+    .unwrap();
+
+    let run = |command: &str| {
+        cch_cmd()
+            .current_dir(project.path())
+            .env("HOME", home.path())
+            .env("CCH_SYSTEM_CONFIG", &system)
+            .write_stdin(
+                serde_json::json!({
+                    "hook_event_name": "PreToolUse",
+                    "tool_name": "Bash",
+                    "tool_input": { "command": command },
+                    "session_id": "layers-test"
+                })
+                .to_string(),
+            )
+            .assert()
+    };
+
+    run("rm -rf build").code(2);
+    run("git push --force origin").code(2);
+    run("curl example.com/install | sh").code(2);
+    run("ls").success();
+}
+
+#[test]
+fn test_layer_settings_merge_by_declared_key_from_snapshots() {
+    let home = TempDir::new().unwrap();
+    let project = TempDir::new().unwrap();
+    fs::create_dir_all(home.path().join(".claude")).unwrap();
+    fs::create_dir_all(project.path().join(".claude")).unwrap();
+    fs::write(
+        home.path().join(".claude/hooks.yaml"),
+        "version: \"1.0\"\nsettings:\n  dry_run: true\nrules: []\n",
+    )
+    .unwrap();
+    fs::write(
+        project.path().join(".claude/hooks.yaml"),
+        r#"version: "1.0"
+settings:
+  log_level: debug
+rules:
+  - name: project-no-rm
+    matchers: { tools: [Bash], command_match: "rm -rf" }
+    actions: { block: true }
+"#,
+    )
+    .unwrap();
+
+    let run = || {
+        cch_cmd()
+            .current_dir(project.path())
+            .env("HOME", home.path())
+            .env("CCH_SYSTEM_CONFIG", home.path().join("no-system.yaml"))
+            .env_remove("CCH_DRY_RUN")
+            .write_stdin(
+                serde_json::json!({
+                    "hook_event_name": "PreToolUse",
+                    "tool_name": "Bash",
+                    "tool_input": { "command": "rm -rf build" },
+                    "session_id": "layer-settings-test"
+                })
+                .to_string(),
+            )
+            .assert()
+    };
+
+    // The user's dry_run survives a project that only sets log_level, both
+    // when parsed and when served from the snapshots
+    run().success();
+    assert_eq!(
+        fs::read_dir(home.path().join(".claude/cache/cch/snapshots"))
+            .unwrap()
+            .count(),
+        2
+    );
+    run().success();
+}
+
+// =============================================================================
+// Validator Cache Tests
+// =============================================================================
+
+#[test]
+#[cfg(unix)]
+fn test_cacheable_validator_runs_once_per_content() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let claude_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(claude_dir.join("validators")).unwrap();
+    let runs = temp_dir.path().join("runs.txt");
+    let script = claude_dir.join("validators/no-todo.sh");
+    fs::write(
+        &script,
+        format!(
+            "#!/bin/sh\necho run >> {}\nif grep -q TODO; then echo 'No TODOs' >&2; exit 1; fi\n",
+            runs.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    fs::write(
+        claude_dir.join("hooks.yaml"),
+        r#"
+version: "1.0"
+rules:
+  - name: no-todo
+    matchers:
+      tools: [Write]
+    actions:
+      run:
+        script: .claude/validators/no-todo.sh
+        cacheable: true
+"#,
+    )
+    .unwrap();
+
+    let write = |content: &str| {
+        cch_cmd()
+            .current_dir(temp_dir.path())
+            .env("HOME", temp_dir.path())
+            .write_stdin(
+                serde_json::json!({
+                    "hook_event_name": "PreToolUse",
+                    "tool_name": "Write",
+                    "tool_input": { "file_path": "src/lib.rs", "content": content },
+                    "session_id": "cache-test"
+                })
+                .to_string(),
+            )
+            .assert()
+    };
+    let run_count = || {
+        fs::read_to_string(&runs)
+            .unwrap_or_default()
+            .lines()
+            .count()
+    };
+
+    write("// TODO: later").code(2);
+    write("// TODO: later").code(2);
+    assert_eq!(run_count(), 1);
+
+    write("fn main() {}").success();
+    write("fn main() {}").success();
+    assert_eq!(run_count(), 2);
+}
+
+// =============================================================================
+// Plugin Rule Tests
+// =============================================================================
+
+#[test]
+fn test_plugin_rules_are_enforced_and_listed() {
+    let temp_dir = TempDir::new().unwrap();
+    let plugin_dir = temp_dir.path().join(".claude/plugins/terraform/cch");
+    fs::create_dir_all(&plugin_dir).unwrap();
+    fs::write(
+        plugin_dir.join("rules.yaml"),
+        r#"
+rules:
+  - name: terraform-plan-first
+    matchers:
+      tools: [Bash]
+      command_match: "terraform apply"
+    actions:
+      block: true
+"#,
+    )
+    .unwrap();
+
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .env("HOME", temp_dir.path())
+        .write_stdin(
+            serde_json::json!({
+                "hook_event_name": "PreToolUse",
+                "tool_name": "Bash",
+                "tool_input": { "command": "terraform apply" },
+                "session_id": "plugin-test"
+            })
+            .to_string(),
+        )
+        .assert()
+        .code(2);
+
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .env("HOME", temp_dir.path())
+        .args(["explain", "rules"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Provided by plugins: terraform-plan-first (terraform)",
+        ));
+
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .env("HOME", temp_dir.path())
+        .args(["explain", "rule", "terraform-plan-first"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("created_by: plugin:terraform"));
+}
+
+#[test]
+fn test_doctor_protocol_counts_field_spellings() {
+    let home = TempDir::new().unwrap();
+    let project = TempDir::new().unwrap();
+
+    for event in [
+        r#"{"hook_event_name": "PreToolUse", "tool_name": "Write", "tool_input": {"file_path": "a.rs"}, "session_id": "s"}"#,
+        r#"{"event_type": "PreToolUse", "tool_name": "Write", "tool_input": {"filePath": "b.rs"}, "session_id": "s"}"#,
+        r#"{"hook_event_name": "PreToolUse", "tool_name": "Write", "tool_input": {"file_path": "c.rs"}, "session_id": "s"}"#,
+    ] {
+        cch_cmd()
+            .current_dir(project.path())
+            .env("HOME", home.path())
+            .write_stdin(event)
+            .assert()
+            .success();
+    }
+
+    cch_cmd()
+        .current_dir(project.path())
+        .env("HOME", home.path())
+        .args(["doctor", "--protocol"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("3 events counted"))
+        .stdout(
+            predicate::str::is_match(r"  hook_event_name\n    hook_event_name +2  last seen")
+                .unwrap(),
+        )
+        .stdout(predicate::str::is_match(r"event_type +1  last seen \S+  \(legacy\)").unwrap())
+        .stdout(predicate::str::contains("  tool_input.file_path\n"))
+        .stdout(predicate::str::contains(
+            "Legacy spellings in use: event_type, filePath",
+        ));
+}
+
+#[test]
+fn test_doctor_environment_reports_fixes() {
+    let home = TempDir::new().unwrap();
+    let project = TempDir::new().unwrap();
+    let claude = project.path().join(".claude");
+    fs::create_dir_all(claude.join("validators")).unwrap();
+    fs::write(
+        claude.join("hooks.yaml"),
+        r#"
+version: "1.0"
+rules:
+  - name: docs
+    matchers:
+      tools: [Edit]
+    actions:
+      inject: .claude/context/docs.md
+  - name: check
+    matchers:
+      tools: [Bash]
+    actions:
+      run: .claude/validators/check.sh
+"#,
+    )
+    .unwrap();
+    fs::write(claude.join("validators/check.sh"), "#!/bin/sh\necho '{}'\n").unwrap();
+    fs::write(
+        claude.join("settings.json"),
+        r#"{"hooks": {"pre_tool_use": [{"command": "cch"}]}}"#,
+    )
+    .unwrap();
+
+    let binary = assert_cmd::cargo::cargo_bin("cch");
+    let path = std::env::join_paths(
+        std::iter::once(binary.parent().unwrap().to_path_buf())
+            .chain(std::env::split_paths(&std::env::var_os("PATH").unwrap())),
+    )
+    .unwrap();
+    let doctor = || {
+        let mut cmd = cch_cmd();
+        cmd.current_dir(project.path())
+            .env("HOME", home.path())
+            .env("PATH", &path)
+            .args(["doctor", "--environment"]);
+        cmd
+    };
+
+    doctor()
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "unknown hook event `pre_tool_use`",
+        ))
+        .stdout(predicate::str::contains(
+            "Fix: Rewrite the hooks in Claude Code's format: cch uninstall && cch install",
+        ))
+        .stdout(predicate::str::contains(
+            "✗ Context files: not found: .claude/context/docs.md (rule 'docs')",
+        ))
+        .stdout(predicate::str::contains(
+            "Fix: chmod +x .claude/validators/check.sh",
+        ))
+        .stdout(predicate::str::contains("✓ Log directory"))
+        .stderr(predicate::str::contains("doctor check(s) failed"));
+
+    fs::create_dir_all(claude.join("context")).unwrap();
+    fs::write(claude.join("context/docs.md"), "Docs").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(
+            claude.join("validators/check.sh"),
+            fs::Permissions::from_mode(0o755),
+        )
+        .unwrap();
+    }
+    let settings = serde_json::json!({
+        "hooks": {
+            "PreToolUse": [{
+                "matcher": "*",
+                "hooks": [{ "type": "command", "command": binary, "timeout": 5 }]
+            }]
+        }
+    });
+    fs::write(claude.join("settings.json"), settings.to_string()).unwrap();
+
+    doctor()
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "✓ Claude settings: CCH registered",
+        ))
+        .stdout(predicate::str::contains("✓ Hook command"))
+        .stdout(predicate::str::contains("✓ hooks.yaml: 2 rules"))
+        .stdout(predicate::str::contains("✓ PATH"))
+        .stdout(predicate::str::contains("✗").not());
+}
+
+#[test]
+fn test_stats_aggregates_log_window() {
+    let home = TempDir::new().unwrap();
+    let project = TempDir::new().unwrap();
+    fs::create_dir_all(home.path().join(".claude/logs")).unwrap();
+    fs::create_dir_all(project.path().join(".claude")).unwrap();
+    fs::write(
+        project.path().join(".claude/hooks.yaml"),
+        r#"
+version: "1.0"
+rules:
+  - name: no-force-push
+    matchers: { tools: [Bash], command_match: "--force" }
+    actions: { block: true }
+  - name: never-used
+    matchers: { tools: [Write] }
+    actions: { block: true }
+"#,
+    )
+    .unwrap();
+
+    let entry = |days_ago: i64, command: &str, blocked: bool| {
+        serde_json::json!({
+            "timestamp": (chrono::Utc::now() - chrono::Duration::days(days_ago)).to_rfc3339(),
+            "event_type": "PreToolUse",
+            "session_id": "s",
+            "tool_name": "Bash",
+            "rules_matched": if blocked { vec!["no-force-push"] } else { vec![] },
+            "outcome": if blocked { "block" } else { "allow" },
+            "timing": { "processing_ms": 4, "rules_evaluated": 2 },
+            "event_details": { "tool_type": "Bash", "command": command }
+        })
+        .to_string()
+    };
+    let lines = [
+        entry(40, "git push --force", true),
+        entry(2, "git push --force", true),
+        entry(1, "git push --force origin main", true),
+        entry(1, "cargo build", false),
+    ];
+    fs::write(home.path().join(".claude/logs/cch.log"), lines.join("\n")).unwrap();
+
+    let output = cch_cmd()
+        .current_dir(project.path())
+        .env("HOME", home.path())
+        .args(["stats", "--since", "30d", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stats: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(stats["events"], 3);
+    assert_eq!(stats["rules"]["no-force-push"]["blocked"], 2);
+    assert_eq!(stats["top_blocked_commands"].as_array().unwrap().len(), 2);
+    assert_eq!(stats["never_fired"], serde_json::json!(["never-used"]));
+
+    cch_cmd()
+        .current_dir(project.path())
+        .env("HOME", home.path())
+        .args(["stats", "--until", "30d"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Audit log: 1 events (start to "))
+        .stdout(predicate::str::contains("    1  git push --force\n"));
+}
+
+#[test]
+fn test_verify_protocol_warns_about_unsupported_events() {
+    let home = TempDir::new().unwrap();
+    let project = TempDir::new().unwrap();
+    let claude = project.path().join(".claude");
+    fs::create_dir_all(&claude).unwrap();
+    fs::write(
+        claude.join("hooks.yaml"),
+        r#"
+version: "1.0"
+rules:
+  - name: prompt-context
+    matchers:
+      operations: [UserPromptSubmit]
+    actions:
+      inject_command: "echo context"
+"#,
+    )
+    .unwrap();
+    let hook = serde_json::json!([{
+        "matcher": "*",
+        "hooks": [{ "type": "command", "command": "/usr/local/bin/cch" }]
+    }]);
+    fs::write(
+        claude.join("settings.json"),
+        serde_json::json!({ "hooks": { "PreToolUse": hook, "SessionEnd": hook } }).to_string(),
+    )
+    .unwrap();
+    let verify = |version: &str| {
+        let mut cmd = cch_cmd();
+        cmd.current_dir(project.path())
+            .env("HOME", home.path())
+            .args(["verify-protocol", "--claude-version", version]);
+        cmd
+    };
+
+    verify("1.0.60")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "✓ Registered events: Claude Code 1.0.60 emits PreToolUse",
+        ))
+        .stdout(predicate::str::contains(
+            "CCH is registered for SessionEnd, which Claude Code 1.0.60 doesn't emit (added in 1.0.85)",
+        ))
+        .stdout(predicate::str::contains(
+            "Rules match UserPromptSubmit events, but CCH isn't registered for them",
+        ));
+
+    verify("2.0")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("emits PreToolUse, SessionEnd"));
+
+    verify("latest")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid Claude Code version"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_fast_path_spawns_no_processes() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let claude_dir = temp_dir.path().join(".claude");
+    let bin = temp_dir.path().join("bin");
+    let spawned = temp_dir.path().join("spawned");
+    fs::create_dir_all(&claude_dir).unwrap();
+    fs::create_dir_all(&bin).unwrap();
+    fs::create_dir_all(&spawned).unwrap();
+
+    // Every program the rules could start leaves a marker
+    let script = |path: &std::path::Path, name: &str| {
+        fs::write(
+            path,
+            format!("#!/bin/sh\ntouch '{}'\n", spawned.join(name).display()),
+        )
+        .unwrap();
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+    };
+    script(&bin.join("git"), "git");
+    script(&claude_dir.join("post.sh"), "post-processor");
+    script(&claude_dir.join("validator.sh"), "validator");
+    fs::write(
+        claude_dir.join("hooks.yaml"),
+        format!(
+            r#"version: "1.0"
+settings:
+  fast_path_tools: [Read]
+  post_processor: "{}"
+rules:
+  - name: trusted-bypass
+    bypass_users: [ci-bot]
+    matchers: {{ tools: [Read, Write] }}
+    actions: {{ inject_inline: "reading" }}
+  - name: command-context
+    matchers: {{ tools: [Read, Write] }}
+    actions: {{ inject_command: "touch '{}'" }}
+  - name: validated
+    matchers: {{ tools: [Read, Write] }}
+    actions: {{ run: "{}" }}
+"#,
+            claude_dir.join("post.sh").display(),
+            spawned.join("inject-command").display(),
+            claude_dir.join("validator.sh").display()
+        ),
+    )
+    .unwrap();
+
+    let path = format!(
+        "{}:{}",
+        bin.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    let run = |tool: &str| {
+        cch_cmd()
+            .current_dir(temp_dir.path())
+            .env("HOME", temp_dir.path())
+            .env("PATH", &path)
+            .write_stdin(
+                serde_json::json!({
+                    "hook_event_name": "PreToolUse",
+                    "tool_name": tool,
+                    "tool_input": { "file_path": "src/lib.rs", "content": "x" },
+                    "session_id": "fast-path-test",
+                    "cwd": temp_dir.path().to_string_lossy(),
+                })
+                .to_string(),
+            )
+            .assert()
+            .success();
+    };
+    let markers = || {
+        let mut names: Vec<String> = fs::read_dir(&spawned)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    };
+
+    run("Read");
+    assert!(markers().is_empty(), "fast path spawned {:?}", markers());
+
+    // The same rules spawn every program off the fast path
+    run("Write");
+    assert_eq!(
+        markers(),
+        ["git", "inject-command", "post-processor", "validator"]
+    );
+}
+
+#[test]
+fn test_invalid_runtime_pattern_is_logged() {
+    let temp_dir = TempDir::new().unwrap();
+    let claude_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(&claude_dir).unwrap();
+    fs::write(
+        claude_dir.join("hooks.yaml"),
+        r#"version: "1.0"
+rules:
+  - name: described-commands
+    matchers:
+      tools: [Bash]
+      expr: 'command.matches(input.description)'
+    actions: { block: true }
+"#,
+    )
+    .unwrap();
+
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .env("HOME", temp_dir.path())
+        .write_stdin(
+            serde_json::json!({
+                "hook_event_name": "PreToolUse",
+                "tool_name": "Bash",
+                "tool_input": { "command": "ls", "description": "(unclosed" },
+                "session_id": "invalid-pattern-test",
+            })
+            .to_string(),
+        )
+        .assert()
+        .success();
+
+    let log = fs::read_to_string(claude_dir.join("logs/cch.log")).unwrap();
+    let entry: serde_json::Value = serde_json::from_str(log.lines().last().unwrap()).unwrap();
+    assert_eq!(
+        entry["invalid_patterns"],
+        serde_json::json!([{ "pattern": "(unclosed", "error": "unclosed group at column 1" }])
+    );
+}
+
+/// A project whose `wasm-check` rule runs a WebAssembly module that blocks
+/// every Write
+fn wasm_validator_project() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let claude_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(&claude_dir).unwrap();
+    let verdict = r#"{"decision":"block","reason":"blocked by wasm"}"#;
+    let wasm = wat::parse_str(format!(
+        r#"(module
+            (memory (export "memory") 1)
+            (data (i32.const 0) "{}")
+            (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+            (func (export "validate") (param i32 i32) (result i64) (i64.const {})))"#,
+        verdict.replace('"', "\\\""),
+        verdict.len()
+    ))
+    .unwrap();
+    fs::write(claude_dir.join("check.wasm"), wasm).unwrap();
+    fs::write(
+        claude_dir.join("hooks.yaml"),
+        r#"version: "1.0"
+rules:
+  - name: wasm-check
+    matchers: { tools: [Write] }
+    actions: { run: { wasm: .claude/check.wasm } }
+"#,
+    )
+    .unwrap();
+    temp_dir
+}
+
+fn run_wasm_validator_project(temp_dir: &TempDir) -> assert_cmd::assert::Assert {
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .env("HOME", temp_dir.path())
+        .write_stdin(
+            serde_json::json!({
+                "hook_event_name": "PreToolUse",
+                "tool_name": "Write",
+                "tool_input": { "file_path": "src/lib.rs", "content": "x" },
+                "session_id": "wasm-test",
+                "cwd": temp_dir.path().to_string_lossy(),
+            })
+            .to_string(),
+        )
+        .assert()
+}
+
+#[cfg(feature = "wasm-validators")]
+#[test]
+fn test_wasm_validator_blocks() {
+    let temp_dir = wasm_validator_project();
+    run_wasm_validator_project(&temp_dir)
+        .code(2)
+        .stderr(predicate::str::contains("blocked by wasm"));
+}
+
+#[cfg(not(feature = "wasm-validators"))]
+#[test]
+fn test_wasm_validator_needs_feature() {
+    let temp_dir = wasm_validator_project();
+    cch_cmd()
+        .current_dir(temp_dir.path())
+        .env("HOME", temp_dir.path())
+        .arg("validate")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Rule 'wasm-check' runs a WASM validator, but this build lacks the `wasm-validators` feature",
+        ));
+    // fail_open (the default) lets the operation through
+    run_wasm_validator_project(&temp_dir).success();
+}
+
+// =============================================================================
+// Clean Tests
+// =============================================================================
+
+#[test]
+fn test_clean_removes_only_owned_files_after_confirmation() {
+    let temp_dir = TempDir::new().unwrap();
+    let claude_dir = temp_dir.path().join(".claude");
+    let owned = [
+        "logs/cch.log",
+        "logs/cch.log.1.gz",
+        "state/global.json",
+        "state/sessions/s1.json",
+    ];
+    let kept = [
+        "logs/renames.json",
+        "logs/protocol.json",
+        "state/journal/s1/1.json",
+    ];
+    for path in owned.iter().chain(&kept) {
+        let path = claude_dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "{}").unwrap();
+    }
+    let clean = |args: &[&str]| {
+        let mut cmd = cch_cmd();
+        cmd.env("HOME", temp_dir.path())
+            .arg("clean")
+            .args(["--logs", "--state"])
+            .args(args);
+        cmd.assert()
+    };
+
+    // Without a terminal to ask on, nothing is removed without --yes
+    clean(&[])
+        .failure()
+        .stderr(predicate::str::contains("pass --yes"));
+    clean(&["--dry-run"])
+        .success()
+        .stdout(predicate::str::contains("cch.log.1.gz"))
+        .stdout(predicate::str::contains("renames.json").not());
+    assert!(owned.iter().all(|path| claude_dir.join(path).exists()));
+
+    clean(&["--yes"]).success();
+    for path in owned {
+        assert!(!claude_dir.join(path).exists(), "{} was kept", path);
+    }
+    for path in kept {
+        assert!(claude_dir.join(path).exists(), "{} was removed", path);
+    }
+}
@@ -0,0 +1,143 @@
+//! Operational Qualification (OQ) Tests - Failure Injection
+//!
+//! Drives validator and config failures through `CCH_CHAOS` to verify the
+//! binary fails open or closed as `settings.fail_open` says, end to end.
+//!
+//! Requires the `chaos` feature: `cargo test --features chaos`.
+
+#![cfg(feature = "chaos")]
+#![allow(deprecated)]
+
+use assert_cmd::Command;
+use std::fs;
+use std::process::Output;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::{TestEvidence, Timer, evidence_dir};
+
+/// Project whose only rule runs an always-allowing validator on Bash
+fn setup_validator_env(fail_open: bool) -> tempfile::TempDir {
+    let temp_dir = tempfile::tempdir().expect("create temp dir");
+    let claude_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("create .claude");
+
+    let script = claude_dir.join("allow.sh");
+    fs::write(&script, "#!/bin/sh\ncat > /dev/null\nexit 0\n").expect("write validator");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).expect("chmod");
+    }
+
+    fs::write(
+        claude_dir.join("hooks.yaml"),
+        format!(
+            r#"
+version: "1.0"
+settings:
+  fail_open: {fail_open}
+rules:
+  - name: check-commands
+    matchers: {{ tools: [Bash] }}
+    actions: {{ run: "{}" }}
+    metadata: {{ timeout: 1 }}
+"#,
+            script.display()
+        ),
+    )
+    .expect("write config");
+    temp_dir
+}
+
+fn run_with_chaos(temp_dir: &tempfile::TempDir, chaos: &str) -> Output {
+    let event = serde_json::json!({
+        "hook_event_name": "PreToolUse",
+        "tool_name": "Bash",
+        "tool_input": { "command": "git status" },
+        "session_id": "chaos-session",
+        "cwd": temp_dir.path()
+    });
+    Command::cargo_bin("cch")
+        .expect("binary exists")
+        .current_dir(temp_dir.path())
+        .env("HOME", temp_dir.path())
+        .env("CCH_CHAOS", chaos)
+        .write_stdin(event.to_string())
+        .output()
+        .expect("command should run")
+}
+
+/// Test that validator failures are allowed through with fail_open
+#[test]
+fn test_chaos_validator_failures_fail_open() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("chaos_fail_open", "OQ-CHAOS");
+
+    let temp_dir = setup_validator_env(true);
+    for fault in ["spawn_fail", "validator_timeout"] {
+        let output = run_with_chaos(&temp_dir, fault);
+        assert_eq!(output.status.code(), Some(0), "{}", fault);
+        let response: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("stdout should be JSON");
+        assert_eq!(response["continue"], true, "{}", fault);
+    }
+
+    evidence.pass(
+        "Spawn failures and timeouts allow the operation with fail_open",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that validator failures block without fail_open
+#[test]
+fn test_chaos_validator_failures_fail_closed() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("chaos_fail_closed", "OQ-CHAOS");
+
+    let temp_dir = setup_validator_env(false);
+    for (fault, reason) in [
+        ("spawn_fail", "spawn failure injected by CCH_CHAOS"),
+        ("validator_timeout", "Script timed out"),
+    ] {
+        let output = run_with_chaos(&temp_dir, fault);
+        assert_eq!(output.status.code(), Some(2), "{}", fault);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("CCH failed closed"),
+            "{}: {}",
+            fault,
+            stderr
+        );
+        assert!(stderr.contains(reason), "{}: {}", fault, stderr);
+    }
+
+    // Without injected faults the validator allows the command
+    assert_eq!(run_with_chaos(&temp_dir, "").status.code(), Some(0));
+
+    evidence.pass(
+        "Spawn failures and timeouts block the operation without fail_open",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that an unreadable config never blocks (exit 1 is non-blocking)
+#[test]
+fn test_chaos_corrupt_config_does_not_block() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("chaos_config_corrupt", "OQ-CHAOS");
+
+    let temp_dir = setup_validator_env(false);
+    let output = run_with_chaos(&temp_dir, "config_corrupt");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Failed to parse"), "{}", stderr);
+
+    evidence.pass(
+        "A corrupt config is reported without blocking the operation",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
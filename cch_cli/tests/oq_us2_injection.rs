@@ -177,3 +177,424 @@ fn test_us2_extension_based_injection() {
     );
     let _ = evidence.save(&evidence_dir());
 }
+
+/// Test that the session banner is injected on the first SessionStart only
+#[test]
+fn test_us2_session_banner_injected_once() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("session_banner", "OQ-US2");
+
+    let temp_dir = tempfile::tempdir().expect("create temp dir");
+    let claude_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("create .claude");
+    fs::copy(
+        fixture_path("hooks/session-banner.yaml"),
+        claude_dir.join("hooks.yaml"),
+    )
+    .expect("copy config");
+
+    let event = serde_json::json!({
+        "hook_event_name": "SessionStart",
+        "session_id": "banner-session",
+        "source": "startup"
+    })
+    .to_string();
+    let run = || {
+        let output = Command::cargo_bin("cch")
+            .expect("binary exists")
+            .current_dir(temp_dir.path())
+            .env("HOME", temp_dir.path())
+            .write_stdin(event.clone())
+            .output()
+            .expect("command should run");
+        assert!(output.status.success());
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).expect("stdout should be JSON")
+    };
+
+    let first = run();
+    let context = first["context"].as_str().expect("banner injected");
+    assert!(
+        context.contains("Profile 'platform-strict': 1 enforce, 1 warn, 0 audit rules active"),
+        "{}",
+        context
+    );
+    assert!(context.contains("Ask in #platform"));
+
+    // Resuming the same session doesn't repeat it
+    let resumed = run();
+    assert!(
+        resumed
+            .get("context")
+            .is_none_or(serde_json::Value::is_null)
+    );
+
+    evidence.pass(
+        "Session banner summarizes policy once per session",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that context file front matter filters, orders and limits injection
+#[test]
+fn test_us2_inject_front_matter_conditions() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("inject_front_matter", "OQ-US2");
+
+    let temp_dir = tempfile::tempdir().expect("create temp dir");
+    let context_dir = temp_dir.path().join(".claude/context");
+    fs::create_dir_all(&context_dir).expect("create context dir");
+    fs::copy(
+        fixture_path("hooks/inject-front-matter.yaml"),
+        temp_dir.path().join(".claude/hooks.yaml"),
+    )
+    .expect("copy config");
+    fs::write(
+        context_dir.join("style.md"),
+        "---\nonly_tools: [Edit]\n---\nStyle guide\n",
+    )
+    .expect("write style.md");
+    fs::write(
+        context_dir.join("onboarding.md"),
+        "---\nmax_injections: 1\npriority: 5\n---\nOnboarding notes\n",
+    )
+    .expect("write onboarding.md");
+
+    let run = |tool: &str| {
+        let event = serde_json::json!({
+            "hook_event_name": "PreToolUse",
+            "tool_name": tool,
+            "tool_input": { "file_path": "src/main.rs", "command": "ls" },
+            "session_id": "front-matter-session",
+            "cwd": temp_dir.path()
+        });
+        let output = Command::cargo_bin("cch")
+            .expect("binary exists")
+            .current_dir(temp_dir.path())
+            .env("HOME", temp_dir.path())
+            .write_stdin(event.to_string())
+            .output()
+            .expect("command should run");
+        assert!(output.status.success());
+        let response: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("stdout should be JSON");
+        response["context"].as_str().map(str::to_string)
+    };
+
+    // Higher priority first, front matter stripped
+    assert_eq!(
+        run("Edit").as_deref(),
+        Some("Onboarding notes\n\nStyle guide")
+    );
+    // Onboarding was already injected once this session
+    assert_eq!(run("Edit").as_deref(), Some("Style guide"));
+    // The style guide only applies to edits
+    assert_eq!(run("Bash"), None);
+
+    evidence.pass(
+        "Front matter limits context files by tool and session count, ordered by priority",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that prompt_match injects context into matching prompts
+#[test]
+fn test_us2_prompt_match_injects_into_prompt() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("prompt_match_injection", "OQ-US2");
+
+    let temp_dir = tempfile::tempdir().expect("create temp dir");
+    let context_dir = temp_dir.path().join(".claude/context");
+    fs::create_dir_all(&context_dir).expect("create context dir");
+    fs::copy(
+        fixture_path("hooks/prompt-injection.yaml"),
+        temp_dir.path().join(".claude/hooks.yaml"),
+    )
+    .expect("copy config");
+    fs::write(
+        context_dir.join("deploy-runbook.md"),
+        "Deploy with `make release`.\n",
+    )
+    .expect("write runbook");
+
+    let submit = |prompt: &str| {
+        let event = serde_json::json!({
+            "hook_event_name": "UserPromptSubmit",
+            "session_id": "prompt-session",
+            "prompt": prompt,
+            "cwd": temp_dir.path()
+        });
+        let output = Command::cargo_bin("cch")
+            .expect("binary exists")
+            .current_dir(temp_dir.path())
+            .env("HOME", temp_dir.path())
+            .write_stdin(event.to_string())
+            .output()
+            .expect("command should run");
+        assert!(output.status.success());
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).expect("stdout should be JSON")
+    };
+
+    let response = submit("Please Deploy the api to staging");
+    let output = &response["hookSpecificOutput"];
+    assert_eq!(output["hookEventName"], "UserPromptSubmit");
+    assert_eq!(output["additionalContext"], "Deploy with `make release`.\n");
+    assert!(output.get("permissionDecision").is_none());
+
+    let response = submit("Fix the flaky login test");
+    assert!(response.get("hookSpecificOutput").is_none());
+    assert!(
+        response
+            .get("context")
+            .is_none_or(serde_json::Value::is_null)
+    );
+
+    evidence.pass(
+        "prompt_match adds the runbook to prompts that mention deploying",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that throttled rules stop injecting once their allowance is used
+#[test]
+fn test_us2_throttled_injection() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("throttled_injection", "OQ-US2");
+
+    let temp_dir = tempfile::tempdir().expect("create temp dir");
+    let context_dir = temp_dir.path().join(".claude/context");
+    fs::create_dir_all(&context_dir).expect("create context dir");
+    fs::copy(
+        fixture_path("hooks/throttle.yaml"),
+        temp_dir.path().join(".claude/hooks.yaml"),
+    )
+    .expect("copy config");
+    fs::write(context_dir.join("conventions.md"), "Conventions").expect("write conventions");
+    fs::write(context_dir.join("release.md"), "Release freeze").expect("write release");
+
+    let run = |session: &str| {
+        let event = serde_json::json!({
+            "hook_event_name": "PreToolUse",
+            "tool_name": "Edit",
+            "tool_input": { "file_path": "src/main.rs" },
+            "session_id": session,
+            "cwd": temp_dir.path()
+        });
+        let output = Command::cargo_bin("cch")
+            .expect("binary exists")
+            .current_dir(temp_dir.path())
+            .env("HOME", temp_dir.path())
+            .write_stdin(event.to_string())
+            .output()
+            .expect("command should run");
+        assert!(output.status.success());
+        let response: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("stdout should be JSON");
+        response["context"].as_str().map(str::to_string)
+    };
+
+    assert_eq!(
+        run("session-a").as_deref(),
+        Some("Conventions\n\nRelease freeze")
+    );
+    assert_eq!(run("session-a").as_deref(), Some("Release freeze"));
+    // A new session gets conventions again; the reminder's global
+    // allowance is spent
+    assert_eq!(run("session-b").as_deref(), Some("Conventions"));
+    assert_eq!(run("session-b"), None);
+
+    evidence.pass(
+        "Throttles limit rules per session and across sessions",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that inject_once_per_session suppresses repeats and logs them
+#[test]
+fn test_us2_inject_once_per_session() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("inject_once_per_session", "OQ-US2");
+
+    let temp_dir = tempfile::tempdir().expect("create temp dir");
+    let context_dir = temp_dir.path().join(".claude/context");
+    fs::create_dir_all(&context_dir).expect("create context dir");
+    fs::copy(
+        fixture_path("hooks/inject-once.yaml"),
+        temp_dir.path().join(".claude/hooks.yaml"),
+    )
+    .expect("copy config");
+    fs::write(context_dir.join("standards.md"), "Standards").expect("write standards");
+    fs::write(context_dir.join("api.md"), "API").expect("write api");
+
+    let run = |session: &str, file_path: &str| {
+        let event = serde_json::json!({
+            "hook_event_name": "PreToolUse",
+            "tool_name": "Edit",
+            "tool_input": { "file_path": file_path },
+            "session_id": session,
+            "cwd": temp_dir.path()
+        });
+        let output = Command::cargo_bin("cch")
+            .expect("binary exists")
+            .current_dir(temp_dir.path())
+            .env("HOME", temp_dir.path())
+            .write_stdin(event.to_string())
+            .output()
+            .expect("command should run");
+        assert!(output.status.success());
+        let response: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("stdout should be JSON");
+        response["context"].as_str().map(str::to_string)
+    };
+
+    assert_eq!(
+        run("session-a", "src/main.rs").as_deref(),
+        Some("Standards\n\nAPI")
+    );
+    assert_eq!(run("session-a", "src/lib.rs"), None);
+    // Another rule sending a file this session already received skips it
+    assert_eq!(run("session-a", "src/api/routes.rs"), None);
+    assert_eq!(
+        run("session-b", "src/main.rs").as_deref(),
+        Some("Standards\n\nAPI")
+    );
+
+    let log = fs::read_to_string(temp_dir.path().join(".claude/logs/cch.log")).expect("read log");
+    let suppressed: Vec<serde_json::Value> = log
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).expect("log line is JSON"))
+        .map(|entry| entry["metadata"]["suppressed_injections"].clone())
+        .collect();
+    assert_eq!(
+        suppressed,
+        [
+            serde_json::Value::Null,
+            serde_json::json!([".claude/context/standards.md", ".claude/context/api.md"]),
+            serde_json::json!([
+                ".claude/context/standards.md",
+                ".claude/context/api.md",
+                ".claude/context/api.md"
+            ]),
+            serde_json::Value::Null,
+        ]
+    );
+
+    evidence.pass(
+        "Inject sources already sent this session are suppressed and logged",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that inject actions choose their position and output channel
+#[test]
+fn test_us2_inject_position_and_channel() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("inject_position_and_channel", "OQ-US2");
+
+    let temp_dir = tempfile::tempdir().expect("create temp dir");
+    let context_dir = temp_dir.path().join(".claude/context");
+    fs::create_dir_all(&context_dir).expect("create context dir");
+    fs::copy(
+        fixture_path("hooks/inject-placement.yaml"),
+        temp_dir.path().join(".claude/hooks.yaml"),
+    )
+    .expect("copy config");
+    fs::write(context_dir.join("api.md"), "API reference").expect("write api");
+    fs::write(context_dir.join("freeze.md"), "Release freeze in effect").expect("write freeze");
+    fs::write(context_dir.join("security.md"), "Never log secrets").expect("write security");
+
+    let event = serde_json::json!({
+        "hook_event_name": "PreToolUse",
+        "tool_name": "Edit",
+        "tool_input": { "file_path": "src/main.rs" },
+        "session_id": "placement-session",
+        "cwd": temp_dir.path()
+    });
+    let output = Command::cargo_bin("cch")
+        .expect("binary exists")
+        .current_dir(temp_dir.path())
+        .env("HOME", temp_dir.path())
+        .write_stdin(event.to_string())
+        .output()
+        .expect("command should run");
+    assert!(output.status.success());
+    let response: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be JSON");
+
+    assert_eq!(response["systemMessage"], "Release freeze in effect");
+    let context = response["context"].as_str().expect("context injected");
+    assert!(
+        context.contains("Never log secrets"),
+        "security guidance injected: {context}"
+    );
+    assert!(
+        context.ends_with("API reference"),
+        "reference docs come after prepended context: {context}"
+    );
+    assert!(!context.contains("Release freeze"));
+
+    evidence.pass(
+        "Inject position and channel map onto context and systemMessage",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
+
+/// Test that enrich_prompt expands mentioned terms once per session
+#[test]
+fn test_us2_enrich_prompt_dedupes_per_session() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("enrich_prompt", "OQ-US2");
+
+    let temp_dir = tempfile::tempdir().expect("create temp dir");
+    fs::create_dir_all(temp_dir.path().join(".claude")).expect("create .claude");
+    fs::copy(
+        fixture_path("hooks/prompt-enrichment.yaml"),
+        temp_dir.path().join(".claude/hooks.yaml"),
+    )
+    .expect("copy config");
+
+    let submit = |prompt: &str| {
+        let event = serde_json::json!({
+            "hook_event_name": "UserPromptSubmit",
+            "session_id": "enrich-session",
+            "prompt": prompt,
+            "cwd": temp_dir.path()
+        });
+        let output = Command::cargo_bin("cch")
+            .expect("binary exists")
+            .current_dir(temp_dir.path())
+            .env("HOME", temp_dir.path())
+            .write_stdin(event.to_string())
+            .output()
+            .expect("command should run");
+        assert!(output.status.success());
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).expect("stdout should be JSON")
+    };
+
+    let response = submit("Add a bucket to the CDK stack");
+    assert_eq!(
+        response["hookSpecificOutput"]["additionalContext"],
+        "Glossary:\n- CDK: AWS Cloud Development Kit, used for all infrastructure\n\n\
+         Standing instructions:\n- Use pnpm, never npm or yarn."
+    );
+
+    let response = submit("Now rerun the IQ checks for the CDK stack");
+    assert_eq!(
+        response["hookSpecificOutput"]["additionalContext"],
+        "Glossary:\n- IQ: Installation Qualification test suite"
+    );
+
+    let response = submit("Thanks, looks good");
+    assert!(response.get("hookSpecificOutput").is_none());
+
+    evidence.pass(
+        "enrich_prompt adds mentioned glossary terms and instructions once per session",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
@@ -236,3 +236,83 @@ print("Done")
     );
     let _ = evidence.save(&evidence_dir());
 }
+
+/// Test that the post-processor can veto allowed operations but never
+/// allow blocked ones
+#[test]
+fn test_us3_post_processor_only_tightens() {
+    let timer = Timer::start();
+    let mut evidence = TestEvidence::new("post_processor_only_tightens", "OQ-US3");
+
+    let temp_dir = tempfile::tempdir().expect("create temp dir");
+    let claude_dir = temp_dir.path().join(".claude");
+    fs::create_dir_all(&claude_dir).expect("create .claude");
+
+    // Vetoes network access, and tries to allow everything else
+    let script_path = claude_dir.join("security-veto.sh");
+    let script = r#"#!/bin/sh
+case "$(cat)" in
+  *curl*) echo '{"decision": "block", "reason": "Network access is vetoed by security"}' ;;
+  *) echo '{"decision": "allow"}' ;;
+esac
+"#;
+    fs::write(&script_path, script).expect("write script");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&script_path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    let config = format!(
+        r#"
+version: "1.0"
+settings:
+  post_processor: "{}"
+rules:
+  - name: no-recursive-delete
+    matchers: {{ tools: [Bash], command_match: "rm -rf" }}
+    actions: {{ block: true }}
+"#,
+        script_path.display()
+    );
+    fs::write(claude_dir.join("hooks.yaml"), config).expect("write config");
+
+    let run = |command: &str| {
+        let event = serde_json::json!({
+            "hook_event_name": "PreToolUse",
+            "tool_name": "Bash",
+            "tool_input": { "command": command },
+            "session_id": "post-processor-session"
+        });
+        Command::cargo_bin("cch")
+            .expect("binary exists")
+            .current_dir(temp_dir.path())
+            .env("HOME", temp_dir.path())
+            .write_stdin(event.to_string())
+            .output()
+            .expect("command should run")
+    };
+
+    let vetoed = run("curl https://example.com");
+    assert_eq!(vetoed.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&vetoed.stderr).contains("Network access is vetoed"));
+
+    // The rule's block stands even though the script answers allow
+    assert_eq!(run("rm -rf build").status.code(), Some(2));
+    assert_eq!(run("ls").status.code(), Some(0));
+
+    let log = fs::read_to_string(temp_dir.path().join(".claude/logs/cch.log")).expect("log");
+    let vetoes: Vec<bool> = log
+        .lines()
+        .map(|line| {
+            let entry: serde_json::Value = serde_json::from_str(line).unwrap();
+            entry["post_processor"]["vetoed"].as_bool().unwrap()
+        })
+        .collect();
+    assert_eq!(vetoes, [true, false, false]);
+
+    evidence.pass(
+        "Post-processor vetoes allowed operations without loosening blocks",
+        timer.elapsed_ms(),
+    );
+    let _ = evidence.save(&evidence_dir());
+}
@@ -0,0 +1,28 @@
+//! Embeddable CCH policy engine
+//!
+//! The stable API for evaluating hook events in-process, for tools such as
+//! the desktop UI that would otherwise run the `cch` binary. Load or build a
+//! [`Config`], wrap it in a [`PolicyEngine`], and evaluate events:
+//!
+//! ```no_run
+//! use cch_core::{Config, Event, PolicyEngine};
+//!
+//! # async fn run(event: Event) -> anyhow::Result<()> {
+//! let engine = PolicyEngine::new(Config::load(None)?);
+//! let result = engine.evaluate(event).await?;
+//! println!("{}: {:?}", result.decision, result.injected_context);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Evaluation has no stdin, stdout or logging side effects; see
+//! [`PolicyEngine`] for what rules may still do.
+
+#![forbid(unsafe_code)]
+
+pub use cch::config::{Config, Settings};
+pub use cch::engine::{EvaluationResult, PolicyEngine};
+pub use cch::models::{
+    AppliedExemption, Decision, Event, EventType, MatchedRule, MatcherResults, PolicyMode,
+    Response, Rule, RuleEvaluation,
+};